@@ -0,0 +1,18 @@
+//! AFF4 ZIP container, turtle metadata, map, idx and bevy parsing of a
+//! single volume held in memory.
+#![no_main]
+
+use exhume_body::aff4::AFF4;
+use exhume_body::ReadAt;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(aff4) = AFF4::from_bytes(data) else {
+        return;
+    };
+    let _ = aff4.info();
+    let _ = aff4.files();
+    let mut buf = vec![0u8; 64 * 1024];
+    let _ = aff4.read_at(0, &mut buf);
+    let _ = aff4.read_at(aff4.size() / 2, &mut buf);
+});
@@ -0,0 +1,18 @@
+//! EWF section and table parsing, then chunk decoding, on a single
+//! in-memory segment.
+#![no_main]
+
+use exhume_body::ewf::EWF;
+use exhume_body::ReadAt;
+use libfuzzer_sys::fuzz_target;
+use std::io::Cursor;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(ewf) = EWF::from_readers(vec![Box::new(Cursor::new(data.to_vec()))]) else {
+        return;
+    };
+    let _ = ewf.info();
+    let _ = ewf.verify_chunks(|_, _| {});
+    let mut buf = vec![0u8; 64 * 1024];
+    let _ = ewf.read_at(0, &mut buf);
+});
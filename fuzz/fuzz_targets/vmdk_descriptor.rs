@@ -0,0 +1,10 @@
+//! VMDK text descriptor and change tracking file parsing.
+#![no_main]
+
+use exhume_body::vmdk::{VMDKChangeTracking, VMDKDescriptorFile};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = VMDKDescriptorFile::from_bytes(data);
+    let _ = VMDKChangeTracking::parse(data);
+});
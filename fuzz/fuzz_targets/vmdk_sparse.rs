@@ -0,0 +1,20 @@
+//! VMDK sparse extent header, embedded descriptor and grain tables of a
+//! monolithic sparse disk held in memory.
+#![no_main]
+
+use exhume_body::vmdk::VMDK;
+use exhume_body::ReadAt;
+use libfuzzer_sys::fuzz_target;
+use std::io::Cursor;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(vmdk) = VMDK::from_reader(Cursor::new(data.to_vec())) else {
+        return;
+    };
+    let _ = vmdk.info();
+    let _ = vmdk.validate();
+    let _ = vmdk.allocated_ranges();
+    let mut buf = vec![0u8; 64 * 1024];
+    let _ = vmdk.read_at(0, &mut buf);
+    let _ = vmdk.read_at(vmdk.size() / 2, &mut buf);
+});
@@ -0,0 +1,140 @@
+#ifndef EXHUME_BODY_H
+#define EXHUME_BODY_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * `whence` of [`exhume_body_seek`]: from the start of the body.
+ */
+#define EXHUME_SEEK_SET 0
+
+/**
+ * `whence` of [`exhume_body_seek`]: from the current position.
+ */
+#define EXHUME_SEEK_CUR 1
+
+/**
+ * `whence` of [`exhume_body_seek`]: from the end of the body.
+ */
+#define EXHUME_SEEK_END 2
+
+/**
+ * Opaque handle on an opened body.
+ */
+typedef struct ExhumeBody ExhumeBody;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Open the image at `path` as `format` (`"auto"` when `format` is `NULL`).
+ *
+ * Returns `NULL` on failure.
+ *
+ * # Safety
+ *
+ * `path` and `format` must be `NULL` or NUL-terminated strings.
+ */
+ExhumeBody *exhume_body_open(const char *path, const char *format);
+
+/**
+ * Read up to `len` bytes at the current position into `buf`.
+ *
+ * Returns the number of bytes read (0 at the end of the body), or -1.
+ *
+ * # Safety
+ *
+ * `body` must come from [`exhume_body_open`] and `buf` must be valid for
+ * `len` bytes of writes.
+ */
+int64_t exhume_body_read(ExhumeBody *body, uint8_t *buf, size_t len);
+
+/**
+ * Read up to `len` bytes at `offset` into `buf`, without moving the
+ * current position.
+ *
+ * Returns the number of bytes read (0 past the end of the body), or -1.
+ *
+ * # Safety
+ *
+ * Same as [`exhume_body_read`].
+ */
+int64_t exhume_body_read_at(ExhumeBody *body, uint64_t offset, uint8_t *buf, size_t len);
+
+/**
+ * Move the current position, `whence` being one of `EXHUME_SEEK_SET`,
+ * `EXHUME_SEEK_CUR` or `EXHUME_SEEK_END`.
+ *
+ * Returns the new position, or -1.
+ *
+ * # Safety
+ *
+ * `body` must come from [`exhume_body_open`].
+ */
+int64_t exhume_body_seek(ExhumeBody *body, int64_t offset, int whence);
+
+/**
+ * Size of the body in bytes, or -1.
+ *
+ * # Safety
+ *
+ * `body` must come from [`exhume_body_open`].
+ */
+int64_t exhume_body_size(ExhumeBody *body);
+
+/**
+ * Logical sector size in bytes, or -1.
+ *
+ * # Safety
+ *
+ * `body` must come from [`exhume_body_open`].
+ */
+int64_t exhume_body_sector_size(ExhumeBody *body);
+
+/**
+ * Metadata of the body (format, description, size, sector size, segment
+ * count, acquisition information and geometry) as a JSON object.
+ *
+ * Returns a string to release with [`exhume_body_string_free`], or `NULL`.
+ *
+ * # Safety
+ *
+ * `body` must come from [`exhume_body_open`].
+ */
+char *exhume_body_metadata_json(ExhumeBody *body);
+
+/**
+ * Release a string returned by this library. `NULL` is ignored.
+ *
+ * # Safety
+ *
+ * `s` must be `NULL` or come from [`exhume_body_metadata_json`], and must
+ * not be used afterwards.
+ */
+void exhume_body_string_free(char *s);
+
+/**
+ * Message of the last failure on the calling thread, or `NULL`. The string
+ * stays valid until the next failing call on that thread.
+ */
+const char *exhume_body_last_error(void);
+
+/**
+ * Close the body and release its handle. `NULL` is ignored.
+ *
+ * # Safety
+ *
+ * `body` must be `NULL` or come from [`exhume_body_open`], and must not be
+ * used afterwards.
+ */
+void exhume_body_close(ExhumeBody *body);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* EXHUME_BODY_H */
@@ -20,17 +20,31 @@
 //! | **Tail magic** | 4 bytes         | `ATT\0`                              |
 //! | **seg_len**    | 4 bytes (BE)    | Total segment length (head+…+tail)   |
 //!
-//! Data pages are named `page0`, `page1`, …, `pageN` and may be zlib-compressed
-//! (indicated by a non-zero flag).  Metadata segments such as `pagesize`,
-//! `imagesize`, and `sectorsize` carry acquisition parameters.
-
+//! Data pages are named `page0`, `page1`, …, `pageN` (`seg0`, `seg1`, … in
+//! images written by early AFFLIB releases).  The low bit of the flag marks a
+//! compressed page and bits 4–7 select the algorithm (zlib, LZMA or a run of
+//! NUL bytes).  Metadata segments such as `pagesize`, `imagesize`, and
+//! `sectorsize` carry acquisition parameters, either in the payload or in the
+//! 32-bit flag (`arg`) when the payload is empty.  `pageN_md5` (or `_sha1`,
+//! `_sha256`) segments hold a digest of the uncompressed page, checked on read
+//! with [`AFF::set_verify_checksums`].
+//!
+//! Reference: <https://github.com/sshock/AFFLIBv3/blob/master/lib/afflib.h>
+
+use crate::error::ExhumeBodyError;
+use crate::hash::{to_hex, HashAlgorithm, MultiHasher};
+use crate::info::FormatInfo;
+use crate::metadata::SectorRange;
+use crate::read_at::ReadAt;
+use crate::source::Source;
+use crate::vfs::{FileSystem, StdFileSystem};
 use flate2::read::ZlibDecoder;
-use log::info;
+use log::{info, warn};
 use std::cmp::min;
 use std::collections::HashMap;
-use std::fs::File;
 use std::io::{self, Read, Seek, SeekFrom};
 use std::path::Path;
+use std::sync::Arc;
 
 // ---- AFF constants ----------------------------------------------------------
 
@@ -49,9 +63,24 @@ const AFF_DEFAULT_PAGE_SIZE: u32 = 16 * 1024 * 1024;
 /// Default sector size.
 const AFF_DEFAULT_SECTOR_SIZE: u16 = 512;
 
+/// Page flag bit: the payload is compressed.
+const AFF_PAGE_COMPRESSED: u32 = 0x0001;
+
+/// Mask selecting the compression algorithm of a page.
+const AFF_PAGE_COMP_ALG_MASK: u32 = 0x00F0;
+
+/// Compression algorithm: zlib.
+const AFF_PAGE_COMP_ALG_ZLIB: u32 = 0x0000;
+
+/// Compression algorithm: LZMA.
+const AFF_PAGE_COMP_ALG_LZMA: u32 = 0x0020;
+
+/// Compression algorithm: the payload is a BE u32 count of NUL bytes.
+const AFF_PAGE_COMP_ALG_ZERO: u32 = 0x0030;
+
 // ---- Helper: read big-endian u32 --------------------------------------------
 
-fn read_be_u32(file: &mut File) -> io::Result<u32> {
+fn read_be_u32(file: &mut Source) -> io::Result<u32> {
     let mut buf = [0u8; 4];
     file.read_exact(&mut buf)?;
     Ok(u32::from_be_bytes(buf))
@@ -77,10 +106,55 @@ struct AffPage {
     data_offset: u64,
     /// Length of the (possibly compressed) data payload in bytes.
     data_len: u32,
-    /// Segment flag – non-zero ⇒ the payload is zlib-compressed.
+    /// Segment flag – see the `AFF_PAGE_*` constants.
     flag: u32,
 }
 
+/// Parse a data page segment name (`pageN`, or the legacy `segN`) into its
+/// page number.
+fn page_number(name: &str) -> Option<usize> {
+    name.strip_prefix("page")
+        .or_else(|| name.strip_prefix("seg"))
+        .and_then(|s| s.parse::<usize>().ok())
+}
+
+/// Render a metadata segment for display: printable payloads as text, hash
+/// segments as hex, and empty segments as their 32-bit `arg`.
+fn format_metadata(data: Option<&[u8]>, arg: u32) -> String {
+    let Some(d) = data else {
+        return arg.to_string();
+    };
+    match std::str::from_utf8(d) {
+        Ok(txt)
+            if !txt
+                .chars()
+                .any(|c| c.is_control() && !c.is_whitespace() && c != '\0') =>
+        {
+            txt.trim_end_matches('\0').trim().to_string()
+        }
+        _ => d.iter().map(|b| format!("{:02x}", b)).collect(),
+    }
+}
+
+/// Acquisition digest stored in the `md5`, `sha1` or `sha256` segment.
+fn stored_hash(name: &str, data: Option<&[u8]>) -> Option<(HashAlgorithm, String)> {
+    let data = data?;
+    let algorithm = match (name, data.len()) {
+        ("md5", 16) => HashAlgorithm::Md5,
+        ("sha1", 20) => HashAlgorithm::Sha1,
+        ("sha256", 32) => HashAlgorithm::Sha256,
+        _ => return None,
+    };
+    Some((algorithm, to_hex(data)))
+}
+
+/// Digest of a data page stored in a `pageN_md5`, `pageN_sha1` or
+/// `pageN_sha256` segment, with its page number.
+fn page_digest(name: &str, data: Option<&[u8]>) -> Option<(usize, (HashAlgorithm, String))> {
+    let (page, algorithm) = name.strip_prefix("page")?.split_once('_')?;
+    Some((page.parse().ok()?, stored_hash(algorithm, data)?))
+}
+
 // ---- Public AFF reader ------------------------------------------------------
 
 /// Native AFF image reader.
@@ -89,7 +163,7 @@ struct AffPage {
 /// source of evidence bytes inside the [`Body`](crate::Body) abstraction.
 pub struct AFF {
     /// Open file handle to the `.aff` file.
-    file: File,
+    file: Source,
     /// Original path (kept for display / cloning).
     path: String,
     /// Virtual cursor position inside the *uncompressed* image.
@@ -100,8 +174,17 @@ pub struct AFF {
     page_size: u32,
     /// Sector size (from `sectorsize` segment, default 512).
     sector_size: u16,
+    /// Remaining metadata segments (`acquisition_date`, `md5`, …) in file
+    /// order, rendered for display.
+    metadata: Vec<(String, String)>,
+    /// Digests of the whole image recorded at acquisition time.
+    hashes: Vec<(HashAlgorithm, String)>,
     /// Ordered index of data pages (`page0`, `page1`, …).
-    pages: Vec<AffPage>,
+    pages: Arc<[AffPage]>,
+    /// Digests of the uncompressed pages, keyed by page number.
+    page_digests: Arc<HashMap<usize, Vec<(HashAlgorithm, String)>>>,
+    /// Check pages against `page_digests` when read.
+    verify_checksums: bool,
     /// Page number currently held in `cache_data` (`None` = empty cache).
     cache_page: Option<usize>,
     /// Decompressed bytes of the cached page.
@@ -115,9 +198,21 @@ impl AFF {
     ///
     /// The constructor validates the file header, scans every segment to build a
     /// page index, and extracts metadata (`pagesize`, `imagesize`, `sectorsize`).
-    pub fn new(file_path: &str) -> Result<AFF, String> {
+    pub fn new(file_path: &str) -> Result<AFF, ExhumeBodyError> {
+        Self::open_in(&StdFileSystem, file_path)
+    }
+
+    /// Same as [`AFF::new`], opening the image through `fs` instead of the
+    /// operating system.
+    pub fn open_in(fs: &dyn FileSystem, file_path: &str) -> Result<AFF, ExhumeBodyError> {
+        Self::new_impl(fs, file_path).map_err(ExhumeBodyError::Aff)
+    }
+
+    fn new_impl(fs: &dyn FileSystem, file_path: &str) -> Result<AFF, String> {
         let path = Path::new(file_path);
-        let mut file = File::open(path).map_err(|e| format!("Error opening AFF image: {}", e))?;
+        let mut file = fs
+            .open(path)
+            .map_err(|e| format!("Error opening AFF image: {}", e))?;
 
         // --- Validate file header ---
         let mut header = [0u8; 8];
@@ -132,6 +227,9 @@ impl AFF {
         let mut page_size: Option<u32> = None;
         let mut image_size: Option<u64> = None;
         let mut sector_size: Option<u16> = None;
+        let mut metadata: Vec<(String, String)> = Vec::new();
+        let mut hashes = Vec::new();
+        let mut page_digests: HashMap<usize, Vec<(HashAlgorithm, String)>> = HashMap::new();
 
         loop {
             // Try to read segment head magic.
@@ -172,7 +270,7 @@ impl AFF {
                 // record offsets, but small metadata segments are fine to
                 // buffer fully.  Pages can be huge so we only fully read
                 // metadata segments (name does NOT start with "page").
-                if name.starts_with("page") && name[4..].parse::<usize>().is_ok() {
+                if page_number(&name).is_some() {
                     // Data page – skip the payload, just record the offset.
                     file.seek(SeekFrom::Current(data_len as i64))
                         .map_err(|e| format!("Error skipping page data: {}", e))?;
@@ -201,10 +299,7 @@ impl AFF {
             }
 
             // ---- Interpret known segment names ----
-            if let Some(page_num) = name
-                .strip_prefix("page")
-                .and_then(|s| s.parse::<usize>().ok())
-            {
+            if let Some(page_num) = page_number(&name) {
                 pages_map.insert(
                     page_num,
                     AffPage {
@@ -213,12 +308,13 @@ impl AFF {
                         flag,
                     },
                 );
-            } else if name == "pagesize" {
-                if let Some(ref d) = data {
-                    if d.len() >= 4 {
-                        page_size = Some(u32::from_be_bytes([d[0], d[1], d[2], d[3]]));
+            } else if name == "pagesize" || name == "segsize" {
+                page_size = match data {
+                    Some(ref d) if d.len() >= 4 => {
+                        Some(u32::from_be_bytes([d[0], d[1], d[2], d[3]]))
                     }
-                }
+                    _ => Some(flag),
+                };
             } else if name == "imagesize" {
                 if let Some(ref d) = data {
                     if d.len() >= 8 {
@@ -226,12 +322,19 @@ impl AFF {
                     }
                 }
             } else if name == "sectorsize" {
-                if let Some(ref d) = data {
-                    if d.len() >= 4 {
-                        let v = u32::from_be_bytes([d[0], d[1], d[2], d[3]]);
-                        sector_size = Some(v as u16);
-                    }
+                let v = match data {
+                    Some(ref d) if d.len() >= 4 => u32::from_be_bytes([d[0], d[1], d[2], d[3]]),
+                    _ => flag,
+                };
+                sector_size = Some(v as u16);
+            } else if let Some((page, digest)) = page_digest(&name, data.as_deref()) {
+                let digests = page_digests.entry(page).or_default();
+                if !digests.iter().any(|(a, _)| *a == digest.0) {
+                    digests.push(digest);
                 }
+            } else if !name.is_empty() && !name.starts_with("page") && !name.starts_with("seg") {
+                hashes.extend(stored_hash(&name, data.as_deref()));
+                metadata.push((name, format_metadata(data.as_deref(), flag)));
             }
         }
 
@@ -251,7 +354,9 @@ impl AFF {
             }
         }
 
-        let ps = page_size.unwrap_or(AFF_DEFAULT_PAGE_SIZE);
+        let ps = page_size
+            .filter(|&v| v != 0)
+            .unwrap_or(AFF_DEFAULT_PAGE_SIZE);
         let is = image_size.unwrap_or_else(|| pages.len() as u64 * ps as u64);
 
         info!(
@@ -267,8 +372,14 @@ impl AFF {
             position: 0,
             image_size: is,
             page_size: ps,
-            sector_size: sector_size.unwrap_or(AFF_DEFAULT_SECTOR_SIZE),
-            pages,
+            sector_size: sector_size
+                .filter(|&v| v != 0)
+                .unwrap_or(AFF_DEFAULT_SECTOR_SIZE),
+            metadata,
+            hashes,
+            pages: pages.into(),
+            page_digests: Arc::new(page_digests),
+            verify_checksums: false,
             cache_page: None,
             cache_data: Vec::new(),
         })
@@ -276,14 +387,25 @@ impl AFF {
 
     // ---- Info helpers -------------------------------------------------------
 
+    /// Path, sizes, page count and the AFF metadata segments.
+    pub fn info(&self) -> FormatInfo {
+        let metadata = self
+            .metadata
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        FormatInfo::new("AFF Image Information")
+            .field("Path", &self.path)
+            .field("Image Size", format!("{} bytes", self.image_size))
+            .field("Page Size", format!("{} bytes", self.page_size))
+            .field("Sector Size", self.sector_size)
+            .field("Total Pages", self.pages.len())
+            .section("Metadata", metadata)
+    }
+
     /// Print parsed metadata to the log.
     pub fn print_info(&self) {
-        info!("AFF Image Information:");
-        info!("Path          : {}", self.path);
-        info!("Image Size    : {} bytes", self.image_size);
-        info!("Page Size     : {} bytes", self.page_size);
-        info!("Sector Size   : {}", self.sector_size);
-        info!("Total Pages   : {}", self.pages.len());
+        self.info().log();
     }
 
     /// Returns the sector size parsed from the image (default 512).
@@ -291,10 +413,96 @@ impl AFF {
         self.sector_size
     }
 
+    /// Size of the acquired image in bytes.
+    pub fn size(&self) -> u64 {
+        self.image_size
+    }
+
+    /// Metadata segments (`acquisition_date`, `md5`, …) as name/value pairs.
+    pub fn acquisition_info(&self) -> &[(String, String)] {
+        &self.metadata
+    }
+
+    /// Digests of the image stored at acquisition time (`md5`, `sha1` and
+    /// `sha256` segments).
+    pub fn stored_hashes(&self) -> &[(HashAlgorithm, String)] {
+        &self.hashes
+    }
+
+    /// Compare every page read with the digests stored for it (`pageN_md5`,
+    /// …). A mismatch fails the read with [`io::ErrorKind::InvalidData`];
+    /// pages without a stored digest are read unchecked. Off by default.
+    pub fn set_verify_checksums(&mut self, verify: bool) {
+        self.verify_checksums = verify;
+        self.cache_page = None;
+        self.cache_data.clear();
+    }
+
+    /// Whether reads check the page digests.
+    pub fn verify_checksums(&self) -> bool {
+        self.verify_checksums
+    }
+
+    /// Read and check every page of the image, whatever
+    /// [`AFF::set_verify_checksums`] says, and return the sectors of the
+    /// pages that do not decompress or fail their stored digest, sorted and
+    /// merged. `progress` receives the pages checked and the page count.
+    pub fn verify_pages(&self, mut progress: impl FnMut(u64, u64)) -> Vec<SectorRange> {
+        let total = self.pages.len() as u64;
+        let sector_size = self.sector_size as u64;
+        let sectors_per_page = self.page_size as u64 / sector_size;
+        let total_sectors = self.image_size.div_ceil(sector_size);
+        let mut bad: Vec<SectorRange> = Vec::new();
+        for page in 0..self.pages.len() {
+            if let Err(e) = self.load_page(page, true) {
+                warn!("{}", e);
+                let start = page as u64 * sectors_per_page;
+                let count = sectors_per_page.min(total_sectors.saturating_sub(start));
+                match bad.last_mut() {
+                    Some(last) if last.end() == start => last.count += count,
+                    _ => bad.push(SectorRange { start, count }),
+                }
+            }
+            progress(page as u64 + 1, total);
+        }
+        bad
+    }
+
     // ---- Internal page reading ----------------------------------------------
 
+    /// Read a single page, checking it against its stored digests when
+    /// [`AFF::set_verify_checksums`] is on.
+    fn read_page(&self, page_num: usize) -> io::Result<Vec<u8>> {
+        self.load_page(page_num, self.verify_checksums)
+    }
+
+    fn load_page(&self, page_num: usize, verify: bool) -> io::Result<Vec<u8>> {
+        let data = self.decode_page(page_num)?;
+        if !verify {
+            return Ok(data);
+        }
+        let Some(digests) = self.page_digests.get(&page_num) else {
+            return Ok(data);
+        };
+        let algorithms: Vec<HashAlgorithm> = digests.iter().map(|(a, _)| *a).collect();
+        let mut hasher = MultiHasher::new(&algorithms);
+        hasher.update(&data);
+        for ((algorithm, stored), (_, actual)) in digests.iter().zip(hasher.finalize()) {
+            if *stored != actual {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "page{} does not match its stored {} ({} instead of {})",
+                        page_num, algorithm, actual, stored
+                    ),
+                ));
+            }
+        }
+        Ok(data)
+    }
+
     /// Read and (if necessary) decompress a single page into memory.
-    fn read_page(&mut self, page_num: usize) -> io::Result<Vec<u8>> {
+    fn decode_page(&self, page_num: usize) -> io::Result<Vec<u8>> {
         if page_num >= self.pages.len() {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
@@ -312,24 +520,49 @@ impl AFF {
         let flag = page.flag;
 
         // Read raw payload from disk.
-        self.file.seek(SeekFrom::Start(data_offset))?;
         let mut raw = vec![0u8; data_len];
-        self.file.read_exact(&mut raw)?;
-
-        if flag != 0 {
-            // Zlib-compressed page.
-            let mut decoder = ZlibDecoder::new(&raw[..]);
-            let mut decompressed = Vec::with_capacity(self.page_size as usize);
-            decoder.read_to_end(&mut decompressed).map_err(|e| {
-                io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    format!("Failed to decompress page{}: {}", page_num, e),
-                )
-            })?;
-            Ok(decompressed)
-        } else {
+        self.file.read_exact_at(data_offset, &mut raw)?;
+
+        if flag & AFF_PAGE_COMPRESSED == 0 {
             // Uncompressed page.
-            Ok(raw)
+            return Ok(raw);
+        }
+
+        match flag & AFF_PAGE_COMP_ALG_MASK {
+            AFF_PAGE_COMP_ALG_ZLIB => {
+                let mut decoder = ZlibDecoder::new(&raw[..]);
+                let mut decompressed = Vec::with_capacity(self.page_size as usize);
+                decoder.read_to_end(&mut decompressed).map_err(|e| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Failed to decompress page{}: {}", page_num, e),
+                    )
+                })?;
+                Ok(decompressed)
+            }
+            AFF_PAGE_COMP_ALG_ZERO => {
+                // Payload is the number of NUL bytes making up the page.
+                let count = if raw.len() >= 4 {
+                    u32::from_be_bytes([raw[0], raw[1], raw[2], raw[3]]) as usize
+                } else {
+                    self.page_size as usize
+                };
+                Ok(vec![0u8; count])
+            }
+            AFF_PAGE_COMP_ALG_LZMA => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "page{} is LZMA-compressed, which is not supported",
+                    page_num
+                ),
+            )),
+            alg => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "page{} uses unknown compression algorithm 0x{:x}",
+                    page_num, alg
+                ),
+            )),
         }
     }
 
@@ -350,16 +583,17 @@ impl AFF {
 impl Clone for AFF {
     fn clone(&self) -> Self {
         Self {
-            file: self
-                .file
-                .try_clone()
-                .expect("failed to clone AFF file handle"),
+            file: self.file.clone(),
             path: self.path.clone(),
             position: self.position,
             image_size: self.image_size,
             page_size: self.page_size,
             sector_size: self.sector_size,
+            metadata: self.metadata.clone(),
+            hashes: self.hashes.clone(),
             pages: self.pages.clone(),
+            page_digests: self.page_digests.clone(),
+            verify_checksums: self.verify_checksums,
             // Reset cache – will be lazily filled.
             cache_page: None,
             cache_data: Vec::new(),
@@ -445,3 +679,68 @@ impl Seek for AFF {
         Ok(self.position)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::TempDir;
+    use crate::{Body, ReadPolicy, SectorRange};
+    use md5::{Digest, Md5};
+    use std::io::Read;
+
+    /// An AFF segment: head, name, payload and tail.
+    fn segment(name: &str, flag: u32, data: &[u8]) -> Vec<u8> {
+        let mut seg = b"AFF\0".to_vec();
+        seg.extend_from_slice(&(name.len() as u32).to_be_bytes());
+        seg.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        seg.extend_from_slice(&flag.to_be_bytes());
+        seg.extend_from_slice(name.as_bytes());
+        seg.extend_from_slice(data);
+        seg.extend_from_slice(b"ATT\0");
+        seg.extend_from_slice(&(seg.len() as u32 + 4).to_be_bytes());
+        seg
+    }
+
+    #[test]
+    fn page_digests_are_checked_on_read() {
+        let pages = [[0x11u8; 1024], [0x22u8; 1024]];
+        let mut image = b"AFF10\r\n\0".to_vec();
+        image.extend(segment("pagesize", 1024, &[]));
+        image.extend(segment("imagesize", 0, &[0, 0, 8, 0, 0, 0, 0, 0]));
+        for (i, page) in pages.iter().enumerate() {
+            image.extend(segment(&format!("page{}", i), 0, page));
+            let digest = Md5::digest(pages[0]);
+            image.extend(segment(&format!("page{}_md5", i), 0, &digest));
+        }
+        let dir = TempDir::new("aff");
+        let path = dir.join("aff.aff");
+        std::fs::write(&path, image).unwrap();
+
+        // page1 holds 0x22 bytes but its stored digest is the one of page0.
+        let mut plain = Body::try_new(path.to_string_lossy().into(), "aff").unwrap();
+        let mut data = Vec::new();
+        plain.read_to_end(&mut data).unwrap();
+        assert_eq!(data.len(), 2048);
+        assert_eq!(
+            plain.verify_chunks(|_, _| {}),
+            [SectorRange { start: 2, count: 2 }]
+        );
+
+        let mut verified = Body::builder(path.to_string_lossy())
+            .verify_chunk_checksums(true)
+            .build()
+            .unwrap();
+        let err = verified.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        let mut tolerant = Body::builder(path.to_string_lossy())
+            .verify_chunk_checksums(true)
+            .read_policy(ReadPolicy::BestEffort { fill: vec![0xee] })
+            .build()
+            .unwrap();
+        let mut data = Vec::new();
+        tolerant.read_to_end(&mut data).unwrap();
+        assert!(data[..1024].iter().all(|&b| b == 0x11));
+        assert!(data[1024..].iter().all(|&b| b == 0xee));
+        assert_eq!(tolerant.bad_ranges(), [SectorRange { start: 2, count: 2 }]);
+    }
+}
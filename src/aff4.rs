@@ -1,13 +1,26 @@
+use crate::read_at::ReadAt;
 use flate2::read::DeflateDecoder;
 use log::{debug, info, warn};
-use rio_api::model::{Literal, Term};
+use crate::error::ExhumeBodyError;
+use crate::hash::HashAlgorithm;
+use crate::info::FormatInfo;
+use crate::metadata::{merge_ranges, serialize_digests, EvidenceFile};
+use crate::source::Source;
+use crate::stats::IoCounters;
+use crate::trace::io_span;
+use crate::vfs::{FileSystem, MemoryFileSystem, StdFileSystem};
+use rio_api::model::{Literal, Subject, Term};
 use rio_api::parser::TriplesParser;
 use rio_turtle::TurtleParser;
+use serde::Serialize;
 
 use lz4_flex::block;
 use std::collections::BTreeMap;
-use std::fs::File;
 use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
 
 // -----------------------------
 // ZIP constants
@@ -18,6 +31,9 @@ const ZIP64_EOCD_SIG: [u8; 4] = [0x50, 0x4b, 0x06, 0x06]; // Zip64 EOCD Record
 const CD_ENTRY_SIG: [u8; 4] = [0x50, 0x4b, 0x01, 0x02]; // Central Dir File Header
 const LOCAL_FILE_SIG: [u8; 4] = [0x50, 0x4b, 0x03, 0x04];
 
+/// RDF metadata member every AFF4 volume carries at its root.
+const AFF4_INFORMATION_MEMBER: &str = "information.turtle";
+
 // -----------------------------
 // Error handling
 // -----------------------------
@@ -55,7 +71,7 @@ type Aff4Result<T> = Result<T, Aff4Error>;
 // -----------------------------
 
 /// Supported AFF4 compression methods (inside segments/chunks).
-#[derive(Clone, Debug, PartialEq, Default)]
+#[derive(Clone, Debug, PartialEq, Default, Serialize)]
 pub enum CompressionMethod {
     #[default]
     None,
@@ -81,6 +97,9 @@ struct Aff4Interval {
     length: u64,
     target_urn: String,
     target_offset: u64,
+    /// Byte the run reads as when it targets a pseudo-stream (`aff4:Zero`,
+    /// `aff4:SymbolicStreamXX`, ...) instead of stored data.
+    fill: Option<u8>,
 }
 
 /// Cache holds the last decoded chunk (simple, effective).
@@ -95,27 +114,47 @@ struct ChunkCache {
 // Small helper structs
 // -----------------------------
 
-/// Metadata extracted from information.turtle.
-#[derive(Debug, Clone)]
-struct Aff4Metadata {
-    image_size: u64,
-    chunk_size: u64,
-    chunks_in_segment: u64,
-    compression: CompressionMethod,
-    data_base_path: String, // e.g. "aff4%3A%2F%2F.../data"
-    // stored_urn currently unused in this codepath, keep if you need it later:
-    #[allow(dead_code)]
-    stored_urn: Option<String>,
+/// Metadata extracted from information.turtle, see [`AFF4::metadata`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Aff4Metadata {
+    pub image_size: u64,
+    pub chunk_size: u64,
+    pub chunks_in_segment: u64,
+    pub compression: CompressionMethod,
+    /// ZIP member prefix of the image stream, e.g. `aff4%3A%2F%2F.../data`.
+    pub data_base_path: String,
+    /// URN of the `aff4:stored` volume, when declared.
+    pub stored_urn: Option<String>,
+    /// `aff4:hash` digests of the image (not of its backing streams).
+    #[serde(serialize_with = "serialize_digests")]
+    pub hashes: Vec<(HashAlgorithm, String)>,
+}
+
+/// One ZIP container of a (possibly striped) AFF4 image.
+struct Aff4Volume {
+    path: String,
+    file: Source,
+    directory: BTreeMap<String, ZipEntry>,
+}
+
+impl Clone for Aff4Volume {
+    fn clone(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            file: self.file.clone(),
+            directory: self.directory.clone(),
+        }
+    }
 }
 
 /// ZIP access helper. Owns no state besides a file handle clone + directory.
 struct ZipReader {
-    file: File,
+    file: Source,
     dir: BTreeMap<String, ZipEntry>,
 }
 
 impl ZipReader {
-    fn new(file: &File, dir: BTreeMap<String, ZipEntry>) -> Aff4Result<Self> {
+    fn new(file: &Source, dir: BTreeMap<String, ZipEntry>) -> Aff4Result<Self> {
         Ok(Self {
             file: file.try_clone()?,
             dir,
@@ -133,11 +172,9 @@ impl ZipReader {
     }
 
     /// Computes the payload start (after local header + filename + extra).
-    fn payload_offset(&mut self, header_offset: u64) -> Aff4Result<u64> {
-        self.file.seek(SeekFrom::Start(header_offset))?;
-
+    fn payload_offset(&self, header_offset: u64) -> Aff4Result<u64> {
         let mut fixed = [0u8; 30];
-        self.file.read_exact(&mut fixed)?;
+        self.file.read_exact_at(header_offset, &mut fixed)?;
 
         if fixed[0..4] != LOCAL_FILE_SIG {
             return Err(Aff4Error::Format(format!(
@@ -153,18 +190,17 @@ impl ZipReader {
     }
 
     /// Reads the raw compressed payload bytes for a member.
-    fn read_member_compressed(&mut self, name: &str) -> Aff4Result<Vec<u8>> {
+    fn read_member_compressed(&self, name: &str) -> Aff4Result<Vec<u8>> {
         let e = self.entry(name)?.clone();
         let payload = self.payload_offset(e.header_offset)?;
 
-        self.file.seek(SeekFrom::Start(payload))?;
         let mut buf = vec![0u8; e.compressed_size as usize];
-        self.file.read_exact(&mut buf)?;
+        self.file.read_exact_at(payload, &mut buf)?;
         Ok(buf)
     }
 
     /// Reads & decompresses a ZIP member (supports STORE and DEFLATE).
-    fn read_member(&mut self, name: &str) -> Aff4Result<Vec<u8>> {
+    fn read_member(&self, name: &str) -> Aff4Result<Vec<u8>> {
         let e = self.entry(name)?.clone();
         let compressed = self.read_member_compressed(name)?;
 
@@ -187,7 +223,7 @@ impl ZipReader {
 
     /// Range read inside STORE member payload (fast path).
     fn read_store_range(
-        &mut self,
+        &self,
         name: &str,
         offset_in_member: u64,
         out: &mut [u8],
@@ -210,9 +246,7 @@ impl ZipReader {
             )));
         }
 
-        self.file
-            .seek(SeekFrom::Start(payload + offset_in_member))?;
-        self.file.read_exact(out)?;
+        self.file.read_exact_at(payload + offset_in_member, out)?;
         Ok(())
     }
 }
@@ -222,111 +256,348 @@ impl ZipReader {
 // -----------------------------
 #[derive(Default)]
 pub struct AFF4 {
-    file: Option<File>, // backing .aff4
-    image_size: u64,
-
-    intervals: Vec<Aff4Interval>,
+    /// Backing .aff4 containers, the opened one first. Striped images pull in
+    /// sibling volumes holding the streams the map refers to.
+    volumes: Arc<[Aff4Volume]>,
+    metadata: Aff4Metadata,
+    details: Aff4Details,
+    /// Every value of information.turtle, by predicate local name.
+    properties: Arc<BTreeMap<String, Vec<String>>>,
 
-    chunk_size: u64,
-    chunks_in_segment: u64,
-    compression: CompressionMethod,
+    intervals: Arc<[Aff4Interval]>,
 
-    zip_directory: BTreeMap<String, ZipEntry>,
     cache: ChunkCache,
 
     position: u64,
+    /// Read statistics, shared with the clones.
+    io: Arc<IoCounters>,
 }
 
 impl AFF4 {
-    pub fn new(path: &str) -> Result<Self, String> {
+    pub fn new(path: &str) -> Result<Self, ExhumeBodyError> {
+        Self::open_in(&StdFileSystem, path)
+    }
+
+    /// Same as [`AFF4::new`], opening the volumes through `fs` instead of
+    /// the operating system.
+    pub fn open_in(fs: &dyn FileSystem, path: &str) -> Result<Self, ExhumeBodyError> {
         // Fast reject path: AFF4 is ZIP-based and should start with a local file header.
         // Avoid expensive ZIP64/EOCD scanning on large non-AFF4 raw images.
-        if let Ok(mut file) = File::open(path) {
+        if let Ok(mut file) = fs.open(Path::new(path)) {
             let mut sig = [0u8; 4];
             if file.read_exact(&mut sig).is_err() || sig != LOCAL_FILE_SIG {
-                return Err("not an AFF4 ZIP container (missing local header signature)".into());
+                return Err(Aff4Error::Format(
+                    "not an AFF4 ZIP container (missing local header signature)".into(),
+                )
+                .into());
             }
         }
 
-        match Self::new_impl(path) {
-            Ok(v) => Ok(v),
-            Err(e) => Err(e.to_string()),
-        }
+        Ok(Self::new_impl(fs, path)?)
     }
 
-    fn new_impl(path: &str) -> Aff4Result<Self> {
-        let mut file = File::open(path)?;
-        let zip_directory = Self::parse_zip_structure(&mut file)?;
+    /// Parse a single-volume image held in memory.
+    pub fn from_bytes(data: impl Into<Arc<[u8]>>) -> Result<Self, ExhumeBodyError> {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("image.aff4", data);
+        Self::open_in(&fs, "image.aff4")
+    }
 
-        let mut zip = ZipReader::new(&file, zip_directory.clone())?;
+    fn new_impl(fs: &dyn FileSystem, path: &str) -> Aff4Result<Self> {
+        let primary = Self::open_volume(fs, path)?;
+        let zip = ZipReader::new(&primary.file, primary.directory.clone())?;
 
         // Read metadata
-        let turtle_bytes = zip.read_member("information.turtle")?;
+        let turtle_bytes = zip.read_member(AFF4_INFORMATION_MEMBER)?;
         let turtle_content = String::from_utf8(turtle_bytes)
             .map_err(|e| Aff4Error::Format(format!("information.turtle not utf-8: {}", e)))?;
         let meta = Self::parse_metadata(&turtle_content)?;
+        let mut properties: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for property in Self::parse_triples(&turtle_content)?.values().flatten() {
+            properties
+                .entry(property.name().to_string())
+                .or_default()
+                .push(property.value.clone());
+        }
+        let details = Aff4Details::from_properties(&properties);
 
-        // Locate map and idx based on the current strategy: "{data_base_path}/map"
+        let mut volumes = vec![primary];
+        let mut siblings: Option<Vec<Aff4Volume>> = None;
+
+        // Locate map and idx based on the current strategy: "{data_base_path}/map".
+        // In a striped image the map may live in another volume.
         let map_member = format!("{}/map", meta.data_base_path);
-        if !zip.directory().contains_key(&map_member) {
-            return Err(Aff4Error::Missing(format!(
-                "no binary map found at expected {}",
-                map_member
-            )));
+        if !volumes[0].directory.contains_key(&map_member) {
+            let found = siblings
+                .get_or_insert_with(|| Self::sibling_volumes(fs, path))
+                .iter()
+                .position(|v| v.directory.contains_key(&map_member));
+            match found {
+                Some(i) => {
+                    let volume = siblings.as_mut().unwrap().remove(i);
+                    info!("Binary map found in volume {}", volume.path);
+                    volumes.push(volume);
+                }
+                None => {
+                    return Err(Aff4Error::Missing(format!(
+                        "no binary map found at expected {}",
+                        map_member
+                    )))
+                }
+            }
         }
 
-        let intervals = Self::parse_map_stream_with_idx(&mut zip, &map_member, meta.image_size)?;
+        let map_volume = volumes.last().unwrap();
+        let zip = ZipReader::new(&map_volume.file, map_volume.directory.clone())?;
+        let intervals = Self::parse_map_stream_with_idx(&zip, &map_member, meta.image_size)?;
+
+        // Follow intervals whose target stream is stored in another volume.
+        let mut targets: Vec<&str> = intervals
+            .iter()
+            .filter(|iv| iv.fill.is_none())
+            .map(|iv| iv.target_urn.as_str())
+            .collect();
+        targets.sort_unstable();
+        targets.dedup();
+        for target in targets {
+            let prefix = format!("{}/", target);
+            let stored = |v: &Aff4Volume| {
+                v.directory
+                    .range(prefix.clone()..)
+                    .next()
+                    .is_some_and(|(name, _)| name.starts_with(&prefix))
+            };
+            if volumes.iter().any(stored) {
+                continue;
+            }
+            let candidates = siblings.get_or_insert_with(|| Self::sibling_volumes(fs, path));
+            match candidates.iter().position(stored) {
+                Some(i) => {
+                    let volume = candidates.remove(i);
+                    info!("Stream {} is stored in volume {}", target, volume.path);
+                    volumes.push(volume);
+                }
+                None => warn!("No volume stores the stream {}", target),
+            }
+        }
 
         Ok(Self {
-            file: Some(file),
-            image_size: meta.image_size,
-            chunk_size: meta.chunk_size,
-            chunks_in_segment: meta.chunks_in_segment,
-            compression: meta.compression,
-            intervals,
-            zip_directory,
+            volumes: volumes.into(),
+            metadata: meta,
+            details,
+            properties: Arc::new(properties),
+            intervals: intervals.into(),
             cache: ChunkCache::default(),
             position: 0,
+            io: Arc::default(),
+        })
+    }
+
+    /// Internal counters behind [`Body::stats`](crate::Body::stats).
+    pub(crate) fn io_counters(&self) -> &IoCounters {
+        &self.io
+    }
+
+    /// Opens a ZIP container and checks it is an AFF4 volume.
+    fn open_volume(fs: &dyn FileSystem, path: &str) -> Aff4Result<Aff4Volume> {
+        let mut file = fs.open(Path::new(path))?;
+        let directory = Self::parse_zip_structure(&mut file)?;
+
+        // A ZIP without the RDF metadata member is not an AFF4 volume.
+        if !directory.contains_key(AFF4_INFORMATION_MEMBER) {
+            return Err(Aff4Error::Missing(format!(
+                "ZIP container has no {} member (not an AFF4 volume)",
+                AFF4_INFORMATION_MEMBER
+            )));
+        }
+
+        Ok(Aff4Volume {
+            path: path.to_string(),
+            file,
+            directory,
         })
     }
 
+    /// Other AFF4 volumes next to `path`, candidates for a striped image.
+    fn sibling_volumes(fs: &dyn FileSystem, path: &str) -> Vec<Aff4Volume> {
+        let own = Path::new(path);
+        let dir = match own.parent() {
+            Some(p) if !p.as_os_str().is_empty() => p,
+            _ => Path::new("."),
+        };
+        let own = fs.canonicalize(own).unwrap_or_else(|_| own.to_path_buf());
+
+        let mut candidates: Vec<_> = match fs.read_dir(dir) {
+            Ok(entries) => entries
+                .into_iter()
+                .filter(|p| {
+                    p.extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("aff4"))
+                })
+                .filter(|p| fs.canonicalize(p).map_or(true, |c| c != own))
+                .collect(),
+            Err(e) => {
+                warn!("Cannot list {} for AFF4 volumes: {}", dir.display(), e);
+                return Vec::new();
+            }
+        };
+        candidates.sort();
+
+        candidates
+            .iter()
+            .filter_map(|p| {
+                let name = p.to_string_lossy();
+                match Self::open_volume(fs, &name) {
+                    Ok(v) => Some(v),
+                    Err(e) => {
+                        debug!("Skipping {}: {}", name, e);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Image stream layout and, for striped images, the volume files.
+    pub fn info(&self) -> FormatInfo {
+        let volumes = if self.volumes.len() > 1 {
+            self.volumes
+                .iter()
+                .enumerate()
+                .map(|(i, volume)| (format!("Volume {}", i + 1), volume.path.clone()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        FormatInfo::new("AFF4 Image Information")
+            .field("Image Size", format!("0x{:x}", self.metadata.image_size))
+            .field("Chunk Size", format!("0x{:x}", self.metadata.chunk_size))
+            .field("Chunks In Segment", self.metadata.chunks_in_segment)
+            .field("Compression", format!("{:?}", self.metadata.compression))
+            .field("Intervals", self.intervals.len())
+            .section("Acquisition", self.details.fields())
+            .section("Volumes", volumes)
+    }
+
     pub fn print_info(&self) {
-        info!(
-            "AFF4 image_size=0x{:x}, chunk_size=0x{:x}, chunks_in_segment={}, compression={:?}, intervals={}",
-            self.image_size,
-            self.chunk_size,
-            self.chunks_in_segment,
-            self.compression,
-            self.intervals.len()
-        );
+        self.info().log();
     }
 
     pub fn get_sector_size(&self) -> u16 {
         512
     }
+
+    /// Size of the image stream in bytes.
+    pub fn size(&self) -> u64 {
+        self.metadata.image_size
+    }
+
+    /// Byte ranges of the image covered by map intervals; the holes between
+    /// them, and the runs mapped to `aff4:Zero` or `aff4:UnknownData`, read
+    /// as zeros.
+    pub fn allocated_ranges(&self) -> Vec<Range<u64>> {
+        merge_ranges(
+            self.intervals
+                .iter()
+                .filter(|iv| iv.fill != Some(0))
+                .map(|iv| {
+                    iv.virtual_offset.min(self.metadata.image_size)
+                        ..(iv.virtual_offset + iv.length).min(self.metadata.image_size)
+                })
+                .collect(),
+        )
+    }
+
+    /// Number of ZIP volumes the image is read from.
+    pub fn volume_count(&self) -> usize {
+        self.volumes.len()
+    }
+
+    /// Every ZIP volume the image is read from, each followed by its members
+    /// (`volume!member`, with their stored size).
+    pub fn files(&self) -> io::Result<Vec<EvidenceFile>> {
+        let mut files = Vec::new();
+        for volume in self.volumes.iter() {
+            files.push(EvidenceFile {
+                path: volume.path.clone(),
+                size: volume.file.len()?,
+                role: "AFF4 volume".to_string(),
+            });
+            for (name, entry) in &volume.directory {
+                let method = match entry.compression_method {
+                    0 => "stored".to_string(),
+                    8 => "deflate".to_string(),
+                    m => format!("method {}", m),
+                };
+                files.push(EvidenceFile {
+                    path: format!("{}!{}", volume.path, name),
+                    size: entry.compressed_size,
+                    role: format!(
+                        "AFF4 member ({}, {} bytes)",
+                        method, entry.uncompressed_size
+                    ),
+                });
+            }
+        }
+        Ok(files)
+    }
+
+    /// Digests of the image recorded in `information.turtle` (`aff4:hash`).
+    pub fn stored_hashes(&self) -> &[(HashAlgorithm, String)] {
+        &self.metadata.hashes
+    }
+
+    /// Image stream properties read from `information.turtle`.
+    pub fn metadata(&self) -> &Aff4Metadata {
+        &self.metadata
+    }
+
+    /// Case, tool and device details read from `information.turtle`.
+    pub fn details(&self) -> &Aff4Details {
+        &self.details
+    }
+
+    /// Every value of `information.turtle`, keyed by the local name of its
+    /// predicate (`caseNumber`, `size`, ...), in document order.
+    pub fn properties(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.properties
+    }
+
+    /// [`AFF4::details`] as label/value pairs.
+    pub fn acquisition_info(&self) -> Vec<(String, String)> {
+        self.details.fields()
+    }
 }
 
 // -----------------------------
 // Zip64 parsing
 // -----------------------------
 impl AFF4 {
-    fn parse_zip_structure(file: &mut File) -> Aff4Result<BTreeMap<String, ZipEntry>> {
+    fn parse_zip_structure(file: &mut Source) -> Aff4Result<BTreeMap<String, ZipEntry>> {
         let eocd_offset = Self::find_legacy_eocd_offset(file)?;
         debug!("Found Legacy EOCD at offset: {}", eocd_offset);
 
-        let locator_offset = eocd_offset
-            .checked_sub(20)
-            .ok_or_else(|| Aff4Error::Format("file too small for zip64 locator".into()))?;
-
-        file.seek(SeekFrom::Start(locator_offset))?;
+        // The Zip64 locator immediately precedes the legacy EOCD.
         let mut locator_buf = [0u8; 20];
-        file.read_exact(&mut locator_buf)?;
+        let has_locator = match eocd_offset.checked_sub(20) {
+            Some(locator_offset) => {
+                file.seek(SeekFrom::Start(locator_offset))?;
+                file.read_exact(&mut locator_buf)?;
+                locator_buf[0..4] == ZIP64_LOCATOR_SIG
+            }
+            None => false,
+        };
 
-        if locator_buf[0..4] != ZIP64_LOCATOR_SIG {
-            return Err(Aff4Error::Format(format!(
-                "invalid zip64 locator signature at 0x{:x}",
-                locator_offset
-            )));
+        if !has_locator {
+            // Small volumes may be written without Zip64 records: fall back to
+            // the legacy EOCD fields.
+            debug!("No zip64 locator before EOCD, using legacy EOCD fields");
+            file.seek(SeekFrom::Start(eocd_offset))?;
+            let mut eocd_buf = [0u8; 22];
+            file.read_exact(&mut eocd_buf)?;
+            let total_entries = u16::from_le_bytes(eocd_buf[10..12].try_into().unwrap()) as u64;
+            let cd_start_offset = u32::from_le_bytes(eocd_buf[16..20].try_into().unwrap()) as u64;
+            return Self::parse_central_directory(file, cd_start_offset, total_entries);
         }
 
         let eocd64_offset = u64::from_le_bytes(locator_buf[8..16].try_into().unwrap());
@@ -354,7 +625,7 @@ impl AFF4 {
     }
 
     fn parse_central_directory(
-        file: &mut File,
+        file: &mut Source,
         offset: u64,
         count: u64,
     ) -> Aff4Result<BTreeMap<String, ZipEntry>> {
@@ -444,8 +715,8 @@ impl AFF4 {
         Ok(directory)
     }
 
-    fn find_legacy_eocd_offset(file: &mut File) -> Aff4Result<u64> {
-        let file_len = file.metadata()?.len();
+    fn find_legacy_eocd_offset(file: &mut Source) -> Aff4Result<u64> {
+        let file_len = file.len()?;
         let mut cursor = file_len;
 
         // scan backwards in chunks, with seam overlap
@@ -490,6 +761,10 @@ impl AFF4 {
         let mut compression = CompressionMethod::None;
         let mut stored_urn: Option<String> = None;
         let mut data_urn: Option<String> = None;
+        // `(subject, algorithm, digest)` for every aff4:hash, and the
+        // subjects typed as streams (their hash covers stored bytes only).
+        let mut hash_candidates: Vec<(String, HashAlgorithm, String)> = Vec::new();
+        let mut stream_subjects: Vec<String> = Vec::new();
 
         let turtle_bytes = turtle_content.as_bytes();
         let mut parser = TurtleParser::new(Cursor::new(turtle_bytes), None);
@@ -516,6 +791,11 @@ impl AFF4 {
                 }
 
                 let pred_lower = predicate.to_lowercase();
+                let subject = match t.subject {
+                    Subject::NamedNode(node) => node.iri.to_string(),
+                    Subject::BlankNode(node) => format!("_:{}", node.id),
+                    _ => String::new(),
+                };
 
                 // Helpers: extract literal string if present
                 let lit_value = |o: Term<'_>| -> Option<String> {
@@ -547,6 +827,19 @@ impl AFF4 {
                     if let Term::NamedNode(node) = object {
                         data_urn = Some(node.iri.to_string());
                     }
+                } else if pred_lower.ends_with("schema#hash") {
+                    if let Term::Literal(Literal::Typed { value, datatype }) = object {
+                        if let Some(algorithm) = Self::hash_algorithm(datatype.iri) {
+                            hash_candidates.push((subject, algorithm, value.to_lowercase()));
+                        }
+                    }
+                } else if pred_lower.ends_with("rdf-syntax-ns#type") {
+                    if let Term::NamedNode(node) = object {
+                        let kind = node.iri.to_lowercase();
+                        if kind.ends_with("#imagestream") || kind.ends_with("#zipsegment") {
+                            stream_subjects.push(subject);
+                        }
+                    }
                 } else if pred_lower.ends_with("schema#chunksize") {
                     if let Some(v) = lit_value(object) {
                         if let Ok(n) = v.parse::<u64>() {
@@ -560,21 +853,10 @@ impl AFF4 {
                         }
                     }
                 } else if pred_lower.ends_with("compressionmethod") {
-                    let method = match object {
-                        Term::NamedNode(n) => n.iri,
-                        _ => "",
+                    compression = match object {
+                        Term::NamedNode(n) => Self::compression_method(n.iri),
+                        _ => Self::compression_method(""),
                     };
-                    if method.contains("lz4") {
-                        compression = CompressionMethod::Lz4;
-                    } else if method.contains("snappy") {
-                        compression = CompressionMethod::Snappy;
-                    } else if method.contains("zlib") {
-                        compression = CompressionMethod::Zlib;
-                    } else if method.contains("none") {
-                        compression = CompressionMethod::None;
-                    } else {
-                        compression = CompressionMethod::Unknown;
-                    }
                 }
 
                 Ok(()) as Result<(), Box<dyn std::error::Error>>
@@ -593,6 +875,14 @@ impl AFF4 {
             "data".to_string()
         };
 
+        let mut hashes: Vec<(HashAlgorithm, String)> = Vec::new();
+        for (subject, algorithm, digest) in hash_candidates {
+            if stream_subjects.contains(&subject) || hashes.iter().any(|(a, _)| *a == algorithm) {
+                continue;
+            }
+            hashes.push((algorithm, digest));
+        }
+
         Ok(Aff4Metadata {
             image_size,
             chunk_size,
@@ -600,8 +890,176 @@ impl AFF4 {
             compression,
             data_base_path,
             stored_urn,
+            hashes,
         })
     }
+
+    /// Digest algorithm of an `aff4:hash` literal, from its datatype IRI.
+    fn hash_algorithm(datatype: &str) -> Option<HashAlgorithm> {
+        match datatype.rsplit('#').next() {
+            Some(name) if name.eq_ignore_ascii_case("md5") => Some(HashAlgorithm::Md5),
+            Some(name) if name.eq_ignore_ascii_case("sha1") => Some(HashAlgorithm::Sha1),
+            Some(name) if name.eq_ignore_ascii_case("sha256") => Some(HashAlgorithm::Sha256),
+            _ => None,
+        }
+    }
+
+    /// Chunk compression named by an `aff4:compressionMethod` IRI.
+    fn compression_method(iri: &str) -> CompressionMethod {
+        let iri = iri.to_lowercase();
+        if iri.contains("lz4") {
+            CompressionMethod::Lz4
+        } else if iri.contains("snappy") {
+            CompressionMethod::Snappy
+        } else if iri.contains("zlib") {
+            CompressionMethod::Zlib
+        } else if iri.contains("none") || iri.ends_with("#nullcompressor") {
+            CompressionMethod::None
+        } else {
+            CompressionMethod::Unknown
+        }
+    }
+
+    /// Every triple of `turtle_content`: objects (IRIs, or literal values
+    /// with their datatype) by subject and predicate.
+    fn parse_triples(turtle_content: &str) -> Aff4Result<BTreeMap<String, Vec<TurtleProperty>>> {
+        let mut subjects: BTreeMap<String, Vec<TurtleProperty>> = BTreeMap::new();
+        let mut parser = TurtleParser::new(Cursor::new(turtle_content.as_bytes()), None);
+        parser
+            .parse_all(&mut |t| {
+                let subject = match t.subject {
+                    Subject::NamedNode(node) => node.iri.to_string(),
+                    Subject::BlankNode(node) => format!("_:{}", node.id),
+                    _ => return Ok(()) as Result<(), Box<dyn std::error::Error>>,
+                };
+                let (value, datatype) = match t.object {
+                    Term::NamedNode(node) => (node.iri.to_string(), None),
+                    Term::BlankNode(node) => (format!("_:{}", node.id), None),
+                    Term::Literal(Literal::Simple { value })
+                    | Term::Literal(Literal::LanguageTaggedString { value, .. }) => {
+                        (value.to_string(), None)
+                    }
+                    Term::Literal(Literal::Typed { value, datatype }) => {
+                        (value.to_string(), Some(datatype.iri.to_string()))
+                    }
+                    _ => return Ok(()),
+                };
+                subjects.entry(subject).or_default().push(TurtleProperty {
+                    predicate: t.predicate.iri.to_string(),
+                    value,
+                    datatype,
+                });
+                Ok(())
+            })
+            .map_err(|e| Aff4Error::Format(format!("turtle parse error: {}", e)))?;
+        Ok(subjects)
+    }
+}
+
+/// One `predicate object` pair of a turtle subject, see
+/// [`AFF4::parse_triples`].
+struct TurtleProperty {
+    /// Predicate IRI.
+    predicate: String,
+    value: String,
+    /// Datatype IRI of a typed literal.
+    datatype: Option<String>,
+}
+
+impl TurtleProperty {
+    /// Local name of the predicate, e.g. `caseNumber` for `aff4:caseNumber`.
+    fn name(&self) -> &str {
+        self.predicate
+            .rsplit(['#', '/'])
+            .next()
+            .unwrap_or(&self.predicate)
+    }
+
+    /// Whether the predicate's local name is `name`, ignoring case.
+    fn is(&self, name: &str) -> bool {
+        self.name().eq_ignore_ascii_case(name)
+    }
+}
+
+/// Case, acquisition tool and source device details recorded in
+/// information.turtle, see [`AFF4::details`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Aff4Details {
+    pub case_name: Option<String>,
+    pub case_number: Option<String>,
+    pub case_description: Option<String>,
+    pub evidence_number: Option<String>,
+    pub examiner: Option<String>,
+    pub notes: Option<String>,
+    /// Acquisition software, `aff4:tool`.
+    pub tool: Option<String>,
+    /// `aff4:creationTime` of the volume.
+    pub creation_time: Option<String>,
+    /// Acquisition start and end, `aff4:startTime` / `aff4:endTime`.
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    /// Source device, `aff4:diskDeviceName` (e.g. `/dev/sda`).
+    pub device_name: Option<String>,
+    pub device_model: Option<String>,
+    pub device_serial: Option<String>,
+    pub device_firmware: Option<String>,
+    pub device_interface: Option<String>,
+}
+
+impl Aff4Details {
+    /// Picks the known predicates out of `properties` (see
+    /// [`AFF4::properties`]), matching their local names ignoring case.
+    fn from_properties(properties: &BTreeMap<String, Vec<String>>) -> Self {
+        let get = |names: &[&str]| {
+            names.iter().find_map(|name| {
+                properties
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                    .and_then(|(_, values)| values.first().cloned())
+            })
+        };
+        Self {
+            case_name: get(&["caseName"]),
+            case_number: get(&["caseNumber"]),
+            case_description: get(&["caseDescription"]),
+            evidence_number: get(&["evidenceNumber"]),
+            examiner: get(&["examiner"]),
+            notes: get(&["notes"]),
+            tool: get(&["tool"]),
+            creation_time: get(&["creationTime"]),
+            start_time: get(&["startTime"]),
+            end_time: get(&["endTime"]),
+            device_name: get(&["diskDeviceName", "deviceName"]),
+            device_model: get(&["diskModel", "model"]),
+            device_serial: get(&["diskSerial", "serial"]),
+            device_firmware: get(&["diskFirmware", "firmware"]),
+            device_interface: get(&["diskInterfaceType"]),
+        }
+    }
+
+    /// The recorded details as label/value pairs, in a stable order.
+    pub fn fields(&self) -> Vec<(String, String)> {
+        [
+            ("Case Name", &self.case_name),
+            ("Case Number", &self.case_number),
+            ("Case Description", &self.case_description),
+            ("Evidence Number", &self.evidence_number),
+            ("Examiner", &self.examiner),
+            ("Notes", &self.notes),
+            ("Tool", &self.tool),
+            ("Creation Time", &self.creation_time),
+            ("Start Time", &self.start_time),
+            ("End Time", &self.end_time),
+            ("Device Name", &self.device_name),
+            ("Device Model", &self.device_model),
+            ("Device Serial", &self.device_serial),
+            ("Device Firmware", &self.device_firmware),
+            ("Device Interface", &self.device_interface),
+        ]
+        .into_iter()
+        .filter_map(|(label, value)| Some((label.to_string(), value.clone()?)))
+        .collect()
+    }
 }
 
 // -----------------------------
@@ -629,6 +1087,19 @@ impl AFF4 {
         Ok(out)
     }
 
+    /// Byte a pseudo-stream reads as: `aff4:Zero`, `aff4:UnknownData` and
+    /// `aff4:UnreadableData` read as zeros, `aff4:SymbolicStreamXX` as the
+    /// byte 0xXX. None for a stored stream.
+    fn symbolic_fill(uri: &str) -> Option<u8> {
+        let name = uri
+            .strip_prefix("http://aff4.org/Schema#")
+            .or_else(|| uri.strip_prefix("aff4:").filter(|n| !n.starts_with("//")))?;
+        match name {
+            "Zero" | "UnknownData" | "UnreadableData" => Some(0),
+            _ => u8::from_str_radix(name.strip_prefix("SymbolicStream")?, 16).ok(),
+        }
+    }
+
     fn aff4_uri_to_zip_base(uri: &str) -> String {
         // "aff4://uuid/path" -> "aff4%3A%2F%2Fuuid/path"
         if let Some(pos) = uri.find("://") {
@@ -641,7 +1112,7 @@ impl AFF4 {
     }
 
     fn parse_map_stream_with_idx(
-        zip: &mut ZipReader,
+        zip: &ZipReader,
         map_member: &str,
         image_size: u64,
     ) -> Aff4Result<Vec<Aff4Interval>> {
@@ -734,6 +1205,7 @@ impl AFF4 {
                 length: extent_len,
                 target_urn: target_zip,
                 target_offset: target_off,
+                fill: Self::symbolic_fill(target_uri),
             });
         }
 
@@ -802,7 +1274,10 @@ impl AFF4 {
 
     /// Cellebrite segments appear as ".../data/00001078" (8-digit decimal)
     fn resolve_segment_member(&self, base_stream: &str, logical_off: u64) -> Option<(String, u64)> {
-        let seg_size = self.chunk_size.saturating_mul(self.chunks_in_segment);
+        let seg_size = self
+            .metadata
+            .chunk_size
+            .saturating_mul(self.metadata.chunks_in_segment);
         if seg_size == 0 {
             return None;
         }
@@ -811,23 +1286,30 @@ impl AFF4 {
         let off_in_seg = logical_off % seg_size;
 
         let m_dec8 = format!("{}/{:08}", base_stream, seg_index);
-        if self.zip_directory.contains_key(&m_dec8) {
+        if self.volume_of(&m_dec8).is_some() {
             return Some((m_dec8, off_in_seg));
         }
 
         // fallbacks
         let m_hex8 = format!("{}/{:08x}", base_stream, seg_index);
-        if self.zip_directory.contains_key(&m_hex8) {
+        if self.volume_of(&m_hex8).is_some() {
             return Some((m_hex8, off_in_seg));
         }
 
         let m_dec = format!("{}/{}", base_stream, seg_index);
-        if self.zip_directory.contains_key(&m_dec) {
+        if self.volume_of(&m_dec).is_some() {
             return Some((m_dec, off_in_seg));
         }
 
         None
     }
+
+    /// Index of the volume storing `member`.
+    fn volume_of(&self, member: &str) -> Option<usize> {
+        self.volumes
+            .iter()
+            .position(|v| v.directory.contains_key(member))
+    }
 }
 
 // -----------------------------
@@ -838,7 +1320,7 @@ impl Seek for AFF4 {
         let new_pos = match pos {
             SeekFrom::Start(o) => o as i64,
             SeekFrom::Current(o) => self.position as i64 + o,
-            SeekFrom::End(o) => self.image_size as i64 + o,
+            SeekFrom::End(o) => self.metadata.image_size as i64 + o,
         };
 
         if new_pos < 0 {
@@ -853,19 +1335,26 @@ impl Seek for AFF4 {
     }
 }
 
-impl Read for AFF4 {
-    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        if buf.is_empty() || self.position >= self.image_size {
+impl AFF4 {
+    /// Read into `buf` from the virtual `position`, decoding chunks through
+    /// `cache`.
+    fn read_from(
+        &self,
+        position: u64,
+        buf: &mut [u8],
+        cache: &mut ChunkCache,
+    ) -> io::Result<usize> {
+        if buf.is_empty() || position >= self.metadata.image_size {
             return Ok(0);
         }
 
-        let max_can_read = (self.image_size - self.position) as usize;
+        let max_can_read = (self.metadata.image_size - position) as usize;
         let want_total = buf.len().min(max_can_read);
 
         let mut written = 0usize;
 
-        while written < want_total && self.position < self.image_size {
-            let pos = self.position;
+        while written < want_total {
+            let pos = position + written as u64;
 
             // Which interval covers current virtual position?
             let idx_opt = self.find_interval_index(pos);
@@ -873,7 +1362,9 @@ impl Read for AFF4 {
 
             // Hole -> zero fill until next interval or EOF
             if in_interval.is_none() {
-                let next = self.next_interval_start(pos).unwrap_or(self.image_size);
+                let next = self
+                    .next_interval_start(pos)
+                    .unwrap_or(self.metadata.image_size);
                 let hole_len = next.saturating_sub(pos) as usize;
                 let can = hole_len.min(want_total - written);
 
@@ -881,7 +1372,6 @@ impl Read for AFF4 {
 
                 buf[written..written + can].fill(0);
                 written += can;
-                self.position += can as u64;
                 continue;
             }
 
@@ -892,6 +1382,13 @@ impl Read for AFF4 {
             let remain_iv = iv.length - within_iv;
             let can_iv = (remain_iv as usize).min(want_total - written);
 
+            // Pseudo-streams are not stored, they read as a constant byte.
+            if let Some(byte) = iv.fill {
+                buf[written..written + can_iv].fill(byte);
+                written += can_iv;
+                continue;
+            }
+
             // Logical offset into the target stream
             let logical_off = iv.target_offset + within_iv;
 
@@ -908,8 +1405,8 @@ impl Read for AFF4 {
                 })?;
 
             // Determine chunk index and offset inside chunk
-            let chunk_index = (seg_off / self.chunk_size) as u32;
-            let within_chunk = (seg_off % self.chunk_size) as usize;
+            let chunk_index = (seg_off / self.metadata.chunk_size) as u32;
+            let within_chunk = (seg_off % self.metadata.chunk_size) as usize;
 
             if written == 0 {
                 debug!(
@@ -919,32 +1416,49 @@ impl Read for AFF4 {
             }
 
             // Load/decode chunk into cache (compression-aware)
-            self.load_chunk_into_cache(&member, chunk_index)?;
+            self.load_chunk_into_cache(cache, &member, chunk_index)?;
 
-            if within_chunk >= self.cache.data.len() {
+            if within_chunk >= cache.data.len() {
                 return Err(io::Error::other(
                     format!(
                         "within_chunk=0x{:x} beyond decoded chunk size=0x{:x}",
                         within_chunk,
-                        self.cache.data.len()
+                        cache.data.len()
                     ),
                 ));
             }
 
-            let available = self.cache.data.len() - within_chunk;
+            let available = cache.data.len() - within_chunk;
             let take = available.min(can_iv);
 
             buf[written..written + take]
-                .copy_from_slice(&self.cache.data[within_chunk..within_chunk + take]);
+                .copy_from_slice(&cache.data[within_chunk..within_chunk + take]);
 
             written += take;
-            self.position += take as u64;
         }
 
         Ok(written)
     }
 }
 
+impl Read for AFF4 {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut cache = std::mem::take(&mut self.cache);
+        let read = self.read_from(self.position, buf, &mut cache);
+        self.cache = cache;
+        let read = read?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl ReadAt for AFF4 {
+    /// Positional read, with a private chunk cache for the call.
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.read_from(offset, buf, &mut ChunkCache::default())
+    }
+}
+
 // -----------------------------
 // Chunk loading: index -> compressed slice -> decode
 // -----------------------------
@@ -956,8 +1470,8 @@ struct IndexEntry {
 
 impl AFF4 {
     fn read_index_entry(
-        &mut self,
-        zip: &mut ZipReader,
+        &self,
+        zip: &ZipReader,
         index_member: &str,
         idx: u32,
     ) -> io::Result<IndexEntry> {
@@ -1002,28 +1516,33 @@ impl AFF4 {
 }
 
 impl AFF4 {
-    fn load_chunk_into_cache(&mut self, member: &str, chunk_index: u32) -> io::Result<()> {
-        if self.cache.member == member
-            && self.cache.chunk_index == chunk_index
-            && !self.cache.data.is_empty()
-        {
+    fn load_chunk_into_cache(
+        &self,
+        cache: &mut ChunkCache,
+        member: &str,
+        chunk_index: u32,
+    ) -> io::Result<()> {
+        if cache.member == member && cache.chunk_index == chunk_index && !cache.data.is_empty() {
+            self.io.cache_hit();
             return Ok(());
         }
-
-        // Use a ZipReader clone for safe member reads.
-        let file = self
-            .file
-            .as_ref()
-            .ok_or_else(|| io::Error::other("AFF4 file is closed"))?;
-        let mut zip = ZipReader::new(file, self.zip_directory.clone())
+        self.io.cache_miss();
+
+        let _span = io_span!("aff4.chunk", member, chunk = chunk_index);
+        // Use a ZipReader clone of the volume storing the segment.
+        let volume = self
+            .volume_of(member)
+            .map(|i| &self.volumes[i])
+            .ok_or_else(|| io::Error::other(format!("missing data member {:?}", member)))?;
+        let zip = ZipReader::new(&volume.file, volume.directory.clone())
             .map_err(|e| io::Error::other(e.to_string()))?;
 
         let index_member = format!("{}.index", member);
 
-        let ent = self.read_index_entry(&mut zip, &index_member, chunk_index)?;
+        let ent = self.read_index_entry(&zip, &index_member, chunk_index)?;
 
-        let member_len = self
-            .zip_directory
+        let member_len = zip
+            .directory()
             .get(member)
             .ok_or_else(|| {
                 io::Error::other(
@@ -1057,14 +1576,16 @@ impl AFF4 {
             .map_err(|e| io::Error::other(e.to_string()))?;
 
         // Decode according to AFF4 layer-2 compression declared by metadata.
-        let decoded = match self.compression {
+        let _decompress = io_span!("aff4.decompress", compressed = c_len);
+        let decoded = match self.metadata.compression {
             CompressionMethod::None => compressed,
             CompressionMethod::Lz4 => {
-                if ent.c_len as u64 == self.chunk_size {
+                if ent.c_len as u64 == self.metadata.chunk_size {
                     // common optimization: store raw chunk when incompressible
                     compressed
                 } else {
-                    let mut out = vec![0u8; self.chunk_size as usize];
+                    let started = Instant::now();
+                    let mut out = vec![0u8; self.metadata.chunk_size as usize];
                     block::decompress_into(&compressed, &mut out).map_err(|err| {
                         let magic = compressed.get(0..4).unwrap_or(&compressed);
                         io::Error::other(
@@ -1074,6 +1595,7 @@ impl AFF4 {
                             ),
                         )
                     })?;
+                    self.io.decompressed(started.elapsed());
                     out
                 }
             }
@@ -1097,9 +1619,9 @@ impl AFF4 {
             }
         };
 
-        self.cache.member = member.to_string();
-        self.cache.chunk_index = chunk_index;
-        self.cache.data = decoded;
+        cache.member = member.to_string();
+        cache.chunk_index = chunk_index;
+        cache.data = decoded;
 
         Ok(())
     }
@@ -1110,21 +1632,512 @@ impl AFF4 {
 // -----------------------------
 impl Clone for AFF4 {
     fn clone(&self) -> Self {
-        let file = self
-            .file
-            .as_ref()
-            .map(|f| f.try_clone().expect("Failed to clone AFF4 file handle"));
-
         Self {
-            file,
-            image_size: self.image_size,
+            volumes: self.volumes.clone(),
+            metadata: self.metadata.clone(),
+            details: self.details.clone(),
+            properties: self.properties.clone(),
             intervals: self.intervals.clone(),
-            chunk_size: self.chunk_size,
-            chunks_in_segment: self.chunks_in_segment, // FIXED BUG
-            zip_directory: self.zip_directory.clone(),
-            compression: self.compression.clone(),
             cache: self.cache.clone(),
             position: self.position,
+            io: self.io.clone(),
+        }
+    }
+}
+
+// -----------------------------
+// Logical images (AFF4-L)
+// -----------------------------
+
+/// A file of an AFF4-L container (an `aff4:FileImage`), see
+/// [`Aff4Logical::files`].
+#[derive(Debug, Clone, Serialize)]
+pub struct Aff4LogicalFile {
+    /// URN of the object, e.g. `aff4://<volume>/Users/report.pdf`.
+    pub urn: String,
+    /// `aff4:originalFileName`: path of the file on the acquired system.
+    pub name: String,
+    pub size: u64,
+    /// `aff4:hash` digests of the file content.
+    #[serde(serialize_with = "serialize_digests")]
+    pub hashes: Vec<(HashAlgorithm, String)>,
+}
+
+/// Where the content of a logical file is stored in the volume.
+#[derive(Clone, Debug)]
+enum LogicalData {
+    /// Zero-length file, nothing stored.
+    Empty,
+    /// A single ZIP member (the usual layout of small files).
+    Member(String),
+    /// An `aff4:ImageStream`: chunked segments under the ZIP member prefix,
+    /// laid out as described by the metadata.
+    Stream(Aff4Metadata),
+}
+
+/// An AFF4-L container: individual files (`aff4:FileImage` objects) rather
+/// than a disk image, each readable as a stream.
+#[derive(Clone)]
+pub struct Aff4Logical {
+    volume: Aff4Volume,
+    files: Vec<Aff4LogicalFile>,
+    /// Storage of each of `files`.
+    data: Vec<LogicalData>,
+}
+
+impl Aff4Logical {
+    pub fn new(path: &str) -> Result<Self, ExhumeBodyError> {
+        Self::open_in(&StdFileSystem, path)
+    }
+
+    /// Same as [`Aff4Logical::new`], opening the volume through `fs`.
+    pub fn open_in(fs: &dyn FileSystem, path: &str) -> Result<Self, ExhumeBodyError> {
+        Ok(Self::new_impl(fs, path)?)
+    }
+
+    /// Parse a container held in memory.
+    pub fn from_bytes(data: impl Into<Arc<[u8]>>) -> Result<Self, ExhumeBodyError> {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("logical.aff4", data);
+        Self::open_in(&fs, "logical.aff4")
+    }
+
+    fn new_impl(fs: &dyn FileSystem, path: &str) -> Aff4Result<Self> {
+        let volume = AFF4::open_volume(fs, path)?;
+        let zip = ZipReader::new(&volume.file, volume.directory.clone())?;
+        let turtle_bytes = zip.read_member(AFF4_INFORMATION_MEMBER)?;
+        let turtle_content = String::from_utf8(turtle_bytes)
+            .map_err(|e| Aff4Error::Format(format!("information.turtle not utf-8: {}", e)))?;
+        let subjects = AFF4::parse_triples(&turtle_content)?;
+
+        let mut files = Vec::new();
+        let mut data = Vec::new();
+        for (urn, properties) in &subjects {
+            let get = |name: &str| {
+                properties
+                    .iter()
+                    .find(|p| p.is(name))
+                    .map(|p| p.value.as_str())
+            };
+            let is_file = properties
+                .iter()
+                .any(|p| p.is("type") && p.value.to_lowercase().ends_with("#fileimage"));
+            if !is_file {
+                continue;
+            }
+
+            let size = get("size").and_then(|v| v.parse().ok()).unwrap_or(0);
+            let mut hashes: Vec<(HashAlgorithm, String)> = Vec::new();
+            for property in properties.iter().filter(|p| p.is("hash")) {
+                let algorithm = property.datatype.as_deref().and_then(AFF4::hash_algorithm);
+                if let Some(algorithm) = algorithm {
+                    if !hashes.iter().any(|(a, _)| *a == algorithm) {
+                        hashes.push((algorithm, property.value.to_lowercase()));
+                    }
+                }
+            }
+            let name = get("originalfilename")
+                .map(str::to_string)
+                .unwrap_or_else(|| urn.rsplit('/').next().unwrap_or(urn).to_string());
+
+            let stored = get("stored");
+            let member = Self::member_names(urn, stored).into_iter().find(|m| {
+                volume.directory.contains_key(m)
+                    || volume.directory.contains_key(&format!("{}/{:08}", m, 0))
+            });
+            let location = match member {
+                Some(member) if volume.directory.contains_key(&member) => {
+                    LogicalData::Member(member)
+                }
+                Some(member) => LogicalData::Stream(Aff4Metadata {
+                    image_size: size,
+                    chunk_size: get("chunksize")
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(32768),
+                    chunks_in_segment: get("chunksinsegment")
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(1024),
+                    compression: get("compressionmethod")
+                        .map_or(CompressionMethod::None, AFF4::compression_method),
+                    data_base_path: member,
+                    stored_urn: stored.map(str::to_string),
+                    hashes: hashes.clone(),
+                }),
+                None if size == 0 => LogicalData::Empty,
+                None => {
+                    warn!("No ZIP member stores the logical file {}", urn);
+                    continue;
+                }
+            };
+            files.push(Aff4LogicalFile {
+                urn: urn.clone(),
+                name,
+                size,
+                hashes,
+            });
+            data.push(location);
+        }
+
+        if files.is_empty() {
+            return Err(Aff4Error::Missing(
+                "no aff4:FileImage object found (not an AFF4-L container)".into(),
+            ));
+        }
+        info!("AFF4-L container with {} logical files", files.len());
+
+        Ok(Self {
+            volume,
+            files,
+            data,
+        })
+    }
+
+    /// ZIP member names a logical object may be stored under: its URN
+    /// relative to the volume URN, or the whole URN with its scheme escaped.
+    fn member_names(urn: &str, volume_urn: Option<&str>) -> Vec<String> {
+        let mut names = Vec::new();
+        if let Some(relative) = volume_urn
+            .and_then(|volume| urn.strip_prefix(volume))
+            .and_then(|rest| rest.strip_prefix('/'))
+        {
+            names.push(relative.to_string());
         }
+        names.push(AFF4::aff4_uri_to_zip_base(urn));
+        names
+    }
+
+    /// The logical files of the container, sorted by URN.
+    pub fn files(&self) -> &[Aff4LogicalFile] {
+        &self.files
+    }
+
+    /// Path of the container.
+    pub fn path(&self) -> &str {
+        &self.volume.path
+    }
+
+    /// Opens the content of the logical file `urn` as a stream.
+    pub fn open_file(&self, urn: &str) -> Result<Aff4LogicalStream, ExhumeBodyError> {
+        let index = self
+            .files
+            .iter()
+            .position(|f| f.urn == urn)
+            .ok_or_else(|| Aff4Error::Missing(format!("no logical file {}", urn)))?;
+        let size = self.files[index].size;
+        let content = match &self.data[index] {
+            LogicalData::Empty => LogicalContent::Bytes(Arc::from([])),
+            LogicalData::Member(member) => {
+                let zip = ZipReader::new(&self.volume.file, self.volume.directory.clone())?;
+                let entry = zip.entry(member)?;
+                if entry.compression_method == 0 {
+                    LogicalContent::Stored {
+                        file: self.volume.file.clone(),
+                        offset: zip.payload_offset(entry.header_offset)?,
+                        len: entry.compressed_size,
+                    }
+                } else {
+                    LogicalContent::Bytes(zip.read_member(member)?.into())
+                }
+            }
+            LogicalData::Stream(metadata) => LogicalContent::Stream(Box::new(AFF4 {
+                volumes: Arc::from([self.volume.clone()]),
+                intervals: Arc::from([Aff4Interval {
+                    virtual_offset: 0,
+                    length: size,
+                    target_urn: metadata.data_base_path.clone(),
+                    target_offset: 0,
+                    fill: None,
+                }]),
+                metadata: metadata.clone(),
+                details: Aff4Details::default(),
+                properties: Arc::default(),
+                cache: ChunkCache::default(),
+                position: 0,
+                io: Arc::default(),
+            })),
+        };
+        Ok(Aff4LogicalStream {
+            content,
+            size,
+            position: 0,
+        })
+    }
+}
+
+/// Bytes of a logical file, see [`Aff4LogicalStream`].
+enum LogicalContent {
+    /// STORE member, read in place.
+    Stored {
+        file: Source,
+        offset: u64,
+        len: u64,
+    },
+    /// DEFLATE member (or empty file), decoded when opened.
+    Bytes(Arc<[u8]>),
+    Stream(Box<AFF4>),
+}
+
+/// Content of a logical file of an [`Aff4Logical`] container, read with
+/// `Read + Seek` or [`ReadAt`].
+pub struct Aff4LogicalStream {
+    content: LogicalContent,
+    size: u64,
+    position: u64,
+}
+
+impl Aff4LogicalStream {
+    /// Size of the file in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl ReadAt for Aff4LogicalStream {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        if offset >= self.size {
+            return Ok(0);
+        }
+        let want = buf.len().min((self.size - offset) as usize);
+        let buf = &mut buf[..want];
+        match &self.content {
+            LogicalContent::Stored {
+                file,
+                offset: start,
+                len,
+            } => {
+                if offset >= *len {
+                    return Ok(0);
+                }
+                let want = want.min((len - offset) as usize);
+                file.read_at(start + offset, &mut buf[..want])
+            }
+            LogicalContent::Bytes(bytes) => {
+                let start = (offset as usize).min(bytes.len());
+                let take = want.min(bytes.len() - start);
+                buf[..take].copy_from_slice(&bytes[start..start + take]);
+                Ok(take)
+            }
+            LogicalContent::Stream(stream) => stream.read_at(offset, buf),
+        }
+    }
+}
+
+impl Read for Aff4LogicalStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.read_at(self.position, buf)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for Aff4LogicalStream {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(o) => o as i64,
+            SeekFrom::Current(o) => self.position as i64 + o,
+            SeekFrom::End(o) => self.size as i64 + o,
+        };
+
+        if new_pos < 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek before start",
+            ));
+        }
+
+        self.position = new_pos as u64;
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::to_hex;
+    use md5::{Digest, Md5};
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    const PREFIXES: &str = "@prefix aff4: <http://aff4.org/Schema#> .\n\
+                            @prefix xsd: <http://www.w3.org/2001/XMLSchema#> .\n\n";
+
+    /// A ZIP container holding `members`, DEFLATEd when flagged, else
+    /// stored (as AFF4 writers store chunk data and indexes).
+    fn container(members: &[(&str, &[u8], bool)]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data, deflate) in members {
+            let method = if *deflate {
+                zip::CompressionMethod::Deflated
+            } else {
+                zip::CompressionMethod::Stored
+            };
+            let options = SimpleFileOptions::default().compression_method(method);
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    /// One uncompressed segment of `data` and its chunk index.
+    fn segment(data: &[u8], chunk_size: usize) -> (Vec<u8>, Vec<u8>) {
+        let mut index = Vec::new();
+        for (i, chunk) in data.chunks(chunk_size).enumerate() {
+            index.extend_from_slice(&((i * chunk_size) as u64).to_le_bytes());
+            index.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+        }
+        (data.to_vec(), index)
+    }
+
+    #[test]
+    fn lists_and_reads_logical_files() {
+        let report = b"quarterly figures, draft 3";
+        let notes: Vec<u8> = b"- call the lab\n".repeat(40);
+        let turtle = format!(
+            "{}<aff4://c0ffee> a aff4:ZipVolume .\n\n\
+             <aff4://c0ffee/docs/report.txt> a aff4:FileImage ;\n    \
+             aff4:originalFileName \"docs/report.txt\" ;\n    \
+             aff4:size \"{}\"^^xsd:long ;\n    \
+             aff4:hash \"{}\"^^aff4:MD5 ;\n    \
+             aff4:stored <aff4://c0ffee> .\n\n\
+             <aff4://c0ffee/notes.md> a aff4:FileImage ;\n    \
+             aff4:originalFileName \"notes.md\" ;\n    \
+             aff4:size \"{}\"^^xsd:long .\n",
+            PREFIXES,
+            report.len(),
+            to_hex(&Md5::digest(report)),
+            notes.len()
+        );
+        // The report is stored under its URN relative to the volume, the
+        // notes under their whole escaped URN.
+        let logical = Aff4Logical::from_bytes(container(&[
+            (AFF4_INFORMATION_MEMBER, turtle.as_bytes(), true),
+            ("docs/report.txt", report, false),
+            ("aff4%3A%2F%2Fc0ffee/notes.md", &notes, true),
+        ]))
+        .unwrap();
+
+        let files = logical.files();
+        let listed: Vec<(&str, u64)> = files.iter().map(|f| (f.name.as_str(), f.size)).collect();
+        assert_eq!(
+            listed,
+            [
+                ("docs/report.txt", report.len() as u64),
+                ("notes.md", notes.len() as u64)
+            ]
+        );
+        assert_eq!(files[0].hashes[0].0, HashAlgorithm::Md5);
+        assert!(files[1].hashes.is_empty());
+
+        for (file, content) in files.iter().zip([&report[..], &notes]) {
+            let mut stream = logical.open_file(&file.urn).unwrap();
+            assert_eq!(stream.size(), content.len() as u64);
+            let mut back = Vec::new();
+            stream.read_to_end(&mut back).unwrap();
+            assert_eq!(back, content);
+
+            stream.seek(SeekFrom::Start(8)).unwrap();
+            let mut buf = [0u8; 6];
+            stream.read_exact(&mut buf).unwrap();
+            assert_eq!(buf, content[8..14]);
+        }
+        assert!(logical.open_file("aff4://c0ffee/missing").is_err());
+    }
+
+    #[test]
+    fn reads_striped_volumes_with_symbolic_runs() {
+        let chunk_size = 512;
+        let first: Vec<u8> = (0..1024u32).map(|i| (i % 251) as u8).collect();
+        let second: Vec<u8> = (0..1024u32).map(|i| (i % 239) as u8 ^ 0x5a).collect();
+
+        // 0..1024 from the first stream, then aff4:Zero, 2048..3072 from the
+        // second stream (in the other volume), 512 bytes of 0xff and a hole.
+        let mut expected = first.clone();
+        expected.resize(2048, 0);
+        expected.extend_from_slice(&second);
+        expected.resize(3584, 0xff);
+        expected.resize(4096, 0);
+
+        let targets = [
+            "aff4://c0ffee/stream1",
+            "http://aff4.org/Schema#Zero",
+            "aff4://c0ffee/stream2",
+            "http://aff4.org/Schema#SymbolicStreamFF",
+        ];
+        let mut map = Vec::new();
+        for (index, (offset, length)) in [(0u64, 1024u64), (1024, 1024), (2048, 1024), (3072, 512)]
+            .into_iter()
+            .enumerate()
+        {
+            map.extend_from_slice(&offset.to_le_bytes());
+            map.extend_from_slice(&length.to_le_bytes());
+            map.extend_from_slice(&0u64.to_le_bytes());
+            map.extend_from_slice(&(index as u32).to_le_bytes());
+        }
+        let idx = targets.join("\0");
+
+        let turtle = format!(
+            "{}<aff4://c0ffee/image> a aff4:DiskImage ;\n    \
+             aff4:size \"4096\"^^xsd:long ;\n    \
+             aff4:dataStream <aff4://c0ffee/map> ;\n    \
+             aff4:caseNumber \"2024-007\" ;\n    \
+             aff4:examiner \"J. Doe\" ;\n    \
+             aff4:tool \"exhume tests\" ;\n    \
+             aff4:diskDeviceName \"/dev/sdb\" ;\n    \
+             aff4:hash \"{}\"^^aff4:MD5 .\n\n\
+             <aff4://c0ffee/stream1> a aff4:ImageStream ;\n    \
+             aff4:chunkSize \"{}\"^^xsd:int ;\n    \
+             aff4:chunksInSegment \"2\"^^xsd:int .\n",
+            PREFIXES,
+            to_hex(&Md5::digest(&expected)),
+            chunk_size
+        );
+        let (data1, index1) = segment(&first, chunk_size);
+        let (data2, index2) = segment(&second, chunk_size);
+        let stripe = format!("{}<aff4://c0ffee-2> a aff4:ZipVolume .\n", PREFIXES);
+
+        let mut fs = MemoryFileSystem::new();
+        fs.insert(
+            "case/image.aff4",
+            container(&[
+                (AFF4_INFORMATION_MEMBER, turtle.as_bytes(), true),
+                ("aff4%3A%2F%2Fc0ffee/map/map", &map, false),
+                ("aff4%3A%2F%2Fc0ffee/map/idx", idx.as_bytes(), false),
+                ("aff4%3A%2F%2Fc0ffee/stream1/00000000", &data1, false),
+                ("aff4%3A%2F%2Fc0ffee/stream1/00000000.index", &index1, false),
+            ]),
+        );
+        fs.insert(
+            "case/image-2.aff4",
+            container(&[
+                (AFF4_INFORMATION_MEMBER, stripe.as_bytes(), true),
+                ("aff4%3A%2F%2Fc0ffee/stream2/00000000", &data2, false),
+                ("aff4%3A%2F%2Fc0ffee/stream2/00000000.index", &index2, false),
+            ]),
+        );
+
+        let mut image = AFF4::open_in(&fs, "case/image.aff4").unwrap();
+        assert_eq!(image.size(), 4096);
+        assert_eq!(image.volume_count(), 2);
+        let mut back = Vec::new();
+        image.read_to_end(&mut back).unwrap();
+        assert_eq!(back, expected);
+        assert_eq!(image.allocated_ranges(), [0..1024, 2048..3584]);
+
+        let details = image.details();
+        assert_eq!(details.case_number.as_deref(), Some("2024-007"));
+        assert_eq!(details.examiner.as_deref(), Some("J. Doe"));
+        assert_eq!(details.tool.as_deref(), Some("exhume tests"));
+        assert_eq!(details.device_name.as_deref(), Some("/dev/sdb"));
+        assert_eq!(image.properties()["caseNumber"], ["2024-007"]);
+        assert_eq!(
+            image.stored_hashes(),
+            [(HashAlgorithm::Md5, to_hex(&Md5::digest(&expected)))]
+        );
+
+        // Without the second volume the striped run cannot be read.
+        fs.remove("case/image-2.aff4");
+        let image = AFF4::open_in(&fs, "case/image.aff4").unwrap();
+        assert_eq!(image.volume_count(), 1);
+        assert!(image.read_exact_at(2048, &mut [0u8; 16]).is_err());
     }
 }
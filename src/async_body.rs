@@ -0,0 +1,181 @@
+//! Tokio adapter, enabled by the `async` feature.
+//!
+//! [`AsyncBody`] implements [`AsyncRead`] and [`AsyncSeek`] over any [`Body`].
+//! Reads are positional ([`ReadAt`]) and run on tokio's blocking pool, so
+//! decompression and file IO never stall the executor.
+//!
+//! ```no_run
+//! # use exhume_body::AsyncBody;
+//! # use tokio::io::{AsyncReadExt, AsyncSeekExt};
+//! # async fn serve() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut body = AsyncBody::open("/evidence/disk.E01", "auto").await?;
+//! body.seek(std::io::SeekFrom::Start(0x100000)).await?;
+//! let mut sector = [0u8; 512];
+//! body.read_exact(&mut sector).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::read_at::ReadAt;
+use crate::{Body, ExhumeBodyError};
+use std::future::Future;
+use std::io::{self, SeekFrom};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+use tokio::task::JoinHandle;
+
+/// Largest read handed to a blocking task at once.
+const ASYNC_READ_MAX: usize = 1024 * 1024;
+
+/// Asynchronous reader over a shared [`Body`].
+///
+/// Cloning an `AsyncBody` is cheap: clones share the body and get their own
+/// position.
+pub struct AsyncBody {
+    body: Arc<Body>,
+    size: u64,
+    position: u64,
+    pending: Option<JoinHandle<io::Result<Vec<u8>>>>,
+}
+
+impl AsyncBody {
+    /// Wrap an opened body. Computes its size, which may block (gzip images
+    /// have to be decompressed once); prefer [`AsyncBody::open`] from async
+    /// code.
+    pub fn new(mut body: Body) -> io::Result<Self> {
+        let size = body.size()?;
+        Ok(Self::from_shared(Arc::new(body), size))
+    }
+
+    /// Open `file_path` as [`Body::try_new`] would, on the blocking pool.
+    pub async fn open(
+        file_path: impl Into<String>,
+        format: impl Into<String>,
+    ) -> Result<Self, ExhumeBodyError> {
+        let (file_path, format) = (file_path.into(), format.into());
+        tokio::task::spawn_blocking(move || {
+            let body = Body::try_new(file_path, &format)?;
+            Ok(Self::new(body)?)
+        })
+        .await
+        .map_err(|e| ExhumeBodyError::Io(io::Error::other(e)))?
+    }
+
+    /// Share a body already behind an `Arc` (e.g. with synchronous readers
+    /// using [`ReadAt`]). `size` is the value of [`Body::size`].
+    pub fn from_shared(body: Arc<Body>, size: u64) -> Self {
+        Self {
+            body,
+            size,
+            position: 0,
+            pending: None,
+        }
+    }
+
+    /// The shared body.
+    pub fn body(&self) -> &Arc<Body> {
+        &self.body
+    }
+
+    /// Total length of the evidence in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl Clone for AsyncBody {
+    fn clone(&self) -> Self {
+        Self {
+            body: self.body.clone(),
+            size: self.size,
+            position: self.position,
+            pending: None,
+        }
+    }
+}
+
+impl AsyncRead for AsyncBody {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+        if this.pending.is_none() {
+            let want = buf
+                .remaining()
+                .min(ASYNC_READ_MAX)
+                .min(this.size.saturating_sub(this.position) as usize);
+            if want == 0 {
+                return Poll::Ready(Ok(()));
+            }
+            let (body, position) = (this.body.clone(), this.position);
+            this.pending = Some(tokio::task::spawn_blocking(move || {
+                let mut data = vec![0u8; want];
+                let n = body.read_at(position, &mut data)?;
+                data.truncate(n);
+                Ok(data)
+            }));
+        }
+
+        let handle = this.pending.as_mut().unwrap();
+        let result = ready!(Pin::new(handle).poll(cx));
+        this.pending = None;
+        let data = result.map_err(io::Error::other)??;
+        let n = data.len().min(buf.remaining());
+        buf.put_slice(&data[..n]);
+        this.position += n as u64;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncSeek for AsyncBody {
+    fn start_seek(mut self: Pin<&mut Self>, pos: SeekFrom) -> io::Result<()> {
+        let next = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.size.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Seek before start or overflow")
+        })?;
+        // A read still running targets the old position: forget its result.
+        self.pending = None;
+        self.position = next;
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(self.position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+    #[tokio::test]
+    async fn reads_and_seeks() {
+        let dir = TempDir::new("async");
+        let path = dir.join("async.raw");
+        let data: Vec<u8> = (0..100_000).map(|i| (i % 253) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let mut body = AsyncBody::open(path.to_string_lossy(), "raw")
+            .await
+            .unwrap();
+        let mut all = Vec::new();
+        body.read_to_end(&mut all).await.unwrap();
+        assert_eq!(all, data);
+
+        let mut other = body.clone();
+        assert_eq!(other.seek(SeekFrom::End(-10)).await.unwrap(), 99_990);
+        let mut tail = Vec::new();
+        other.read_to_end(&mut tail).await.unwrap();
+        assert_eq!(tail, &data[99_990..]);
+    }
+}
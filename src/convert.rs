@@ -0,0 +1,263 @@
+//! Conversion of any supported image to a raw or E01 image.
+//!
+//! [`convert`] streams a [`Body`] into the target format, hashing the source
+//! on the way, then reads the result back to hash the destination so both
+//! sets of digests can be compared.
+//!
+//! ```no_run
+//! # use exhume_body::{convert, Body, ConvertOptions, ConvertTarget};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut body = Body::try_new("/evidence/disk.vmdk".to_string(), "auto")?;
+//! let summary = convert(
+//!     &mut body,
+//!     ConvertTarget::Ewf("/evidence/disk".into()),
+//!     ConvertOptions::default(),
+//! )?;
+//! assert!(summary.is_verified());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::ewf_writer::{EwfCaseInfo, EwfCompression, EwfWriter};
+use crate::hash::{HashAlgorithm, MultiHasher};
+use crate::metadata::SectorRange;
+use crate::writer::RawWriter;
+use crate::{Body, ExhumeBodyError};
+use log::{info, warn};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+/// Output format of [`convert`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConvertTarget {
+    /// A single raw (dd) image at this path.
+    Raw(PathBuf),
+    /// E01 segments named after this path (`disk` gives `disk.E01`, …).
+    Ewf(PathBuf),
+}
+
+/// Options of [`convert`].
+#[derive(Clone, Debug)]
+pub struct ConvertOptions {
+    /// Digests computed over the source and the destination.
+    pub hashes: Vec<HashAlgorithm>,
+    /// Leave runs of zeroes as holes in raw outputs.
+    pub sparse: bool,
+    /// Read the destination back and hash it.
+    pub verify: bool,
+    /// Maximum size of E01 segments (see [`EwfWriter::segment_size`]).
+    pub segment_size: Option<u64>,
+    /// Compression of E01 chunks.
+    pub compression: EwfCompression,
+    /// Case information stored in E01 outputs.
+    pub case: EwfCaseInfo,
+}
+
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        Self {
+            hashes: vec![HashAlgorithm::Md5, HashAlgorithm::Sha1],
+            sparse: true,
+            verify: true,
+            segment_size: None,
+            compression: EwfCompression::default(),
+            case: EwfCaseInfo::default(),
+        }
+    }
+}
+
+/// Outcome of [`convert`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConvertSummary {
+    /// Files written, in order.
+    pub outputs: Vec<PathBuf>,
+    /// Number of bytes copied from the source.
+    pub bytes: u64,
+    /// Digests of the source, in the order they were requested.
+    pub source_hashes: Vec<(HashAlgorithm, String)>,
+    /// Digests of the destination read back, empty without verification.
+    pub destination_hashes: Vec<(HashAlgorithm, String)>,
+    /// Sectors of the source that could not be read and were filled, see
+    /// [`Body::bad_ranges`]; E01 outputs record them in their `error2`
+    /// section.
+    pub bad_ranges: Vec<SectorRange>,
+}
+
+impl ConvertSummary {
+    /// Whether the destination was read back and matches the source.
+    pub fn is_verified(&self) -> bool {
+        !self.destination_hashes.is_empty() && self.source_hashes == self.destination_hashes
+    }
+}
+
+/// Copy `body` from its first to its last byte into `target`.
+///
+/// Existing files are never overwritten. E01 outputs are padded to whole
+/// sectors; only the first [`ConvertSummary::bytes`] bytes are hashed back.
+pub fn convert(
+    body: &mut Body,
+    target: ConvertTarget,
+    opts: ConvertOptions,
+) -> Result<ConvertSummary, ExhumeBodyError> {
+    convert_with_progress(body, target, opts, |_, _| {})
+}
+
+/// Same as [`convert`], calling `progress` with the bytes copied so far and
+/// the total, e.g. to draw a progress bar while acquiring a drive.
+pub fn convert_with_progress(
+    body: &mut Body,
+    target: ConvertTarget,
+    opts: ConvertOptions,
+    progress: impl FnMut(u64, u64) + 'static,
+) -> Result<ConvertSummary, ExhumeBodyError> {
+    info!("Converting {} image to {:?}", body.format_name(), target);
+    let (outputs, bytes, source_hashes) = match &target {
+        ConvertTarget::Raw(path) => {
+            let mut writer = RawWriter::new().sparse(opts.sparse).progress(progress);
+            for &algorithm in &opts.hashes {
+                writer = writer.hash(algorithm);
+            }
+            let summary = writer.write_to_path(body, path)?;
+            (vec![path.clone()], summary.bytes_written, summary.hashes)
+        }
+        ConvertTarget::Ewf(path) => {
+            let damaged = body.clone();
+            let mut writer = EwfWriter::new()
+                .bytes_per_sector(body.get_sector_size() as u32)
+                .compression(opts.compression)
+                .case_info(opts.case.clone())
+                .acquisition_errors_from(move || damaged.bad_ranges())
+                .progress(progress);
+            if let Some(size) = opts.segment_size {
+                writer = writer.segment_size(size);
+            }
+            for &algorithm in &opts.hashes {
+                writer = writer.hash(algorithm);
+            }
+            let summary = writer.write(body, path)?;
+            // Report the requested digests only, in the requested order.
+            let hashes = opts
+                .hashes
+                .iter()
+                .filter_map(|algorithm| summary.hashes.iter().find(|(a, _)| a == algorithm))
+                .cloned()
+                .collect();
+            (summary.segments, summary.bytes_written, hashes)
+        }
+    };
+
+    let mut summary = ConvertSummary {
+        outputs,
+        bytes,
+        source_hashes,
+        destination_hashes: Vec::new(),
+        bad_ranges: body.bad_ranges(),
+    };
+    for range in &summary.bad_ranges {
+        warn!(
+            "{} unreadable sectors at {} were filled",
+            range.count, range.start
+        );
+    }
+    if opts.verify {
+        summary.destination_hashes = match &target {
+            ConvertTarget::Raw(path) => hash_prefix(File::open(path)?, bytes, &opts.hashes)?,
+            ConvertTarget::Ewf(_) => {
+                let first = summary.outputs[0].to_string_lossy().to_string();
+                hash_prefix(Body::try_new(first, "ewf")?, bytes, &opts.hashes)?
+            }
+        };
+        if summary.is_verified() {
+            info!("Destination digests match the source");
+        } else {
+            info!("Destination digests DO NOT match the source");
+        }
+    }
+    Ok(summary)
+}
+
+/// Hash the first `length` bytes of `reader`.
+fn hash_prefix(
+    reader: impl Read,
+    length: u64,
+    algorithms: &[HashAlgorithm],
+) -> io::Result<Vec<(HashAlgorithm, String)>> {
+    let mut hasher = MultiHasher::new(algorithms);
+    let mut reader = reader.take(length);
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let n = reader.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn converts_to_raw_and_ewf_and_verifies() {
+        let dir = TempDir::new("convert");
+        let source = dir.join("source.raw");
+        let mut data = vec![0u8; 3 * 65536 + 512];
+        data[70_000..70_010].copy_from_slice(b"evidence!!");
+        std::fs::write(&source, &data).unwrap();
+        let source = source.to_string_lossy().to_string();
+
+        let opts = ConvertOptions {
+            hashes: vec![HashAlgorithm::Sha256, HashAlgorithm::Md5],
+            ..Default::default()
+        };
+        let mut body = Body::try_new(source.clone(), "raw").unwrap();
+        let raw = convert(
+            &mut body,
+            ConvertTarget::Raw(dir.join("out.raw")),
+            opts.clone(),
+        )
+        .unwrap();
+        assert!(raw.is_verified());
+        assert_eq!(std::fs::read(dir.join("out.raw")).unwrap(), data);
+
+        let mut body = Body::try_new(source, "raw").unwrap();
+        let ewf = convert(&mut body, ConvertTarget::Ewf(dir.join("out")), opts).unwrap();
+        assert!(ewf.is_verified());
+        assert_eq!(ewf.outputs, vec![dir.join("out.E01")]);
+        assert_eq!(ewf.source_hashes, raw.source_hashes);
+    }
+
+    #[test]
+    fn records_unreadable_sectors_in_error2() {
+        let dir = TempDir::new("convert_bad");
+        std::fs::write(dir.join("disk.raw"), vec![0x55u8; 64 * 1024]).unwrap();
+        std::fs::write(
+            dir.join("disk.map"),
+            "0x0 +\n0x0 0x2000 +\n0x2000 0x400 -\n0x2400 0xDC00 +\n",
+        )
+        .unwrap();
+        let mut body = Body::builder(dir.join("disk.raw").to_string_lossy())
+            .ddrescue_map(dir.join("disk.map"))
+            .read_policy(crate::ReadPolicy::BestEffort { fill: Vec::new() })
+            .error_granularity(4)
+            .build()
+            .unwrap();
+
+        let summary = convert(
+            &mut body,
+            ConvertTarget::Ewf(dir.join("out")),
+            ConvertOptions::default(),
+        )
+        .unwrap();
+        // Filled 4 sectors at a time: the next kilobyte goes with the bad one.
+        let bad = vec![SectorRange { start: 16, count: 4 }];
+        assert_eq!(summary.bad_ranges, bad);
+        assert!(summary.is_verified());
+        let ewf = crate::ewf::EWF::new(&dir.join("out.E01").to_string_lossy()).unwrap();
+        assert_eq!(ewf.acquisition_errors(), bad);
+    }
+}
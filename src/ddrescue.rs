@@ -0,0 +1,264 @@
+//! GNU ddrescue mapfiles, see [`RescueMap`].
+//!
+//! A drive imaged with ddrescue leaves a RAW image whose unread areas hold
+//! zeros, and a mapfile telling which areas those are. Opened with
+//! [`BodyBuilder::ddrescue_map`](crate::BodyBuilder::ddrescue_map), the
+//! image no longer passes those zeros off as the content of the drive:
+//! reading an unrescued area fails, or under
+//! [`ReadPolicy::BestEffort`](crate::ReadPolicy::BestEffort) is filled and
+//! listed in [`Body::bad_ranges`](crate::Body::bad_ranges), and
+//! [`Body::allocated_ranges`](crate::Body::allocated_ranges) only covers
+//! what was rescued.
+//!
+//! ```text
+//! # Mapfile. Created by GNU ddrescue version 1.27
+//! # current_pos  current_status  current_pass
+//! 0x00117000     +               1
+//! #      pos        size  status
+//! 0x00000000  0x00117000  +
+//! 0x00117000  0x00000200  -
+//! 0x00117200  0x00008E00  ?
+//! ```
+
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+
+/// State of a block of the mapfile, one character in the file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RescueStatus {
+    /// `?`: not read yet.
+    NonTried,
+    /// `*`: failed during the copying pass, not trimmed yet.
+    NonTrimmed,
+    /// `/`: trimmed, not scraped yet.
+    NonScraped,
+    /// `-`: a sector that failed every pass.
+    BadSector,
+    /// `+`: rescued.
+    Finished,
+}
+
+impl RescueStatus {
+    fn from_char(c: char) -> Option<Self> {
+        Some(match c {
+            '?' => Self::NonTried,
+            '*' => Self::NonTrimmed,
+            '/' => Self::NonScraped,
+            '-' => Self::BadSector,
+            '+' => Self::Finished,
+            _ => return None,
+        })
+    }
+
+    /// The character ddrescue writes for the status.
+    pub fn as_char(self) -> char {
+        match self {
+            Self::NonTried => '?',
+            Self::NonTrimmed => '*',
+            Self::NonScraped => '/',
+            Self::BadSector => '-',
+            Self::Finished => '+',
+        }
+    }
+}
+
+/// One line of the block list of a mapfile.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RescueBlock {
+    /// Offset of the block on the rescued drive, in bytes.
+    pub start: u64,
+    pub size: u64,
+    pub status: RescueStatus,
+}
+
+impl RescueBlock {
+    pub fn range(&self) -> Range<u64> {
+        self.start..self.start + self.size
+    }
+}
+
+/// The content of a GNU ddrescue mapfile (formerly logfile).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RescueMap {
+    /// Position ddrescue was at when the mapfile was written.
+    pub current_position: u64,
+    /// Blocks in the order of the file.
+    pub blocks: Vec<RescueBlock>,
+}
+
+impl RescueMap {
+    /// Read and parse the mapfile at `path`.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        std::fs::read_to_string(path)?.parse().map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), e),
+            )
+        })
+    }
+
+    /// End of the last block: the size of the rescue domain.
+    pub fn end(&self) -> u64 {
+        self.blocks
+            .iter()
+            .map(|b| b.start + b.size)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Bytes rescued.
+    pub fn rescued_bytes(&self) -> u64 {
+        self.blocks
+            .iter()
+            .filter(|b| b.status == RescueStatus::Finished)
+            .map(|b| b.size)
+            .sum()
+    }
+
+    /// Byte ranges not rescued (any status but [`RescueStatus::Finished`])
+    /// within the first `size` bytes, sorted and coalesced. What lies past
+    /// the end of the map was never tried and is included.
+    pub fn unrescued(&self, size: u64) -> Vec<Range<u64>> {
+        let mut ranges: Vec<Range<u64>> = self
+            .blocks
+            .iter()
+            .filter(|b| b.status != RescueStatus::Finished)
+            .map(|b| b.start.min(size)..(b.start + b.size).min(size))
+            .collect();
+        ranges.push(self.end().min(size)..size);
+        crate::metadata::merge_ranges(ranges)
+    }
+}
+
+impl FromStr for RescueMap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        // current_pos current_status [current_pass]; the pass was added in
+        // ddrescue 1.21.
+        let (number, status) = lines.next().ok_or("empty mapfile")?;
+        let current_position = status
+            .split_whitespace()
+            .next()
+            .and_then(parse_number)
+            .ok_or_else(|| format!("line {}: invalid status line '{}'", number, status))?;
+
+        let mut blocks: Vec<RescueBlock> = Vec::new();
+        for (number, line) in lines {
+            let invalid = || format!("line {}: invalid block '{}'", number, line);
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [start, size, status] = fields[..] else {
+                return Err(invalid());
+            };
+            let mut status = status.chars();
+            let block = RescueBlock {
+                start: parse_number(start).ok_or_else(invalid)?,
+                size: parse_number(size).ok_or_else(invalid)?,
+                status: status
+                    .next()
+                    .filter(|_| status.next().is_none())
+                    .and_then(RescueStatus::from_char)
+                    .ok_or_else(invalid)?,
+            };
+            if block.start.checked_add(block.size).is_none() {
+                return Err(invalid());
+            }
+            if blocks
+                .last()
+                .is_some_and(|last| last.start + last.size > block.start)
+            {
+                return Err(format!("line {}: block overlaps the previous one", number));
+            }
+            blocks.push(block);
+        }
+        Ok(Self {
+            current_position,
+            blocks,
+        })
+    }
+}
+
+/// ddrescue writes hexadecimal, and reads any base `strtoll` does.
+fn parse_number(s: &str) -> Option<u64> {
+    if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).ok()
+    } else if s.len() > 1 && s.starts_with('0') {
+        u64::from_str_radix(&s[1..], 8).ok()
+    } else {
+        s.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use crate::{Body, ReadPolicy, SectorRange};
+    use std::io::Read;
+
+    const MAPFILE: &str = "\
+# Mapfile. Created by GNU ddrescue version 1.27
+# Command line: ddrescue /dev/sdb disk.raw disk.map
+# current_pos  current_status  current_pass
+0x00000A00     -               1
+#      pos        size  status
+0x00000000  0x00000400  +
+0x00000400  0x00000200  -
+0x00000600  0x00000200  +
+0x00000800  0x00000400  ?
+";
+
+    #[test]
+    fn unrescued_areas_are_not_read_as_data() {
+        let map: RescueMap = MAPFILE.parse().unwrap();
+        assert_eq!(map.current_position, 0xa00);
+        assert_eq!(map.blocks[1].status, RescueStatus::BadSector);
+        assert_eq!(map.rescued_bytes(), 0x600);
+        assert_eq!(map.unrescued(0x1000), vec![0x400..0x600, 0x800..0x1000]);
+
+        let dir = TempDir::new("ddrescue");
+        std::fs::write(dir.join("disk.raw"), [0xaau8; 0x1000]).unwrap();
+        std::fs::write(dir.join("disk.map"), MAPFILE).unwrap();
+        let open = |policy| {
+            Body::builder(dir.join("disk.raw").to_string_lossy())
+                .ddrescue_map(dir.join("disk.map"))
+                .read_policy(policy)
+                .build()
+                .unwrap()
+        };
+
+        let mut body = open(ReadPolicy::Strict);
+        assert_eq!(
+            body.allocated_ranges().unwrap(),
+            vec![0..0x400, 0x600..0x800]
+        );
+        let mut buf = vec![0u8; 0x1000];
+        assert_eq!(
+            body.read_exact(&mut buf).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let mut body = open(ReadPolicy::BestEffort { fill: Vec::new() });
+        assert_eq!(
+            body.bad_ranges(),
+            [
+                SectorRange { start: 2, count: 1 },
+                SectorRange { start: 4, count: 4 },
+            ]
+        );
+        body.read_exact(&mut buf).unwrap();
+        assert!(buf[..0x400].iter().all(|&b| b == 0xaa));
+        assert!(buf[0x400..0x600].iter().all(|&b| b == 0));
+        assert!(buf[0x600..0x800].iter().all(|&b| b == 0xaa));
+        assert!(buf[0x800..].iter().all(|&b| b == 0));
+    }
+}
@@ -0,0 +1,585 @@
+//! **Physical block device reader**
+//!
+//! This module reads evidence straight from an attached drive (`/dev/sdb`,
+//! `/dev/disk2`, `\\.\PhysicalDrive1`, …).  Unlike regular files, devices
+//! report a zero length through `File::metadata()`, so the size and sector
+//! geometry are queried from the operating system:
+//!
+//! | Platform | Size                         | Logical / physical sector size                    |
+//! |----------|------------------------------|---------------------------------------------------|
+//! | Linux    | `BLKGETSIZE64`               | `BLKSSZGET` / `BLKPBSZGET`                        |
+//! | macOS    | `DKIOCGETBLOCKCOUNT` × size  | `DKIOCGETBLOCKSIZE` / `DKIOCGETPHYSICALBLOCKSIZE` |
+//! | Windows  | `IOCTL_DISK_GET_LENGTH_INFO` | `IOCTL_DISK_GET_DRIVE_GEOMETRY_EX` / `IOCTL_STORAGE_QUERY_PROPERTY` |
+//!
+//! The device is always opened **read-only** (and, on Windows, shared with
+//! other readers and writers so that mounted volumes can still be acquired).
+//! Reads are issued on logical-sector boundaries into a buffer aligned on the
+//! physical sector size, as required by raw disk and volume handles on
+//! Windows.
+//!
+//! On Windows, physical drives (`\\.\PhysicalDrive1`, or just
+//! `PhysicalDrive1`) and logical volumes (`\\.\C:`, `C:`,
+//! `\\?\Volume{…}`) are accepted. Volumes are read up to the end of the
+//! partition, past the last cluster of their file system.
+
+use crate::error::ExhumeBodyError;
+use crate::info::FormatInfo;
+use crate::read_at::ReadAt;
+use crate::retry::RetryPolicy;
+use log::debug;
+use std::cmp::min;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Sector size assumed when the platform cannot report one.
+const DEVICE_DEFAULT_SECTOR_SIZE: u32 = 512;
+
+// ---- Platform queries -------------------------------------------------------
+
+/// Geometry reported by the operating system for an open device.
+#[derive(Clone, Copy, Debug)]
+struct DeviceGeometry {
+    /// Device size in bytes.
+    size: u64,
+    /// Logical sector size in bytes.
+    logical_sector_size: u32,
+    /// Physical sector size in bytes.
+    physical_sector_size: u32,
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use super::DeviceGeometry;
+    use std::fs::File;
+    use std::io;
+    use std::os::unix::io::AsRawFd;
+
+    /// `BLKGETSIZE64` – device size in bytes (`_IOR(0x12, 114, size_t)`).
+    const BLKGETSIZE64: libc::Ioctl = libc::_IOR::<libc::size_t>(0x12, 114);
+
+    pub fn open(path: &str) -> io::Result<File> {
+        crate::integrity::open_read_only(path)
+    }
+
+    pub fn query(file: &File) -> io::Result<DeviceGeometry> {
+        let fd = file.as_raw_fd();
+        let mut size: u64 = 0;
+        let mut logical: libc::c_int = 0;
+        let mut physical: libc::c_uint = 0;
+
+        // SAFETY: each request writes a single integer of the given type.
+        unsafe {
+            if libc::ioctl(fd, BLKGETSIZE64, &mut size) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if libc::ioctl(fd, libc::BLKSSZGET, &mut logical) != 0 {
+                logical = 0;
+            }
+            if libc::ioctl(fd, libc::BLKPBSZGET, &mut physical) != 0 {
+                physical = 0;
+            }
+        }
+
+        Ok(DeviceGeometry {
+            size,
+            logical_sector_size: logical as u32,
+            physical_sector_size: physical,
+        })
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod sys {
+    use super::DeviceGeometry;
+    use std::fs::File;
+    use std::io;
+    use std::os::unix::io::AsRawFd;
+
+    /// `DKIOCGETBLOCKSIZE` – `_IOR('d', 24, uint32_t)`.
+    const DKIOCGETBLOCKSIZE: libc::c_ulong = 0x4004_6418;
+    /// `DKIOCGETBLOCKCOUNT` – `_IOR('d', 25, uint64_t)`.
+    const DKIOCGETBLOCKCOUNT: libc::c_ulong = 0x4008_6419;
+    /// `DKIOCGETPHYSICALBLOCKSIZE` – `_IOR('d', 77, uint32_t)`.
+    const DKIOCGETPHYSICALBLOCKSIZE: libc::c_ulong = 0x4004_644D;
+
+    pub fn open(path: &str) -> io::Result<File> {
+        crate::integrity::open_read_only(path)
+    }
+
+    pub fn query(file: &File) -> io::Result<DeviceGeometry> {
+        let fd = file.as_raw_fd();
+        let mut block_size: u32 = 0;
+        let mut block_count: u64 = 0;
+        let mut physical: u32 = 0;
+
+        // SAFETY: each request writes a single integer of the given type.
+        unsafe {
+            if libc::ioctl(fd, DKIOCGETBLOCKSIZE, &mut block_size) != 0
+                || libc::ioctl(fd, DKIOCGETBLOCKCOUNT, &mut block_count) != 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+            if libc::ioctl(fd, DKIOCGETPHYSICALBLOCKSIZE, &mut physical) != 0 {
+                physical = 0;
+            }
+        }
+
+        Ok(DeviceGeometry {
+            size: block_count * block_size as u64,
+            logical_sector_size: block_size,
+            physical_sector_size: physical,
+        })
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use super::DeviceGeometry;
+    use std::ffi::c_void;
+    use std::fs::{File, OpenOptions};
+    use std::io;
+    use std::mem::{size_of, zeroed};
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use std::ptr::{null, null_mut};
+    use windows_sys::Win32::Storage::FileSystem::{FILE_SHARE_READ, FILE_SHARE_WRITE};
+    use windows_sys::Win32::System::Ioctl::{
+        PropertyStandardQuery, StorageAccessAlignmentProperty, DISK_GEOMETRY, DISK_GEOMETRY_EX,
+        FSCTL_ALLOW_EXTENDED_DASD_IO, GET_LENGTH_INFORMATION, IOCTL_DISK_GET_DRIVE_GEOMETRY,
+        IOCTL_DISK_GET_DRIVE_GEOMETRY_EX, IOCTL_DISK_GET_LENGTH_INFO, IOCTL_STORAGE_QUERY_PROPERTY,
+        STORAGE_ACCESS_ALIGNMENT_DESCRIPTOR, STORAGE_PROPERTY_QUERY,
+    };
+    use windows_sys::Win32::System::IO::DeviceIoControl;
+
+    /// Issue `code` on `file` and return the fixed-size output structure.
+    ///
+    /// # Safety
+    ///
+    /// `O` must be the output structure documented for `code` (plain data).
+    unsafe fn device_io_control<O>(
+        file: &File,
+        code: u32,
+        input: Option<&STORAGE_PROPERTY_QUERY>,
+    ) -> io::Result<O> {
+        let mut out: O = zeroed();
+        let mut returned = 0u32;
+        let (in_ptr, in_len) = match input {
+            Some(query) => (
+                query as *const STORAGE_PROPERTY_QUERY as *const c_void,
+                size_of::<STORAGE_PROPERTY_QUERY>() as u32,
+            ),
+            None => (null(), 0),
+        };
+        let ok = DeviceIoControl(
+            file.as_raw_handle() as _,
+            code,
+            in_ptr,
+            in_len,
+            &mut out as *mut O as *mut c_void,
+            size_of::<O>() as u32,
+            &mut returned,
+            null_mut(),
+        );
+        if ok == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(out)
+    }
+
+    pub fn open(path: &str) -> io::Result<File> {
+        let path = super::windows_device_path(path).unwrap_or_else(|| path.to_string());
+        let file = OpenOptions::new()
+            .read(true)
+            .share_mode(FILE_SHARE_READ | FILE_SHARE_WRITE)
+            .open(&path)?;
+        if super::is_volume_path(&path) {
+            // Reads of a mounted volume stop at the end of its file system
+            // otherwise, leaving the slack up to the partition end unread.
+            // SAFETY: the control code takes no input and returns no output.
+            if let Err(e) =
+                unsafe { device_io_control::<()>(&file, FSCTL_ALLOW_EXTENDED_DASD_IO, None) }
+            {
+                log::debug!("Extended DASD I/O refused on {}: {}", path, e);
+            }
+        }
+        Ok(file)
+    }
+
+    pub fn query(file: &File) -> io::Result<DeviceGeometry> {
+        // SAFETY: every output type matches the IOCTL it is requested with.
+        let geometry = unsafe {
+            device_io_control::<DISK_GEOMETRY_EX>(file, IOCTL_DISK_GET_DRIVE_GEOMETRY_EX, None)
+        };
+        let size = match unsafe {
+            device_io_control::<GET_LENGTH_INFORMATION>(file, IOCTL_DISK_GET_LENGTH_INFO, None)
+        } {
+            Ok(length) => length.Length as u64,
+            // Only drives report a geometry with their size.
+            Err(e) => geometry.as_ref().map_err(|_| e)?.DiskSize as u64,
+        };
+        let logical = match geometry {
+            Ok(g) => g.Geometry.BytesPerSector,
+            Err(_) => unsafe {
+                device_io_control::<DISK_GEOMETRY>(file, IOCTL_DISK_GET_DRIVE_GEOMETRY, None)
+            }
+            .map_or(0, |g| g.BytesPerSector),
+        };
+
+        let query = STORAGE_PROPERTY_QUERY {
+            PropertyId: StorageAccessAlignmentProperty,
+            QueryType: PropertyStandardQuery,
+            AdditionalParameters: [0],
+        };
+        let physical = unsafe {
+            device_io_control::<STORAGE_ACCESS_ALIGNMENT_DESCRIPTOR>(
+                file,
+                IOCTL_STORAGE_QUERY_PROPERTY,
+                Some(&query),
+            )
+        }
+        .map_or(0, |d| d.BytesPerPhysicalSector);
+
+        Ok(DeviceGeometry {
+            size,
+            logical_sector_size: logical,
+            physical_sector_size: physical,
+        })
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod sys {
+    use super::DeviceGeometry;
+    use std::fs::File;
+    use std::io;
+
+    pub fn open(path: &str) -> io::Result<File> {
+        crate::integrity::open_read_only(path)
+    }
+
+    pub fn query(_file: &File) -> io::Result<DeviceGeometry> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "device geometry queries are not implemented on this platform",
+        ))
+    }
+}
+
+/// The device namespace path of a Windows drive or volume name:
+/// `PhysicalDrive1` and `C:` (or `C:\`) are completed to `\\.\PhysicalDrive1`
+/// and `\\.\C:`, and device paths lose their trailing backslash, which would
+/// open the root directory of a volume instead of the volume. `None` when
+/// `path` names neither.
+#[cfg(any(not(unix), test))]
+fn windows_device_path(path: &str) -> Option<String> {
+    let (prefix, name, explicit) = match path
+        .strip_prefix(r"\\.\")
+        .map(|name| (r"\\.\", name))
+        .or_else(|| path.strip_prefix(r"\\?\").map(|name| (r"\\?\", name)))
+    {
+        Some((prefix, name)) => (prefix, name, true),
+        None => (r"\\.\", path, false),
+    };
+    let name = name.trim_end_matches(['\\', '/']);
+    let is_letter = matches!(name.as_bytes(), [letter, b':'] if letter.is_ascii_alphabetic());
+    let is_drive = name.len() > 13
+        && name[..13].eq_ignore_ascii_case("PhysicalDrive")
+        && name[13..].bytes().all(|b| b.is_ascii_digit());
+    // Past an explicit prefix any name without a directory part is a device
+    // (`\\?\C:\case\disk.E01` is a file).
+    let is_named = explicit
+        && !name.is_empty()
+        && (!name.contains(['\\', '/']) || name.starts_with(r"GLOBALROOT\Device\"));
+    (is_letter || is_drive || is_named).then(|| format!("{}{}", prefix, name))
+}
+
+/// Whether the device namespace path `path` names a logical volume rather
+/// than a whole drive.
+#[cfg(any(windows, test))]
+fn is_volume_path(path: &str) -> bool {
+    let name = path.get(4..).unwrap_or_default();
+    matches!(name.as_bytes(), [letter, b':'] if letter.is_ascii_alphabetic())
+        || name.starts_with("Volume{")
+        || name.contains("HarddiskVolume")
+        || (name.contains(r"\Partition") && !name.ends_with(r"\Partition0"))
+}
+
+/// Whether `path` names a block or character device (or a Windows drive or
+/// volume, such as `\\.\PhysicalDrive0`, `PhysicalDrive0` or `C:`).
+pub fn is_device_path(path: &str) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        std::fs::metadata(path)
+            .map(|m| m.file_type().is_block_device() || m.file_type().is_char_device())
+            .unwrap_or(false)
+    }
+    #[cfg(not(unix))]
+    {
+        windows_device_path(path).is_some()
+    }
+}
+
+/// Whether `metadata` is that of a block device, whose length is reported
+/// as zero (see [`block_device_size`]).
+pub(crate) fn is_block_device(metadata: &std::fs::Metadata) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        metadata.file_type().is_block_device()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        false
+    }
+}
+
+/// Size of the block device open as `file`, as reported by the kernel.
+///
+/// Where the platform query is not implemented, the size is that of a seek
+/// to the end of the device; the cursor of `file` is restored.
+pub(crate) fn block_device_size(file: &File) -> io::Result<u64> {
+    match sys::query(file) {
+        Ok(geometry) => Ok(geometry.size),
+        Err(e) => {
+            debug!("Device geometry query failed ({}), seeking to the end", e);
+            let mut file = file;
+            let position = file.stream_position()?;
+            let size = file.seek(SeekFrom::End(0))?;
+            file.seek(SeekFrom::Start(position))?;
+            Ok(size)
+        }
+    }
+}
+
+// ---- Public device reader ---------------------------------------------------
+
+/// Read-only reader over a physical block device.
+///
+/// Implements [`Read`], [`Seek`], [`ReadAt`] and [`Clone`] so it can be used
+/// as a drop-in source of evidence bytes inside the [`Body`](crate::Body)
+/// abstraction.
+pub struct DEVICE {
+    /// Open (read-only) device handle.
+    file: File,
+    /// Original path (kept for display).
+    path: String,
+    /// Size and sector geometry reported by the operating system.
+    geometry: DeviceGeometry,
+    /// Virtual cursor position on the device.
+    position: u64,
+    /// How failed reads are retried.
+    retry: RetryPolicy,
+}
+
+impl DEVICE {
+    // ---- Construction -------------------------------------------------------
+
+    /// Open `file_path` read-only and query its size and sector sizes.
+    ///
+    /// When the platform query fails (e.g. `file_path` is a regular file),
+    /// the size falls back to seeking to the end of the handle and the sector
+    /// size to 512 bytes.
+    pub fn new(file_path: &str) -> Result<DEVICE, ExhumeBodyError> {
+        Self::new_impl(file_path).map_err(ExhumeBodyError::Device)
+    }
+
+    fn new_impl(file_path: &str) -> Result<DEVICE, String> {
+        let mut file = sys::open(file_path).map_err(|e| format!("Error opening device: {}", e))?;
+
+        let geometry = match sys::query(&file) {
+            Ok(geometry) => geometry,
+            Err(e) => {
+                debug!("Device geometry query failed ({}), seeking to the end", e);
+                let size = file
+                    .seek(SeekFrom::End(0))
+                    .map_err(|e| format!("Error querying device size: {}", e))?;
+                DeviceGeometry {
+                    size,
+                    logical_sector_size: 0,
+                    physical_sector_size: 0,
+                }
+            }
+        };
+
+        let logical = match geometry.logical_sector_size {
+            0 => DEVICE_DEFAULT_SECTOR_SIZE,
+            size => size,
+        };
+        let geometry = DeviceGeometry {
+            logical_sector_size: logical,
+            physical_sector_size: match geometry.physical_sector_size {
+                0 => logical,
+                size => size,
+            },
+            ..geometry
+        };
+        debug!("Device geometry: {:?}", geometry);
+
+        Ok(DEVICE {
+            file,
+            path: file_path.to_string(),
+            geometry,
+            position: 0,
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    // ---- Info helpers -------------------------------------------------------
+
+    /// Path, size and sector sizes of the device.
+    pub fn info(&self) -> FormatInfo {
+        FormatInfo::new("Block Device Information")
+            .field("Path", &self.path)
+            .field("Size", format!("{} bytes", self.geometry.size))
+            .field(
+                "Sector Size",
+                format!(
+                    "{} logical / {} physical",
+                    self.geometry.logical_sector_size, self.geometry.physical_sector_size
+                ),
+            )
+    }
+
+    /// Print the device geometry to the log.
+    pub fn print_info(&self) {
+        self.info().log();
+    }
+
+    /// Returns the logical sector size reported by the device.
+    pub fn get_sector_size(&self) -> u16 {
+        self.geometry.logical_sector_size as u16
+    }
+
+    /// Size of the device in bytes.
+    pub fn size(&self) -> u64 {
+        self.geometry.size
+    }
+
+    /// Returns the physical sector size reported by the device.
+    pub fn get_physical_sector_size(&self) -> u32 {
+        self.geometry.physical_sector_size
+    }
+
+    /// Retry failed reads under `policy`, e.g. for a drive with weak
+    /// sectors, see [`RetryPolicy`].
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry = policy;
+    }
+}
+
+// ---- Clone ------------------------------------------------------------------
+
+impl Clone for DEVICE {
+    fn clone(&self) -> Self {
+        Self {
+            file: self
+                .file
+                .try_clone()
+                .expect("failed to clone device handle"),
+            path: self.path.clone(),
+            geometry: self.geometry,
+            position: self.position,
+            retry: self.retry,
+        }
+    }
+}
+
+// ---- Read / ReadAt ----------------------------------------------------------
+
+impl Read for DEVICE {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.read_at(self.position, buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl ReadAt for DEVICE {
+    /// `pread` (`ReadFile` at an offset on Windows) on the shared handle.
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let size = self.geometry.size;
+        if buf.is_empty() || offset >= size {
+            return Ok(0);
+        }
+
+        // Widen the request to whole logical sectors and bounce through a
+        // temporary buffer, itself aligned in memory on the physical sector
+        // size for the volume handles Windows opens uncached.
+        let sector = self.geometry.logical_sector_size as u64;
+        let want = min(buf.len() as u64, size - offset);
+        let aligned_start = offset - offset % sector;
+        let aligned_end = min((offset + want).div_ceil(sector) * sector, size);
+
+        let len = (aligned_end - aligned_start) as usize;
+        let align = self
+            .geometry
+            .physical_sector_size
+            .max(self.geometry.logical_sector_size)
+            .next_power_of_two() as usize;
+        let mut storage = vec![0u8; len + align];
+        let pad = storage.as_ptr().align_offset(align);
+        let bounce = &mut storage[pad..pad + len];
+        self.retry
+            .run(|| self.file.read_exact_at(aligned_start, bounce))?;
+
+        let skip = (offset - aligned_start) as usize;
+        buf[..want as usize].copy_from_slice(&bounce[skip..skip + want as usize]);
+        Ok(want as usize)
+    }
+}
+
+// ---- Seek -------------------------------------------------------------------
+
+impl Seek for DEVICE {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let next = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.geometry.size.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Seek before start or overflow")
+        })?;
+
+        self.position = next;
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completes_windows_drive_and_volume_names() {
+        let device = |path| windows_device_path(path);
+        assert_eq!(device("PhysicalDrive2").unwrap(), r"\\.\PhysicalDrive2");
+        assert_eq!(
+            device(r"\\.\PhysicalDrive0").unwrap(),
+            r"\\.\PhysicalDrive0"
+        );
+        assert_eq!(device("e:").unwrap(), r"\\.\e:");
+        assert_eq!(device(r"C:\").unwrap(), r"\\.\C:");
+        assert_eq!(device(r"\\.\C:\").unwrap(), r"\\.\C:");
+        assert_eq!(
+            device(r"\\?\Volume{6a1c4d52-0000-0000-0000-100000000000}\").unwrap(),
+            r"\\?\Volume{6a1c4d52-0000-0000-0000-100000000000}"
+        );
+        assert!(device(r"C:\case\disk.E01").is_none());
+        assert!(device(r"\\?\C:\case\disk.E01").is_none());
+        assert!(device("PhysicalDrive").is_none());
+        assert!(device("disk.raw").is_none());
+
+        assert!(is_volume_path(r"\\.\C:"));
+        assert!(is_volume_path(r"\\?\GLOBALROOT\Device\HarddiskVolume3"));
+        assert!(is_volume_path(
+            r"\\?\GLOBALROOT\Device\Harddisk0\Partition2"
+        ));
+        assert!(!is_volume_path(
+            r"\\?\GLOBALROOT\Device\Harddisk0\Partition0"
+        ));
+        assert!(!is_volume_path(r"\\.\PhysicalDrive1"));
+    }
+}
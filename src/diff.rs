@@ -0,0 +1,156 @@
+//! Block-level comparison of two bodies, see [`compare`].
+//!
+//! ```no_run
+//! # use exhume_body::{compare, Body};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut original = Body::try_new("/evidence/disk.E01".to_string(), "auto")?;
+//! let mut reacquired = Body::try_new("/evidence/disk-2.E01".to_string(), "auto")?;
+//! let report = compare(&mut original, &mut reacquired, 64 * 1024)?;
+//! for range in &report.differences {
+//!     println!("0x{:x}..0x{:x} differs", range.start, range.end);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::metadata::{clip_ranges, merge_ranges};
+use crate::read_at::ReadAt;
+use crate::Body;
+use log::info;
+use serde::Serialize;
+use std::io;
+use std::ops::Range;
+
+/// Outcome of [`compare`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct DiffReport {
+    /// Granularity of the comparison, in bytes.
+    pub block_size: u64,
+    /// Size of the first body.
+    pub size_a: u64,
+    /// Size of the second body.
+    pub size_b: u64,
+    /// Bytes read from both bodies and compared.
+    pub bytes_compared: u64,
+    /// Bytes skipped as implicit zeros in both bodies.
+    pub bytes_skipped: u64,
+    /// Byte ranges of the blocks that differ, sorted and coalesced. Only the
+    /// bytes both bodies hold are compared; see
+    /// [`sizes_differ`](DiffReport::sizes_differ) for the rest.
+    pub differences: Vec<Range<u64>>,
+}
+
+impl DiffReport {
+    /// Whether both bodies have the same size and content.
+    pub fn is_identical(&self) -> bool {
+        !self.sizes_differ() && self.differences.is_empty()
+    }
+
+    /// Whether one body is longer than the other.
+    pub fn sizes_differ(&self) -> bool {
+        self.size_a != self.size_b
+    }
+
+    /// Total size of the differing blocks.
+    pub fn differing_bytes(&self) -> u64 {
+        self.differences.iter().map(|r| r.end - r.start).sum()
+    }
+}
+
+/// Compare `a` and `b` block by block over the bytes both hold, and report
+/// the blocks that differ.
+///
+/// Regions that are holes in the allocation maps of both bodies (see
+/// [`Body::allocated_ranges`]) read as zeros on both sides and are skipped,
+/// so comparing a VMDK snapshot with its parent only reads what either
+/// stores. Reads go through [`ReadAt`], leaving both cursors where they are.
+pub fn compare(a: &mut Body, b: &mut Body, block_size: usize) -> io::Result<DiffReport> {
+    if block_size == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "block size must not be zero",
+        ));
+    }
+    let block = block_size as u64;
+    let size_a = a.size()?;
+    let size_b = b.size()?;
+    let common = size_a.min(size_b);
+
+    // Blocks holding stored data on either side.
+    let mut allocated = a.allocated_ranges()?;
+    allocated.extend(b.allocated_ranges()?);
+    let allocated = merge_ranges(allocated);
+    let blocks = merge_ranges(
+        clip_ranges(&allocated, 0..common)
+            .map(|r| {
+                r.start / block * block..r.end.div_ceil(block).saturating_mul(block).min(common)
+            })
+            .collect(),
+    );
+    info!(
+        "Comparing {} and {} in {} byte blocks",
+        a.format_name(),
+        b.format_name(),
+        block
+    );
+
+    let mut report = DiffReport {
+        block_size: block,
+        size_a,
+        size_b,
+        ..DiffReport::default()
+    };
+    let mut buf_a = vec![0u8; block_size];
+    let mut buf_b = vec![0u8; block_size];
+    for range in blocks {
+        let mut offset = range.start;
+        while offset < range.end {
+            let len = (range.end - offset).min(block) as usize;
+            a.read_exact_at(offset, &mut buf_a[..len])?;
+            b.read_exact_at(offset, &mut buf_b[..len])?;
+            if buf_a[..len] != buf_b[..len] {
+                let end = offset + len as u64;
+                match report.differences.last_mut() {
+                    Some(last) if last.end == offset => last.end = end,
+                    _ => report.differences.push(offset..end),
+                }
+            }
+            report.bytes_compared += len as u64;
+            offset += len as u64;
+        }
+    }
+    report.bytes_skipped = common - report.bytes_compared;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn reports_differing_blocks() {
+        let dir = TempDir::new("diff");
+        let original: Vec<u8> = (0..64 * 1024u32).map(|i| (i % 241) as u8).collect();
+        let mut changed = original.clone();
+        changed[5000] ^= 0xff;
+        changed[9000] ^= 0xff;
+        changed[40000] ^= 0xff;
+        changed.extend_from_slice(&[0; 512]);
+        std::fs::write(dir.join("a.raw"), &original).unwrap();
+        std::fs::write(dir.join("b.raw"), &changed).unwrap();
+
+        let open = |name: &str| Body::try_new(dir.join(name).to_string_lossy().into(), "raw");
+        let mut a = open("a.raw").unwrap();
+        let mut b = open("b.raw").unwrap();
+        let report = compare(&mut a, &mut b, 4096).unwrap();
+        assert_eq!(report.differences, vec![4096..12288, 36864..40960]);
+        assert_eq!(report.differing_bytes(), 3 * 4096);
+        assert_eq!(report.bytes_compared, original.len() as u64);
+        assert!(report.sizes_differ());
+        assert!(!report.is_identical());
+
+        let mut same = open("a.raw").unwrap();
+        assert!(compare(&mut a, &mut same, 4096).unwrap().is_identical());
+    }
+}
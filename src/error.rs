@@ -0,0 +1,137 @@
+//! Error type returned by the `Body` and image constructors.
+
+use crate::aff4::Aff4Error;
+use std::fmt;
+use std::io;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ExhumeBodyError {
+    /// The requested format name is not supported.
+    #[error("Invalid format '{0}'. Supported formats are 'raw', 'ewf', 'vmdk', 'aff', 'aff4', 'vhd', 'vhdx', 'vdi', 'dmg', 'parallels', 'qcow', 'device', 'gzip', 'zstd', 'xz' or 'auto'.")]
+    UnknownFormat(String),
+    #[error("cannot open ewf evidence: {0}")]
+    Ewf(String),
+    #[error("cannot open vmdk evidence: {0}")]
+    Vmdk(String),
+    #[error("cannot open aff evidence: {0}")]
+    Aff(String),
+    #[error("cannot open aff4 evidence: {0}")]
+    Aff4(#[from] Aff4Error),
+    #[error("cannot open raw evidence: {0}")]
+    Raw(#[source] io::Error),
+    #[error("cannot open vhd evidence: {0}")]
+    Vhd(String),
+    #[error("cannot open vhdx evidence: {0}")]
+    Vhdx(String),
+    #[error("cannot open vdi evidence: {0}")]
+    Vdi(String),
+    #[error("cannot open dmg evidence: {0}")]
+    Dmg(String),
+    #[error("cannot open parallels evidence: {0}")]
+    Parallels(String),
+    #[error("cannot open qcow evidence: {0}")]
+    Qcow(String),
+    #[error("cannot open device evidence: {0}")]
+    Device(String),
+    #[error("cannot open gzip evidence: {0}")]
+    Gzip(String),
+    #[error("cannot open zstd evidence: {0}")]
+    Zstd(String),
+    #[error("cannot open xz evidence: {0}")]
+    Xz(String),
+    /// The evidence could not be opened by a registered format plugin, or
+    /// the format cannot be read from the given kind of source.
+    #[error("cannot open {format} evidence: {message}")]
+    Open {
+        format: &'static str,
+        message: String,
+    },
+    /// I/O error while positioning the body.
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+}
+
+impl ExhumeBodyError {
+    pub(crate) fn open(format: &'static str, message: impl fmt::Display) -> Self {
+        Self::Open {
+            format,
+            message: message.to_string(),
+        }
+    }
+
+    /// Short name of the format that failed to open, `None` for an unknown
+    /// format name or an I/O error.
+    pub fn format(&self) -> Option<&'static str> {
+        match self {
+            Self::Ewf(_) => Some("ewf"),
+            Self::Vmdk(_) => Some("vmdk"),
+            Self::Aff(_) => Some("aff"),
+            Self::Aff4(_) => Some("aff4"),
+            Self::Raw(_) => Some("raw"),
+            Self::Vhd(_) => Some("vhd"),
+            Self::Vhdx(_) => Some("vhdx"),
+            Self::Vdi(_) => Some("vdi"),
+            Self::Dmg(_) => Some("dmg"),
+            Self::Parallels(_) => Some("parallels"),
+            Self::Qcow(_) => Some("qcow"),
+            Self::Device(_) => Some("device"),
+            Self::Gzip(_) => Some("gzip"),
+            Self::Zstd(_) => Some("zstd"),
+            Self::Xz(_) => Some("xz"),
+            Self::Open { format, .. } => Some(format),
+            Self::UnknownFormat(_) | Self::Io(_) => None,
+        }
+    }
+
+    /// Why the evidence was rejected, without the `cannot open …` prefix.
+    pub(crate) fn reason(&self) -> String {
+        match self {
+            Self::Ewf(message)
+            | Self::Vmdk(message)
+            | Self::Aff(message)
+            | Self::Vhd(message)
+            | Self::Vhdx(message)
+            | Self::Vdi(message)
+            | Self::Dmg(message)
+            | Self::Parallels(message)
+            | Self::Qcow(message)
+            | Self::Device(message)
+            | Self::Gzip(message)
+            | Self::Zstd(message)
+            | Self::Xz(message)
+            | Self::Open { message, .. } => message.clone(),
+            Self::Aff4(e) => e.to_string(),
+            Self::Raw(e) => e.to_string(),
+            Self::UnknownFormat(_) | Self::Io(_) => self.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use crate::Body;
+    use std::error::Error;
+
+    #[test]
+    fn keeps_the_format_and_source() {
+        let missing = "/nonexistent/exhume_body_error.raw".to_string();
+        let Err(err) = Body::try_new(missing, "raw") else {
+            panic!("opened a missing file");
+        };
+        assert_eq!(err.format(), Some("raw"));
+        let source = err.source().unwrap().downcast_ref::<io::Error>().unwrap();
+        assert_eq!(source.kind(), io::ErrorKind::NotFound);
+
+        let dir = TempDir::new("error");
+        let path = dir.join("error.vhd");
+        std::fs::write(&path, [0u8; 16]).unwrap();
+        let Err(err) = Body::try_new(path.to_string_lossy().into(), "vhd") else {
+            panic!("opened a truncated VHD");
+        };
+        assert!(matches!(err, ExhumeBodyError::Vhd(_)));
+        assert_eq!(err.reason(), "File too small to be a VHD image");
+    }
+}
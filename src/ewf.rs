@@ -3,13 +3,36 @@
 //! This module provides all the data-structures and helper functions required to
 //! parse, inspect and stream data from a multi-segment **EWF / EnCase** forensic
 //! image (`.E01`, `.L01`, …).
-
+//!
+//! EWF2 segments (`.Ex01`, `.Lx01`) written by EnCase 7+ are supported as
+//! well: their section chain is walked backwards from the trailing `done` /
+//! `next` descriptor and merged into the same chunk map as EWF1 images.
+//!
+//! Password-protected E01 images open normally: EnCase only stores a password
+//! hash in the header and does not encrypt the media data. EWF2 images with
+//! encrypted sections are detected and rejected with an explicit error, as the
+//! EnCase key-wrapping scheme is not publicly documented.
+
+use crate::error::ExhumeBodyError;
+use crate::ewf_writer::{adler32, header_date, EwfCaseInfo};
+use crate::hash::{to_hex, HashAlgorithm};
+use crate::info::FormatInfo;
+use crate::metadata::{DiskGeometry, EvidenceFile, SectorRange};
+use crate::read_at::ReadAt;
+use crate::source::{ReadSeek, Source};
+use crate::stats::IoCounters;
+use crate::trace::{io_event, io_span};
+use crate::vfs::{FileSystem, StdFileSystem};
+use crate::vhdx::format_guid;
+use bzip2::read::BzDecoder;
 use flate2::read::ZlibDecoder;
-use log::{debug, error, info};
+use log::{debug, warn};
+use serde::Serialize;
 use std::collections::HashMap;
-use std::fs::File;
 use std::io::{self, Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, UNIX_EPOCH};
 
 /// Header located at the very beginning of every *segment* (E01, E02 …).
 ///
@@ -18,10 +41,18 @@ use std::path::{Path, PathBuf};
 /// preserved for integrity checks but never interpreted by the library.
 #[derive(Default, Clone)]
 struct EwfHeader {
-    /// Either `EVF` / `MVF` signature depending on the EWF **flavour**.
+    /// Either `EVF` / `MVF` (EWF1) or `EVF2` / `LEF2` (EWF2) signature
+    /// depending on the EWF **flavour**.
     _signature: [u8; 8],
     /// Logical position of the segment in the multi-part image (starts at `1`).
-    segment_number: u16,
+    segment_number: u32,
+    /// Major format version: `1` for E01/L01, `2` for Ex01/Lx01.
+    version: u8,
+    /// EWF2 only – compression method of the segment's chunks and sections
+    /// (`0` none, `1` deflate, `2` bzip2).
+    compression_method: u16,
+    /// EWF2 only – GUID shared by the segments of the image.
+    set_identifier: [u8; 16],
 }
 
 /// Generic *section descriptor* that precedes **every** section in the format
@@ -43,6 +74,58 @@ struct EwfSectionDescriptor {
     _checksum: u32,
 }
 
+// ---- EWF2 constants ---------------------------------------------------------
+const EWF_L01_SIGNATURE: [u8; 8] = [0x4d, 0x56, 0x46, 0x09, 0x0d, 0x0a, 0xff, 0x00];
+const EWF_E01_SIGNATURE: [u8; 8] = [0x45, 0x56, 0x46, 0x09, 0x0d, 0x0a, 0xff, 0x00];
+const EWF2_LX01_SIGNATURE: [u8; 8] = *b"LEF2\r\n\x81\x00";
+const EWF2_EX01_SIGNATURE: [u8; 8] = *b"EVF2\r\n\x81\x00";
+const EWF2_SECTION_DESCRIPTOR_SIZE: u64 = 64;
+const EWF2_SECTION_DEVICE_INFORMATION: u32 = 0x01;
+const EWF2_SECTION_CASE_DATA: u32 = 0x02;
+const EWF2_SECTION_SECTOR_TABLE: u32 = 0x04;
+const EWF2_SECTION_ERROR_TABLE: u32 = 0x05;
+const EWF2_SECTION_SESSION_TABLE: u32 = 0x06;
+/// Session entry flag marking an audio track.
+const EWF_SESSION_FLAG_AUDIO: u32 = 0x01;
+const EWF2_SECTION_MD5_HASH: u32 = 0x08;
+const EWF2_SECTION_SHA1_HASH: u32 = 0x09;
+const EWF2_SECTION_ENCRYPTION_KEYS: u32 = 0x0b;
+const EWF2_SECTION_SINGLE_FILES_DATA: u32 = 0x20;
+/// Size of the header preceding the data of an EWF1 `ltree` section: MD5 of
+/// the data, data size, checksum and padding.
+const EWF_LTREE_HEADER_SIZE: u64 = 48;
+/// Deepest directory nesting accepted in the single files tree.
+const EWF_LTREE_MAX_DEPTH: usize = 512;
+const EWF2_SECTION_FLAG_ENCRYPTED: u32 = 0x02;
+const EWF2_CHUNK_FLAG_COMPRESSED: u32 = 0x01;
+const EWF2_CHUNK_FLAG_HAS_CHECKSUM: u32 = 0x02;
+const EWF2_CHUNK_FLAG_PATTERN_FILL: u32 = 0x04;
+const EWF2_COMPRESSION_NONE: u16 = 0;
+const EWF2_COMPRESSION_DEFLATE: u16 = 1;
+const EWF2_COMPRESSION_BZIP2: u16 = 2;
+/// Chunks decompressed ahead per worker thread on sequential reads.
+const EWF_PREFETCH_PER_THREAD: usize = 8;
+/// Consecutive chunks a read must cross before read-ahead kicks in, so random
+/// access does not pay for chunks it never uses.
+const EWF_PREFETCH_AFTER: usize = 4;
+
+/// EWF2 *section descriptor*. Unlike EWF1, it is stored **after** the section
+/// data and points backwards to the previous descriptor of the segment.
+///
+/// Refer to the official specification <https://github.com/libyal/libewf/blob/main/documentation/Expert%20Witness%20Compression%20Format%202%20(EWF2).asciidoc#3-section-descriptor>
+#[derive(Clone)]
+struct Ewf2SectionDescriptor {
+    /// Numeric section type (device information, sector table, …).
+    section_type: u32,
+    /// Data flags (`0x01` has MD5, `0x02` encrypted).
+    data_flags: u32,
+    /// Offset **from the beginning of the segment** to the previous section
+    /// descriptor, `0` for the first section.
+    previous_offset: u64,
+    /// Size (in bytes) of the section data preceding this descriptor.
+    data_size: u64,
+}
+
 /// Compressed *header* section — contains acquisition metadata (case number,
 /// examiner name, hashes, …). The structure is kept opaque for the moment.
 #[derive(Default, Clone)]
@@ -63,7 +146,13 @@ struct EwfVolumeSection {
     /// Logical sector size in bytes (usually **512**).
     bytes_per_sector: u32,
     /// Overall amount of sectors in the original evidence.
-    total_sector_count: u32,
+    total_sector_count: u64,
+    /// CHS geometry of the device as cylinders, heads and sectors per track,
+    /// all zero when the acquisition did not record it (and always for EWF2).
+    chs: (u32, u32, u32),
+    /// GUID shared by the segments of the image, all zero when not recorded
+    /// (EWF-S01 images, EWF2 which stores it in the segment header).
+    set_identifier: [u8; 16],
 }
 
 /// Lightweight descriptor of a single *chunk*.
@@ -73,6 +162,13 @@ struct Chunk {
     compressed: bool,
     /// Absolute offset (within the segment) to the start of the chunk payload.
     data_offset: u64,
+    /// Stored size of the payload (EWF2 only). EWF1 chunks are delimited by
+    /// the offset of their successor instead.
+    data_size: Option<u64>,
+    /// 8-byte pattern repeated over the whole chunk (EWF2 pattern fill).
+    fill_pattern: Option<[u8; 8]>,
+    /// Whether an uncompressed payload is followed by its Adler-32.
+    checksum: bool,
     /// Chunk index **from the beginning of image**, not just its segment.
     chunk_number: usize,
 }
@@ -102,22 +198,155 @@ impl Default for ChunkCache {
     }
 }
 
+/// A digest of the media stored in an EWF section, see
+/// [`EWF::stored_digests`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct EwfDigest {
+    /// Section holding the digest: `hash` or `digest` (EWF1), `md5 hash` or
+    /// `sha1 hash` (EWF2).
+    pub section: &'static str,
+    pub algorithm: HashAlgorithm,
+    /// Lower-case hex digest.
+    pub value: String,
+}
+
+/// A session of an optical disc image, see [`EWF::sessions`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct EwfSession {
+    /// Sectors of the session, up to the start of the next one (or the end
+    /// of the media for the last).
+    pub sectors: SectorRange,
+    /// Whether the session starts with an audio track, whose sectors hold
+    /// CD-DA samples rather than file system data.
+    pub audio: bool,
+}
+
+/// A file or directory of a logical evidence file (L01, Lx01), from the
+/// single files tree, see [`EWF::file_entries`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct EwfFileEntry {
+    /// Path from the root of the evidence, `/`-separated, starting with `/`.
+    pub path: String,
+    pub is_directory: bool,
+    /// Logical size in bytes.
+    pub size: u64,
+    /// Timestamps in seconds since the Unix epoch, `None` when not recorded.
+    pub created: Option<u64>,
+    pub accessed: Option<u64>,
+    pub modified: Option<u64>,
+    /// Last change of the file system entry (MFT record, inode).
+    pub entry_modified: Option<u64>,
+    pub deleted: Option<u64>,
+    /// Lower-case hex digests of the content, when computed at acquisition.
+    pub md5: Option<String>,
+    pub sha1: Option<String>,
+}
+
+impl EwfFileEntry {
+    /// Parse the `entry` category of the single files tree: a count line,
+    /// the tab-separated value types, then every entry depth first as a line
+    /// starting with its number of sub entries followed by a line of values.
+    /// The first entry is the unnamed root.
+    fn parse_tree(text: &str) -> Result<Vec<Self>, String> {
+        let lines: Vec<&str> = text
+            .trim_start_matches('\u{FEFF}')
+            .split('\n')
+            .map(|l| l.trim_end_matches(['\r', '\0']))
+            .collect();
+        let start = lines
+            .iter()
+            .position(|l| l.trim() == "entry")
+            .ok_or("EWF single files tree has no entry category")?;
+        let types: Vec<&str> = lines
+            .get(start + 2)
+            .ok_or("EWF single files tree is truncated")?
+            .split('\t')
+            .collect();
+        let mut entries = Vec::new();
+        let mut index = start + 3;
+        Self::parse_entry(&lines, &mut index, &types, "", 0, &mut entries)?;
+        Ok(entries)
+    }
+
+    fn parse_entry(
+        lines: &[&str],
+        index: &mut usize,
+        types: &[&str],
+        parent: &str,
+        depth: usize,
+        entries: &mut Vec<Self>,
+    ) -> Result<(), String> {
+        if depth > EWF_LTREE_MAX_DEPTH {
+            return Err("EWF single files tree is nested too deeply".into());
+        }
+        let truncated = || "EWF single files tree is truncated".to_string();
+        while lines.get(*index).is_some_and(|l| l.trim().is_empty()) {
+            *index += 1;
+        }
+        let counts = lines.get(*index).ok_or_else(truncated)?;
+        let sub_entries: usize = counts
+            .split('\t')
+            .next()
+            .and_then(|n| n.trim().parse().ok())
+            .ok_or_else(|| format!("Invalid EWF single files entry header: {:?}", counts))?;
+        let values: HashMap<&str, &str> = types
+            .iter()
+            .copied()
+            .zip(lines.get(*index + 1).ok_or_else(truncated)?.split('\t'))
+            .collect();
+        *index += 2;
+
+        let value = |key: &str| values.get(key).map_or("", |v| v.trim());
+        let time = |key: &str| value(key).parse::<u64>().ok().filter(|&t| t != 0);
+        let digest = |key: &str| {
+            let digest = value(key).to_ascii_lowercase();
+            (!digest.is_empty() && digest.bytes().any(|b| b != b'0')).then_some(digest)
+        };
+        let path = if depth == 0 {
+            String::new()
+        } else {
+            format!("{}/{}", parent, value("n"))
+        };
+        if depth > 0 {
+            entries.push(Self {
+                path: path.clone(),
+                is_directory: value("p") == "1",
+                size: value("ls").parse().unwrap_or(0),
+                created: time("cr"),
+                accessed: time("ac"),
+                modified: time("wr"),
+                entry_modified: time("mo"),
+                deleted: time("dl"),
+                md5: digest("ha"),
+                sha1: digest("sha"),
+            });
+        }
+        for _ in 0..sub_entries {
+            Self::parse_entry(lines, index, types, &path, depth + 1, entries)?;
+        }
+        Ok(())
+    }
+}
+
 /// Public façade – implements the `Read` / `Seek` traits over an entire multi-
 /// segment EWF image just like a `File` on the original evidence.
 #[derive(Default)]
 pub struct EWF {
-    /// File descriptors for every segment (ordered).
-    segments: Vec<File>,
+    /// Files (or streams) of every segment, ordered.
+    segments: Arc<Vec<Source>>,
+    /// Paths of the segment files, empty when opened from streams.
+    segment_paths: Arc<Vec<PathBuf>>,
     /// Segment header (from the *last* parsed segment).
     ewf_header: EwfHeader,
-    /// All discovered section descriptors of the currently processed segment.
-    sections: Vec<EwfSectionDescriptor>,
     /// Global header (only one is expected per image even in multi-segment).
+    /// For EWF2 images this holds the *case data* section.
     header: EwfHeaderSection,
+    /// EWF2 *device information* section (serial, model, sector count, …).
+    device_information: HashMap<String, String>,
     /// Geometry / layout information.
     volume: EwfVolumeSection,
     /// Mapping `segment → [list of chunks]`.
-    chunks: HashMap<usize, Vec<Chunk>>,
+    chunks: Arc<HashMap<usize, Vec<Chunk>>>,
     /// Map `segment → offset` of the *sectors* section tail – helps delimitate
     /// the last compressed chunk.
     end_of_sectors: HashMap<usize, u64>,
@@ -127,32 +356,80 @@ pub struct EWF {
     chunk_count: usize,
     /// Last absolute position after a `seek()` (needed for relative seeks).
     position: u64,
+    /// Threads decompressing upcoming chunks during sequential reads (0 or 1
+    /// disables read-ahead).
+    decompression_threads: usize,
+    /// Check the Adler-32 of every chunk read, see
+    /// [`EWF::set_verify_checksums`].
+    verify_checksums: bool,
+    /// Chunks decompressed ahead of the reader, keyed by `(segment, chunk)`.
+    prefetched: HashMap<(usize, usize), Vec<u8>>,
+    /// Chunks crossed sequentially since the last seek.
+    sequential_chunks: usize,
+    /// Acquisition digests from the `hash` / `digest` sections (EWF1) or the
+    /// MD5 / SHA1 hash sections (EWF2).
+    hashes: Vec<(HashAlgorithm, String)>,
+    /// The digests of every hash section, see [`EWF::stored_digests`].
+    digests: Vec<EwfDigest>,
+    /// Sectors the acquisition tool could not read, from the `error2`
+    /// section (EWF1) or the error table (EWF2).
+    acquisition_errors: Arc<Vec<SectorRange>>,
+    /// `(first sector, flags)` of each session of an optical disc, from the
+    /// `session` section (EWF1) or session table (EWF2).
+    sessions: Vec<(u64, u32)>,
+    /// Files and directories of a logical evidence file, from the `ltree`
+    /// section (EWF1) or single files data section (EWF2).
+    file_entries: Arc<Vec<EwfFileEntry>>,
+    /// Read statistics, shared with the clones.
+    io: Arc<IoCounters>,
 }
 
 // ===== impl EwfVolumeSection =================================================
 impl EwfVolumeSection {
     /// Parse and inflate a *volume* section located at `offset` within `file`.
-    fn new(mut file: &File, offset: u64) -> Self {
-        let mut chunk_count = [0u8; 4];
-        let mut sector_per_chunk = [0u8; 4];
-        let mut bytes_per_sector = [0u8; 4];
-        let mut total_sector_count = [0u8; 4];
-
-        file.seek(SeekFrom::Start(offset + 4)).unwrap();
-        file.read_exact(&mut chunk_count).unwrap();
-        file.seek(SeekFrom::Start(offset + 8)).unwrap();
-        file.read_exact(&mut sector_per_chunk).unwrap();
-        file.seek(SeekFrom::Start(offset + 12)).unwrap();
-        file.read_exact(&mut bytes_per_sector).unwrap();
-        file.seek(SeekFrom::Start(offset + 16)).unwrap();
-        file.read_exact(&mut total_sector_count).unwrap();
-
-        Self {
-            chunk_count: u32::from_le_bytes(chunk_count),
-            sector_per_chunk: u32::from_le_bytes(sector_per_chunk),
-            bytes_per_sector: u32::from_le_bytes(bytes_per_sector),
-            total_sector_count: u32::from_le_bytes(total_sector_count),
+    fn new(file: &Source, offset: u64) -> Result<Self, String> {
+        let mut buf = [0u8; 80];
+        file.read_exact_at(offset, &mut buf)
+            .map_err(|e| format!("Could not read EWF volume section: {}", e))?;
+        let le = |i: usize| u32::from_le_bytes(buf[i..i + 4].try_into().unwrap());
+
+        let volume = Self {
+            chunk_count: le(4),
+            sector_per_chunk: le(8),
+            bytes_per_sector: le(12),
+            total_sector_count: le(16) as u64,
+            chs: (le(24), le(28), le(32)),
+            set_identifier: buf[64..80].try_into().unwrap(),
+        };
+        if volume.chunk_size() == 0 {
+            return Err("EWF volume section declares empty chunks".into());
         }
+        Ok(volume)
+    }
+
+    /// Build the geometry of an EWF2 image from its *device information*
+    /// (`ts`, `bp`) and *case data* (`sb`) key/value pairs.
+    fn from_ewf2(
+        device: &HashMap<String, String>,
+        case: &HashMap<String, String>,
+    ) -> Result<Self, String> {
+        let value = |map: &HashMap<String, String>, key: &str| {
+            map.get(key).and_then(|v| v.trim().parse::<u64>().ok())
+        };
+
+        let total_sector_count = value(device, "ts")
+            .ok_or("EWF2 device information does not declare a sector count.")?;
+        let bytes_per_sector = value(device, "bp").filter(|&v| v != 0).unwrap_or(512);
+        let sector_per_chunk = value(case, "sb").filter(|&v| v != 0).unwrap_or(64);
+
+        Ok(Self {
+            chunk_count: total_sector_count.div_ceil(sector_per_chunk) as u32,
+            sector_per_chunk: sector_per_chunk as u32,
+            bytes_per_sector: bytes_per_sector as u32,
+            total_sector_count,
+            chs: (0, 0, 0),
+            set_identifier: [0; 16],
+        })
     }
 
     /// Computed size (in **bytes**) of a single *chunk*.
@@ -174,33 +451,66 @@ impl EwfHeader {
     ///
     /// The function ensures the 8-byte signature matches either the *L01* or
     /// *E01* flavour and validates a few sanity bytes that must follow.
-    fn new(mut file: &File) -> Result<Self, String> {
-        const EWF_L01_SIGNATURE: [u8; 8] = [0x4d, 0x56, 0x46, 0x09, 0x0d, 0x0a, 0xff, 0x00];
-        const EWF_E01_SIGNATURE: [u8; 8] = [0x45, 0x56, 0x46, 0x09, 0x0d, 0x0a, 0xff, 0x00];
-
+    fn new(mut file: &Source) -> Result<Self, String> {
         let mut signature = [0u8; 8];
-        file.read_exact(&mut signature).unwrap();
+        file.read_exact(&mut signature)
+            .map_err(|_| "Invalid Signature.".to_string())?;
+
+        if signature == EWF2_EX01_SIGNATURE || signature == EWF2_LX01_SIGNATURE {
+            return Self::new_v2(file, signature);
+        }
 
         if signature != EWF_L01_SIGNATURE && signature != EWF_E01_SIGNATURE {
             return Err("Invalid Signature.".into());
         }
 
-        let mut one_byte = [0u8; 1];
-        file.read_exact(&mut one_byte).unwrap();
+        let mut fields = [0u8; 5];
+        file.read_exact(&mut fields)
+            .map_err(|_| "Invalid Header Fields.".to_string())?;
+        let one_byte = [fields[0]];
+        let segment_number = [fields[1], fields[2]];
+        let zero_field = [fields[3], fields[4]];
+
+        if one_byte[0] != 1 || zero_field != [0u8; 2] {
+            return Err("Invalid Header Fields.".into());
+        }
+
+        Ok(Self {
+            _signature: signature,
+            segment_number: u16::from_le_bytes(segment_number) as u32,
+            version: 1,
+            compression_method: EWF2_COMPRESSION_DEFLATE,
+            set_identifier: [0; 16],
+        })
+    }
 
-        let mut segment_number = [0u8; 2];
-        file.read_exact(&mut segment_number).unwrap();
+    /// Parse the remainder of a 32-byte EWF2 file header (the signature has
+    /// already been consumed).
+    fn new_v2(mut file: &Source, signature: [u8; 8]) -> Result<Self, String> {
+        let mut buf = [0u8; 24];
+        file.read_exact(&mut buf)
+            .map_err(|_| "Invalid Header Fields.".to_string())?;
 
-        let mut zero_field = [0u8; 2];
-        file.read_exact(&mut zero_field).unwrap();
+        let major_version = buf[0];
+        let compression_method = u16::from_le_bytes(buf[2..4].try_into().unwrap());
+        let segment_number = u32::from_le_bytes(buf[4..8].try_into().unwrap());
 
-        if one_byte[0] != 1 || zero_field != [0u8; 2] {
+        if major_version != 2 || segment_number == 0 {
             return Err("Invalid Header Fields.".into());
         }
+        if compression_method > EWF2_COMPRESSION_BZIP2 {
+            return Err(format!(
+                "Unsupported EWF2 compression method {}.",
+                compression_method
+            ));
+        }
 
         Ok(Self {
             _signature: signature,
-            segment_number: u16::from_le_bytes(segment_number),
+            segment_number,
+            version: 2,
+            compression_method,
+            set_identifier: buf[8..24].try_into().unwrap(),
         })
     }
 }
@@ -208,30 +518,50 @@ impl EwfHeader {
 // ===== impl EwfSectionDescriptor ============================================
 impl EwfSectionDescriptor {
     /// Parse a **section descriptor** present at `offset` in `file`.
-    fn new(mut file: &File, offset: u64) -> Self {
-        let mut section_type_def = [0u8; 16];
-        let mut next_section_offset = [0u8; 8];
-        let mut section_size = [0u8; 8];
-        let mut checksum = [0u8; 4];
-
-        file.seek(SeekFrom::Start(offset)).unwrap();
-        file.read_exact(&mut section_type_def).unwrap();
-        file.seek(SeekFrom::Start(offset + 16)).unwrap();
-        file.read_exact(&mut next_section_offset).unwrap();
-        file.seek(SeekFrom::Start(offset + 24)).unwrap();
-        file.read_exact(&mut section_size).unwrap();
-        file.seek(SeekFrom::Start(offset + 72)).unwrap();
-        file.read_exact(&mut checksum).unwrap();
-
-        let mut section_type = String::from_utf8(section_type_def.to_vec()).unwrap();
+    fn new(file: &Source, offset: u64) -> Result<Self, String> {
+        let mut buf = [0u8; 76];
+        file.read_exact_at(offset, &mut buf).map_err(|e| {
+            format!(
+                "Could not read EWF section descriptor at offset 0x{:x}: {}",
+                offset, e
+            )
+        })?;
+
+        let mut section_type = String::from_utf8_lossy(&buf[..16]).into_owned();
         section_type.retain(|c| c != '\0');
 
-        Self {
+        Ok(Self {
             section_type_def: section_type,
-            next_section_offset: u64::from_le_bytes(next_section_offset),
-            section_size: u64::from_le_bytes(section_size),
-            _checksum: u32::from_le_bytes(checksum),
+            next_section_offset: u64::from_le_bytes(buf[16..24].try_into().unwrap()),
+            section_size: u64::from_le_bytes(buf[24..32].try_into().unwrap()),
+            _checksum: u32::from_le_bytes(buf[72..76].try_into().unwrap()),
+        })
+    }
+}
+
+// ===== impl Ewf2SectionDescriptor ===========================================
+impl Ewf2SectionDescriptor {
+    /// Parse an EWF2 **section descriptor** present at `offset` in `file`.
+    fn new(mut file: &Source, offset: u64) -> Result<Self, String> {
+        let mut buf = [0u8; EWF2_SECTION_DESCRIPTOR_SIZE as usize];
+        file.seek(SeekFrom::Start(offset))
+            .and_then(|_| file.read_exact(&mut buf))
+            .map_err(|e| format!("Could not read EWF2 section descriptor: {}", e))?;
+
+        let descriptor_size = u32::from_le_bytes(buf[24..28].try_into().unwrap());
+        if descriptor_size as u64 != EWF2_SECTION_DESCRIPTOR_SIZE {
+            return Err(format!(
+                "Invalid EWF2 section descriptor at offset 0x{:x}",
+                offset
+            ));
         }
+
+        Ok(Self {
+            section_type: u32::from_le_bytes(buf[0..4].try_into().unwrap()),
+            data_flags: u32::from_le_bytes(buf[4..8].try_into().unwrap()),
+            previous_offset: u64::from_le_bytes(buf[8..16].try_into().unwrap()),
+            data_size: u64::from_le_bytes(buf[16..24].try_into().unwrap()),
+        })
     }
 }
 
@@ -240,9 +570,15 @@ impl EwfHeaderSection {
     /* ---------------------------------------------------------------- helpers */
 
     /// Decode raw bytes (`ASCII` first, then `UTF-16LE`) into a `String`.
+    ///
+    /// BOM-less UTF-16LE (as found in some EWF2 sections) is valid UTF-8 too,
+    /// so a NUL high byte on the first character forces the UTF-16 path.
     fn decode(raw: &[u8]) -> String {
-        if let Ok(txt) = String::from_utf8(raw.to_vec()) {
-            return txt;
+        let utf16_hint = raw.len() >= 2 && raw[0] != 0 && raw[1] == 0;
+        if !utf16_hint {
+            if let Ok(txt) = String::from_utf8(raw.to_vec()) {
+                return txt;
+            }
         }
         if raw.len().is_multiple_of(2) {
             let utf16: Vec<u16> = raw
@@ -305,12 +641,14 @@ impl EwfHeaderSection {
     }
 
     /// Inflate the compressed section and immediately parse its metadata.
-    fn new(file: &File, offset: u64, section: &EwfSectionDescriptor) -> Result<Self, String> {
-        let mut fd = file.try_clone().unwrap();
-        fd.seek(SeekFrom::Start(offset)).unwrap();
-
-        let mut compressed = vec![0; section.section_size as usize];
-        fd.read_exact(&mut compressed).unwrap();
+    fn new(file: &Source, offset: u64, section: &EwfSectionDescriptor) -> Result<Self, String> {
+        let size = section.section_size.saturating_sub(0x4c);
+        if offset.saturating_add(size) > file.len().map_err(|e| e.to_string())? {
+            return Err("EWF header section extends past the end of the segment".into());
+        }
+        let mut compressed = vec![0; size as usize];
+        file.read_exact_at(offset, &mut compressed)
+            .map_err(|e| format!("Could not read the header section: {}", e))?;
 
         let mut decoder = ZlibDecoder::new(&compressed[..]);
         let mut data = Vec::new();
@@ -339,83 +677,271 @@ impl EWF {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn new(file_path: &str) -> Result<Self, String> {
-        let fp = Path::new(file_path);
-        let files = find_files(fp)?;
+    pub fn new(file_path: &str) -> Result<Self, ExhumeBodyError> {
+        Self::open_in(&StdFileSystem, file_path)
+    }
+
+    /// Same as [`EWF::new`], opening the segments through `fs` instead of
+    /// the operating system.
+    pub fn open_in(fs: &dyn FileSystem, file_path: &str) -> Result<Self, ExhumeBodyError> {
+        Self::open_segments(fs, file_path).map_err(ExhumeBodyError::Ewf)
+    }
+
+    fn open_segments(fs: &dyn FileSystem, file_path: &str) -> Result<Self, String> {
+        let files = find_files(fs, Path::new(file_path))?;
 
         let mut ewf = Self::default();
 
         // Iterate over every segment and merge their structures.
-        for file in files {
-            let fd = File::open(file).map_err(|e| e.to_string())?;
+        for file in &files {
+            let fd = fs.open(file).map_err(|e| e.to_string())?;
             ewf = ewf.parse_segment(fd)?;
         }
+        ewf.segment_paths = Arc::new(files);
 
         Ok(ewf)
     }
-    /// Ref: https://github.com/libyal/libewf/blob/main/documentation/Expert%20Witness%20Compression%20Format%202%20(EWF2).asciidoc
-    /// Outputs a human-readable summary to the current `log` subscriber.
-    pub fn print_info(&self) {
-        info!("EWF File Information:");
-        info!("Number of Segments: {}", self.segments.len());
-
-        if !self.header.metadata.is_empty() {
-            info!("Acquisition Metadata:");
-
-            // canonical display order
-            let order = [
-                "c", "cn", "n", "en", "a", "e", "ex", "t", "nt", "av", "ov", "m", "u", "p", "r",
-            ];
-
-            /// Map identifier → human-readable label.
-            fn pretty(id: &str) -> &str {
-                match id {
-                    "c" | "cn" => "Case Number",
-                    "n" | "en" => "Evidence Number",
-                    "a" => "Description",
-                    "e" | "ex" => "Examiner",
-                    "t" | "nt" => "Notes",
-                    "av" => "Application Version",
-                    "ov" => "OS Version",
-                    "m" => "Acquisition Date",
-                    "u" => "System Date",
-                    "p" => "Password Hash",
-                    "r" => "Reserved",
-                    _ => id, // fall back to the caller’s borrow
+
+    /// Parse an image held in memory, in an archive or behind any other
+    /// transport: `readers` are its segments, in order (a single reader for
+    /// a one-segment image).
+    pub fn from_readers(readers: Vec<Box<dyn ReadSeek>>) -> Result<Self, ExhumeBodyError> {
+        Self::from_sources(readers.into_iter().map(Source::from_boxed).collect())
+    }
+
+    /// Same as [`EWF::from_readers`] with already opened [`Source`]s.
+    pub fn from_sources(sources: Vec<Source>) -> Result<Self, ExhumeBodyError> {
+        Self::parse_sources(sources).map_err(ExhumeBodyError::Ewf)
+    }
+
+    fn parse_sources(sources: Vec<Source>) -> Result<Self, String> {
+        if sources.is_empty() {
+            return Err("No EWF segment given".into());
+        }
+        let mut ewf = Self::default();
+        for source in sources {
+            (&source)
+                .seek(SeekFrom::Start(0))
+                .map_err(|e| e.to_string())?;
+            ewf = ewf.parse_segment(source)?;
+        }
+        let found: usize = ewf.chunks.values().map(Vec::len).sum();
+        if found < ewf.volume.chunk_count as usize {
+            return Err(format!(
+                "EWF image is incomplete ({} of {} chunks), every segment must be given",
+                found, ewf.volume.chunk_count
+            ));
+        }
+        Ok(ewf)
+    }
+
+    /// Decompress upcoming chunks on `threads` worker threads during large
+    /// sequential reads. `0` or `1` (the default) keeps decompression on the
+    /// reading thread.
+    pub fn set_decompression_threads(&mut self, threads: usize) {
+        self.decompression_threads = threads;
+        self.prefetched.clear();
+    }
+
+    /// Number of threads decompressing chunks (at least 1).
+    pub fn decompression_threads(&self) -> usize {
+        self.decompression_threads.max(1)
+    }
+
+    /// Compare every chunk read with its stored Adler-32 checksum (the one
+    /// following an uncompressed chunk, or the zlib trailer of a compressed
+    /// one). A mismatch fails the read with [`io::ErrorKind::InvalidData`].
+    /// Off by default.
+    pub fn set_verify_checksums(&mut self, verify: bool) {
+        self.verify_checksums = verify;
+        self.prefetched.clear();
+        self.cached_chunk.data.clear();
+    }
+
+    /// Whether reads check the chunk checksums.
+    pub fn verify_checksums(&self) -> bool {
+        self.verify_checksums
+    }
+
+    /// Read and check every chunk of the image, whatever
+    /// [`EWF::set_verify_checksums`] says, and return the sectors of the
+    /// chunks that are missing, do not inflate or fail their checksum,
+    /// sorted and merged. `progress` receives the chunks checked and the
+    /// chunk count.
+    pub fn verify_chunks(&self, mut progress: impl FnMut(u64, u64)) -> Vec<SectorRange> {
+        let total = self.chunks.values().map(Vec::len).sum::<usize>() as u64;
+        let sectors_per_chunk = self.volume.sector_per_chunk as u64;
+        let mut done = 0;
+        let mut bad: Vec<SectorRange> = Vec::new();
+        for segment in 1..=self.segments.len() {
+            let count = self.chunks.get(&segment).map_or(0, Vec::len);
+            for number in 0..count {
+                if let Err(e) = self.load_chunk(segment, number, true) {
+                    warn!("{}", e);
+                    let start =
+                        self.chunks[&segment][number].chunk_number as u64 * sectors_per_chunk;
+                    let count =
+                        sectors_per_chunk.min(self.volume.total_sector_count.saturating_sub(start));
+                    match bad.last_mut() {
+                        Some(last) if last.end() == start => last.count += count,
+                        _ => bad.push(SectorRange { start, count }),
+                    }
                 }
+                done += 1;
+                progress(done, total);
             }
+        }
+        bad
+    }
 
-            // first: well-known keys in a stable order
-            for k in order {
-                if let Some(v) = self.header.metadata.get(k) {
-                    info!("  {}: {}", pretty(k), v);
-                }
+    /// Ref: https://github.com/libyal/libewf/blob/main/documentation/Expert%20Witness%20Compression%20Format%202%20(EWF2).asciidoc
+    /// Format version, segments, device and acquisition information, volume
+    /// geometry, read errors, sessions and chunk counts.
+    pub fn info(&self) -> FormatInfo {
+        let mut info = FormatInfo::new("EWF File Information")
+            .field("Format Version", format!("EWF{}", self.ewf_header.version))
+            .field("Number of Segments", self.segments.len());
+        if self.ewf_header.version == 2 {
+            let method = match self.ewf_header.compression_method {
+                EWF2_COMPRESSION_NONE => "none",
+                EWF2_COMPRESSION_BZIP2 => "bzip2",
+                _ => "deflate",
+            };
+            info = info.field("Compression Method", method);
+        }
+        if self.is_password_protected() {
+            info = info.field(
+                "Password Protected",
+                "yes (access control only, data is not encrypted)",
+            );
+        }
+
+        let order = ["sn", "md", "lb", "ts", "bp", "hs", "dc", "dt"];
+
+        /// Map identifier → human-readable label.
+        fn pretty(id: &str) -> &str {
+            match id {
+                "sn" => "Serial Number",
+                "md" => "Model",
+                "lb" => "Label",
+                "ts" => "Total Sector Count",
+                "bp" => "Bytes Per Sector",
+                "hs" => "HPA Sector Count",
+                "dc" => "DCO Sector Count",
+                "dt" => "Drive Type",
+                _ => id,
             }
-            // then any non-standard fields
-            for (k, v) in &self.header.metadata {
-                if !order.contains(&k.as_str()) {
-                    info!("  {}: {}", pretty(k), v);
-                }
+        }
+
+        let mut device: Vec<(String, String)> = order
+            .iter()
+            .filter_map(|k| {
+                self.device_information
+                    .get(*k)
+                    .map(|v| (pretty(k).to_string(), v.clone()))
+            })
+            .collect();
+        for (k, v) in &self.device_information {
+            if !order.contains(&k.as_str()) {
+                device.push((pretty(k).to_string(), v.clone()));
             }
         }
-        info!("Volume Information:");
-        info!("  Chunk Count: {}", self.volume.chunk_count);
-        info!(
-            "  Sectors Per Chunk: {} ({} bytes)",
-            self.volume.sector_per_chunk,
-            self.volume.chunk_size()
-        );
-        info!("  Bytes Per Sector: {}", self.volume.bytes_per_sector);
-        info!("  Total Sector Count: {}", self.volume.total_sector_count);
 
-        info!("Chunk Information:");
-        for (segment_number, chunks) in &self.chunks {
-            info!("  Segment Number: {}", segment_number);
-            info!("  Number of Chunks: {}", chunks.len());
+        let mut volume = vec![
+            (
+                "Chunk Count".to_string(),
+                self.volume.chunk_count.to_string(),
+            ),
+            (
+                "Sectors Per Chunk".to_string(),
+                format!(
+                    "{} ({} bytes)",
+                    self.volume.sector_per_chunk,
+                    self.volume.chunk_size()
+                ),
+            ),
+            (
+                "Bytes Per Sector".to_string(),
+                self.volume.bytes_per_sector.to_string(),
+            ),
+            (
+                "Total Sector Count".to_string(),
+                self.volume.total_sector_count.to_string(),
+            ),
+        ];
+        if let Some(geometry) = self.geometry() {
+            volume.push((
+                "CHS Geometry".to_string(),
+                format!(
+                    "{}/{}/{}",
+                    geometry.cylinders, geometry.heads, geometry.sectors_per_track
+                ),
+            ));
+        }
+
+        let errors = self
+            .acquisition_errors
+            .iter()
+            .map(|range| {
+                (
+                    "Sectors".to_string(),
+                    format!(
+                        "{} - {} ({} sectors)",
+                        range.start,
+                        range.end() - 1,
+                        range.count
+                    ),
+                )
+            })
+            .collect();
+
+        let sessions = self
+            .sessions()
+            .iter()
+            .enumerate()
+            .map(|(i, session)| {
+                (
+                    format!("Session {}", i + 1),
+                    format!(
+                        "sectors {} - {} ({} sectors){}",
+                        session.sectors.start,
+                        session.sectors.end().saturating_sub(1),
+                        session.sectors.count,
+                        if session.audio { ", audio" } else { "" }
+                    ),
+                )
+            })
+            .collect();
+
+        let mut segments: Vec<_> = self.chunks.iter().collect();
+        segments.sort_by_key(|(number, _)| **number);
+        let chunks = segments
+            .into_iter()
+            .map(|(number, chunks)| {
+                (
+                    format!("Segment {}", number),
+                    format!("{} chunks", chunks.len()),
+                )
+            })
+            .collect();
+
+        info.section("Device Information", device)
+            .section("Acquisition Metadata", self.acquisition_info())
+            .section("Volume Information", volume)
+            .section("Acquisition Read Errors", errors)
+            .section("Optical Disc Sessions", sessions)
+            .section("Chunk Information", chunks)
+    }
+
+    /// Outputs [`EWF::info`] to the current `log` subscriber, followed by
+    /// every chunk at debug level.
+    pub fn print_info(&self) {
+        self.info().log();
+        for (segment_number, chunks) in self.chunks.iter() {
             for chunk in chunks {
                 debug!(
-                    "    Chunk Number: {} – Compressed: {} – Data Offset: 0x{:x}",
-                    chunk.chunk_number, chunk.compressed, chunk.data_offset
+                    "Segment {} Chunk Number: {} – Compressed: {} – Data Offset: 0x{:x}",
+                    segment_number, chunk.chunk_number, chunk.compressed, chunk.data_offset
                 );
             }
         }
@@ -427,30 +953,255 @@ impl EWF {
         self.volume.bytes_per_sector as u16
     }
 
+    /// Size of the acquired media in bytes.
+    pub fn size(&self) -> u64 {
+        self.volume.max_offset() as u64
+    }
+
+    /// Sector ranges the acquisition tool failed to read. EnCase stores
+    /// zeros (or whatever its error granularity filled in) for them, so their
+    /// content is not evidence.
+    pub fn acquisition_errors(&self) -> Vec<SectorRange> {
+        self.acquisition_errors.to_vec()
+    }
+
+    /// Sessions of an optical disc (CD, DVD, BD) image, in disc order. Empty
+    /// for other media.
+    pub fn sessions(&self) -> Vec<EwfSession> {
+        let total = self.volume.total_sector_count;
+        self.sessions
+            .iter()
+            .enumerate()
+            .map(|(i, &(start, flags))| {
+                let end = self.sessions.get(i + 1).map_or(total, |&(next, _)| next);
+                EwfSession {
+                    sectors: SectorRange {
+                        start,
+                        count: end.saturating_sub(start),
+                    },
+                    audio: flags & EWF_SESSION_FLAG_AUDIO != 0,
+                }
+            })
+            .collect()
+    }
+
+    /// CHS geometry recorded in the volume section, if the acquisition
+    /// software filled it in.
+    pub fn geometry(&self) -> Option<DiskGeometry> {
+        let (cylinders, heads, sectors) = self.volume.chs;
+        (cylinders != 0 && heads != 0 && sectors != 0).then_some(DiskGeometry {
+            cylinders: cylinders as u64,
+            heads,
+            sectors_per_track: sectors,
+        })
+    }
+
+    /// Major format version of the image: `1` for E01/L01, `2` for Ex01/Lx01.
+    #[inline]
+    pub fn format_version(&self) -> u8 {
+        self.ewf_header.version
+    }
+
+    /// Number of segment files making up the image.
+    pub fn segment_count(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Every segment file with its size, in order.
+    pub fn files(&self) -> io::Result<Vec<EvidenceFile>> {
+        let count = self.segments.len();
+        self.segments
+            .iter()
+            .enumerate()
+            .map(|(i, segment)| {
+                Ok(EvidenceFile {
+                    path: self
+                        .segment_paths
+                        .get(i)
+                        .map(|p| p.display().to_string())
+                        .unwrap_or_default(),
+                    size: segment.len()?,
+                    role: format!("EWF segment {}/{}", i + 1, count),
+                })
+            })
+            .collect()
+    }
+
+    /// Acquisition metadata from the header sections as label/value pairs:
+    /// well-known fields (with the drive model and serial number of EWF2
+    /// images and the set identifier) first in a stable order, then any
+    /// non-standard ones.
+    pub fn acquisition_info(&self) -> Vec<(String, String)> {
+        // canonical display order
+        let order = [
+            "c", "cn", "n", "en", "a", "nm", "e", "ex", "t", "nt", "md", "sn", "av", "ov", "os",
+            "m", "at", "u", "tt", "p", "r",
+        ];
+
+        /// Map identifier → human-readable label.
+        fn pretty(id: &str) -> &str {
+            match id {
+                "c" | "cn" => "Case Number",
+                "n" | "en" => "Evidence Number",
+                "a" | "nm" => "Description",
+                "e" | "ex" => "Examiner",
+                "t" | "nt" => "Notes",
+                "md" => "Model",
+                "sn" => "Serial Number",
+                "av" => "Application Version",
+                "ov" | "os" => "OS Version",
+                "m" | "at" => "Acquisition Date",
+                "u" | "tt" => "System Date",
+                "p" => "Password Hash",
+                "r" => "Reserved",
+                _ => id, // fall back to the caller’s borrow
+            }
+        }
+
+        let mut info: Vec<(String, String)> = order
+            .iter()
+            .filter_map(|k| {
+                self.header
+                    .metadata
+                    .get(*k)
+                    .or_else(|| match *k {
+                        "md" | "sn" => self.device_information.get(*k),
+                        _ => None,
+                    })
+                    .map(|v| (pretty(k).to_string(), v.clone()))
+            })
+            .collect();
+        let mut others: Vec<_> = self
+            .header
+            .metadata
+            .iter()
+            .filter(|(k, _)| !order.contains(&k.as_str()))
+            .map(|(k, v)| (pretty(k).to_string(), v.clone()))
+            .collect();
+        others.sort();
+        if let Some(guid) = self.set_identifier() {
+            info.push(("Set Identifier".to_string(), guid));
+        }
+        info.extend(others);
+        info
+    }
+
+    /// Case and acquisition information of the header (EWF1) or case data
+    /// and device information (EWF2) sections.
+    pub fn case_info(&self) -> EwfCaseInfo {
+        let value = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|k| self.header.metadata.get(*k))
+                .map_or(String::new(), |v| v.trim().to_string())
+        };
+        // EWF2 stores dates as Unix timestamps, EWF1 as `YYYY M D h m s`.
+        let date = |keys: &[&str]| {
+            let date = value(keys);
+            match date.parse::<u64>() {
+                Ok(secs) if self.ewf_header.version == 2 => {
+                    header_date(UNIX_EPOCH + Duration::from_secs(secs))
+                }
+                _ => date,
+            }
+        };
+        // EWF2 keeps the drive details in the device information section,
+        // EnCase 6+ EWF1 images in the header2 section.
+        let device = |key: &str| {
+            self.device_information
+                .get(key)
+                .or_else(|| self.header.metadata.get(key))
+                .map_or(String::new(), |v| v.trim().to_string())
+        };
+        EwfCaseInfo {
+            case_number: value(&["c", "cn"]),
+            evidence_number: value(&["n", "en"]),
+            description: value(&["a", "nm"]),
+            examiner: value(&["e", "ex"]),
+            notes: value(&["t", "nt"]),
+            acquired_at: date(&["m", "at"]),
+            system_date: date(&["u", "tt"]),
+            tool_version: value(&["av"]),
+            os_version: value(&["ov", "os"]),
+            model: device("md"),
+            serial_number: device("sn"),
+            guid: self.set_identifier().unwrap_or_default(),
+        }
+    }
+
+    /// GUID shared by the segments of the image (the *set identifier*), from
+    /// the volume section (EWF1) or the segment header (EWF2). `None` when
+    /// the acquisition tool did not record one.
+    pub fn set_identifier(&self) -> Option<String> {
+        let raw = match self.ewf_header.version {
+            2 => &self.ewf_header.set_identifier,
+            _ => &self.volume.set_identifier,
+        };
+        raw.iter().any(|&b| b != 0).then(|| format_guid(raw))
+    }
+
+    /// MD5 / SHA-1 digests of the media recorded by the acquisition tool.
+    pub fn stored_hashes(&self) -> &[(HashAlgorithm, String)] {
+        &self.hashes
+    }
+
+    /// Every digest of the media stored in the image, per section: EWF1
+    /// images usually record the MD5 in both the `hash` and the `digest`
+    /// sections, which should agree.
+    pub fn stored_digests(&self) -> &[EwfDigest] {
+        &self.digests
+    }
+
+    /// Files and directories stored in a logical evidence file (L01, Lx01),
+    /// in tree order, each directory before its content. Empty for media
+    /// images.
+    pub fn file_entries(&self) -> &[EwfFileEntry] {
+        &self.file_entries
+    }
+
+    /// Whether the acquisition header carries an EnCase password hash.
+    pub fn is_password_protected(&self) -> bool {
+        self.header
+            .metadata
+            .get("p")
+            .is_some_and(|hash| !hash.is_empty() && hash != "0")
+    }
+
+    /// Human-readable name of the format flavour, used by `Body`.
+    pub fn description(&self) -> &'static str {
+        if self.ewf_header.version == 2 {
+            "Expert Witness Compression Format 2 (EWF2)"
+        } else {
+            "Expert Witness Compression Format (EWF)"
+        }
+    }
+
     // ---------------------------------------------------------------------
     // Internal helpers (parsing & IO glue). Nothing below this point is part
     // of the public API.
     // ---------------------------------------------------------------------
 
     /// Parse the *table* section and return a flat list of chunks.
-    fn parse_table(&mut self, mut file: &File, offset: u64) -> Vec<Chunk> {
+    fn parse_table(&mut self, file: &Source, offset: u64) -> Result<Vec<Chunk>, String> {
         // Reference: §3.9.1 of the official spec.
-        let mut chunks = Vec::new();
-        let mut buffer = [0u8; 4];
-        file.seek(SeekFrom::Start(offset)).unwrap();
-        file.read_exact(&mut buffer).unwrap();
-        let entry_count = u32::from_le_bytes(buffer);
-
-        let mut buffer_u64 = [0u8; 8];
-        file.seek(SeekFrom::Start(offset + 8)).unwrap();
-        file.read_exact(&mut buffer_u64).unwrap();
-        let table_base_offset = u64::from_le_bytes(buffer_u64);
-
-        file.read_exact(&mut buffer).unwrap(); // checksum – ignored
+        let mut header = [0u8; 24];
+        file.read_exact_at(offset, &mut header)
+            .map_err(|e| format!("Could not read EWF table: {}", e))?;
+        let entry_count = u32::from_le_bytes(header[0..4].try_into().unwrap());
+        let table_base_offset = u64::from_le_bytes(header[8..16].try_into().unwrap());
+        // The checksum (bytes 16..20) is ignored.
+
+        let entries_len = entry_count as u64 * 4;
+        if (offset + 24).saturating_add(entries_len) > file.len().map_err(|e| e.to_string())? {
+            return Err(format!(
+                "EWF table at offset 0x{:x} declares {} entries past the end of the segment",
+                offset, entry_count
+            ));
+        }
+        let mut entry_buffer = vec![0u8; entries_len as usize];
+        file.read_exact_at(offset + 24, &mut entry_buffer)
+            .map_err(|e| format!("Could not read EWF table entries: {}", e))?;
 
-        file.seek(SeekFrom::Start(offset + 24)).unwrap();
-        let mut entry_buffer = vec![0u8; entry_count as usize * 4];
-        file.read_exact(&mut entry_buffer).unwrap();
+        let mut chunks = Vec::with_capacity(entry_count as usize);
 
         for i in 0..entry_count as usize {
             let start = i * 4;
@@ -462,20 +1213,375 @@ impl EWF {
             chunks.push(Chunk {
                 compressed: (tentry & msb) != 0,
                 data_offset: ptr,
+                data_size: None,
+                fill_pattern: None,
+                checksum: (tentry & msb) == 0,
                 chunk_number: self.chunk_count,
             });
 
             self.chunk_count = self
                 .chunk_count
                 .checked_add(1)
-                .expect("Chunk count overflow");
+                .ok_or("EWF chunk count overflow")?;
         }
-        chunks
+        Ok(chunks)
+    }
+
+    /// Parse an EWF2 *sector table* section and return its chunks.
+    fn parse_table_v2(&mut self, mut file: &Source, offset: u64) -> Result<Vec<Chunk>, String> {
+        // Reference: §5.4 of the EWF2 spec.
+        let mut header = [0u8; 32];
+        file.seek(SeekFrom::Start(offset))
+            .and_then(|_| file.read_exact(&mut header))
+            .map_err(|e| format!("Could not read EWF2 sector table: {}", e))?;
+        let first_chunk = u64::from_le_bytes(header[0..8].try_into().unwrap()) as usize;
+        let entry_count = u32::from_le_bytes(header[8..12].try_into().unwrap()) as usize;
+
+        if (offset + 32).saturating_add(entry_count as u64 * 16)
+            > file.len().map_err(|e| e.to_string())?
+        {
+            return Err(format!(
+                "EWF2 sector table at offset 0x{:x} declares {} entries past the end of the segment",
+                offset, entry_count
+            ));
+        }
+        let mut entry_buffer = vec![0u8; entry_count * 16];
+        file.read_exact(&mut entry_buffer)
+            .map_err(|e| format!("Could not read EWF2 sector table entries: {}", e))?;
+
+        let mut chunks = Vec::with_capacity(entry_count);
+        for (i, entry) in entry_buffer.chunks_exact(16).enumerate() {
+            let data_offset = u64::from_le_bytes(entry[0..8].try_into().unwrap());
+            let data_size = u32::from_le_bytes(entry[8..12].try_into().unwrap());
+            let flags = u32::from_le_bytes(entry[12..16].try_into().unwrap());
+
+            chunks.push(Chunk {
+                compressed: flags & EWF2_CHUNK_FLAG_COMPRESSED != 0,
+                data_offset,
+                data_size: Some(data_size as u64),
+                fill_pattern: (flags & EWF2_CHUNK_FLAG_PATTERN_FILL != 0)
+                    .then(|| entry[0..8].try_into().unwrap()),
+                checksum: flags & EWF2_CHUNK_FLAG_HAS_CHECKSUM != 0,
+                chunk_number: first_chunk + i,
+            });
+        }
+
+        self.chunk_count = self.chunk_count.max(first_chunk + entry_count);
+        Ok(chunks)
+    }
+
+    /// Read (and inflate when the segment is compressed) the data of an EWF2
+    /// section holding a metadata string.
+    fn read_section_v2(
+        &self,
+        mut file: &Source,
+        offset: u64,
+        size: u64,
+    ) -> Result<Vec<u8>, String> {
+        // `size` comes straight from the descriptor: check it against the
+        // segment before allocating.
+        let available = file
+            .len()
+            .map_err(|e| e.to_string())?
+            .saturating_sub(offset);
+        if size > available {
+            return Err(format!(
+                "EWF2 section at offset 0x{:x} declares {} bytes, past the end of the segment",
+                offset, size
+            ));
+        }
+        let mut raw = vec![0u8; size as usize];
+        file.seek(SeekFrom::Start(offset))
+            .and_then(|_| file.read_exact(&mut raw))
+            .map_err(|e| format!("Could not read EWF2 section data: {}", e))?;
+
+        if self.ewf_header.compression_method == EWF2_COMPRESSION_NONE {
+            return Ok(raw);
+        }
+
+        let mut data = Vec::new();
+        let decoded = if self.ewf_header.compression_method == EWF2_COMPRESSION_BZIP2 {
+            BzDecoder::new(&raw[..]).read_to_end(&mut data)
+        } else {
+            ZlibDecoder::new(&raw[..]).read_to_end(&mut data)
+        };
+        decoded.map_err(|_| "Could not decompress the EWF2 section".to_string())?;
+        Ok(data)
+    }
+
+    /// Fully parse a single EWF2 *segment*. Sections are chained backwards
+    /// from the trailing `done` / `next` descriptor, so the chain is collected
+    /// first and then processed in file order.
+    fn parse_segment_v2(mut self, file: Source) -> Result<Self, String> {
+        let file_size = file.len().map_err(|e| e.to_string())?;
+        let mut descriptor_offset = file_size
+            .checked_sub(EWF2_SECTION_DESCRIPTOR_SIZE)
+            .ok_or("EWF2 segment is too small.")?;
+
+        let mut descriptors = Vec::new();
+        loop {
+            let section = Ewf2SectionDescriptor::new(&file, descriptor_offset)?;
+            let previous_offset = section.previous_offset;
+            descriptors.push((descriptor_offset, section));
+
+            if previous_offset == 0 || previous_offset >= descriptor_offset {
+                break;
+            }
+            descriptor_offset = previous_offset;
+        }
+        descriptors.reverse();
+
+        let mut extracted_chunks = Vec::new();
+        for (descriptor_offset, section) in descriptors {
+            debug!(
+                "EWF2 section type 0x{:x} at 0x{:x} ({} bytes)",
+                section.section_type, descriptor_offset, section.data_size
+            );
+            if section.data_flags & EWF2_SECTION_FLAG_ENCRYPTED != 0
+                || section.section_type == EWF2_SECTION_ENCRYPTION_KEYS
+            {
+                return Err("Encrypted EWF2 images are not supported.".into());
+            }
+            let data_offset = descriptor_offset
+                .checked_sub(section.data_size)
+                .ok_or("Invalid EWF2 section data size.")?;
+
+            match section.section_type {
+                EWF2_SECTION_DEVICE_INFORMATION => {
+                    let data = self.read_section_v2(&file, data_offset, section.data_size)?;
+                    self.device_information = EwfHeaderSection::parse_metadata(&data);
+                }
+                EWF2_SECTION_CASE_DATA => {
+                    let data = self.read_section_v2(&file, data_offset, section.data_size)?;
+                    self.header
+                        .metadata
+                        .extend(EwfHeaderSection::parse_metadata(&data));
+                }
+                EWF2_SECTION_SECTOR_TABLE => {
+                    extracted_chunks.extend(self.parse_table_v2(&file, data_offset)?);
+                }
+                EWF2_SECTION_ERROR_TABLE => {
+                    // 4-byte count, padding and a checksum up to 32 bytes,
+                    // then 16-byte entries (u64 first sector, u32 count).
+                    self.parse_error_table(&file, data_offset, section.data_size, 32, 16);
+                }
+                EWF2_SECTION_SESSION_TABLE => {
+                    self.parse_session_table(&file, data_offset, section.data_size, 32);
+                }
+                EWF2_SECTION_MD5_HASH => {
+                    self.store_hash(&file, "md5 hash", HashAlgorithm::Md5, data_offset);
+                }
+                EWF2_SECTION_SHA1_HASH => {
+                    self.store_hash(&file, "sha1 hash", HashAlgorithm::Sha1, data_offset);
+                }
+                EWF2_SECTION_SINGLE_FILES_DATA => {
+                    let data = self.read_section_v2(&file, data_offset, section.data_size)?;
+                    self.store_file_entries(&data);
+                }
+                _ => {}
+            }
+        }
+        extracted_chunks.sort_by_key(|c| c.chunk_number);
+
+        // Device information and case data live in the first segment only.
+        if self.volume.chunk_size() == 0 && !self.device_information.is_empty() {
+            self.volume =
+                EwfVolumeSection::from_ewf2(&self.device_information, &self.header.metadata)?;
+        }
+
+        Arc::make_mut(&mut self.segments).push(file);
+        Arc::make_mut(&mut self.chunks)
+            .insert(self.ewf_header.segment_number as usize, extracted_chunks);
+        Ok(self)
+    }
+
+    /// Record the `algorithm` digest stored at `offset` by the `section`
+    /// section. All-zero digests (hash not computed by the acquisition tool)
+    /// are skipped. The first value seen for an algorithm becomes its stored
+    /// hash; every section keeps its own value in [`EWF::stored_digests`].
+    fn store_hash(
+        &mut self,
+        file: &Source,
+        section: &'static str,
+        algorithm: HashAlgorithm,
+        offset: u64,
+    ) {
+        let mut digest = vec![0u8; algorithm.digest_len()];
+        if let Err(e) = file.read_exact_at(offset, &mut digest) {
+            warn!("Could not read the stored {} hash: {}", algorithm, e);
+            return;
+        }
+        if digest.iter().all(|&b| b == 0)
+            || self
+                .digests
+                .iter()
+                .any(|d| d.section == section && d.algorithm == algorithm)
+        {
+            return;
+        }
+        let value = to_hex(&digest);
+        match self.hashes.iter().find(|(a, _)| *a == algorithm) {
+            None => self.hashes.push((algorithm, value.clone())),
+            Some((_, first)) if *first != value => warn!(
+                "EWF {} section {} ({}) differs from the one stored earlier ({})",
+                section, algorithm, value, first
+            ),
+            Some(_) => {}
+        }
+        self.digests.push(EwfDigest {
+            section,
+            algorithm,
+            value,
+        });
+    }
+
+    /// Parse the single files tree of a logical evidence file. A damaged
+    /// tree is reported and leaves the entry list empty, the media data
+    /// stays readable.
+    fn store_file_entries(&mut self, data: &[u8]) {
+        match EwfFileEntry::parse_tree(&EwfHeaderSection::decode(data)) {
+            Ok(entries) => self.file_entries = Arc::new(entries),
+            Err(e) => warn!("Could not parse the single files tree: {}", e),
+        }
+    }
+
+    /// Parse an EWF1 `ltree` section whose data starts at `offset`: a
+    /// 48-byte header holding the size of the UTF-16 tree that follows.
+    fn parse_ltree(&mut self, file: &Source, offset: u64, data_size: u64) {
+        let data_size = match segment_bounded(file, offset, data_size) {
+            Ok(size) => size,
+            Err(e) => {
+                warn!("Could not read the ltree section: {}", e);
+                return;
+            }
+        };
+        let mut header = [0u8; EWF_LTREE_HEADER_SIZE as usize];
+        if let Err(e) = file.read_exact_at(offset, &mut header) {
+            warn!("Could not read the ltree section: {}", e);
+            return;
+        }
+        let size = u64::from_le_bytes(header[16..24].try_into().unwrap())
+            .min(data_size.saturating_sub(EWF_LTREE_HEADER_SIZE));
+        let mut data = vec![0u8; size as usize];
+        if let Err(e) = file.read_exact_at(offset + EWF_LTREE_HEADER_SIZE, &mut data) {
+            warn!("Could not read the ltree section: {}", e);
+            return;
+        }
+        self.store_file_entries(&data);
+    }
+
+    /// Record the sector ranges of an `error2` section (EWF1) or error table
+    /// (EWF2) whose data starts at `offset`. Both hold an entry count
+    /// followed, after a header of `header_size` bytes, by entries of
+    /// `entry_size` bytes starting with the first sector and the sector count.
+    fn parse_error_table(
+        &mut self,
+        file: &Source,
+        offset: u64,
+        data_size: u64,
+        header_size: u64,
+        entry_size: u64,
+    ) {
+        let data_size = match segment_bounded(file, offset, data_size) {
+            Ok(size) => size,
+            Err(e) => {
+                warn!("Could not read the acquisition error table: {}", e);
+                return;
+            }
+        };
+        let mut count = [0u8; 4];
+        if let Err(e) = file.read_exact_at(offset, &mut count) {
+            warn!("Could not read the acquisition error table: {}", e);
+            return;
+        }
+        let available = data_size.saturating_sub(header_size) / entry_size;
+        let count = (u32::from_le_bytes(count) as u64).min(available);
+        let mut entries = vec![0u8; (count * entry_size) as usize];
+        if let Err(e) = file.read_exact_at(offset + header_size, &mut entries) {
+            warn!("Could not read the acquisition error table: {}", e);
+            return;
+        }
+        for entry in entries.chunks_exact(entry_size as usize) {
+            let range = if entry_size == 8 {
+                SectorRange {
+                    start: u32::from_le_bytes(entry[..4].try_into().unwrap()) as u64,
+                    count: u32::from_le_bytes(entry[4..8].try_into().unwrap()) as u64,
+                }
+            } else {
+                SectorRange {
+                    start: u64::from_le_bytes(entry[..8].try_into().unwrap()),
+                    count: u32::from_le_bytes(entry[8..12].try_into().unwrap()) as u64,
+                }
+            };
+            if range.count != 0 && !self.acquisition_errors.contains(&range) {
+                Arc::make_mut(&mut self.acquisition_errors).push(range);
+            }
+        }
+        Arc::make_mut(&mut self.acquisition_errors).sort();
+    }
+
+    /// Record the sessions of a `session` section (EWF1) or session table
+    /// (EWF2) whose data starts at `offset`. Both hold an entry count
+    /// followed, after a header of `header_size` bytes, by 32-byte entries:
+    /// flags then a u32 first sector in EWF1, a u64 first sector then flags
+    /// in EWF2.
+    fn parse_session_table(
+        &mut self,
+        file: &Source,
+        offset: u64,
+        data_size: u64,
+        header_size: u64,
+    ) {
+        let data_size = match segment_bounded(file, offset, data_size) {
+            Ok(size) => size,
+            Err(e) => {
+                warn!("Could not read the session table: {}", e);
+                return;
+            }
+        };
+        let mut count = [0u8; 4];
+        if let Err(e) = file.read_exact_at(offset, &mut count) {
+            warn!("Could not read the session table: {}", e);
+            return;
+        }
+        let available = data_size.saturating_sub(header_size) / 32;
+        let count = (u32::from_le_bytes(count) as u64).min(available);
+        let mut entries = vec![0u8; (count * 32) as usize];
+        if let Err(e) = file.read_exact_at(offset + header_size, &mut entries) {
+            warn!("Could not read the session table: {}", e);
+            return;
+        }
+        let le32 =
+            |entry: &[u8], at: usize| u32::from_le_bytes(entry[at..at + 4].try_into().unwrap());
+        self.sessions = entries
+            .chunks_exact(32)
+            .map(|entry| match self.ewf_header.version {
+                2 => (
+                    u64::from_le_bytes(entry[..8].try_into().unwrap()),
+                    le32(entry, 8),
+                ),
+                _ => (le32(entry, 4) as u64, le32(entry, 0)),
+            })
+            .collect();
+        self.sessions.sort_by_key(|&(start, _)| start);
     }
 
     /// Fully parse a single *segment* and merge its metadata into `self`.
-    fn parse_segment(mut self, file: File) -> Result<Self, String> {
-        self.ewf_header = EwfHeader::new(&file)?;
+    fn parse_segment(mut self, file: Source) -> Result<Self, String> {
+        let previous = std::mem::replace(&mut self.ewf_header, EwfHeader::new(&file)?);
+        if self.ewf_header.version == 2 {
+            if !self.segments.is_empty()
+                && previous.set_identifier != self.ewf_header.set_identifier
+            {
+                warn!(
+                    "EWF2 segment {} has set identifier {}, unlike the previous segments ({}): it may belong to another image",
+                    self.ewf_header.segment_number,
+                    format_guid(&self.ewf_header.set_identifier),
+                    format_guid(&previous.set_identifier)
+                );
+            }
+            return self.parse_segment_v2(file);
+        }
 
         // Position ourselves right *after* the header (13 bytes).
         let mut current_offset = 13u64;
@@ -483,18 +1589,17 @@ impl EWF {
         let mut extracted_chunks = Vec::new();
 
         loop {
-            let section = EwfSectionDescriptor::new(&file, current_offset);
+            let section = EwfSectionDescriptor::new(&file, current_offset)?;
             let section_offset = section.next_section_offset;
             let section_size = section.section_size;
             let section_type = section.section_type_def.clone();
-            self.sections.push(section);
 
             match section_type.as_str() {
                 "header" | "header2" => {
                     let h = EwfHeaderSection::new(
                         &file,
                         current_offset + ewf_section_descriptor_size,
-                        self.sections.last().unwrap(),
+                        &section,
                     )?;
                     if self.header._data.is_empty() {
                         self.header = h;
@@ -505,13 +1610,57 @@ impl EWF {
                 }
                 "disk" | "volume" => {
                     self.volume =
-                        EwfVolumeSection::new(&file, current_offset + ewf_section_descriptor_size);
+                        EwfVolumeSection::new(&file, current_offset + ewf_section_descriptor_size)?;
                 }
                 "table" => {
                     extracted_chunks.extend(
-                        self.parse_table(&file, current_offset + ewf_section_descriptor_size),
+                        self.parse_table(&file, current_offset + ewf_section_descriptor_size)?,
+                    );
+                }
+                "error2" => {
+                    // 4-byte count, 512 bytes of padding and a checksum, then
+                    // (first sector, count) u32 pairs.
+                    self.parse_error_table(
+                        &file,
+                        current_offset + ewf_section_descriptor_size,
+                        section_size.saturating_sub(ewf_section_descriptor_size),
+                        520,
+                        8,
+                    );
+                }
+                "session" => {
+                    // 4-byte count, 28 bytes of padding and a checksum.
+                    self.parse_session_table(
+                        &file,
+                        current_offset + ewf_section_descriptor_size,
+                        section_size.saturating_sub(ewf_section_descriptor_size),
+                        36,
                     );
                 }
+                "ltree" => {
+                    self.parse_ltree(
+                        &file,
+                        current_offset + ewf_section_descriptor_size,
+                        section_size.saturating_sub(ewf_section_descriptor_size),
+                    );
+                }
+                "hash" => {
+                    let data_offset = current_offset + ewf_section_descriptor_size;
+                    self.store_hash(&file, "hash", HashAlgorithm::Md5, data_offset);
+                }
+                "digest" => {
+                    // MD5, SHA1 and 40 bytes of padding, then an Adler-32
+                    // checksum of those 76 bytes.
+                    let data_offset = current_offset + ewf_section_descriptor_size;
+                    let mut data = [0u8; 80];
+                    if file.read_exact_at(data_offset, &mut data).is_ok()
+                        && adler32(&data[..76]).to_le_bytes() != data[76..]
+                    {
+                        warn!("EWF digest section checksum mismatch, the stored digests may be damaged");
+                    }
+                    self.store_hash(&file, "digest", HashAlgorithm::Md5, data_offset);
+                    self.store_hash(&file, "digest", HashAlgorithm::Sha1, data_offset + 16);
+                }
                 "sectors" => {
                     self.end_of_sectors.insert(
                         self.ewf_header.segment_number as usize,
@@ -524,68 +1673,226 @@ impl EWF {
             if current_offset == section_offset || section_type == "done" {
                 break;
             }
+            if section_offset < current_offset {
+                return Err(format!(
+                    "EWF section at offset 0x{:x} points back to offset 0x{:x}",
+                    current_offset, section_offset
+                ));
+            }
             current_offset = section_offset;
         }
 
-        self.segments.push(file);
-        self.chunks
+        Arc::make_mut(&mut self.segments).push(file);
+        Arc::make_mut(&mut self.chunks)
             .insert(self.ewf_header.segment_number as usize, extracted_chunks);
         Ok(self)
     }
 
     /// Read and *optionally* inflate the `chunk_number` of `segment`.
-    fn read_chunk(&self, segment: usize, chunk_number: usize) -> Vec<u8> {
+    ///
+    /// Malformed tables and chunks that fail to inflate are reported as
+    /// [`io::ErrorKind::InvalidData`].
+    fn read_chunk(&self, segment: usize, chunk_number: usize) -> io::Result<Vec<u8>> {
+        self.load_chunk(segment, chunk_number, self.verify_checksums)
+    }
+
+    /// [`EWF::read_chunk`], checking the chunk checksum when `verify` is
+    /// set.
+    fn load_chunk(&self, segment: usize, chunk_number: usize, verify: bool) -> io::Result<Vec<u8>> {
+        let _span = io_span!("ewf.chunk", segment, chunk = chunk_number);
         debug!(
             "Reading chunk number {} (segment {})",
             chunk_number, segment
         );
+        let corrupt = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let check = |stored: u32, data: &[u8]| {
+            let computed = adler32(data);
+            if stored == computed {
+                Ok(())
+            } else {
+                Err(corrupt(format!(
+                    "EWF chunk {} of segment {} fails its Adler-32 checksum (stored 0x{:08x}, computed 0x{:08x})",
+                    chunk_number, segment, stored, computed
+                )))
+            }
+        };
 
-        if chunk_number >= self.chunks[&segment].len() {
-            error!(
+        let (Some(chunks), Some(file)) = (
+            self.chunks.get(&segment),
+            segment.checked_sub(1).and_then(|i| self.segments.get(i)),
+        ) else {
+            return Err(corrupt(format!("EWF segment {} is missing", segment)));
+        };
+        let Some(chunk) = chunks.get(chunk_number) else {
+            return Err(corrupt(format!(
                 "Could not read chunk number {} in segment {}",
                 chunk_number, segment
-            );
-            std::process::exit(1);
-        }
-
-        let chunk = &self.chunks[&segment][chunk_number];
+            )));
+        };
         let start_offset = chunk.data_offset;
+        let chunk_size = self.volume.chunk_size();
 
-        let mut file = self.segments[segment - 1].try_clone().unwrap();
-        file.seek(SeekFrom::Start(start_offset)).unwrap();
+        if let Some(pattern) = chunk.fill_pattern {
+            return Ok(pattern.iter().copied().cycle().take(chunk_size).collect());
+        }
+
+        if !chunk.compressed && verify && chunk.checksum {
+            // The payload is the stored size minus the trailing checksum; a
+            // full EWF1 chunk is followed directly by its own.
+            let stored = match chunk.data_size {
+                Some(size) => size,
+                None if chunk_number + 1 == chunks.len() => self
+                    .end_of_sectors
+                    .get(&segment)
+                    .map_or(chunk_size as u64 + 4, |end| {
+                        end.saturating_sub(start_offset)
+                    }),
+                None => chunk_size as u64 + 4,
+            };
+            let len = stored
+                .checked_sub(4)
+                .filter(|&len| len <= chunk_size as u64)
+                .ok_or_else(|| {
+                    corrupt(format!(
+                        "EWF chunk {} of segment {} has an invalid size",
+                        chunk_number, segment
+                    ))
+                })? as usize;
+            let mut data = vec![0u8; len + 4];
+            file.read_exact_at(start_offset, &mut data)?;
+            let stored = u32::from_le_bytes(data[len..].try_into().unwrap());
+            check(stored, &data[..len])?;
+            data.resize(len, 0);
+            data.resize(chunk_size, 0);
+            return Ok(data);
+        }
 
         if !chunk.compressed {
-            let mut data = vec![0u8; self.volume.chunk_size()];
-            file.read_exact(&mut data).unwrap();
-            return data;
+            // EWF2 may store a short trailing chunk (and appends a checksum to
+            // full ones), so never read past the declared size. A short last
+            // EWF1 chunk is bounded by the end of its sectors section.
+            let stored = match chunk.data_size {
+                Some(size) => (size as usize).min(chunk_size),
+                None if chunk_number + 1 == chunks.len() => {
+                    self.end_of_sectors.get(&segment).map_or(chunk_size, |end| {
+                        (end.saturating_sub(start_offset) as usize).min(chunk_size)
+                    })
+                }
+                None => chunk_size,
+            };
+            let mut data = vec![0u8; chunk_size];
+            file.read_exact_at(start_offset, &mut data[..stored])?;
+            return Ok(data);
         }
 
         // Compressed chunk – compute its length first (end offset varies).
-        let end_offset = if chunk.data_offset == self.chunks[&segment].last().unwrap().data_offset {
-            self.end_of_sectors[&segment]
-        } else {
-            self.chunks[&segment][chunk_number + 1].data_offset
+        let end_offset = match chunk.data_size {
+            Some(size) => start_offset.saturating_add(size),
+            None if chunk_number + 1 == chunks.len() => {
+                *self.end_of_sectors.get(&segment).ok_or_else(|| {
+                    corrupt(format!("EWF segment {} has no sectors section", segment))
+                })?
+            }
+            None => chunks[chunk_number + 1].data_offset,
         };
-        let mut compressed_data = vec![0u8; (end_offset - start_offset) as usize];
-        file.read_exact(&mut compressed_data).unwrap();
+        // Deflate and bzip2 never expand a chunk much; anything larger is a
+        // damaged offset, not data worth allocating for.
+        let compressed_len = end_offset
+            .checked_sub(start_offset)
+            .filter(|&len| len <= 2 * chunk_size as u64 + 1024)
+            .ok_or_else(|| {
+                corrupt(format!(
+                    "EWF chunk {} of segment {} has an invalid size",
+                    chunk_number, segment
+                ))
+            })?;
+        let mut compressed_data = vec![0u8; compressed_len as usize];
+        file.read_exact_at(start_offset, &mut compressed_data)?;
+        let _inflate = io_span!(
+            "ewf.inflate",
+            compressed = compressed_len,
+            size = chunk_size
+        );
+        let started = Instant::now();
 
+        let inflate_error = |e: io::Error| {
+            corrupt(format!(
+                "EWF chunk {} of segment {} is corrupt: {}",
+                chunk_number, segment, e
+            ))
+        };
+        let mut data = Vec::with_capacity(chunk_size);
+        if self.ewf_header.compression_method == EWF2_COMPRESSION_BZIP2 {
+            // A bzip2 stream carries its own CRC, checked by the decoder
+            // whenever it reaches the end of the stream.
+            let mut decoder = BzDecoder::new(&compressed_data[..]);
+            (&mut decoder)
+                .take(chunk_size as u64)
+                .read_to_end(&mut data)
+                .map_err(inflate_error)?;
+            if verify && decoder.read(&mut [0u8; 1]).map_err(inflate_error)? != 0 {
+                return Err(corrupt(format!(
+                    "EWF chunk {} of segment {} decompresses past the chunk size",
+                    chunk_number, segment
+                )));
+            }
+            self.io.decompressed(started.elapsed());
+            data.resize(chunk_size, 0);
+            return Ok(data);
+        }
         let mut decoder = ZlibDecoder::new(&compressed_data[..]);
-        let mut data = Vec::new();
-        decoder.read_to_end(&mut data).unwrap();
-        data
+        (&mut decoder)
+            .take(chunk_size as u64)
+            .read_to_end(&mut data)
+            .map_err(inflate_error)?;
+        if verify {
+            // Run the stream to its end so that the Adler-32 trailer is
+            // consumed, then check it against the inflated data.
+            if decoder.read(&mut [0u8; 1]).map_err(inflate_error)? != 0 {
+                return Err(corrupt(format!(
+                    "EWF chunk {} of segment {} inflates past the chunk size",
+                    chunk_number, segment
+                )));
+            }
+            let end = decoder.total_in() as usize;
+            let trailer = end
+                .checked_sub(4)
+                .and_then(|start| compressed_data.get(start..end))
+                .ok_or_else(|| {
+                    corrupt(format!(
+                        "EWF chunk {} of segment {} has no zlib trailer",
+                        chunk_number, segment
+                    ))
+                })?;
+            check(u32::from_be_bytes(trailer.try_into().unwrap()), &data)?;
+        }
+        self.io.decompressed(started.elapsed());
+        // The last chunk of the media may be shorter than the others.
+        data.resize(chunk_size, 0);
+        Ok(data)
     }
 
     /// Copy `buf.len()` bytes from the image into `buf`, starting at the
     /// *current* offset (tracked by `self.cached_chunk`). Returns the amount of
     /// bytes actually copied (0 on EOF).
-    fn ewf_read(&mut self, buf: &mut [u8]) -> usize {
+    ///
+    /// A chunk that cannot be read ends the read early when some bytes were
+    /// already copied, so that the next call reports its error.
+    fn ewf_read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         let mut total_bytes_read = 0;
-        let mut remaining = buf.len();
+        let mut remaining = buf.len().min(
+            self.volume
+                .max_offset()
+                .saturating_sub(self.current_offset()),
+        );
 
         // Ensure we have something in cache.
-        if self.cached_chunk.data.is_empty() {
+        if self.cached_chunk.data.is_empty() && remaining > 0 {
+            self.io.cache_miss();
             self.cached_chunk.data =
-                self.read_chunk(self.cached_chunk.segment, self.cached_chunk.number);
+                self.read_chunk(self.cached_chunk.segment, self.cached_chunk.number)?;
+        } else if remaining > 0 {
+            self.io.cache_hit();
         }
 
         // While there is still room in the caller buffer.
@@ -611,29 +1918,148 @@ impl EWF {
                 self.cached_chunk.ptr = current_chunk_size; // EOF of chunk.
 
                 // Move to **next** chunk (same segment or the following one).
-                if self.cached_chunk.segment < self.segments.len()
-                    || (self.cached_chunk.segment == self.segments.len()
-                        && self.cached_chunk.number + 1
-                            < self.chunks[&self.cached_chunk.segment].len())
-                {
-                    if self.cached_chunk.number + 1 < self.chunks[&self.cached_chunk.segment].len()
-                    {
-                        self.cached_chunk.number += 1;
-                    } else {
-                        self.cached_chunk.segment += 1;
-                        self.cached_chunk.number = 0;
-                    }
-
-                    self.cached_chunk.data =
-                        self.read_chunk(self.cached_chunk.segment, self.cached_chunk.number);
-                    self.cached_chunk.ptr = 0;
+                let in_segment = self
+                    .chunks
+                    .get(&self.cached_chunk.segment)
+                    .map_or(0, Vec::len);
+                let (segment, number) = if self.cached_chunk.number + 1 < in_segment {
+                    (self.cached_chunk.segment, self.cached_chunk.number + 1)
+                } else if self.cached_chunk.segment < self.segments.len() {
+                    io_event!("ewf.segment", segment = self.cached_chunk.segment + 1);
+                    self.io.segment_switch();
+                    (self.cached_chunk.segment + 1, 0)
                 } else {
                     // No more data.
                     break;
+                };
+
+                match self.next_chunk(segment, number) {
+                    Ok(data) => self.cached_chunk.data = data,
+                    Err(_) if total_bytes_read > 0 => break,
+                    Err(e) => return Err(e),
+                }
+                self.cached_chunk.segment = segment;
+                self.cached_chunk.number = number;
+                self.cached_chunk.ptr = 0;
+            }
+        }
+        self.position = self.current_offset() as u64;
+        Ok(total_bytes_read)
+    }
+
+    /// Chunk reached by a sequential read: served from the read-ahead, which
+    /// is refilled in parallel when worker threads are enabled.
+    fn next_chunk(&mut self, segment: usize, chunk_number: usize) -> io::Result<Vec<u8>> {
+        if let Some(data) = self.prefetched.remove(&(segment, chunk_number)) {
+            self.io.cache_hit();
+            return Ok(data);
+        }
+        self.io.cache_miss();
+        self.sequential_chunks += 1;
+        if self.decompression_threads > 1 && self.sequential_chunks >= EWF_PREFETCH_AFTER {
+            self.prefetch(segment, chunk_number);
+            if let Some(data) = self.prefetched.remove(&(segment, chunk_number)) {
+                return Ok(data);
+            }
+        }
+        self.read_chunk(segment, chunk_number)
+    }
+
+    /// Decompress the chunks following (and including) `chunk_number` of
+    /// `segment` on `decompression_threads` scoped workers. Chunks that fail
+    /// are left out, to be read (and reported) on demand.
+    fn prefetch(&mut self, segment: usize, chunk_number: usize) {
+        let threads = self.decompression_threads;
+        let mut wanted = Vec::with_capacity(threads * EWF_PREFETCH_PER_THREAD);
+        let (mut segment, mut number) = (segment, chunk_number);
+        while wanted.len() < threads * EWF_PREFETCH_PER_THREAD {
+            match self.chunks.get(&segment) {
+                Some(chunks) if number < chunks.len() => {
+                    wanted.push((segment, number));
+                    number += 1;
+                }
+                Some(_) if segment < self.segments.len() => {
+                    segment += 1;
+                    number = 0;
                 }
+                _ => break,
             }
         }
-        total_bytes_read
+
+        let this = &*self;
+        let per_thread = wanted.len().div_ceil(threads).max(1);
+        let decoded: Vec<_> = std::thread::scope(|scope| {
+            let workers: Vec<_> = wanted
+                .chunks(per_thread)
+                .map(|group| {
+                    scope.spawn(move || {
+                        group
+                            .iter()
+                            .filter_map(|&(s, n)| Some(((s, n), this.read_chunk(s, n).ok()?)))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("EWF decompression worker panicked"))
+                .collect()
+        });
+        self.prefetched = decoded.into_iter().collect();
+    }
+
+    /// Absolute offset of the cached chunk pointer.
+    fn current_offset(&self) -> usize {
+        self.chunks
+            .get(&self.cached_chunk.segment)
+            .and_then(|chunks| chunks.get(self.cached_chunk.number))
+            .map_or(0, |chunk| {
+                chunk.chunk_number * self.volume.chunk_size() + self.cached_chunk.ptr
+            })
+    }
+
+    /// Segment holding the image-wide `chunk_number`, and the index of the
+    /// chunk within that segment.
+    fn locate_chunk(&self, chunk_number: usize) -> io::Result<(usize, usize)> {
+        let holds = |chunks: &Vec<Chunk>| match (chunks.first(), chunks.last()) {
+            (Some(first), Some(last)) => {
+                (first.chunk_number..=last.chunk_number).contains(&chunk_number)
+            }
+            _ => false,
+        };
+        (1..=self.segments.len())
+            .find_map(|segment| {
+                let chunks = self.chunks.get(&segment).filter(|chunks| holds(chunks))?;
+                Some((segment, chunk_number - chunks[0].chunk_number))
+            })
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("No EWF table references chunk {}", chunk_number),
+                )
+            })
+    }
+
+    /// Decode chunk `chunk_number` of the media for a positional read, which
+    /// bypasses the chunk cache. `segment` holds the segment of the previous
+    /// chunk of the same read, to count segment switches.
+    fn positional_chunk(
+        &self,
+        chunk_number: usize,
+        segment: &mut Option<usize>,
+    ) -> io::Result<Vec<u8>> {
+        let (in_segment, number) = self.locate_chunk(chunk_number)?;
+        if segment.is_some_and(|previous| previous != in_segment) {
+            self.io.segment_switch();
+        }
+        *segment = Some(in_segment);
+        self.io.cache_miss();
+        self.read_chunk(in_segment, number)
+    }
+
+    /// Internal counters behind [`Body::stats`](crate::Body::stats).
+    pub(crate) fn io_counters(&self) -> &IoCounters {
+        &self.io
     }
 
     /// Translate an absolute offset into the appropriate chunk and refresh the
@@ -652,6 +2078,12 @@ impl EWF {
 
         let chunk_size = self.volume.chunk_size();
         let mut chunk_number = offset / chunk_size;
+        let mut ptr = offset % chunk_size;
+        // The end of a chunk-aligned image is the end of its last chunk.
+        if ptr == 0 && chunk_number > 0 && chunk_number == self.volume.chunk_count as usize {
+            chunk_number -= 1;
+            ptr = chunk_size;
+        }
         if chunk_number >= self.volume.chunk_count as usize {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
@@ -659,22 +2091,18 @@ impl EWF {
             ));
         }
 
-        // Locate the appropriate segment.
-        let mut segment = 1;
-        while segment < self.segments.len()
-            && (self.chunks[&segment][0].chunk_number > chunk_number
-                || chunk_number > self.chunks[&segment].last().unwrap().chunk_number)
-        {
-            segment += 1;
-        }
-
-        chunk_number -= self.chunks[&segment][0].chunk_number;
+        let (segment, chunk_number) = self.locate_chunk(chunk_number)?;
 
-        // Populate cache.
-        self.cached_chunk.data = self.read_chunk(segment, chunk_number);
+        self.sequential_chunks = 0;
+        // Reuse a chunk already decompressed ahead, otherwise the next read
+        // loads it (and reports a damaged chunk, the seek itself succeeds).
+        self.cached_chunk.data = self
+            .prefetched
+            .remove(&(segment, chunk_number))
+            .unwrap_or_default();
         self.cached_chunk.number = chunk_number;
         self.cached_chunk.segment = segment;
-        self.cached_chunk.ptr = offset % chunk_size;
+        self.cached_chunk.ptr = ptr;
         self.position = offset as u64;
         Ok(())
     }
@@ -682,24 +2110,31 @@ impl EWF {
 
 // ===== Clone impl ===========================================================
 impl Clone for EWF {
+    /// Clones share the segment handles and the chunk tables; only the
+    /// position and the chunk caches are their own.
     fn clone(&self) -> Self {
-        let segments = self
-            .segments
-            .iter()
-            .map(|fd| fd.try_clone().expect("failed to duplicate segment FD"))
-            .collect();
-
         Self {
-            segments,
+            segments: self.segments.clone(),
+            segment_paths: self.segment_paths.clone(),
             ewf_header: self.ewf_header.clone(),
-            sections: self.sections.clone(),
             header: self.header.clone(),
+            device_information: self.device_information.clone(),
             volume: self.volume.clone(),
             chunks: self.chunks.clone(),
             end_of_sectors: self.end_of_sectors.clone(),
             cached_chunk: self.cached_chunk.clone(),
             chunk_count: self.chunk_count,
             position: self.position,
+            decompression_threads: self.decompression_threads,
+            verify_checksums: self.verify_checksums,
+            prefetched: HashMap::new(),
+            sequential_chunks: 0,
+            hashes: self.hashes.clone(),
+            digests: self.digests.clone(),
+            acquisition_errors: self.acquisition_errors.clone(),
+            sessions: self.sessions.clone(),
+            file_entries: self.file_entries.clone(),
+            io: self.io.clone(),
         }
     }
 }
@@ -707,8 +2142,59 @@ impl Clone for EWF {
 // ===== std::io trait implementations =======================================
 impl Read for EWF {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        let bytes_read = self.ewf_read(buf);
-        Ok(bytes_read)
+        self.ewf_read(buf)
+    }
+}
+
+impl ReadAt for EWF {
+    /// Positional read, bypassing the chunk cache used by [`Read`].
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let max_offset = self.volume.max_offset() as u64;
+        if offset >= max_offset {
+            return Ok(0);
+        }
+        let chunk_size = self.volume.chunk_size();
+        let want = buf.len().min((max_offset - offset) as usize);
+        let mut done = 0;
+        let mut segment = None;
+        while done < want {
+            let position = offset as usize + done;
+            let data = self.positional_chunk(position / chunk_size, &mut segment)?;
+            let within = position % chunk_size;
+            let take = (chunk_size - within).min(want - done);
+            buf[done..done + take].copy_from_slice(&data[within..within + take]);
+            done += take;
+        }
+        Ok(done)
+    }
+
+    /// Decoded chunks are appended as they are, without zeroing `buf`.
+    fn read_at_append(&self, offset: u64, buf: &mut Vec<u8>, len: usize) -> io::Result<usize> {
+        let max_offset = self.volume.max_offset() as u64;
+        if offset >= max_offset {
+            return Ok(0);
+        }
+        let chunk_size = self.volume.chunk_size();
+        let want = len.min((max_offset - offset) as usize);
+        let start = buf.len();
+        buf.reserve(want);
+        let mut done = 0;
+        let mut segment = None;
+        while done < want {
+            let position = offset as usize + done;
+            let data = match self.positional_chunk(position / chunk_size, &mut segment) {
+                Ok(data) => data,
+                Err(e) => {
+                    buf.truncate(start);
+                    return Err(e);
+                }
+            };
+            let within = position % chunk_size;
+            let take = (chunk_size - within).min(want - done);
+            buf.extend_from_slice(&data[within..within + take]);
+            done += take;
+        }
+        Ok(done)
     }
 }
 
@@ -731,35 +2217,501 @@ impl Seek for EWF {
         self.ewf_seek(new_offset_usize)?;
         Ok(new_offset as u64)
     }
+
+    /// Answered without seeking, which would drop the decompressed chunk.
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.position)
+    }
 }
 
 // ===== helpers ==============================================================
+/// Whether `magic` (the first 8 bytes of a file) is an EWF1 or EWF2 segment
+/// signature.
+pub(crate) fn is_ewf_signature(magic: &[u8; 8]) -> bool {
+    [
+        EWF_E01_SIGNATURE,
+        EWF_L01_SIGNATURE,
+        EWF2_EX01_SIGNATURE,
+        EWF2_LX01_SIGNATURE,
+    ]
+    .contains(magic)
+}
+
+/// Extension of segment `number` (from 1) of the family `prefix` (`E`, `L`
+/// or `S`, or `Ex` / `Lx` for EWF2), in upper case: `E01` … `E99`, then
+/// `EAA` … `EZZ`, `FAA` … `ZZZ`; `Ex01` … `Ex99`, then `ExAA` … `ExZZ`.
+/// None past the end of the sequence.
+pub(crate) fn segment_extension(prefix: &str, number: u32) -> Option<String> {
+    if number == 0 {
+        return None;
+    }
+    if number < 100 {
+        return Some(format!("{}{:02}", prefix, number));
+    }
+    let index = number - 100;
+    let letter = |i: u32| char::from(b'A' + i as u8);
+    if prefix.len() == 1 {
+        let first = (prefix.as_bytes()[0].checked_sub(b'A')? as u32) + index / (26 * 26);
+        (first < 26).then(|| {
+            format!(
+                "{}{}{}",
+                letter(first),
+                letter(index / 26 % 26),
+                letter(index % 26)
+            )
+        })
+    } else {
+        (index < 26 * 26).then(|| format!("{}{}{}", prefix, letter(index / 26), letter(index % 26)))
+    }
+}
+
+/// Segment family of a file extension (see [`segment_extension`]), `None`
+/// when the extension is not one of a segment; a three-letter extension
+/// (`EAB`, `LBA`, …) does not tell its family, every one is returned.
+fn segment_prefixes(extension: &str) -> Option<Vec<String>> {
+    let ext = extension.to_ascii_uppercase();
+    let bytes = ext.as_bytes();
+    let numbered = |tail: &[u8]| {
+        tail.iter().all(u8::is_ascii_digit) || tail.iter().all(u8::is_ascii_uppercase)
+    };
+    match bytes {
+        [family @ (b'E' | b'L' | b'S'), tail @ ..]
+            if tail.len() == 2 && tail.iter().all(u8::is_ascii_digit) =>
+        {
+            Some(vec![char::from(*family).to_string()])
+        }
+        [family @ (b'E' | b'L'), b'X', tail @ ..] if tail.len() == 2 && numbered(tail) => {
+            Some(vec![format!("{}x", char::from(*family))])
+        }
+        [_, _, _] if numbered(bytes) => {
+            Some(["E", "L", "S"].iter().map(|f| f.to_string()).collect())
+        }
+        _ => None,
+    }
+}
+
 /// Look for every segment belonging to the *same* multi-part image as `path`.
 ///
-/// The function builds a glob pattern **in the parent directory** replacing the
-/// numeric suffix with a wild-card (e.g. `image.E01` ⇒ `image.E??`) and returns
-/// the sorted list of matching paths.
-fn find_files(path: &Path) -> Result<Vec<PathBuf>, String> {
-    let path = path
-        .canonicalize()
+/// `path` may name any segment. Its extension gives the naming family
+/// (`E01`, `L01`, `S01`, `Ex01` or `Lx01`, any case); the segments are then
+/// looked up in sequence from the first (`.E01` … `.E99`, `.EAA` … `.ZZZ`)
+/// until one is missing. A later segment past that gap means the set is
+/// incomplete, which is an error rather than a silently truncated image.
+/// A file whose extension is not a segment one, or without a first segment
+/// next to it, is a single-segment image.
+fn find_files(fs: &dyn FileSystem, path: &Path) -> Result<Vec<PathBuf>, String> {
+    let path = fs
+        .canonicalize(path)
         .map_err(|_| "Invalid path".to_string())?;
-    let filename = path.file_name().ok_or("Invalid file name")?;
-    let filename_str = filename.to_str().ok_or("Invalid file name")?;
+    let filename_str = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("Invalid file name")?;
+    let Some((stem, prefixes)) = filename_str
+        .rsplit_once('.')
+        .and_then(|(stem, ext)| Some((stem, segment_prefixes(ext)?)))
+    else {
+        return Ok(vec![path.clone()]);
+    };
+    let parent = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+
+    // Segment names are matched ignoring case (`image.e01`, `image.E02`).
+    let entries: HashMap<String, PathBuf> = fs
+        .read_dir(parent)
+        .map_err(|e| format!("Cannot list {}: {}", parent.display(), e))?
+        .into_iter()
+        .filter_map(|p| Some((p.file_name()?.to_str()?.to_lowercase(), p)))
+        .collect();
+    let name = |prefix: &str, number: u32| {
+        segment_extension(prefix, number).map(|ext| format!("{}.{}", stem, ext).to_lowercase())
+    };
+    let Some(prefix) = prefixes
+        .iter()
+        .find(|prefix| name(prefix, 1).is_some_and(|n| entries.contains_key(&n)))
+    else {
+        // A lone file named like a later segment (or like `image.img`).
+        return Ok(vec![path.clone()]);
+    };
+
+    let mut paths = Vec::new();
+    while let Some(segment) = name(prefix, paths.len() as u32 + 1).and_then(|n| entries.get(&n)) {
+        paths.push(segment.clone());
+    }
+    let missing = paths.len() as u32 + 1;
+    // Three-letter names also cover unrelated files (`image.raw`), only
+    // an actual segment past the gap counts.
+    let later = (missing + 1..)
+        .map_while(|number| name(prefix, number))
+        .find(|n| {
+            entries.get(n).is_some_and(|p| {
+                let mut magic = [0u8; 8];
+                fs.open(p)
+                    .and_then(|mut file| file.read_exact(&mut magic))
+                    .is_ok_and(|_| is_ewf_signature(&magic))
+            })
+        });
+    if let Some(later) = later {
+        return Err(format!(
+            "EWF segment set is not contiguous: {} is missing but {} exists",
+            name(prefix, missing).unwrap_or_default(),
+            later
+        ));
+    }
+
+    Ok(paths)
+}
+
+/// `data_size`, as a section descriptor declares it for data starting at
+/// `offset`, capped at what is left of `file`: a corrupt descriptor must not
+/// make the parser allocate more than the segment holds.
+fn segment_bounded(file: &Source, offset: u64, data_size: u64) -> io::Result<u64> {
+    Ok(data_size.min(file.len()?.saturating_sub(offset)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ewf_writer::{section_descriptor, EwfCompression, EwfWriteSummary, EwfWriter};
+    use crate::testing::TempDir;
+    use crate::{Body, BodyFormat, ReadPolicy};
+
+    #[test]
+    fn parses_the_single_files_tree() {
+        let tree = "\u{FEFF}5\r\nrec\r\ntb\tcl\r\n1\t0\r\n\r\nentry\r\n1\t1\r\n\
+                    p\tn\tid\tls\tcr\tac\twr\tmo\tdl\tha\tsha\r\n\
+                    1\t1\r\n\t\t\t\t\t\t\t\t\t\t\r\n\
+                    2\t1\r\n1\tdocs\t1\t0\t1600000000\t\t\t\t\t\t\r\n\
+                    0\t1\r\n\tnotes.txt\t2\t1234\t1600000000\t1600000100\t1600000200\t1600000300\t0\t\
+                    D41D8CD98F00B204E9800998ECF8427E\t00000000000000000000\r\n\
+                    0\t1\r\n\tempty\t3\t0\t\t\t\t\t\t\t\r\n";
+        let entries = EwfFileEntry::parse_tree(tree).unwrap();
+        let paths: Vec<&str> = entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["/docs", "/docs/notes.txt", "/docs/empty"]);
+        assert!(entries[0].is_directory && !entries[1].is_directory);
+        let notes = &entries[1];
+        assert_eq!(notes.size, 1234);
+        assert_eq!(notes.created, Some(1600000000));
+        assert_eq!(notes.entry_modified, Some(1600000300));
+        assert_eq!(notes.deleted, None);
+        assert_eq!(
+            notes.md5.as_deref(),
+            Some("d41d8cd98f00b204e9800998ecf8427e")
+        );
+        assert_eq!(notes.sha1, None);
 
-    if filename_str.len() < 2 {
-        return Err("File name too short".into());
+        assert!(EwfFileEntry::parse_tree(&tree[..tree.len() - 40]).is_err());
     }
 
-    let base_filename = &filename_str[..filename_str.len() - 2];
-    let parent = path.parent().ok_or("No parent directory")?;
+    #[test]
+    fn ltree_size_is_bounded_by_the_segment() {
+        let mut data = vec![0u8; EWF_LTREE_HEADER_SIZE as usize];
+        data[16..24].copy_from_slice(&u64::MAX.to_le_bytes());
+        let image = single_section_image("ltree", 1 << 62, &data);
+        assert!(image.file_entries().is_empty());
+    }
 
-    let mut pattern_path = PathBuf::from(parent);
-    pattern_path.push(format!("{}??", base_filename));
-    let pattern = pattern_path.to_str().ok_or("Invalid pattern")?.to_string();
+    /// Write `data` with `writer` as `<name>.E01` (and any later segments) in
+    /// a scratch directory that lives as long as the returned guard.
+    fn write_image(name: &str, mut writer: EwfWriter, data: &[u8]) -> (TempDir, EwfWriteSummary) {
+        let dir = TempDir::new(&format!("ewf_{}", name));
+        let summary = writer
+            .write(&mut io::Cursor::new(data), dir.join(name))
+            .unwrap();
+        (dir, summary)
+    }
 
-    let files = glob::glob(&pattern).map_err(|e| format!("Glob error: {}", e))?;
-    let mut paths: Vec<PathBuf> = files.filter_map(Result::ok).collect();
-    paths.sort();
+    /// Open the first segment of `summary` as an EWF body.
+    fn open_body(summary: &EwfWriteSummary) -> Body {
+        Body::try_new(summary.segments[0].to_string_lossy().into(), "ewf").unwrap()
+    }
 
-    Ok(paths)
+    /// Overwrite part of the first chunk of a single-segment image with `0xff`.
+    fn corrupt_first_chunk(path: &Path) {
+        let mut image = std::fs::read(path).unwrap();
+        let sectors = image.windows(7).position(|w| w == b"sectors").unwrap();
+        image[sectors + 76 + 2..sectors + 76 + 64].fill(0xff);
+        std::fs::write(path, image).unwrap();
+    }
+
+    /// A single-segment E01 holding a `kind` section that declares
+    /// `section_size` bytes and stores `data`, then the `done` section.
+    fn single_section_image(kind: &str, section_size: u64, data: &[u8]) -> EWF {
+        let mut image = EWF_E01_SIGNATURE.to_vec();
+        image.extend_from_slice(&[1, 1, 0, 0, 0]);
+        let done = image.len() as u64 + 76 + data.len() as u64;
+        image.extend_from_slice(&section_descriptor(kind, done, section_size));
+        image.extend_from_slice(data);
+        image.extend_from_slice(&section_descriptor("done", done, 76));
+        EWF::from_readers(vec![Box::new(io::Cursor::new(image))]).unwrap()
+    }
+
+    #[test]
+    fn gap_in_the_segment_set_is_an_error() {
+        let data: Vec<u8> = (0..400 * 1024u32).map(|i| (i / 7 % 251) as u8).collect();
+        let writer = EwfWriter::new()
+            .sectors_per_chunk(8)
+            .segment_size(32 * 1024);
+        let (dir, summary) = write_image("gap", writer, &data);
+        assert!(summary.segments.len() > 2);
+        std::fs::rename(dir.join("gap.E03"), dir.join("gap.e03")).unwrap();
+        assert!(EWF::new(&dir.join("gap.E02").to_string_lossy()).is_ok());
+
+        std::fs::remove_file(dir.join("gap.E02")).unwrap();
+        let Err(err) = EWF::new(&dir.join("gap.E01").to_string_lossy()) else {
+            panic!("opened an image with a missing segment");
+        };
+        assert!(err.to_string().contains("gap.e02 is missing"), "{}", err);
+    }
+
+    #[test]
+    fn parallel_decompression_reads_back_every_segment() {
+        let data: Vec<u8> = (0..400 * 1024u32).map(|i| (i / 7 % 251) as u8).collect();
+        let writer = EwfWriter::new()
+            .sectors_per_chunk(8)
+            .segment_size(128 * 1024);
+        let (_dir, summary) = write_image("mt", writer, &data);
+        assert!(summary.segments.len() > 1);
+
+        let mut body = Body::builder(summary.segments[0].to_string_lossy())
+            .decompression_threads(4)
+            .build()
+            .unwrap();
+        let mut back = Vec::new();
+        body.read_to_end(&mut back).unwrap();
+        assert_eq!(back, data);
+    }
+
+    #[test]
+    fn geometry_round_trips_through_the_volume_section() {
+        let geometry = DiskGeometry {
+            cylinders: 2,
+            heads: 16,
+            sectors_per_track: 63,
+        };
+        let data = vec![0u8; 2 * 16 * 63 * 512];
+        let (_dir, summary) = write_image("chs", EwfWriter::new().geometry(geometry), &data);
+
+        let mut body = open_body(&summary);
+        let read = body.metadata().unwrap().geometry.unwrap();
+        assert_eq!(read, geometry);
+        assert_eq!(read.lba_to_chs(63), Some((0, 1, 1)));
+        assert_eq!(read.chs_to_lba(1, 0, 1), Some(16 * 63));
+    }
+
+    #[test]
+    fn acquisition_errors_round_trip_through_error2() {
+        let errors = [
+            SectorRange { start: 8, count: 2 },
+            SectorRange {
+                start: 300,
+                count: 64,
+            },
+        ];
+        let writer = EwfWriter::new()
+            .segment_size(256 * 1024)
+            .compression(EwfCompression::None)
+            .acquisition_errors(errors);
+        let (_dir, summary) = write_image("err", writer, &vec![0u8; 1024 * 512]);
+
+        let body = open_body(&summary);
+        let BodyFormat::EWF { image, .. } = &body.format else {
+            panic!("not opened as EWF");
+        };
+        assert!(image.segment_count() > 1);
+        assert_eq!(image.acquisition_errors(), errors);
+    }
+
+    #[test]
+    fn error2_count_is_bounded_by_the_segment() {
+        let mut data = vec![0u8; 520];
+        data[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        // Only the 76-byte done descriptor follows, which holds no range.
+        let image = single_section_image("error2", 1 << 62, &data);
+        assert!(image.acquisition_errors().is_empty());
+    }
+
+    #[test]
+    fn optical_sessions_round_trip() {
+        let session = |start, count, audio| EwfSession {
+            sectors: SectorRange { start, count },
+            audio,
+        };
+        let sessions = [session(0, 16, true), session(16, 48, false)];
+        let writer = EwfWriter::new().bytes_per_sector(2048).sessions(sessions);
+        let (_dir, summary) = write_image("cd", writer, &vec![0u8; 64 * 2048]);
+
+        let body = open_body(&summary);
+        let BodyFormat::EWF { image, .. } = &body.format else {
+            panic!("not opened as EWF");
+        };
+        assert_eq!(image.sessions(), sessions);
+    }
+
+    #[test]
+    fn session_count_is_bounded_by_the_segment() {
+        let mut data = vec![0u8; 36 + 32];
+        data[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        data[36 + 4..36 + 8].copy_from_slice(&16u32.to_le_bytes());
+        let image = single_section_image("session", 1 << 62, &data);
+        // The stored entry, then the two whole entries the done descriptor
+        // makes room for.
+        assert_eq!(image.sessions.len(), 3);
+        assert!(image.sessions.contains(&(16, 0)));
+    }
+
+    #[test]
+    fn case_info_reads_back() {
+        let writer = EwfWriter::new()
+            .case_number("2024-001")
+            .evidence_number("HDD-3")
+            .examiner("J. Doe")
+            .notes("seized\tat the office");
+        let (_dir, summary) = write_image("case", writer, &[7u8; 4096]);
+
+        let body = open_body(&summary);
+        let BodyFormat::EWF { image, .. } = &body.format else {
+            panic!("not opened as EWF");
+        };
+        let case = image.case_info();
+        assert_eq!(case.case_number, "2024-001");
+        assert_eq!(case.evidence_number, "HDD-3");
+        assert_eq!(case.examiner, "J. Doe");
+        assert_eq!(case.notes, "seized at the office");
+        assert_eq!(case.tool_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(case.acquired_at.split(' ').count(), 6);
+        assert_eq!(case.guid.len(), 36);
+        assert_eq!(image.set_identifier().as_deref(), Some(case.guid.as_str()));
+        assert!(image
+            .acquisition_info()
+            .contains(&("Set Identifier".to_string(), case.guid.clone())));
+    }
+
+    #[test]
+    fn info_describes_the_image() {
+        let writer = EwfWriter::new().case_number("2024-002");
+        let (_dir, summary) = write_image("info", writer, &[0u8; 8192]);
+
+        let path = summary.segments[0].to_string_lossy().into_owned();
+        let body = open_body(&summary);
+        let info = body.info();
+        assert_eq!(info.title, "EWF File Information");
+        assert_eq!(info.get("Evidence"), Some(path.as_str()));
+        assert_eq!(info.get("Number of Segments"), Some("1"));
+        assert_eq!(info.get("Total Sector Count"), Some("16"));
+        assert!(info
+            .sections
+            .iter()
+            .any(|s| s.title == "Acquisition Metadata"));
+        assert!(info.to_string().contains("  Chunk Count: 1\n"));
+    }
+
+    #[test]
+    fn metadata_serializes_to_json() {
+        let writer = EwfWriter::new().case_number("2024-003");
+        let (_dir, summary) = write_image("json", writer, &[1u8; 4096]);
+
+        let mut body = open_body(&summary);
+        let metadata = serde_json::to_value(body.metadata().unwrap()).unwrap();
+        assert_eq!(metadata["format"], "ewf");
+        assert_eq!(metadata["size"], 4096);
+        assert!(metadata["acquisition"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|field| field["label"] == "Case Number" && field["value"] == "2024-003"));
+        let md5 = &metadata["stored_hashes"][0];
+        assert_eq!(md5["algorithm"], "md5");
+        assert_eq!(md5["digest"].as_str().unwrap().len(), 32);
+
+        let BodyFormat::EWF { image, .. } = &body.format else {
+            panic!("not opened as EWF");
+        };
+        let case = serde_json::to_value(image.case_info()).unwrap();
+        assert_eq!(case["case_number"], "2024-003");
+    }
+
+    #[test]
+    fn corrupt_chunk_is_an_io_error() {
+        let data: Vec<u8> = (0..64 * 1024u32).map(|i| (i % 13) as u8).collect();
+        let (_dir, summary) = write_image("bad", EwfWriter::new(), &data);
+        corrupt_first_chunk(&summary.segments[0]);
+
+        let mut body = open_body(&summary);
+        let err = body.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn best_effort_fills_corrupt_chunk() {
+        let data: Vec<u8> = (0..64 * 1024u32).map(|i| (i % 13) as u8).collect();
+        let (_dir, summary) = write_image("fill", EwfWriter::new(), &data);
+        corrupt_first_chunk(&summary.segments[0]);
+
+        let mut body = Body::builder(summary.segments[0].to_string_lossy())
+            .read_policy(ReadPolicy::BestEffort {
+                fill: b"BAD!".to_vec(),
+            })
+            .build()
+            .unwrap();
+        let mut back = Vec::new();
+        body.read_to_end(&mut back).unwrap();
+        assert_eq!(back.len(), data.len());
+        assert_eq!(&back[..8], b"BAD!BAD!");
+        assert_eq!(back[32 * 1024..], data[32 * 1024..]);
+        assert_eq!(
+            body.bad_ranges(),
+            [SectorRange {
+                start: 0,
+                count: 64
+            }]
+        );
+    }
+
+    #[test]
+    fn verifies_chunk_checksums() {
+        let data: Vec<u8> = (0..97 * 1024u32).map(|i| (i % 13) as u8).collect();
+        let plain = EwfWriter::new().compression(EwfCompression::None);
+        let (_plain_dir, plain) = write_image("plain", plain, &data);
+        let packed = EwfWriter::new().compression(EwfCompression::Fast);
+        let (_packed_dir, packed) = write_image("packed", packed, &data);
+
+        let packed = Body::builder(packed.segments[0].to_string_lossy())
+            .verify_chunk_checksums(true)
+            .build()
+            .unwrap();
+        assert!(packed.verify_chunks(|_, _| {}).is_empty());
+
+        // Flip a byte in the second (uncompressed) chunk: plain reads do not
+        // notice, verified ones do.
+        let path = &plain.segments[0];
+        let mut image = std::fs::read(path).unwrap();
+        let sectors = image.windows(7).position(|w| w == b"sectors").unwrap();
+        image[sectors + 76 + 32 * 1024 + 4 + 10] ^= 0xff;
+        std::fs::write(path, image).unwrap();
+
+        let mut body = open_body(&plain);
+        let mut back = Vec::new();
+        body.read_to_end(&mut back).unwrap();
+        assert_eq!(back.len(), data.len());
+        assert_ne!(back, data);
+        let mut checks = 0;
+        assert_eq!(
+            body.verify_chunks(|_, total| checks = total),
+            [SectorRange {
+                start: 64,
+                count: 64
+            }]
+        );
+        assert_eq!(checks, 4);
+
+        let mut body = Body::builder(path.to_string_lossy())
+            .verify_chunk_checksums(true)
+            .build()
+            .unwrap();
+        let err = body.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
 }
@@ -0,0 +1,840 @@
+//! **EWF (E01) image writer**
+//!
+//! Re-packages any readable source (a [`Body`](crate::Body), a device, …) as an
+//! EnCase 6 style Expert Witness image, split over `.E01`, `.E02`, … segments:
+//!
+//! | Segment      | Sections                                                        |
+//! |--------------|-----------------------------------------------------------------|
+//! | first        | file header, `header`, `volume`, then the data groups           |
+//! | following    | file header, `data` (copy of `volume`), then the data groups    |
+//! | data group   | `sectors` (chunks), `table` and `table2` (chunk offsets)        |
+//! | end          | `next`, or `hash` + `digest` + `done` in the last segment       |
+//!
+//! Chunks are zlib-compressed unless compression is disabled or does not pay
+//! off, in which case they are stored followed by their Adler-32 checksum.
+//! A data group is closed after 16375 chunks or 2 GiB of chunk data so that
+//! table offsets fit in 31 bits, and a segment is closed before it would
+//! exceed the configured segment size.
+//!
+//! Reference: <https://github.com/libyal/libewf/blob/main/documentation/Expert%20Witness%20Compression%20Format%20(EWF).asciidoc>
+
+use crate::ewf::{segment_extension, EwfSession};
+use crate::hash::{HashAlgorithm, MultiHasher};
+use crate::metadata::{DiskGeometry, SectorRange};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use log::info;
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// ---- EWF1 constants ---------------------------------------------------------
+const EWF_E01_SIGNATURE: [u8; 8] = [0x45, 0x56, 0x46, 0x09, 0x0d, 0x0a, 0xff, 0x00];
+const EWF_FILE_HEADER_SIZE: u64 = 13;
+const EWF_SECTION_DESCRIPTOR_SIZE: u64 = 76;
+const EWF_VOLUME_DATA_SIZE: usize = 1052;
+const EWF_TABLE_HEADER_SIZE: u64 = 24;
+const EWF_TABLE_MAX_ENTRIES: usize = 16375;
+const EWF_TABLE_MAX_OFFSET: u64 = 0x7FFF_FFFF;
+const EWF_CHUNK_COMPRESSED: u32 = 0x8000_0000;
+const EWF_MEDIA_TYPE_FIXED: u8 = 0x01;
+const EWF_MEDIA_TYPE_OPTICAL: u8 = 0x03;
+const EWF_MEDIA_FLAG_IMAGE: u8 = 0x01;
+/// `hash` (36 bytes), `digest` (80 bytes) and three bare descriptors.
+const EWF_SEGMENT_TAIL_SIZE: u64 = 3 * EWF_SECTION_DESCRIPTOR_SIZE + 36 + 80;
+
+/// Default segment size, as proposed by EnCase (1.4 GiB).
+pub const EWF_DEFAULT_SEGMENT_SIZE: u64 = 1500 * 1024 * 1024;
+
+/// Chunk compression applied by [`EwfWriter`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EwfCompression {
+    None,
+    #[default]
+    Fast,
+    Best,
+}
+
+impl EwfCompression {
+    /// Value of the `r` header field.
+    fn header_flag(self) -> &'static str {
+        match self {
+            EwfCompression::None => "n",
+            EwfCompression::Fast => "f",
+            EwfCompression::Best => "b",
+        }
+    }
+}
+
+/// Case information stored in the `header` section, read back by
+/// [`EWF::case_info`](crate::ewf::EWF::case_info). Empty strings stand for
+/// values the image does not record.
+///
+/// [`EwfWriter`] only writes the case number, evidence number, description,
+/// examiner and notes; it records its own dates, version and OS.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct EwfCaseInfo {
+    pub case_number: String,
+    pub evidence_number: String,
+    pub description: String,
+    pub examiner: String,
+    pub notes: String,
+    /// Acquisition date as `YYYY M D h m s` (EWF2 timestamps are converted
+    /// from UTC, EWF1 dates are in the acquiring machine's local time).
+    pub acquired_at: String,
+    /// System date of the acquiring machine, same format.
+    pub system_date: String,
+    /// Version of the acquisition software.
+    pub tool_version: String,
+    /// Operating system the acquisition ran on.
+    pub os_version: String,
+    /// Model of the acquired device (EWF1 header2 or EWF2 device
+    /// information).
+    pub model: String,
+    /// Serial number of the acquired device, same sections.
+    pub serial_number: String,
+    /// Set identifier of the image, the GUID shared by its segments, as
+    /// `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx`. Generated by [`EwfWriter`].
+    pub guid: String,
+}
+
+/// Outcome of an [`EwfWriter`] run.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EwfWriteSummary {
+    /// Segment files written, in order.
+    pub segments: Vec<PathBuf>,
+    /// Number of media bytes acquired.
+    pub bytes_written: u64,
+    /// MD5, SHA-1 (also stored in the `hash` and `digest` sections) and any
+    /// other digest requested with [`EwfWriter::hash`].
+    pub hashes: Vec<(HashAlgorithm, String)>,
+}
+
+/// Writes a readable source as a multi-segment E01 image.
+///
+/// ```no_run
+/// # use exhume_body::{Body, EwfWriter};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut body = Body::try_new("/evidence/disk.vmdk".to_string(), "vmdk")?;
+/// let summary = EwfWriter::new()
+///     .case_number("2024-001")
+///     .examiner("J. Doe")
+///     .segment_size(650 * 1024 * 1024)
+///     .write(&mut body, "/evidence/disk")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct EwfWriter {
+    sectors_per_chunk: u32,
+    bytes_per_sector: u32,
+    segment_size: u64,
+    compression: EwfCompression,
+    case: EwfCaseInfo,
+    geometry: Option<DiskGeometry>,
+    acquisition_errors: Vec<SectorRange>,
+    error_source: Option<Box<dyn Fn() -> Vec<SectorRange>>>,
+    sessions: Vec<EwfSession>,
+    hashes: Vec<HashAlgorithm>,
+    progress: Option<Box<dyn FnMut(u64, u64)>>,
+}
+
+impl Default for EwfWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EwfWriter {
+    pub fn new() -> Self {
+        Self {
+            sectors_per_chunk: 64,
+            bytes_per_sector: 512,
+            segment_size: EWF_DEFAULT_SEGMENT_SIZE,
+            compression: EwfCompression::default(),
+            case: EwfCaseInfo::default(),
+            geometry: None,
+            acquisition_errors: Vec::new(),
+            error_source: None,
+            sessions: Vec::new(),
+            hashes: vec![HashAlgorithm::Md5, HashAlgorithm::Sha1],
+            progress: None,
+        }
+    }
+
+    /// Number of sectors per chunk (64 by default, i.e. 32 KiB chunks).
+    pub fn sectors_per_chunk(mut self, sectors: u32) -> Self {
+        self.sectors_per_chunk = sectors.max(1);
+        self
+    }
+
+    /// Logical sector size of the media (512 by default).
+    pub fn bytes_per_sector(mut self, bytes: u32) -> Self {
+        self.bytes_per_sector = bytes.max(1);
+        self
+    }
+
+    /// Maximum size of a segment file in bytes.
+    pub fn segment_size(mut self, bytes: u64) -> Self {
+        self.segment_size = bytes;
+        self
+    }
+
+    pub fn compression(mut self, compression: EwfCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// CHS geometry of the source device, recorded in the volume section.
+    pub fn geometry(mut self, geometry: DiskGeometry) -> Self {
+        self.geometry = Some(geometry);
+        self
+    }
+
+    /// Sectors that could not be read from the source, recorded in an
+    /// `error2` section so readers know their content is filler.
+    pub fn acquisition_errors(mut self, ranges: impl IntoIterator<Item = SectorRange>) -> Self {
+        self.acquisition_errors = ranges.into_iter().collect();
+        self
+    }
+
+    /// Sectors found unreadable while the source is read, e.g. the
+    /// [`Body::bad_ranges`](crate::Body::bad_ranges) of a best-effort body:
+    /// `source` is called once the media is written, and its ranges are
+    /// recorded in the `error2` section along with the
+    /// [`acquisition_errors`](EwfWriter::acquisition_errors).
+    pub fn acquisition_errors_from(
+        mut self,
+        source: impl Fn() -> Vec<SectorRange> + 'static,
+    ) -> Self {
+        self.error_source = Some(Box::new(source));
+        self
+    }
+
+    /// Sessions of an optical disc, recorded in a `session` section; the
+    /// media is then declared optical. Only the first sector and the audio
+    /// flag of each session are stored.
+    pub fn sessions(mut self, sessions: impl IntoIterator<Item = EwfSession>) -> Self {
+        self.sessions = sessions.into_iter().collect();
+        self
+    }
+
+    /// Replace all the case information at once.
+    pub fn case_info(mut self, case: EwfCaseInfo) -> Self {
+        self.case = case;
+        self
+    }
+
+    pub fn case_number(mut self, value: impl Into<String>) -> Self {
+        self.case.case_number = value.into();
+        self
+    }
+
+    pub fn evidence_number(mut self, value: impl Into<String>) -> Self {
+        self.case.evidence_number = value.into();
+        self
+    }
+
+    pub fn description(mut self, value: impl Into<String>) -> Self {
+        self.case.description = value.into();
+        self
+    }
+
+    pub fn examiner(mut self, value: impl Into<String>) -> Self {
+        self.case.examiner = value.into();
+        self
+    }
+
+    pub fn notes(mut self, value: impl Into<String>) -> Self {
+        self.case.notes = value.into();
+        self
+    }
+
+    /// Also compute `algorithm` over the media (MD5 and SHA-1 always are).
+    pub fn hash(mut self, algorithm: HashAlgorithm) -> Self {
+        self.hashes.push(algorithm);
+        self
+    }
+
+    /// Call `callback(bytes_done, bytes_total)` after every chunk.
+    pub fn progress(mut self, callback: impl FnMut(u64, u64) + 'static) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Acquire `source` from its start to its end into segments named after
+    /// `path` (`disk` or `disk.E01` both give `disk.E01`, `disk.E02`, …).
+    ///
+    /// Refuses to overwrite existing segment files.
+    pub fn write<R>(
+        &mut self,
+        source: &mut R,
+        path: impl AsRef<Path>,
+    ) -> io::Result<EwfWriteSummary>
+    where
+        R: Read + Seek + ?Sized,
+    {
+        let base = segment_base(path.as_ref());
+        let total = source.seek(SeekFrom::End(0))?;
+        source.seek(SeekFrom::Start(0))?;
+        if total == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Nothing to acquire: the source is empty",
+            ));
+        }
+
+        let chunk_size = self.sectors_per_chunk as u64 * self.bytes_per_sector as u64;
+        let sector_count = total.div_ceil(self.bytes_per_sector as u64);
+        let chunk_count = total.div_ceil(chunk_size);
+        let chunk_count = u32::try_from(chunk_count).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Source too large for the chunk size, use more sectors per chunk",
+            )
+        })?;
+
+        // Fail before acquiring anything when the known errors do not fit.
+        self.error2_data(&self.acquisition_errors)?;
+        let sessions = self.session_data()?;
+        let volume = self.volume_data(chunk_count, sector_count);
+        let header = self.header_data()?;
+        let minimum = EWF_FILE_HEADER_SIZE
+            + 2 * EWF_SECTION_DESCRIPTOR_SIZE
+            + header.len() as u64
+            + EWF_VOLUME_DATA_SIZE as u64
+            + 3 * (EWF_SECTION_DESCRIPTOR_SIZE + EWF_TABLE_HEADER_SIZE + 8)
+            + EWF_SEGMENT_TAIL_SIZE
+            + chunk_size
+            + 4;
+        if self.segment_size < minimum {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Segment size must be at least {} bytes", minimum),
+            ));
+        }
+
+        let mut hasher = MultiHasher::new(&self.hashes);
+        let mut segments = Vec::new();
+        let mut segment = Segment::create(&base, 1, &[("header", &header), ("volume", &volume)])?;
+        segments.push(segment.path.clone());
+
+        let mut buffer = vec![0u8; chunk_size as usize];
+        let mut done = 0u64;
+        while done < total {
+            let want = (total - done).min(chunk_size) as usize;
+            source.read_exact(&mut buffer[..want]).map_err(|e| {
+                io::Error::new(e.kind(), format!("Read error at offset {}: {}", done, e))
+            })?;
+            hasher.update(&buffer[..want]);
+
+            // The media is made of whole sectors: pad the last one.
+            let stored_len =
+                (want as u64).div_ceil(self.bytes_per_sector as u64) * self.bytes_per_sector as u64;
+            buffer[want..stored_len as usize].fill(0);
+            let (stored, compressed) = self.store_chunk(&buffer[..stored_len as usize])?;
+
+            if segment.chunk_count() > 0 && !segment.fits(stored.len() as u64, self.segment_size) {
+                segment.finish_next()?;
+                let number = segments.len() as u32 + 1;
+                segment = Segment::create(&base, number, &[("data", &volume)])?;
+                segments.push(segment.path.clone());
+            }
+            segment.add_chunk(&stored, compressed)?;
+
+            done += want as u64;
+            if let Some(callback) = self.progress.as_mut() {
+                callback(done, total);
+            }
+        }
+
+        let hashes = hasher.finalize();
+        let mut acquisition_errors = self.acquisition_errors.clone();
+        if let Some(source) = &self.error_source {
+            acquisition_errors.extend(source());
+        }
+        let errors = self.error2_data(&acquisition_errors)?;
+        segment.finish_done(sessions.as_deref(), errors.as_deref(), &hashes)?;
+
+        info!(
+            "Wrote {} bytes to {} EWF segment(s) at {}",
+            done,
+            segments.len(),
+            base.display()
+        );
+        for (algorithm, digest) in &hashes {
+            info!("  {}: {}", algorithm, digest);
+        }
+        Ok(EwfWriteSummary {
+            segments,
+            bytes_written: done,
+            hashes,
+        })
+    }
+
+    /// Compress `chunk` when it pays off, otherwise store it followed by its
+    /// Adler-32 checksum.
+    fn store_chunk(&self, chunk: &[u8]) -> io::Result<(Vec<u8>, bool)> {
+        let level = match self.compression {
+            EwfCompression::None => None,
+            EwfCompression::Fast => Some(Compression::fast()),
+            EwfCompression::Best => Some(Compression::best()),
+        };
+        if let Some(level) = level {
+            let mut encoder = ZlibEncoder::new(Vec::with_capacity(chunk.len()), level);
+            encoder.write_all(chunk)?;
+            let compressed = encoder.finish()?;
+            if compressed.len() < chunk.len() {
+                return Ok((compressed, true));
+            }
+        }
+        let mut stored = Vec::with_capacity(chunk.len() + 4);
+        stored.extend_from_slice(chunk);
+        stored.extend_from_slice(&adler32(chunk).to_le_bytes());
+        Ok((stored, false))
+    }
+
+    /// Build the zlib-compressed `header` section.
+    fn header_data(&self) -> io::Result<Vec<u8>> {
+        let now = header_date(SystemTime::now());
+        let clean = |s: &str| s.replace(['\t', '\r', '\n'], " ");
+        let text = format!(
+            "1\nmain\nc\tn\ta\te\tt\tav\tov\tm\tu\tp\tr\n{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t0\t{}\n\n",
+            clean(&self.case.case_number),
+            clean(&self.case.evidence_number),
+            clean(&self.case.description),
+            clean(&self.case.examiner),
+            clean(&self.case.notes),
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            now,
+            now,
+            self.compression.header_flag(),
+        );
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(text.as_bytes())?;
+        encoder.finish()
+    }
+
+    /// Build the `error2` section payload, `None` without acquisition errors.
+    fn error2_data(&self, acquisition_errors: &[SectorRange]) -> io::Result<Option<Vec<u8>>> {
+        if acquisition_errors.is_empty() {
+            return Ok(None);
+        }
+        let mut data = vec![0u8; 520];
+        data[..4].copy_from_slice(&(acquisition_errors.len() as u32).to_le_bytes());
+        let checksum = adler32(&data[..516]);
+        data[516..520].copy_from_slice(&checksum.to_le_bytes());
+        let mut entries = Vec::with_capacity(acquisition_errors.len() * 8);
+        for range in acquisition_errors {
+            let (Ok(start), Ok(count)) = (u32::try_from(range.start), u32::try_from(range.count))
+            else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Acquisition error at sector {} does not fit an EWF error2 entry",
+                        range.start
+                    ),
+                ));
+            };
+            entries.extend_from_slice(&start.to_le_bytes());
+            entries.extend_from_slice(&count.to_le_bytes());
+        }
+        let checksum = adler32(&entries);
+        data.extend_from_slice(&entries);
+        data.extend_from_slice(&checksum.to_le_bytes());
+        Ok(Some(data))
+    }
+
+    /// Build the `session` section payload, `None` without sessions.
+    fn session_data(&self) -> io::Result<Option<Vec<u8>>> {
+        if self.sessions.is_empty() {
+            return Ok(None);
+        }
+        let mut data = vec![0u8; 36];
+        data[..4].copy_from_slice(&(self.sessions.len() as u32).to_le_bytes());
+        let checksum = adler32(&data[..32]);
+        data[32..36].copy_from_slice(&checksum.to_le_bytes());
+        let mut entries = vec![0u8; self.sessions.len() * 32];
+        for (session, entry) in self.sessions.iter().zip(entries.chunks_exact_mut(32)) {
+            let start = u32::try_from(session.sectors.start).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Session at sector {} does not fit an EWF session entry",
+                        session.sectors.start
+                    ),
+                )
+            })?;
+            entry[..4].copy_from_slice(&(session.audio as u32).to_le_bytes());
+            entry[4..8].copy_from_slice(&start.to_le_bytes());
+        }
+        let checksum = adler32(&entries);
+        data.extend_from_slice(&entries);
+        data.extend_from_slice(&checksum.to_le_bytes());
+        Ok(Some(data))
+    }
+
+    /// Build the `volume` (and `data`) section payload.
+    fn volume_data(&self, chunk_count: u32, sector_count: u64) -> Vec<u8> {
+        let mut data = vec![0u8; EWF_VOLUME_DATA_SIZE];
+        data[0] = if self.sessions.is_empty() {
+            EWF_MEDIA_TYPE_FIXED
+        } else {
+            EWF_MEDIA_TYPE_OPTICAL
+        };
+        data[4..8].copy_from_slice(&chunk_count.to_le_bytes());
+        data[8..12].copy_from_slice(&self.sectors_per_chunk.to_le_bytes());
+        data[12..16].copy_from_slice(&self.bytes_per_sector.to_le_bytes());
+        data[16..24].copy_from_slice(&sector_count.to_le_bytes());
+        if let Some(geometry) = self.geometry {
+            data[24..28].copy_from_slice(&(geometry.cylinders as u32).to_le_bytes());
+            data[28..32].copy_from_slice(&geometry.heads.to_le_bytes());
+            data[32..36].copy_from_slice(&geometry.sectors_per_track.to_le_bytes());
+        }
+        data[36] = EWF_MEDIA_FLAG_IMAGE;
+        data[52] = match self.compression {
+            EwfCompression::None => 0,
+            EwfCompression::Fast => 1,
+            EwfCompression::Best => 2,
+        };
+        data[56..60].copy_from_slice(&self.sectors_per_chunk.to_le_bytes());
+        data[64..80].copy_from_slice(&set_identifier());
+        let checksum = adler32(&data[..1048]);
+        data[1048..1052].copy_from_slice(&checksum.to_le_bytes());
+        data
+    }
+}
+
+/// An open segment file and its current data group.
+struct Segment {
+    path: PathBuf,
+    out: BufWriter<File>,
+    offset: u64,
+    chunks: usize,
+    group: Option<Group>,
+}
+
+/// A `sectors` section being filled, with its future table entries.
+struct Group {
+    descriptor_offset: u64,
+    data_start: u64,
+    entries: Vec<u32>,
+}
+
+impl Segment {
+    fn create(base: &Path, number: u32, sections: &[(&str, &[u8])]) -> io::Result<Self> {
+        let path = segment_path(base, number)?;
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        let mut segment = Self {
+            path,
+            out: BufWriter::new(file),
+            offset: 0,
+            chunks: 0,
+            group: None,
+        };
+
+        let number = u16::try_from(number)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Too many EWF segments"))?;
+        let mut header = Vec::with_capacity(EWF_FILE_HEADER_SIZE as usize);
+        header.extend_from_slice(&EWF_E01_SIGNATURE);
+        header.push(1);
+        header.extend_from_slice(&number.to_le_bytes());
+        header.extend_from_slice(&[0, 0]);
+        segment.write_raw(&header)?;
+
+        for (kind, data) in sections {
+            segment.write_section(kind, data)?;
+        }
+        Ok(segment)
+    }
+
+    fn chunk_count(&self) -> usize {
+        self.chunks
+    }
+
+    /// Whether a chunk of `stored` bytes still fits below `segment_size`,
+    /// including the sections closing the segment.
+    fn fits(&self, stored: u64, segment_size: u64) -> bool {
+        let entries = self.group.as_ref().map_or(0, |g| g.entries.len()) as u64 + 1;
+        let tables = 2 * (EWF_SECTION_DESCRIPTOR_SIZE + EWF_TABLE_HEADER_SIZE + 4 * entries + 4);
+        let opening = if self.group.is_none() {
+            EWF_SECTION_DESCRIPTOR_SIZE
+        } else {
+            0
+        };
+        self.offset + opening + stored + tables + EWF_SEGMENT_TAIL_SIZE <= segment_size
+    }
+
+    fn add_chunk(&mut self, stored: &[u8], compressed: bool) -> io::Result<()> {
+        if let Some(group) = &self.group {
+            let used = self.offset - group.data_start;
+            if group.entries.len() >= EWF_TABLE_MAX_ENTRIES
+                || used + stored.len() as u64 > EWF_TABLE_MAX_OFFSET
+            {
+                self.close_group()?;
+            }
+        }
+        if self.group.is_none() {
+            let descriptor_offset = self.offset;
+            // Placeholder, rewritten once the group size is known.
+            self.write_raw(&[0u8; EWF_SECTION_DESCRIPTOR_SIZE as usize])?;
+            self.group = Some(Group {
+                descriptor_offset,
+                data_start: self.offset,
+                entries: Vec::new(),
+            });
+        }
+
+        let group = self.group.as_mut().unwrap();
+        let mut entry = (self.offset - group.data_start) as u32;
+        if compressed {
+            entry |= EWF_CHUNK_COMPRESSED;
+        }
+        group.entries.push(entry);
+        self.write_raw(stored)?;
+        self.chunks += 1;
+        Ok(())
+    }
+
+    /// Patch the `sectors` descriptor and write the `table` / `table2` pair.
+    fn close_group(&mut self) -> io::Result<()> {
+        let Some(group) = self.group.take() else {
+            return Ok(());
+        };
+        let descriptor = section_descriptor(
+            "sectors",
+            self.offset,
+            self.offset - group.descriptor_offset,
+        );
+        self.out.seek(SeekFrom::Start(group.descriptor_offset))?;
+        self.out.write_all(&descriptor)?;
+        self.out.seek(SeekFrom::Start(self.offset))?;
+
+        let mut table =
+            Vec::with_capacity(EWF_TABLE_HEADER_SIZE as usize + 4 * group.entries.len() + 4);
+        table.extend_from_slice(&(group.entries.len() as u32).to_le_bytes());
+        table.extend_from_slice(&[0u8; 4]);
+        table.extend_from_slice(&group.data_start.to_le_bytes());
+        table.extend_from_slice(&[0u8; 4]);
+        let checksum = adler32(&table);
+        table.extend_from_slice(&checksum.to_le_bytes());
+        let entries: Vec<u8> = group.entries.iter().flat_map(|e| e.to_le_bytes()).collect();
+        table.extend_from_slice(&entries);
+        table.extend_from_slice(&adler32(&entries).to_le_bytes());
+
+        self.write_section("table", &table)?;
+        self.write_section("table2", &table)
+    }
+
+    /// Close the segment with a `next` section.
+    fn finish_next(mut self) -> io::Result<()> {
+        self.close_group()?;
+        self.write_terminal("next")?;
+        self.sync()
+    }
+
+    /// Close the last segment with its `session` and `error2` (when given),
+    /// `hash`, `digest` and `done` sections.
+    fn finish_done(
+        mut self,
+        sessions: Option<&[u8]>,
+        errors: Option<&[u8]>,
+        hashes: &[(HashAlgorithm, String)],
+    ) -> io::Result<()> {
+        self.close_group()?;
+        if let Some(sessions) = sessions {
+            self.write_section("session", sessions)?;
+        }
+        if let Some(errors) = errors {
+            self.write_section("error2", errors)?;
+        }
+        let digest = |algorithm| {
+            hashes
+                .iter()
+                .find(|(a, _)| *a == algorithm)
+                .map(|(_, hex)| from_hex(hex))
+                .unwrap_or_default()
+        };
+        let md5 = digest(HashAlgorithm::Md5);
+        let sha1 = digest(HashAlgorithm::Sha1);
+
+        let mut hash = vec![0u8; 32];
+        hash[..16].copy_from_slice(&md5);
+        let checksum = adler32(&hash);
+        hash.extend_from_slice(&checksum.to_le_bytes());
+        self.write_section("hash", &hash)?;
+
+        let mut digest = vec![0u8; 76];
+        digest[..16].copy_from_slice(&md5);
+        digest[16..36].copy_from_slice(&sha1);
+        let checksum = adler32(&digest);
+        digest.extend_from_slice(&checksum.to_le_bytes());
+        self.write_section("digest", &digest)?;
+
+        self.write_terminal("done")?;
+        self.sync()
+    }
+
+    fn write_section(&mut self, kind: &str, data: &[u8]) -> io::Result<()> {
+        let size = EWF_SECTION_DESCRIPTOR_SIZE + data.len() as u64;
+        let descriptor = section_descriptor(kind, self.offset + size, size);
+        self.write_raw(&descriptor)?;
+        self.write_raw(data)
+    }
+
+    /// `next` and `done` sections point to themselves.
+    fn write_terminal(&mut self, kind: &str) -> io::Result<()> {
+        let descriptor = section_descriptor(kind, self.offset, EWF_SECTION_DESCRIPTOR_SIZE);
+        self.write_raw(&descriptor)
+    }
+
+    fn write_raw(&mut self, data: &[u8]) -> io::Result<()> {
+        self.out.write_all(data)?;
+        self.offset += data.len() as u64;
+        Ok(())
+    }
+
+    fn sync(self) -> io::Result<()> {
+        self.out
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .sync_all()
+    }
+}
+
+/// Serialize a 76-byte EWF1 section descriptor.
+pub(crate) fn section_descriptor(kind: &str, next: u64, size: u64) -> [u8; 76] {
+    let mut descriptor = [0u8; 76];
+    let name = kind.as_bytes();
+    descriptor[..name.len().min(16)].copy_from_slice(&name[..name.len().min(16)]);
+    descriptor[16..24].copy_from_slice(&next.to_le_bytes());
+    descriptor[24..32].copy_from_slice(&size.to_le_bytes());
+    let checksum = adler32(&descriptor[..72]);
+    descriptor[72..76].copy_from_slice(&checksum.to_le_bytes());
+    descriptor
+}
+
+/// Adler-32 checksum used by EWF1 descriptors, tables and stored chunks.
+pub(crate) fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 is the largest block for which `b` cannot overflow.
+    for block in data.chunks(5552) {
+        for &byte in block {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len() / 2)
+        .filter_map(|i| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok())
+        .collect()
+}
+
+/// Path of segment files without their extension.
+fn segment_base(path: &Path) -> PathBuf {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("e01") => path.with_extension(""),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// `base.E01` … `base.E99`, then `base.EAA` … `base.EZZ`, `base.FAA` … `base.ZZZ`.
+fn segment_path(base: &Path, number: u32) -> io::Result<PathBuf> {
+    let extension = segment_extension("E", number)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Too many EWF segments"))?;
+    let mut name = base.as_os_str().to_owned();
+    name.push(".");
+    name.push(extension);
+    Ok(PathBuf::from(name))
+}
+
+/// `header` date format: `year month day hour minute second` (UTC).
+pub(crate) fn header_date(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rem) = (secs / 86400, secs % 86400);
+
+    // Civil-from-days conversion (proleptic Gregorian calendar).
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{} {} {} {} {} {}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// Segment file set identifier: 16 bytes derived from the clock and process.
+fn set_identifier() -> [u8; 16] {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    let mut state = nanos as u64 ^ ((std::process::id() as u64) << 32);
+    let mut id = [0u8; 16];
+    for chunk in id.chunks_mut(8) {
+        // splitmix64
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        chunk.copy_from_slice(&(z ^ (z >> 31)).to_le_bytes());
+    }
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adler32_matches_reference() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn segment_names_follow_encase() {
+        let base = Path::new("disk");
+        let name = |n| segment_path(base, n).unwrap();
+        assert_eq!(name(1), PathBuf::from("disk.E01"));
+        assert_eq!(name(99), PathBuf::from("disk.E99"));
+        assert_eq!(name(100), PathBuf::from("disk.EAA"));
+        assert_eq!(name(126), PathBuf::from("disk.EBA"));
+        assert_eq!(name(100 + 26 * 26), PathBuf::from("disk.FAA"));
+        assert_eq!(
+            segment_extension("E", 100 + 22 * 26 * 26 - 1),
+            Some("ZZZ".into())
+        );
+        assert_eq!(segment_extension("E", 100 + 22 * 26 * 26), None);
+        assert_eq!(segment_extension("Lx", 7), Some("Lx07".into()));
+        assert_eq!(segment_extension("Ex", 101), Some("ExAB".into()));
+    }
+
+    #[test]
+    fn header_date_is_utc_civil_time() {
+        let time = UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+        assert_eq!(header_date(time), "2023 11 14 22 13 20");
+    }
+}
@@ -0,0 +1,571 @@
+//! **Gzip-compressed RAW reader**
+//!
+//! This module gives seekable access to raw disk images that were shipped
+//! through `gzip` (`disk.raw.gz`, `image.dd.gz`, …) without unpacking them to
+//! disk first.
+//!
+//! # On-disk layout
+//!
+//! | Offset | Size | Description                                        |
+//! |--------|------|----------------------------------------------------|
+//! | `0x00` | 2    | Magic `1F 8B`                                      |
+//! | `0x02` | 1    | Compression method (8 = deflate)                   |
+//! | `0x03` | 1    | Flags (`FTEXT`, `FHCRC`, `FEXTRA`, `FNAME`, …)     |
+//! | `0x04` | 4    | Modification time                                  |
+//! | `0x08` | 1    | Extra flags                                        |
+//! | `0x09` | 1    | Operating system                                   |
+//! | `0x0A` | …    | Optional extra field, file name, comment, CRC16    |
+//! | …      | …    | Raw deflate stream                                 |
+//! | end-8  | 4    | CRC32 of the uncompressed member                   |
+//! | end-4  | 4    | Uncompressed size modulo 2^32 (`ISIZE`)            |
+//!
+//! Several members may be concatenated; they decompress to one stream.
+//!
+//! Deflate is not seekable by itself, so the reader builds an **access index**
+//! while it decompresses: every [`GZIP_INDEX_SPAN`] bytes of output it stores a
+//! checkpoint holding the inflater state, the last 32 KiB of output (the
+//! deflate window) and the matching compressed offset.  A later seek resumes
+//! from the nearest checkpoint instead of restarting at the beginning.  The
+//! index grows lazily, so opening an image is cheap; the uncompressed size is
+//! only known once the stream has been read to its end (seeking from the end
+//! triggers that pass).
+//!
+//! Refer to RFC 1952 for the reference definition.
+
+use crate::error::ExhumeBodyError;
+use crate::info::FormatInfo;
+use crate::read_at::ReadAt;
+use crate::source::Source;
+use crate::vfs::{FileSystem, StdFileSystem};
+use log::{debug, warn};
+use miniz_oxide::inflate::core::inflate_flags::TINFL_FLAG_HAS_MORE_INPUT;
+use miniz_oxide::inflate::core::{decompress, DecompressorOxide};
+use miniz_oxide::inflate::TINFLStatus;
+use std::cmp::min;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+// ---- Gzip constants ---------------------------------------------------------
+
+/// Member magic followed by the deflate compression method.
+const GZIP_MAGIC: [u8; 3] = [0x1F, 0x8B, 0x08];
+
+/// Size of the fixed part of a member header.
+const GZIP_HEADER_SIZE: usize = 10;
+
+/// Size of the CRC32 + ISIZE member trailer.
+const GZIP_TRAILER_SIZE: u64 = 8;
+
+/// Upper bound on a member header (extra field, name and comment included).
+const GZIP_MAX_HEADER_SIZE: usize = 64 * 1024;
+
+/// Header flags (RFC 1952, section 2.3.1).
+const GZIP_FLAG_FHCRC: u8 = 0x02;
+const GZIP_FLAG_FEXTRA: u8 = 0x04;
+const GZIP_FLAG_FNAME: u8 = 0x08;
+const GZIP_FLAG_FCOMMENT: u8 = 0x10;
+
+/// Deflate window size; also the size of the wrapping output buffer.
+const GZIP_WINDOW_SIZE: usize = 32 * 1024;
+
+/// Distance in uncompressed bytes between two index checkpoints.
+pub const GZIP_INDEX_SPAN: u64 = 32 * 1024 * 1024;
+
+/// Amount of compressed data fed to the inflater per call.
+const GZIP_INPUT_CHUNK: usize = 64 * 1024;
+
+/// Gzip does not record a sector size; assume 512-byte sectors.
+const GZIP_SECTOR_SIZE: u16 = 512;
+
+// ---- Member header ----------------------------------------------------------
+
+/// Fields of interest from a member header.
+#[derive(Clone, Debug, Default)]
+struct GzipMember {
+    /// Total header length in bytes (offset of the deflate stream).
+    header_size: u64,
+    /// Modification time (seconds since the epoch, 0 if unset).
+    mtime: u32,
+    /// Original file name (`FNAME`), if recorded.
+    name: Option<String>,
+}
+
+/// Read a NUL-terminated Latin-1 string starting at `off`.
+fn read_cstring(data: &[u8], off: usize) -> Option<(String, usize)> {
+    let end = data[off..].iter().position(|&b| b == 0)? + off;
+    let value = data[off..end].iter().map(|&b| b as char).collect();
+    Some((value, end + 1))
+}
+
+/// Parse the member header at `offset`.
+///
+/// Returns `Ok(None)` when no further member starts there (end of file, or
+/// trailing padding that some tools append after the last member).
+fn parse_member(file: &impl ReadAt, offset: u64) -> io::Result<Option<GzipMember>> {
+    let mut data = vec![0u8; GZIP_MAX_HEADER_SIZE];
+    let mut filled = 0;
+    while filled < data.len() {
+        match file.read_at(offset + filled as u64, &mut data[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    data.truncate(filled);
+
+    if data.len() < GZIP_HEADER_SIZE || data[0..3] != GZIP_MAGIC {
+        return Ok(None);
+    }
+
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Truncated gzip member header");
+    let flags = data[3];
+    let mtime = u32::from_le_bytes(data[4..8].try_into().unwrap());
+    let mut off = GZIP_HEADER_SIZE;
+    let mut name = None;
+
+    if flags & GZIP_FLAG_FEXTRA != 0 {
+        let xlen = data
+            .get(off..off + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+            .ok_or_else(invalid)?;
+        off += 2 + xlen;
+    }
+    if flags & GZIP_FLAG_FNAME != 0 {
+        let (value, next) = data
+            .get(off..)
+            .and_then(|_| read_cstring(&data, off))
+            .ok_or_else(invalid)?;
+        name = Some(value);
+        off = next;
+    }
+    if flags & GZIP_FLAG_FCOMMENT != 0 {
+        let (_, next) = data
+            .get(off..)
+            .and_then(|_| read_cstring(&data, off))
+            .ok_or_else(invalid)?;
+        off = next;
+    }
+    if flags & GZIP_FLAG_FHCRC != 0 {
+        off += 2;
+    }
+    if off > data.len() {
+        return Err(invalid());
+    }
+
+    Ok(Some(GzipMember {
+        header_size: off as u64,
+        mtime,
+        name,
+    }))
+}
+
+// ---- Inflater state ---------------------------------------------------------
+
+/// Resumable position inside the compressed stream.
+///
+/// A clone of this structure is an index checkpoint: restoring it and feeding
+/// the compressed bytes from `in_offset` onwards reproduces the output from
+/// `out_offset` onwards.
+#[derive(Clone)]
+struct InflateState {
+    /// Inflater state of the current member.
+    decompressor: Box<DecompressorOxide>,
+    /// Wrapping output buffer holding the last [`GZIP_WINDOW_SIZE`] bytes.
+    window: Box<[u8]>,
+    /// Next write position inside `window`.
+    window_pos: usize,
+    /// Compressed offset of the next input byte.
+    in_offset: u64,
+    /// Uncompressed offset of the next output byte.
+    out_offset: u64,
+    /// `false` when positioned on a member header rather than inside a member.
+    in_member: bool,
+}
+
+impl InflateState {
+    fn new() -> Self {
+        Self {
+            decompressor: Box::default(),
+            window: vec![0u8; GZIP_WINDOW_SIZE].into_boxed_slice(),
+            window_pos: 0,
+            in_offset: 0,
+            out_offset: 0,
+            in_member: false,
+        }
+    }
+
+    /// Number of bytes before `out_offset` still available in `window`.
+    fn history(&self) -> u64 {
+        min(GZIP_WINDOW_SIZE as u64, self.out_offset)
+    }
+}
+
+// ---- Public gzip reader -----------------------------------------------------
+
+/// Seekable reader over a gzip-compressed raw image.
+///
+/// Implements [`Read`], [`Seek`] and [`Clone`] so it can be used as a drop-in
+/// source of evidence bytes inside the [`Body`](crate::Body) abstraction.
+pub struct GZIP {
+    /// Open file handle to the `.gz` file.
+    file: Source,
+    /// Original path (kept for display).
+    path: String,
+    /// Size of the compressed file.
+    compressed_size: u64,
+    /// Header of the first member.
+    first_member: GzipMember,
+    /// `ISIZE` field of the last member (size modulo 2^32, a hint only).
+    size_hint: u32,
+    /// Checkpoints ordered by `out_offset`; the first one is the stream start.
+    index: Vec<InflateState>,
+    /// Live inflater state.
+    cursor: InflateState,
+    /// Compressed bytes buffered for the cursor.
+    input: Vec<u8>,
+    /// Compressed offset of `input[0]`.
+    input_offset: u64,
+    /// Uncompressed size, known once the end of the stream has been reached.
+    size: Option<u64>,
+    /// Virtual cursor position inside the uncompressed stream.
+    position: u64,
+}
+
+impl GZIP {
+    // ---- Construction -------------------------------------------------------
+
+    /// Open a gzip-compressed image.
+    ///
+    /// Only the first member header and the trailer are read here; the access
+    /// index is built as the stream gets decompressed.
+    pub fn new(file_path: &str) -> Result<GZIP, ExhumeBodyError> {
+        Self::open_in(&StdFileSystem, file_path)
+    }
+
+    /// Same as [`GZIP::new`], opening the image through `fs` instead of the
+    /// operating system.
+    pub fn open_in(fs: &dyn FileSystem, file_path: &str) -> Result<GZIP, ExhumeBodyError> {
+        Self::new_impl(fs, file_path).map_err(ExhumeBodyError::Gzip)
+    }
+
+    fn new_impl(fs: &dyn FileSystem, file_path: &str) -> Result<GZIP, String> {
+        let path = Path::new(file_path);
+        let mut file = fs
+            .open(path)
+            .map_err(|e| format!("Error opening gzip image: {}", e))?;
+
+        let first_member = parse_member(&file, 0)
+            .map_err(|e| format!("Error reading gzip header: {}", e))?
+            .ok_or_else(|| "Invalid gzip signature (expected 1F 8B 08)".to_string())?;
+        debug!("Parsed gzip member header: {:?}", first_member);
+
+        let compressed_size = file
+            .seek(SeekFrom::End(0))
+            .map_err(|e| format!("Error reading gzip image size: {}", e))?;
+        let mut trailer = [0u8; 4];
+        if compressed_size >= GZIP_HEADER_SIZE as u64 + GZIP_TRAILER_SIZE {
+            file.seek(SeekFrom::End(-4))
+                .and_then(|_| file.read_exact(&mut trailer))
+                .map_err(|e| format!("Error reading gzip trailer: {}", e))?;
+        }
+
+        let start = InflateState::new();
+        Ok(GZIP {
+            file,
+            path: file_path.to_string(),
+            compressed_size,
+            first_member,
+            size_hint: u32::from_le_bytes(trailer),
+            index: vec![start.clone()],
+            cursor: start,
+            input: Vec::new(),
+            input_offset: 0,
+            size: None,
+            position: 0,
+        })
+    }
+
+    // ---- Info helpers -------------------------------------------------------
+
+    /// Compressed and decompressed sizes, member header and index size.
+    pub fn info(&self) -> FormatInfo {
+        let size = match self.size {
+            Some(size) => format!("{} bytes", size),
+            None => format!(
+                "unknown until fully read (ISIZE hint {} bytes)",
+                self.size_hint
+            ),
+        };
+        let mut info = FormatInfo::new("Gzip Image Information")
+            .field("Path", &self.path)
+            .field("Compressed", format!("{} bytes", self.compressed_size))
+            .field("Size", size)
+            .field("Sector Size", GZIP_SECTOR_SIZE);
+        if let Some(name) = &self.first_member.name {
+            info = info.field("Original Name", name);
+        }
+        info.field(
+            "Modified",
+            format!("{} (unix time)", self.first_member.mtime),
+        )
+        .field("Index Points", self.index.len())
+    }
+
+    /// Print parsed metadata to the log.
+    pub fn print_info(&self) {
+        self.info().log();
+    }
+
+    /// Returns the sector size (always 512 for gzip images).
+    pub fn get_sector_size(&self) -> u16 {
+        GZIP_SECTOR_SIZE
+    }
+
+    /// Uncompressed size of the image.
+    ///
+    /// Decompresses the remainder of the stream the first time it is called.
+    pub fn size(&mut self) -> io::Result<u64> {
+        if let Some(size) = self.size {
+            return Ok(size);
+        }
+        let last = self.index.len() - 1;
+        if self.cursor.out_offset < self.index[last].out_offset {
+            self.restore(last);
+        }
+        while self.step()? {}
+        Ok(self.cursor.out_offset)
+    }
+
+    // ---- Internal decompression ---------------------------------------------
+
+    /// Replace the cursor with index checkpoint `idx`.
+    fn restore(&mut self, idx: usize) {
+        self.cursor = self.index[idx].clone();
+        self.input.clear();
+        self.input_offset = self.cursor.in_offset;
+    }
+
+    /// Move the cursor so that it is at or before `target` and either the
+    /// window still holds `target` or no checkpoint lies closer.
+    fn position_cursor(&mut self, target: u64) {
+        let idx = self.index.partition_point(|c| c.out_offset <= target) - 1;
+        let behind_cursor = target < self.cursor.out_offset;
+        if behind_cursor && self.cursor.out_offset - target <= self.cursor.history() {
+            return;
+        }
+        if !behind_cursor && self.index[idx].out_offset <= self.cursor.out_offset {
+            return;
+        }
+        self.restore(idx);
+    }
+
+    /// Load compressed bytes starting at the cursor's input offset.
+    fn refill(&mut self) -> io::Result<()> {
+        self.input.resize(GZIP_INPUT_CHUNK, 0);
+        let mut filled = 0;
+        while filled < self.input.len() {
+            let offset = self.cursor.in_offset + filled as u64;
+            match self.file.read_at(offset, &mut self.input[filled..])? {
+                0 => break,
+                n => filled += n,
+            }
+        }
+        self.input.truncate(filled);
+        self.input_offset = self.cursor.in_offset;
+        Ok(())
+    }
+
+    /// Run the inflater until it produces output.
+    ///
+    /// Returns `Ok(false)` at the end of the stream, in which case the
+    /// uncompressed size becomes known.
+    fn step(&mut self) -> io::Result<bool> {
+        loop {
+            if !self.cursor.in_member {
+                match parse_member(&self.file, self.cursor.in_offset)? {
+                    Some(member) => {
+                        self.cursor.in_offset += member.header_size;
+                        self.cursor.decompressor.init();
+                        self.cursor.in_member = true;
+                        self.input.clear();
+                    }
+                    None => {
+                        self.size = Some(self.cursor.out_offset);
+                        return Ok(false);
+                    }
+                }
+            }
+
+            let consumed = (self.cursor.in_offset - self.input_offset) as usize;
+            if consumed >= self.input.len() {
+                self.refill()?;
+            }
+            let start = (self.cursor.in_offset - self.input_offset) as usize;
+            let at_eof = self.input_offset + (self.input.len() as u64) >= self.compressed_size;
+            let flags = if at_eof { 0 } else { TINFL_FLAG_HAS_MORE_INPUT };
+
+            let (status, read, written) = decompress(
+                &mut self.cursor.decompressor,
+                &self.input[start..],
+                &mut self.cursor.window,
+                self.cursor.window_pos,
+                flags,
+            );
+            self.cursor.in_offset += read as u64;
+            self.cursor.window_pos = (self.cursor.window_pos + written) & (GZIP_WINDOW_SIZE - 1);
+            self.cursor.out_offset += written as u64;
+
+            match status {
+                TINFLStatus::Done => {
+                    self.cursor.in_offset += GZIP_TRAILER_SIZE;
+                    self.cursor.in_member = false;
+                }
+                TINFLStatus::NeedsMoreInput | TINFLStatus::FailedCannotMakeProgress if at_eof => {
+                    warn!(
+                        "Gzip stream is truncated at compressed offset {}",
+                        self.cursor.in_offset
+                    );
+                    self.cursor.in_member = false;
+                    self.cursor.in_offset = self.compressed_size;
+                }
+                TINFLStatus::NeedsMoreInput | TINFLStatus::HasMoreOutput => {}
+                failure => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Corrupt deflate data at compressed offset {} ({:?})",
+                            self.cursor.in_offset, failure
+                        ),
+                    ));
+                }
+            }
+
+            if written > 0 {
+                let last = self.index.last().map_or(0, |c| c.out_offset);
+                if self.cursor.out_offset >= last + GZIP_INDEX_SPAN {
+                    debug!(
+                        "Adding gzip index point at {} (compressed {})",
+                        self.cursor.out_offset, self.cursor.in_offset
+                    );
+                    self.index.push(self.cursor.clone());
+                }
+                return Ok(true);
+            }
+        }
+    }
+}
+
+// ---- Clone ------------------------------------------------------------------
+
+impl Clone for GZIP {
+    fn clone(&self) -> Self {
+        Self {
+            file: self.file.clone(),
+            path: self.path.clone(),
+            compressed_size: self.compressed_size,
+            first_member: self.first_member.clone(),
+            size_hint: self.size_hint,
+            index: self.index.clone(),
+            cursor: self.cursor.clone(),
+            // Reset the input buffer – will be lazily refilled.
+            input: Vec::new(),
+            input_offset: self.cursor.in_offset,
+            size: self.size,
+            position: self.position,
+        }
+    }
+}
+
+// ---- Read -------------------------------------------------------------------
+
+impl Read for GZIP {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.size.is_some_and(|size| self.position >= size) {
+            return Ok(0);
+        }
+
+        self.position_cursor(self.position);
+        while self.cursor.out_offset <= self.position {
+            if !self.step()? {
+                return Ok(0);
+            }
+        }
+
+        // The requested bytes are now the tail of the window.
+        let behind = (self.cursor.out_offset - self.position) as usize;
+        let len = min(buf.len(), behind);
+        let start = (self.cursor.window_pos + GZIP_WINDOW_SIZE - behind) & (GZIP_WINDOW_SIZE - 1);
+        let first = min(len, GZIP_WINDOW_SIZE - start);
+        buf[..first].copy_from_slice(&self.cursor.window[start..start + first]);
+        buf[first..len].copy_from_slice(&self.cursor.window[..len - first]);
+
+        self.position += len as u64;
+        Ok(len)
+    }
+}
+
+// ---- Seek -------------------------------------------------------------------
+
+impl Seek for GZIP {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let next = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.size()?.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Seek before start or overflow")
+        })?;
+
+        self.position = next;
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use flate2::write::GzEncoder;
+    use flate2::{Compression, GzBuilder};
+    use std::io::Write;
+
+    #[test]
+    fn reads_and_seeks_across_concatenated_members() {
+        let data: Vec<u8> = (0..160 * 1024u32)
+            .map(|i| (i.wrapping_mul(31) >> 3) as u8)
+            .collect();
+        let (head, tail) = data.split_at(100 * 1024);
+
+        let mut first = GzBuilder::new()
+            .filename("disk.raw")
+            .write(Vec::new(), Compression::fast());
+        first.write_all(head).unwrap();
+        let mut image = first.finish().unwrap();
+        let mut second = GzEncoder::new(Vec::new(), Compression::best());
+        second.write_all(tail).unwrap();
+        image.extend(second.finish().unwrap());
+
+        let dir = TempDir::new("gzip");
+        let path = dir.join("disk.raw.gz");
+        std::fs::write(&path, image).unwrap();
+        let mut gzip = GZIP::new(&path.to_string_lossy()).unwrap();
+
+        let mut back = Vec::new();
+        gzip.read_to_end(&mut back).unwrap();
+        assert_eq!(back, data);
+        assert_eq!(gzip.size().unwrap(), data.len() as u64);
+
+        // Across the member boundary, then back towards the start.
+        for offset in [head.len() as u64 - 100, 4096] {
+            gzip.seek(SeekFrom::Start(offset)).unwrap();
+            let mut buf = [0u8; 200];
+            gzip.read_exact(&mut buf).unwrap();
+            assert_eq!(buf[..], data[offset as usize..offset as usize + 200]);
+        }
+
+        gzip.seek(SeekFrom::End(-10)).unwrap();
+        let mut rest = Vec::new();
+        gzip.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, data[data.len() - 10..]);
+    }
+}
@@ -0,0 +1,164 @@
+//! Independent cursors over one body, see [`Body::handles`].
+//!
+//! A filesystem walker handing files to worker threads needs one reader per
+//! worker. Cloning the body for each works, but duplicates file handles and
+//! per-reader caches; a [`BodyHandle`] is only a position over the body that
+//! all handles share, read through [`ReadAt`].
+//!
+//! ```no_run
+//! # use exhume_body::Body;
+//! # use std::io::{Read, Seek, SeekFrom};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut body = Body::try_new("/evidence/disk.E01".to_string(), "auto")?;
+//! let workers: Vec<_> = body
+//!     .handles(8)?
+//!     .into_iter()
+//!     .map(|mut handle| {
+//!         std::thread::spawn(move || {
+//!             let mut sector = [0u8; 512];
+//!             handle.seek(SeekFrom::Start(0x100000))?;
+//!             handle.read_exact(&mut sector)
+//!         })
+//!     })
+//!     .collect();
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::read_at::ReadAt;
+use crate::Body;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::Arc;
+
+#[derive(Clone)]
+enum Inner {
+    /// Positional reads on the body every handle shares.
+    Shared(Arc<Body>),
+    /// A clone of its own, for the formats that decode through a cache of
+    /// their own and would otherwise clone their backend on every read.
+    Own(Box<Body>),
+}
+
+/// A cursor over a body, made by [`Body::handles`].
+///
+/// Implements [`Read`], [`Seek`] and [`ReadAt`]; handles are `Send`, so each
+/// can move to its own thread.
+#[derive(Clone)]
+pub struct BodyHandle {
+    inner: Inner,
+    position: u64,
+    size: u64,
+}
+
+impl BodyHandle {
+    /// Handles on `body`: `n` positions over one shared clone when the
+    /// format reads natively at an offset, `n` clones otherwise.
+    pub(crate) fn split(body: &mut Body, n: usize) -> io::Result<Vec<Self>> {
+        let size = body.size()?;
+        let handle = |inner| Self {
+            inner,
+            position: 0,
+            size,
+        };
+        if body.reads_natively_at() {
+            let shared = Arc::new(body.clone());
+            Ok((0..n)
+                .map(|_| handle(Inner::Shared(shared.clone())))
+                .collect())
+        } else {
+            Ok((0..n)
+                .map(|_| handle(Inner::Own(Box::new(body.clone()))))
+                .collect())
+        }
+    }
+
+    /// The body read by the handle.
+    pub fn body(&self) -> &Body {
+        match &self.inner {
+            Inner::Shared(body) => body,
+            Inner::Own(body) => body,
+        }
+    }
+
+    /// Size of the body in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl Read for BodyHandle {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = match &mut self.inner {
+            Inner::Shared(body) => body.read_at(self.position, buf)?,
+            Inner::Own(body) => {
+                body.seek(SeekFrom::Start(self.position))?;
+                body.read(buf)?
+            }
+        };
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for BodyHandle {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let next = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.size.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Seek before start or overflow")
+        })?;
+        self.position = next;
+        Ok(self.position)
+    }
+}
+
+impl ReadAt for BodyHandle {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.body().read_at(offset, buf)
+    }
+
+    fn read_at_append(&self, offset: u64, buf: &mut Vec<u8>, len: usize) -> io::Result<usize> {
+        self.body().read_at_append(offset, buf, len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use std::thread;
+
+    #[test]
+    fn handles_read_in_parallel() {
+        let dir = TempDir::new("handles");
+        let path = dir.join("handles.raw");
+        let data: Vec<u8> = (0..64 * 1024u32).map(|i| (i % 239) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+        let mut body = Body::try_new(path.to_string_lossy().into(), "raw").unwrap();
+
+        let handles = body.handles(4).unwrap();
+        assert!(matches!(handles[0].inner, Inner::Shared(_)));
+        let data = Arc::new(data);
+        let workers: Vec<_> = handles
+            .into_iter()
+            .enumerate()
+            .map(|(worker, mut handle)| {
+                let data = data.clone();
+                thread::spawn(move || {
+                    let start = worker as u64 * 16 * 1024;
+                    handle.seek(SeekFrom::Start(start)).unwrap();
+                    let mut buf = vec![0u8; 16 * 1024];
+                    handle.read_exact(&mut buf).unwrap();
+                    assert_eq!(buf, data[start as usize..start as usize + buf.len()]);
+                    assert_eq!(handle.stream_position().unwrap(), start + 16 * 1024);
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+    }
+}
@@ -0,0 +1,385 @@
+//! Digests computed while streaming evidence.
+
+use md5::{Digest, Md5};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha1::Sha1;
+use sha2::Sha256;
+use std::fmt;
+use std::io::{self, Read};
+use std::str::FromStr;
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+/// Blocks queued for each digest thread of [`digest_stream`].
+const HASH_QUEUE_DEPTH: usize = 4;
+
+/// Hash algorithms supported when streaming a body.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+impl HashAlgorithm {
+    /// Lower-case name, as accepted by [`FromStr`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Md5 => "md5",
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Sha256 => "sha256",
+        }
+    }
+
+    /// Length of a digest in bytes.
+    pub fn digest_len(&self) -> usize {
+        match self {
+            HashAlgorithm::Md5 => 16,
+            HashAlgorithm::Sha1 => 20,
+            HashAlgorithm::Sha256 => 32,
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Serialized as its [`name`](HashAlgorithm::name).
+impl Serialize for HashAlgorithm {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// Deserialized from any name [`FromStr`] accepts.
+impl<'de> Deserialize<'de> for HashAlgorithm {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('-', "").as_str() {
+            "md5" => Ok(HashAlgorithm::Md5),
+            "sha1" => Ok(HashAlgorithm::Sha1),
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            _ => Err(format!(
+                "Unknown hash algorithm '{}' (expected md5, sha1 or sha256)",
+                s
+            )),
+        }
+    }
+}
+
+enum Digester {
+    Md5(Md5),
+    Sha1(Sha1),
+    Sha256(Sha256),
+}
+
+/// Computes several digests over the same stream in one pass.
+pub struct MultiHasher {
+    digesters: Vec<(HashAlgorithm, Digester)>,
+}
+
+impl MultiHasher {
+    pub fn new(algorithms: &[HashAlgorithm]) -> Self {
+        let mut digesters = Vec::with_capacity(algorithms.len());
+        for &algorithm in algorithms {
+            if digesters.iter().any(|(a, _)| *a == algorithm) {
+                continue;
+            }
+            let digester = match algorithm {
+                HashAlgorithm::Md5 => Digester::Md5(Md5::new()),
+                HashAlgorithm::Sha1 => Digester::Sha1(Sha1::new()),
+                HashAlgorithm::Sha256 => Digester::Sha256(Sha256::new()),
+            };
+            digesters.push((algorithm, digester));
+        }
+        Self { digesters }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.digesters.is_empty()
+    }
+
+    /// The algorithms computed, without duplicates.
+    pub fn algorithms(&self) -> Vec<HashAlgorithm> {
+        self.digesters.iter().map(|(a, _)| *a).collect()
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for (_, digester) in &mut self.digesters {
+            match digester {
+                Digester::Md5(d) => d.update(data),
+                Digester::Sha1(d) => d.update(data),
+                Digester::Sha256(d) => d.update(data),
+            }
+        }
+    }
+
+    /// Lower-case hex digests, in the order the algorithms were requested.
+    pub fn finalize(self) -> Vec<(HashAlgorithm, String)> {
+        self.digesters
+            .into_iter()
+            .map(|(algorithm, digester)| {
+                let digest = match digester {
+                    Digester::Md5(d) => d.finalize().to_vec(),
+                    Digester::Sha1(d) => d.finalize().to_vec(),
+                    Digester::Sha256(d) => d.finalize().to_vec(),
+                };
+                (algorithm, to_hex(&digest))
+            })
+            .collect()
+    }
+}
+
+/// A reader that digests everything read through it, so that a
+/// [`Body`](crate::Body) or [`BodySlice`](crate::BodySlice) can be extracted
+/// (or copied to a verifier) and hashed in the same pass.
+///
+/// ```no_run
+/// # use exhume_body::{Body, BodySlice, HashAlgorithm, HashingReader};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let body = Body::try_new("disk.E01".into(), "auto")?;
+/// let partition = BodySlice::new(&body, 1_048_576, 512 * 1_024_000)?;
+/// let mut reader = HashingReader::new(partition, &[HashAlgorithm::Md5, HashAlgorithm::Sha256]);
+/// std::io::copy(&mut reader, &mut std::fs::File::create("partition.raw")?)?;
+/// for (algorithm, digest) in reader.finalize() {
+///     println!("{}: {}", algorithm, digest);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Only the bytes returned by [`Read::read`] are digested, in the order they
+/// are read: the result is the digest of the stream read through the
+/// wrapper, whatever position the inner reader started at.
+pub struct HashingReader<R> {
+    inner: R,
+    hasher: MultiHasher,
+    bytes_hashed: u64,
+}
+
+impl<R: Read> HashingReader<R> {
+    pub fn new(inner: R, algorithms: &[HashAlgorithm]) -> Self {
+        Self {
+            inner,
+            hasher: MultiHasher::new(algorithms),
+            bytes_hashed: 0,
+        }
+    }
+
+    /// Bytes digested so far.
+    pub fn bytes_hashed(&self) -> u64 {
+        self.bytes_hashed
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// The wrapped reader. What is read or skipped through it directly is
+    /// not digested.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Lower-case hex digests of the bytes read, in the order the
+    /// algorithms were requested.
+    pub fn finalize(self) -> Vec<(HashAlgorithm, String)> {
+        self.hasher.finalize()
+    }
+
+    /// The wrapped reader and the digests of the bytes read.
+    pub fn into_parts(self) -> (R, Vec<(HashAlgorithm, String)>) {
+        (self.inner, self.hasher.finalize())
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.bytes_hashed += n as u64;
+        Ok(n)
+    }
+}
+
+/// Digest `total` bytes of `reader` in `block_size` blocks. With
+/// `parallel`, each algorithm runs on its own thread fed with the blocks,
+/// so the reader (typically a [`ReadAhead`](crate::ReadAhead) doing the IO
+/// and decompression) and every digest proceed concurrently.
+pub(crate) fn digest_stream(
+    reader: &mut impl Read,
+    total: u64,
+    algorithms: &[HashAlgorithm],
+    block_size: usize,
+    parallel: bool,
+    mut progress: impl FnMut(u64, u64),
+) -> io::Result<Vec<(HashAlgorithm, String)>> {
+    let mut read_block = |done: u64| -> io::Result<Vec<u8>> {
+        let want = (total - done).min(block_size as u64) as usize;
+        let mut block = vec![0u8; want];
+        let n = reader.read(&mut block)?;
+        if n == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("Body ended at {} bytes, {} expected", done, total),
+            ));
+        }
+        block.truncate(n);
+        Ok(block)
+    };
+
+    if !parallel {
+        let mut hasher = MultiHasher::new(algorithms);
+        let mut done = 0u64;
+        while done < total {
+            let block = read_block(done)?;
+            hasher.update(&block);
+            done += block.len() as u64;
+            progress(done, total);
+        }
+        return Ok(hasher.finalize());
+    }
+
+    thread::scope(|scope| {
+        let mut queues = Vec::new();
+        let mut workers = Vec::new();
+        for algorithm in MultiHasher::new(algorithms).algorithms() {
+            let (queue, blocks) = mpsc::sync_channel::<Arc<Vec<u8>>>(HASH_QUEUE_DEPTH);
+            workers.push(scope.spawn(move || {
+                let mut hasher = MultiHasher::new(&[algorithm]);
+                for block in blocks {
+                    hasher.update(&block);
+                }
+                hasher.finalize()
+            }));
+            queues.push(queue);
+        }
+
+        let mut done = 0u64;
+        while done < total {
+            let block = Arc::new(read_block(done)?);
+            done += block.len() as u64;
+            for queue in &queues {
+                // A worker only hangs up by panicking, reported below.
+                let _ = queue.send(block.clone());
+            }
+            progress(done, total);
+        }
+        drop(queues);
+
+        let mut digests = Vec::new();
+        for worker in workers {
+            let digest = worker
+                .join()
+                .map_err(|_| io::Error::other("A hashing thread panicked"))?;
+            digests.extend(digest);
+        }
+        Ok(digests)
+    })
+}
+
+/// Lower-case hex rendering of `bytes`.
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use crate::Body;
+    use std::io::{Seek, SeekFrom};
+
+    #[test]
+    fn hashes_a_body_in_one_pass() {
+        let dir = TempDir::new("hash");
+        let path = dir.join("hash.raw");
+        std::fs::write(&path, b"abc").unwrap();
+
+        let mut body = Body::try_new(path.to_string_lossy().to_string(), "raw").unwrap();
+        body.seek(SeekFrom::Start(1)).unwrap();
+        let mut calls = Vec::new();
+        let digests = body
+            .hash(&[HashAlgorithm::Sha1, HashAlgorithm::Md5], |done, total| {
+                calls.push((done, total))
+            })
+            .unwrap();
+        assert_eq!(
+            digests,
+            vec![
+                (
+                    HashAlgorithm::Sha1,
+                    "a9993e364706816aba3e25717850c26c9cd0d89d".to_string()
+                ),
+                (
+                    HashAlgorithm::Md5,
+                    "900150983cd24fb0d6963f7d28e17f72".to_string()
+                ),
+            ]
+        );
+        assert_eq!(calls, vec![(3, 3)]);
+        assert_eq!(body.stream_position().unwrap(), 1);
+    }
+
+    #[test]
+    fn hashes_what_flows_through() {
+        let dir = TempDir::new("hashing");
+        let path = dir.join("hashing.raw");
+        std::fs::write(&path, b"--abc--").unwrap();
+        let body = Body::try_new(path.to_string_lossy().to_string(), "raw").unwrap();
+
+        let slice = crate::BodySlice::new(&body, 2, 3).unwrap();
+        let mut reader = HashingReader::new(slice, &[HashAlgorithm::Md5]);
+        let mut copy = Vec::new();
+        io::copy(&mut reader, &mut copy).unwrap();
+        assert_eq!(copy, b"abc");
+        assert_eq!(reader.bytes_hashed(), 3);
+        assert_eq!(
+            reader.finalize(),
+            vec![(
+                HashAlgorithm::Md5,
+                "900150983cd24fb0d6963f7d28e17f72".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn threaded_hashing_matches_the_single_thread() {
+        let dir = TempDir::new("mt_hash");
+        let path = dir.join("mt_hash.raw");
+        let data: Vec<u8> = (0..3 * 1024 * 1024 + 4096u32)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        std::fs::write(&path, &data).unwrap();
+        let algorithms = [
+            HashAlgorithm::Sha256,
+            HashAlgorithm::Md5,
+            HashAlgorithm::Sha1,
+        ];
+
+        let mut body = Body::try_new(path.to_string_lossy().to_string(), "raw").unwrap();
+        let expected = body.hash(&algorithms, |_, _| {}).unwrap();
+        let mut body = Body::builder(path.to_string_lossy())
+            .hash_threads(3)
+            .build()
+            .unwrap();
+        let mut last = 0;
+        assert_eq!(
+            body.hash(&algorithms, |done, _| last = done).unwrap(),
+            expected
+        );
+        assert_eq!(last, data.len() as u64);
+    }
+}
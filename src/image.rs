@@ -0,0 +1,131 @@
+//! The [`BodyImage`] trait every image backend implements.
+//!
+//! Built-in formats are listed in [`BodyFormat`](crate::BodyFormat); other
+//! crates implement the trait for their own reader and make it available to
+//! [`Body`](crate::Body) through [`register_format`](crate::registry::register_format).
+
+use crate::aff::AFF;
+use crate::aff4::AFF4;
+use crate::device::DEVICE;
+use crate::dmg::DMG;
+use crate::ewf::EWF;
+use crate::gzip::GZIP;
+use crate::info::FormatInfo;
+use crate::parallels::PARALLELS;
+use crate::qcow::QCOW;
+use crate::raw::RAW;
+use crate::vdi::VDI;
+use crate::vhd::VHD;
+use crate::vhdx::VHDX;
+use crate::vmdk::VMDK;
+use crate::xz::XZ;
+use crate::zstd::ZSTD;
+use std::io::{self, Read, Seek};
+
+/// A readable, seekable disk image.
+///
+/// Images must be `Send + Sync` so that a [`Body`](crate::Body) can be shared
+/// between threads.
+pub trait BodyImage: Read + Seek + Send + Sync {
+    /// Structured description of the image. The default is empty, for
+    /// images with nothing to report.
+    fn info(&self) -> FormatInfo {
+        FormatInfo::default()
+    }
+
+    /// Log [`BodyImage::info`].
+    fn print_info(&self) {
+        self.info().log()
+    }
+
+    /// Logical sector size in bytes.
+    fn get_sector_size(&self) -> u16;
+
+    /// Total length of the image in bytes.
+    fn size(&mut self) -> io::Result<u64>;
+
+    /// Clone the image behind a box, with its own file handles.
+    fn box_clone(&self) -> Box<dyn BodyImage>;
+}
+
+impl Clone for Box<dyn BodyImage> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+/// Implements [`BodyImage`] for backends exposing the usual inherent
+/// `info`, `print_info`, `get_sector_size` and infallible `size` methods.
+macro_rules! impl_body_image {
+    ($($image:ty),* $(,)?) => {
+        $(
+            impl BodyImage for $image {
+                fn info(&self) -> FormatInfo {
+                    <$image>::info(self)
+                }
+
+                fn print_info(&self) {
+                    <$image>::print_info(self)
+                }
+
+                fn get_sector_size(&self) -> u16 {
+                    <$image>::get_sector_size(self) as u16
+                }
+
+                fn size(&mut self) -> io::Result<u64> {
+                    Ok(<$image>::size(self))
+                }
+
+                fn box_clone(&self) -> Box<dyn BodyImage> {
+                    Box::new(self.clone())
+                }
+            }
+        )*
+    };
+}
+
+impl_body_image!(EWF, VMDK, AFF, AFF4, VHD, VHDX, VDI, DMG, PARALLELS, QCOW, DEVICE, ZSTD, XZ);
+
+impl BodyImage for RAW {
+    fn info(&self) -> FormatInfo {
+        RAW::info(self)
+    }
+
+    fn print_info(&self) {
+        RAW::print_info(self)
+    }
+
+    fn get_sector_size(&self) -> u16 {
+        RAW::get_sector_size(self)
+    }
+
+    fn size(&mut self) -> io::Result<u64> {
+        RAW::size(self)
+    }
+
+    fn box_clone(&self) -> Box<dyn BodyImage> {
+        Box::new(self.clone())
+    }
+}
+
+impl BodyImage for GZIP {
+    fn info(&self) -> FormatInfo {
+        GZIP::info(self)
+    }
+
+    fn print_info(&self) {
+        GZIP::print_info(self)
+    }
+
+    fn get_sector_size(&self) -> u16 {
+        GZIP::get_sector_size(self)
+    }
+
+    fn size(&mut self) -> io::Result<u64> {
+        GZIP::size(self)
+    }
+
+    fn box_clone(&self) -> Box<dyn BodyImage> {
+        Box::new(self.clone())
+    }
+}
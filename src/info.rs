@@ -0,0 +1,89 @@
+//! Structured description of an image backend, see
+//! [`BodyImage::info`](crate::BodyImage::info).
+
+use crate::metadata::serialize_labelled;
+use log::info;
+use serde::Serialize;
+use std::fmt;
+
+/// What a backend reports about its image: a title, label/value fields in
+/// display order and named groups of further fields. `print_info` logs it;
+/// GUIs and report generators can use it (or its JSON form) directly.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct FormatInfo {
+    /// Heading, e.g. `VHD Image Information`.
+    pub title: String,
+    #[serde(serialize_with = "serialize_labelled")]
+    pub fields: Vec<(String, String)>,
+    pub sections: Vec<InfoSection>,
+}
+
+/// A named group of fields of a [`FormatInfo`], e.g. the EWF device
+/// information or the VMDK extent list.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct InfoSection {
+    pub title: String,
+    #[serde(serialize_with = "serialize_labelled")]
+    pub fields: Vec<(String, String)>,
+}
+
+impl FormatInfo {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Append a field.
+    pub fn field(mut self, label: impl Into<String>, value: impl ToString) -> Self {
+        self.fields.push((label.into(), value.to_string()));
+        self
+    }
+
+    /// Append a section, skipped when `fields` is empty.
+    pub fn section(mut self, title: impl Into<String>, fields: Vec<(String, String)>) -> Self {
+        if !fields.is_empty() {
+            self.sections.push(InfoSection {
+                title: title.into(),
+                fields,
+            });
+        }
+        self
+    }
+
+    /// Value of the first field labelled `label`, in the fields then the
+    /// sections.
+    pub fn get(&self, label: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .chain(self.sections.iter().flat_map(|s| &s.fields))
+            .find(|(l, _)| l == label)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Write every line of the description to the `log` facade at info
+    /// level.
+    pub fn log(&self) {
+        for line in self.to_string().lines() {
+            info!("{}", line);
+        }
+    }
+}
+
+impl fmt::Display for FormatInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}:", self.title)?;
+        let width = self.fields.iter().map(|(l, _)| l.len()).max().unwrap_or(0);
+        for (label, value) in &self.fields {
+            writeln!(f, "{:<width$}: {}", label, value, width = width)?;
+        }
+        for section in &self.sections {
+            writeln!(f, "{}:", section.title)?;
+            for (label, value) in &section.fields {
+                writeln!(f, "  {}: {}", label, value)?;
+            }
+        }
+        Ok(())
+    }
+}
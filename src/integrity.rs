@@ -0,0 +1,148 @@
+//! How the evidence is protected from writes, see
+//! [`Body::integrity_mode`](crate::Body::integrity_mode).
+//!
+//! Every file and device a backend reads is opened through
+//! [`open_read_only`]: read access only (no write, append, create or
+//! truncate flag), and on Unix a shared advisory lock, which a cooperating
+//! writer asking for an exclusive lock will see, and which shows when a
+//! writer already holds one.
+
+use log::warn;
+use serde::Serialize;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io;
+use std::path::Path;
+
+/// Open `path` for reading only, taking a shared advisory lock on Unix.
+///
+/// A file locked exclusively by another process (an acquisition tool still
+/// writing it, for instance) is opened anyway, with a warning.
+pub(crate) fn open_read_only(path: impl AsRef<Path>) -> io::Result<File> {
+    let path = path.as_ref();
+    let file = OpenOptions::new().read(true).open(path)?;
+    if writer_lock(&file) == Some(true) {
+        warn!(
+            "{} is locked for writing by another process, its content may change while it is read",
+            path.display()
+        );
+    }
+    Ok(file)
+}
+
+/// Whether another process holds an exclusive lock on `file`, `None` when
+/// it cannot be told. The shared lock taken otherwise is kept as long as
+/// `file` is open.
+fn writer_lock(file: &File) -> Option<bool> {
+    if cfg!(not(unix)) {
+        // Windows locks are mandatory: a shared lock would block writers
+        // instead of merely showing them.
+        return None;
+    }
+    match file.try_lock_shared() {
+        Ok(()) => Some(false),
+        Err(TryLockError::WouldBlock) => Some(true),
+        Err(TryLockError::Error(_)) => None,
+    }
+}
+
+/// Lock state of one file of the evidence, see [`IntegrityMode`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FileIntegrity {
+    pub path: String,
+    /// Whether another process holds an exclusive (writer) lock on the file,
+    /// `None` when it cannot be told (no such file on the OS file system,
+    /// locks not supported).
+    pub writer_lock: Option<bool>,
+}
+
+/// What the library guarantees about the evidence it reads, for the
+/// examination notes of a lab.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct IntegrityMode {
+    /// Always `true`: backends open their files and devices read-only and
+    /// a [`Body`](crate::Body) offers no way to write to them. Outputs
+    /// (conversions, exports) only ever go to new files.
+    pub read_only: bool,
+    /// Whether the files are held under a shared advisory lock while open
+    /// (Unix).
+    pub advisory_locks: bool,
+    pub files: Vec<FileIntegrity>,
+}
+
+impl IntegrityMode {
+    /// Check the lock state of every path of `paths`.
+    pub(crate) fn check(paths: impl IntoIterator<Item = String>) -> Self {
+        let files = paths
+            .into_iter()
+            .map(|path| {
+                let writer_lock = OpenOptions::new()
+                    .read(true)
+                    .open(&path)
+                    .ok()
+                    .and_then(|file| writer_lock(&file));
+                FileIntegrity { path, writer_lock }
+            })
+            .collect();
+        Self {
+            read_only: true,
+            advisory_locks: cfg!(unix),
+            files,
+        }
+    }
+
+    /// Fail when the evidence is not read-only or another process holds a
+    /// writer lock on one of its files.
+    pub fn assert(&self) -> io::Result<()> {
+        if !self.read_only {
+            return Err(io::Error::other("evidence is not opened read-only"));
+        }
+        let locked: Vec<&str> = self
+            .files
+            .iter()
+            .filter(|file| file.writer_lock == Some(true))
+            .map(|file| file.path.as_str())
+            .collect();
+        if !locked.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::ResourceBusy,
+                format!(
+                    "locked for writing by another process: {}",
+                    locked.join(", ")
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use crate::testing::TempDir;
+    use crate::Body;
+
+    #[test]
+    fn reports_writer_locks() {
+        let dir = TempDir::new("integrity");
+        let path = dir.join("integrity.raw");
+        std::fs::write(&path, [0u8; 4096]).unwrap();
+        let mut body = Body::try_new(path.to_string_lossy().into(), "raw").unwrap();
+
+        let mode = body.integrity_mode().unwrap();
+        assert!(mode.read_only);
+        assert_eq!(mode.files.len(), 1);
+        assert_eq!(mode.files[0].writer_lock, Some(false));
+        mode.assert().unwrap();
+
+        // Stands for another process: flock locks belong to the open file
+        // description.
+        drop(body);
+        let writer = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        writer.lock().unwrap();
+        let mut body = Body::try_new(path.to_string_lossy().into(), "raw").unwrap();
+        let mode = body.integrity_mode().unwrap();
+        assert_eq!(mode.files[0].writer_lock, Some(true));
+        assert!(mode.assert().is_err());
+
+        drop(writer);
+    }
+}
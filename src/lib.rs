@@ -1,17 +1,111 @@
 pub mod aff;
 pub mod aff4;
+#[cfg(feature = "async")]
+pub mod async_body;
+pub mod convert;
+pub mod ddrescue;
+pub mod device;
+pub mod diff;
+pub mod dmg;
+pub mod error;
 pub mod ewf;
+pub mod ewf_writer;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod gzip;
+pub mod handle;
+pub mod hash;
+pub mod image;
+pub mod info;
+pub mod integrity;
+pub mod manifest;
+pub mod metadata;
+pub mod overlay;
+pub mod parallels;
+pub mod probe;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod qcow;
 pub mod raw;
+pub mod read_ahead;
+pub mod read_at;
+pub mod read_policy;
+pub mod registry;
+#[cfg(feature = "remote")]
+pub mod remote;
+pub mod retry;
+pub mod sectors;
+pub mod source;
+pub mod stats;
+#[cfg(test)]
+mod testing;
+pub mod throttle;
+mod trace;
+pub mod vdi;
+pub mod verify;
+pub mod vfs;
+pub mod vhd;
+pub mod vhdx;
 pub mod vmdk;
+pub mod writer;
+pub mod xz;
+pub mod zstd;
 
 use aff::AFF;
 use aff4::AFF4;
+#[cfg(feature = "async")]
+pub use async_body::AsyncBody;
+pub use convert::{convert, convert_with_progress, ConvertOptions, ConvertSummary, ConvertTarget};
+pub use ddrescue::{RescueBlock, RescueMap, RescueStatus};
+use device::DEVICE;
+pub use diff::{compare, DiffReport};
+use dmg::DMG;
+pub use error::ExhumeBodyError;
 use ewf::EWF;
-use log::{error, info};
+pub use ewf_writer::{EwfCaseInfo, EwfCompression, EwfWriteSummary, EwfWriter};
+use gzip::GZIP;
+pub use handle::BodyHandle;
+pub use hash::{HashAlgorithm, HashingReader};
+pub use image::BodyImage;
+pub use info::{FormatInfo, InfoSection};
+pub use integrity::IntegrityMode;
+use log::{error, info, warn};
+pub use manifest::BlockManifest;
+pub use metadata::{DiskGeometry, DiskMetadata, EvidenceFile, SectorRange};
+pub use overlay::Overlay;
+use overlay::{ExtentMap, PatchBytes};
+use parallels::PARALLELS;
+pub use probe::{ProbeCandidate, ProbeResult};
+use qcow::QCOW;
 use raw::RAW;
-use vmdk::VMDK;
+pub use read_ahead::ReadAhead;
+pub use read_at::ReadAt;
+use read_policy::Damage;
+pub use read_policy::ReadPolicy;
+pub use registry::{register_format, FormatPlugin};
+#[cfg(feature = "remote")]
+pub use remote::HttpBody;
+pub use retry::{RetryPolicy, Retrying};
+pub use sectors::{Sectors, Window, Windows};
+pub use source::{ReadSeek, Source};
+use stats::IoCounters;
+pub use stats::IoStats;
+use throttle::ThrottledFileSystem;
+pub use throttle::{RateLimit, Throttled};
+use vdi::VDI;
+pub use verify::{HashCheck, VerificationReport};
+pub use vfs::{FileSystem, MemoryFileSystem, StdFileSystem};
+use vhd::VHD;
+use vhdx::VHDX;
+use vmdk::{VMDKOpenOptions, VMDK};
+pub use writer::{RawWriter, WriteSummary};
+use xz::XZ;
+use zstd::ZSTD;
 
 use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
 
 #[derive(Clone)]
 pub enum BodyFormat {
@@ -35,148 +129,1540 @@ pub enum BodyFormat {
         image: aff4::AFF4,
         description: String,
     },
-    // Other compatible image formats here.
+    VHD {
+        image: vhd::VHD,
+        description: String,
+    },
+    VHDX {
+        image: vhdx::VHDX,
+        description: String,
+    },
+    VDI {
+        image: vdi::VDI,
+        description: String,
+    },
+    DMG {
+        image: dmg::DMG,
+        description: String,
+    },
+    PARALLELS {
+        image: parallels::PARALLELS,
+        description: String,
+    },
+    QCOW {
+        image: qcow::QCOW,
+        description: String,
+    },
+    DEVICE {
+        image: device::DEVICE,
+        description: String,
+    },
+    GZIP {
+        image: gzip::GZIP,
+        description: String,
+    },
+    ZSTD {
+        image: zstd::ZSTD,
+        description: String,
+    },
+    XZ {
+        image: xz::XZ,
+        description: String,
+    },
+    /// A format provided by a registered [`FormatPlugin`].
+    Custom {
+        name: &'static str,
+        image: Box<dyn BodyImage>,
+        description: String,
+    },
 }
 
+/// An opened piece of evidence, readable as one continuous disk.
+///
+/// `Body` is `Send + Sync`. To fan reads out across threads, share it (for
+/// instance behind an `Arc`) and use [`ReadAt::read_at`], which never moves a
+/// cursor. `Read + Seek` need `&mut` access; a clone gets its own cursor.
 #[derive(Clone)]
 pub struct Body {
     pub path: String,
     pub format: BodyFormat,
+    state: BodyState,
+}
+
+/// What a body keeps besides its backend, shared by its clones.
+#[derive(Clone, Default)]
+struct BodyState {
+    /// Set when opened with [`ReadPolicy::BestEffort`].
+    damage: Option<Arc<Damage>>,
+    /// [`Body::allocated_ranges`], computed on first use.
+    allocation: Arc<OnceLock<Vec<Range<u64>>>>,
+    /// Threads used by [`Body::hash`], see [`Body::set_hash_threads`].
+    hash_threads: usize,
+    /// Sector size forced with [`BodyBuilder::sector_size`].
+    sector_size: Option<u16>,
+    /// Bytes read, see [`Body::stats`].
+    io: Arc<IoCounters>,
+    /// [`Body::patch`]es, copied (not shared) by clones.
+    patches: ExtentMap<PatchBytes>,
+}
+
+/// Options applied while opening a body, set through [`BodyBuilder`].
+#[derive(Clone, Debug, Default)]
+struct BodyOptions {
+    /// Worker threads decompressing EWF chunks ahead of sequential reads.
+    decompression_threads: Option<usize>,
+    /// Check every EWF chunk and AFF page read against its stored checksum.
+    verify_checksums: bool,
+    /// Where the image files are opened from, the OS when `None`.
+    file_system: Option<Arc<dyn FileSystem>>,
+    /// What reads do with chunks that cannot be decoded.
+    read_policy: ReadPolicy,
+    /// Threads used by [`Body::hash`].
+    hash_threads: Option<usize>,
+    /// Logical sector size reported instead of the detected one.
+    sector_size: Option<u16>,
+    /// Budget every image file is read under.
+    rate_limit: Option<RateLimit>,
+    /// How failed reads of RAW images and devices are retried.
+    retry_policy: Option<RetryPolicy>,
+    /// ddrescue mapfile of a RAW image.
+    ddrescue_map: Option<PathBuf>,
+    /// Sectors filled together under [`ReadPolicy::BestEffort`].
+    error_granularity: Option<u32>,
+    /// How VMDK extent files and parent disks are located.
+    vmdk: VMDKOpenOptions,
 }
 
+/// Builder for a [`Body`] when more than a path and a format are needed.
+///
+/// ```no_run
+/// # use exhume_body::Body;
+/// # fn main() -> Result<(), exhume_body::ExhumeBodyError> {
+/// let body = Body::builder("/evidence/disk.Ex01")
+///     .format("ewf")
+///     .decompression_threads(4)
+///     .offset(0x100000)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct BodyBuilder {
+    path: String,
+    format: String,
+    offset: Option<u64>,
+    options: BodyOptions,
+}
+
+impl BodyBuilder {
+    /// Start building a body for `file_path`, with format auto-detection.
+    pub fn new(file_path: impl Into<String>) -> Self {
+        Self {
+            path: file_path.into(),
+            format: "auto".to_string(),
+            offset: None,
+            options: BodyOptions::default(),
+        }
+    }
+
+    /// Force a format (`"ewf"`, `"vmdk"`, …) instead of auto-detecting it.
+    pub fn format(mut self, format: impl Into<String>) -> Self {
+        self.format = format.into();
+        self
+    }
+
+    /// Position the body at `offset` once opened.
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Decompress upcoming EWF chunks on `threads` worker threads, which
+    /// speeds up large sequential reads (hashing, conversion). Ignored by
+    /// other formats.
+    pub fn decompression_threads(mut self, threads: usize) -> Self {
+        self.options.decompression_threads = Some(threads);
+        self
+    }
+
+    /// Check every chunk read against the checksum the image stores for it:
+    /// the Adler-32 of EWF chunks, the `pageN_md5` (or SHA) digest of AFF
+    /// pages. A mismatch fails the read with [`io::ErrorKind::InvalidData`],
+    /// or is filled and lands in [`Body::bad_ranges`] under
+    /// [`ReadPolicy::BestEffort`], so that nothing computed from the body
+    /// rests on altered data. Ignored by formats without such checksums.
+    pub fn verify_chunk_checksums(mut self, verify: bool) -> Self {
+        self.options.verify_checksums = verify;
+        self
+    }
+
+    /// Hash with `threads` reading and decompressing threads and one thread
+    /// per digest, see [`Body::set_hash_threads`].
+    pub fn hash_threads(mut self, threads: usize) -> Self {
+        self.options.hash_threads = Some(threads);
+        self
+    }
+
+    /// Report `size`-byte logical sectors (512, 4096, …) instead of the
+    /// size the format records or the one detected from the image, e.g.
+    /// for a 4Kn disk imaged to a RAW file without a GPT.
+    pub fn sector_size(mut self, size: u16) -> Self {
+        self.options.sector_size = Some(size);
+        self
+    }
+
+    /// Open the image files through `fs` instead of the operating system,
+    /// e.g. a [`MemoryFileSystem`] in a browser or sandbox. Only EWF, VMDK,
+    /// AFF4 and RAW images can be opened this way. `"auto"` detects the
+    /// first three and fails on anything else rather than reading it as
+    /// RAW, which has to be asked for with `format("raw")`.
+    pub fn file_system(mut self, fs: Arc<dyn FileSystem>) -> Self {
+        self.options.file_system = Some(fs);
+        self
+    }
+
+    /// Read every image file under `limit`, so that a triage job does not
+    /// saturate a NAS or evidence server. Bodies built with clones of the
+    /// same limit share its budget. Applies to every format but block
+    /// devices and registered formats, which open their files themselves.
+    ///
+    /// ```no_run
+    /// # use exhume_body::{Body, RateLimit};
+    /// # fn main() -> Result<(), exhume_body::ExhumeBodyError> {
+    /// let body = Body::builder("/mnt/evidence/case42/disk.E01")
+    ///     .rate_limit(RateLimit::new(50 * 1024 * 1024))
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.options.rate_limit = Some(limit);
+        self
+    }
+
+    /// Retry failed reads of a RAW image or block device under `policy`
+    /// before surfacing the error, for failing drives and flaky network
+    /// mounts, see [`Body::set_retry_policy`].
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.options.retry_policy = Some(policy);
+        self
+    }
+
+    /// Read a RAW image made by GNU ddrescue along with its mapfile, so
+    /// that the areas left unrescued are not read as zeros: they fail, or
+    /// under [`ReadPolicy::BestEffort`] are filled and listed in
+    /// [`Body::bad_ranges`] up front, and [`Body::allocated_ranges`] leaves
+    /// them out. Fails to build for other formats.
+    ///
+    /// ```no_run
+    /// # use exhume_body::{Body, ReadPolicy};
+    /// # fn main() -> Result<(), exhume_body::ExhumeBodyError> {
+    /// let body = Body::builder("/evidence/failing.raw")
+    ///     .ddrescue_map("/evidence/failing.map")
+    ///     .read_policy(ReadPolicy::BestEffort { fill: Vec::new() })
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn ddrescue_map(mut self, mapfile: impl Into<PathBuf>) -> Self {
+        self.options.ddrescue_map = Some(mapfile.into());
+        self
+    }
+
+    /// Open a VMDK disk even when some of its extent files are missing, see
+    /// [`VMDK::open_allowing_missing_extents`]. The missing extents read as
+    /// zeros; under [`ReadPolicy::BestEffort`] they are filled and listed in
+    /// [`Body::bad_ranges`] up front instead.
+    pub fn allow_missing_extents(mut self, allow: bool) -> Self {
+        self.options.vmdk.allow_missing_extents = allow;
+        self
+    }
+
+    /// Look up the VMDK extent files and parent disks that are not where
+    /// the descriptor says in `directory` too. Directories are tried in the
+    /// order they are added, after the descriptor's own.
+    pub fn extent_search_path(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.options.vmdk.search_paths.push(directory.into());
+        self
+    }
+
+    /// What reads do when a chunk or grain fails to read, decompress or
+    /// verify, [`ReadPolicy::Strict`] by default.
+    ///
+    /// ```no_run
+    /// # use exhume_body::{Body, ReadPolicy};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut body = Body::builder("/evidence/damaged.E01")
+    ///     .read_policy(ReadPolicy::BestEffort {
+    ///         fill: b"BAD SECTOR ".to_vec(),
+    ///     })
+    ///     .build()?;
+    /// body.hash(&[exhume_body::HashAlgorithm::Md5], |_, _| {})?;
+    /// for range in body.bad_ranges() {
+    ///     eprintln!("unreadable: {} sectors at {}", range.count, range.start);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_policy(mut self, policy: ReadPolicy) -> Self {
+        self.options.read_policy = policy;
+        self
+    }
+
+    /// Under [`ReadPolicy::BestEffort`], read a failed area again (and fill
+    /// what still fails) `sectors` at a time rather than sector by sector:
+    /// faster over a dying drive, at the cost of readable sectors next to
+    /// the bad ones. 1 by default.
+    pub fn error_granularity(mut self, sectors: u32) -> Self {
+        self.options.error_granularity = Some(sectors);
+        self
+    }
+
+    /// Open the body.
+    pub fn build(mut self) -> Result<Body, ExhumeBodyError> {
+        if let Some(size) = self.options.sector_size {
+            if size < 512 || !size.is_power_of_two() {
+                return Err(ExhumeBodyError::Io(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid sector size {}", size),
+                )));
+            }
+        }
+        // Without a custom file system, `Body::open` applies the limit itself.
+        if let (Some(limit), Some(fs)) = (&self.options.rate_limit, &self.options.file_system) {
+            self.options.file_system = Some(Arc::new(ThrottledFileSystem::new(
+                fs.clone(),
+                limit.clone(),
+            )));
+        }
+        let mut body = Body::open(self.path, &self.format, &self.options)?;
+        body.state.sector_size = self.options.sector_size;
+        if let Some(policy) = self.options.retry_policy {
+            body.set_retry_policy(policy);
+        }
+        if let Some(path) = &self.options.ddrescue_map {
+            let map = RescueMap::open(path)?;
+            let BodyFormat::RAW { image, .. } = &mut body.format else {
+                return Err(ExhumeBodyError::open(
+                    "ddrescue",
+                    format!(
+                        "mapfiles apply to RAW images, not to '{}' images",
+                        body.format_name()
+                    ),
+                ));
+            };
+            image.set_rescue_map(map)?;
+        }
+        if let ReadPolicy::BestEffort { fill } = self.options.read_policy {
+            let size = body.size()?;
+            let sector_size = body.get_sector_size();
+            let damage = Damage::new(fill, size, sector_size)
+                .with_granularity(self.options.error_granularity.unwrap_or(1));
+            match &body.format {
+                BodyFormat::RAW { image, .. } => {
+                    if let Some(map) = image.rescue_map() {
+                        for range in map.unrescued(size) {
+                            damage.mark(range);
+                        }
+                    }
+                }
+                BodyFormat::VMDK { image, .. } => {
+                    for range in image.missing_ranges() {
+                        damage.mark(range);
+                    }
+                }
+                _ => {}
+            }
+            body.state.damage = Some(Arc::new(damage));
+        }
+        if let Some(threads) = self.options.hash_threads {
+            body.set_hash_threads(threads);
+        }
+        if let Some(off) = self.offset {
+            body.seek(SeekFrom::Start(off))?;
+        }
+        Ok(body)
+    }
+}
+
+/// Built-in image formats tried by `"auto"` detection, in order, after block
+/// devices and before the registered formats and the RAW fallback.
+const DETECTION_ORDER: &[&str] = &[
+    "ewf",
+    "vmdk",
+    "aff",
+    "aff4",
+    "vhd",
+    "vhdx",
+    "vdi",
+    "dmg",
+    "parallels",
+    "qcow",
+    "gzip",
+    "zstd",
+    "xz",
+];
+
 impl Body {
     /// Create a new Body given a file path and a format.
     /// If the format string is "auto", the image format will be auto-detected.
+    pub fn try_new(file_path: String, format: &str) -> Result<Body, ExhumeBodyError> {
+        Self::open(file_path, format, &BodyOptions::default())
+    }
+
+    /// Same as [`Body::try_new`], positioned at `offset` when given.
+    pub fn try_new_from(
+        file_path: String,
+        format: &str,
+        offset: Option<u64>,
+    ) -> Result<Body, ExhumeBodyError> {
+        let mut body = Body::try_new(file_path, format)?;
+        if let Some(off) = offset {
+            body.seek(SeekFrom::Start(off))?;
+        }
+        Ok(body)
+    }
+
+    /// Create a new Body given a file path and a format, exiting the process
+    /// on error.
+    #[deprecated(note = "use `Body::try_new`, which returns the error instead of exiting")]
     pub fn new(file_path: String, format: &str) -> Body {
+        match Self::try_new(file_path, format) {
+            Ok(body) => body,
+            Err(err) => {
+                error!("Error: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    /// Start a [`BodyBuilder`] for `file_path`.
+    pub fn builder(file_path: impl Into<String>) -> BodyBuilder {
+        BodyBuilder::new(file_path)
+    }
+
+    /// Open evidence held in memory, in an archive or behind a custom
+    /// transport instead of a file.
+    ///
+    /// `format` is `"raw"`, `"ewf"` (single-segment images), `"vmdk"`
+    /// (monolithic disks) or `"auto"`, which recognizes EWF and VMDK and
+    /// falls back to RAW. The other formats can only be opened from a path.
+    ///
+    /// ```no_run
+    /// # use exhume_body::Body;
+    /// # fn main() -> Result<(), exhume_body::ExhumeBodyError> {
+    /// let image: Vec<u8> = std::fs::read("/evidence/disk.E01")?;
+    /// let body = Body::from_reader(Box::new(std::io::Cursor::new(image)), "auto")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_reader(reader: Box<dyn ReadSeek>, format: &str) -> Result<Body, ExhumeBodyError> {
+        let source = Source::from_boxed(reader);
+        let ewf = |source: &Source| -> Result<BodyFormat, ExhumeBodyError> {
+            let evidence = EWF::from_sources(vec![source.try_clone()?])?;
+            Ok(BodyFormat::EWF {
+                description: evidence.description().to_string(),
+                image: evidence,
+            })
+        };
+        let vmdk = |source: &Source| -> Result<BodyFormat, ExhumeBodyError> {
+            let evidence = VMDK::from_source(source.try_clone()?)?;
+            Ok(BodyFormat::VMDK {
+                image: evidence,
+                description: "VMDK (Virtual Machine Disk) file".to_string(),
+            })
+        };
+        let raw = |source: Source| BodyFormat::RAW {
+            image: RAW::from_source(source),
+            description: "Raw image format".to_string(),
+        };
+
+        let format = match format {
+            "ewf" => ewf(&source)?,
+            "vmdk" => vmdk(&source)?,
+            "raw" => raw(source),
+            "auto" => {
+                let mut magic = [0u8; 8];
+                source.read_at(0, &mut magic)?;
+                if ewf::is_ewf_signature(&magic) {
+                    ewf(&source)?
+                } else {
+                    vmdk(&source).unwrap_or_else(|_| raw(source))
+                }
+            }
+            _ => {
+                return Err(ExhumeBodyError::open(
+                    "stream",
+                    format!("'{}' images can only be opened from a path", format),
+                ))
+            }
+        };
+        Ok(Body {
+            path: String::new(),
+            format,
+            state: BodyState::default(),
+        })
+    }
+
+    /// Open an image served over HTTP(S) through range requests, with the
+    /// formats accepted by [`Body::from_reader`]. Requires the `remote`
+    /// feature; see [`HttpBody`] for block size, cache and headers.
+    #[cfg(feature = "remote")]
+    pub fn from_url(url: &str, format: &str) -> Result<Body, ExhumeBodyError> {
+        let remote = HttpBody::open(url)?;
+        let mut body = Self::from_reader(Box::new(remote), format)?;
+        body.path = url.to_string();
+        Ok(body)
+    }
+
+    /// Open `file_path` through a custom [`FileSystem`], for the formats whose
+    /// readers support it.
+    fn open_in(
+        fs: &dyn FileSystem,
+        file_path: String,
+        format: &str,
+        options: &BodyOptions,
+    ) -> Result<Body, ExhumeBodyError> {
+        let ewf = || -> Result<BodyFormat, ExhumeBodyError> {
+            let mut evidence = EWF::open_in(fs, &file_path)?;
+            if let Some(threads) = options.decompression_threads {
+                evidence.set_decompression_threads(threads);
+            }
+            evidence.set_verify_checksums(options.verify_checksums);
+            Ok(BodyFormat::EWF {
+                description: evidence.description().to_string(),
+                image: evidence,
+            })
+        };
+        let vmdk = || -> Result<BodyFormat, ExhumeBodyError> {
+            Ok(BodyFormat::VMDK {
+                image: VMDK::open_with(fs, &file_path, &options.vmdk)?,
+                description: "VMDK (Virtual Machine Disk) file".to_string(),
+            })
+        };
+        let aff4 = || -> Result<BodyFormat, ExhumeBodyError> {
+            Ok(BodyFormat::AFF4 {
+                image: AFF4::open_in(fs, &file_path)?,
+                description: "AFF4 / AFF4-L (ImageStream)".to_string(),
+            })
+        };
+        let raw = || -> Result<BodyFormat, ExhumeBodyError> {
+            Ok(BodyFormat::RAW {
+                image: RAW::open_in(fs, &file_path)?,
+                description: "Raw image format".to_string(),
+            })
+        };
+
+        let format = match format {
+            "ewf" => ewf()?,
+            "vmdk" => vmdk()?,
+            "aff4" | "aff4l" => aff4()?,
+            "raw" => raw()?,
+            // Other formats cannot be opened through `fs`: falling back to RAW
+            // would pass their container off as the evidence.
+            "auto" => ewf().or_else(|_| vmdk()).or_else(|_| aff4()).map_err(|_| {
+                ExhumeBodyError::open(
+                    "file system",
+                    "format not supported through a custom FileSystem, only EWF, VMDK and AFF4 images are detected (use format(\"raw\") for RAW images)",
+                )
+            })?,
+            _ => {
+                return Err(ExhumeBodyError::open(
+                    "file system",
+                    format!(
+                        "'{}' images can only be opened from the operating system's files",
+                        format
+                    ),
+                ))
+            }
+        };
+        Ok(Body {
+            path: file_path,
+            format,
+            state: BodyState::default(),
+        })
+    }
+
+    fn open(
+        file_path: String,
+        format: &str,
+        options: &BodyOptions,
+    ) -> Result<Body, ExhumeBodyError> {
+        if let Some(fs) = &options.file_system {
+            return Self::open_in(fs.as_ref(), file_path, format, options);
+        }
         if format == "auto" {
-            return Body {
-                path: file_path.clone(),
-                format: Self::detect_format(&file_path),
-            };
+            return Ok(Body {
+                format: Self::detect_format(&file_path, options)?,
+                path: file_path,
+                state: BodyState::default(),
+            });
         }
 
+        // Every file is read under the rate limit, if any.
+        let throttled;
+        let fs: &dyn FileSystem = match &options.rate_limit {
+            Some(limit) => {
+                throttled = ThrottledFileSystem::new(Arc::new(StdFileSystem), limit.clone());
+                &throttled
+            }
+            None => &StdFileSystem,
+        };
         match format {
             "ewf" => {
-                let evidence = match EWF::new(&file_path) {
-                    Ok(ewf) => ewf,
-                    Err(err) => {
-                        error!("Error: {}", err);
-                        std::process::exit(1);
-                    }
-                };
-                Body {
+                let mut evidence = EWF::open_in(fs, &file_path)?;
+                if let Some(threads) = options.decompression_threads {
+                    evidence.set_decompression_threads(threads);
+                }
+                evidence.set_verify_checksums(options.verify_checksums);
+                Ok(Body {
                     path: file_path,
                     format: BodyFormat::EWF {
+                        description: evidence.description().to_string(),
                         image: evidence,
-                        description: "Expert Witness Compression Format".to_string(),
                     },
-                }
+                    state: BodyState::default(),
+                })
             }
             "vmdk" => {
-                let evidence = match VMDK::new(&file_path) {
-                    Ok(evidence) => evidence,
-                    Err(err) => {
-                        error!("Error: {}", err);
-                        std::process::exit(1);
-                    }
-                };
-                Body {
+                let evidence = VMDK::open_with(fs, &file_path, &options.vmdk)?;
+                Ok(Body {
                     path: file_path,
                     format: BodyFormat::VMDK {
                         image: evidence,
                         description: "VMDK (Virtual Machine Disk) file".to_string(),
                     },
-                }
+                    state: BodyState::default(),
+                })
             }
             "raw" => {
-                let evidence = match RAW::new(&file_path) {
-                    Ok(evidence) => evidence,
-                    Err(err) => {
-                        error!("Error: {}", err);
-                        std::process::exit(1);
-                    }
-                };
-                Body {
+                let evidence = RAW::open_in(fs, &file_path)?;
+                Ok(Body {
                     path: file_path,
                     format: BodyFormat::RAW {
                         image: evidence,
                         description: "Raw image format".to_string(),
                     },
-                }
+                    state: BodyState::default(),
+                })
             }
             "aff" => {
-                let evidence = match AFF::new(&file_path) {
-                    Ok(evidence) => evidence,
-                    Err(err) => {
-                        error!("Error: {}", err);
-                        std::process::exit(1);
-                    }
-                };
-                Body {
+                let mut evidence = AFF::open_in(fs, &file_path)?;
+                evidence.set_verify_checksums(options.verify_checksums);
+                Ok(Body {
                     path: file_path,
                     format: BodyFormat::AFF {
                         image: evidence,
                         description: "Advanced Forensics Format (AFF)".to_string(),
                     },
-                }
+                    state: BodyState::default(),
+                })
             }
             "aff4" | "aff4l" => {
-                let evidence = match AFF4::new(&file_path) {
-                    Ok(a) => a,
-                    Err(err) => {
-                        error!("Error: {}", err);
-                        std::process::exit(1);
-                    }
-                };
-                Body {
+                let evidence = AFF4::open_in(fs, &file_path)?;
+                Ok(Body {
                     path: file_path,
                     format: BodyFormat::AFF4 {
                         image: evidence,
                         description: "AFF4 / AFF4-L (ImageStream)".to_string(),
                     },
-                }
+                    state: BodyState::default(),
+                })
             }
-            _ => {
-                error!(
-                    "Error: Invalid format '{}'. Supported formats are 'raw', 'ewf', 'vmdk', 'aff', 'aff4' or 'auto'.",
-                    format
-                );
-                std::process::exit(1);
+            "vhd" => {
+                let evidence = VHD::open_in(fs, &file_path)?;
+                Ok(Body {
+                    path: file_path,
+                    format: BodyFormat::VHD {
+                        image: evidence,
+                        description: "Virtual Hard Disk (VHD)".to_string(),
+                    },
+                    state: BodyState::default(),
+                })
+            }
+            "vhdx" => {
+                let evidence = VHDX::open_in(fs, &file_path)?;
+                Ok(Body {
+                    path: file_path,
+                    format: BodyFormat::VHDX {
+                        image: evidence,
+                        description: "Virtual Hard Disk v2 (VHDX)".to_string(),
+                    },
+                    state: BodyState::default(),
+                })
+            }
+            "vdi" => {
+                let evidence = VDI::open_in(fs, &file_path)?;
+                Ok(Body {
+                    path: file_path,
+                    format: BodyFormat::VDI {
+                        image: evidence,
+                        description: "VirtualBox Disk Image (VDI)".to_string(),
+                    },
+                    state: BodyState::default(),
+                })
+            }
+            "dmg" => {
+                let evidence = DMG::open_in(fs, &file_path)?;
+                Ok(Body {
+                    path: file_path,
+                    format: BodyFormat::DMG {
+                        image: evidence,
+                        description: "Apple Disk Image (DMG)".to_string(),
+                    },
+                    state: BodyState::default(),
+                })
+            }
+            "parallels" | "hds" | "hdd" => {
+                let evidence = PARALLELS::open_in(fs, &file_path)?;
+                Ok(Body {
+                    path: file_path,
+                    format: BodyFormat::PARALLELS {
+                        image: evidence,
+                        description: "Parallels Desktop disk image (HDS)".to_string(),
+                    },
+                    state: BodyState::default(),
+                })
+            }
+            "qcow" | "qcow1" => {
+                let evidence = QCOW::open_in(fs, &file_path)?;
+                Ok(Body {
+                    path: file_path,
+                    format: BodyFormat::QCOW {
+                        image: evidence,
+                        description: "QEMU Copy-On-Write v1 (QCOW)".to_string(),
+                    },
+                    state: BodyState::default(),
+                })
+            }
+            "device" => {
+                let evidence = DEVICE::new(&file_path)?;
+                Ok(Body {
+                    path: file_path,
+                    format: BodyFormat::DEVICE {
+                        image: evidence,
+                        description: "Physical block device".to_string(),
+                    },
+                    state: BodyState::default(),
+                })
+            }
+            "gzip" | "gz" => {
+                let evidence = GZIP::open_in(fs, &file_path)?;
+                Ok(Body {
+                    path: file_path,
+                    format: BodyFormat::GZIP {
+                        image: evidence,
+                        description: "Gzip-compressed RAW image".to_string(),
+                    },
+                    state: BodyState::default(),
+                })
+            }
+            "zstd" | "zst" => {
+                let evidence = ZSTD::open_in(fs, &file_path)?;
+                Ok(Body {
+                    path: file_path,
+                    format: BodyFormat::ZSTD {
+                        image: evidence,
+                        description: "Zstandard-compressed RAW image".to_string(),
+                    },
+                    state: BodyState::default(),
+                })
             }
+            "xz" => {
+                let evidence = XZ::open_in(fs, &file_path)?;
+                Ok(Body {
+                    path: file_path,
+                    format: BodyFormat::XZ {
+                        image: evidence,
+                        description: "XZ-compressed RAW image".to_string(),
+                    },
+                    state: BodyState::default(),
+                })
+            }
+            _ => match registry::find_format(format) {
+                Some(plugin) => Ok(Body {
+                    format: Self::open_plugin(&plugin, &file_path)?,
+                    path: file_path,
+                    state: BodyState::default(),
+                }),
+                None => Err(ExhumeBodyError::UnknownFormat(format.to_string())),
+            },
         }
     }
 
+    /// Same as [`Body::new`], positioned at `offset` when given.
+    #[deprecated(note = "use `Body::try_new_from`, which returns the error instead of exiting")]
     pub fn new_from(file_path: String, format: &str, offset: Option<u64>) -> Body {
-        let mut body = Body::new(file_path, format);
-        if let Some(off) = offset {
-            if let Err(e) = body.seek(SeekFrom::Start(off)) {
-                error!("Error seeking to offset {}: {}", off, e);
+        match Self::try_new_from(file_path, format, offset) {
+            Ok(body) => body,
+            Err(err) => {
+                error!("Error: {}", err);
                 std::process::exit(1);
             }
         }
-        body
+    }
+
+    /// Description of the image from its backend, with the evidence path
+    /// as first field.
+    ///
+    /// ```no_run
+    /// # use exhume_body::Body;
+    /// # fn main() -> Result<(), exhume_body::ExhumeBodyError> {
+    /// let body = Body::try_new("disk.E01".to_string(), "auto")?;
+    /// println!("{}", serde_json::to_string(&body.info()).unwrap());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn info(&self) -> FormatInfo {
+        let mut info = self.image().info();
+        info.fields
+            .insert(0, ("Evidence".to_string(), self.path.clone()));
+        info
     }
 
     pub fn print_info(&self) {
         info!("Evidence : {}", self.path);
+        self.image().print_info();
+    }
+
+    /// Logical sector size in bytes: the one set with
+    /// [`BodyBuilder::sector_size`], else the one the format records (or,
+    /// for RAW and VMDK, detects from a GPT).
+    pub fn get_sector_size(&self) -> u16 {
+        self.state
+            .sector_size
+            .unwrap_or_else(|| self.image().get_sector_size())
+    }
+
+    /// Sectors found unreadable so far, left unrescued by ddrescue, or held
+    /// by missing VMDK extents, and replaced by the fill pattern of
+    /// [`ReadPolicy::BestEffort`], sorted and merged. Clones of the body
+    /// share the list. Always empty under [`ReadPolicy::Strict`].
+    pub fn bad_ranges(&self) -> Vec<SectorRange> {
+        self.state
+            .damage
+            .as_ref()
+            .map(|damage| damage.ranges())
+            .unwrap_or_default()
+    }
+
+    /// Make this handle read `bytes` at `offset`, in memory: the evidence,
+    /// other handles on it and clones made before are not affected, clones
+    /// made after start with the same patches. Later patches cover earlier
+    /// ones. Meant for what-if analysis and for testing parsers against
+    /// altered data; see [`Overlay`] for changes kept in a file.
+    ///
+    /// Fails with `InvalidInput` past the end of the body.
+    pub fn patch(&mut self, offset: u64, bytes: &[u8]) -> io::Result<()> {
+        let size = self.size()?;
+        if offset
+            .checked_add(bytes.len() as u64)
+            .is_none_or(|end| end > size)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "patch of {} bytes at 0x{:x} ends past the end of the body ({} bytes)",
+                    bytes.len(),
+                    offset,
+                    size
+                ),
+            ));
+        }
+        self.state.patches.insert_bytes(offset, bytes);
+        Ok(())
+    }
+
+    /// Byte ranges replaced by [`Body::patch`], sorted and coalesced.
+    pub fn patched_ranges(&self) -> Vec<Range<u64>> {
+        self.state.patches.ranges()
+    }
+
+    /// Drop every [`Body::patch`] of this handle.
+    pub fn clear_patches(&mut self) {
+        self.state.patches = ExtentMap::default();
+    }
+
+    /// I/O counters of the body and its clones since it was opened or since
+    /// the last [`Body::reset_stats`]: bytes read, chunks decompressed and
+    /// the time spent on them, chunk and grain table cache hits and misses,
+    /// segment switches.
+    ///
+    /// ```no_run
+    /// # use exhume_body::{Body, HashAlgorithm};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut body = Body::try_new("/evidence/disk.E01".to_string(), "auto")?;
+    /// body.reset_stats();
+    /// body.hash(&[HashAlgorithm::Md5], |_, _| {})?;
+    /// let stats = body.stats();
+    /// println!(
+    ///     "{} chunks inflated in {:?}",
+    ///     stats.chunks_decompressed, stats.decompression_time
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stats(&self) -> IoStats {
+        let mut stats = IoStats::default();
+        self.state.io.add_to(&mut stats);
+        if let Some(io) = self.backend_io() {
+            io.add_to(&mut stats);
+        }
+        stats
+    }
+
+    /// Zero the counters of [`Body::stats`], e.g. before each operation to
+    /// profile.
+    pub fn reset_stats(&self) {
+        self.state.io.reset();
+        if let Some(io) = self.backend_io() {
+            io.reset();
+        }
+    }
+
+    fn backend_io(&self) -> Option<&IoCounters> {
         match &self.format {
-            BodyFormat::EWF { image, .. } => image.print_info(),
-            BodyFormat::VMDK { image, .. } => image.print_info(),
-            BodyFormat::AFF { image, .. } => image.print_info(),
-            BodyFormat::AFF4 { image, .. } => image.print_info(),
-            BodyFormat::RAW { .. } => (),
-            // All other compatible formats are handled here.
+            BodyFormat::RAW { image, .. } => Some(image.io_counters()),
+            BodyFormat::EWF { image, .. } => Some(image.io_counters()),
+            BodyFormat::VMDK { image, .. } => Some(image.io_counters()),
+            BodyFormat::AFF4 { image, .. } => Some(image.io_counters()),
+            _ => None,
         }
     }
 
-    pub fn get_sector_size(&self) -> u16 {
+    /// Fill `buf` from `offset` one sector (or error granularity, see
+    /// [`BodyBuilder::error_granularity`]) at a time, replacing the ones
+    /// that still fail with the fill pattern. Stops at the end of the body.
+    fn read_damaged(&self, damage: &Damage, offset: u64, buf: &mut [u8]) -> usize {
+        let granularity = damage.granularity();
+        let len = damage.size().saturating_sub(offset).min(buf.len() as u64) as usize;
+        let mut done = 0;
+        while done < len {
+            let position = offset + done as u64;
+            let step = (granularity - position % granularity).min((len - done) as u64) as usize;
+            let sector = &mut buf[done..done + step];
+            let mut got = 0;
+            while got < step {
+                match self.read_image_at(position + got as u64, &mut sector[got..]) {
+                    Ok(0) => break,
+                    Ok(n) => got += n,
+                    Err(_) => {
+                        damage.fill(position, sector);
+                        got = step;
+                    }
+                }
+            }
+            if got < step {
+                return done + got;
+            }
+            done += step;
+        }
+        done
+    }
+
+    /// Whether [`Body::read_image_at`] reads the backend in place rather
+    /// than through a clone of it.
+    pub(crate) fn reads_natively_at(&self) -> bool {
+        matches!(
+            self.format,
+            BodyFormat::RAW { .. }
+                | BodyFormat::EWF { .. }
+                | BodyFormat::VMDK { .. }
+                | BodyFormat::AFF4 { .. }
+                | BodyFormat::VHD { .. }
+                | BodyFormat::VHDX { .. }
+                | BodyFormat::VDI { .. }
+                | BodyFormat::PARALLELS { .. }
+                | BodyFormat::DEVICE { .. }
+        )
+    }
+
+    /// Read from the backend, ignoring the read policy.
+    fn read_image_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        match &self.format {
+            BodyFormat::RAW { image, .. } => image.read_at(offset, buf),
+            BodyFormat::EWF { image, .. } => image.read_at(offset, buf),
+            BodyFormat::VMDK { image, .. } => image.read_at(offset, buf),
+            BodyFormat::AFF4 { image, .. } => image.read_at(offset, buf),
+            BodyFormat::VHD { image, .. } => image.read_at(offset, buf),
+            BodyFormat::VHDX { image, .. } => image.read_at(offset, buf),
+            BodyFormat::VDI { image, .. } => image.read_at(offset, buf),
+            BodyFormat::PARALLELS { image, .. } => image.read_at(offset, buf),
+            BodyFormat::DEVICE { image, .. } => image.read_at(offset, buf),
+            _ => {
+                let mut image = self.image().box_clone();
+                image.seek(SeekFrom::Start(offset))?;
+                image.read(buf)
+            }
+        }
+    }
+
+    /// Total length of the evidence in bytes.
+    ///
+    /// Takes `&mut self` as compressed streams without a size index (gzip)
+    /// have to be decompressed up to their end once.
+    pub fn size(&mut self) -> io::Result<u64> {
+        self.image_mut().size()
+    }
+
+    /// Stream the whole body in blocks of `chunk` sectors.
+    ///
+    /// ```no_run
+    /// # use exhume_body::Body;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut body = Body::try_new("/evidence/disk.E01".to_string(), "auto")?;
+    /// for block in body.sectors(128) {
+    ///     let block = block?;
+    ///     // scan `block`...
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn sectors(&mut self, chunk: usize) -> Sectors<'_> {
+        Sectors::new(self, 0, u64::MAX, chunk)
+    }
+
+    /// Stream the sectors in `range` (end excluded) in blocks of `chunk`
+    /// sectors.
+    pub fn sector_range(&mut self, range: Range<u64>, chunk: usize) -> Sectors<'_> {
+        Sectors::new(self, range.start, range.end, chunk)
+    }
+
+    /// Stream the whole body in blocks of `chunk` sectors, each preceded by
+    /// the last `overlap` bytes of the previous one, so that carvers find
+    /// signatures spanning block boundaries.
+    ///
+    /// ```no_run
+    /// # use exhume_body::Body;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut body = Body::try_new("/evidence/disk.E01".to_string(), "auto")?;
+    /// for window in body.windows(2048, 16) {
+    ///     let window = window?;
+    ///     for (i, _) in window.data.windows(4).enumerate().filter(|(_, w)| *w == b"%PDF") {
+    ///         if i + 4 > window.overlap {
+    ///             println!("PDF header at 0x{:x}", window.offset + i as u64);
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn windows(&mut self, chunk: usize, overlap: usize) -> Windows<'_> {
+        self.sectors(chunk).with_overlap(overlap)
+    }
+
+    /// Read `count` sectors from `start_sector`, in units of the image's own
+    /// sector size ([`Body::get_sector_size`]). The body's position is left
+    /// unchanged.
+    ///
+    /// Fails with `UnexpectedEof` when the range ends past the end of the
+    /// body.
+    pub fn read_sectors(&mut self, start_sector: u64, count: u64) -> io::Result<Vec<u8>> {
+        let sector_size = self.get_sector_size() as u64;
+        let range = start_sector
+            .checked_mul(sector_size)
+            .and_then(|start| Some(start..start.checked_add(count.checked_mul(sector_size)?)?));
+        let size = self.size()?;
+        let range = match range {
+            Some(range) if range.end <= size => range,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!(
+                        "sectors {}+{} end past the end of the body ({} sectors of {} bytes)",
+                        start_sector,
+                        count,
+                        size / sector_size,
+                        sector_size
+                    ),
+                ))
+            }
+        };
+
+        let position = self.stream_position()?;
+        let len = (range.end - range.start) as usize;
+        let mut sectors = Vec::with_capacity(len);
+        self.seek(SeekFrom::Start(range.start))?;
+        let mut result = Ok(());
+        while sectors.len() < len {
+            let missing = len - sectors.len();
+            match self.read_append(&mut sectors, missing) {
+                Ok(0) => {
+                    result = Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+                    break;
+                }
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        self.seek(SeekFrom::Start(position))?;
+        result.map(|()| sectors)
+    }
+
+    /// Like [`Read::read`], but append up to `len` bytes to `buf` instead of
+    /// filling a slice. RAW and EWF bodies write into the spare capacity of
+    /// `buf` directly (see [`ReadAt::read_at_append`]), so imaging loops do
+    /// not pay for zeroing their blocks first.
+    pub fn read_append(&mut self, buf: &mut Vec<u8>, len: usize) -> io::Result<usize> {
+        if matches!(self.format, BodyFormat::RAW { .. } | BodyFormat::EWF { .. }) {
+            let position = self.stream_position()?;
+            let n = self.read_at_append(position, buf, len)?;
+            self.seek(SeekFrom::Start(position + n as u64))?;
+            return Ok(n);
+        }
+        let start = buf.len();
+        buf.resize(start + len, 0);
+        let result = self.read(&mut buf[start..]);
+        buf.truncate(start + *result.as_ref().unwrap_or(&0));
+        result
+    }
+
+    /// Hash the whole body in one streaming pass, computing every algorithm
+    /// in `algorithms` at once. `progress` receives the bytes hashed so far
+    /// and the total after each block.
+    ///
+    /// Returns lower-case hex digests in the order the algorithms were
+    /// requested. The body's position is left unchanged.
+    ///
+    /// With [`Body::set_hash_threads`] above 1, blocks are read and
+    /// decompressed ahead on that many threads while each algorithm digests
+    /// them on a thread of its own.
+    ///
+    /// ```no_run
+    /// # use exhume_body::{Body, HashAlgorithm};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut body = Body::try_new("/evidence/disk.E01".to_string(), "auto")?;
+    /// let digests = body.hash(&[HashAlgorithm::Md5, HashAlgorithm::Sha1], |done, total| {
+    ///     eprint!("\r{}/{}", done, total)
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn hash(
+        &mut self,
+        algorithms: &[HashAlgorithm],
+        progress: impl FnMut(u64, u64),
+    ) -> io::Result<Vec<(HashAlgorithm, String)>> {
+        let threads = self.state.hash_threads;
+        let total = self.size()?;
+        let block_size = writer::RAW_WRITER_BLOCK_SIZE;
+        if threads > 1 {
+            let mut reader = ReadAhead::from_shared(Arc::new(self.clone()), total)
+                .threads(threads)
+                .depth(2 * threads)
+                .block_size(block_size);
+            return hash::digest_stream(&mut reader, total, algorithms, block_size, true, progress);
+        }
+
+        let position = self.stream_position()?;
+        self.seek(SeekFrom::Start(0))?;
+        let digests = hash::digest_stream(self, total, algorithms, block_size, false, progress)?;
+        self.seek(SeekFrom::Start(position))?;
+        Ok(digests)
+    }
+
+    /// Hash the body every `block_size` bytes with `algorithm`, for a
+    /// manifest of exactly what was analyzed that can be checked again later
+    /// with [`BlockManifest::verify`]. `progress` receives the bytes hashed
+    /// so far and the total. The body's position is left unchanged.
+    pub fn block_manifest(
+        &mut self,
+        block_size: usize,
+        algorithm: HashAlgorithm,
+        progress: impl FnMut(u64, u64),
+    ) -> io::Result<BlockManifest> {
+        BlockManifest::compute(self, block_size, algorithm, progress)
+    }
+
+    /// `n` independent cursors over the body, e.g. one per worker of a
+    /// parallel filesystem walker, see [`BodyHandle`]. They share the body's
+    /// handles and parsed state (one clone in all) when the format reads
+    /// natively at an offset, and get a clone each otherwise. Patches and
+    /// the read policy carry over.
+    pub fn handles(&mut self, n: usize) -> io::Result<Vec<BodyHandle>> {
+        BodyHandle::split(self, n)
+    }
+
+    /// Make [`Body::hash`] (and [`Body::verify`]) read and decompress on
+    /// `threads` threads while every digest runs on its own. `0` or `1`
+    /// (the default) hashes on the calling thread.
+    pub fn set_hash_threads(&mut self, threads: usize) {
+        self.state.hash_threads = threads;
+    }
+
+    /// Retry failed reads under `policy` before surfacing the error. Applies
+    /// to RAW images (including those opened with [`Body::from_reader`] or
+    /// `Body::from_url`) and block devices; ignored by other formats.
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        match &mut self.format {
+            BodyFormat::RAW { image, .. } => image.set_retry_policy(policy),
+            BodyFormat::DEVICE { image, .. } => image.set_retry_policy(policy),
+            _ => {}
+        }
+    }
+
+    /// Digests of the whole image recorded by the acquisition tool (EWF
+    /// `hash` / `digest` sections, AFF hash segments, AFF4 `aff4:hash`).
+    /// Empty for formats that do not store any.
+    pub fn stored_hashes(&self) -> Vec<(HashAlgorithm, String)> {
+        match &self.format {
+            BodyFormat::EWF { image, .. } => image.stored_hashes().to_vec(),
+            BodyFormat::AFF { image, .. } => image.stored_hashes().to_vec(),
+            BodyFormat::AFF4 { image, .. } => image.stored_hashes().to_vec(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The stored digests with where the image keeps them, one entry per
+    /// distinct value: an EWF MD5 recorded identically in the `hash` and
+    /// `digest` sections is checked once, differing ones separately.
+    fn stored_digests(&self) -> Vec<(HashAlgorithm, String, String)> {
+        let labelled = |hashes: &[(HashAlgorithm, String)], source: &str| {
+            hashes
+                .iter()
+                .map(|(algorithm, value)| (*algorithm, value.clone(), source.to_string()))
+                .collect()
+        };
+        match &self.format {
+            BodyFormat::EWF { image, .. } => {
+                let mut digests: Vec<(HashAlgorithm, String, Vec<&str>)> = Vec::new();
+                for digest in image.stored_digests() {
+                    match digests
+                        .iter_mut()
+                        .find(|(a, v, _)| *a == digest.algorithm && *v == digest.value)
+                    {
+                        Some((_, _, sections)) => sections.push(digest.section),
+                        None => digests.push((
+                            digest.algorithm,
+                            digest.value.clone(),
+                            vec![digest.section],
+                        )),
+                    }
+                }
+                digests
+                    .into_iter()
+                    .map(|(algorithm, value, sections)| {
+                        let plural = if sections.len() > 1 { "s" } else { "" };
+                        let source = format!("EWF {} section{}", sections.join(", "), plural);
+                        (algorithm, value, source)
+                    })
+                    .collect()
+            }
+            BodyFormat::AFF { image, .. } => labelled(image.stored_hashes(), "AFF hash segment"),
+            BodyFormat::AFF4 { image, .. } => labelled(image.stored_hashes(), "AFF4 aff4:hash"),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Recompute the image digests and compare them with the ones stored at
+    /// acquisition time, see [`Body::stored_hashes`]. Every stored copy is
+    /// checked, so an EWF `hash` section disagreeing with the `digest`
+    /// section shows up as a mismatch.
+    ///
+    /// The report is empty when the image stores no digest.
+    ///
+    /// ```no_run
+    /// # use exhume_body::Body;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut body = Body::try_new("/evidence/disk.E01".to_string(), "auto")?;
+    /// let report = body.verify(|_, _| {})?;
+    /// for check in report.mismatches() {
+    ///     eprintln!("{} mismatch: stored {}, computed {}", check.algorithm, check.stored, check.computed);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn verify(&mut self, progress: impl FnMut(u64, u64)) -> io::Result<VerificationReport> {
+        let stored = self.stored_digests();
+        if stored.is_empty() {
+            return Ok(VerificationReport::default());
+        }
+        let mut algorithms: Vec<HashAlgorithm> = Vec::new();
+        for (algorithm, _, _) in &stored {
+            if !algorithms.contains(algorithm) {
+                algorithms.push(*algorithm);
+            }
+        }
+        let computed = self.hash(&algorithms, progress)?;
+        let checks = stored
+            .into_iter()
+            .map(|(algorithm, stored, source)| HashCheck {
+                computed: computed
+                    .iter()
+                    .find(|(a, _)| *a == algorithm)
+                    .map(|(_, digest)| digest.clone())
+                    .unwrap_or_default(),
+                algorithm,
+                stored,
+                source,
+            })
+            .collect();
+        Ok(VerificationReport { checks })
+    }
+
+    /// Read every chunk of the image and check it against its stored
+    /// checksum, returning the sectors of the chunks that fail, sorted and
+    /// merged. `progress` receives the chunks checked and the chunk count.
+    ///
+    /// Only EWF (Adler-32 of every chunk) and AFF (digests of the pages
+    /// that have one) store per-chunk checksums; the list is empty for the
+    /// other formats.
+    pub fn verify_chunks(&self, progress: impl FnMut(u64, u64)) -> Vec<SectorRange> {
+        match &self.format {
+            BodyFormat::EWF { image, .. } => image.verify_chunks(progress),
+            BodyFormat::AFF { image, .. } => image.verify_pages(progress),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Files and directories stored in a logical evidence file (L01, Lx01),
+    /// see [`EWF::file_entries`](ewf::EWF::file_entries). Empty for the
+    /// other formats.
+    pub fn file_entries(&self) -> &[ewf::EwfFileEntry] {
+        match &self.format {
+            BodyFormat::EWF { image, .. } => image.file_entries(),
+            _ => &[],
+        }
+    }
+
+    /// The backend behind the body.
+    pub fn image(&self) -> &dyn BodyImage {
+        match &self.format {
+            BodyFormat::EWF { image, .. } => image,
+            BodyFormat::VMDK { image, .. } => image,
+            BodyFormat::RAW { image, .. } => image,
+            BodyFormat::AFF { image, .. } => image,
+            BodyFormat::AFF4 { image, .. } => image,
+            BodyFormat::VHD { image, .. } => image,
+            BodyFormat::VHDX { image, .. } => image,
+            BodyFormat::VDI { image, .. } => image,
+            BodyFormat::DMG { image, .. } => image,
+            BodyFormat::PARALLELS { image, .. } => image,
+            BodyFormat::QCOW { image, .. } => image,
+            BodyFormat::DEVICE { image, .. } => image,
+            BodyFormat::GZIP { image, .. } => image,
+            BodyFormat::ZSTD { image, .. } => image,
+            BodyFormat::XZ { image, .. } => image,
+            BodyFormat::Custom { image, .. } => image.as_ref(),
+        }
+    }
+
+    /// Mutable access to the backend behind the body.
+    pub fn image_mut(&mut self) -> &mut dyn BodyImage {
+        match &mut self.format {
+            BodyFormat::EWF { image, .. } => image,
+            BodyFormat::VMDK { image, .. } => image,
+            BodyFormat::RAW { image, .. } => image,
+            BodyFormat::AFF { image, .. } => image,
+            BodyFormat::AFF4 { image, .. } => image,
+            BodyFormat::VHD { image, .. } => image,
+            BodyFormat::VHDX { image, .. } => image,
+            BodyFormat::VDI { image, .. } => image,
+            BodyFormat::DMG { image, .. } => image,
+            BodyFormat::PARALLELS { image, .. } => image,
+            BodyFormat::QCOW { image, .. } => image,
+            BodyFormat::DEVICE { image, .. } => image,
+            BodyFormat::GZIP { image, .. } => image,
+            BodyFormat::ZSTD { image, .. } => image,
+            BodyFormat::XZ { image, .. } => image,
+            BodyFormat::Custom { image, .. } => image.as_mut(),
+        }
+    }
+
+    /// The physical files making up the evidence (EWF segments, split RAW
+    /// segments, VMDK descriptor, extents and parents, AFF4 volumes and
+    /// their members) with their sizes and roles, to check an image set is
+    /// complete. Other formats list their single file.
+    pub fn layout(&mut self) -> io::Result<Vec<EvidenceFile>> {
+        let mut files = match &self.format {
+            BodyFormat::EWF { image, .. } => image.files()?,
+            BodyFormat::VMDK { image, .. } => image.files()?,
+            BodyFormat::AFF4 { image, .. } => image.files()?,
+            BodyFormat::RAW { image, .. } => image.files()?,
+            // Block devices report no file size.
+            BodyFormat::DEVICE { .. } => vec![EvidenceFile {
+                path: String::new(),
+                size: self.size()?,
+                role: self.format_description().to_string(),
+            }],
+            _ => vec![EvidenceFile {
+                path: String::new(),
+                size: std::fs::metadata(&self.path)?.len(),
+                role: self.format_description().to_string(),
+            }],
+        };
+        for file in files.iter_mut().filter(|file| file.path.is_empty()) {
+            file.path.clone_from(&self.path);
+        }
+        Ok(files)
+    }
+
+    /// How the evidence is protected: files and devices opened read-only
+    /// and, on Unix, whether another process holds a writer lock on any of
+    /// the files of [`Body::layout`] right now. [`IntegrityMode::assert`]
+    /// turns it into a check for the examination workflow.
+    ///
+    /// ```no_run
+    /// # use exhume_body::Body;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut body = Body::try_new("/evidence/disk.E01".to_string(), "auto")?;
+    /// body.integrity_mode()?.assert()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn integrity_mode(&mut self) -> io::Result<IntegrityMode> {
+        let mut paths: Vec<String> = Vec::new();
+        for file in self.layout()? {
+            // AFF4 members are listed as `volume!member`.
+            let path = match file.path.split_once('!') {
+                Some((volume, _)) => volume.to_string(),
+                None => file.path,
+            };
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        Ok(IntegrityMode::check(paths))
+    }
+
+    /// Byte ranges of the body backed by stored data, sorted and disjoint.
+    /// Everything outside them reads as zeros without being stored, so
+    /// carving and conversion tools can skip it.
+    ///
+    /// VMDK reports its allocated grains (including those of its parents),
+    /// AFF4 its map intervals, a RAW image read with a ddrescue mapfile
+    /// (see [`BodyBuilder::ddrescue_map`]) what was rescued; EWF stores
+    /// every chunk, and the other formats report the whole body. The map is
+    /// built on first use and shared with the body's clones.
+    pub fn allocated_ranges(&mut self) -> io::Result<Vec<Range<u64>>> {
+        Ok(self.allocation()?.to_vec())
+    }
+
+    /// Move to the first byte of stored data at or after `offset`, like
+    /// `lseek(SEEK_DATA)`, and return its position. `None`, with the
+    /// position unchanged, when only implicit zeros follow.
+    ///
+    /// Together with [`Body::seek_hole`] this copies a sparse image without
+    /// reading its empty space:
+    ///
+    /// ```no_run
+    /// # use exhume_body::Body;
+    /// # use std::io::Read;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut body = Body::try_new("/evidence/disk.vmdk".to_string(), "auto")?;
+    /// let mut offset = 0;
+    /// while let Some(start) = body.seek_data(offset)? {
+    ///     let end = body.seek_hole(start)?;
+    ///     body.seek_data(start)?;
+    ///     let mut data = Vec::new();
+    ///     (&mut body).take(end - start).read_to_end(&mut data)?;
+    ///     // write `data` at `start`...
+    ///     offset = end;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn seek_data(&mut self, offset: u64) -> io::Result<Option<u64>> {
+        match metadata::next_data(self.allocation()?, offset) {
+            Some(data) => self.seek(SeekFrom::Start(data)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Move to the first byte of implicit zeros at or after `offset`, like
+    /// `lseek(SEEK_HOLE)`, and return its position. The end of the body
+    /// counts as a hole; `offset` past it is an error.
+    pub fn seek_hole(&mut self, offset: u64) -> io::Result<u64> {
+        let size = self.size()?;
+        if offset > size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "offset {} is past the end of the body ({} bytes)",
+                    offset, size
+                ),
+            ));
+        }
+        let hole = metadata::next_hole(self.allocation()?, offset);
+        self.seek(SeekFrom::Start(hole))
+    }
+
+    fn allocation(&mut self) -> io::Result<&[Range<u64>]> {
+        if self.state.allocation.get().is_none() {
+            let ranges = match &self.format {
+                BodyFormat::RAW { image, .. } => image.allocated_ranges()?,
+                BodyFormat::VMDK { image, .. } => image.allocated_ranges()?,
+                BodyFormat::AFF4 { image, .. } => image.allocated_ranges(),
+                _ => {
+                    let size = self.size()?;
+                    metadata::merge_ranges(std::iter::once(0..size).collect())
+                }
+            };
+            let _ = self.state.allocation.set(ranges);
+        }
+        Ok(self.state.allocation.get().unwrap())
+    }
+
+    /// Structured description of the body, for tools that need more than the
+    /// [`Body::print_info`] log output.
+    pub fn metadata(&mut self) -> io::Result<DiskMetadata> {
+        let mut metadata = DiskMetadata {
+            format: self.format_name().to_string(),
+            description: self.format_description().to_string(),
+            size: self.size()?,
+            sector_size: self.get_sector_size(),
+            segment_count: 1,
+            stored_hashes: self.stored_hashes(),
+            ..DiskMetadata::default()
+        };
+        match &self.format {
+            BodyFormat::EWF { image, .. } => {
+                metadata.segment_count = image.segment_count();
+                metadata.acquisition = image.acquisition_info();
+                metadata.geometry = image.geometry();
+            }
+            BodyFormat::VMDK { image, .. } => {
+                metadata.segment_count = image.extent_count();
+                metadata.geometry = image.geometry();
+            }
+            BodyFormat::RAW { image, .. } => metadata.segment_count = image.segment_count(),
+            BodyFormat::AFF { image, .. } => {
+                metadata.acquisition = image.acquisition_info().to_vec();
+            }
+            BodyFormat::AFF4 { image, .. } => {
+                metadata.segment_count = image.volume_count();
+                metadata.acquisition = image.acquisition_info();
+            }
+            BodyFormat::VHD { image, .. } => metadata.geometry = image.geometry(),
+            BodyFormat::VDI { image, .. } => metadata.geometry = image.geometry(),
+            _ => {}
+        }
+        Ok(metadata)
+    }
+
+    /// Short name of the format, as accepted by [`Body::try_new`].
+    pub fn format_name(&self) -> &'static str {
         match &self.format {
-            BodyFormat::EWF { image, .. } => image.get_sector_size(),
-            BodyFormat::VMDK { image, .. } => image.get_sector_size() as u16,
-            BodyFormat::AFF { image, .. } => image.get_sector_size(),
-            BodyFormat::AFF4 { image, .. } => image.get_sector_size(),
-            BodyFormat::RAW { .. } => 512,
-            // All other compatible formats are handled here.
+            BodyFormat::EWF { .. } => "ewf",
+            BodyFormat::VMDK { .. } => "vmdk",
+            BodyFormat::RAW { .. } => "raw",
+            BodyFormat::AFF { .. } => "aff",
+            BodyFormat::AFF4 { .. } => "aff4",
+            BodyFormat::VHD { .. } => "vhd",
+            BodyFormat::VHDX { .. } => "vhdx",
+            BodyFormat::VDI { .. } => "vdi",
+            BodyFormat::DMG { .. } => "dmg",
+            BodyFormat::PARALLELS { .. } => "parallels",
+            BodyFormat::QCOW { .. } => "qcow",
+            BodyFormat::DEVICE { .. } => "device",
+            BodyFormat::GZIP { .. } => "gzip",
+            BodyFormat::ZSTD { .. } => "zstd",
+            BodyFormat::XZ { .. } => "xz",
+            BodyFormat::Custom { name, .. } => name,
         }
     }
 
@@ -188,88 +1674,220 @@ impl Body {
             BodyFormat::RAW { description, .. } => description,
             BodyFormat::AFF { description, .. } => description,
             BodyFormat::AFF4 { description, .. } => description,
-            // Handle additional formats here.
+            BodyFormat::VHD { description, .. } => description,
+            BodyFormat::VHDX { description, .. } => description,
+            BodyFormat::VDI { description, .. } => description,
+            BodyFormat::DMG { description, .. } => description,
+            BodyFormat::PARALLELS { description, .. } => description,
+            BodyFormat::QCOW { description, .. } => description,
+            BodyFormat::DEVICE { description, .. } => description,
+            BodyFormat::GZIP { description, .. } => description,
+            BodyFormat::ZSTD { description, .. } => description,
+            BodyFormat::XZ { description, .. } => description,
+            BodyFormat::Custom { description, .. } => description,
         }
     }
 
-    /// Detect the image format by attempting to create each format.
-    /// Currently, tries EWF first then falls back to RAW.
-    fn detect_format(file_path: &str) -> BodyFormat {
-        // Try EWF detection first.
-        if let Ok(evidence) = EWF::new(file_path) {
-            info!("Detected an EWF disk image.");
-            return BodyFormat::EWF {
-                image: evidence,
-                description: "Expert Witness Compression Format (EWF)".to_string(),
-            };
-        }
+    fn open_plugin(plugin: &FormatPlugin, file_path: &str) -> Result<BodyFormat, ExhumeBodyError> {
+        let image = (plugin.open)(file_path).map_err(|e| ExhumeBodyError::open(plugin.name, e))?;
+        Ok(BodyFormat::Custom {
+            name: plugin.name,
+            image,
+            description: plugin.description.to_string(),
+        })
+    }
 
-        // Then try VMDK detection.
-        if let Ok(evidence) = VMDK::new(file_path) {
-            info!("Detected a VMDK disk image.");
-            return BodyFormat::VMDK {
-                image: evidence,
-                description: "VMDK (Virtual Machine Disk) file".to_string(),
-            };
+    /// Report which formats `file_path` opens as, and why the others were
+    /// rejected, without committing to one. Candidates are listed in the
+    /// order `"auto"` detection tries them, so
+    /// [`ProbeResult::detected`] is the format it would pick.
+    pub fn probe(file_path: &str) -> ProbeResult {
+        let mut result = ProbeResult {
+            path: file_path.to_string(),
+            ..ProbeResult::default()
+        };
+        if let Ok(mut file) = integrity::open_read_only(file_path) {
+            let mut magic = Vec::with_capacity(16);
+            if (&mut file).take(16).read_to_end(&mut magic).is_ok() {
+                result.magic = magic;
+            }
         }
 
-        // Then try AFF detection.
-        if let Ok(evidence) = AFF::new(file_path) {
-            info!("Detected an AFF disk image.");
-            return BodyFormat::AFF {
-                image: evidence,
-                description: "Advanced Forensics Format (AFF)".to_string(),
+        let mut candidate = |format: &str, opened: Result<Body, ExhumeBodyError>| {
+            let (matched, detail) = match opened {
+                Ok(mut body) => {
+                    let size = body.size().map(|s| format!(", {} bytes", s));
+                    (
+                        true,
+                        format!("{}{}", body.format_description(), size.unwrap_or_default()),
+                    )
+                }
+                Err(e) => (false, e.reason()),
             };
+            result.candidates.push(ProbeCandidate {
+                format: format.to_string(),
+                matched,
+                detail,
+            });
+        };
+
+        let options = BodyOptions::default();
+        if device::is_device_path(file_path) {
+            candidate(
+                "device",
+                Self::open(file_path.to_string(), "device", &options),
+            );
+        } else {
+            candidate(
+                "device",
+                Err(ExhumeBodyError::Device("not a block device path".into())),
+            );
+        }
+        for format in DETECTION_ORDER {
+            candidate(format, Self::open(file_path.to_string(), format, &options));
+        }
+        for plugin in registry::registered_formats() {
+            if (plugin.detect)(file_path) {
+                let opened = Self::open_plugin(&plugin, file_path).map(|format| Body {
+                    path: file_path.to_string(),
+                    format,
+                    state: BodyState::default(),
+                });
+                candidate(plugin.name, opened);
+            } else {
+                candidate(
+                    plugin.name,
+                    Err(ExhumeBodyError::open(
+                        "plugin",
+                        "detector did not recognize the file",
+                    )),
+                );
+            }
         }
+        candidate("raw", Self::open(file_path.to_string(), "raw", &options));
+        result
+    }
 
-        // Then try AFF4 detection.
-        if let Ok(evidence) = AFF4::new(file_path) {
-            info!("Detected an AFF4/AFF4-L volume (ImageStream).");
-            return BodyFormat::AFF4 {
-                image: evidence,
-                description: "AFF4 / AFF4-L (ImageStream)".to_string(),
-            };
+    /// Detect the image format by attempting to create each format.
+    /// Block devices are checked first, then every built-in image format and
+    /// the registered ones, falling back to RAW.
+    fn detect_format(
+        file_path: &str,
+        options: &BodyOptions,
+    ) -> Result<BodyFormat, ExhumeBodyError> {
+        // Block devices are read as-is.
+        if device::is_device_path(file_path) {
+            if let Ok(body) = Self::open(file_path.to_string(), "device", options) {
+                info!("Detected a block device.");
+                return Ok(body.format);
+            }
         }
 
-        // Default to RAW.
-        match RAW::new(file_path) {
-            Ok(evidence) => {
-                info!("Detected RAW Data");
-                BodyFormat::RAW {
-                    image: evidence,
-                    description: "Raw image format".to_string(),
-                }
+        // Then try the built-in image formats.
+        for format in DETECTION_ORDER {
+            if let Ok(body) = Self::open(file_path.to_string(), format, options) {
+                info!("Detected {}.", body.format_description());
+                return Ok(body.format);
             }
-            Err(err) => {
-                error!("Error opening data: {}", err);
-                std::process::exit(1);
+        }
+
+        // Then try the registered formats.
+        for plugin in registry::registered_formats() {
+            if (plugin.detect)(file_path) {
+                info!("Detected a registered {} image.", plugin.name);
+                return Self::open_plugin(&plugin, file_path);
             }
         }
+
+        // Default to RAW.
+        let evidence = RAW::new(file_path)?;
+        info!("Detected RAW Data");
+        Ok(BodyFormat::RAW {
+            image: evidence,
+            description: "Raw image format".to_string(),
+        })
     }
 }
 
 impl Read for Body {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        match &mut self.format {
-            BodyFormat::EWF { image, .. } => image.read(buf),
-            BodyFormat::VMDK { image, .. } => image.read(buf),
-            BodyFormat::RAW { image, .. } => image.read(buf),
-            BodyFormat::AFF { image, .. } => image.read(buf),
-            BodyFormat::AFF4 { image, .. } => image.read(buf),
-            // TODO: Handle other compatible formats here.
+        let _span = trace::io_span!("body.read", len = buf.len());
+        if self.state.patches.is_empty() {
+            return self.read_evidence(buf);
         }
+        let position = self.image_mut().stream_position()?;
+        let n = self.read_evidence(buf)?;
+        self.state.patches.apply(position, &mut buf[..n]);
+        Ok(n)
+    }
+}
+
+impl Body {
+    /// [`Read::read`] without the patches.
+    fn read_evidence(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some(damage) = self.state.damage.clone() else {
+            let n = self.image_mut().read(buf)?;
+            self.state.io.read(n);
+            return Ok(n);
+        };
+        let position = self.image_mut().stream_position()?;
+        let n = match self.image_mut().read(buf) {
+            Ok(n) => n,
+            Err(err) => {
+                warn!("Damaged data at 0x{:x}: {}", position, err);
+                let n = self.read_damaged(&damage, position, buf);
+                self.image_mut()
+                    .seek(SeekFrom::Start(position + n as u64))?;
+                n
+            }
+        };
+        self.state.io.read(n);
+        Ok(n)
     }
 }
 
 impl Seek for Body {
     fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
-        match &mut self.format {
-            BodyFormat::EWF { image, .. } => image.seek(pos),
-            BodyFormat::VMDK { image, .. } => image.seek(pos),
-            BodyFormat::RAW { image, .. } => image.seek(pos),
-            BodyFormat::AFF { image, .. } => image.seek(pos),
-            BodyFormat::AFF4 { image, .. } => image.seek(pos),
-            // TODO: Handle other compatible formats here.
+        self.image_mut().seek(pos)
+    }
+}
+
+impl ReadAt for Body {
+    /// Native positional reads (`pread` on the shared handles, no cursor)
+    /// for RAW, EWF, VMDK, AFF4, VHD, VHDX, VDI, Parallels and block
+    /// devices; the formats decoding through a cache of their own (QCOW,
+    /// DMG, AFF, compressed streams) read through a clone of their backend.
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let _span = trace::io_span!("body.read_at", offset, len = buf.len());
+        let n = match (self.read_image_at(offset, buf), &self.state.damage) {
+            (Err(err), Some(damage)) => {
+                warn!("Damaged data at 0x{:x}: {}", offset, err);
+                self.read_damaged(damage, offset, buf)
+            }
+            (result, _) => result?,
+        };
+        self.state.io.read(n);
+        self.state.patches.apply(offset, &mut buf[..n]);
+        Ok(n)
+    }
+
+    fn read_at_append(&self, offset: u64, buf: &mut Vec<u8>, len: usize) -> io::Result<usize> {
+        let _span = trace::io_span!("body.read_at", offset, len);
+        let start = buf.len();
+        let result = match &self.format {
+            BodyFormat::RAW { image, .. } => image.read_at_append(offset, buf, len),
+            BodyFormat::EWF { image, .. } => image.read_at_append(offset, buf, len),
+            _ => return read_at::append_zeroed(self, offset, buf, len),
+        };
+        match (result, &self.state.damage) {
+            // Go through `read_at` to fill the damaged sectors.
+            (Err(_), Some(_)) => read_at::append_zeroed(self, offset, buf, len),
+            (result, _) => {
+                let n = result?;
+                self.state.io.read(n);
+                self.state.patches.apply(offset, &mut buf[start..start + n]);
+                Ok(n)
+            }
         }
     }
 }
@@ -292,6 +1910,38 @@ impl BodySlice {
             pos: 0,
         })
     }
+
+    /// Slice of `sector_count` sectors starting at `start_sector`, in units
+    /// of the body's sector size ([`Body::get_sector_size`]), e.g. a
+    /// partition from its table entry.
+    pub fn from_sectors(src: &Body, start_sector: u64, sector_count: u64) -> io::Result<Self> {
+        let sector_size = src.get_sector_size() as u64;
+        match (
+            start_sector.checked_mul(sector_size),
+            sector_count.checked_mul(sector_size),
+        ) {
+            (Some(start), Some(len)) if start.checked_add(len).is_some() => {
+                BodySlice::new(src, start, len)
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "sector range overflows the body",
+            )),
+        }
+    }
+
+    /// Sub-slice of `slice_len` bytes starting at `slice_start` within this
+    /// slice (e.g. a file system inside a partition).
+    pub fn slice(&self, slice_start: u64, slice_len: u64) -> io::Result<Self> {
+        let end = slice_start.checked_add(slice_len);
+        if end.is_none_or(|end| end > self.slice_len) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "sub-slice out of slice",
+            ));
+        }
+        BodySlice::new(&self.body, self.slice_start + slice_start, slice_len)
+    }
 }
 
 impl Read for BodySlice {
@@ -329,6 +1979,17 @@ impl Seek for BodySlice {
     }
 }
 
+impl ReadAt for BodySlice {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        if offset >= self.slice_len {
+            return Ok(0);
+        }
+        let max = std::cmp::min(buf.len() as u64, self.slice_len - offset) as usize;
+        self.body
+            .read_at(self.slice_start + offset, &mut buf[..max])
+    }
+}
+
 impl Clone for BodySlice {
     fn clone(&self) -> Self {
         let mut body = self.body.clone();
@@ -342,3 +2003,9 @@ impl Clone for BodySlice {
         }
     }
 }
+
+// `Body` is documented as shareable across threads: keep it that way.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Body>();
+};
@@ -1,15 +1,20 @@
 use clap::*;
 use clap_num::maybe_hex;
-use exhume_body::Body;
+use exhume_body::vmdk::{self, VMDKDescriptorFile};
+use exhume_body::{
+    convert_with_progress, BlockManifest, Body, BodySlice, ConvertOptions, ConvertTarget,
+    EwfCaseInfo, EwfCompression, HashAlgorithm, RawWriter, ReadPolicy, RetryPolicy,
+};
 use log::{debug, error, info, LevelFilter};
-use std::io::Read;
+use serde::Serialize;
+use std::io::{IsTerminal, Read, Seek, SeekFrom, Write};
 
 fn process_file(file_path: &str, format: &str, size: &u64, offset: &u64) {
     let mut reader: Body;
     match format {
         "raw" => {
             info!("Processing the file '{}' in 'raw' format...", file_path);
-            reader = Body::new_from(file_path.to_string(), format, Some(*offset));
+            reader = open_body(file_path, format, offset);
 
             debug!("------------------------------------------------------------");
             info!("Selected format: RAW");
@@ -17,7 +22,7 @@ fn process_file(file_path: &str, format: &str, size: &u64, offset: &u64) {
             debug!("------------------------------------------------------------");
         }
         "ewf" => {
-            reader = Body::new_from(file_path.to_string(), format, Some(*offset));
+            reader = open_body(file_path, format, offset);
             info!("Processing the file '{}' in 'ewf' format...", file_path);
             info!("------------------------------------------------------------");
             info!("Selected format: EWF");
@@ -27,7 +32,7 @@ fn process_file(file_path: &str, format: &str, size: &u64, offset: &u64) {
         }
         "vmdk" => {
             info!("Processing the file '{}' in 'vmdk' format...", file_path);
-            reader = Body::new_from(file_path.to_string(), format, Some(*offset));
+            reader = open_body(file_path, format, offset);
             info!("------------------------------------------------------------");
             info!("Selected format: VMDK");
             info!("Description: VMDK (Virtual Machine Disk) file.");
@@ -35,7 +40,7 @@ fn process_file(file_path: &str, format: &str, size: &u64, offset: &u64) {
         }
         "aff" => {
             info!("Processing the file '{}' in 'aff' format...", file_path);
-            reader = Body::new_from(file_path.to_string(), "aff", Some(*offset));
+            reader = open_body(file_path, "aff", offset);
             info!("------------------------------------------------------------");
             info!("Selected format: AFF");
             info!("Description: Advanced Forensics Format.");
@@ -44,20 +49,113 @@ fn process_file(file_path: &str, format: &str, size: &u64, offset: &u64) {
         }
         "auto" => {
             info!("Processing the file '{}' in 'auto' format...", file_path);
-            reader = Body::new_from(file_path.to_string(), format, Some(*offset));
+            reader = open_body(file_path, format, offset);
         }
         "aff4" | "aff4l" => {
             info!("Processing the file '{}' in 'aff4' format...", file_path);
-            reader = Body::new_from(file_path.to_string(), "aff4", Some(*offset));
+            reader = open_body(file_path, "aff4", offset);
             info!("------------------------------------------------------------");
             info!("Selected format: AFF4 / AFF4-L");
             info!("Description: AFF4 ImageStream (Zip volume).");
             info!("Sector size: {:?}", reader.get_sector_size());
             debug!("------------------------------------------------------------");
         }
+        "vhd" => {
+            info!("Processing the file '{}' in 'vhd' format...", file_path);
+            reader = open_body(file_path, format, offset);
+            info!("------------------------------------------------------------");
+            info!("Selected format: VHD");
+            info!("Description: Virtual Hard Disk (fixed or dynamic).");
+            info!("Sector size: {:?}", reader.get_sector_size());
+            debug!("------------------------------------------------------------");
+        }
+        "vhdx" => {
+            info!("Processing the file '{}' in 'vhdx' format...", file_path);
+            reader = open_body(file_path, "vhdx", offset);
+            info!("------------------------------------------------------------");
+            info!("Selected format: VHDX");
+            info!("Description: Hyper-V Virtual Hard Disk v2.");
+            info!("Sector size: {:?}", reader.get_sector_size());
+            debug!("------------------------------------------------------------");
+        }
+        "vdi" => {
+            info!("Processing the file '{}' in 'vdi' format...", file_path);
+            reader = open_body(file_path, "vdi", offset);
+            info!("------------------------------------------------------------");
+            info!("Selected format: VDI");
+            info!("Description: VirtualBox Disk Image.");
+            info!("Sector size: {:?}", reader.get_sector_size());
+            debug!("------------------------------------------------------------");
+        }
+        "dmg" => {
+            info!("Processing the file '{}' in 'dmg' format...", file_path);
+            reader = open_body(file_path, "dmg", offset);
+            info!("------------------------------------------------------------");
+            info!("Selected format: DMG");
+            info!("Description: Apple Disk Image (UDIF).");
+            info!("Sector size: {:?}", reader.get_sector_size());
+            debug!("------------------------------------------------------------");
+        }
+        "parallels" | "hds" | "hdd" => {
+            info!(
+                "Processing the file '{}' in 'parallels' format...",
+                file_path
+            );
+            reader = open_body(file_path, "parallels", offset);
+            info!("------------------------------------------------------------");
+            info!("Selected format: Parallels");
+            info!("Description: Parallels Desktop expanding disk image.");
+            info!("Sector size: {:?}", reader.get_sector_size());
+            debug!("------------------------------------------------------------");
+        }
+        "qcow" | "qcow1" => {
+            info!("Processing the file '{}' in 'qcow' format...", file_path);
+            reader = open_body(file_path, "qcow", offset);
+            info!("------------------------------------------------------------");
+            info!("Selected format: QCOW");
+            info!("Description: QEMU Copy-On-Write image (version 1).");
+            info!("Sector size: {:?}", reader.get_sector_size());
+            debug!("------------------------------------------------------------");
+        }
+        "device" => {
+            info!("Processing the file '{}' in 'device' format...", file_path);
+            reader = open_body(file_path, "device", offset);
+            info!("------------------------------------------------------------");
+            info!("Selected format: Device");
+            info!("Description: Physical block device (read-only).");
+            info!("Sector size: {:?}", reader.get_sector_size());
+            debug!("------------------------------------------------------------");
+        }
+        "gzip" | "gz" => {
+            info!("Processing the file '{}' in 'gzip' format...", file_path);
+            reader = open_body(file_path, "gzip", offset);
+            info!("------------------------------------------------------------");
+            info!("Selected format: Gzip");
+            info!("Description: Gzip-compressed RAW image.");
+            info!("Sector size: {:?}", reader.get_sector_size());
+            debug!("------------------------------------------------------------");
+        }
+        "zstd" | "zst" => {
+            info!("Processing the file '{}' in 'zstd' format...", file_path);
+            reader = open_body(file_path, "zstd", offset);
+            info!("------------------------------------------------------------");
+            info!("Selected format: Zstd");
+            info!("Description: Zstandard-compressed RAW image.");
+            info!("Sector size: {:?}", reader.get_sector_size());
+            debug!("------------------------------------------------------------");
+        }
+        "xz" => {
+            info!("Processing the file '{}' in 'xz' format...", file_path);
+            reader = open_body(file_path, "xz", offset);
+            info!("------------------------------------------------------------");
+            info!("Selected format: XZ");
+            info!("Description: XZ-compressed RAW image.");
+            info!("Sector size: {:?}", reader.get_sector_size());
+            debug!("------------------------------------------------------------");
+        }
         _ => {
             error!(
-                "Invalid format '{}'. Supported formats are 'raw', 'ewf', 'vmdk', 'aff', 'aff4', and 'auto'.",
+                "Invalid format '{}'. Supported formats are 'raw', 'ewf', 'vmdk', 'aff', 'aff4', 'vhd', 'vhdx', 'vdi', 'dmg', 'parallels', 'qcow', 'device', 'gzip', 'zstd', 'xz', and 'auto'.",
                 format
             );
             std::process::exit(1);
@@ -71,35 +169,410 @@ fn process_file(file_path: &str, format: &str, size: &u64, offset: &u64) {
     println!("{}", result);
 }
 
+fn open_body(file_path: &str, format: &str, offset: &u64) -> Body {
+    match Body::builder(file_path)
+        .format(format)
+        .offset(*offset)
+        .build()
+    {
+        Ok(body) => body,
+        Err(err) => {
+            error!("Error: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Stream `size` bytes (the rest of the body when `None`) starting at
+/// `offset` to `out`, or to stdout when `out` is `None` or `-`.
+fn extract(
+    mut body: Body,
+    offset: u64,
+    size: Option<u64>,
+    out: Option<&String>,
+) -> Result<(), String> {
+    let total = body.size().map_err(|e| e.to_string())?;
+    if offset > total {
+        return Err(format!(
+            "offset 0x{:x} is past the end of the body ({} bytes)",
+            offset, total
+        ));
+    }
+    let size = size.unwrap_or(total - offset);
+    if offset.checked_add(size).is_none_or(|end| end > total) {
+        return Err(format!(
+            "range 0x{:x}+0x{:x} ends past the end of the body ({} bytes)",
+            offset, size, total
+        ));
+    }
+
+    let mut slice = BodySlice::new(&body, offset, size).map_err(|e| e.to_string())?;
+    let mut writer = RawWriter::new();
+    if std::io::stderr().is_terminal() {
+        writer = writer.progress(progress_bar);
+    }
+    let summary = match out.map(String::as_str) {
+        // Straight to the stdout handle, block by block, so a reader at the
+        // other end of a pipe gets the data as it is decoded. A reader that
+        // stops early (`| head`) ends the copy quietly.
+        None | Some("-") => match writer.write(&mut slice, &mut std::io::stdout().lock()) {
+            Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => return Ok(()),
+            result => result.map_err(|e| e.to_string())?,
+        },
+        Some(path) => writer
+            .write_to_path(&mut slice, path)
+            .map_err(|e| format!("{}: {}", path, e))?,
+    };
+    if std::io::stderr().is_terminal() {
+        eprintln!();
+    }
+    info!(
+        "Extracted {} bytes from offset 0x{:x}",
+        summary.bytes_written, offset
+    );
+    Ok(())
+}
+
+/// Print the files of the evidence as a table, then their count and total
+/// size.
+fn layout(body: &mut Body) -> std::io::Result<()> {
+    let files = body.layout()?;
+    let role_width = files.iter().map(|f| f.role.len()).max().unwrap_or(0);
+    for file in &files {
+        println!(
+            "{:<width$}  {:>15}  {}",
+            file.role,
+            file.size,
+            file.path,
+            width = role_width
+        );
+    }
+    println!(
+        "{} files, {} bytes",
+        files.len(),
+        files.iter().map(|f| f.size).sum::<u64>()
+    );
+    Ok(())
+}
+
+/// One row of the `entropy` report.
+#[derive(Serialize)]
+struct BlockEntropy {
+    offset: u64,
+    size: u64,
+    /// Shannon entropy in bits per byte, from 0 (constant) to 8 (random).
+    entropy: f64,
+}
+
+/// Shannon entropy of `data` in bits per byte.
+fn shannon_entropy(data: &[u8]) -> f64 {
+    let mut counts = [0u64; 256];
+    for &byte in data {
+        counts[byte as usize] += 1;
+    }
+    let len = data.len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Write the entropy of every `block` bytes of the body to `out`, as CSV
+/// or as a JSON array. The last block may be shorter.
+fn entropy(body: &mut Body, block: u64, json: bool, out: &mut dyn Write) -> std::io::Result<()> {
+    let total = body.size()?;
+    let progress = std::io::stderr().is_terminal();
+    let mut buf = vec![0u8; block as usize];
+    body.seek(SeekFrom::Start(0))?;
+
+    if json {
+        write!(out, "[")?;
+    } else {
+        writeln!(out, "offset,size,entropy")?;
+    }
+    let mut offset = 0;
+    while offset < total {
+        let len = block.min(total - offset);
+        let data = &mut buf[..len as usize];
+        body.read_exact(data)?;
+        let row = BlockEntropy {
+            offset,
+            size: len,
+            entropy: shannon_entropy(data),
+        };
+        if json {
+            let separator = if offset == 0 { "" } else { "," };
+            write!(out, "{}\n  {}", separator, serde_json::to_string(&row)?)?;
+        } else {
+            writeln!(out, "{},{},{:.4}", row.offset, row.size, row.entropy)?;
+        }
+        offset += len;
+        if progress {
+            progress_bar(offset, total);
+        }
+    }
+    if json {
+        writeln!(out, "\n]")?;
+    }
+    if progress {
+        eprintln!();
+    }
+    out.flush()
+}
+
+/// Hash every `block` bytes of the body with `algorithm` and write the
+/// manifest to `out`, as JSON or as CSV rows of offset, size and digest.
+fn manifest(
+    body: &mut Body,
+    block: u64,
+    algorithm: HashAlgorithm,
+    json: bool,
+    out: &mut dyn Write,
+) -> std::io::Result<()> {
+    let progress = std::io::stderr().is_terminal();
+    let manifest = body.block_manifest(block as usize, algorithm, |done, total| {
+        if progress {
+            progress_bar(done, total)
+        }
+    })?;
+    if progress {
+        eprintln!();
+    }
+    if json {
+        serde_json::to_writer_pretty(&mut *out, &manifest)?;
+        writeln!(out)?;
+    } else {
+        writeln!(out, "offset,size,{}", manifest.algorithm)?;
+        for (index, digest) in manifest.blocks.iter().enumerate() {
+            let range = manifest.block_range(index);
+            writeln!(
+                out,
+                "{},{},{}",
+                range.start,
+                range.end - range.start,
+                digest
+            )?;
+        }
+    }
+    info!(
+        "{} of the whole body: {}",
+        manifest.algorithm, manifest.digest
+    );
+    out.flush()
+}
+
+/// Check the body against the JSON manifest at `path`, logging the blocks
+/// that differ.
+fn verify_manifest(body: &mut Body, path: &str) -> Result<(), String> {
+    let data = std::fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+    let manifest: BlockManifest =
+        serde_json::from_slice(&data).map_err(|e| format!("{}: {}", path, e))?;
+    let progress = std::io::stderr().is_terminal();
+    let changed = manifest
+        .verify(body, |done, total| {
+            if progress {
+                progress_bar(done, total)
+            }
+        })
+        .map_err(|e| e.to_string())?;
+    if progress {
+        eprintln!();
+    }
+    if changed.is_empty() {
+        info!(
+            "All {} blocks match the manifest of {}",
+            manifest.blocks.len(),
+            manifest.source
+        );
+        return Ok(());
+    }
+    for range in &changed {
+        error!(
+            "0x{:x}..0x{:x} differs from the manifest",
+            range.start, range.end
+        );
+    }
+    Err(format!(
+        "{} bytes differ from the manifest",
+        changed.iter().map(|r| r.end - r.start).sum::<u64>()
+    ))
+}
+
+/// Image the `source` argument (a block device, or any body) into a new
+/// E01 or raw image, hashing it on the way and reading the result back.
+fn acquire(matches: &ArgMatches) -> Result<(), String> {
+    let source = matches.get_one::<String>("source").unwrap();
+    let retries = *matches.get_one::<u32>("retries").unwrap();
+    let mut builder = Body::builder(source)
+        .format(matches.get_one::<String>("source_format").unwrap())
+        .retry_policy(RetryPolicy::new(retries + 1));
+    if matches.get_one::<String>("on_error").unwrap() == "fill" {
+        builder = builder
+            .read_policy(ReadPolicy::BestEffort { fill: Vec::new() })
+            .error_granularity(*matches.get_one::<u32>("error_granularity").unwrap());
+    }
+    let mut body = builder.build().map_err(|e| e.to_string())?;
+
+    let out = matches.get_one::<String>("out").unwrap().into();
+    let target = match matches.get_one::<String>("format").unwrap().as_str() {
+        "raw" => ConvertTarget::Raw(out),
+        _ => ConvertTarget::Ewf(out),
+    };
+    let text = |id: &str| matches.get_one::<String>(id).cloned().unwrap_or_default();
+    let opts = ConvertOptions {
+        hashes: matches
+            .get_many::<String>("hash")
+            .unwrap()
+            .map(|name| name.parse())
+            .collect::<Result<_, _>>()?,
+        verify: !matches.get_flag("no_verify"),
+        segment_size: matches.get_one::<u64>("segment_size").copied(),
+        compression: match matches.get_one::<String>("compression").unwrap().as_str() {
+            "none" => EwfCompression::None,
+            "best" => EwfCompression::Best,
+            _ => EwfCompression::Fast,
+        },
+        case: EwfCaseInfo {
+            case_number: text("case"),
+            evidence_number: text("evidence"),
+            description: text("description"),
+            examiner: text("examiner"),
+            notes: text("notes"),
+            ..EwfCaseInfo::default()
+        },
+        ..ConvertOptions::default()
+    };
+
+    let progress = std::io::stderr().is_terminal();
+    let summary = convert_with_progress(&mut body, target, opts, move |done, total| {
+        if progress {
+            progress_bar(done, total)
+        }
+    })
+    .map_err(|e| e.to_string())?;
+    if progress {
+        eprintln!();
+    }
+
+    for output in &summary.outputs {
+        info!("Wrote {}", output.display());
+    }
+    for (algorithm, digest) in &summary.source_hashes {
+        info!("{} of {}: {}", algorithm, source, digest);
+    }
+    if !summary.bad_ranges.is_empty() {
+        error!(
+            "{} sectors could not be read and were zero-filled",
+            summary.bad_ranges.iter().map(|r| r.count).sum::<u64>()
+        );
+    }
+    if !summary.destination_hashes.is_empty() && !summary.is_verified() {
+        return Err("the image read back does not match the source".to_string());
+    }
+    Ok(())
+}
+
+/// Write the descriptor embedded in the monolithic sparse VMDK at `path` to
+/// `out` (stdout when `None`), as stored or, with `rewrite`, regenerated
+/// from its parsed fields.
+fn descriptor(path: &str, rewrite: bool, out: Option<&String>) -> Result<(), String> {
+    let mut data = vmdk::read_embedded_descriptor(path).map_err(|e| e.to_string())?;
+    if rewrite {
+        data = VMDKDescriptorFile::from_bytes(&data)?.to_bytes();
+    }
+    match out {
+        Some(path) => std::fs::File::create_new(path)
+            .and_then(|mut file| file.write_all(&data))
+            .map_err(|e| format!("{}: {}", path, e)),
+        None => std::io::stdout()
+            .write_all(&data)
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// Parse a size in bytes, in decimal or hexadecimal, with an optional
+/// `K`, `M` or `G` (binary) suffix: `4096`, `0x1000`, `512K`, `1M`.
+fn parse_size(value: &str) -> Result<u64, String> {
+    let (number, unit) = match value.to_ascii_uppercase().trim_end_matches(['B', 'I']) {
+        v if v.ends_with('K') => (value[..v.len() - 1].to_string(), 1u64 << 10),
+        v if v.ends_with('M') => (value[..v.len() - 1].to_string(), 1 << 20),
+        v if v.ends_with('G') => (value[..v.len() - 1].to_string(), 1 << 30),
+        _ => (value.to_string(), 1),
+    };
+    let size = maybe_hex::<u64>(&number)?
+        .checked_mul(unit)
+        .ok_or_else(|| format!("size '{}' is too large", value))?;
+    if size == 0 || size > u32::MAX as u64 {
+        return Err(format!("size '{}' is out of range", value));
+    }
+    Ok(size)
+}
+
+/// Redraw a one-line progress bar on stderr.
+fn progress_bar(done: u64, total: u64) {
+    const WIDTH: u64 = 40;
+    let filled = (done * WIDTH).checked_div(total).unwrap_or(WIDTH);
+    let percent = (done * 100).checked_div(total).unwrap_or(100);
+    let mut stderr = std::io::stderr().lock();
+    let _ = write!(
+        stderr,
+        "\r[{}{}] {:>3}% {}/{} MiB",
+        "#".repeat(filled as usize),
+        " ".repeat((WIDTH - filled) as usize),
+        percent,
+        done >> 20,
+        total >> 20
+    );
+    let _ = stderr.flush();
+}
+
+/// Arguments locating and opening a body, shared by every command.
+fn body_args() -> [Arg; 2] {
+    [
+        Arg::new("body")
+            .short('b')
+            .long("body")
+            .value_parser(value_parser!(String))
+            .required(true)
+            .help("The path to the body to exhume."),
+        Arg::new("format")
+            .short('f')
+            .long("format")
+            .value_parser(value_parser!(String))
+            .required(false)
+            .help(
+                "The format of the file, either 'raw', 'ewf', 'vmdk', 'aff', 'aff4', 'vhd', 'vhdx', 'vdi', 'dmg', 'parallels', 'qcow', 'device', 'gzip', 'zstd', 'xz' or 'auto'.",
+            ),
+    ]
+}
+
+fn log_level_arg() -> Arg {
+    Arg::new("log_level")
+        .short('l')
+        .long("log-level")
+        .value_parser(["error", "warn", "info", "debug", "trace"])
+        .default_value("info")
+        .help("Set the log verbosity level")
+}
+
 fn main() {
     let matches = Command::new("exhume_body")
         .version(crate_version!())
         .author(crate_authors!())
         .about("Exhume a body of data from many file formats.")
-        .arg(
-            Arg::new("body")
-                .short('b')
-                .long("body")
-                .value_parser(value_parser!(String))
-                .required(true)
-                .help("The path to the body to exhume."),
-        )
-        .arg(
-            Arg::new("format")
-                .short('f')
-                .long("format")
-                .value_parser(value_parser!(String))
-                .required(false)
-                .help(
-                    "The format of the file, either 'raw', 'ewf', 'vmdk', 'aff', 'aff4' or 'auto'.",
-                ),
-        )
+        .args_conflicts_with_subcommands(true)
+        .subcommand_negates_reqs(true)
+        .args(body_args())
         .arg(
             Arg::new("size")
                 .short('s')
                 .long("size")
                 .value_parser(maybe_hex::<u64>)
-                .required(true)
+                .required_unless_present("raw")
                 .help("The size (in bytes) to read."),
         )
         .arg(
@@ -110,16 +583,245 @@ fn main() {
                 .required(false)
                 .help("Read at a specific offset."),
         )
+        .arg(Arg::new("raw").long("raw").action(ArgAction::SetTrue).help(
+            "Write the bytes as they are instead of as text, up to the end when no size is given.",
+        ))
         .arg(
-            Arg::new("log_level")
-                .short('l')
-                .long("log-level")
-                .value_parser(["error", "warn", "info", "debug", "trace"])
-                .default_value("info")
-                .help("Set the log verbosity level"),
+            Arg::new("out")
+                .long("out")
+                .value_parser(value_parser!(String))
+                .requires("raw")
+                .help("Output file of --raw, which must not exist (default: stdout, also '-')."),
+        )
+        .arg(log_level_arg())
+        .subcommand(
+            Command::new("extract")
+                .about("Stream a byte range of the body to a file or stdout.")
+                .args(body_args())
+                .arg(
+                    Arg::new("offset")
+                        .short('o')
+                        .long("offset")
+                        .value_parser(maybe_hex::<u64>)
+                        .default_value("0")
+                        .help("First byte of the range."),
+                )
+                .arg(
+                    Arg::new("size")
+                        .short('s')
+                        .long("size")
+                        .value_parser(maybe_hex::<u64>)
+                        .help("Length of the range in bytes (default: up to the end)."),
+                )
+                .arg(
+                    Arg::new("out")
+                        .long("out")
+                        .value_parser(value_parser!(String))
+                        .help("Output file, which must not exist (default: stdout)."),
+                )
+                .arg(log_level_arg()),
+        )
+        .subcommand(
+            Command::new("entropy")
+                .about("Report the Shannon entropy of every block of the body, as CSV or JSON.")
+                .args(body_args())
+                .arg(
+                    Arg::new("block")
+                        .long("block")
+                        .value_parser(parse_size)
+                        .default_value("1M")
+                        .help("Block size, e.g. 4096, 64K or 1M."),
+                )
+                .arg(
+                    Arg::new("output_format")
+                        .long("output-format")
+                        .value_parser(["csv", "json"])
+                        .default_value("csv")
+                        .help("Report format."),
+                )
+                .arg(
+                    Arg::new("out")
+                        .long("out")
+                        .value_parser(value_parser!(String))
+                        .help("Output file, which must not exist (default: stdout)."),
+                )
+                .arg(log_level_arg()),
+        )
+        .subcommand(
+            Command::new("manifest")
+                .about(
+                    "Hash every block of the body into a manifest, or check the body against one.",
+                )
+                .args(body_args())
+                .arg(
+                    Arg::new("block")
+                        .long("block")
+                        .value_parser(parse_size)
+                        .default_value("1M")
+                        .help("Block size, e.g. 4096, 64K or 1M."),
+                )
+                .arg(
+                    Arg::new("algorithm")
+                        .long("algorithm")
+                        .value_parser(["md5", "sha1", "sha256"])
+                        .default_value("sha256")
+                        .help("Hash algorithm."),
+                )
+                .arg(
+                    Arg::new("output_format")
+                        .long("output-format")
+                        .value_parser(["json", "csv"])
+                        .default_value("json")
+                        .help("Manifest format (only JSON manifests can be verified)."),
+                )
+                .arg(
+                    Arg::new("out")
+                        .long("out")
+                        .value_parser(value_parser!(String))
+                        .help("Output file, which must not exist (default: stdout)."),
+                )
+                .arg(
+                    Arg::new("verify")
+                        .long("verify")
+                        .value_parser(value_parser!(String))
+                        .conflicts_with_all(["block", "algorithm", "output_format", "out"])
+                        .help("Check the body against this JSON manifest instead."),
+                )
+                .arg(log_level_arg()),
+        )
+        .subcommand(
+            Command::new("acquire")
+                .about("Image a drive (or any body) to a new E01 or raw image, hashing and verifying it.")
+                .arg(
+                    Arg::new("source")
+                        .long("source")
+                        .value_parser(value_parser!(String))
+                        .required(true)
+                        .help("The device or image to acquire, e.g. /dev/sdb or PhysicalDrive1."),
+                )
+                .arg(
+                    Arg::new("source_format")
+                        .long("source-format")
+                        .value_parser(value_parser!(String))
+                        .default_value("auto")
+                        .help("The format of the source, 'device', 'raw', 'ewf', … or 'auto'."),
+                )
+                .arg(
+                    Arg::new("format")
+                        .short('f')
+                        .long("format")
+                        .value_parser(["e01", "raw"])
+                        .default_value("e01")
+                        .help("Format of the image written."),
+                )
+                .arg(
+                    Arg::new("out")
+                        .long("out")
+                        .value_parser(value_parser!(String))
+                        .required(true)
+                        .help("Image to write, which must not exist ('disk' gives disk.E01, disk.E02, … for E01)."),
+                )
+                .arg(
+                    Arg::new("segment_size")
+                        .long("segment-size")
+                        .value_parser(parse_size)
+                        .help("Maximum size of an E01 segment, e.g. 650M or 2G."),
+                )
+                .arg(
+                    Arg::new("compression")
+                        .long("compression")
+                        .value_parser(["none", "fast", "best"])
+                        .default_value("fast")
+                        .help("Compression of E01 chunks."),
+                )
+                .arg(
+                    Arg::new("hash")
+                        .long("hash")
+                        .value_parser(["md5", "sha1", "sha256"])
+                        .value_delimiter(',')
+                        .default_value("md5,sha1")
+                        .help("Digests computed over the source and the image read back."),
+                )
+                .arg(
+                    Arg::new("no_verify")
+                        .long("no-verify")
+                        .action(ArgAction::SetTrue)
+                        .help("Do not read the image back to check its digests."),
+                )
+                .arg(
+                    Arg::new("on_error")
+                        .long("on-error")
+                        .value_parser(["fill", "fail"])
+                        .default_value("fill")
+                        .help("Zero-fill unreadable sectors (recorded in the E01 error2 section) or stop."),
+                )
+                .arg(
+                    Arg::new("error_granularity")
+                        .long("error-granularity")
+                        .value_parser(value_parser!(u32).range(1..))
+                        .default_value("1")
+                        .help("Sectors read again, and filled, together once a read fails."),
+                )
+                .arg(
+                    Arg::new("retries")
+                        .long("retries")
+                        .value_parser(value_parser!(u32))
+                        .default_value("2")
+                        .help("Times a failed read is retried before it counts as an error."),
+                )
+                .args([
+                    ("case", "Case number."),
+                    ("evidence", "Evidence number."),
+                    ("description", "Description of the evidence."),
+                    ("examiner", "Name of the examiner."),
+                    ("notes", "Notes."),
+                ]
+                .map(|(id, help)| {
+                    Arg::new(id)
+                        .long(id)
+                        .value_parser(value_parser!(String))
+                        .help(help)
+                }))
+                .arg(log_level_arg()),
+        )
+        .subcommand(
+            Command::new("descriptor")
+                .about("Dump the descriptor embedded in a monolithic sparse VMDK, to recreate a lost descriptor file.")
+                .arg(
+                    Arg::new("body")
+                        .short('b')
+                        .long("body")
+                        .value_parser(value_parser!(String))
+                        .required(true)
+                        .help("The monolithic sparse or streamOptimized VMDK file."),
+                )
+                .arg(
+                    Arg::new("rewrite")
+                        .long("rewrite")
+                        .action(ArgAction::SetTrue)
+                        .help("Regenerate the descriptor from its parsed fields instead of copying it as stored."),
+                )
+                .arg(
+                    Arg::new("out")
+                        .long("out")
+                        .value_parser(value_parser!(String))
+                        .help("Output file, which must not exist (default: stdout)."),
+                )
+                .arg(log_level_arg()),
+        )
+        .subcommand(
+            Command::new("layout")
+                .about("List the files making up the evidence, with their sizes and roles.")
+                .args(body_args())
+                .arg(log_level_arg()),
         )
         .get_matches();
 
+    let (command, matches) = match matches.subcommand() {
+        Some((name, sub)) => (name, sub),
+        None => ("", &matches),
+    };
+
     let log_level_str = matches.get_one::<String>("log_level").unwrap();
     let level_filter = match log_level_str.as_str() {
         "error" => LevelFilter::Error,
@@ -132,11 +834,110 @@ fn main() {
 
     env_logger::Builder::new().filter_level(level_filter).init();
 
+    if command == "acquire" {
+        if let Err(err) = acquire(matches) {
+            error!("Error: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if command == "descriptor" {
+        let path = matches.get_one::<String>("body").unwrap();
+        let rewrite = matches.get_flag("rewrite");
+        if let Err(err) = descriptor(path, rewrite, matches.get_one::<String>("out")) {
+            error!("Error: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let file_path = matches.get_one::<String>("body").unwrap();
     let auto = String::from("auto");
     let format = matches.get_one::<String>("format").unwrap_or(&auto);
-    let size = matches.get_one::<u64>("size").unwrap();
-    let offset = matches.get_one::<u64>("offset").unwrap_or(&0);
+    // `layout`, `entropy` and `manifest` have no offset.
+    let offset = matches
+        .try_get_one::<u64>("offset")
+        .ok()
+        .flatten()
+        .unwrap_or(&0);
 
-    process_file(file_path, format, size, offset);
+    match command {
+        "extract" => {
+            let body = open_body(file_path, format, &0);
+            let size = matches.get_one::<u64>("size").copied();
+            if let Err(err) = extract(body, *offset, size, matches.get_one::<String>("out")) {
+                error!("Error: {}", err);
+                std::process::exit(1);
+            }
+        }
+        "entropy" => {
+            let mut body = open_body(file_path, format, &0);
+            let block = *matches.get_one::<u64>("block").unwrap();
+            let json = matches.get_one::<String>("output_format").unwrap() == "json";
+            let result = match matches.get_one::<String>("out") {
+                Some(path) => std::fs::File::create_new(path)
+                    .map(std::io::BufWriter::new)
+                    .and_then(|mut file| entropy(&mut body, block, json, &mut file))
+                    .map_err(|e| format!("{}: {}", path, e)),
+                // A reader that stops early (`| head`) ends the report quietly.
+                None => match entropy(&mut body, block, json, &mut std::io::stdout().lock()) {
+                    Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+                    result => result.map_err(|e| e.to_string()),
+                },
+            };
+            if let Err(err) = result {
+                error!("Error: {}", err);
+                std::process::exit(1);
+            }
+        }
+        "manifest" => {
+            let mut body = open_body(file_path, format, &0);
+            let result = match matches.get_one::<String>("verify") {
+                Some(path) => verify_manifest(&mut body, path),
+                None => {
+                    let block = *matches.get_one::<u64>("block").unwrap();
+                    let algorithm = matches
+                        .get_one::<String>("algorithm")
+                        .unwrap()
+                        .parse()
+                        .unwrap();
+                    let json = matches.get_one::<String>("output_format").unwrap() == "json";
+                    match matches.get_one::<String>("out") {
+                        Some(path) => std::fs::File::create_new(path)
+                            .map(std::io::BufWriter::new)
+                            .and_then(|mut file| {
+                                manifest(&mut body, block, algorithm, json, &mut file)
+                            })
+                            .map_err(|e| format!("{}: {}", path, e)),
+                        None => manifest(&mut body, block, algorithm, json, &mut std::io::stdout())
+                            .map_err(|e| e.to_string()),
+                    }
+                }
+            };
+            if let Err(err) = result {
+                error!("Error: {}", err);
+                std::process::exit(1);
+            }
+        }
+        "layout" => {
+            let mut body = open_body(file_path, format, &0);
+            if let Err(err) = layout(&mut body) {
+                error!("Error: {}", err);
+                std::process::exit(1);
+            }
+        }
+        _ if matches.get_flag("raw") => {
+            let body = open_body(file_path, format, &0);
+            let size = matches.get_one::<u64>("size").copied();
+            if let Err(err) = extract(body, *offset, size, matches.get_one::<String>("out")) {
+                error!("Error: {}", err);
+                std::process::exit(1);
+            }
+        }
+        _ => {
+            let size = matches.get_one::<u64>("size").unwrap();
+            process_file(file_path, format, size, offset);
+        }
+    }
 }
@@ -0,0 +1,201 @@
+//! Per-block digests of a body, see [`Body::block_manifest`].
+//!
+//! A manifest records what was analyzed down to the block: kept with the
+//! case notes (or attached to a report as JSON), it shows later that the
+//! evidence, or a copy of it, still holds exactly the same bytes, and which
+//! blocks changed when it does not.
+//!
+//! ```no_run
+//! # use exhume_body::{Body, HashAlgorithm};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut body = Body::try_new("/evidence/disk.E01".to_string(), "auto")?;
+//! let manifest = body.block_manifest(1024 * 1024, HashAlgorithm::Sha256, |_, _| {})?;
+//! std::fs::write("disk.manifest.json", serde_json::to_vec_pretty(&manifest)?)?;
+//!
+//! // Months later, on the copy handed to the other party.
+//! let mut copy = Body::try_new("/exhibits/disk.raw".to_string(), "raw")?;
+//! let changed = manifest.verify(&mut copy, |_, _| {})?;
+//! assert!(changed.is_empty());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::hash::{HashAlgorithm, MultiHasher};
+use crate::Body;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+
+/// Digest of every block of a body, and of the whole body.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockManifest {
+    /// Path of the body the manifest was computed from.
+    pub source: String,
+    /// Short format name of the body (`ewf`, `vmdk`, …).
+    pub format: String,
+    /// Size of the body in bytes.
+    pub size: u64,
+    /// Size of a block in bytes; the last block may be shorter.
+    pub block_size: u64,
+    pub algorithm: HashAlgorithm,
+    /// Lower-case hex digest of the whole body.
+    pub digest: String,
+    /// Lower-case hex digest of every block, in order.
+    pub blocks: Vec<String>,
+}
+
+impl BlockManifest {
+    /// Hash `body` block by block.
+    pub(crate) fn compute(
+        body: &mut Body,
+        block_size: usize,
+        algorithm: HashAlgorithm,
+        mut progress: impl FnMut(u64, u64),
+    ) -> io::Result<Self> {
+        if block_size == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "block size must not be zero",
+            ));
+        }
+        let size = body.size()?;
+        let mut whole = MultiHasher::new(&[algorithm]);
+        let mut blocks = Vec::with_capacity(size.div_ceil(block_size as u64) as usize);
+        each_block(body, size, block_size, |offset, data| {
+            whole.update(data);
+            blocks.push(digest(algorithm, data));
+            progress(offset + data.len() as u64, size);
+        })?;
+        Ok(Self {
+            source: body.path.clone(),
+            format: body.format_name().to_string(),
+            size,
+            block_size: block_size as u64,
+            algorithm,
+            digest: whole.finalize().remove(0).1,
+            blocks,
+        })
+    }
+
+    /// Byte range of block `index`.
+    pub fn block_range(&self, index: usize) -> Range<u64> {
+        let start = index as u64 * self.block_size;
+        start.min(self.size)..(start + self.block_size).min(self.size)
+    }
+
+    /// Hash `body` again with the block size and algorithm of the manifest,
+    /// and return the byte ranges of the blocks that differ, sorted and
+    /// coalesced: empty when the body holds exactly the bytes the manifest
+    /// was computed from. `progress` receives the bytes hashed and the total.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidData`] when the body is not the
+    /// size the manifest records, or the manifest is not consistent.
+    pub fn verify(
+        &self,
+        body: &mut Body,
+        mut progress: impl FnMut(u64, u64),
+    ) -> io::Result<Vec<Range<u64>>> {
+        let size = body.size()?;
+        if size != self.size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "body is {} bytes, the manifest covers {} bytes",
+                    size, self.size
+                ),
+            ));
+        }
+        let block_size = usize::try_from(self.block_size)
+            .ok()
+            .filter(|&b| b != 0)
+            .filter(|&b| size.div_ceil(b as u64) == self.blocks.len() as u64)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "manifest block size does not match its block count",
+                )
+            })?;
+        let mut changed: Vec<Range<u64>> = Vec::new();
+        let mut index = 0;
+        each_block(body, size, block_size, |offset, data| {
+            let end = offset + data.len() as u64;
+            if !digest(self.algorithm, data).eq_ignore_ascii_case(&self.blocks[index]) {
+                match changed.last_mut() {
+                    Some(last) if last.end == offset => last.end = end,
+                    _ => changed.push(offset..end),
+                }
+            }
+            index += 1;
+            progress(end, size);
+        })?;
+        Ok(changed)
+    }
+}
+
+/// Call `f` with the offset and content of every `block_size` bytes of
+/// `body`, up to `size`. The body is read sequentially, which streaming
+/// formats (gzip, xz) decode much faster than random reads, and put back
+/// where it was.
+fn each_block(
+    body: &mut Body,
+    size: u64,
+    block_size: usize,
+    mut f: impl FnMut(u64, &[u8]),
+) -> io::Result<()> {
+    let position = body.stream_position()?;
+    body.seek(SeekFrom::Start(0))?;
+    let mut buf = vec![0u8; block_size];
+    let mut offset = 0;
+    while offset < size {
+        let len = (size - offset).min(block_size as u64) as usize;
+        body.read_exact(&mut buf[..len])?;
+        f(offset, &buf[..len]);
+        offset += len as u64;
+    }
+    body.seek(SeekFrom::Start(position))?;
+    Ok(())
+}
+
+/// Lower-case hex `algorithm` digest of `data`.
+fn digest(algorithm: HashAlgorithm, data: &[u8]) -> String {
+    let mut hasher = MultiHasher::new(&[algorithm]);
+    hasher.update(data);
+    hasher.finalize().remove(0).1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn manifest_finds_changed_blocks() {
+        let dir = TempDir::new("manifest");
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(dir.join("a.raw"), &data).unwrap();
+        let mut body = Body::try_new(dir.join("a.raw").to_string_lossy().into(), "raw").unwrap();
+
+        let manifest = body
+            .block_manifest(4096, HashAlgorithm::Sha256, |_, _| {})
+            .unwrap();
+        assert_eq!(manifest.blocks.len(), 3);
+        assert_eq!(manifest.block_range(2), 8192..10_000);
+        assert_eq!(
+            manifest.digest,
+            body.hash(&[HashAlgorithm::Sha256], |_, _| {}).unwrap()[0].1
+        );
+        let json = serde_json::to_string(&manifest).unwrap();
+        let manifest: BlockManifest = serde_json::from_str(&json).unwrap();
+        assert!(manifest.verify(&mut body, |_, _| {}).unwrap().is_empty());
+
+        body.patch(5000, b"changed").unwrap();
+        body.patch(9000, b"changed").unwrap();
+        assert_eq!(
+            manifest.verify(&mut body, |_, _| {}).unwrap(),
+            [Range {
+                start: 4096,
+                end: 10_000
+            }]
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! Format-independent description of a body, see [`Body::metadata`](crate::Body::metadata).
+
+use crate::hash::HashAlgorithm;
+use crate::read_at::ReadAt;
+use serde::ser::{SerializeSeq, Serializer};
+use serde::Serialize;
+use std::ops::Range;
+
+/// Common facts about an opened body, filled in by each backend where
+/// the format records them.
+///
+/// Serializes (e.g. with `serde_json`) with the acquisition fields as
+/// `{"label", "value"}` objects and the stored hashes as
+/// `{"algorithm", "digest"}` objects.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct DiskMetadata {
+    /// Short format name, as accepted by [`Body::try_new`](crate::Body::try_new).
+    pub format: String,
+    /// Human-readable format description.
+    pub description: String,
+    /// Total length of the evidence in bytes.
+    pub size: u64,
+    /// Logical sector size in bytes.
+    pub sector_size: u16,
+    /// Number of files backing the body (segments, extents or volumes).
+    pub segment_count: usize,
+    /// Acquisition information (case number, examiner, dates, …) as
+    /// label/value pairs, in the order the format stores them.
+    #[serde(serialize_with = "serialize_labelled")]
+    pub acquisition: Vec<(String, String)>,
+    /// Disk geometry, when recorded by the format.
+    pub geometry: Option<DiskGeometry>,
+    /// Digests of the media recorded at acquisition time, see
+    /// [`Body::stored_hashes`](crate::Body::stored_hashes).
+    #[serde(serialize_with = "serialize_digests")]
+    pub stored_hashes: Vec<(HashAlgorithm, String)>,
+}
+
+/// Serializes label/value pairs as a sequence of `{"label", "value"}`
+/// objects.
+pub(crate) fn serialize_labelled<S: Serializer>(
+    fields: &[(String, String)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct Field<'a> {
+        label: &'a str,
+        value: &'a str,
+    }
+    let mut seq = serializer.serialize_seq(Some(fields.len()))?;
+    for (label, value) in fields {
+        seq.serialize_element(&Field { label, value })?;
+    }
+    seq.end()
+}
+
+/// Serializes digests as a sequence of `{"algorithm", "digest"}` objects.
+pub(crate) fn serialize_digests<S: Serializer>(
+    digests: &[(HashAlgorithm, String)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct Digest<'a> {
+        algorithm: HashAlgorithm,
+        digest: &'a str,
+    }
+    let mut seq = serializer.serialize_seq(Some(digests.len()))?;
+    for (algorithm, digest) in digests {
+        seq.serialize_element(&Digest {
+            algorithm: *algorithm,
+            digest,
+        })?;
+    }
+    seq.end()
+}
+
+/// Cylinder/head/sector geometry of a disk.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct DiskGeometry {
+    pub cylinders: u64,
+    pub heads: u32,
+    pub sectors_per_track: u32,
+}
+
+impl DiskGeometry {
+    /// Cylinder, head and (1-based) sector addressing `lba`, `None` when it
+    /// lies past the last cylinder or the geometry is empty.
+    pub fn lba_to_chs(&self, lba: u64) -> Option<(u64, u32, u32)> {
+        let heads = self.heads as u64;
+        let sectors = self.sectors_per_track as u64;
+        let cylinder = lba.checked_div(heads * sectors)?;
+        if cylinder >= self.cylinders {
+            return None;
+        }
+        let head = lba / sectors % heads;
+        let sector = lba % sectors + 1;
+        Some((cylinder, head as u32, sector as u32))
+    }
+
+    /// LBA of a cylinder/head/sector address (sectors counted from 1), as
+    /// found in MBR partition entries. `None` when it is outside the
+    /// geometry.
+    pub fn chs_to_lba(&self, cylinder: u64, head: u32, sector: u32) -> Option<u64> {
+        if cylinder >= self.cylinders
+            || head >= self.heads
+            || sector == 0
+            || sector > self.sectors_per_track
+        {
+            return None;
+        }
+        let heads = self.heads as u64;
+        let sectors = self.sectors_per_track as u64;
+        Some((cylinder * heads + head as u64) * sectors + sector as u64 - 1)
+    }
+}
+
+/// A run of `count` sectors starting at `start`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct SectorRange {
+    pub start: u64,
+    pub count: u64,
+}
+
+impl SectorRange {
+    /// First sector after the range.
+    pub fn end(&self) -> u64 {
+        self.start.saturating_add(self.count)
+    }
+}
+
+/// Sort `ranges`, drop the empty ones and coalesce those that overlap or
+/// touch.
+pub(crate) fn merge_ranges(mut ranges: Vec<Range<u64>>) -> Vec<Range<u64>> {
+    ranges.retain(|r| r.start < r.end);
+    ranges.sort_by_key(|r| r.start);
+    let mut merged: Vec<Range<u64>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Parts of the sorted, disjoint `ranges` that fall within `window`.
+pub(crate) fn clip_ranges<'a>(
+    ranges: &'a [Range<u64>],
+    window: Range<u64>,
+) -> impl Iterator<Item = Range<u64>> + 'a {
+    let first = ranges.partition_point(|r| r.end <= window.start);
+    ranges[first..]
+        .iter()
+        .take_while(move |r| r.start < window.end)
+        .map(move |r| r.start.max(window.start)..r.end.min(window.end))
+}
+
+/// Start of the first of the sorted, disjoint `ranges` at or after
+/// `offset`, or `offset` itself when a range holds it.
+pub(crate) fn next_data(ranges: &[Range<u64>], offset: u64) -> Option<u64> {
+    let next = ranges.get(ranges.partition_point(|r| r.end <= offset))?;
+    Some(next.start.max(offset))
+}
+
+/// First offset at or after `offset` outside the sorted, merged `ranges`.
+pub(crate) fn next_hole(ranges: &[Range<u64>], offset: u64) -> u64 {
+    match ranges.get(ranges.partition_point(|r| r.end <= offset)) {
+        Some(range) if range.start <= offset => range.end,
+        _ => offset,
+    }
+}
+
+/// Logical sector size of a disk whose GPT header sits at LBA 1: 4096 when
+/// the `EFI PART` signature is found at byte 4096 (4Kn disks) rather than
+/// 512. None when the disk has no GPT, or it cannot be read.
+pub(crate) fn probe_sector_size(disk: &impl ReadAt) -> Option<u16> {
+    [512u16, 4096].into_iter().find(|&size| {
+        let mut signature = [0u8; 8];
+        disk.read_exact_at(size as u64, &mut signature).is_ok() && &signature == b"EFI PART"
+    })
+}
+
+/// A physical file (or container member) holding part of the evidence, see
+/// [`Body::layout`](crate::Body::layout).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct EvidenceFile {
+    /// Path of the file, `volume!member` for a member of a container (AFF4).
+    /// Empty for images opened from a stream.
+    pub path: String,
+    /// Size in bytes (stored size for container members).
+    pub size: u64,
+    /// What the file holds, e.g. `EWF segment 2/3` or `VMDK extent (SPARSE)`.
+    pub role: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use std::io;
+
+    #[test]
+    fn merges_clips_and_searches_ranges() {
+        let merged = merge_ranges(vec![8..12, 0..4, 4..6, 10..16, 20..20]);
+        assert_eq!(merged, [0..6, 8..16]);
+        assert_eq!(clip_ranges(&merged, 5..9).collect::<Vec<_>>(), [5..6, 8..9]);
+        assert_eq!(clip_ranges(&merged, 6..8).count(), 0);
+
+        assert_eq!(next_data(&merged, 2), Some(2));
+        assert_eq!(next_data(&merged, 6), Some(8));
+        assert_eq!(next_data(&merged, 16), None);
+        assert_eq!(next_hole(&merged, 2), 6);
+        assert_eq!(next_hole(&merged, 7), 7);
+        assert_eq!(next_hole(&merged, 16), 16);
+    }
+
+    #[test]
+    fn sector_size_follows_the_gpt_and_the_builder() {
+        let mut disk = vec![0u8; 16 * 1024];
+        assert_eq!(
+            probe_sector_size(&crate::raw::RAW::from_reader(io::Cursor::new(disk.clone()))),
+            None
+        );
+        disk[4096..4104].copy_from_slice(b"EFI PART");
+        let raw = crate::raw::RAW::from_reader(io::Cursor::new(disk.clone()));
+        assert_eq!(raw.get_sector_size(), 4096);
+
+        let dir = TempDir::new("4kn");
+        let path = dir.join("4kn.raw");
+        std::fs::write(&path, &disk).unwrap();
+        let body = crate::Body::builder(path.to_string_lossy())
+            .format("raw")
+            .build()
+            .unwrap();
+        assert_eq!(body.get_sector_size(), 4096);
+        let body = crate::Body::builder(path.to_string_lossy())
+            .format("raw")
+            .sector_size(512)
+            .build()
+            .unwrap();
+        assert_eq!(body.get_sector_size(), 512);
+        assert!(crate::Body::builder(path.to_string_lossy())
+            .sector_size(1000)
+            .build()
+            .is_err());
+    }
+}
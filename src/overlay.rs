@@ -0,0 +1,485 @@
+//! Copy-on-write overlay over a body, see [`Overlay`], and the in-memory
+//! patches of [`Body::patch`].
+//!
+//! Writes never reach the evidence: they are appended to a sidecar file,
+//! and reads return the evidence with the written ranges replaced. A broken
+//! boot sector can be "repaired", or a hypothesis tested, and the sidecar
+//! thrown away (or kept to document the changes) afterwards.
+//!
+//! ```no_run
+//! # use exhume_body::{Body, Overlay};
+//! # use std::io::{Read, Seek, SeekFrom, Write};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let body = Body::try_new("/evidence/disk.E01".to_string(), "auto")?;
+//! let mut disk = Overlay::create(body, "/work/disk.cow")?;
+//! // Restore the boot signature the partition parser refuses to do without.
+//! disk.write_at(510, &[0x55, 0xaa])?;
+//! let mut mbr = [0u8; 512];
+//! disk.seek(SeekFrom::Start(0))?;
+//! disk.read_exact(&mut mbr)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The sidecar is a journal: a header (`EXHUMCOW`, a version and the size of
+//! the body) followed by one record per write, its offset (u64) and length
+//! (u32, little endian) then the written bytes. [`Overlay::open`] replays it,
+//! later records covering earlier ones.
+
+use crate::read_at::ReadAt;
+use crate::Body;
+use log::{info, warn};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+const COW_MAGIC: &[u8; 8] = b"EXHUMCOW";
+const COW_VERSION: u32 = 1;
+const COW_HEADER_SIZE: u64 = 24;
+const COW_RECORD_HEADER_SIZE: u64 = 12;
+/// Largest record written at once.
+const COW_MAX_RECORD: usize = 1 << 30;
+
+/// Where the bytes of an extent of an [`ExtentMap`] come from, so that
+/// splitting the extent can tell where its tail starts.
+pub(crate) trait ExtentSource: Clone {
+    /// The source of the byte `by` bytes into this one.
+    fn advance(&self, by: u64) -> Self;
+}
+
+/// Offset in a sidecar file.
+impl ExtentSource for u64 {
+    fn advance(&self, by: u64) -> Self {
+        self + by
+    }
+}
+
+/// Bytes of an in-memory patch, see [`Body::patch`].
+#[derive(Clone, Debug)]
+pub(crate) struct PatchBytes {
+    bytes: Arc<[u8]>,
+    start: usize,
+}
+
+impl ExtentSource for PatchBytes {
+    fn advance(&self, by: u64) -> Self {
+        Self {
+            bytes: self.bytes.clone(),
+            start: self.start + by as usize,
+        }
+    }
+}
+
+/// Disjoint ranges of a body replaced by other bytes, the latest insert
+/// covering the earlier ones.
+#[derive(Clone, Debug)]
+pub(crate) struct ExtentMap<T> {
+    /// Start of each extent, to its end and the source of its first byte.
+    extents: BTreeMap<u64, (u64, T)>,
+}
+
+impl<T> Default for ExtentMap<T> {
+    fn default() -> Self {
+        Self {
+            extents: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T: ExtentSource> ExtentMap<T> {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.extents.is_empty()
+    }
+
+    /// Replace `range` with the bytes at `source`, trimming or splitting the
+    /// extents it overlaps.
+    pub(crate) fn insert(&mut self, range: Range<u64>, source: T) {
+        if range.is_empty() {
+            return;
+        }
+        let overlapping: Vec<u64> = self.overlapping_starts(range.clone()).collect();
+        for start in overlapping {
+            let (end, old) = self.extents.remove(&start).expect("listed extent");
+            if start < range.start {
+                self.extents.insert(start, (range.start, old.clone()));
+            }
+            if end > range.end {
+                self.extents
+                    .insert(range.end, (end, old.advance(range.end - start)));
+            }
+        }
+        self.extents.insert(range.start, (range.end, source));
+    }
+
+    fn overlapping_starts(&self, range: Range<u64>) -> impl Iterator<Item = u64> + '_ {
+        // The extent starting before `range` may reach into it.
+        let before = self
+            .extents
+            .range(..range.start)
+            .next_back()
+            .filter(|(_, (end, _))| *end > range.start)
+            .map(|(start, _)| *start);
+        before.into_iter().chain(
+            self.extents
+                .range(range.start..range.end)
+                .map(|(start, _)| *start),
+        )
+    }
+
+    /// The parts of the extents within `range`, in order, each with the
+    /// source of its first byte.
+    pub(crate) fn overlapping(
+        &self,
+        range: Range<u64>,
+    ) -> impl Iterator<Item = (Range<u64>, T)> + '_ {
+        self.overlapping_starts(range.clone()).map(move |start| {
+            let (end, source) = &self.extents[&start];
+            let clipped = start.max(range.start)..(*end).min(range.end);
+            let source = source.advance(clipped.start - start);
+            (clipped, source)
+        })
+    }
+
+    /// The replaced ranges, sorted and coalesced.
+    pub(crate) fn ranges(&self) -> Vec<Range<u64>> {
+        crate::metadata::merge_ranges(
+            self.extents
+                .iter()
+                .map(|(start, (end, _))| *start..*end)
+                .collect(),
+        )
+    }
+}
+
+impl ExtentMap<PatchBytes> {
+    /// Replace the bytes at `offset` with `bytes`.
+    pub(crate) fn insert_bytes(&mut self, offset: u64, bytes: &[u8]) {
+        let source = PatchBytes {
+            bytes: bytes.into(),
+            start: 0,
+        };
+        self.insert(offset..offset + bytes.len() as u64, source);
+    }
+
+    /// Overwrite the patched parts of `buf`, read at `offset`.
+    pub(crate) fn apply(&self, offset: u64, buf: &mut [u8]) {
+        for (range, patch) in self.overlapping(offset..offset + buf.len() as u64) {
+            let start = (range.start - offset) as usize;
+            let len = (range.end - range.start) as usize;
+            buf[start..start + len].copy_from_slice(&patch.bytes[patch.start..patch.start + len]);
+        }
+    }
+}
+
+/// A [`Body`] whose writes go to a sidecar file, see the [module
+/// documentation](self).
+///
+/// Implements [`Read`], [`Seek`], [`Write`] and [`ReadAt`]. The size is that
+/// of the body: writes past its end fail.
+pub struct Overlay {
+    base: Body,
+    size: u64,
+    sidecar: File,
+    /// End of the last complete record of the sidecar.
+    sidecar_len: u64,
+    extents: ExtentMap<u64>,
+    position: u64,
+}
+
+impl Overlay {
+    /// Start an overlay over `base` in a new sidecar file at `sidecar`,
+    /// which must not exist.
+    pub fn create(mut base: Body, sidecar: impl AsRef<Path>) -> io::Result<Self> {
+        let size = base.size()?;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(sidecar.as_ref())?;
+        let mut header = Vec::with_capacity(COW_HEADER_SIZE as usize);
+        header.extend_from_slice(COW_MAGIC);
+        header.extend_from_slice(&COW_VERSION.to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(&size.to_le_bytes());
+        file.write_all(&header)?;
+        info!("Recording writes to {}", sidecar.as_ref().display());
+        Ok(Self {
+            base,
+            size,
+            sidecar: file,
+            sidecar_len: COW_HEADER_SIZE,
+            extents: ExtentMap::default(),
+            position: 0,
+        })
+    }
+
+    /// Resume the overlay recorded in `sidecar` over `base`, which must have
+    /// the size recorded in it. A record cut short (a crash while writing)
+    /// is dropped.
+    pub fn open(mut base: Body, sidecar: impl AsRef<Path>) -> io::Result<Self> {
+        let size = base.size()?;
+        let sidecar = sidecar.as_ref();
+        let file = OpenOptions::new().read(true).write(true).open(sidecar)?;
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+        let mut header = [0u8; COW_HEADER_SIZE as usize];
+        file.read_exact_at(0, &mut header)
+            .map_err(|_| invalid(format!("{} is not an overlay file", sidecar.display())))?;
+        if &header[..8] != COW_MAGIC {
+            return Err(invalid(format!(
+                "{} is not an overlay file",
+                sidecar.display()
+            )));
+        }
+        let version = u32::from_le_bytes(header[8..12].try_into().unwrap());
+        if version != COW_VERSION {
+            return Err(invalid(format!("unsupported overlay version {}", version)));
+        }
+        let recorded = u64::from_le_bytes(header[16..24].try_into().unwrap());
+        if recorded != size {
+            return Err(invalid(format!(
+                "overlay recorded over a {} byte body, not {} bytes",
+                recorded, size
+            )));
+        }
+
+        let file_len = file.metadata()?.len();
+        let mut extents = ExtentMap::default();
+        let mut position = COW_HEADER_SIZE;
+        let mut record = [0u8; COW_RECORD_HEADER_SIZE as usize];
+        while position + COW_RECORD_HEADER_SIZE <= file_len {
+            file.read_exact_at(position, &mut record)?;
+            let offset = u64::from_le_bytes(record[..8].try_into().unwrap());
+            let len = u32::from_le_bytes(record[8..].try_into().unwrap()) as u64;
+            let data = position + COW_RECORD_HEADER_SIZE;
+            if data + len > file_len {
+                break;
+            }
+            match offset.checked_add(len) {
+                Some(end) if end <= size => extents.insert(offset..end, data),
+                _ => {
+                    return Err(invalid(format!(
+                        "overlay record at {} is out of range",
+                        position
+                    )))
+                }
+            }
+            position = data + len;
+        }
+        if position < file_len {
+            warn!(
+                "Dropping the incomplete last record of {} ({} bytes)",
+                sidecar.display(),
+                file_len - position
+            );
+            file.set_len(position)?;
+        }
+
+        Ok(Self {
+            base,
+            size,
+            sidecar: file,
+            sidecar_len: position,
+            extents,
+            position: 0,
+        })
+    }
+
+    /// Record `data` as the content of the body at `offset`.
+    pub fn write_at(&mut self, offset: u64, data: &[u8]) -> io::Result<()> {
+        if offset
+            .checked_add(data.len() as u64)
+            .is_none_or(|end| end > self.size)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "write of {} bytes at 0x{:x} ends past the end of the body ({} bytes)",
+                    data.len(),
+                    offset,
+                    self.size
+                ),
+            ));
+        }
+        let mut offset = offset;
+        for chunk in data.chunks(COW_MAX_RECORD) {
+            let mut record = Vec::with_capacity(COW_RECORD_HEADER_SIZE as usize + chunk.len());
+            record.extend_from_slice(&offset.to_le_bytes());
+            record.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+            record.extend_from_slice(chunk);
+            self.sidecar.seek(SeekFrom::Start(self.sidecar_len))?;
+            self.sidecar.write_all(&record)?;
+
+            let data = self.sidecar_len + COW_RECORD_HEADER_SIZE;
+            self.extents
+                .insert(offset..offset + chunk.len() as u64, data);
+            self.sidecar_len = data + chunk.len() as u64;
+            offset += chunk.len() as u64;
+        }
+        Ok(())
+    }
+
+    /// Byte ranges written through the overlay, sorted and coalesced.
+    pub fn written_ranges(&self) -> Vec<Range<u64>> {
+        self.extents.ranges()
+    }
+
+    /// Size of the body in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The underlying body, as it was before any write.
+    pub fn base(&self) -> &Body {
+        &self.base
+    }
+
+    /// Close the sidecar and return the underlying body.
+    pub fn into_base(self) -> Body {
+        self.base
+    }
+}
+
+impl ReadAt for Overlay {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        if offset >= self.size {
+            return Ok(0);
+        }
+        let want = buf.len().min((self.size - offset) as usize);
+        let buf = &mut buf[..want];
+        let n = if self.extents.is_empty() {
+            self.base.read_at(offset, buf)?
+        } else {
+            // The written ranges may leave nothing to read from the base.
+            self.base.read_exact_at(offset, buf)?;
+            want
+        };
+        for (range, data) in self.extents.overlapping(offset..offset + n as u64) {
+            let start = (range.start - offset) as usize;
+            let end = (range.end - offset) as usize;
+            self.sidecar.read_exact_at(data, &mut buf[start..end])?;
+        }
+        Ok(n)
+    }
+}
+
+impl Read for Overlay {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.read_at(self.position, buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for Overlay {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let next = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.size.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Seek before start or overflow")
+        })?;
+        self.position = next;
+        Ok(self.position)
+    }
+}
+
+impl Write for Overlay {
+    /// Writes at the cursor. Fails, writing nothing, past the end of the
+    /// body.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_at(self.position, buf)?;
+        self.position += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    /// Flush the sidecar to disk.
+    fn flush(&mut self) -> io::Result<()> {
+        self.sidecar.sync_data()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn records_writes_beside_the_evidence() {
+        let dir = TempDir::new("overlay");
+        let data: Vec<u8> = (0..8192u32).map(|i| (i % 199) as u8).collect();
+        let image = dir.join("disk.raw");
+        std::fs::write(&image, &data).unwrap();
+        let open = || Body::try_new(image.to_string_lossy().into(), "raw").unwrap();
+
+        let mut expected = data.clone();
+        let mut disk = Overlay::create(open(), dir.join("disk.cow")).unwrap();
+        disk.write_at(510, &[0x55, 0xaa]).unwrap();
+        disk.seek(SeekFrom::Start(1000)).unwrap();
+        disk.write_all(&[1; 3000]).unwrap();
+        disk.write_at(2000, &[2; 100]).unwrap();
+        expected[510..512].copy_from_slice(&[0x55, 0xaa]);
+        expected[1000..4000].fill(1);
+        expected[2000..2100].fill(2);
+        assert!(disk.write_at(8000, &[0; 200]).is_err());
+        assert_eq!(disk.written_ranges(), vec![510..512, 1000..4000]);
+
+        let mut read = Vec::new();
+        disk.seek(SeekFrom::Start(0)).unwrap();
+        disk.read_to_end(&mut read).unwrap();
+        assert_eq!(read, expected);
+        drop(disk);
+        assert_eq!(std::fs::read(&image).unwrap(), data);
+
+        // A record cut short is dropped on reopening.
+        let sidecar = OpenOptions::new()
+            .append(true)
+            .open(dir.join("disk.cow"))
+            .unwrap();
+        (&sidecar).write_all(&[0; 7]).unwrap();
+        let disk = Overlay::open(open(), dir.join("disk.cow")).unwrap();
+        let mut read = vec![0u8; 4096];
+        disk.read_exact_at(0, &mut read).unwrap();
+        assert_eq!(read, expected[..4096]);
+        assert!(Overlay::create(open(), dir.join("disk.cow")).is_err());
+    }
+
+    #[test]
+    fn patches_stay_with_their_handle() {
+        let dir = TempDir::new("patch");
+        let path = dir.join("patch.raw");
+        let data: Vec<u8> = (0..4096u32).map(|i| (i % 211) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let mut body = Body::try_new(path.to_string_lossy().into(), "raw").unwrap();
+        let untouched = body.clone();
+        body.patch(510, &[0x55, 0xaa]).unwrap();
+        body.patch(1000, &[7; 100]).unwrap();
+        body.patch(1050, &[8; 10]).unwrap();
+        assert!(body.patch(4090, &[0; 10]).is_err());
+        assert_eq!(body.patched_ranges(), vec![510..512, 1000..1100]);
+
+        let mut expected = data.clone();
+        expected[510..512].copy_from_slice(&[0x55, 0xaa]);
+        expected[1000..1100].fill(7);
+        expected[1050..1060].fill(8);
+        let mut read = Vec::new();
+        body.read_to_end(&mut read).unwrap();
+        assert_eq!(read, expected);
+        assert_eq!(body.read_sectors(0, 3).unwrap(), &expected[..1536]);
+        let mut buf = [0u8; 64];
+        body.read_exact_at(1040, &mut buf).unwrap();
+        assert_eq!(buf, expected[1040..1104]);
+
+        let mut buf = [0u8; 2];
+        untouched.read_exact_at(510, &mut buf).unwrap();
+        assert_eq!(buf, data[510..512]);
+        body.clear_patches();
+        assert_eq!(body.read_sectors(0, 3).unwrap(), &data[..1536]);
+    }
+}
@@ -0,0 +1,500 @@
+//! **Parallels Desktop disk image reader**
+//!
+//! This module provides a pure-Rust reader for **expanding** Parallels images
+//! (`.hds` files, usually found inside a `.hdd` bundle directory next to a
+//! `DiskDescriptor.xml`).
+//!
+//! # On-disk layout
+//!
+//! | Offset | Size | Description                                             |
+//! |--------|------|---------------------------------------------------------|
+//! | `0x00` | 16   | Magic `WithoutFreeSpace` or `WithouFreSpacExt`          |
+//! | `0x10` | 4    | Version (2)                                             |
+//! | `0x14` | 12   | Legacy geometry: heads, cylinders, sectors per cluster |
+//! | `0x20` | 4    | Number of BAT entries                                   |
+//! | `0x24` | 8    | Disk size in 512-byte sectors                           |
+//! | `0x2C` | 4    | In-use marker (`0x746F6E59` while the VM runs)          |
+//! | `0x30` | 4    | Data area offset (sectors)                              |
+//! | `0x40` | …    | **BAT**: one little-endian `u32` per cluster            |
+//!
+//! A BAT entry of `0` marks an unallocated cluster (reads back as zeroes).
+//! Otherwise the cluster lives at `entry × 512` bytes for `WithoutFreeSpace`
+//! images, or `entry × cluster_size` bytes for `WithouFreSpacExt` images.
+//!
+//! Snapshot chains and *plain* (pre-allocated, headerless) images are not
+//! handled; the latter can be opened with the RAW backend.
+//!
+//! Refer to QEMU's `docs/interop/parallels.txt` for the reference definition.
+
+use crate::error::ExhumeBodyError;
+use crate::info::FormatInfo;
+use crate::read_at::ReadAt;
+use crate::source::Source;
+use crate::vfs::{FileSystem, StdFileSystem};
+use log::{debug, warn};
+use std::cmp::min;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// ---- Parallels constants ----------------------------------------------------
+
+/// Magic of images whose BAT holds sector offsets.
+const PARALLELS_MAGIC: [u8; 16] = *b"WithoutFreeSpace";
+
+/// Magic of images whose BAT holds cluster offsets (disks > 2 TiB).
+const PARALLELS_MAGIC_EXT: [u8; 16] = *b"WithouFreSpacExt";
+
+/// Only version 2 headers exist in the wild.
+const PARALLELS_VERSION: u32 = 2;
+
+/// Size of the fixed header preceding the BAT.
+const PARALLELS_HEADER_SIZE: usize = 64;
+
+/// In-use marker written while the image is attached to a running VM.
+const PARALLELS_IN_USE: u32 = 0x746F_6E59;
+
+/// Parallels always addresses 512-byte sectors.
+const PARALLELS_SECTOR_SIZE: u64 = 512;
+
+// ---- Helpers ----------------------------------------------------------------
+
+fn le_u32(data: &[u8], off: usize) -> u32 {
+    u32::from_le_bytes(data[off..off + 4].try_into().unwrap())
+}
+
+fn le_u64(data: &[u8], off: usize) -> u64 {
+    u64::from_le_bytes(data[off..off + 8].try_into().unwrap())
+}
+
+/// Resolve a `.hdd` bundle directory to the image file it describes.
+///
+/// The first `<File>` element of `DiskDescriptor.xml` (the base image) is
+/// preferred; otherwise the first `.hds` file of the directory is used.
+fn resolve_bundle(fs: &dyn FileSystem, dir: &Path) -> Result<PathBuf, String> {
+    let mut xml = String::new();
+    if fs
+        .open(&dir.join("DiskDescriptor.xml"))
+        .and_then(|mut file| file.read_to_string(&mut xml))
+        .is_ok()
+    {
+        if let Some(start) = xml.find("<File>") {
+            let rest = &xml[start + "<File>".len()..];
+            if let Some(end) = rest.find("</File>") {
+                return Ok(dir.join(rest[..end].trim()));
+            }
+        }
+    }
+
+    let mut images: Vec<PathBuf> = fs
+        .read_dir(dir)
+        .map_err(|e| format!("Error reading Parallels bundle: {}", e))?
+        .into_iter()
+        .filter(|p| p.extension().is_some_and(|ext| ext == "hds"))
+        .collect();
+    images.sort();
+    images
+        .into_iter()
+        .next()
+        .ok_or_else(|| "No .hds image found in the Parallels bundle".to_string())
+}
+
+// ---- Header -----------------------------------------------------------------
+
+/// Parallels image header.
+#[derive(Clone, Debug)]
+struct ParallelsHeader {
+    /// `true` for `WithouFreSpacExt` images (BAT in cluster units).
+    extended: bool,
+    /// Legacy geometry: heads, cylinders.
+    geometry: (u32, u32),
+    /// Cluster size in sectors (the `tracks` field).
+    cluster_sectors: u32,
+    /// Number of BAT entries.
+    bat_entries: u32,
+    /// Virtual disk size in sectors.
+    sector_count: u64,
+    /// Whether the in-use marker is set (image not cleanly closed).
+    in_use: bool,
+    /// Start of the data area in sectors.
+    data_offset: u32,
+}
+
+impl ParallelsHeader {
+    /// Parse and validate the 64-byte header.
+    fn parse(data: &[u8]) -> Result<Self, String> {
+        if data.len() < PARALLELS_HEADER_SIZE {
+            return Err("Parallels header too short".to_string());
+        }
+        let extended = match &data[0..16] {
+            m if m == PARALLELS_MAGIC => false,
+            m if m == PARALLELS_MAGIC_EXT => true,
+            _ => return Err("Invalid Parallels signature".to_string()),
+        };
+        let version = le_u32(data, 16);
+        if version != PARALLELS_VERSION {
+            return Err(format!("Unsupported Parallels version {}", version));
+        }
+
+        let header = Self {
+            extended,
+            geometry: (le_u32(data, 20), le_u32(data, 24)),
+            cluster_sectors: le_u32(data, 28),
+            bat_entries: le_u32(data, 32),
+            // Only the low 32 bits are meaningful in `WithoutFreeSpace` images.
+            sector_count: if extended {
+                le_u64(data, 36)
+            } else {
+                le_u32(data, 36) as u64
+            },
+            in_use: le_u32(data, 44) == PARALLELS_IN_USE,
+            data_offset: le_u32(data, 48),
+        };
+
+        if header.cluster_sectors == 0 {
+            return Err("Invalid Parallels cluster size 0".to_string());
+        }
+        if (header.bat_entries as u64) * (header.cluster_sectors as u64) < header.sector_count {
+            return Err(format!(
+                "Parallels BAT too small: {} clusters of {} sectors for {} sectors",
+                header.bat_entries, header.cluster_sectors, header.sector_count
+            ));
+        }
+
+        Ok(header)
+    }
+
+    /// Cluster size in bytes.
+    fn cluster_size(&self) -> u64 {
+        self.cluster_sectors as u64 * PARALLELS_SECTOR_SIZE
+    }
+}
+
+// ---- Public Parallels reader ------------------------------------------------
+
+/// Native Parallels image reader.
+///
+/// Implements [`Read`], [`Seek`] and [`Clone`] so it can be used as a drop-in
+/// source of evidence bytes inside the [`Body`](crate::Body) abstraction.
+pub struct PARALLELS {
+    /// Open file handle to the `.hds` file.
+    file: Source,
+    /// Path of the `.hds` file actually opened (kept for display).
+    path: String,
+    /// Parsed header.
+    header: ParallelsHeader,
+    /// Block allocation table (cluster → file offset in BAT units).
+    bat: Arc<[u32]>,
+    /// Virtual cursor position inside the disk.
+    position: u64,
+}
+
+impl PARALLELS {
+    // ---- Construction -------------------------------------------------------
+
+    /// Open and parse a Parallels image.
+    ///
+    /// `file_path` may point either to the `.hds` file itself or to the
+    /// enclosing `.hdd` bundle directory.
+    pub fn new(file_path: &str) -> Result<PARALLELS, ExhumeBodyError> {
+        Self::open_in(&StdFileSystem, file_path)
+    }
+
+    /// Same as [`PARALLELS::new`], opening the image through `fs` instead of the
+    /// operating system.
+    pub fn open_in(fs: &dyn FileSystem, file_path: &str) -> Result<PARALLELS, ExhumeBodyError> {
+        Self::new_impl(fs, file_path).map_err(ExhumeBodyError::Parallels)
+    }
+
+    fn new_impl(fs: &dyn FileSystem, file_path: &str) -> Result<PARALLELS, String> {
+        let mut path = PathBuf::from(file_path);
+        // A `.hdd` bundle is a directory holding the image files.
+        if !fs.is_file(&path) && fs.read_dir(&path).is_ok_and(|entries| !entries.is_empty()) {
+            path = resolve_bundle(fs, &path)?;
+            debug!("Resolved Parallels bundle to {}", path.display());
+        }
+        let mut file = fs
+            .open(&path)
+            .map_err(|e| format!("Error opening Parallels image: {}", e))?;
+
+        let mut raw_header = vec![0u8; PARALLELS_HEADER_SIZE];
+        file.read_exact(&mut raw_header)
+            .map_err(|e| format!("Error reading Parallels header: {}", e))?;
+        let header = ParallelsHeader::parse(&raw_header)?;
+        debug!("Parsed Parallels header: {:?}", header);
+
+        if header.in_use {
+            warn!("Parallels image is flagged as in use; it may not have been closed cleanly");
+        }
+
+        let file_len = file
+            .len()
+            .map_err(|e| format!("Error reading Parallels metadata: {}", e))?;
+        let bat_end = PARALLELS_HEADER_SIZE as u64 + header.bat_entries as u64 * 4;
+        if bat_end > file_len {
+            return Err(format!(
+                "Parallels BAT of {} entries runs past the end of the file",
+                header.bat_entries
+            ));
+        }
+
+        let mut raw_bat = vec![0u8; header.bat_entries as usize * 4];
+        file.read_exact(&mut raw_bat)
+            .map_err(|e| format!("Error reading Parallels BAT: {}", e))?;
+        let bat = raw_bat
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+            .collect();
+
+        Ok(PARALLELS {
+            file,
+            path: path.to_string_lossy().into_owned(),
+            header,
+            bat,
+            position: 0,
+        })
+    }
+
+    // ---- Info helpers -------------------------------------------------------
+
+    /// Header variant, virtual size, geometry and cluster allocation.
+    pub fn info(&self) -> FormatInfo {
+        let (heads, cylinders) = self.header.geometry;
+        let allocated = self.bat.iter().filter(|&&e| e != 0).count();
+        FormatInfo::new("Parallels Image Information")
+            .field("Path", &self.path)
+            .field(
+                "Format",
+                if self.header.extended {
+                    "WithouFreSpacExt"
+                } else {
+                    "WithoutFreeSpace"
+                },
+            )
+            .field("Virtual Size", format!("{} bytes", self.size()))
+            .field("Sector Size", PARALLELS_SECTOR_SIZE)
+            .field("Geometry (CH)", format!("{}/{}", cylinders, heads))
+            .field(
+                "Cluster Size",
+                format!("{} bytes", self.header.cluster_size()),
+            )
+            .field(
+                "Clusters",
+                format!("{} ({} allocated)", self.header.bat_entries, allocated),
+            )
+            .field(
+                "Data Offset",
+                format!("{} sectors", self.header.data_offset),
+            )
+    }
+
+    /// Print parsed metadata to the log.
+    pub fn print_info(&self) {
+        self.info().log();
+    }
+
+    /// Returns the sector size (always 512 for Parallels images).
+    pub fn get_sector_size(&self) -> u16 {
+        PARALLELS_SECTOR_SIZE as u16
+    }
+
+    /// Size of the virtual disk in bytes.
+    pub fn size(&self) -> u64 {
+        self.header.sector_count * PARALLELS_SECTOR_SIZE
+    }
+
+    // ---- Internal cluster reading -------------------------------------------
+
+    /// Fill `buf` from the virtual offset `position`, without crossing a
+    /// cluster boundary. Returns the number of bytes produced.
+    fn read_cluster(&self, position: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let cluster_size = self.header.cluster_size();
+        let cluster = (position / cluster_size) as usize;
+        let offset_in_cluster = position % cluster_size;
+        let len = min(buf.len() as u64, cluster_size - offset_in_cluster) as usize;
+
+        let entry = *self.bat.get(cluster).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Parallels cluster {} is beyond the BAT", cluster),
+            )
+        })?;
+
+        if entry == 0 {
+            buf[..len].fill(0);
+            return Ok(len);
+        }
+
+        let unit = if self.header.extended {
+            cluster_size
+        } else {
+            PARALLELS_SECTOR_SIZE
+        };
+        self.file
+            .read_exact_at(entry as u64 * unit + offset_in_cluster, &mut buf[..len])?;
+        Ok(len)
+    }
+}
+
+// ---- Clone ------------------------------------------------------------------
+
+impl Clone for PARALLELS {
+    fn clone(&self) -> Self {
+        Self {
+            file: self.file.clone(),
+            path: self.path.clone(),
+            header: self.header.clone(),
+            bat: self.bat.clone(),
+            position: self.position,
+        }
+    }
+}
+
+// ---- Read / ReadAt ----------------------------------------------------------
+
+impl Read for PARALLELS {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.read_at(self.position, buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl ReadAt for PARALLELS {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let image_size = self.size();
+        if buf.is_empty() || offset >= image_size {
+            return Ok(0);
+        }
+
+        let want = min(buf.len() as u64, image_size - offset) as usize;
+        let mut total = 0usize;
+
+        while total < want {
+            let position = offset + total as u64;
+            let n = self.read_cluster(position, &mut buf[total..want])?;
+            total += n;
+        }
+
+        Ok(total)
+    }
+}
+
+// ---- Seek -------------------------------------------------------------------
+
+impl Seek for PARALLELS {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let next = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.size().checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Seek before start or overflow")
+        })?;
+
+        self.position = next;
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use crate::vfs::MemoryFileSystem;
+
+    const CLUSTER: usize = 1024;
+
+    /// A 2560-byte expanding image with 1 KiB clusters: cluster 0 stored
+    /// last, cluster 1 unallocated and cluster 2 stored first.
+    fn make_parallels(extended: bool, first: &[u8], last: &[u8]) -> Vec<u8> {
+        let mut image = vec![0u8; 3 * CLUSTER];
+        image[0..16].copy_from_slice(if extended {
+            &PARALLELS_MAGIC_EXT
+        } else {
+            &PARALLELS_MAGIC
+        });
+        let fields: [(usize, u32); 6] = [
+            (16, PARALLELS_VERSION),
+            (20, 16),
+            (24, 1),
+            (28, 2),
+            (32, 3),
+            (48, 2),
+        ];
+        for (offset, value) in fields {
+            image[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        }
+        image[36..40].copy_from_slice(&5u32.to_le_bytes());
+
+        let bat: [u32; 3] = if extended { [2, 0, 1] } else { [4, 0, 2] };
+        for (i, entry) in bat.iter().enumerate() {
+            let offset = PARALLELS_HEADER_SIZE + i * 4;
+            image[offset..offset + 4].copy_from_slice(&entry.to_le_bytes());
+        }
+        image[CLUSTER..CLUSTER + last.len()].copy_from_slice(last);
+        image[2 * CLUSTER..].copy_from_slice(first);
+        image
+    }
+
+    #[test]
+    fn reads_both_header_variants() {
+        let first: Vec<u8> = (0..CLUSTER).map(|i| (i % 251) as u8).collect();
+        let last: Vec<u8> = (0..CLUSTER / 2).map(|i| (i % 7) as u8 + 1).collect();
+
+        let dir = TempDir::new("parallels");
+        for extended in [false, true] {
+            let path = dir.join("disk.hds");
+            std::fs::write(&path, make_parallels(extended, &first, &last)).unwrap();
+
+            let mut image = PARALLELS::new(&path.to_string_lossy()).unwrap();
+            assert_eq!(image.size(), 5 * PARALLELS_SECTOR_SIZE);
+            assert_eq!(image.get_sector_size(), 512);
+
+            let mut back = Vec::new();
+            image.read_to_end(&mut back).unwrap();
+            assert_eq!(back.len(), 5 * 512);
+            assert_eq!(back[..CLUSTER], first);
+            assert!(back[CLUSTER..2 * CLUSTER].iter().all(|&b| b == 0));
+            assert_eq!(back[2 * CLUSTER..], last);
+
+            let mut buf = [0xAAu8; 16];
+            image.seek(SeekFrom::Start(2 * CLUSTER as u64 - 8)).unwrap();
+            image.read_exact(&mut buf).unwrap();
+            assert_eq!(buf[..8], [0; 8]);
+            assert_eq!(buf[8..], last[..8]);
+        }
+    }
+    #[test]
+    fn rejects_bats_past_the_end_of_the_file() {
+        let mut image = make_parallels(false, &[0; CLUSTER], &[0; CLUSTER / 2]);
+        image[32..36].copy_from_slice(&0x4000_0000u32.to_le_bytes());
+        let dir = TempDir::new("parallels_bat");
+        let path = dir.join("disk.hds");
+        std::fs::write(&path, image).unwrap();
+        let Err(err) = PARALLELS::new(&path.to_string_lossy()) else {
+            panic!("opened a Parallels image whose BAT runs past the end of the file");
+        };
+        assert!(err.to_string().contains("past the end"), "{}", err);
+    }
+
+    #[test]
+    fn resolves_bundles_through_the_file_system() {
+        let first: Vec<u8> = (0..CLUSTER).map(|i| (i % 251) as u8).collect();
+        let mut fs = MemoryFileSystem::new();
+        fs.insert(
+            "vm.hdd/DiskDescriptor.xml",
+            b"<Storage><Image><File>base.hds</File></Image></Storage>".to_vec(),
+        );
+        fs.insert(
+            "vm.hdd/base.hds",
+            make_parallels(false, &first, &[0; CLUSTER / 2]),
+        );
+
+        let mut image = PARALLELS::open_in(&fs, "vm.hdd").unwrap();
+        let mut buf = vec![0u8; CLUSTER];
+        image.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, first);
+    }
+}
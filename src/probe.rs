@@ -0,0 +1,85 @@
+//! Format detection report, see [`Body::probe`](crate::Body::probe).
+
+use std::fmt;
+
+/// Outcome of trying one format on a file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProbeCandidate {
+    /// Format name, as accepted by [`Body::try_new`](crate::Body::try_new).
+    pub format: String,
+    /// Whether the file opened as this format.
+    pub matched: bool,
+    /// What was found when the format matched, or why it was rejected.
+    pub detail: String,
+}
+
+/// Result of [`Body::probe`](crate::Body::probe): every format `"auto"`
+/// detection considers, in the order it tries them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProbeResult {
+    pub path: String,
+    /// First bytes of the file (up to 16), empty when it cannot be read.
+    pub magic: Vec<u8>,
+    pub candidates: Vec<ProbeCandidate>,
+}
+
+impl ProbeResult {
+    /// The format `"auto"` detection picks: the first matching candidate.
+    pub fn detected(&self) -> Option<&str> {
+        self.matches().next().map(|c| c.format.as_str())
+    }
+
+    /// Candidates the file opened as.
+    pub fn matches(&self) -> impl Iterator<Item = &ProbeCandidate> {
+        self.candidates.iter().filter(|c| c.matched)
+    }
+
+    /// Candidates the file was rejected as.
+    pub fn rejections(&self) -> impl Iterator<Item = &ProbeCandidate> {
+        self.candidates.iter().filter(|c| !c.matched)
+    }
+}
+
+impl fmt::Display for ProbeResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.path)?;
+        let magic: Vec<String> = self.magic.iter().map(|b| format!("{:02x}", b)).collect();
+        writeln!(f, "  magic: {}", magic.join(" "))?;
+        for candidate in &self.candidates {
+            let mark = if candidate.matched { '+' } else { '-' };
+            writeln!(
+                f,
+                "  {} {:<10} {}",
+                mark, candidate.format, candidate.detail
+            )?;
+        }
+        write!(f, "  detected: {}", self.detected().unwrap_or("none"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::TempDir;
+    use crate::{Body, EwfWriter};
+    use std::io::Cursor;
+
+    #[test]
+    fn reports_every_candidate() {
+        let dir = TempDir::new("probe");
+        let data = vec![0x5au8; 64 * 1024];
+        EwfWriter::new()
+            .write(&mut Cursor::new(&data), dir.join("p"))
+            .unwrap();
+
+        let result = Body::probe(&dir.join("p.E01").to_string_lossy());
+        assert_eq!(result.detected(), Some("ewf"));
+        assert_eq!(&result.magic[..3], b"EVF");
+        assert!(result.candidates.iter().any(|c| c.format == "vmdk"));
+        assert!(result.rejections().all(|c| !c.detail.is_empty()));
+        assert!(result.matches().any(|c| c.format == "raw"));
+
+        let result = Body::probe(&dir.join("missing").to_string_lossy());
+        assert_eq!(result.detected(), None);
+        assert!(result.magic.is_empty());
+    }
+}
@@ -0,0 +1,192 @@
+//! Python bindings, enabled by the `python` feature.
+//!
+//! Builds the `exhume_body` extension module (with maturin, or any pyo3
+//! build backend) whose `Body` class is a read-only binary file object:
+//!
+//! ```python
+//! import exhume_body
+//!
+//! with exhume_body.Body("/evidence/disk.E01") as body:
+//!     body.seek(0x1FE)
+//!     assert body.read(2) == b"\x55\xaa"
+//!     print(body.metadata()["description"], body.size())
+//! ```
+
+use crate::{Body, ExhumeBodyError, ReadAt};
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use std::io::{Read, Seek, SeekFrom};
+
+impl From<ExhumeBodyError> for PyErr {
+    fn from(e: ExhumeBodyError) -> Self {
+        match e {
+            ExhumeBodyError::UnknownFormat(_) => PyValueError::new_err(e.to_string()),
+            ExhumeBodyError::Io(e) | ExhumeBodyError::Raw(e) => e.into(),
+            e => PyOSError::new_err(e.to_string()),
+        }
+    }
+}
+
+/// A disk image opened as a read-only binary file.
+#[pyclass(name = "Body", module = "exhume_body")]
+pub struct PyBody {
+    body: Option<Body>,
+}
+
+impl PyBody {
+    fn body(&mut self) -> PyResult<&mut Body> {
+        self.body
+            .as_mut()
+            .ok_or_else(|| PyValueError::new_err("I/O operation on closed body"))
+    }
+}
+
+#[pymethods]
+impl PyBody {
+    /// Open `path` as `format` (`"auto"` detects it).
+    #[new]
+    #[pyo3(signature = (path, format = "auto"))]
+    fn new(path: String, format: &str) -> PyResult<Self> {
+        Ok(Self {
+            body: Some(Body::builder(path).format(format).build()?),
+        })
+    }
+
+    /// Read up to `size` bytes, everything up to the end when `size` is
+    /// negative.
+    #[pyo3(signature = (size = -1))]
+    fn read<'py>(&mut self, py: Python<'py>, size: i64) -> PyResult<Bound<'py, PyBytes>> {
+        let body = self.body()?;
+        let mut buf = Vec::new();
+        py.allow_threads(|| {
+            if size < 0 {
+                body.read_to_end(&mut buf)
+            } else {
+                body.take(size as u64).read_to_end(&mut buf)
+            }
+        })?;
+        Ok(PyBytes::new(py, &buf))
+    }
+
+    /// Read up to `size` bytes at `offset` without moving the position,
+    /// the `Img_Info.read(offset, size)` shape pytsk expects.
+    fn read_at<'py>(
+        &mut self,
+        py: Python<'py>,
+        offset: u64,
+        size: usize,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let body = self.body()?;
+        let mut buf = vec![0u8; size];
+        let mut done = 0;
+        py.allow_threads(|| -> std::io::Result<()> {
+            while done < size {
+                match body.read_at(offset + done as u64, &mut buf[done..])? {
+                    0 => break,
+                    n => done += n,
+                }
+            }
+            Ok(())
+        })?;
+        Ok(PyBytes::new(py, &buf[..done]))
+    }
+
+    /// Move the position as `io.IOBase.seek` does and return it.
+    #[pyo3(signature = (offset, whence = 0))]
+    fn seek(&mut self, offset: i64, whence: i32) -> PyResult<u64> {
+        let pos = match whence {
+            0 if offset >= 0 => SeekFrom::Start(offset as u64),
+            0 => return Err(PyValueError::new_err("negative seek position")),
+            1 => SeekFrom::Current(offset),
+            2 => SeekFrom::End(offset),
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "invalid whence ({})",
+                    whence
+                )))
+            }
+        };
+        Ok(self.body()?.seek(pos)?)
+    }
+
+    fn tell(&mut self) -> PyResult<u64> {
+        Ok(self.body()?.stream_position()?)
+    }
+
+    /// Size of the image contents in bytes.
+    fn size(&mut self) -> PyResult<u64> {
+        Ok(self.body()?.size()?)
+    }
+
+    /// Logical sector size in bytes.
+    fn sector_size(&mut self) -> PyResult<u16> {
+        Ok(self.body()?.get_sector_size())
+    }
+
+    /// Format, description, size, sector size, segment count, acquisition
+    /// information, geometry and stored hashes, as a dict.
+    fn metadata<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let metadata = self.body()?.metadata()?;
+        let dict = PyDict::new(py);
+        dict.set_item("format", metadata.format)?;
+        dict.set_item("description", metadata.description)?;
+        dict.set_item("size", metadata.size)?;
+        dict.set_item("sector_size", metadata.sector_size)?;
+        dict.set_item("segment_count", metadata.segment_count)?;
+        dict.set_item("acquisition", metadata.acquisition)?;
+        match metadata.geometry {
+            Some(geometry) => {
+                let g = PyDict::new(py);
+                g.set_item("cylinders", geometry.cylinders)?;
+                g.set_item("heads", geometry.heads)?;
+                g.set_item("sectors_per_track", geometry.sectors_per_track)?;
+                dict.set_item("geometry", g)?;
+            }
+            None => dict.set_item("geometry", py.None())?,
+        }
+        let hashes = PyDict::new(py);
+        for (algorithm, digest) in metadata.stored_hashes {
+            hashes.set_item(algorithm.name(), digest)?;
+        }
+        dict.set_item("stored_hashes", hashes)?;
+        Ok(dict)
+    }
+
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn seekable(&self) -> bool {
+        true
+    }
+
+    fn writable(&self) -> bool {
+        false
+    }
+
+    #[getter]
+    fn closed(&self) -> bool {
+        self.body.is_none()
+    }
+
+    /// Release the image files. Further operations raise `ValueError`.
+    fn close(&mut self) {
+        self.body = None;
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __exit__(&mut self, _args: &Bound<'_, pyo3::types::PyTuple>) {
+        self.close();
+    }
+}
+
+#[pymodule]
+fn exhume_body(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyBody>()?;
+    Ok(())
+}
@@ -3,38 +3,277 @@
 //! This module provides the [`RAW`] struct, a thin wrapper around [`std::fs::File`]
 //! that implements [`std::io::Read`] and [`std::io::Seek`].
 //!
+//! [`RAW::from_reader`] reads the image from any [`Read`] + [`Seek`] stream
+//! instead of a file.
+//!
+//! Split images (`image.001`, `image.002`, … as produced by `split -d` or
+//! FTK Imager) are discovered automatically and stitched into one continuous
+//! stream.
+//!
 
+use crate::ddrescue::RescueMap;
+use crate::error::ExhumeBodyError;
+use crate::info::FormatInfo;
+use crate::metadata::{probe_sector_size, EvidenceFile};
+use crate::read_at::ReadAt;
+use crate::retry::RetryPolicy;
+use crate::source::Source;
+use crate::stats::IoCounters;
+use crate::trace::io_span;
+use crate::vfs::{FileSystem, StdFileSystem};
+use log::warn;
 use std::{
-    fs::File,
     io::{self, Read, Seek, SeekFrom},
-    path::Path,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
 };
 
-/// A simple, clonable wrapper around a [`File`] that represents a RAW binary stream.
+/// One file of a split RAW image.
+struct RawSegment {
+    /// Path of the segment file.
+    path: PathBuf,
+    /// Handle to the segment file.
+    file: Source,
+    /// Offset of the segment's first byte inside the stitched image.
+    start: u64,
+    /// Size of the segment in bytes.
+    size: u64,
+}
+
+/// A simple, clonable wrapper around a [`File`](std::fs::File) that represents a RAW binary stream.
 ///
 /// This wrapper allows random access (`Seek`) and buffered reads (`Read`)
 /// while also exposing convenience helpers to read fixed-size blocks and
 /// to reposition the cursor.
 ///
-/// Cloning a [`RAW`] duplicates the underlying file handle using
-/// [`File::try_clone`], so both instances share the same file but maintain
-/// independent cursors.
+/// Cloning a [`RAW`] shares the underlying handles and segment list; reads
+/// are positional, so both instances maintain independent cursors.
 pub struct RAW {
-    /// The underlying file handle.
-    pub file: File,
+    /// The underlying file or stream (the first segment of a split image).
+    pub file: Source,
+    /// Every segment of a split image, in order. Empty for single-file
+    /// images, which delegate straight to `file`.
+    segments: Arc<[RawSegment]>,
+    /// Current position, independent from the (possibly shared) handles.
+    position: u64,
+    /// Logical sector size, 4096 for a 4Kn GPT disk and 512 otherwise.
+    sector_size: u16,
+    /// Read statistics, shared with the clones.
+    io: Arc<IoCounters>,
+    /// How failed reads are retried.
+    retry: RetryPolicy,
+    /// ddrescue mapfile of the image, if any.
+    rescue_map: Option<Arc<RescueMap>>,
+    /// Byte ranges the mapfile leaves unrescued, sorted and coalesced.
+    unrescued: Arc<[Range<u64>]>,
 }
 
 impl RAW {
     /// Opens the file at `file_path` and returns a new [`RAW`] wrapper.
     ///
+    /// When the file name ends with a numeric extension (`.001`, `.002`, …),
+    /// every sibling segment of the same split image is opened as well.
+    ///
     /// # Errors
     ///
-    /// Returns any [`io::Error`] produced by [`File::open`], e.g. when the
-    /// path does not exist or the process lacks sufficient permissions.
-    pub fn new(file_path: &str) -> Result<RAW, io::Error> {
+    /// Returns [`ExhumeBodyError::Raw`] with the [`io::Error`] produced by
+    /// [`FileSystem::open`], e.g. when the path does not exist or the process
+    /// lacks sufficient permissions.
+    pub fn new(file_path: &str) -> Result<RAW, ExhumeBodyError> {
+        Self::open_in(&StdFileSystem, file_path)
+    }
+
+    /// Same as [`RAW::new`], opening the file and its split siblings
+    /// through `fs` instead of the operating system.
+    pub fn open_in(fs: &dyn FileSystem, file_path: &str) -> Result<RAW, ExhumeBodyError> {
+        Self::open_segments(fs, file_path).map_err(ExhumeBodyError::Raw)
+    }
+
+    fn open_segments(fs: &dyn FileSystem, file_path: &str) -> Result<RAW, io::Error> {
         let path = Path::new(file_path);
-        let file = File::open(path)?;
-        Ok(RAW { file })
+        let file = fs.open(path)?;
+
+        let paths = find_segments(fs, path)?;
+        if paths.len() < 2 || !paths.iter().any(|p| p.file_name() == path.file_name()) {
+            return Ok(RAW::from_source(file));
+        }
+
+        let mut segments = Vec::with_capacity(paths.len());
+        let mut start = 0u64;
+        for segment_path in &paths {
+            let segment_file = fs.open(segment_path)?;
+            let size = segment_file.len()?;
+            segments.push(RawSegment {
+                path: segment_path.clone(),
+                file: segment_file,
+                start,
+                size,
+            });
+            start += size;
+        }
+
+        Ok(RAW {
+            file: segments[0].file.clone(),
+            segments: segments.into(),
+            position: 0,
+            sector_size: 512,
+            io: Arc::default(),
+            retry: RetryPolicy::default(),
+            rescue_map: None,
+            unrescued: Arc::new([]),
+        }
+        .with_probed_sector_size())
+    }
+
+    /// Reads the image from `reader`, e.g. an in-memory buffer.
+    pub fn from_reader(reader: impl Read + Seek + Send + 'static) -> RAW {
+        Self::from_source(Source::from_reader(reader))
+    }
+
+    /// Reads the image from an already opened [`Source`].
+    pub fn from_source(source: Source) -> RAW {
+        RAW {
+            file: source,
+            segments: Arc::new([]),
+            position: 0,
+            sector_size: 512,
+            io: Arc::default(),
+            retry: RetryPolicy::default(),
+            rescue_map: None,
+            unrescued: Arc::new([]),
+        }
+        .with_probed_sector_size()
+    }
+
+    fn with_probed_sector_size(mut self) -> Self {
+        self.sector_size = probe_sector_size(&self).unwrap_or(512);
+        self
+    }
+
+    /// Logical sector size: 4096 when the image holds a GPT written for
+    /// 4096-byte sectors, 512 otherwise.
+    pub fn get_sector_size(&self) -> u16 {
+        self.sector_size
+    }
+
+    /// Internal counters behind [`Body::stats`](crate::Body::stats).
+    pub(crate) fn io_counters(&self) -> &IoCounters {
+        &self.io
+    }
+
+    /// Segment count and total size of a split image, or the size of a
+    /// single-file one.
+    pub fn info(&self) -> FormatInfo {
+        if self.segments.is_empty() {
+            let info = FormatInfo::new("RAW Image Information");
+            return match self.file.len() {
+                Ok(size) => info.field("Size", format!("{} bytes", size)),
+                Err(_) => info,
+            };
+        }
+        FormatInfo::new("Split RAW Image Information")
+            .field("Number of Segments", self.segments.len())
+            .field("Total Size", format!("{} bytes", self.split_size()))
+    }
+
+    /// Outputs a human-readable summary of split images to the current `log`
+    /// subscriber. Single-file images have nothing to report.
+    pub fn print_info(&self) {
+        self.info().log();
+    }
+
+    /// Total size of a split image (sum of its segments).
+    fn split_size(&self) -> u64 {
+        self.segments.last().map_or(0, |s| s.start + s.size)
+    }
+
+    /// Number of files making up the image (1 unless split).
+    pub fn segment_count(&self) -> usize {
+        self.segments.len().max(1)
+    }
+
+    /// Retry failed reads under `policy`, see [`RetryPolicy`].
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry = policy;
+    }
+
+    /// Read the image as rescued by ddrescue according to `map`: reads of
+    /// the areas it did not rescue fail with [`io::ErrorKind::InvalidData`]
+    /// instead of returning the zeros the image holds there.
+    pub fn set_rescue_map(&mut self, map: RescueMap) -> io::Result<()> {
+        self.unrescued = map.unrescued(self.size()?).into();
+        self.rescue_map = Some(Arc::new(map));
+        Ok(())
+    }
+
+    /// The ddrescue mapfile set with [`RAW::set_rescue_map`].
+    pub fn rescue_map(&self) -> Option<&RescueMap> {
+        self.rescue_map.as_deref()
+    }
+
+    /// Byte ranges holding rescued data: the whole image without a
+    /// ddrescue mapfile.
+    pub fn allocated_ranges(&self) -> io::Result<Vec<Range<u64>>> {
+        let size = self.size()?;
+        let mut ranges = Vec::with_capacity(self.unrescued.len() + 1);
+        let mut start = 0;
+        for hole in self.unrescued.iter() {
+            ranges.push(start..hole.start);
+            start = hole.end;
+        }
+        ranges.push(start..size);
+        ranges.retain(|r| r.start < r.end);
+        Ok(ranges)
+    }
+
+    /// How many of the `len` bytes at `offset` can be read before an area
+    /// the ddrescue mapfile leaves unrescued; an error when `offset` is in
+    /// one.
+    fn rescued_len(&self, offset: u64, len: usize) -> io::Result<usize> {
+        let unrescued = &self.unrescued;
+        let Some(hole) = unrescued.get(unrescued.partition_point(|r| r.end <= offset)) else {
+            return Ok(len);
+        };
+        if hole.start <= offset {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("0x{:x} was not rescued by ddrescue", offset),
+            ));
+        }
+        Ok((hole.start - offset).min(len as u64) as usize)
+    }
+
+    /// Every segment of a split image with its size, in order. The single
+    /// file of other images is reported without a path, which RAW does not
+    /// keep.
+    pub fn files(&self) -> io::Result<Vec<EvidenceFile>> {
+        if self.segments.is_empty() {
+            return Ok(vec![EvidenceFile {
+                path: String::new(),
+                size: self.file.len()?,
+                role: "RAW image".to_string(),
+            }]);
+        }
+        let count = self.segments.len();
+        Ok(self
+            .segments
+            .iter()
+            .enumerate()
+            .map(|(i, segment)| EvidenceFile {
+                path: segment.path.display().to_string(),
+                size: segment.size,
+                role: format!("RAW segment {}/{}", i + 1, count),
+            })
+            .collect())
+    }
+
+    /// Size of the image in bytes (all segments of a split image).
+    pub fn size(&self) -> io::Result<u64> {
+        if self.segments.is_empty() {
+            return self.file.len();
+        }
+        Ok(self.split_size())
     }
 
     /// Reads exactly `size` bytes (or until EOF) from the current cursor
@@ -48,7 +287,7 @@ impl RAW {
     /// Propagates any I/O error returned by [`Read::read`].
     pub fn read_size(&mut self, size: usize) -> io::Result<Vec<u8>> {
         let mut buffer = vec![0; size];
-        let bytes_read = self.file.read(&mut buffer)?;
+        let bytes_read = self.read(&mut buffer)?;
         buffer.truncate(bytes_read);
         Ok(buffer)
     }
@@ -60,26 +299,25 @@ impl RAW {
     ///
     /// Propagates any I/O error returned by [`Seek::seek`].
     pub fn seek_from_start(&mut self, offset: u64) -> io::Result<u64> {
-        self.file.seek(SeekFrom::Start(offset))
+        self.seek(SeekFrom::Start(offset))
     }
 }
 
 impl Clone for RAW {
-    /// Clones the [`RAW`] instance by duplicating the underlying file handle.
-    ///
-    /// Cloning is cheap (just a file-descriptor duplication) but note that
-    /// the new handle has a *separate* seek position.
+    /// Clones the [`RAW`] instance, sharing the underlying handles.
     ///
-    /// # Panics
-    ///
-    /// Panics if [`File::try_clone`] fails—this usually indicates running
-    /// out of file descriptors or OS-level resource limits.
+    /// Cloning is cheap (no file descriptor is duplicated) and the new
+    /// instance has a *separate* position.
     fn clone(&self) -> Self {
         Self {
-            file: self
-                .file
-                .try_clone()
-                .expect("failed to clone RAW file handle"),
+            file: self.file.clone(),
+            segments: self.segments.clone(),
+            position: self.position,
+            sector_size: self.sector_size,
+            io: self.io.clone(),
+            retry: self.retry,
+            rescue_map: self.rescue_map.clone(),
+            unrescued: self.unrescued.clone(),
         }
     }
 }
@@ -87,15 +325,197 @@ impl Clone for RAW {
 impl Read for RAW {
     /// Reads data from the underlying file into `buf` and returns the number of bytes read.
     ///
-    /// This just forwards to [`File::read`].
+    /// Reads at the instance's own position (see [`ReadAt`]); split images
+    /// are read across segment boundaries.
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        self.file.read(buf)
+        let n = self.read_at(self.position, buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl ReadAt for RAW {
+    /// Positional read, across segment boundaries for split images, retried
+    /// under the [`RetryPolicy`] of the image.
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let len = self.rescued_len(offset, buf.len())?;
+        let buf = &mut buf[..len];
+        self.retry.run(|| self.read_segments_at(offset, buf))
+    }
+
+    fn read_at_append(&self, offset: u64, buf: &mut Vec<u8>, len: usize) -> io::Result<usize> {
+        if len == 0 {
+            return Ok(0);
+        }
+        let len = self.rescued_len(offset, len)?;
+        self.retry
+            .run(|| self.read_segments_at_append(offset, buf, len))
+    }
+}
+
+impl RAW {
+    fn read_segments_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        if self.segments.is_empty() {
+            return self.file.read_at(offset, buf);
+        }
+
+        let mut total = 0usize;
+        let mut position = offset;
+        let mut previous = None;
+        while total < buf.len() {
+            // Index of the segment holding `position` (if any).
+            let idx = self
+                .segments
+                .partition_point(|s| s.start + s.size <= position);
+            let Some(segment) = self.segments.get(idx) else {
+                break;
+            };
+            if previous
+                .replace(idx)
+                .is_some_and(|previous| previous != idx)
+            {
+                self.io.segment_switch();
+            }
+
+            let offset_in_segment = position - segment.start;
+            let to_read = (segment.size - offset_in_segment).min((buf.len() - total) as u64);
+            let _span = io_span!(
+                "raw.segment",
+                segment = idx,
+                offset = offset_in_segment,
+                len = to_read
+            );
+            let n = segment
+                .file
+                .read_at(offset_in_segment, &mut buf[total..total + to_read as usize])?;
+            if n == 0 {
+                break;
+            }
+            total += n;
+            position += n as u64;
+        }
+        Ok(total)
+    }
+
+    fn read_segments_at_append(
+        &self,
+        offset: u64,
+        buf: &mut Vec<u8>,
+        len: usize,
+    ) -> io::Result<usize> {
+        if self.segments.is_empty() {
+            return self.file.read_at_append(offset, buf, len);
+        }
+
+        let start = buf.len();
+        let mut position = offset;
+        let mut previous = None;
+        while buf.len() - start < len {
+            let idx = self
+                .segments
+                .partition_point(|s| s.start + s.size <= position);
+            let Some(segment) = self.segments.get(idx) else {
+                break;
+            };
+            if previous
+                .replace(idx)
+                .is_some_and(|previous| previous != idx)
+            {
+                self.io.segment_switch();
+            }
+
+            let offset_in_segment = position - segment.start;
+            let to_read =
+                (segment.size - offset_in_segment).min((len - (buf.len() - start)) as u64);
+            let _span = io_span!(
+                "raw.segment",
+                segment = idx,
+                offset = offset_in_segment,
+                len = to_read
+            );
+            let n = match segment
+                .file
+                .read_at_append(offset_in_segment, buf, to_read as usize)
+            {
+                Ok(n) => n,
+                Err(e) => {
+                    buf.truncate(start);
+                    return Err(e);
+                }
+            };
+            if n == 0 {
+                break;
+            }
+            position += n as u64;
+        }
+        Ok(buf.len() - start)
     }
 }
 
 impl Seek for RAW {
-    /// Seeks within the underlying file, delegating to [`File::seek`].
+    /// Moves the instance's own position, leaving the file handles untouched.
+    /// For split images, `SeekFrom::End` is relative to the last segment.
     fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
-        self.file.seek(pos)
+        let next = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.size()?.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Seek before start or overflow")
+        })?;
+
+        self.position = next;
+        Ok(self.position)
+    }
+}
+
+/// Look for every segment belonging to the *same* split image as `path`.
+///
+/// Only file names ending with a numeric extension (`.001`, `.0001`, …) are
+/// considered split. The function lists the siblings with an extension of
+/// the same width and returns the consecutive run starting at the lowest
+/// number. Any other file yields an empty list.
+fn find_segments(fs: &dyn FileSystem, path: &Path) -> io::Result<Vec<PathBuf>> {
+    let Some(extension) = path.extension().and_then(|e| e.to_str()) else {
+        return Ok(Vec::new());
+    };
+    if extension.is_empty() || !extension.bytes().all(|b| b.is_ascii_digit()) {
+        return Ok(Vec::new());
+    }
+    let stem = path.file_stem();
+    let directory = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+
+    let mut numbered: Vec<(u64, PathBuf)> = fs
+        .read_dir(directory)?
+        .into_iter()
+        .filter(|p| p.file_stem() == stem)
+        .filter_map(|p| {
+            let ext = p.extension()?.to_str()?;
+            if ext.len() != extension.len() || !ext.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            Some((ext.parse::<u64>().ok()?, p))
+        })
+        .collect();
+    numbered.sort();
+
+    let mut segments = Vec::with_capacity(numbered.len());
+    for (i, (n, p)) in numbered.iter().enumerate() {
+        if i > 0 && *n != numbered[i - 1].0 + 1 {
+            warn!(
+                "Split RAW segment numbering has a gap before {}, ignoring the remaining files",
+                p.display()
+            );
+            break;
+        }
+        segments.push(p.clone());
     }
+    Ok(segments)
 }
@@ -0,0 +1,329 @@
+//! Read-ahead prefetching over any [`Body`], see [`ReadAhead`].
+//!
+//! Hashing and imaging passes read an image front to back. [`ReadAhead`]
+//! notices that pattern and keeps worker threads busy reading (and therefore
+//! decompressing) the next blocks through [`ReadAt`], so the caller finds them
+//! ready instead of waiting on zlib, LZMA or grain lookups.
+//!
+//! ```no_run
+//! # use exhume_body::{Body, ReadAhead};
+//! # use std::io::Read;
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let body = Body::try_new("/evidence/disk.vmdk".to_string(), "auto")?;
+//! let mut reader = ReadAhead::new(body)?.depth(16).threads(4);
+//! let mut data = Vec::new();
+//! reader.read_to_end(&mut data)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::read_at::ReadAt;
+use crate::Body;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+
+/// Default size of a prefetched block.
+const READ_AHEAD_BLOCK_SIZE: usize = 1024 * 1024;
+/// Default number of blocks kept in flight ahead of the reader.
+const READ_AHEAD_DEPTH: usize = 8;
+/// Default number of worker threads.
+const READ_AHEAD_THREADS: usize = 2;
+
+/// State of a block handed to the workers.
+enum Slot {
+    /// Queued or being read.
+    Pending,
+    Ready(io::Result<Vec<u8>>),
+}
+
+#[derive(Default)]
+struct State {
+    /// Blocks waiting for a worker, in reading order.
+    queue: VecDeque<u64>,
+    /// Blocks requested ahead of the reader. A worker drops its result when
+    /// the entry was discarded in the meantime (the reader seeked away).
+    blocks: HashMap<u64, Slot>,
+    shutdown: bool,
+}
+
+struct Shared {
+    body: Arc<Body>,
+    block_size: usize,
+    state: Mutex<State>,
+    /// Signalled when work is queued or on shutdown.
+    work: Condvar,
+    /// Signalled when a block is ready.
+    ready: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn read_block(&self, block: u64) -> io::Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(self.block_size);
+        let offset = block * self.block_size as u64;
+        while data.len() < self.block_size {
+            let filled = data.len();
+            match self.body.read_at_append(
+                offset + filled as u64,
+                &mut data,
+                self.block_size - filled,
+            ) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(data)
+    }
+
+    fn work(&self) {
+        let mut state = self.lock();
+        loop {
+            if state.shutdown {
+                return;
+            }
+            let Some(block) = state.queue.pop_front() else {
+                state = self.work.wait(state).unwrap_or_else(|e| e.into_inner());
+                continue;
+            };
+            drop(state);
+            let result = self.read_block(block);
+            state = self.lock();
+            if let Some(slot @ Slot::Pending) = state.blocks.get_mut(&block) {
+                *slot = Slot::Ready(result);
+                self.ready.notify_all();
+            }
+        }
+    }
+}
+
+/// Sequential reader over a [`Body`] that prefetches upcoming blocks on
+/// worker threads.
+///
+/// Prefetching starts once two consecutive blocks have been read, so random
+/// access costs no more than reading the body directly. Configure the reader
+/// with [`depth`](Self::depth), [`threads`](Self::threads) and
+/// [`block_size`](Self::block_size) before the first read.
+pub struct ReadAhead {
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+    threads: usize,
+    depth: usize,
+    size: u64,
+    position: u64,
+    /// Block being consumed by the reader.
+    current: Option<(u64, Vec<u8>)>,
+    /// Consecutive blocks read since the last jump.
+    run: usize,
+}
+
+impl ReadAhead {
+    /// Wrap an opened body. Computes its size, which may read the whole image
+    /// for formats without an index (gzip).
+    pub fn new(mut body: Body) -> io::Result<Self> {
+        let size = body.size()?;
+        Ok(Self::from_shared(Arc::new(body), size))
+    }
+
+    /// Prefetch from a body already shared behind an `Arc`. `size` is the
+    /// value of [`Body::size`].
+    pub fn from_shared(body: Arc<Body>, size: u64) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                body,
+                block_size: READ_AHEAD_BLOCK_SIZE,
+                state: Mutex::new(State::default()),
+                work: Condvar::new(),
+                ready: Condvar::new(),
+            }),
+            workers: Vec::new(),
+            threads: READ_AHEAD_THREADS,
+            depth: READ_AHEAD_DEPTH,
+            size,
+            position: 0,
+            current: None,
+            run: 0,
+        }
+    }
+
+    /// Number of blocks read ahead of the current one (default 8).
+    pub fn depth(mut self, blocks: usize) -> Self {
+        self.depth = blocks;
+        self
+    }
+
+    /// Number of worker threads (default 2). `0` disables prefetching.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Size of a prefetched block in bytes (default 1 MiB). Ignored once
+    /// reading has started.
+    pub fn block_size(mut self, bytes: usize) -> Self {
+        if let Some(shared) = Arc::get_mut(&mut self.shared) {
+            shared.block_size = bytes.max(1);
+        }
+        self
+    }
+
+    /// The shared body.
+    pub fn body(&self) -> &Arc<Body> {
+        &self.shared.body
+    }
+
+    /// Total length of the evidence in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    fn block_size_u64(&self) -> u64 {
+        self.shared.block_size as u64
+    }
+
+    /// Start the workers on first use.
+    fn spawn_workers(&mut self) {
+        while self.workers.len() < self.threads {
+            let shared = self.shared.clone();
+            self.workers.push(std::thread::spawn(move || shared.work()));
+        }
+    }
+
+    /// Fetch `block`, from the prefetched blocks when possible, and queue the
+    /// following ones when the access pattern is sequential.
+    fn load(&mut self, block: u64) -> io::Result<Vec<u8>> {
+        let sequential = matches!(self.current, Some((previous, _)) if previous + 1 == block);
+        self.run = if sequential { self.run + 1 } else { 0 };
+        let prefetch = self.threads > 0 && self.depth > 0 && self.run > 0;
+        if prefetch {
+            self.spawn_workers();
+        }
+
+        let last_block = self.size.saturating_sub(1) / self.block_size_u64();
+        let shared = self.shared.clone();
+        let mut state = shared.lock();
+        if !sequential {
+            // Jumped elsewhere: whatever was prefetched is useless now.
+            state.queue.clear();
+            state.blocks.clear();
+        }
+        state.blocks.retain(|&b, _| b >= block);
+
+        if prefetch {
+            let end = block.saturating_add(self.depth as u64).min(last_block);
+            for next in block + 1..=end {
+                if let Entry::Vacant(slot) = state.blocks.entry(next) {
+                    slot.insert(Slot::Pending);
+                    state.queue.push_back(next);
+                }
+            }
+            shared.work.notify_all();
+        }
+
+        if !state.blocks.contains_key(&block) {
+            drop(state);
+            return shared.read_block(block);
+        }
+        loop {
+            if let Some(Slot::Ready(_)) = state.blocks.get(&block) {
+                let Some(Slot::Ready(result)) = state.blocks.remove(&block) else {
+                    unreachable!();
+                };
+                return result;
+            }
+            state = shared.ready.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+}
+
+impl Read for ReadAhead {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.position >= self.size {
+            return Ok(0);
+        }
+        let block = self.position / self.block_size_u64();
+        if !matches!(self.current, Some((current, _)) if current == block) {
+            let data = self.load(block)?;
+            self.current = Some((block, data));
+        }
+        let Some((_, data)) = &self.current else {
+            unreachable!();
+        };
+
+        let start = (self.position - block * self.block_size_u64()) as usize;
+        let n = data.len().saturating_sub(start).min(buf.len());
+        buf[..n].copy_from_slice(&data[start..start + n]);
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for ReadAhead {
+    /// Moves the reader's position. Prefetched blocks are kept until the next
+    /// read shows whether access is still sequential.
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let next = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.size.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Seek before start or overflow")
+        })?;
+        self.position = next;
+        Ok(self.position)
+    }
+}
+
+impl ReadAt for ReadAhead {
+    /// Positional reads bypass the prefetcher.
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.shared.body.read_at(offset, buf)
+    }
+}
+
+impl Drop for ReadAhead {
+    fn drop(&mut self) {
+        self.shared.lock().shutdown = true;
+        self.shared.work.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn prefetched_reads_match_the_body() {
+        let dir = TempDir::new("read_ahead");
+        let path = dir.join("read_ahead.raw");
+        let data: Vec<u8> = (0..300_000u32).map(|i| (i % 241) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let body = Body::try_new(path.to_string_lossy().to_string(), "raw").unwrap();
+        let mut reader = ReadAhead::new(body)
+            .unwrap()
+            .block_size(4096)
+            .depth(4)
+            .threads(3);
+        let mut all = Vec::new();
+        reader.read_to_end(&mut all).unwrap();
+        assert_eq!(all, data);
+
+        reader.seek(SeekFrom::Start(123_457)).unwrap();
+        let mut part = vec![0u8; 50_000];
+        reader.read_exact(&mut part).unwrap();
+        assert_eq!(part, &data[123_457..173_457]);
+    }
+}
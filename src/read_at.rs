@@ -0,0 +1,163 @@
+//! Positional reads, see [`ReadAt`].
+
+use std::fs::File;
+use std::io;
+
+/// Reads at an absolute offset without moving any cursor.
+///
+/// Unlike [`Read`](std::io::Read) + [`Seek`](std::io::Seek), `read_at` takes
+/// `&self`, so several logical readers can share one image (for instance one
+/// per partition) without fighting over a single position.
+pub trait ReadAt {
+    /// Read up to `buf.len()` bytes starting at `offset`, returning how many
+    /// were read (0 at the end of the image).
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize>;
+
+    /// Fill `buf` with the bytes starting at `offset`.
+    fn read_exact_at(&self, mut offset: u64, mut buf: &mut [u8]) -> io::Result<()> {
+        while !buf.is_empty() {
+            match self.read_at(offset, buf) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        format!("Unexpected end of image at offset {}", offset),
+                    ))
+                }
+                Ok(n) => {
+                    buf = &mut buf[n..];
+                    offset += n as u64;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Append up to `len` bytes starting at `offset` to `buf`, returning how
+    /// many were appended (0 at the end of the image).
+    ///
+    /// Stable Rust's stand-in for `Read::read_buf`: readers that can write
+    /// straight into the spare capacity of `buf` (files on Unix, RAW and EWF
+    /// images) skip the zeroing a `&mut [u8]` destination needs, which adds
+    /// up over multi-megabyte imaging blocks. The default zero-extends `buf`
+    /// and calls [`read_at`](ReadAt::read_at). On error `buf` is left as it
+    /// was.
+    fn read_at_append(&self, offset: u64, buf: &mut Vec<u8>, len: usize) -> io::Result<usize> {
+        append_zeroed(self, offset, buf, len)
+    }
+}
+
+/// [`ReadAt::read_at_append`] through a zero-initialised tail.
+pub(crate) fn append_zeroed<R: ReadAt + ?Sized>(
+    reader: &R,
+    offset: u64,
+    buf: &mut Vec<u8>,
+    len: usize,
+) -> io::Result<usize> {
+    let start = buf.len();
+    buf.resize(start + len, 0);
+    let result = reader.read_at(offset, &mut buf[start..]);
+    buf.truncate(start + *result.as_ref().unwrap_or(&0));
+    result
+}
+
+impl ReadAt for File {
+    #[cfg(unix)]
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        std::os::unix::fs::FileExt::read_at(self, buf, offset)
+    }
+
+    /// `seek_read` moves the handle's cursor, which the readers of this crate
+    /// never rely on.
+    #[cfg(windows)]
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        std::os::windows::fs::FileExt::seek_read(self, buf, offset)
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        use std::io::{Read, Seek, SeekFrom};
+        let mut file = self;
+        file.seek(SeekFrom::Start(offset))?;
+        file.read(buf)
+    }
+
+    /// `pread` into the spare capacity of `buf`.
+    #[cfg(unix)]
+    fn read_at_append(&self, offset: u64, buf: &mut Vec<u8>, len: usize) -> io::Result<usize> {
+        use std::os::unix::io::AsRawFd;
+        let offset = libc::off_t::try_from(offset)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "offset too large"))?;
+        let len = len.min(isize::MAX as usize);
+        buf.reserve(len);
+        let spare = &mut buf.spare_capacity_mut()[..len];
+        loop {
+            // SAFETY: `spare` is valid for `len` bytes of writes; pread only
+            // writes, and returns how many bytes it initialised.
+            let n =
+                unsafe { libc::pread(self.as_raw_fd(), spare.as_mut_ptr().cast(), len, offset) };
+            if n >= 0 {
+                // SAFETY: the first `n <= len` spare bytes were just written.
+                unsafe { buf.set_len(buf.len() + n as usize) };
+                return Ok(n as usize);
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use crate::Body;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn body_is_shared_across_threads() {
+        let dir = TempDir::new("read_at");
+        let path = dir.join("read_at.raw");
+        let data: Vec<u8> = (0..64 * 1024).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let body = Arc::new(Body::try_new(path.to_string_lossy().to_string(), "raw").unwrap());
+        let data = Arc::new(data);
+        let workers: Vec<_> = (0..4u64)
+            .map(|worker| {
+                let (body, data) = (body.clone(), data.clone());
+                thread::spawn(move || {
+                    for step in 0..64u64 {
+                        let offset = (worker * 16 + step) * 997 % (data.len() as u64 - 512);
+                        let mut buf = [0u8; 512];
+                        body.read_exact_at(offset, &mut buf).unwrap();
+                        assert_eq!(&buf[..], &data[offset as usize..offset as usize + 512]);
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn appends_after_the_existing_bytes() {
+        let dir = TempDir::new("append");
+        let path = dir.join("append.raw");
+        let data: Vec<u8> = (0..16 * 1024).map(|i| (i % 249) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let body = Body::try_new(path.to_string_lossy().to_string(), "raw").unwrap();
+        let mut buf = b"head".to_vec();
+        assert_eq!(body.read_at_append(12_000, &mut buf, 8192).unwrap(), 4384);
+        assert_eq!(&buf[..4], b"head");
+        assert_eq!(&buf[4..], &data[12_000..]);
+        assert_eq!(body.read_at_append(16 * 1024, &mut buf, 512).unwrap(), 0);
+        assert_eq!(buf.len(), 4 + 4384);
+    }
+}
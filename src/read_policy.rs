@@ -0,0 +1,125 @@
+//! What reads do when part of the evidence cannot be decoded, see
+//! [`BodyBuilder::read_policy`](crate::BodyBuilder::read_policy).
+
+use crate::metadata::SectorRange;
+use std::ops::Range;
+use std::sync::Mutex;
+
+/// How a [`Body`](crate::Body) reacts to a chunk or grain that fails to
+/// read, decompress or verify.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ReadPolicy {
+    /// Return the error to the caller.
+    #[default]
+    Strict,
+    /// Replace every unreadable sector with `fill` repeated (zeros when
+    /// empty), record it in [`Body::bad_ranges`](crate::Body::bad_ranges)
+    /// and keep reading.
+    BestEffort { fill: Vec<u8> },
+}
+
+/// Bookkeeping of a best-effort body, shared by its clones.
+#[derive(Debug)]
+pub(crate) struct Damage {
+    fill: Vec<u8>,
+    /// Length of the body, so that a damaged last sector is not padded past
+    /// the end.
+    size: u64,
+    sector_size: u64,
+    /// Bytes read again, or filled, together once a read fails: a whole
+    /// number of sectors.
+    granularity: u64,
+    /// Unreadable sectors found so far, sorted and merged.
+    ranges: Mutex<Vec<SectorRange>>,
+}
+
+impl Damage {
+    pub(crate) fn new(fill: Vec<u8>, size: u64, sector_size: u16) -> Self {
+        let sector_size = sector_size.max(1) as u64;
+        Self {
+            fill,
+            size,
+            sector_size,
+            granularity: sector_size,
+            ranges: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Read failed areas again, and fill them, `sectors` at a time instead
+    /// of one by one.
+    pub(crate) fn with_granularity(mut self, sectors: u32) -> Self {
+        self.granularity = self.sector_size * sectors.max(1) as u64;
+        self
+    }
+
+    pub(crate) fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub(crate) fn granularity(&self) -> u64 {
+        self.granularity
+    }
+
+    /// Overwrite `buf`, which starts at byte `offset` of the body, with the
+    /// fill pattern and record the sectors it covers.
+    pub(crate) fn fill(&self, offset: u64, buf: &mut [u8]) {
+        if self.fill.is_empty() {
+            buf.fill(0);
+        } else {
+            for (byte, pattern) in buf.iter_mut().zip(self.fill.iter().cycle()) {
+                *byte = *pattern;
+            }
+        }
+        self.mark(offset..offset + buf.len() as u64);
+    }
+
+    /// Record the sectors covering `bytes` as unreadable.
+    pub(crate) fn mark(&self, bytes: Range<u64>) {
+        let start = bytes.start / self.sector_size;
+        let end = bytes.end.div_ceil(self.sector_size);
+        self.record(SectorRange {
+            start,
+            count: end - start,
+        });
+    }
+
+    fn record(&self, range: SectorRange) {
+        let mut ranges = self.ranges.lock().unwrap();
+        let at = ranges.partition_point(|r| r.end() < range.start);
+        let mut merged = range;
+        while let Some(next) = ranges.get(at).filter(|r| r.start <= merged.end()) {
+            let end = next.end().max(merged.end());
+            merged.start = merged.start.min(next.start);
+            merged.count = end - merged.start;
+            ranges.remove(at);
+        }
+        ranges.insert(at, merged);
+    }
+
+    pub(crate) fn ranges(&self) -> Vec<SectorRange> {
+        self.ranges.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_adjacent_and_overlapping_ranges() {
+        let damage = Damage::new(b"BAD!".to_vec(), 1 << 20, 512);
+        let mut buf = [0u8; 6];
+        damage.fill(1024, &mut buf);
+        assert_eq!(&buf, b"BAD!BA");
+        damage.fill(4096, &mut [0u8; 512]);
+        damage.fill(1536, &mut [0u8; 1024]);
+        damage.fill(3584, &mut [0u8; 512]);
+        assert_eq!(
+            damage.ranges(),
+            [
+                SectorRange { start: 2, count: 3 },
+                SectorRange { start: 7, count: 2 },
+            ]
+        );
+    }
+}
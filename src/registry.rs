@@ -0,0 +1,129 @@
+//! Runtime registry of third-party image formats.
+//!
+//! A registered [`FormatPlugin`] can be opened by name through
+//! [`Body::try_new`](crate::Body::try_new) and takes part in `"auto"`
+//! detection, after the built-in formats and before the RAW fallback.
+//! Built-in format names always take precedence over registered ones.
+
+use crate::image::BodyImage;
+use std::sync::RwLock;
+
+/// Detector and opener of an image format provided outside this crate.
+#[derive(Clone, Copy, Debug)]
+pub struct FormatPlugin {
+    /// Name accepted as the `format` argument of the `Body` constructors.
+    pub name: &'static str,
+    /// Human-readable description, reported by `Body::format_description`.
+    pub description: &'static str,
+    /// Cheap check used by auto-detection (usually a magic number).
+    pub detect: fn(&str) -> bool,
+    /// Opens the image at the given path.
+    pub open: fn(&str) -> Result<Box<dyn BodyImage>, String>,
+}
+
+static PLUGINS: RwLock<Vec<FormatPlugin>> = RwLock::new(Vec::new());
+
+/// Register `plugin`, replacing any plugin previously registered under the
+/// same name.
+pub fn register_format(plugin: FormatPlugin) {
+    let mut plugins = PLUGINS.write().unwrap_or_else(|e| e.into_inner());
+    plugins.retain(|p| p.name != plugin.name);
+    plugins.push(plugin);
+}
+
+/// Remove the plugin registered under `name`, returning it.
+pub fn unregister_format(name: &str) -> Option<FormatPlugin> {
+    let mut plugins = PLUGINS.write().unwrap_or_else(|e| e.into_inner());
+    let index = plugins.iter().position(|p| p.name == name)?;
+    Some(plugins.remove(index))
+}
+
+/// Currently registered plugins, in registration order.
+pub fn registered_formats() -> Vec<FormatPlugin> {
+    PLUGINS.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+pub(crate) fn find_format(name: &str) -> Option<FormatPlugin> {
+    registered_formats().into_iter().find(|p| p.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use crate::Body;
+    use std::io::{self, Cursor, Read, Seek, SeekFrom};
+
+    const MAGIC: &[u8] = b"TESTIMG\0";
+
+    #[derive(Clone)]
+    struct TestImage(Cursor<Vec<u8>>);
+
+    impl Read for TestImage {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl Seek for TestImage {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.0.seek(pos)
+        }
+    }
+
+    impl BodyImage for TestImage {
+        fn print_info(&self) {}
+
+        fn get_sector_size(&self) -> u16 {
+            4096
+        }
+
+        fn size(&mut self) -> io::Result<u64> {
+            Ok(self.0.get_ref().len() as u64)
+        }
+
+        fn box_clone(&self) -> Box<dyn BodyImage> {
+            Box::new(self.clone())
+        }
+    }
+
+    fn detect(path: &str) -> bool {
+        std::fs::read(path).is_ok_and(|data| data.starts_with(MAGIC))
+    }
+
+    fn open(path: &str) -> Result<Box<dyn BodyImage>, String> {
+        let data = std::fs::read(path).map_err(|e| e.to_string())?;
+        Ok(Box::new(TestImage(Cursor::new(
+            data[MAGIC.len()..].to_vec(),
+        ))))
+    }
+
+    #[test]
+    fn registered_format_is_detected_and_opened() {
+        let dir = TempDir::new("plugin");
+        let path = dir.join("plugin.img");
+        std::fs::write(&path, [MAGIC, b"payload"].concat()).unwrap();
+        let path = path.to_string_lossy().to_string();
+
+        register_format(FormatPlugin {
+            name: "testimg",
+            description: "Test image",
+            detect,
+            open,
+        });
+
+        let mut body = Body::try_new(path.clone(), "auto").unwrap();
+        assert_eq!(body.format_name(), "testimg");
+        assert_eq!(body.get_sector_size(), 4096);
+        assert_eq!(body.size().unwrap(), 7);
+        let mut data = String::new();
+        body.read_to_string(&mut data).unwrap();
+        assert_eq!(data, "payload");
+
+        let body = Body::try_new(path.clone(), "testimg").unwrap();
+        assert_eq!(body.format_description(), "Test image");
+
+        assert!(unregister_format("testimg").is_some());
+        assert!(Body::try_new(path.clone(), "testimg").is_err());
+    }
+}
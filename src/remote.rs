@@ -0,0 +1,399 @@
+//! HTTP(S) backend, enabled by the `remote` feature.
+//!
+//! [`HttpBody`] reads an image stored on an evidence server or behind an
+//! object storage gateway through `Range` requests, one block at a time, and
+//! keeps the most recently used blocks in memory. Only the parts of the image
+//! actually read are transferred, which is what triage needs.
+//!
+//! It implements [`Read`], [`Seek`] and [`ReadAt`], so it can be handed to
+//! [`Body::from_reader`](crate::Body::from_reader), or opened directly with
+//! [`Body::from_url`](crate::Body::from_url):
+//!
+//! ```no_run
+//! # use exhume_body::Body;
+//! # fn main() -> Result<(), exhume_body::ExhumeBodyError> {
+//! let body = Body::from_url("https://evidence.example/case42/disk.E01", "auto")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::read_at::ReadAt;
+use crate::retry::RetryPolicy;
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Default size of a block fetched by one request (1 MiB).
+pub const HTTP_BLOCK_SIZE: u64 = 1024 * 1024;
+/// Default number of blocks kept in memory.
+pub const HTTP_CACHE_BLOCKS: usize = 64;
+
+/// Least recently used blocks of the remote file.
+struct BlockCache {
+    blocks: HashMap<u64, Arc<Vec<u8>>>,
+    /// Block numbers, least recently used first.
+    order: VecDeque<u64>,
+    capacity: usize,
+}
+
+impl BlockCache {
+    fn get(&mut self, block: u64) -> Option<Arc<Vec<u8>>> {
+        let data = self.blocks.get(&block)?.clone();
+        self.order.retain(|&b| b != block);
+        self.order.push_back(block);
+        Some(data)
+    }
+
+    fn insert(&mut self, block: u64, data: Arc<Vec<u8>>) {
+        if self.blocks.insert(block, data).is_none() {
+            self.order.push_back(block);
+        }
+        while self.order.len() > self.capacity {
+            if let Some(evicted) = self.order.pop_front() {
+                self.blocks.remove(&evicted);
+            }
+        }
+    }
+}
+
+/// State shared by the clones of an [`HttpBody`].
+struct Remote {
+    url: String,
+    agent: ureq::Agent,
+    headers: Vec<(String, String)>,
+    size: u64,
+    block_size: u64,
+    cache: Mutex<BlockCache>,
+    retry: RetryPolicy,
+}
+
+impl Remote {
+    /// GET `bytes=start-end` (inclusive). Error statuses come back as a
+    /// response, only transport failures are errors.
+    fn get_range(&self, start: u64, end: u64) -> io::Result<ureq::Response> {
+        let mut request = self
+            .agent
+            .get(&self.url)
+            .set("Range", &format!("bytes={}-{}", start, end));
+        for (name, value) in &self.headers {
+            request = request.set(name, value);
+        }
+        match request.call() {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => Ok(response),
+            Err(e) => Err(io::Error::other(format!("{}: {}", self.url, e))),
+        }
+    }
+
+    fn block(&self, block: u64) -> io::Result<Arc<Vec<u8>>> {
+        if let Some(data) = self.lock().get(block) {
+            return Ok(data);
+        }
+        let data = Arc::new(self.retry.run(|| self.fetch(block))?);
+        self.lock().insert(block, data.clone());
+        Ok(data)
+    }
+
+    /// Download block `block`.
+    fn fetch(&self, block: u64) -> io::Result<Vec<u8>> {
+        let start = block * self.block_size;
+        let end = (start + self.block_size).min(self.size);
+        let response = self.get_range(start, end - 1)?;
+        if response.status() != 206 {
+            return Err(status_error(&self.url, response.status()));
+        }
+        let mut data = Vec::with_capacity((end - start) as usize);
+        response
+            .into_reader()
+            .take(end - start)
+            .read_to_end(&mut data)?;
+        if data.len() as u64 != end - start {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "{} returned {} bytes for block {}, {} expected",
+                    self.url,
+                    data.len(),
+                    block,
+                    end - start
+                ),
+            ));
+        }
+        Ok(data)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BlockCache> {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn status_error(url: &str, status: u16) -> io::Error {
+    let kind = match status {
+        401 | 403 => io::ErrorKind::PermissionDenied,
+        404 => io::ErrorKind::NotFound,
+        200 => io::ErrorKind::Unsupported,
+        _ => io::ErrorKind::Other,
+    };
+    let message = if status == 200 {
+        format!("{} does not support range requests", url)
+    } else {
+        format!("{} answered HTTP {} to a range request", url, status)
+    };
+    io::Error::new(kind, message)
+}
+
+/// Total size from a `Content-Range: bytes 0-0/12345` (or `bytes */0`)
+/// header.
+fn content_range_total(value: &str) -> Option<u64> {
+    value.rsplit('/').next()?.trim().parse().ok()
+}
+
+/// Builder for an [`HttpBody`].
+#[derive(Clone, Debug)]
+pub struct HttpBodyBuilder {
+    url: String,
+    block_size: u64,
+    cache_blocks: usize,
+    headers: Vec<(String, String)>,
+    timeout: Option<Duration>,
+    retry: RetryPolicy,
+}
+
+impl HttpBodyBuilder {
+    /// Bytes fetched per request (default 1 MiB).
+    pub fn block_size(mut self, bytes: u64) -> Self {
+        self.block_size = bytes.max(512);
+        self
+    }
+
+    /// Blocks kept in memory (default 64).
+    pub fn cache_blocks(mut self, blocks: usize) -> Self {
+        self.cache_blocks = blocks.max(1);
+        self
+    }
+
+    /// Extra header sent with every request, e.g. `Authorization`.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Timeout of each request.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Retry failed requests under `policy`, see [`RetryPolicy`]; a missing
+    /// file or a refused authorization fails right away. Not retried by
+    /// default.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Connect and fetch the size of the remote file.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the server is unreachable, refuses the request or does not
+    /// support range requests.
+    pub fn open(self) -> io::Result<HttpBody> {
+        let mut agent = ureq::AgentBuilder::new();
+        if let Some(timeout) = self.timeout {
+            agent = agent.timeout(timeout);
+        }
+        let mut remote = Remote {
+            url: self.url,
+            agent: agent.build(),
+            headers: self.headers,
+            size: 0,
+            block_size: self.block_size,
+            cache: Mutex::new(BlockCache {
+                blocks: HashMap::new(),
+                order: VecDeque::new(),
+                capacity: self.cache_blocks,
+            }),
+            retry: self.retry,
+        };
+
+        let response = self.retry.run(|| remote.get_range(0, 0))?;
+        let total = response
+            .header("Content-Range")
+            .and_then(content_range_total);
+        remote.size = match (response.status(), total) {
+            (206, Some(size)) => size,
+            // An empty file cannot satisfy any range.
+            (416, size) => size.unwrap_or(0),
+            (206, None) => {
+                return Err(io::Error::other(format!(
+                    "{} sent no usable Content-Range",
+                    remote.url
+                )))
+            }
+            (status, _) => return Err(status_error(&remote.url, status)),
+        };
+
+        Ok(HttpBody {
+            remote: Arc::new(remote),
+            position: 0,
+        })
+    }
+}
+
+/// A remote image read through HTTP(S) range requests.
+///
+/// Clones share the connection pool and block cache, and get their own
+/// position.
+pub struct HttpBody {
+    remote: Arc<Remote>,
+    position: u64,
+}
+
+impl HttpBody {
+    /// Open `url` with the default block size and cache.
+    pub fn open(url: impl Into<String>) -> io::Result<Self> {
+        Self::builder(url).open()
+    }
+
+    /// Start an [`HttpBodyBuilder`] for `url`.
+    pub fn builder(url: impl Into<String>) -> HttpBodyBuilder {
+        HttpBodyBuilder {
+            url: url.into(),
+            block_size: HTTP_BLOCK_SIZE,
+            cache_blocks: HTTP_CACHE_BLOCKS,
+            headers: Vec::new(),
+            timeout: None,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// The remote file's URL.
+    pub fn url(&self) -> &str {
+        &self.remote.url
+    }
+
+    /// Size of the remote file in bytes.
+    pub fn size(&self) -> u64 {
+        self.remote.size
+    }
+}
+
+impl Clone for HttpBody {
+    fn clone(&self) -> Self {
+        Self {
+            remote: self.remote.clone(),
+            position: self.position,
+        }
+    }
+}
+
+impl ReadAt for HttpBody {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let remote = &self.remote;
+        let mut done = 0;
+        while done < buf.len() {
+            let position = offset + done as u64;
+            if position >= remote.size {
+                break;
+            }
+            let block = position / remote.block_size;
+            let data = remote.block(block)?;
+            let start = (position - block * remote.block_size) as usize;
+            let n = (data.len() - start).min(buf.len() - done);
+            buf[done..done + n].copy_from_slice(&data[start..start + n]);
+            done += n;
+        }
+        Ok(done)
+    }
+}
+
+impl Read for HttpBody {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.read_at(self.position, buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for HttpBody {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let next = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.remote.size.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Seek before start or overflow")
+        })?;
+        self.position = next;
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// Serve `data` over HTTP/1.1, honouring single `Range` requests.
+    fn serve(data: Vec<u8>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut range = None;
+                for line in BufReader::new(&stream).lines() {
+                    let line = line.unwrap();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(value) = line.strip_prefix("Range: bytes=") {
+                        let (start, end) = value.split_once('-').unwrap();
+                        range = Some((
+                            start.parse::<usize>().unwrap(),
+                            end.parse::<usize>().unwrap(),
+                        ));
+                    }
+                }
+                let (start, end) = range.unwrap();
+                let end = end.min(data.len() - 1);
+                write!(
+                    stream,
+                    "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}-{}/{}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    start,
+                    end,
+                    data.len(),
+                    end + 1 - start
+                )
+                .unwrap();
+                stream.write_all(&data[start..=end]).unwrap();
+            }
+        });
+        format!("http://{}/disk.raw", address)
+    }
+
+    #[test]
+    fn reads_through_range_requests() {
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i % 251) as u8).collect();
+        let url = serve(data.clone());
+
+        let mut remote = HttpBody::builder(url)
+            .block_size(4096)
+            .cache_blocks(2)
+            .open()
+            .unwrap();
+        assert_eq!(remote.size(), data.len() as u64);
+
+        let mut buf = vec![0u8; 9000];
+        remote.read_exact_at(3000, &mut buf).unwrap();
+        assert_eq!(buf, &data[3000..12_000]);
+
+        remote.seek(SeekFrom::End(-100)).unwrap();
+        let mut tail = Vec::new();
+        remote.read_to_end(&mut tail).unwrap();
+        assert_eq!(tail, &data[19_900..]);
+    }
+}
@@ -0,0 +1,207 @@
+//! Retries of failed reads, see [`RetryPolicy`].
+//!
+//! A drive with weak sectors often returns a sector on the second or third
+//! attempt, and a network mount or evidence server drops the odd request
+//! under load. Rather than failing a two-hour hash on the first hiccup, the
+//! RAW, device and HTTP backends read under a policy that tries again a few
+//! times, waiting longer each time, and only then surfaces the error.
+//!
+//! ```no_run
+//! # use exhume_body::{Body, RetryPolicy};
+//! # use std::time::Duration;
+//! # fn main() -> Result<(), exhume_body::ExhumeBodyError> {
+//! let body = Body::builder("/dev/sdb")
+//!     .retry_policy(RetryPolicy::new(5).backoff(Duration::from_millis(50), Duration::from_secs(2)))
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::read_at::ReadAt;
+use log::warn;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::time::Duration;
+
+/// How many times a failed read is attempted, and how long to wait in
+/// between: `initial_backoff` after the first failure, doubled after every
+/// other one up to `max_backoff`.
+///
+/// Only transient errors are retried: a missing file, a refused permission,
+/// invalid data or a short read fail right away.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts in total, the first one included; 1 never retries.
+    pub attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// A single attempt.
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl RetryPolicy {
+    /// Try reads `attempts` times, waiting 100 ms after the first failure
+    /// and up to 5 s between the last ones.
+    pub fn new(attempts: u32) -> Self {
+        Self {
+            attempts: attempts.max(1),
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+
+    /// Wait `initial` after the first failure, doubling up to `max`.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Run `read` until it succeeds, fails with an error that is not
+    /// transient, or the attempts are exhausted, in which case the last
+    /// error is returned.
+    pub fn run<T>(&self, mut read: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+        let mut backoff = self.initial_backoff;
+        let mut attempt = 1;
+        loop {
+            match read() {
+                Err(e) if attempt < self.attempts && is_transient(&e) => {
+                    warn!(
+                        "Read failed ({}), retrying in {:?} ({}/{})",
+                        e, backoff, attempt, self.attempts
+                    );
+                    std::thread::sleep(backoff);
+                    backoff = (backoff * 2).min(self.max_backoff);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Whether a read failing with `e` may succeed when tried again.
+fn is_transient(e: &io::Error) -> bool {
+    !matches!(
+        e.kind(),
+        io::ErrorKind::NotFound
+            | io::ErrorKind::PermissionDenied
+            | io::ErrorKind::InvalidInput
+            | io::ErrorKind::InvalidData
+            | io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::Unsupported
+            | io::ErrorKind::OutOfMemory
+    )
+}
+
+/// A reader whose failed reads are retried under a [`RetryPolicy`], for
+/// transports handed to [`Body::from_reader`](crate::Body::from_reader).
+///
+/// Implements [`Read`], [`Seek`] and, over a [`ReadAt`] reader, [`ReadAt`].
+/// A failed [`Read::read`] seeks back to where it started before trying
+/// again, so that a partial read is not skipped.
+#[derive(Clone, Debug)]
+pub struct Retrying<R> {
+    inner: R,
+    policy: RetryPolicy,
+}
+
+impl<R> Retrying<R> {
+    pub fn new(inner: R, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read + Seek> Read for Retrying<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let position = self.inner.stream_position()?;
+        let mut first = true;
+        let inner = &mut self.inner;
+        self.policy.run(|| {
+            if !std::mem::take(&mut first) {
+                inner.seek(SeekFrom::Start(position))?;
+            }
+            inner.read(buf)
+        })
+    }
+}
+
+impl<R: Seek> Seek for Retrying<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+impl<R: ReadAt> ReadAt for Retrying<R> {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.policy.run(|| self.inner.read_at(offset, buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Fails the first `failures` reads with `kind`.
+    struct Flaky {
+        inner: Cursor<Vec<u8>>,
+        failures: u32,
+        kind: io::ErrorKind,
+    }
+
+    impl Read for Flaky {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                // Fail halfway through, as a device would.
+                self.inner.seek(SeekFrom::Current(3))?;
+                return Err(io::Error::new(self.kind, "flaky read"));
+            }
+            self.inner.read(buf)
+        }
+    }
+
+    impl Seek for Flaky {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn transient_errors_are_retried() {
+        let flaky = |failures, kind| Flaky {
+            inner: Cursor::new((0..64u8).collect()),
+            failures,
+            kind,
+        };
+        let policy = RetryPolicy::new(3).backoff(Duration::ZERO, Duration::ZERO);
+
+        let mut reader = Retrying::new(flaky(2, io::ErrorKind::Other), policy);
+        let mut buf = [0u8; 8];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [0, 1, 2, 3, 4, 5, 6, 7]);
+
+        let mut reader = Retrying::new(flaky(3, io::ErrorKind::TimedOut), policy);
+        assert_eq!(
+            reader.read(&mut buf).unwrap_err().kind(),
+            io::ErrorKind::TimedOut
+        );
+
+        let mut reader = Retrying::new(flaky(1, io::ErrorKind::PermissionDenied), policy);
+        assert!(reader.read(&mut buf).is_err());
+        assert_eq!(reader.read(&mut buf).unwrap(), 8);
+    }
+}
@@ -0,0 +1,194 @@
+//! Block iteration over a [`Body`], see [`Body::sectors`] and
+//! [`Body::windows`].
+
+use crate::Body;
+use std::io::{self, Seek, SeekFrom};
+
+/// Iterator over fixed-size blocks of a body, yielding `chunk` sectors per
+/// item (the last block may be shorter).
+///
+/// Created by [`Body::sectors`] and [`Body::sector_range`]. Iteration stops
+/// after the first error.
+pub struct Sectors<'a> {
+    body: &'a mut Body,
+    /// Next byte offset to read.
+    offset: u64,
+    /// End of the iterated range in bytes, clamped to the body size once
+    /// known.
+    end: u64,
+    block_size: usize,
+    started: bool,
+    done: bool,
+}
+
+impl<'a> Sectors<'a> {
+    pub(crate) fn new(
+        body: &'a mut Body,
+        first_sector: u64,
+        end_sector: u64,
+        chunk: usize,
+    ) -> Self {
+        let sector_size = body.get_sector_size() as u64;
+        Self {
+            offset: first_sector.saturating_mul(sector_size),
+            end: end_sector.saturating_mul(sector_size),
+            block_size: (chunk.max(1) as u64 * sector_size) as usize,
+            started: false,
+            done: false,
+            body,
+        }
+    }
+
+    fn start(&mut self) -> io::Result<()> {
+        self.end = self.end.min(self.body.size()?);
+        self.body.seek(SeekFrom::Start(self.offset))?;
+        Ok(())
+    }
+
+    fn read_block(&mut self) -> io::Result<Vec<u8>> {
+        let want = (self.end - self.offset).min(self.block_size as u64) as usize;
+        let mut block = Vec::with_capacity(want);
+        while block.len() < want {
+            let filled = block.len();
+            if self.body.read_append(&mut block, want - filled)? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("Image ended at offset {}", self.offset + filled as u64),
+                ));
+            }
+        }
+        self.offset += want as u64;
+        Ok(block)
+    }
+
+    /// Yield each block preceded by the last `overlap` bytes of the data
+    /// before it, see [`Windows`].
+    pub fn with_overlap(self, overlap: usize) -> Windows<'a> {
+        Windows {
+            sectors: self,
+            overlap,
+            carry: Vec::new(),
+        }
+    }
+}
+
+impl Iterator for Sectors<'_> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if !self.started {
+            self.started = true;
+            if let Err(e) = self.start() {
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+        if self.offset >= self.end {
+            self.done = true;
+            return None;
+        }
+        let block = self.read_block();
+        self.done = block.is_err();
+        Some(block)
+    }
+}
+
+/// One item of [`Windows`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Window {
+    /// Offset of `data[0]` in the body.
+    pub offset: u64,
+    /// Leading bytes of `data` already yielded at the end of the previous
+    /// window (0 for the first one).
+    pub overlap: usize,
+    pub data: Vec<u8>,
+}
+
+/// Iterator over overlapping windows of a body, for carving: each window
+/// holds a block of `chunk` sectors preceded by the last `overlap` bytes of
+/// the window before it, so that a signature of up to `overlap + 1` bytes
+/// spanning a block boundary is found whole in one window.
+///
+/// A match ending within the first [`Window::overlap`] bytes was already
+/// found in the previous window and can be skipped.
+///
+/// Created by [`Body::windows`] and [`Sectors::with_overlap`]. Iteration
+/// stops after the first error.
+pub struct Windows<'a> {
+    sectors: Sectors<'a>,
+    overlap: usize,
+    /// Tail of the previous window.
+    carry: Vec<u8>,
+}
+
+impl Iterator for Windows<'_> {
+    type Item = io::Result<Window>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let block_offset = self.sectors.offset;
+        let block = match self.sectors.next()? {
+            Ok(block) => block,
+            Err(e) => return Some(Err(e)),
+        };
+        let overlap = self.carry.len();
+        let mut data = std::mem::take(&mut self.carry);
+        data.extend_from_slice(&block);
+        self.carry = data[data.len().saturating_sub(self.overlap)..].to_vec();
+        Some(Ok(Window {
+            offset: block_offset - overlap as u64,
+            overlap,
+            data,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::TempDir;
+    use crate::{Body, BodySlice};
+    use std::io::{Read, Seek, SeekFrom};
+
+    #[test]
+    fn reads_sectors_in_native_units() {
+        let dir = TempDir::new("sectors");
+        let path = dir.join("sectors.raw");
+        let data: Vec<u8> = (0..16 * 512u32).map(|i| (i % 253) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let mut body = Body::try_new(path.to_string_lossy().to_string(), "raw").unwrap();
+        body.seek(SeekFrom::Start(100)).unwrap();
+        assert_eq!(body.read_sectors(3, 2).unwrap(), &data[3 * 512..5 * 512]);
+        assert_eq!(body.read_sectors(16, 0).unwrap(), b"");
+        assert_eq!(body.stream_position().unwrap(), 100);
+        let err = body.read_sectors(15, 2).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        assert!(body.read_sectors(u64::MAX, 1).is_err());
+
+        let mut slice = BodySlice::from_sectors(&body, 4, 3).unwrap();
+        let mut buf = Vec::new();
+        slice.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, &data[4 * 512..7 * 512]);
+        assert!(BodySlice::from_sectors(&body, u64::MAX / 2, 1).is_err());
+
+        let windows: Vec<_> = body
+            .sector_range(2..9, 3)
+            .with_overlap(100)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let spans: Vec<_> = windows
+            .iter()
+            .map(|w| (w.offset, w.overlap, w.data.len()))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![(1024, 0, 1536), (2460, 100, 1636), (3996, 100, 612)]
+        );
+        for window in &windows {
+            let start = window.offset as usize;
+            assert_eq!(window.data, &data[start..start + window.data.len()]);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! Byte sources backing an image, see [`Source`].
+
+use crate::device;
+use crate::integrity::open_read_only;
+use crate::read_at::ReadAt;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Any seekable stream an image can be parsed from.
+pub trait ReadSeek: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+/// Both variants are shared between clones, so cloning a source costs no
+/// file descriptor; the cursor is shared, as between `try_clone`d files.
+#[derive(Clone)]
+enum Inner {
+    File(Arc<File>),
+    Stream(Arc<Mutex<Box<dyn ReadSeek>>>),
+}
+
+/// The bytes of one image file: an open [`File`], or any [`Read`] + [`Seek`]
+/// stream (in-memory buffer, archive member, network transport…).
+///
+/// `Source` mirrors the parts of the [`File`] API the readers rely on:
+/// `Read` and `Seek` (also through `&Source`), positional reads through
+/// [`ReadAt`] and [`try_clone`](Source::try_clone). Clones share the open
+/// file or stream.
+#[derive(Clone)]
+pub struct Source(Inner);
+
+impl Source {
+    /// Open the file at `path`.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        open_read_only(path).map(Self::from)
+    }
+
+    /// Wrap a stream. Positional reads lock it, seek and read.
+    pub fn from_reader(reader: impl Read + Seek + Send + 'static) -> Self {
+        Self::from_boxed(Box::new(reader))
+    }
+
+    /// Wrap an already boxed stream.
+    pub fn from_boxed(reader: Box<dyn ReadSeek>) -> Self {
+        Self(Inner::Stream(Arc::new(Mutex::new(reader))))
+    }
+
+    /// Another handle on the same source. Like [`File::try_clone`], the
+    /// cursor is shared. Same as [`Clone::clone`], which never fails.
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Ok(self.clone())
+    }
+
+    /// Length of the source in bytes. Block devices, whose metadata reports
+    /// no length, are asked for their size.
+    pub fn len(&self) -> io::Result<u64> {
+        match &self.0 {
+            Inner::File(file) => {
+                let metadata = file.metadata()?;
+                if device::is_block_device(&metadata) {
+                    return device::block_device_size(file);
+                }
+                Ok(metadata.len())
+            }
+            Inner::Stream(_) => {
+                let mut stream = self.stream()?;
+                let position = stream.stream_position()?;
+                let len = stream.seek(SeekFrom::End(0))?;
+                stream.seek(SeekFrom::Start(position))?;
+                Ok(len)
+            }
+        }
+    }
+
+    /// Whether the source holds no byte.
+    pub fn is_empty(&self) -> io::Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// The underlying file, `None` for streams.
+    pub fn as_file(&self) -> Option<&File> {
+        match &self.0 {
+            Inner::File(file) => Some(&**file),
+            Inner::Stream(_) => None,
+        }
+    }
+
+    fn stream(&self) -> io::Result<MutexGuard<'_, Box<dyn ReadSeek>>> {
+        match &self.0 {
+            Inner::Stream(stream) => Ok(stream.lock().unwrap_or_else(|e| e.into_inner())),
+            Inner::File(_) => Err(io::Error::other("not a stream source")),
+        }
+    }
+}
+
+impl From<File> for Source {
+    fn from(file: File) -> Self {
+        Self(Inner::File(Arc::new(file)))
+    }
+}
+
+impl Read for &Source {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &self.0 {
+            Inner::File(file) => Read::read(&mut &**file, buf),
+            Inner::Stream(_) => self.stream()?.read(buf),
+        }
+    }
+}
+
+impl Seek for &Source {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match &self.0 {
+            Inner::File(file) => Seek::seek(&mut &**file, pos),
+            Inner::Stream(_) => self.stream()?.seek(pos),
+        }
+    }
+}
+
+impl Read for Source {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (&*self).read(buf)
+    }
+}
+
+impl Seek for Source {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        (&*self).seek(pos)
+    }
+}
+
+impl ReadAt for Source {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        match &self.0 {
+            Inner::File(file) => file.read_at(offset, buf),
+            Inner::Stream(_) => {
+                let mut stream = self.stream()?;
+                stream.seek(SeekFrom::Start(offset))?;
+                stream.read(buf)
+            }
+        }
+    }
+
+    fn read_at_append(&self, offset: u64, buf: &mut Vec<u8>, len: usize) -> io::Result<usize> {
+        match &self.0 {
+            Inner::File(file) => file.read_at_append(offset, buf, len),
+            Inner::Stream(_) => crate::read_at::append_zeroed(self, offset, buf, len),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Source;
+    use crate::testing::TempDir;
+    use crate::{Body, BodyFormat, EwfWriter};
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+
+    #[test]
+    fn opens_images_held_in_memory() {
+        let dir = TempDir::new("source");
+        let data: Vec<u8> = (0..128 * 1024u32).map(|i| (i % 233) as u8).collect();
+        EwfWriter::new()
+            .write(&mut Cursor::new(&data), dir.join("mem"))
+            .unwrap();
+        let segment = std::fs::read(dir.join("mem.E01")).unwrap();
+        drop(dir);
+
+        let mut body = Body::from_reader(Box::new(Cursor::new(segment)), "auto").unwrap();
+        assert!(matches!(body.format, BodyFormat::EWF { .. }));
+        let mut back = Vec::new();
+        body.read_to_end(&mut back).unwrap();
+        assert_eq!(back, data);
+
+        let mut body = Body::from_reader(Box::new(Cursor::new(data.clone())), "auto").unwrap();
+        assert!(matches!(body.format, BodyFormat::RAW { .. }));
+        assert_eq!(body.size().unwrap(), data.len() as u64);
+        assert!(Body::from_reader(Box::new(Cursor::new(data)), "qcow").is_err());
+    }
+
+    #[test]
+    fn clones_share_the_open_file() {
+        let dir = TempDir::new("source_clone");
+        let path = dir.join("source.bin");
+        std::fs::write(&path, [7u8; 64]).unwrap();
+        let source = Source::open(&path).unwrap();
+        let clone = source.clone();
+        std::fs::remove_file(&path).unwrap();
+
+        (&source).seek(SeekFrom::Start(10)).unwrap();
+        assert_eq!((&clone).stream_position().unwrap(), 10);
+        assert!(std::ptr::eq(
+            source.as_file().unwrap(),
+            clone.as_file().unwrap()
+        ));
+    }
+}
@@ -0,0 +1,133 @@
+//! I/O statistics of a body, see [`Body::stats`](crate::Body::stats).
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// What the reads of a [`Body`](crate::Body) (and its clones) cost since it
+/// was opened or since the last [`Body::reset_stats`](crate::Body::reset_stats).
+///
+/// Backends without chunks, caches or segments leave the matching counters
+/// at zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct IoStats {
+    /// Bytes returned to the caller.
+    pub bytes_read: u64,
+    /// EWF chunks, VMDK grains and AFF4 chunks decompressed.
+    pub chunks_decompressed: u64,
+    /// Chunks (EWF, AFF4), grain tables and compressed grains (VMDK) found
+    /// already decoded.
+    pub cache_hits: u64,
+    /// Chunks or grain tables that had to be read from the evidence.
+    pub cache_misses: u64,
+    /// Reads that carried on into the next segment file (EWF, split RAW).
+    pub segment_switches: u64,
+    /// Time spent decompressing, summed over every thread.
+    pub decompression_time: Duration,
+}
+
+/// The atomic counters behind [`IoStats`], shared by a backend and its
+/// clones.
+#[derive(Debug, Default)]
+pub(crate) struct IoCounters {
+    bytes_read: AtomicU64,
+    chunks_decompressed: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    segment_switches: AtomicU64,
+    decompression_nanos: AtomicU64,
+}
+
+impl IoCounters {
+    pub(crate) fn read(&self, bytes: usize) {
+        self.bytes_read.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn decompressed(&self, elapsed: Duration) {
+        self.chunks_decompressed.fetch_add(1, Ordering::Relaxed);
+        self.decompression_nanos
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn cache_hit(&self) {
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn cache_miss(&self) {
+        self.cache_misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn segment_switch(&self) {
+        self.segment_switches.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Add the counters to `stats`.
+    pub(crate) fn add_to(&self, stats: &mut IoStats) {
+        stats.bytes_read += self.bytes_read.load(Ordering::Relaxed);
+        stats.chunks_decompressed += self.chunks_decompressed.load(Ordering::Relaxed);
+        stats.cache_hits += self.cache_hits.load(Ordering::Relaxed);
+        stats.cache_misses += self.cache_misses.load(Ordering::Relaxed);
+        stats.segment_switches += self.segment_switches.load(Ordering::Relaxed);
+        stats.decompression_time +=
+            Duration::from_nanos(self.decompression_nanos.load(Ordering::Relaxed));
+    }
+
+    pub(crate) fn reset(&self) {
+        for counter in [
+            &self.bytes_read,
+            &self.chunks_decompressed,
+            &self.cache_hits,
+            &self.cache_misses,
+            &self.segment_switches,
+            &self.decompression_nanos,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::TempDir;
+    use crate::{Body, EwfWriter};
+    use std::io::{self, Read};
+
+    #[test]
+    fn counts_ewf_chunks_and_segments() {
+        let dir = TempDir::new("stats");
+        // Half noise, half zeros: every chunk is stored compressed, two or
+        // three to a segment.
+        let mut seed = 0x2545_f491u32;
+        let data: Vec<u8> = (0..8 * 32768u32)
+            .map(|i| {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                if i % 32768 < 16384 {
+                    seed as u8
+                } else {
+                    0
+                }
+            })
+            .collect();
+        EwfWriter::new()
+            .segment_size(40 * 1024)
+            .write(&mut io::Cursor::new(&data), dir.join("stats"))
+            .unwrap();
+
+        let mut body =
+            Body::try_new(dir.join("stats.E01").to_string_lossy().into(), "ewf").unwrap();
+        body.reset_stats();
+        let mut read = Vec::new();
+        body.read_to_end(&mut read).unwrap();
+        assert_eq!(read, data);
+        let stats = body.stats();
+        assert_eq!(stats.bytes_read, data.len() as u64);
+        assert_eq!(stats.chunks_decompressed, 8);
+        assert!(stats.segment_switches > 0);
+        assert_eq!(stats.cache_misses, 8);
+
+        body.reset_stats();
+        assert_eq!(body.stats(), Default::default());
+    }
+}
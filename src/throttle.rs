@@ -0,0 +1,205 @@
+//! Bandwidth limits on the evidence, see [`RateLimit`].
+//!
+//! A triage job reading an image off the lab's evidence server (NAS share,
+//! [`HttpBody`](crate::HttpBody), any [`Read`] + [`Seek`] transport) can
+//! saturate it for everybody else. [`Throttled`] caps what one reader pulls
+//! from its source; [`BodyBuilder::rate_limit`](crate::BodyBuilder::rate_limit)
+//! does the same for every file a body opens.
+//!
+//! ```no_run
+//! # use exhume_body::{Body, RateLimit, Throttled};
+//! # use std::fs::File;
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // 20 MiB/s, shared by everything opened with this limit.
+//! let limit = RateLimit::new(20 * 1024 * 1024);
+//! let file = File::open("/mnt/evidence/case42/disk.raw")?;
+//! let body = Body::from_reader(Box::new(Throttled::new(file, limit)), "auto")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::read_at::ReadAt;
+use crate::source::Source;
+use crate::vfs::FileSystem;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Token bucket: `tokens` bytes may be read right away, and the bucket
+/// refills at `rate` bytes per second up to one second worth of reads.
+/// Reads past the budget leave it negative, so that the next readers wait
+/// for the debt to be paid back.
+#[derive(Debug)]
+struct Bucket {
+    rate: f64,
+    tokens: f64,
+    refilled: Instant,
+}
+
+/// A reading budget in bytes per second. Clones share the budget, so one
+/// limit can cap several readers (the segments of an image, worker
+/// threads) together.
+#[derive(Clone, Debug)]
+pub struct RateLimit(Arc<Mutex<Bucket>>);
+
+impl RateLimit {
+    /// Allow `bytes_per_second` on average, with bursts of up to one second
+    /// worth of data.
+    pub fn new(bytes_per_second: u64) -> Self {
+        let rate = bytes_per_second.max(1) as f64;
+        Self(Arc::new(Mutex::new(Bucket {
+            rate,
+            tokens: rate,
+            refilled: Instant::now(),
+        })))
+    }
+
+    /// The budget, in bytes per second.
+    pub fn bytes_per_second(&self) -> u64 {
+        self.bucket().rate as u64
+    }
+
+    fn bucket(&self) -> std::sync::MutexGuard<'_, Bucket> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Charge `bytes` to the budget, sleeping while it is overdrawn.
+    pub fn consume(&self, bytes: u64) {
+        let wait = {
+            let mut bucket = self.bucket();
+            let now = Instant::now();
+            let refill = now.duration_since(bucket.refilled).as_secs_f64() * bucket.rate;
+            bucket.tokens = (bucket.tokens + refill).min(bucket.rate);
+            bucket.refilled = now;
+            bucket.tokens -= bytes as f64;
+            (bucket.tokens < 0.0).then(|| Duration::from_secs_f64(-bucket.tokens / bucket.rate))
+        };
+        if let Some(wait) = wait {
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+/// A reader whose reads are charged to a [`RateLimit`].
+///
+/// Implements [`Read`], [`Seek`] and, over a [`ReadAt`] reader, [`ReadAt`].
+/// Clones share the limit.
+#[derive(Clone, Debug)]
+pub struct Throttled<R> {
+    inner: R,
+    limit: RateLimit,
+}
+
+impl<R> Throttled<R> {
+    pub fn new(inner: R, limit: RateLimit) -> Self {
+        Self { inner, limit }
+    }
+
+    /// The limit the reads are charged to.
+    pub fn limit(&self) -> &RateLimit {
+        &self.limit
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for Throttled<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.limit.consume(n as u64);
+        Ok(n)
+    }
+}
+
+impl<R: Seek> Seek for Throttled<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+impl<R: ReadAt> ReadAt for Throttled<R> {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read_at(offset, buf)?;
+        self.limit.consume(n as u64);
+        Ok(n)
+    }
+}
+
+/// A [`FileSystem`] whose files are all read through one [`RateLimit`],
+/// see [`BodyBuilder::rate_limit`](crate::BodyBuilder::rate_limit).
+pub struct ThrottledFileSystem {
+    inner: Arc<dyn FileSystem>,
+    limit: RateLimit,
+}
+
+impl ThrottledFileSystem {
+    pub fn new(inner: Arc<dyn FileSystem>, limit: RateLimit) -> Self {
+        Self { inner, limit }
+    }
+}
+
+impl FileSystem for ThrottledFileSystem {
+    fn open(&self, path: &Path) -> io::Result<Source> {
+        let source = self.inner.open(path)?;
+        Ok(Source::from_reader(Throttled::new(
+            source,
+            self.limit.clone(),
+        )))
+    }
+
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        self.inner.read_dir(dir)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.inner.is_file(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        self.inner.canonicalize(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use std::io::Cursor;
+
+    #[test]
+    fn reads_are_held_to_the_budget() {
+        let limit = RateLimit::new(1024 * 1024);
+        let mut reader = Throttled::new(Cursor::new(vec![7u8; 1280 * 1024]), limit.clone());
+        let started = Instant::now();
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).unwrap();
+        assert_eq!(data.len(), 1280 * 1024);
+        // The first MiB is the initial burst, the rest waits for the refill.
+        assert!(started.elapsed() >= Duration::from_millis(200));
+        assert_eq!(reader.limit().bytes_per_second(), 1024 * 1024);
+
+        let dir = TempDir::new("throttle");
+        let path = dir.join("throttle.raw");
+        std::fs::write(&path, &data[..4096]).unwrap();
+        let mut body = crate::Body::builder(path.to_string_lossy())
+            .rate_limit(limit)
+            .build()
+            .unwrap();
+        assert_eq!(body.read_sectors(1, 2).unwrap(), &data[512..1536]);
+
+        // Formats opened through the file system the limit wraps, not only
+        // those a custom one supports.
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        std::io::Write::write_all(&mut gzip, &data[..4096]).unwrap();
+        std::fs::write(&path, gzip.finish().unwrap()).unwrap();
+        let mut body = crate::Body::builder(path.to_string_lossy())
+            .rate_limit(RateLimit::new(1024 * 1024))
+            .build()
+            .unwrap();
+        assert_eq!(body.format_name(), "gzip");
+        assert_eq!(body.read_sectors(1, 2).unwrap(), &data[512..1536]);
+    }
+}
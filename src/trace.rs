@@ -0,0 +1,44 @@
+//! `tracing` instrumentation of the IO path, compiled in with the `tracing`
+//! feature.
+//!
+//! Spans are emitted at trace level under the `exhume_body` target:
+//!
+//! * `body.read` / `body.read_at`: every read of a [`Body`](crate::Body),
+//!   with its offset and length;
+//! * `raw.segment`: reads from one file of a split RAW image;
+//! * `ewf.chunk` and `ewf.inflate`: chunk loads and their decompression,
+//!   with the stored and decompressed sizes, plus an `ewf.segment` event when
+//!   a sequential read moves to the next segment file;
+//! * `vmdk.grain_table` and `vmdk.inflate_grain`: grain table loads and
+//!   compressed grain reads;
+//! * `aff4.chunk` and `aff4.decompress`: bevy chunk loads.
+//!
+//! Without the feature the macros expand to nothing and their fields are
+//! not evaluated.
+
+/// Placeholder for an entered span when tracing is compiled out.
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct NoSpan;
+
+/// Enter a trace-level span for the rest of the enclosing scope:
+/// `let _span = io_span!("ewf.chunk", segment, chunk = number);`.
+macro_rules! io_span {
+    ($name:literal $($fields:tt)*) => {{
+        #[cfg(feature = "tracing")]
+        let span = tracing::trace_span!(target: "exhume_body", $name $($fields)*).entered();
+        #[cfg(not(feature = "tracing"))]
+        let span = $crate::trace::NoSpan;
+        span
+    }};
+}
+
+/// Emit a trace-level event, e.g. on a segment switch.
+macro_rules! io_event {
+    ($name:literal $($fields:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(name: $name, target: "exhume_body" $($fields)*);
+    };
+}
+
+pub(crate) use io_event;
+pub(crate) use io_span;
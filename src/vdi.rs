@@ -0,0 +1,528 @@
+//! **VDI (VirtualBox Disk Image) reader**
+//!
+//! This module provides a pure-Rust reader for **fixed** and **dynamically
+//! allocated** VirtualBox images (`.vdi` files).
+//!
+//! # On-disk layout
+//!
+//! | Offset  | Size  | Description                                          |
+//! |---------|-------|------------------------------------------------------|
+//! | `0x000` | 64    | Text banner (`<<< Oracle VM VirtualBox Disk Image >>>`) |
+//! | `0x040` | 4     | Signature `0xBEDA107F`                               |
+//! | `0x044` | 4     | Version (`major << 16 \| minor`, 1.1 expected)       |
+//! | `0x048` | …     | Header (image type, offsets, geometry, UUIDs …)      |
+//!
+//! The **block map** (`offBlocks`) holds one little-endian `u32` per block:
+//! either the index of the block inside the data area (`offData`) or one of the
+//! special values `0xFFFFFFFF` (not allocated) and `0xFFFFFFFE` (zeroed), both
+//! of which read back as zeroes.  Fixed images use the same map with an
+//! identity mapping.
+//!
+//! Differencing and undo images (which require a parent VDI) are not supported.
+//!
+//! Refer to the VirtualBox sources (`VDICore.h`) for the reference definition.
+
+use crate::error::ExhumeBodyError;
+use crate::info::FormatInfo;
+use crate::metadata::DiskGeometry;
+use crate::read_at::ReadAt;
+use crate::source::Source;
+use crate::vfs::{FileSystem, StdFileSystem};
+use log::debug;
+use std::cmp::min;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+
+// ---- VDI constants ----------------------------------------------------------
+
+/// Image signature stored at offset `0x40`.
+const VDI_SIGNATURE: u32 = 0xBEDA_107F;
+
+/// Only version 1.1 headers are handled (every VirtualBox release since 1.1).
+const VDI_VERSION_1_1: u32 = 0x0001_0001;
+
+/// Size of the pre-header (banner + signature + version).
+const VDI_PRE_HEADER_SIZE: usize = 0x48;
+
+/// Size of the version 1.1 header (including the pre-header).
+const VDI_HEADER_SIZE: usize = 0x200;
+
+/// Block map entry for a block never written.
+const VDI_BLOCK_FREE: u32 = 0xFFFF_FFFF;
+
+/// Block map entry for a block explicitly discarded / zeroed.
+const VDI_BLOCK_ZERO: u32 = 0xFFFF_FFFE;
+
+// ---- Helpers ----------------------------------------------------------------
+
+fn le_u32(data: &[u8], off: usize) -> u32 {
+    u32::from_le_bytes(data[off..off + 4].try_into().unwrap())
+}
+
+fn le_u64(data: &[u8], off: usize) -> u64 {
+    u64::from_le_bytes(data[off..off + 8].try_into().unwrap())
+}
+
+/// Format a 16-byte on-disk UUID (first three fields little-endian).
+fn format_uuid(raw: &[u8]) -> String {
+    format!(
+        "{:08x}-{:04x}-{:04x}-{}-{}",
+        le_u32(raw, 0),
+        u16::from_le_bytes([raw[4], raw[5]]),
+        u16::from_le_bytes([raw[6], raw[7]]),
+        raw[8..10]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>(),
+        raw[10..16]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    )
+}
+
+// ---- Image type -------------------------------------------------------------
+
+/// Image type declared in the header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VdiImageType {
+    /// Dynamically allocated image.
+    Dynamic,
+    /// Pre-allocated image.
+    Fixed,
+    /// Undo (immutable parent) image.
+    Undo,
+    /// Differencing image backed by a parent.
+    Differencing,
+}
+
+impl VdiImageType {
+    fn from_u32(value: u32) -> Result<Self, String> {
+        match value {
+            1 => Ok(Self::Dynamic),
+            2 => Ok(Self::Fixed),
+            3 => Ok(Self::Undo),
+            4 => Ok(Self::Differencing),
+            other => Err(format!("Unsupported VDI image type {}", other)),
+        }
+    }
+}
+
+// ---- Header -----------------------------------------------------------------
+
+/// Version 1.1 VDI header.
+#[derive(Clone, Debug)]
+struct VdiHeader {
+    /// Banner text (e.g. `<<< Oracle VM VirtualBox Disk Image >>>`).
+    banner: String,
+    /// Dynamic, fixed, …
+    image_type: VdiImageType,
+    /// Free-form user comment.
+    comment: String,
+    /// Absolute offset of the block map.
+    blocks_offset: u32,
+    /// Absolute offset of the first data block.
+    data_offset: u32,
+    /// Legacy CHS geometry: cylinders, heads, sectors.
+    geometry: (u32, u32, u32),
+    /// Sector size in bytes.
+    sector_size: u32,
+    /// Virtual disk size in bytes.
+    disk_size: u64,
+    /// Size of a data block in bytes (1 MiB by default).
+    block_size: u32,
+    /// Extra bytes stored before every block's data.
+    block_extra: u32,
+    /// Number of entries in the block map.
+    block_count: u32,
+    /// Number of allocated blocks.
+    blocks_allocated: u32,
+    /// Image creation UUID.
+    uuid_create: String,
+}
+
+impl VdiHeader {
+    /// Parse and validate the 512-byte header.
+    fn parse(data: &[u8]) -> Result<Self, String> {
+        if data.len() < VDI_HEADER_SIZE {
+            return Err("VDI header too short".to_string());
+        }
+        if le_u32(data, 0x40) != VDI_SIGNATURE {
+            return Err("Invalid VDI signature (expected 0xBEDA107F)".to_string());
+        }
+        let version = le_u32(data, 0x44);
+        if version != VDI_VERSION_1_1 {
+            return Err(format!(
+                "Unsupported VDI version {}.{}",
+                version >> 16,
+                version & 0xFFFF
+            ));
+        }
+
+        let text = |range: std::ops::Range<usize>| {
+            let raw = &data[range];
+            let end = raw.iter().position(|&b| b == 0).unwrap_or(raw.len());
+            String::from_utf8_lossy(&raw[..end]).trim().to_string()
+        };
+
+        let header = Self {
+            banner: text(0..VDI_PRE_HEADER_SIZE - 8),
+            image_type: VdiImageType::from_u32(le_u32(data, 0x4C))?,
+            comment: text(0x54..0x154),
+            blocks_offset: le_u32(data, 0x154),
+            data_offset: le_u32(data, 0x158),
+            geometry: (
+                le_u32(data, 0x15C),
+                le_u32(data, 0x160),
+                le_u32(data, 0x164),
+            ),
+            sector_size: le_u32(data, 0x168),
+            disk_size: le_u64(data, 0x170),
+            block_size: le_u32(data, 0x178),
+            block_extra: le_u32(data, 0x17C),
+            block_count: le_u32(data, 0x180),
+            blocks_allocated: le_u32(data, 0x184),
+            uuid_create: format_uuid(&data[0x188..0x198]),
+        };
+
+        if header.block_size == 0 {
+            return Err("Invalid VDI block size 0".to_string());
+        }
+        if (header.block_count as u64) * (header.block_size as u64) < header.disk_size {
+            return Err(format!(
+                "VDI block map too small: {} blocks of {} bytes for {} bytes",
+                header.block_count, header.block_size, header.disk_size
+            ));
+        }
+
+        Ok(header)
+    }
+}
+
+// ---- Public VDI reader ------------------------------------------------------
+
+/// Native VDI image reader.
+///
+/// Implements [`Read`], [`Seek`] and [`Clone`] so it can be used as a drop-in
+/// source of evidence bytes inside the [`Body`](crate::Body) abstraction.
+pub struct VDI {
+    /// Open file handle to the `.vdi` file.
+    file: Source,
+    /// Original path (kept for display).
+    path: String,
+    /// Parsed header.
+    header: VdiHeader,
+    /// Block map (virtual block → physical block index or special value).
+    block_map: Arc<[u32]>,
+    /// Virtual cursor position inside the disk.
+    position: u64,
+}
+
+impl VDI {
+    // ---- Construction -------------------------------------------------------
+
+    /// Open and parse a VDI image.
+    ///
+    /// The constructor validates the signature and version, then loads the
+    /// whole block map in memory.
+    pub fn new(file_path: &str) -> Result<VDI, ExhumeBodyError> {
+        Self::open_in(&StdFileSystem, file_path)
+    }
+
+    /// Same as [`VDI::new`], opening the image through `fs` instead of the
+    /// operating system.
+    pub fn open_in(fs: &dyn FileSystem, file_path: &str) -> Result<VDI, ExhumeBodyError> {
+        Self::new_impl(fs, file_path).map_err(ExhumeBodyError::Vdi)
+    }
+
+    fn new_impl(fs: &dyn FileSystem, file_path: &str) -> Result<VDI, String> {
+        let path = Path::new(file_path);
+        let mut file = fs
+            .open(path)
+            .map_err(|e| format!("Error opening VDI image: {}", e))?;
+
+        let mut raw_header = vec![0u8; VDI_HEADER_SIZE];
+        file.read_exact(&mut raw_header)
+            .map_err(|e| format!("Error reading VDI header: {}", e))?;
+        let header = VdiHeader::parse(&raw_header)?;
+        debug!("Parsed VDI header: {:?}", header);
+
+        if matches!(
+            header.image_type,
+            VdiImageType::Differencing | VdiImageType::Undo
+        ) {
+            return Err(format!(
+                "VDI {:?} images (requiring a parent disk) are not supported",
+                header.image_type
+            ));
+        }
+
+        let file_len = file
+            .len()
+            .map_err(|e| format!("Error reading VDI metadata: {}", e))?;
+        let map_end = header.blocks_offset as u64 + header.block_count as u64 * 4;
+        if map_end > file_len {
+            return Err(format!(
+                "VDI block map of {} entries at offset 0x{:x} runs past the end of the file",
+                header.block_count, header.blocks_offset
+            ));
+        }
+
+        let mut raw_map = vec![0u8; header.block_count as usize * 4];
+        file.seek(SeekFrom::Start(header.blocks_offset as u64))
+            .and_then(|_| file.read_exact(&mut raw_map))
+            .map_err(|e| format!("Error reading VDI block map: {}", e))?;
+        let block_map = raw_map
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+            .collect();
+
+        Ok(VDI {
+            file,
+            path: file_path.to_string(),
+            header,
+            block_map,
+            position: 0,
+        })
+    }
+
+    // ---- Info helpers -------------------------------------------------------
+
+    /// Header banner, type, UUID, geometry and block allocation.
+    pub fn info(&self) -> FormatInfo {
+        let (cylinders, heads, sectors) = self.header.geometry;
+        let mut info = FormatInfo::new("VDI Image Information")
+            .field("Path", &self.path)
+            .field("Banner", &self.header.banner)
+            .field("Image Type", format!("{:?}", self.header.image_type))
+            .field("UUID", &self.header.uuid_create);
+        if !self.header.comment.is_empty() {
+            info = info.field("Comment", &self.header.comment);
+        }
+        info.field("Virtual Size", format!("{} bytes", self.header.disk_size))
+            .field("Sector Size", self.header.sector_size)
+            .field(
+                "Geometry (CHS)",
+                format!("{}/{}/{}", cylinders, heads, sectors),
+            )
+            .field("Block Size", format!("{} bytes", self.header.block_size))
+            .field(
+                "Blocks",
+                format!(
+                    "{} ({} allocated)",
+                    self.header.block_count, self.header.blocks_allocated
+                ),
+            )
+    }
+
+    /// Print parsed metadata to the log.
+    pub fn print_info(&self) {
+        self.info().log();
+    }
+
+    /// Returns the sector size declared in the header (default 512).
+    pub fn get_sector_size(&self) -> u16 {
+        match self.header.sector_size {
+            0 => 512,
+            size => size as u16,
+        }
+    }
+
+    /// Size of the virtual disk in bytes.
+    pub fn size(&self) -> u64 {
+        self.header.disk_size
+    }
+
+    /// Legacy CHS geometry recorded in the header.
+    pub fn geometry(&self) -> Option<DiskGeometry> {
+        let (cylinders, heads, sectors) = self.header.geometry;
+        (cylinders != 0).then_some(DiskGeometry {
+            cylinders: cylinders as u64,
+            heads,
+            sectors_per_track: sectors,
+        })
+    }
+
+    /// Returns the image type declared in the header.
+    pub fn image_type(&self) -> VdiImageType {
+        self.header.image_type
+    }
+
+    // ---- Internal block reading ---------------------------------------------
+
+    /// Fill `buf` from the virtual offset `position`, without crossing a
+    /// block boundary. Returns the number of bytes produced.
+    fn read_block(&self, position: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let block_size = self.header.block_size as u64;
+        let block = (position / block_size) as usize;
+        let offset_in_block = position % block_size;
+        let len = min(buf.len() as u64, block_size - offset_in_block) as usize;
+
+        let entry = *self.block_map.get(block).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("VDI block {} is beyond the block map", block),
+            )
+        })?;
+
+        if entry == VDI_BLOCK_FREE || entry == VDI_BLOCK_ZERO {
+            buf[..len].fill(0);
+            return Ok(len);
+        }
+
+        let stride = block_size + self.header.block_extra as u64;
+        let file_offset = self.header.data_offset as u64
+            + entry as u64 * stride
+            + self.header.block_extra as u64
+            + offset_in_block;
+        self.file.read_exact_at(file_offset, &mut buf[..len])?;
+        Ok(len)
+    }
+}
+
+// ---- Clone ------------------------------------------------------------------
+
+impl Clone for VDI {
+    fn clone(&self) -> Self {
+        Self {
+            file: self.file.clone(),
+            path: self.path.clone(),
+            header: self.header.clone(),
+            block_map: self.block_map.clone(),
+            position: self.position,
+        }
+    }
+}
+
+// ---- Read / ReadAt ----------------------------------------------------------
+
+impl Read for VDI {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.read_at(self.position, buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl ReadAt for VDI {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let image_size = self.header.disk_size;
+        if buf.is_empty() || offset >= image_size {
+            return Ok(0);
+        }
+
+        let want = min(buf.len() as u64, image_size - offset) as usize;
+        let mut total = 0usize;
+
+        while total < want {
+            let position = offset + total as u64;
+            let n = self.read_block(position, &mut buf[total..want])?;
+            total += n;
+        }
+
+        Ok(total)
+    }
+}
+
+// ---- Seek -------------------------------------------------------------------
+
+impl Seek for VDI {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let next = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.header.disk_size.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Seek before start or overflow")
+        })?;
+
+        self.position = next;
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    const BLOCK: usize = 4096;
+    const EXTRA: usize = 16;
+
+    /// A dynamic VDI of four blocks: block 0 stored second in the data area,
+    /// block 1 free, block 2 zeroed and block 3 stored first.
+    fn make_vdi(block0: &[u8], block3: &[u8]) -> Vec<u8> {
+        let mut image = vec![0u8; VDI_HEADER_SIZE];
+        let banner = b"<<< Oracle VM VirtualBox Disk Image >>>\n";
+        image[..banner.len()].copy_from_slice(banner);
+        image[0x40..0x44].copy_from_slice(&VDI_SIGNATURE.to_le_bytes());
+        image[0x44..0x48].copy_from_slice(&VDI_VERSION_1_1.to_le_bytes());
+        image[0x4C..0x50].copy_from_slice(&1u32.to_le_bytes());
+        image[0x54..0x5C].copy_from_slice(b"evidence");
+        let fields: [(usize, u32); 10] = [
+            (0x154, VDI_HEADER_SIZE as u32),
+            (0x158, VDI_HEADER_SIZE as u32 + 16),
+            (0x15C, 2),
+            (0x160, 4),
+            (0x164, 4),
+            (0x168, 512),
+            (0x178, BLOCK as u32),
+            (0x17C, EXTRA as u32),
+            (0x180, 4),
+            (0x184, 2),
+        ];
+        for (offset, value) in fields {
+            image[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        }
+        image[0x170..0x178].copy_from_slice(&(4 * BLOCK as u64).to_le_bytes());
+
+        for entry in [1, VDI_BLOCK_FREE, VDI_BLOCK_ZERO, 0] {
+            image.extend_from_slice(&entry.to_le_bytes());
+        }
+        for data in [block3, block0] {
+            image.extend_from_slice(&[0xEE; EXTRA]);
+            image.extend_from_slice(data);
+        }
+        image
+    }
+
+    #[test]
+    fn reads_allocated_and_sparse_blocks() {
+        let block0: Vec<u8> = (0..BLOCK).map(|i| (i % 251) as u8).collect();
+        let block3: Vec<u8> = (0..BLOCK).map(|i| (i % 13) as u8 + 1).collect();
+        let dir = TempDir::new("vdi");
+        let path = dir.join("disk.vdi");
+        std::fs::write(&path, make_vdi(&block0, &block3)).unwrap();
+
+        let mut vdi = VDI::new(&path.to_string_lossy()).unwrap();
+        assert_eq!(vdi.get_sector_size(), 512);
+        assert_eq!(vdi.image_type(), VdiImageType::Dynamic);
+
+        let mut back = Vec::new();
+        vdi.read_to_end(&mut back).unwrap();
+        assert_eq!(back.len(), 4 * BLOCK);
+        assert_eq!(back[..BLOCK], block0);
+        assert!(back[BLOCK..3 * BLOCK].iter().all(|&b| b == 0));
+        assert_eq!(back[3 * BLOCK..], block3);
+
+        vdi.seek(SeekFrom::Start(3 * BLOCK as u64 - 8)).unwrap();
+        let mut buf = [0xAAu8; 16];
+        vdi.read_exact(&mut buf).unwrap();
+        assert_eq!(buf[..8], [0; 8]);
+        assert_eq!(buf[8..], block3[..8]);
+    }
+    #[test]
+    fn rejects_block_maps_past_the_end_of_the_file() {
+        let mut image = make_vdi(&[0; BLOCK], &[0; BLOCK]);
+        image[0x180..0x184].copy_from_slice(&0x4000_0000u32.to_le_bytes());
+        let dir = TempDir::new("vdi_map");
+        let path = dir.join("disk.vdi");
+        std::fs::write(&path, image).unwrap();
+        let Err(err) = VDI::new(&path.to_string_lossy()) else {
+            panic!("opened a VDI image whose block map runs past the end of the file");
+        };
+        assert!(err.to_string().contains("past the end"), "{}", err);
+    }
+}
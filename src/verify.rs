@@ -0,0 +1,75 @@
+//! Verification of the digests recorded at acquisition time, see
+//! [`Body::verify`](crate::Body::verify).
+
+use crate::hash::HashAlgorithm;
+use serde::Serialize;
+
+/// Outcome of checking one stored digest against the image contents.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct HashCheck {
+    pub algorithm: HashAlgorithm,
+    /// Lower-case hex digest stored in the image.
+    pub stored: String,
+    /// Lower-case hex digest recomputed from the image contents.
+    pub computed: String,
+    /// Where the image stores the digest, e.g. `EWF hash, digest sections`.
+    pub source: String,
+}
+
+impl HashCheck {
+    /// Whether the recomputed digest equals the stored one.
+    pub fn matches(&self) -> bool {
+        self.stored.eq_ignore_ascii_case(&self.computed)
+    }
+}
+
+/// Result of [`Body::verify`](crate::Body::verify): one [`HashCheck`] per
+/// digest stored in the image.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct VerificationReport {
+    pub checks: Vec<HashCheck>,
+}
+
+impl VerificationReport {
+    /// `true` when the image stores at least one digest and all of them
+    /// match.
+    pub fn is_verified(&self) -> bool {
+        !self.checks.is_empty() && self.checks.iter().all(HashCheck::matches)
+    }
+
+    /// `true` when the image stores no digest, so nothing could be checked.
+    pub fn is_empty(&self) -> bool {
+        self.checks.is_empty()
+    }
+
+    /// The checks that failed.
+    pub fn mismatches(&self) -> impl Iterator<Item = &HashCheck> {
+        self.checks.iter().filter(|check| !check.matches())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::TempDir;
+    use crate::{Body, EwfWriter};
+    use std::io::Cursor;
+
+    #[test]
+    fn verifies_ewf_acquisition_hashes() {
+        let dir = TempDir::new("verify");
+        let data: Vec<u8> = (0..256 * 1024u32).map(|i| (i % 239) as u8).collect();
+        let summary = EwfWriter::new()
+            .write(&mut Cursor::new(&data), dir.join("v"))
+            .unwrap();
+
+        let mut body =
+            Body::try_new(dir.join("v.E01").to_string_lossy().to_string(), "ewf").unwrap();
+        assert_eq!(body.stored_hashes(), summary.hashes);
+        let report = body.verify(|_, _| {}).unwrap();
+        assert_eq!(report.checks.len(), 2);
+        assert!(report.is_verified());
+        assert_eq!(report.mismatches().count(), 0);
+        assert_eq!(report.checks[0].source, "EWF hash, digest sections");
+        assert_eq!(report.checks[1].source, "EWF digest section");
+    }
+}
@@ -0,0 +1,213 @@
+//! Pluggable file access, see [`FileSystem`].
+//!
+//! Every file the EWF, VMDK, AFF4 and RAW readers open (segments, extents,
+//! parent disks, sibling volumes) goes through a [`FileSystem`]. The default
+//! [`StdFileSystem`] is the operating system's; a body opened with
+//! [`BodyBuilder::file_system`](crate::BodyBuilder::file_system) reads from
+//! anything else instead, e.g. a [`MemoryFileSystem`] filled by a browser
+//! upload on `wasm32`, or the object store of a sandboxed analysis
+//! environment without OS file access.
+
+use crate::source::Source;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Cursor};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// Where image files are opened from.
+pub trait FileSystem: Send + Sync {
+    /// Open the file at `path` for reading.
+    fn open(&self, path: &Path) -> io::Result<Source>;
+
+    /// Paths of the entries of the directory `dir`, used to discover the
+    /// other files of a multi-file image.
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<PathBuf>>;
+
+    /// Whether `path` is a file that can be opened.
+    fn is_file(&self, path: &Path) -> bool {
+        self.open(path).is_ok()
+    }
+
+    /// Absolute form of `path`, failing when it does not exist. The default
+    /// only checks that the file exists.
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        self.open(path)?;
+        Ok(path.to_path_buf())
+    }
+}
+
+impl fmt::Debug for dyn FileSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("FileSystem")
+    }
+}
+
+/// The operating system's file system, used unless another one is given.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StdFileSystem;
+
+impl FileSystem for StdFileSystem {
+    fn open(&self, path: &Path) -> io::Result<Source> {
+        Source::open(path)
+    }
+
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        std::fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect()
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        std::fs::canonicalize(path)
+    }
+}
+
+/// Image files held in memory, keyed by path.
+///
+/// ```
+/// # use exhume_body::{Body, MemoryFileSystem};
+/// # use std::sync::Arc;
+/// # fn main() -> Result<(), exhume_body::ExhumeBodyError> {
+/// let mut files = MemoryFileSystem::new();
+/// files.insert("case/disk.raw", vec![0u8; 4096]);
+/// let body = Body::builder("case/disk.raw")
+///     .format("raw")
+///     .file_system(Arc::new(files))
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MemoryFileSystem {
+    files: BTreeMap<PathBuf, Arc<[u8]>>,
+}
+
+impl MemoryFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add (or replace) the file at `path`.
+    pub fn insert(&mut self, path: impl AsRef<Path>, data: impl Into<Arc<[u8]>>) {
+        self.files.insert(normalize(path.as_ref()), data.into());
+    }
+
+    /// Remove the file at `path`, returning its contents.
+    pub fn remove(&mut self, path: impl AsRef<Path>) -> Option<Arc<[u8]>> {
+        self.files.remove(&normalize(path.as_ref()))
+    }
+
+    fn get(&self, path: &Path) -> io::Result<&Arc<[u8]>> {
+        self.files.get(&normalize(path)).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{}: no such file", path.display()),
+            )
+        })
+    }
+}
+
+/// `path` without `.` components, so `./a` and `a` name the same file.
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .collect()
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn open(&self, path: &Path) -> io::Result<Source> {
+        Ok(Source::from_reader(Cursor::new(self.get(path)?.clone())))
+    }
+
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        let dir = normalize(dir);
+        Ok(self
+            .files
+            .keys()
+            .filter(|path| path.parent().unwrap_or(Path::new("")) == dir)
+            .cloned()
+            .collect())
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.get(path).is_ok()
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        self.get(path)?;
+        Ok(normalize(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use crate::{Body, BodyFormat, EwfWriter};
+    use std::io::Read;
+
+    #[test]
+    fn opens_multi_segment_images_from_memory() {
+        let dir = TempDir::new("vfs");
+        // Incompressible, so the image spans several segments.
+        let mut state = 0x2545_f491u32;
+        let data: Vec<u8> = (0..300 * 1024)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect();
+        EwfWriter::new()
+            .segment_size(100 * 1024)
+            .write(&mut Cursor::new(&data), dir.join("m"))
+            .unwrap();
+
+        let mut files = MemoryFileSystem::new();
+        let mut segments = 0;
+        for entry in std::fs::read_dir(dir.path()).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_owned();
+            files.insert(
+                Path::new("evidence").join(name),
+                std::fs::read(&path).unwrap(),
+            );
+            segments += 1;
+        }
+        drop(dir);
+        assert!(segments > 1);
+        files.insert("evidence/notes.txt", b"not part of the image".to_vec());
+        let files = Arc::new(files);
+
+        let mut body = Body::builder("./evidence/m.E01")
+            .file_system(files.clone())
+            .build()
+            .unwrap();
+        assert!(matches!(body.format, BodyFormat::EWF { .. }));
+        let mut back = Vec::new();
+        body.read_to_end(&mut back).unwrap();
+        assert_eq!(back, data);
+
+        assert!(Body::builder("evidence/notes.txt")
+            .file_system(files.clone())
+            .build()
+            .is_err());
+        let body = Body::builder("evidence/notes.txt")
+            .format("raw")
+            .file_system(files.clone())
+            .build()
+            .unwrap();
+        assert!(matches!(body.format, BodyFormat::RAW { .. }));
+        assert!(Body::builder("evidence/m.E01")
+            .format("qcow")
+            .file_system(files)
+            .build()
+            .is_err());
+    }
+}
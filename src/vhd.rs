@@ -0,0 +1,579 @@
+//! **VHD (Microsoft Virtual Hard Disk) reader**
+//!
+//! This module provides a pure-Rust reader for **fixed** and **dynamic** VHD
+//! images (`.vhd` files) as produced by Hyper-V, Virtual PC and many
+//! acquisition tools.
+//!
+//! # On-disk layout
+//!
+//! Every VHD ends with a 512-byte **footer** (`conectix` cookie).  Dynamic
+//! images additionally carry a copy of the footer at offset 0, followed by a
+//! **dynamic disk header** (`cxsparse` cookie) pointing to the **Block
+//! Allocation Table** (BAT).
+//!
+//! | Disk type   | Layout                                                     |
+//! |-------------|------------------------------------------------------------|
+//! | **Fixed**   | raw data · footer                                          |
+//! | **Dynamic** | footer copy · dynamic header · BAT · blocks … · footer     |
+//!
+//! Each BAT entry is a big-endian sector number pointing at a block, or
+//! `0xFFFFFFFF` when the block is not allocated (reads back as zeroes).  A block
+//! starts with a sector bitmap followed by `block_size` bytes of data.
+//!
+//! Differencing images (which require a parent VHD) are not supported.
+//!
+//! Refer to the official specification:
+//! <https://learn.microsoft.com/en-us/windows/win32/vstor/about-vhd>
+
+use crate::error::ExhumeBodyError;
+use crate::info::FormatInfo;
+use crate::metadata::DiskGeometry;
+use crate::read_at::ReadAt;
+use crate::source::Source;
+use crate::vfs::{FileSystem, StdFileSystem};
+use log::debug;
+use std::cmp::min;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+
+// ---- VHD constants ----------------------------------------------------------
+
+/// 8-byte footer cookie: `conectix`
+const VHD_FOOTER_COOKIE: &[u8; 8] = b"conectix";
+
+/// 8-byte dynamic disk header cookie: `cxsparse`
+const VHD_DYNAMIC_COOKIE: &[u8; 8] = b"cxsparse";
+
+/// Size of the hard disk footer.
+const VHD_FOOTER_SIZE: usize = 512;
+
+/// Size of the dynamic disk header.
+const VHD_DYNAMIC_HEADER_SIZE: usize = 1024;
+
+/// BAT entry value marking an unallocated block.
+const VHD_BAT_UNUSED: u32 = 0xFFFF_FFFF;
+
+/// VHD sectors are always 512 bytes.
+const VHD_SECTOR_SIZE: u16 = 512;
+
+// ---- Helpers: read big-endian integers from a slice -------------------------
+
+fn be_u16(data: &[u8], off: usize) -> u16 {
+    u16::from_be_bytes([data[off], data[off + 1]])
+}
+
+fn be_u32(data: &[u8], off: usize) -> u32 {
+    u32::from_be_bytes(data[off..off + 4].try_into().unwrap())
+}
+
+fn be_u64(data: &[u8], off: usize) -> u64 {
+    u64::from_be_bytes(data[off..off + 8].try_into().unwrap())
+}
+
+// ---- Disk type --------------------------------------------------------------
+
+/// Disk type declared in the footer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VhdDiskType {
+    /// Pre-allocated image: the data precedes the footer verbatim.
+    Fixed,
+    /// Sparse image: data is stored in blocks referenced by the BAT.
+    Dynamic,
+    /// Sparse image whose unallocated sectors live in a parent VHD.
+    Differencing,
+}
+
+impl VhdDiskType {
+    fn from_u32(value: u32) -> Result<Self, String> {
+        match value {
+            2 => Ok(Self::Fixed),
+            3 => Ok(Self::Dynamic),
+            4 => Ok(Self::Differencing),
+            other => Err(format!("Unsupported VHD disk type {}", other)),
+        }
+    }
+}
+
+// ---- Footer -----------------------------------------------------------------
+
+/// Hard disk footer (the only structure shared by every VHD flavour).
+#[derive(Clone, Debug)]
+struct VhdFooter {
+    /// Absolute offset of the dynamic disk header (`u64::MAX` for fixed disks).
+    data_offset: u64,
+    /// Four-character code of the application that created the image.
+    creator_application: String,
+    /// Virtual size of the disk in bytes.
+    current_size: u64,
+    /// CHS geometry: cylinders, heads, sectors per track.
+    geometry: (u16, u8, u8),
+    /// Fixed, dynamic or differencing.
+    disk_type: VhdDiskType,
+    /// Image unique identifier.
+    unique_id: [u8; 16],
+}
+
+impl VhdFooter {
+    /// Parse and validate a 512-byte footer.
+    fn parse(data: &[u8]) -> Result<Self, String> {
+        if data.len() < VHD_FOOTER_SIZE || &data[0..8] != VHD_FOOTER_COOKIE {
+            return Err("Invalid VHD footer cookie (expected conectix)".to_string());
+        }
+
+        // The checksum is the one's complement of the byte sum, with the
+        // checksum field itself treated as zero.
+        let stored_checksum = be_u32(data, 64);
+        let sum = data[..VHD_FOOTER_SIZE]
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !(64..68).contains(i))
+            .fold(0u32, |acc, (_, b)| acc.wrapping_add(*b as u32));
+        if !sum != stored_checksum {
+            return Err(format!(
+                "VHD footer checksum mismatch (stored 0x{:08x}, computed 0x{:08x})",
+                stored_checksum, !sum
+            ));
+        }
+
+        Ok(Self {
+            data_offset: be_u64(data, 16),
+            creator_application: String::from_utf8_lossy(&data[28..32])
+                .trim_end_matches('\0')
+                .to_string(),
+            current_size: be_u64(data, 48),
+            geometry: (be_u16(data, 56), data[58], data[59]),
+            disk_type: VhdDiskType::from_u32(be_u32(data, 60))?,
+            unique_id: data[68..84].try_into().unwrap(),
+        })
+    }
+}
+
+// ---- Public VHD reader ------------------------------------------------------
+
+/// Native VHD image reader.
+///
+/// Implements [`Read`], [`Seek`] and [`Clone`] so it can be used as a drop-in
+/// source of evidence bytes inside the [`Body`](crate::Body) abstraction.
+pub struct VHD {
+    /// Open file handle to the `.vhd` file.
+    file: Source,
+    /// Original path (kept for display).
+    path: String,
+    /// Parsed hard disk footer.
+    footer: VhdFooter,
+    /// Size of a dynamic block in bytes (0 for fixed disks).
+    block_size: u32,
+    /// Size of the per-block sector bitmap, rounded up to a whole sector.
+    bitmap_size: u64,
+    /// Block Allocation Table (sector numbers, empty for fixed disks).
+    bat: Arc<[u32]>,
+    /// Virtual cursor position inside the disk.
+    position: u64,
+}
+
+impl VHD {
+    // ---- Construction -------------------------------------------------------
+
+    /// Open and parse a VHD image.
+    ///
+    /// The constructor reads the footer at the end of the file and, for dynamic
+    /// images, the dynamic disk header and the whole BAT.
+    pub fn new(file_path: &str) -> Result<VHD, ExhumeBodyError> {
+        Self::open_in(&StdFileSystem, file_path)
+    }
+
+    /// Same as [`VHD::new`], opening the image through `fs` instead of the
+    /// operating system.
+    pub fn open_in(fs: &dyn FileSystem, file_path: &str) -> Result<VHD, ExhumeBodyError> {
+        Self::new_impl(fs, file_path).map_err(ExhumeBodyError::Vhd)
+    }
+
+    fn new_impl(fs: &dyn FileSystem, file_path: &str) -> Result<VHD, String> {
+        let path = Path::new(file_path);
+        let mut file = fs
+            .open(path)
+            .map_err(|e| format!("Error opening VHD image: {}", e))?;
+        let file_len = file
+            .len()
+            .map_err(|e| format!("Error reading VHD metadata: {}", e))?;
+
+        if file_len < VHD_FOOTER_SIZE as u64 {
+            return Err("File too small to be a VHD image".to_string());
+        }
+
+        // --- Footer (last 512 bytes; very old images use a 511-byte footer) ---
+        let mut footer_buf = [0u8; VHD_FOOTER_SIZE];
+        file.seek(SeekFrom::End(-(VHD_FOOTER_SIZE as i64)))
+            .and_then(|_| file.read_exact(&mut footer_buf))
+            .map_err(|e| format!("Error reading VHD footer: {}", e))?;
+        if &footer_buf[0..8] != VHD_FOOTER_COOKIE {
+            file.seek(SeekFrom::End(-(VHD_FOOTER_SIZE as i64 - 1)))
+                .and_then(|_| file.read_exact(&mut footer_buf[..VHD_FOOTER_SIZE - 1]))
+                .map_err(|e| format!("Error reading VHD footer: {}", e))?;
+            footer_buf[VHD_FOOTER_SIZE - 1] = 0;
+        }
+        let footer = VhdFooter::parse(&footer_buf)?;
+        debug!("Parsed VHD footer: {:?}", footer);
+
+        let mut vhd = VHD {
+            file,
+            path: file_path.to_string(),
+            footer,
+            block_size: 0,
+            bitmap_size: 0,
+            bat: Arc::new([]),
+            position: 0,
+        };
+
+        match vhd.footer.disk_type {
+            VhdDiskType::Fixed => {
+                if vhd.footer.current_size > file_len - VHD_FOOTER_SIZE as u64 {
+                    return Err(format!(
+                        "Fixed VHD declares {} bytes but only {} are present",
+                        vhd.footer.current_size,
+                        file_len - VHD_FOOTER_SIZE as u64
+                    ));
+                }
+            }
+            VhdDiskType::Dynamic => vhd.parse_dynamic_header(file_len)?,
+            VhdDiskType::Differencing => {
+                return Err(
+                    "Differencing VHD images (requiring a parent disk) are not supported"
+                        .to_string(),
+                );
+            }
+        }
+
+        Ok(vhd)
+    }
+
+    /// Parse the dynamic disk header and load the BAT, which must fit in the
+    /// `file_len` bytes of the image.
+    fn parse_dynamic_header(&mut self, file_len: u64) -> Result<(), String> {
+        let mut header = [0u8; VHD_DYNAMIC_HEADER_SIZE];
+        self.file
+            .seek(SeekFrom::Start(self.footer.data_offset))
+            .and_then(|_| self.file.read_exact(&mut header))
+            .map_err(|e| format!("Error reading VHD dynamic header: {}", e))?;
+
+        if &header[0..8] != VHD_DYNAMIC_COOKIE {
+            return Err("Invalid VHD dynamic header cookie (expected cxsparse)".to_string());
+        }
+
+        let table_offset = be_u64(&header, 16);
+        let max_table_entries = be_u32(&header, 28);
+        let block_size = be_u32(&header, 32);
+
+        if block_size == 0 || !block_size.is_multiple_of(VHD_SECTOR_SIZE as u32) {
+            return Err(format!("Invalid VHD block size {}", block_size));
+        }
+
+        let needed_entries = self.footer.current_size.div_ceil(block_size as u64);
+        if (max_table_entries as u64) < needed_entries {
+            return Err(format!(
+                "VHD BAT too small: {} entries for {} blocks",
+                max_table_entries, needed_entries
+            ));
+        }
+
+        let bat_end = (max_table_entries as u64)
+            .checked_mul(4)
+            .and_then(|size| size.checked_add(table_offset));
+        if bat_end.is_none_or(|end| end > file_len) {
+            return Err(format!(
+                "VHD BAT of {} entries at offset 0x{:x} runs past the end of the file",
+                max_table_entries, table_offset
+            ));
+        }
+
+        let mut raw_bat = vec![0u8; max_table_entries as usize * 4];
+        self.file
+            .seek(SeekFrom::Start(table_offset))
+            .and_then(|_| self.file.read_exact(&mut raw_bat))
+            .map_err(|e| format!("Error reading VHD BAT: {}", e))?;
+
+        self.bat = raw_bat
+            .chunks_exact(4)
+            .map(|c| u32::from_be_bytes(c.try_into().unwrap()))
+            .collect();
+        self.block_size = block_size;
+
+        // One bit per sector, padded to a sector boundary.
+        let sectors_per_block = (block_size / VHD_SECTOR_SIZE as u32) as u64;
+        self.bitmap_size = sectors_per_block
+            .div_ceil(8)
+            .next_multiple_of(VHD_SECTOR_SIZE as u64);
+
+        debug!(
+            "VHD dynamic header: table_offset=0x{:x} entries={} block_size=0x{:x}",
+            table_offset, max_table_entries, block_size
+        );
+        Ok(())
+    }
+
+    // ---- Info helpers -------------------------------------------------------
+
+    /// Footer fields and, for dynamic disks, the block allocation.
+    pub fn info(&self) -> FormatInfo {
+        let (cylinders, heads, sectors) = self.footer.geometry;
+        let info = FormatInfo::new("VHD Image Information")
+            .field("Path", &self.path)
+            .field("Disk Type", format!("{:?}", self.footer.disk_type))
+            .field("Creator", &self.footer.creator_application)
+            .field(
+                "Virtual Size",
+                format!("{} bytes", self.footer.current_size),
+            )
+            .field(
+                "Geometry (CHS)",
+                format!("{}/{}/{}", cylinders, heads, sectors),
+            )
+            .field(
+                "Unique ID",
+                self.footer
+                    .unique_id
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect::<String>(),
+            );
+        if self.footer.disk_type != VhdDiskType::Dynamic {
+            return info;
+        }
+        info.field("Block Size", format!("{} bytes", self.block_size))
+            .field(
+                "Blocks",
+                format!(
+                    "{} ({} allocated)",
+                    self.bat.len(),
+                    self.bat.iter().filter(|&&e| e != VHD_BAT_UNUSED).count()
+                ),
+            )
+    }
+
+    /// Print parsed metadata to the log.
+    pub fn print_info(&self) {
+        self.info().log();
+    }
+
+    /// Returns the sector size (always 512 for VHD).
+    pub fn get_sector_size(&self) -> u16 {
+        VHD_SECTOR_SIZE
+    }
+
+    /// Size of the virtual disk in bytes.
+    pub fn size(&self) -> u64 {
+        self.footer.current_size
+    }
+
+    /// CHS geometry recorded in the footer.
+    pub fn geometry(&self) -> Option<DiskGeometry> {
+        let (cylinders, heads, sectors) = self.footer.geometry;
+        (cylinders != 0).then_some(DiskGeometry {
+            cylinders: cylinders as u64,
+            heads: heads as u32,
+            sectors_per_track: sectors as u32,
+        })
+    }
+
+    /// Returns the disk type declared in the footer.
+    pub fn disk_type(&self) -> VhdDiskType {
+        self.footer.disk_type
+    }
+
+    // ---- Internal block reading ---------------------------------------------
+
+    /// Fill `buf` from the virtual offset `position`, without crossing a
+    /// block boundary. Returns the number of bytes produced.
+    fn read_dynamic(&self, position: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let block_size = self.block_size as u64;
+        let block = (position / block_size) as usize;
+        let offset_in_block = position % block_size;
+        let len = min(buf.len() as u64, block_size - offset_in_block) as usize;
+
+        let entry = *self.bat.get(block).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("VHD block {} is beyond the BAT", block),
+            )
+        })?;
+
+        if entry == VHD_BAT_UNUSED {
+            buf[..len].fill(0);
+            return Ok(len);
+        }
+
+        let data_offset =
+            entry as u64 * VHD_SECTOR_SIZE as u64 + self.bitmap_size + offset_in_block;
+        self.file.read_exact_at(data_offset, &mut buf[..len])?;
+        Ok(len)
+    }
+}
+
+// ---- Clone ------------------------------------------------------------------
+
+impl Clone for VHD {
+    fn clone(&self) -> Self {
+        Self {
+            file: self.file.clone(),
+            path: self.path.clone(),
+            footer: self.footer.clone(),
+            block_size: self.block_size,
+            bitmap_size: self.bitmap_size,
+            bat: self.bat.clone(),
+            position: self.position,
+        }
+    }
+}
+
+// ---- Read / ReadAt ----------------------------------------------------------
+
+impl Read for VHD {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.read_at(self.position, buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl ReadAt for VHD {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let image_size = self.footer.current_size;
+        if buf.is_empty() || offset >= image_size {
+            return Ok(0);
+        }
+
+        let want = min(buf.len() as u64, image_size - offset) as usize;
+        let mut total = 0usize;
+
+        while total < want {
+            let position = offset + total as u64;
+            let n = match self.footer.disk_type {
+                VhdDiskType::Fixed => self.file.read_at(position, &mut buf[total..want])?,
+                _ => self.read_dynamic(position, &mut buf[total..want])?,
+            };
+            if n == 0 {
+                break;
+            }
+            total += n;
+        }
+
+        Ok(total)
+    }
+}
+
+// ---- Seek -------------------------------------------------------------------
+
+impl Seek for VHD {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let next = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.footer.current_size.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Seek before start or overflow")
+        })?;
+
+        self.position = next;
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    /// Build a valid footer with the given type and size.
+    fn make_footer(disk_type: u32, size: u64, data_offset: u64) -> [u8; VHD_FOOTER_SIZE] {
+        let mut f = [0u8; VHD_FOOTER_SIZE];
+        f[0..8].copy_from_slice(VHD_FOOTER_COOKIE);
+        f[16..24].copy_from_slice(&data_offset.to_be_bytes());
+        f[28..32].copy_from_slice(b"test");
+        f[40..48].copy_from_slice(&size.to_be_bytes());
+        f[48..56].copy_from_slice(&size.to_be_bytes());
+        f[60..64].copy_from_slice(&disk_type.to_be_bytes());
+        let sum = f.iter().fold(0u32, |acc, b| acc.wrapping_add(*b as u32));
+        f[64..68].copy_from_slice(&(!sum).to_be_bytes());
+        f
+    }
+
+    /// A dynamic VHD with 4 KiB blocks: blocks 0 and 2 present, block 1
+    /// not. The BAT declares `bat_entries` entries.
+    fn make_dynamic(first: &[u8], third: &[u8], bat_entries: u32) -> Vec<u8> {
+        let footer = make_footer(3, 3 * 4096, 512);
+        let mut image = footer.to_vec();
+        let mut header = [0u8; VHD_DYNAMIC_HEADER_SIZE];
+        header[0..8].copy_from_slice(VHD_DYNAMIC_COOKIE);
+        header[16..24].copy_from_slice(&1536u64.to_be_bytes());
+        header[28..32].copy_from_slice(&bat_entries.to_be_bytes());
+        header[32..36].copy_from_slice(&4096u32.to_be_bytes());
+        image.extend_from_slice(&header);
+        // Blocks start at sector 4, each behind a one-sector bitmap.
+        let mut bat = [0xffu8; 512];
+        bat[0..4].copy_from_slice(&4u32.to_be_bytes());
+        bat[8..12].copy_from_slice(&13u32.to_be_bytes());
+        image.extend_from_slice(&bat);
+        for block in [first, third] {
+            image.extend_from_slice(&[0xff; 512]);
+            image.extend_from_slice(block);
+        }
+        image.extend_from_slice(&footer);
+        image
+    }
+
+    #[test]
+    fn test_parse_footer() {
+        let footer = VhdFooter::parse(&make_footer(2, 0x10000, u64::MAX)).unwrap();
+        assert_eq!(footer.disk_type, VhdDiskType::Fixed);
+        assert_eq!(footer.current_size, 0x10000);
+        assert_eq!(footer.creator_application, "test");
+    }
+
+    #[test]
+    fn test_parse_footer_bad_checksum() {
+        let mut raw = make_footer(3, 0x10000, 512);
+        raw[48] ^= 0xff;
+        assert!(VhdFooter::parse(&raw).is_err());
+    }
+
+    #[test]
+    fn test_read_at_leaves_the_cursor() {
+        let dir = TempDir::new("vhd");
+        let path = dir.join("vhd.vhd");
+        let mut data: Vec<u8> = (0..0x10000u32).map(|i| (i % 253) as u8).collect();
+        let footer = make_footer(2, data.len() as u64, u64::MAX);
+        std::fs::write(&path, [&data[..], &footer[..]].concat()).unwrap();
+
+        let mut vhd = VHD::new(&path.to_string_lossy()).unwrap();
+        vhd.seek(SeekFrom::Start(100)).unwrap();
+        let mut buf = [0u8; 1000];
+        vhd.read_exact_at(0xff00, &mut buf[..0x100]).unwrap();
+        assert_eq!(buf[..0x100], data[0xff00..]);
+        assert_eq!(vhd.read_at(0x10000, &mut buf).unwrap(), 0);
+        vhd.read_exact(&mut buf).unwrap();
+        data.truncate(1100);
+        assert_eq!(buf[..], data[100..]);
+    }
+    #[test]
+    fn reads_dynamic_images() {
+        let dir = TempDir::new("vhd_dynamic");
+        let path = dir.join("dynamic.vhd");
+        let first: Vec<u8> = (0..4096u32).map(|i| (i % 251) as u8).collect();
+        let third: Vec<u8> = (0..4096u32).map(|i| (i % 13 + 1) as u8).collect();
+        std::fs::write(&path, make_dynamic(&first, &third, 3)).unwrap();
+
+        let mut vhd = VHD::new(&path.to_string_lossy()).unwrap();
+        assert_eq!(vhd.disk_type(), VhdDiskType::Dynamic);
+        let mut back = Vec::new();
+        vhd.read_to_end(&mut back).unwrap();
+        assert_eq!(back, [&first[..], &[0; 4096], &third[..]].concat());
+
+        std::fs::write(&path, make_dynamic(&first, &third, 0x4000_0000)).unwrap();
+        let Err(err) = VHD::new(&path.to_string_lossy()) else {
+            panic!("opened a VHD whose BAT runs past the end of the file");
+        };
+        assert!(err.to_string().contains("past the end"), "{}", err);
+    }
+}
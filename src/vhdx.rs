@@ -0,0 +1,714 @@
+//! **VHDX (Hyper-V Virtual Hard Disk v2) reader**
+//!
+//! This module provides a pure-Rust reader for **fixed** and **dynamic** VHDX
+//! images (`.vhdx` files) as produced by Hyper-V, Windows Server backups and
+//! triage collectors such as KAPE or Velociraptor.
+//!
+//! # On-disk layout
+//!
+//! The first megabyte of the file is the **header section**:
+//!
+//! | Offset  | Structure                                       |
+//! |---------|-------------------------------------------------|
+//! | 0 KiB   | File type identifier (`vhdxfile` + creator)     |
+//! | 64 KiB  | Header 1 (`head`)                               |
+//! | 128 KiB | Header 2 (`head`)                               |
+//! | 192 KiB | Region table 1 (`regi`)                         |
+//! | 256 KiB | Region table 2 (`regi`)                         |
+//!
+//! The region table locates the **metadata region** (block size, virtual disk
+//! size, sector sizes, …) and the **Block Allocation Table** (BAT).  The BAT
+//! interleaves one *sector bitmap* entry after every `chunk_ratio` *payload*
+//! entries; each payload entry stores a block state and the block offset in
+//! MiB.
+//!
+//! Differencing images (which require a parent VHDX) are not supported and
+//! pending log entries are not replayed.
+//!
+//! Refer to the official specification:
+//! <https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-vhdx>
+
+use crate::error::ExhumeBodyError;
+use crate::info::FormatInfo;
+use crate::read_at::ReadAt;
+use crate::source::Source;
+use crate::vfs::{FileSystem, StdFileSystem};
+use log::{debug, warn};
+use std::cmp::min;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+
+// ---- VHDX constants ---------------------------------------------------------
+
+/// 8-byte file type identifier: `vhdxfile`
+const VHDX_FILE_SIGNATURE: &[u8; 8] = b"vhdxfile";
+
+/// 4-byte header signature: `head`
+const VHDX_HEADER_SIGNATURE: &[u8; 4] = b"head";
+
+/// 4-byte region table signature: `regi`
+const VHDX_REGION_SIGNATURE: &[u8; 4] = b"regi";
+
+/// 8-byte metadata table signature: `metadata`
+const VHDX_METADATA_SIGNATURE: &[u8; 8] = b"metadata";
+
+/// Offsets of the two redundant headers.
+const VHDX_HEADER_OFFSETS: [u64; 2] = [64 * 1024, 128 * 1024];
+
+/// Offsets of the two redundant region tables.
+const VHDX_REGION_OFFSETS: [u64; 2] = [192 * 1024, 256 * 1024];
+
+/// Size of a header structure covered by its checksum.
+const VHDX_HEADER_SIZE: usize = 4 * 1024;
+
+/// Size of a region table structure covered by its checksum.
+const VHDX_REGION_TABLE_SIZE: usize = 64 * 1024;
+
+/// BAT entries encode the block file offset in units of 1 MiB.
+const VHDX_MIB: u64 = 1024 * 1024;
+
+// Region GUIDs (on-disk, mixed-endian byte order).
+const BAT_REGION_GUID: &str = "2dc27766-f623-4200-9d64-115e9bfd4a08";
+const METADATA_REGION_GUID: &str = "8b7ca206-4790-4b9a-b8fe-575f050f886e";
+
+// Metadata item GUIDs.
+const FILE_PARAMETERS_GUID: &str = "caa16737-fa36-4d43-b3b6-33f0aa44e76b";
+const VIRTUAL_DISK_SIZE_GUID: &str = "2fa54224-cd1b-4876-b211-5dbed83bf4b8";
+const VIRTUAL_DISK_ID_GUID: &str = "beca12ab-b2e6-4523-93ef-c309e000c746";
+const LOGICAL_SECTOR_SIZE_GUID: &str = "8141bf1d-a96f-4709-ba47-f233a8faab5f";
+const PHYSICAL_SECTOR_SIZE_GUID: &str = "cda348c7-445d-4471-9cc9-e9885251c556";
+
+// Payload block states (lower 3 bits of a BAT entry).
+const PAYLOAD_BLOCK_FULLY_PRESENT: u64 = 6;
+const PAYLOAD_BLOCK_PARTIALLY_PRESENT: u64 = 7;
+
+// ---- Helpers ----------------------------------------------------------------
+
+fn le_u16(data: &[u8], off: usize) -> u16 {
+    u16::from_le_bytes([data[off], data[off + 1]])
+}
+
+fn le_u32(data: &[u8], off: usize) -> u32 {
+    u32::from_le_bytes(data[off..off + 4].try_into().unwrap())
+}
+
+fn le_u64(data: &[u8], off: usize) -> u64 {
+    u64::from_le_bytes(data[off..off + 8].try_into().unwrap())
+}
+
+/// Format a 16-byte on-disk GUID (first three fields little-endian) as the
+/// usual lowercase `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx` string.
+pub(crate) fn format_guid(raw: &[u8]) -> String {
+    format!(
+        "{:08x}-{:04x}-{:04x}-{}-{}",
+        le_u32(raw, 0),
+        le_u16(raw, 4),
+        le_u16(raw, 6),
+        raw[8..10]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>(),
+        raw[10..16]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    )
+}
+
+/// CRC-32C (Castagnoli) as used by every VHDX checksum field.
+fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0x82F6_3B78
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Verify a structure whose checksum is stored at byte offset 4 (computed with
+/// the checksum field zeroed).
+fn checksum_ok(data: &[u8]) -> bool {
+    let stored = le_u32(data, 4);
+    let mut copy = data.to_vec();
+    copy[4..8].fill(0);
+    crc32c(&copy) == stored
+}
+
+/// Read `len` bytes at `offset`.
+fn read_at(file: &mut Source, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+    let mut buf = vec![0u8; len];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+// ---- Parsed structures ------------------------------------------------------
+
+/// The active (highest sequence number, valid checksum) header.
+#[derive(Clone, Debug)]
+struct VhdxHeader {
+    sequence_number: u64,
+    log_guid: String,
+    version: u16,
+}
+
+/// One entry of the region table.
+#[derive(Clone, Debug)]
+struct VhdxRegion {
+    guid: String,
+    file_offset: u64,
+    length: u32,
+}
+
+/// Values extracted from the metadata region.
+#[derive(Clone, Debug, Default)]
+struct VhdxMetadata {
+    block_size: u32,
+    has_parent: bool,
+    virtual_disk_size: u64,
+    virtual_disk_id: String,
+    logical_sector_size: u32,
+    physical_sector_size: u32,
+}
+
+// ---- Public VHDX reader -----------------------------------------------------
+
+/// Native VHDX image reader.
+///
+/// Implements [`Read`], [`Seek`] and [`Clone`] so it can be used as a drop-in
+/// source of evidence bytes inside the [`Body`](crate::Body) abstraction.
+pub struct VHDX {
+    /// Open file handle to the `.vhdx` file.
+    file: Source,
+    /// Original path (kept for display).
+    path: String,
+    /// Creator string from the file type identifier.
+    creator: String,
+    /// Active header.
+    header: VhdxHeader,
+    /// Parsed metadata items.
+    metadata: VhdxMetadata,
+    /// Number of payload blocks per sector bitmap block.
+    chunk_ratio: u64,
+    /// Raw BAT entries (payload and sector bitmap entries interleaved).
+    bat: Arc<[u64]>,
+    /// Virtual cursor position inside the disk.
+    position: u64,
+}
+
+impl VHDX {
+    // ---- Construction -------------------------------------------------------
+
+    /// Open and parse a VHDX image.
+    ///
+    /// The constructor validates the file identifier, selects the current
+    /// header, reads the region table, the metadata region and the whole BAT.
+    pub fn new(file_path: &str) -> Result<VHDX, ExhumeBodyError> {
+        Self::open_in(&StdFileSystem, file_path)
+    }
+
+    /// Same as [`VHDX::new`], opening the image through `fs` instead of the
+    /// operating system.
+    pub fn open_in(fs: &dyn FileSystem, file_path: &str) -> Result<VHDX, ExhumeBodyError> {
+        Self::new_impl(fs, file_path).map_err(ExhumeBodyError::Vhdx)
+    }
+
+    fn new_impl(fs: &dyn FileSystem, file_path: &str) -> Result<VHDX, String> {
+        let path = Path::new(file_path);
+        let mut file = fs
+            .open(path)
+            .map_err(|e| format!("Error opening VHDX image: {}", e))?;
+
+        // --- File type identifier ---
+        let ident = read_at(&mut file, 0, 520)
+            .map_err(|e| format!("Error reading VHDX identifier: {}", e))?;
+        if &ident[0..8] != VHDX_FILE_SIGNATURE {
+            return Err("Invalid VHDX signature (expected vhdxfile)".to_string());
+        }
+        let creator_utf16: Vec<u16> = ident[8..520]
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .take_while(|&c| c != 0)
+            .collect();
+        let creator = String::from_utf16_lossy(&creator_utf16);
+
+        let header = Self::parse_headers(&mut file)?;
+        if header.log_guid != "00000000-0000-0000-0000-000000000000" {
+            warn!(
+                "VHDX log {} is not empty; pending log entries are not replayed",
+                header.log_guid
+            );
+        }
+
+        let regions = Self::parse_region_table(&mut file)?;
+        let find_region = |guid: &str| {
+            regions
+                .iter()
+                .find(|r| r.guid == guid)
+                .cloned()
+                .ok_or_else(|| format!("VHDX region {} not found", guid))
+        };
+        let metadata_region = find_region(METADATA_REGION_GUID)?;
+        let bat_region = find_region(BAT_REGION_GUID)?;
+
+        let metadata = Self::parse_metadata(&mut file, &metadata_region)?;
+        if metadata.has_parent {
+            return Err(
+                "Differencing VHDX images (requiring a parent disk) are not supported".to_string(),
+            );
+        }
+
+        let chunk_ratio =
+            ((1u64 << 23) * metadata.logical_sector_size as u64) / metadata.block_size as u64;
+        let payload_blocks = metadata
+            .virtual_disk_size
+            .div_ceil(metadata.block_size as u64);
+        let bat_entries = payload_blocks + (payload_blocks.saturating_sub(1) / chunk_ratio);
+
+        if bat_entries * 8 > bat_region.length as u64 {
+            return Err(format!(
+                "VHDX BAT region too small: {} entries needed, region is {} bytes",
+                bat_entries, bat_region.length
+            ));
+        }
+
+        let raw_bat = read_at(&mut file, bat_region.file_offset, bat_entries as usize * 8)
+            .map_err(|e| format!("Error reading VHDX BAT: {}", e))?;
+        let bat = raw_bat
+            .chunks_exact(8)
+            .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+            .collect();
+
+        debug!(
+            "VHDX: block_size=0x{:x} chunk_ratio={} payload_blocks={} bat_entries={}",
+            metadata.block_size, chunk_ratio, payload_blocks, bat_entries
+        );
+
+        Ok(VHDX {
+            file,
+            path: file_path.to_string(),
+            creator,
+            header,
+            metadata,
+            chunk_ratio,
+            bat,
+            position: 0,
+        })
+    }
+
+    /// Select the valid header with the greatest sequence number.
+    fn parse_headers(file: &mut Source) -> Result<VhdxHeader, String> {
+        let mut best: Option<VhdxHeader> = None;
+
+        for offset in VHDX_HEADER_OFFSETS {
+            let raw = match read_at(file, offset, VHDX_HEADER_SIZE) {
+                Ok(raw) => raw,
+                Err(e) => {
+                    debug!("Cannot read VHDX header at 0x{:x}: {}", offset, e);
+                    continue;
+                }
+            };
+            if &raw[0..4] != VHDX_HEADER_SIGNATURE || !checksum_ok(&raw) {
+                debug!("Ignoring invalid VHDX header at 0x{:x}", offset);
+                continue;
+            }
+            let candidate = VhdxHeader {
+                sequence_number: le_u64(&raw, 8),
+                log_guid: format_guid(&raw[48..64]),
+                version: le_u16(&raw, 66),
+            };
+            if best
+                .as_ref()
+                .is_none_or(|b| candidate.sequence_number > b.sequence_number)
+            {
+                best = Some(candidate);
+            }
+        }
+
+        best.ok_or_else(|| "No valid VHDX header found".to_string())
+    }
+
+    /// Read the first valid region table.
+    fn parse_region_table(file: &mut Source) -> Result<Vec<VhdxRegion>, String> {
+        for offset in VHDX_REGION_OFFSETS {
+            let raw = match read_at(file, offset, VHDX_REGION_TABLE_SIZE) {
+                Ok(raw) => raw,
+                Err(_) => continue,
+            };
+            if &raw[0..4] != VHDX_REGION_SIGNATURE || !checksum_ok(&raw) {
+                debug!("Ignoring invalid VHDX region table at 0x{:x}", offset);
+                continue;
+            }
+
+            let entry_count = le_u32(&raw, 8) as usize;
+            if 16 + entry_count * 32 > raw.len() {
+                continue;
+            }
+
+            return Ok((0..entry_count)
+                .map(|i| {
+                    let e = &raw[16 + i * 32..16 + (i + 1) * 32];
+                    VhdxRegion {
+                        guid: format_guid(&e[0..16]),
+                        file_offset: le_u64(e, 16),
+                        length: le_u32(e, 24),
+                    }
+                })
+                .collect());
+        }
+
+        Err("No valid VHDX region table found".to_string())
+    }
+
+    /// Parse the metadata table and the system items we rely on.
+    fn parse_metadata(file: &mut Source, region: &VhdxRegion) -> Result<VhdxMetadata, String> {
+        let raw = read_at(file, region.file_offset, region.length as usize)
+            .map_err(|e| format!("Error reading VHDX metadata region: {}", e))?;
+        if raw.len() < 32 || &raw[0..8] != VHDX_METADATA_SIGNATURE {
+            return Err("Invalid VHDX metadata table signature".to_string());
+        }
+
+        let entry_count = le_u16(&raw, 10) as usize;
+        let mut metadata = VhdxMetadata::default();
+
+        for i in 0..entry_count {
+            let start = 32 + i * 32;
+            let Some(e) = raw.get(start..start + 32) else {
+                break;
+            };
+            let guid = format_guid(&e[0..16]);
+            let item_offset = le_u32(e, 16) as usize;
+            let item_len = le_u32(e, 20) as usize;
+            let Some(item) = raw.get(item_offset..item_offset + item_len) else {
+                return Err(format!("VHDX metadata item {} out of bounds", guid));
+            };
+
+            match guid.as_str() {
+                FILE_PARAMETERS_GUID if item.len() >= 8 => {
+                    metadata.block_size = le_u32(item, 0);
+                    metadata.has_parent = le_u32(item, 4) & 0x2 != 0;
+                }
+                VIRTUAL_DISK_SIZE_GUID if item.len() >= 8 => {
+                    metadata.virtual_disk_size = le_u64(item, 0);
+                }
+                VIRTUAL_DISK_ID_GUID if item.len() >= 16 => {
+                    metadata.virtual_disk_id = format_guid(item);
+                }
+                LOGICAL_SECTOR_SIZE_GUID if item.len() >= 4 => {
+                    metadata.logical_sector_size = le_u32(item, 0);
+                }
+                PHYSICAL_SECTOR_SIZE_GUID if item.len() >= 4 => {
+                    metadata.physical_sector_size = le_u32(item, 0);
+                }
+                _ => debug!("Skipping VHDX metadata item {}", guid),
+            }
+        }
+
+        if metadata.block_size == 0 || !metadata.block_size.is_power_of_two() {
+            return Err(format!("Invalid VHDX block size {}", metadata.block_size));
+        }
+        if metadata.logical_sector_size != 512 && metadata.logical_sector_size != 4096 {
+            return Err(format!(
+                "Invalid VHDX logical sector size {}",
+                metadata.logical_sector_size
+            ));
+        }
+        if metadata.virtual_disk_size == 0 {
+            return Err("VHDX virtual disk size missing".to_string());
+        }
+
+        Ok(metadata)
+    }
+
+    // ---- Info helpers -------------------------------------------------------
+
+    /// Header and metadata region fields, and the number of present blocks.
+    pub fn info(&self) -> FormatInfo {
+        FormatInfo::new("VHDX Image Information")
+            .field("Path", &self.path)
+            .field("Creator", &self.creator)
+            .field("Version", self.header.version)
+            .field("Sequence Number", self.header.sequence_number)
+            .field("Virtual Disk ID", &self.metadata.virtual_disk_id)
+            .field(
+                "Virtual Size",
+                format!("{} bytes", self.metadata.virtual_disk_size),
+            )
+            .field("Block Size", format!("{} bytes", self.metadata.block_size))
+            .field("Logical Sector", self.metadata.logical_sector_size)
+            .field("Physical Sector", self.metadata.physical_sector_size)
+            .field(
+                "Present Blocks",
+                (0..self.payload_block_count())
+                    .filter(|&b| self.payload_entry(b) & 0x7 == PAYLOAD_BLOCK_FULLY_PRESENT)
+                    .count(),
+            )
+    }
+
+    /// Print parsed metadata to the log.
+    pub fn print_info(&self) {
+        self.info().log();
+    }
+
+    /// Returns the logical sector size declared in the metadata region.
+    pub fn get_sector_size(&self) -> u16 {
+        self.metadata.logical_sector_size as u16
+    }
+
+    /// Size of the virtual disk in bytes.
+    pub fn size(&self) -> u64 {
+        self.metadata.virtual_disk_size
+    }
+
+    /// Returns the physical sector size declared in the metadata region.
+    pub fn get_physical_sector_size(&self) -> u32 {
+        self.metadata.physical_sector_size
+    }
+
+    // ---- Internal block resolution ------------------------------------------
+
+    fn payload_block_count(&self) -> u64 {
+        self.metadata
+            .virtual_disk_size
+            .div_ceil(self.metadata.block_size as u64)
+    }
+
+    /// BAT entry for payload block `block`, skipping interleaved bitmap entries.
+    fn payload_entry(&self, block: u64) -> u64 {
+        let index = block + block / self.chunk_ratio;
+        self.bat.get(index as usize).copied().unwrap_or(0)
+    }
+
+    /// Fill `buf` from the virtual offset `position`, without crossing a
+    /// block boundary. Returns the number of bytes produced.
+    fn read_block(&self, position: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let block_size = self.metadata.block_size as u64;
+        let block = position / block_size;
+        let offset_in_block = position % block_size;
+        let len = min(buf.len() as u64, block_size - offset_in_block) as usize;
+
+        let entry = self.payload_entry(block);
+        match entry & 0x7 {
+            PAYLOAD_BLOCK_FULLY_PRESENT => {
+                let file_offset = (entry >> 20) * VHDX_MIB + offset_in_block;
+                self.file.read_exact_at(file_offset, &mut buf[..len])?;
+            }
+            PAYLOAD_BLOCK_PARTIALLY_PRESENT => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!(
+                        "VHDX block {} is partially present (parent required)",
+                        block
+                    ),
+                ));
+            }
+            // NOT_PRESENT, UNDEFINED, ZERO, UNMAPPED all read back as zeroes.
+            _ => buf[..len].fill(0),
+        }
+        Ok(len)
+    }
+}
+
+// ---- Clone ------------------------------------------------------------------
+
+impl Clone for VHDX {
+    fn clone(&self) -> Self {
+        Self {
+            file: self.file.clone(),
+            path: self.path.clone(),
+            creator: self.creator.clone(),
+            header: self.header.clone(),
+            metadata: self.metadata.clone(),
+            chunk_ratio: self.chunk_ratio,
+            bat: self.bat.clone(),
+            position: self.position,
+        }
+    }
+}
+
+// ---- Read / ReadAt ----------------------------------------------------------
+
+impl Read for VHDX {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.read_at(self.position, buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl ReadAt for VHDX {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let image_size = self.metadata.virtual_disk_size;
+        if buf.is_empty() || offset >= image_size {
+            return Ok(0);
+        }
+
+        let want = min(buf.len() as u64, image_size - offset) as usize;
+        let mut total = 0usize;
+
+        while total < want {
+            let position = offset + total as u64;
+            let n = self.read_block(position, &mut buf[total..want])?;
+            total += n;
+        }
+
+        Ok(total)
+    }
+}
+
+// ---- Seek -------------------------------------------------------------------
+
+impl Seek for VHDX {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let next = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.metadata.virtual_disk_size.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Seek before start or overflow")
+        })?;
+
+        self.position = next;
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    /// On-disk bytes of a GUID written as [`format_guid`] prints it.
+    fn guid_bytes(guid: &str) -> [u8; 16] {
+        let hex = guid.replace('-', "");
+        let mut raw = [0u8; 16];
+        for (i, byte) in raw.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).unwrap();
+        }
+        raw[0..4].reverse();
+        raw[4..6].reverse();
+        raw[6..8].reverse();
+        raw
+    }
+
+    /// Store `crc32c` of `data` (checksum field zeroed) at offset 4.
+    fn seal(data: &mut [u8]) {
+        let sum = crc32c(data);
+        data[4..8].copy_from_slice(&sum.to_le_bytes());
+    }
+
+    /// A dynamic VHDX with 1 MiB blocks: blocks 0 and 2 present, block 1
+    /// not, and a virtual size ending half-way through block 2.
+    fn make_vhdx(first: &[u8], third: &[u8]) -> Vec<u8> {
+        let mut image = vec![0u8; 5 * VHDX_MIB as usize];
+        image[0..8].copy_from_slice(VHDX_FILE_SIGNATURE);
+        for (i, c) in "exhume".encode_utf16().enumerate() {
+            image[8 + i * 2..10 + i * 2].copy_from_slice(&c.to_le_bytes());
+        }
+
+        let header = &mut image[64 * 1024..64 * 1024 + VHDX_HEADER_SIZE];
+        header[0..4].copy_from_slice(VHDX_HEADER_SIGNATURE);
+        header[8..16].copy_from_slice(&1u64.to_le_bytes());
+        header[66..68].copy_from_slice(&1u16.to_le_bytes());
+        seal(header);
+
+        let regions = &mut image[192 * 1024..192 * 1024 + VHDX_REGION_TABLE_SIZE];
+        regions[0..4].copy_from_slice(VHDX_REGION_SIGNATURE);
+        regions[8..12].copy_from_slice(&2u32.to_le_bytes());
+        for (i, (guid, offset)) in [(METADATA_REGION_GUID, 1), (BAT_REGION_GUID, 2)]
+            .into_iter()
+            .enumerate()
+        {
+            let entry = &mut regions[16 + i * 32..48 + i * 32];
+            entry[0..16].copy_from_slice(&guid_bytes(guid));
+            entry[16..24].copy_from_slice(&(offset * VHDX_MIB).to_le_bytes());
+            entry[24..28].copy_from_slice(&(VHDX_MIB as u32).to_le_bytes());
+        }
+        seal(regions);
+
+        let metadata = &mut image[VHDX_MIB as usize..2 * VHDX_MIB as usize];
+        metadata[0..8].copy_from_slice(VHDX_METADATA_SIGNATURE);
+        let items: [(&str, Vec<u8>); 5] = [
+            (
+                FILE_PARAMETERS_GUID,
+                [(VHDX_MIB as u32).to_le_bytes(), [0; 4]].concat(),
+            ),
+            (
+                VIRTUAL_DISK_SIZE_GUID,
+                (VHDX_MIB * 5 / 2).to_le_bytes().to_vec(),
+            ),
+            (
+                VIRTUAL_DISK_ID_GUID,
+                guid_bytes("0a1b2c3d-4e5f-6071-8293-a4b5c6d7e8f9").to_vec(),
+            ),
+            (LOGICAL_SECTOR_SIZE_GUID, 512u32.to_le_bytes().to_vec()),
+            (PHYSICAL_SECTOR_SIZE_GUID, 4096u32.to_le_bytes().to_vec()),
+        ];
+        metadata[10..12].copy_from_slice(&(items.len() as u16).to_le_bytes());
+        for (i, (guid, item)) in items.iter().enumerate() {
+            let offset = 0x10000 + i * 0x100;
+            let entry = &mut metadata[32 + i * 32..64 + i * 32];
+            entry[0..16].copy_from_slice(&guid_bytes(guid));
+            entry[16..20].copy_from_slice(&(offset as u32).to_le_bytes());
+            entry[20..24].copy_from_slice(&(item.len() as u32).to_le_bytes());
+            metadata[offset..offset + item.len()].copy_from_slice(item);
+        }
+
+        // Block 0 at 3 MiB, block 2 at 4 MiB.
+        let bat = 2 * VHDX_MIB as usize;
+        let present = |mib: u64| ((mib << 20) | PAYLOAD_BLOCK_FULLY_PRESENT).to_le_bytes();
+        image[bat..bat + 8].copy_from_slice(&present(3));
+        image[bat + 16..bat + 24].copy_from_slice(&present(4));
+        image[3 * VHDX_MIB as usize..][..first.len()].copy_from_slice(first);
+        image[4 * VHDX_MIB as usize..][..third.len()].copy_from_slice(third);
+        image
+    }
+
+    #[test]
+    fn reads_present_and_absent_blocks() {
+        let first: Vec<u8> = (0..VHDX_MIB as u32).map(|i| (i % 251) as u8).collect();
+        let third: Vec<u8> = (0..VHDX_MIB as u32 / 2).map(|i| (i % 241) as u8).collect();
+        let dir = TempDir::new("vhdx");
+        let path = dir.join("disk.vhdx");
+        std::fs::write(&path, make_vhdx(&first, &third)).unwrap();
+
+        let mut vhdx = VHDX::new(&path.to_string_lossy()).unwrap();
+        assert_eq!(vhdx.get_sector_size(), 512);
+        assert_eq!(vhdx.get_physical_sector_size(), 4096);
+
+        let mut back = Vec::new();
+        vhdx.read_to_end(&mut back).unwrap();
+        assert_eq!(back.len(), (VHDX_MIB * 5 / 2) as usize);
+        assert_eq!(back[..first.len()], first);
+        assert!(back[first.len()..2 * first.len()].iter().all(|&b| b == 0));
+        assert_eq!(back[2 * first.len()..], third);
+
+        // A read across the end of block 0.
+        let mut buf = [0xaau8; 32];
+        vhdx.seek(SeekFrom::Start(VHDX_MIB - 16)).unwrap();
+        vhdx.read_exact(&mut buf).unwrap();
+        assert_eq!(buf[..16], first[first.len() - 16..]);
+        assert_eq!(buf[16..], [0; 16]);
+    }
+
+    #[test]
+    fn rejects_a_header_with_a_bad_checksum() {
+        let mut image = make_vhdx(&[1], &[2]);
+        image[64 * 1024 + 8] ^= 1;
+        let dir = TempDir::new("vhdx_header");
+        let path = dir.join("disk.vhdx");
+        std::fs::write(&path, image).unwrap();
+        let Err(err) = VHDX::new(&path.to_string_lossy()) else {
+            panic!("opened a VHDX without a valid header");
+        };
+        assert!(err.to_string().contains("No valid VHDX header"), "{}", err);
+    }
+}
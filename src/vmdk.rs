@@ -1,25 +1,35 @@
 //! This module contains functionality for reading VMDK volumes.
 //!
-//! Currently VMDK files using Flat and Sparse (compressed of not) extents are supported. COWD files (used on ESXi) are not at this stage.
-//! Note that this module does not support reading snapshots and any VMDK disk that requires referring to a parent VMDK.
+//! Currently VMDK files using Flat, Sparse (compressed of not) and seSparse (ESXi 6.5+ snapshots) extents are supported.
+//! COWD files (used on older ESXi) are not at this stage.
+//! Delta links (snapshots) are supported: when the descriptor references a parent through `parentCID` and
+//! `parentFileNameHint`, the parent disk is opened as well and reads of grains absent from the child fall through to it.
 //!
-//! # Known Limitations
-//!
-//! For the moment VMDK descriptor files not written in UTF-8 encoding are not supported.
+//! Descriptors are decoded by the `encoding` their header declares (UTF-8, GBK, Big5, Shift_JIS or windows-1252).
 
 use std::{
     cmp::min,
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     ffi::OsStr,
-    fs::File,
     io::{self, Read, Seek, SeekFrom},
+    ops::Range,
     path::{Path, PathBuf},
     str::FromStr,
-    sync::LazyLock,
+    sync::{Arc, LazyLock, Mutex, OnceLock},
+    time::Instant,
 };
 
+use crate::error::ExhumeBodyError;
+use crate::info::FormatInfo;
+use crate::metadata::{clip_ranges, merge_ranges, probe_sector_size, DiskGeometry, EvidenceFile};
+use crate::read_at::ReadAt;
+use crate::source::Source;
+use crate::stats::IoCounters;
+use crate::trace::io_span;
+use crate::vfs::{FileSystem, StdFileSystem};
+use encoding_rs::{Encoding, BIG5, GBK, SHIFT_JIS, UTF_8, WINDOWS_1252};
 use flate2::bufread::ZlibDecoder;
-use log::{debug, info, warn};
+use log::{debug, warn};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
@@ -32,10 +42,48 @@ const DESCRIPTOR_FILE_DISK_DATABASE_SECTION_SIGNATURE: &str = "# The Disk Data B
 // Flags used in sparse extent file headers.
 const _FLAG_VALID_NEWLINE_DETECTION_TEST: u32 = 0x00000001;
 const FLAG_USE_SECONDARY_GRAIN_DIRECTORY: u32 = 0x00000002;
-const _FLAG_USE_ZEROED_GRAIN_TABLE: u32 = 0x00000004;
+const FLAG_USE_ZEROED_GRAIN_TABLE: u32 = 0x00000004;
 const FLAG_HAS_COMPRESSED_GRAIN_DATA: u32 = 0x00010000;
 const _FLAG_HAS_METADATA: u32 = 0x00020000;
 
+/// Grain directory sector of a streamOptimized header whose grain directory is recorded in the footer.
+const GD_AT_END: i64 = -1;
+// Types of the metadata markers of streamOptimized extents.
+const MARKER_EOS: u32 = 0;
+const MARKER_GRAIN_TABLE: u32 = 1;
+const MARKER_GRAIN_DIRECTORY: u32 = 2;
+const MARKER_FOOTER: u32 = 3;
+
+/// Parent CID value meaning that the disk has no parent.
+const NO_PARENT_CID: u32 = 0xffffffff;
+
+/// Signature of a change tracking (`-ctk.vmdk`) file.
+const CTK_SIGNATURE: &[u8; 4] = b"CTKF";
+/// Size of the change tracking file header, the bitmap follows it.
+const CTK_HEADER_SIZE: usize = 512;
+/// Header flag set while the tracking data is consistent with the disk.
+const CTK_FLAG_CLEAN: u32 = 0x00000001;
+
+/// Grain table entry of a grain explicitly zeroed in a delta disk (when the zeroed-grain flag is set).
+const ZEROED_GRAIN_TABLE_ENTRY: u32 = 1;
+/// Largest grain accepted, in sectors (VMware uses 128).
+const MAX_GRAIN_SECTORS: u64 = 1 << 16;
+/// Largest grain table accepted, in entries (VMware uses 512).
+const MAX_GRAIN_TABLE_ENTRIES: u32 = 1 << 16;
+/// Largest embedded descriptor accepted, in sectors (VMware reserves 20).
+const MAX_DESCRIPTOR_SECTORS: u64 = 2048;
+/// Memory kept for decompressed grains of a compressed extent, in bytes (256 grains of the usual 64 KiB).
+const GRAIN_CACHE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Magic of the constant header of a seSparse extent.
+const SESPARSE_CONST_HEADER_MAGIC: u64 = 0xcafebabe;
+/// Magic of the volatile header of a seSparse extent.
+const SESPARSE_VOLATILE_HEADER_MAGIC: u64 = 0xcafecafe;
+/// The only seSparse version known (2.1).
+const SESPARSE_VERSION: u64 = 0x0000000200000001;
+/// Grain directory entry of an allocated seSparse grain table: type in the top nibble, index in the low 32 bits.
+const SESPARSE_GRAIN_TABLE_ALLOCATED: u64 = 0x1000000000000000;
+
 /// Enum used for VMDK file probing for autodetect
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum VmdkProbe {
@@ -44,7 +92,7 @@ enum VmdkProbe {
     TextDescriptorLikely,
 }
 
-fn probe_vmdk(file: &mut File, file_len: u64) -> io::Result<Option<VmdkProbe>> {
+fn probe_vmdk(file: &mut Source, file_len: u64) -> io::Result<Option<VmdkProbe>> {
     // Check for sparse header at start
     {
         let mut magic = [0u8; 4];
@@ -81,11 +129,17 @@ fn probe_vmdk(file: &mut File, file_len: u64) -> io::Result<Option<VmdkProbe>> {
     }
 
     // If many bytes are non-printable control chars, this is almost certainly
-    // not a text descriptor.
+    // not a text descriptor. Bytes above 0x7f may be part of a legacy
+    // multi-byte encoding (GBK, Shift_JIS, ...).
     let non_printable = head
         .iter()
         .filter(|&&b| {
-            !(b == b'\n' || b == b'\r' || b == b'\t' || b == b' ' || b.is_ascii_graphic())
+            !(b == b'\n'
+                || b == b'\r'
+                || b == b'\t'
+                || b == b' '
+                || b.is_ascii_graphic()
+                || !b.is_ascii())
         })
         .count();
     if non_printable.saturating_mul(100) > head.len().saturating_mul(5) {
@@ -117,7 +171,7 @@ fn probe_vmdk(file: &mut File, file_len: u64) -> io::Result<Option<VmdkProbe>> {
 ///
 /// See also: https://github.com/libyal/libvmdk/blame/main/documentation/VMWare%20Virtual%20Disk%20Format%20(VMDK).asciidoc#211-encodings
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
-enum VMDKEncoding {
+pub enum VMDKEncoding {
     /// UTF-8 encoding
     #[serde(rename = "UTF-8")]
     Utf8,
@@ -138,27 +192,56 @@ enum VMDKEncoding {
     Windows1252,
 }
 
+impl VMDKEncoding {
+    const ALL: [VMDKEncoding; 5] = [
+        VMDKEncoding::Utf8,
+        VMDKEncoding::Big5,
+        VMDKEncoding::Gbk,
+        VMDKEncoding::ShiftJis,
+        VMDKEncoding::Windows1252,
+    ];
+
+    /// Matches an `encoding` header value, case-insensitively and with the
+    /// usual aliases (`cp936`, `sjis`, `latin1`, ...).
+    pub fn from_label(label: &str) -> Option<Self> {
+        let charset = Encoding::for_label(label.trim().as_bytes())?;
+        Self::ALL
+            .into_iter()
+            .find(|encoding| encoding.charset() == charset)
+    }
+
+    fn charset(&self) -> &'static Encoding {
+        match self {
+            VMDKEncoding::Utf8 => UTF_8,
+            VMDKEncoding::Big5 => BIG5,
+            VMDKEncoding::Gbk => GBK,
+            VMDKEncoding::ShiftJis => SHIFT_JIS,
+            VMDKEncoding::Windows1252 => WINDOWS_1252,
+        }
+    }
+}
+
 /// Represents a VMDK header section in a VMDK descriptor file.
 ///
 /// See also: https://github.com/libyal/libvmdk/blob/main/documentation/VMWare%20Virtual%20Disk%20Format%20(VMDK).asciidoc#21-header
 #[derive(Clone, Debug, Serialize, Deserialize)]
-struct VMDKHeader {
+pub struct VMDKHeader {
     /// The VMDK version number, must be 1, 2 or 3.
-    version: u8,
+    pub version: u8,
     /// Encoding of the descriptor file
-    encoding: VMDKEncoding,
+    pub encoding: VMDKEncoding,
     /// Content identifier _ A random 32-bit value updated the first time the content of the virtual disk is modified after the virtual disk is opened.
-    cid: u32,
+    pub cid: u32,
     /// The content identifier of the parent.
     /// A 32-bit value identifying the parent content. A value of 'ffffffff' (-1) represents no parent content.
-    parent_cid: u32,
+    pub parent_cid: u32,
     /// Only seen values are "no"
-    is_native_snapshot: Option<bool>,
+    pub is_native_snapshot: Option<bool>,
     /// The disk type
-    create_type: VMDKDiskType,
+    pub create_type: VMDKDiskType,
     /// Contains the path to the parent image.
     /// This value is only present if the image is a differential image (delta link).
-    parent_file_name_hint: Option<String>,
+    pub parent_file_name_hint: Option<String>,
 }
 
 impl TryFrom<HashMap<String, String>> for VMDKHeader {
@@ -173,12 +256,10 @@ impl TryFrom<HashMap<String, String>> for VMDKHeader {
             .ok_or("version not found in header")?
             .parse()
             .map_err(|_| "invalid version in header")?;
-        let encoding_str = value
-            .get("encoding")
-            .unwrap_or(&String::from("UTF-8"))
-            .to_string();
-        let encoding = serde_json::from_value(serde_json::Value::String(encoding_str))
-            .map_err(|_| "invalid encoding in header")?;
+        let encoding = match value.get("encoding") {
+            Some(label) => VMDKEncoding::from_label(label).ok_or("invalid encoding in header")?,
+            None => VMDKEncoding::Utf8,
+        };
         let cid = u32::from_str_radix(
             value.get("CID").ok_or("CID not found in header")?.as_str(),
             16,
@@ -218,7 +299,7 @@ impl TryFrom<HashMap<String, String>> for VMDKHeader {
 /// See also: https://github.com/libyal/libvmdk/blob/main/documentation/VMWare%20Virtual%20Disk%20Format%20(VMDK).asciidoc#222-extent-access-mode
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "UPPERCASE")]
-enum VMDKExtentAccessMode {
+pub enum VMDKExtentAccessMode {
     /// No access
     NoAccess,
     /// Read-only access
@@ -232,7 +313,7 @@ enum VMDKExtentAccessMode {
 /// See also: https://github.com/libyal/libvmdk/blob/main/documentation/VMWare%20Virtual%20Disk%20Format%20(VMDK).asciidoc#223-extent-type
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "UPPERCASE")]
-enum VMDKExtentType {
+pub enum VMDKExtentType {
     /// RAW extent data file
     /// Seen in VMWare Player 9 to be also used for devices on Windows
     Flat,
@@ -244,6 +325,8 @@ enum VMDKExtentType {
     Vmfs,
     /// COWD sparse extent data file
     VmfsSparse,
+    /// seSparse extent data file (ESXi 6.5+ snapshots of disks larger than 2 TB, and the default since)
+    SeSparse,
     VmfsRdm,
     VmfsRaw,
 }
@@ -252,21 +335,21 @@ enum VMDKExtentType {
 ///
 /// See also: https://github.com/libyal/libvmdk/blob/main/documentation/VMWare%20Virtual%20Disk%20Format%20(VMDK).asciidoc#22-extent-descriptions
 #[derive(Clone, Debug, Serialize, Deserialize)]
-struct VMDKExtentDescriptor {
+pub struct VMDKExtentDescriptor {
     /// Access mode for the extent
-    access_mode: VMDKExtentAccessMode,
+    pub access_mode: VMDKExtentAccessMode,
     /// Number of sectors in the extent
-    sector_number: u64,
+    pub sector_number: u64,
     /// The type of the extent
-    extent_type: VMDKExtentType,
+    pub extent_type: VMDKExtentType,
     /// The name of the extent file. Specified if the extent type is different from flat
-    extent_file_name: Option<String>,
+    pub extent_file_name: Option<String>,
     /// The start sector of the extent in the parent image. Optional and defaults to 0.
-    extent_start_sector: Option<u64>,
+    pub extent_start_sector: Option<u64>,
     /// Only specified in some cases regarding Windows systems
-    partition_uuid: Option<String>,
+    pub partition_uuid: Option<String>,
     /// Only specified in some cases regarding Windows systems
-    device_identifier: Option<String>,
+    pub device_identifier: Option<String>,
 }
 
 impl VMDKExtentDescriptor {
@@ -276,6 +359,32 @@ impl VMDKExtentDescriptor {
     }
 }
 
+impl std::fmt::Display for VMDKExtentDescriptor {
+    /// Writes the extent as a line of the extent description section.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            serde_name(&self.access_mode),
+            self.sector_number,
+            serde_name(&self.extent_type)
+        )?;
+        if let Some(name) = &self.extent_file_name {
+            write!(f, " \"{}\"", name)?;
+        }
+        if let Some(start) = self.extent_start_sector {
+            write!(f, " {}", start)?;
+        }
+        for field in [&self.partition_uuid, &self.device_identifier]
+            .into_iter()
+            .flatten()
+        {
+            write!(f, " {}", field)?;
+        }
+        Ok(())
+    }
+}
+
 impl FromStr for VMDKExtentDescriptor {
     type Err = String;
 
@@ -334,16 +443,184 @@ impl FromStr for VMDKExtentDescriptor {
 ///
 /// See also: https://github.com/libyal/libvmdk/blob/main/documentation/VMWare%20Virtual%20Disk%20Format%20(VMDK).asciidoc#23-change-tracking-file-section
 #[derive(Clone, Debug, Serialize, Deserialize)]
-struct VMDKChangeTrackingSection {
+pub struct VMDKChangeTrackingSection {
     /// Path of the change tracking file.
-    change_track_path: String,
+    pub change_track_path: String,
+}
+
+/// Changed Block Tracking data read from the file named by the change
+/// tracking section, one bit per block of the disk set when the block was
+/// written since tracking was last reset (typically at the last backup or
+/// snapshot).
+///
+/// VMware does not publish the format. The file is read as a 512-byte
+/// little-endian header (`CTKF` signature, version, flags, sectors per
+/// block, block count) followed by the bitmap, least significant bit first.
+#[derive(Clone, Debug, Serialize)]
+pub struct VMDKChangeTracking {
+    /// Version of the change tracking file.
+    pub version: u32,
+    /// Whether the tracking data was left consistent. Changes made while
+    /// the file was not clean (e.g. after a crash) are not recorded.
+    pub clean: bool,
+    /// Bytes of the disk covered by one bit.
+    pub block_size: u64,
+    /// Number of tracked blocks.
+    pub block_count: u64,
+    /// The changed-block bitmap.
+    #[serde(skip)]
+    pub bitmap: Vec<u8>,
+}
+
+impl VMDKChangeTracking {
+    /// Parses the content of a change tracking file.
+    pub fn parse(data: &[u8]) -> Result<Self, String> {
+        if data.len() < CTK_HEADER_SIZE || &data[..4] != CTK_SIGNATURE {
+            return Err("Not a VMDK change tracking file".to_string());
+        }
+        let u32_at =
+            |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+        let sectors_per_block = u32_at(12) as u64;
+        let block_count = u64::from_le_bytes(data[16..24].try_into().unwrap());
+        if sectors_per_block == 0 {
+            return Err("Change tracking block size is zero".to_string());
+        }
+        let bitmap = &data[CTK_HEADER_SIZE..];
+        if (bitmap.len() as u64) < block_count.div_ceil(8) {
+            return Err(format!(
+                "Change tracking bitmap holds {} bytes, {} blocks declared",
+                bitmap.len(),
+                block_count
+            ));
+        }
+        Ok(VMDKChangeTracking {
+            version: u32_at(4),
+            clean: u32_at(8) & CTK_FLAG_CLEAN == CTK_FLAG_CLEAN,
+            block_size: sectors_per_block * SECTOR_SIZE,
+            block_count,
+            bitmap: bitmap[..block_count.div_ceil(8) as usize].to_vec(),
+        })
+    }
+
+    /// Whether `block` was written since tracking was reset.
+    pub fn is_changed(&self, block: u64) -> bool {
+        block < self.block_count && self.bitmap[(block / 8) as usize] & (1 << (block % 8)) != 0
+    }
+
+    /// Byte ranges of the disk written since tracking was reset, sorted and
+    /// merged.
+    pub fn changed_ranges(&self) -> Vec<Range<u64>> {
+        let mut ranges: Vec<Range<u64>> = Vec::new();
+        for block in (0..self.block_count).filter(|&block| self.is_changed(block)) {
+            let start = block * self.block_size;
+            match ranges.last_mut() {
+                Some(last) if last.end == start => last.end += self.block_size,
+                _ => ranges.push(start..start + self.block_size),
+            }
+        }
+        ranges
+    }
+}
+
+/// An inconsistency found by [`VMDK::validate`]. Tools and corruption
+/// produce some of them, but on evidence they are worth a look: a truncated
+/// copy, a swapped extent or an edited descriptor shows up here.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum VMDKAnomaly {
+    /// An extent listed in the descriptor could not be opened.
+    MissingExtent { extent: String },
+    /// The sector count of an extent in the descriptor differs from the
+    /// capacity recorded in its sparse header.
+    CapacityMismatch {
+        extent: String,
+        descriptor_sectors: u64,
+        header_sectors: u64,
+    },
+    /// The disk database geometry addresses more sectors than the extents
+    /// hold.
+    GeometryExceedsExtents {
+        geometry_sectors: u64,
+        extent_sectors: u64,
+    },
+    /// Two extents are placed over the same sectors of the disk.
+    OverlappingExtents { first: String, second: String },
+    /// A grain table entry points past the end of its extent file.
+    GrainOutOfBounds {
+        extent: String,
+        grain: u64,
+        offset: u64,
+        file_size: u64,
+    },
+    /// A sparse extent was not closed cleanly.
+    DirtyExtent { extent: String },
+    /// A streamOptimized extent has no usable footer, usually an
+    /// interrupted export: its grain tables were rebuilt from the grain
+    /// markers.
+    MissingFooter { extent: String },
+    /// The `parentCID` of a delta link is not the `CID` of its parent: the
+    /// parent changed after the snapshot was taken.
+    ParentCidMismatch { expected: u32, found: u32 },
+}
+
+impl std::fmt::Display for VMDKAnomaly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VMDKAnomaly::MissingExtent { extent } => {
+                write!(f, "extent {} could not be opened", extent)
+            }
+            VMDKAnomaly::CapacityMismatch {
+                extent,
+                descriptor_sectors,
+                header_sectors,
+            } => write!(
+                f,
+                "extent {} holds {} sectors per the descriptor but {} per its header",
+                extent, descriptor_sectors, header_sectors
+            ),
+            VMDKAnomaly::GeometryExceedsExtents {
+                geometry_sectors,
+                extent_sectors,
+            } => write!(
+                f,
+                "geometry addresses {} sectors but the extents hold {}",
+                geometry_sectors, extent_sectors
+            ),
+            VMDKAnomaly::OverlappingExtents { first, second } => {
+                write!(f, "extents {} and {} overlap", first, second)
+            }
+            VMDKAnomaly::GrainOutOfBounds {
+                extent,
+                grain,
+                offset,
+                file_size,
+            } => write!(
+                f,
+                "grain {} of extent {} is at offset {} past the end of the file ({} bytes)",
+                grain, extent, offset, file_size
+            ),
+            VMDKAnomaly::DirtyExtent { extent } => {
+                write!(f, "extent {} was not closed cleanly", extent)
+            }
+            VMDKAnomaly::MissingFooter { extent } => write!(
+                f,
+                "extent {} has no usable footer, its grain tables were rebuilt from the grain markers",
+                extent
+            ),
+            VMDKAnomaly::ParentCidMismatch { expected, found } => write!(
+                f,
+                "parentCID is {:08x} but the parent's CID is {:08x}",
+                expected, found
+            ),
+        }
+    }
 }
 
 /// The adapter type for a disk.
 ///
 /// See also: https://github.com/libyal/libvmdk/blob/main/documentation/VMWare%20Virtual%20Disk%20Format%20(VMDK).asciidoc#242-the-disk-adapter-type
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
-enum VMDKDiskAdapterType {
+pub enum VMDKDiskAdapterType {
     #[serde(rename = "ide")]
     Ide,
     #[serde(rename = "buslogic")]
@@ -358,36 +635,36 @@ enum VMDKDiskAdapterType {
 ///
 /// See also: https://github.com/libyal/libvmdk/blob/main/documentation/VMWare%20Virtual%20Disk%20Format%20(VMDK).asciidoc#24-disk-database
 #[derive(Clone, Debug, Serialize, Deserialize)]
-struct VMDKDiskDatabase {
+pub struct VMDKDiskDatabase {
     /// Most encountered value is true
-    ddb_deletable: Option<bool>,
+    pub ddb_deletable: Option<bool>,
     /// The virtual hardware version
     /// For VMWare Player and Workstation this seems to correspond with the application version
-    ddb_virtual_hw_version: Option<String>,
+    pub ddb_virtual_hw_version: Option<String>,
     /// The long content identifier
     /// 128-bit base16 encoded value, without spaces
-    ddb_long_content_id: Option<String>,
+    pub ddb_long_content_id: Option<String>,
     /// Unique identifier
     /// 128-bit base16 encoded value, with spaces between bytes
-    ddb_uuid: Option<String>,
+    pub ddb_uuid: Option<String>,
     /// The number of cylinders
-    ddb_geometry_cylinders: Option<u64>,
+    pub ddb_geometry_cylinders: Option<u64>,
     /// The number of heads
-    ddb_geometry_heads: Option<u64>,
+    pub ddb_geometry_heads: Option<u64>,
     /// The number of sectors
-    ddb_geometry_sectors: Option<u64>,
+    pub ddb_geometry_sectors: Option<u64>,
     /// The number of cylinders as reported by the BIOS
-    ddb_geometry_bios_cylinders: Option<u64>,
+    pub ddb_geometry_bios_cylinders: Option<u64>,
     /// The number of heads as reported by the BIOS
-    ddb_geometry_bios_heads: Option<u64>,
+    pub ddb_geometry_bios_heads: Option<u64>,
     /// The number of sectors as reported by the BIOS
-    ddb_geometry_bios_sectors: Option<u64>,
+    pub ddb_geometry_bios_sectors: Option<u64>,
     /// The disk adapter type
-    ddb_adapter_type: Option<VMDKDiskAdapterType>,
+    pub ddb_adapter_type: Option<VMDKDiskAdapterType>,
     /// String containing the version of the installed VMWare tools
-    ddb_tools_version: Option<String>,
+    pub ddb_tools_version: Option<String>,
     /// Generally set to "1"
-    ddb_thin_provisioned: Option<bool>,
+    pub ddb_thin_provisioned: Option<bool>,
 }
 
 impl TryFrom<HashMap<String, String>> for VMDKDiskDatabase {
@@ -396,7 +673,10 @@ impl TryFrom<HashMap<String, String>> for VMDKDiskDatabase {
     fn try_from(value: HashMap<String, String>) -> Result<Self, Self::Error> {
         let ddb_deletable = value.get("ddb.deletable").map(|s| s == "true");
         let ddb_virtual_hw_version = value.get("ddb.virtualHWVersion").map(|s| s.to_string());
-        let ddb_long_content_id = value.get("ddb.longContentId").map(|s| s.to_string());
+        let ddb_long_content_id = value
+            .get("ddb.longContentID")
+            .or_else(|| value.get("ddb.longContentId"))
+            .map(|s| s.to_string());
         let ddb_uuid = value.get("ddb.uuid").map(|s| s.to_string());
         let ddb_geometry_cylinders = value
             .get("ddb.geometry.cylinders")
@@ -424,7 +704,9 @@ impl TryFrom<HashMap<String, String>> for VMDKDiskDatabase {
             None
         };
         let ddb_tools_version = value.get("ddb.toolsVersion").map(|s| s.to_string());
-        let ddb_thin_provisioned = value.get("ddb.thinProvisioned").map(|s| s == "true");
+        let ddb_thin_provisioned = value
+            .get("ddb.thinProvisioned")
+            .map(|s| s == "1" || s == "true");
         Ok(Self {
             ddb_deletable,
             ddb_virtual_hw_version,
@@ -443,19 +725,80 @@ impl TryFrom<HashMap<String, String>> for VMDKDiskDatabase {
     }
 }
 
+impl VMDKDiskDatabase {
+    /// The fields that are set, as `ddb.*` keys and values, in the order VMware writes them.
+    fn entries(&self) -> Vec<(&'static str, String)> {
+        let flag =
+            |value: bool, set: &str, unset: &str| if value { set } else { unset }.to_string();
+        [
+            (
+                "ddb.adapterType",
+                self.ddb_adapter_type.as_ref().map(serde_name),
+            ),
+            (
+                "ddb.geometry.cylinders",
+                self.ddb_geometry_cylinders.map(|v| v.to_string()),
+            ),
+            (
+                "ddb.geometry.heads",
+                self.ddb_geometry_heads.map(|v| v.to_string()),
+            ),
+            (
+                "ddb.geometry.sectors",
+                self.ddb_geometry_sectors.map(|v| v.to_string()),
+            ),
+            (
+                "ddb.geometry.biosCylinders",
+                self.ddb_geometry_bios_cylinders.map(|v| v.to_string()),
+            ),
+            (
+                "ddb.geometry.biosHeads",
+                self.ddb_geometry_bios_heads.map(|v| v.to_string()),
+            ),
+            (
+                "ddb.geometry.biosSectors",
+                self.ddb_geometry_bios_sectors.map(|v| v.to_string()),
+            ),
+            ("ddb.longContentID", self.ddb_long_content_id.clone()),
+            ("ddb.uuid", self.ddb_uuid.clone()),
+            ("ddb.virtualHWVersion", self.ddb_virtual_hw_version.clone()),
+            ("ddb.toolsVersion", self.ddb_tools_version.clone()),
+            (
+                "ddb.thinProvisioned",
+                self.ddb_thin_provisioned.map(|v| flag(v, "1", "0")),
+            ),
+            (
+                "ddb.deletable",
+                self.ddb_deletable.map(|v| flag(v, "true", "false")),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, value?)))
+        .collect()
+    }
+}
+
+/// Name of a descriptor enum value (`RW`, `SPARSE`, `monolithicSparse`, ...), as it is serialized.
+fn serde_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
 /// Represents a VMDK descriptor file.
 ///
 /// See also: https://github.com/libyal/libvmdk/blob/main/documentation/VMWare%20Virtual%20Disk%20Format%20(VMDK).asciidoc#2-the-descriptor-file
 #[derive(Clone, Debug, Serialize, Deserialize)]
-struct VMDKDescriptorFile {
+pub struct VMDKDescriptorFile {
     /// The VMDK header read from the descriptor file.
-    header: VMDKHeader,
+    pub header: VMDKHeader,
     /// The VMDK extent descriptions read from the descriptor file.
-    extent_descriptions: Vec<VMDKExtentDescriptor>,
+    pub extent_descriptions: Vec<VMDKExtentDescriptor>,
     /// The VMDK change tracking file read from the descriptor file.
-    change_tracking_file: Option<VMDKChangeTrackingSection>,
+    pub change_tracking_file: Option<VMDKChangeTrackingSection>,
     /// The VMDK disk database file read from the descriptor file.
-    disk_database: Option<VMDKDiskDatabase>,
+    pub disk_database: Option<VMDKDiskDatabase>,
 }
 
 /// Returns a keyword related to the section mention from the line recovered from the descriptor file.
@@ -498,6 +841,88 @@ fn parse_key_value_pair(line: &str) -> Option<(&str, &str)> {
     }
 }
 
+impl VMDKDescriptorFile {
+    /// Decodes a descriptor by the `encoding` declared in its header, then
+    /// parses it. The header keys are ASCII in every supported encoding, so
+    /// the declaration can be found before decoding.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, String> {
+        let encoding = data
+            .split(|&b| b == b'\n')
+            .filter_map(|line| std::str::from_utf8(line).ok())
+            .filter_map(|line| parse_key_value_pair(line.trim()))
+            .find(|(key, _)| *key == "encoding")
+            .and_then(|(_, label)| VMDKEncoding::from_label(label))
+            .unwrap_or(VMDKEncoding::Utf8);
+        let (text, malformed) = encoding.charset().decode_without_bom_handling(data);
+        if malformed {
+            warn!(
+                "Descriptor is not valid {:?}, undecodable bytes were replaced",
+                encoding
+            );
+        }
+        text.parse()
+    }
+
+    /// Encodes the text of the descriptor (its [`Display`](std::fmt::Display) form) in the `encoding` its header
+    /// declares, as [`VMDKDescriptorFile::from_bytes`] reads it back: the content of a descriptor file to write next
+    /// to extents whose descriptor was lost or damaged.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let text = self.to_string();
+        let (data, _, unmappable) = self.header.encoding.charset().encode(&text);
+        if unmappable {
+            warn!(
+                "Descriptor has characters {:?} cannot encode, they were replaced",
+                self.header.encoding
+            );
+        }
+        data.into_owned()
+    }
+}
+
+impl std::fmt::Display for VMDKDescriptorFile {
+    /// Writes the descriptor in the layout VMware uses, which [`FromStr`] parses back. Keys the parser does not
+    /// keep (comments, unknown `ddb.*` fields) are not written.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let header = &self.header;
+        writeln!(f, "{}", DESCRIPTOR_FILE_SIGNATURE)?;
+        writeln!(f, "version={}", header.version)?;
+        writeln!(f, "encoding=\"{}\"", serde_name(&header.encoding))?;
+        writeln!(f, "CID={:08x}", header.cid)?;
+        writeln!(f, "parentCID={:08x}", header.parent_cid)?;
+        if let Some(native) = header.is_native_snapshot {
+            writeln!(
+                f,
+                "isNativeSnapshot=\"{}\"",
+                if native { "yes" } else { "no" }
+            )?;
+        }
+        writeln!(f, "createType=\"{}\"", serde_name(&header.create_type))?;
+        if let Some(hint) = &header.parent_file_name_hint {
+            writeln!(f, "parentFileNameHint=\"{}\"", hint)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "{}", DESCRIPTOR_FILE_EXTENT_SECTION_SIGNATURE)?;
+        for extent in &self.extent_descriptions {
+            writeln!(f, "{}", extent)?;
+        }
+        if let Some(section) = &self.change_tracking_file {
+            writeln!(f)?;
+            writeln!(f, "{}", DESCRIPTOR_FILE_CHANGE_TRACKING_SECTION_SIGNATURE)?;
+            writeln!(f, "changeTrackPath=\"{}\"", section.change_track_path)?;
+        }
+        if let Some(ddb) = &self.disk_database {
+            writeln!(f)?;
+            writeln!(f, "{}", DESCRIPTOR_FILE_DISK_DATABASE_SECTION_SIGNATURE)?;
+            writeln!(f, "#DDB")?;
+            writeln!(f)?;
+            for (key, value) in ddb.entries() {
+                writeln!(f, "{} = \"{}\"", key, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl FromStr for VMDKDescriptorFile {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -563,7 +988,7 @@ impl FromStr for VMDKDescriptorFile {
 /// See also: https://github.com/libyal/libvmdk/blame/main/documentation/VMWare%20Virtual%20Disk%20Format%20(VMDK).asciidoc#212-disk-type
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
-enum VMDKDiskType {
+pub enum VMDKDiskType {
     /// The disk is split into fixed-size extents of maximum 2 GB.
     /// The extents consists of RAW extent data files.
     ///
@@ -639,35 +1064,51 @@ enum VMDKDiskType {
     /// The disk is split into sparse (dynamic-size) extents.
     /// The extents consists of COWD sparse extent data files.
     VmfsThin,
+    /// The disk is a delta link whose extent is a seSparse extent data file.
+    ///
+    /// The seSparse disk image consists of:
+    /// * a descriptor file
+    /// * a seSparse data extent file (<name>-sesparse.vmdk)
+    SeSparse,
 }
 
-/// Represents the state of a Sparse extent file, including the flattened grain directory
+/// Represents the state of a Sparse extent file: its grain directory, and the grain tables read so far
 #[derive(Clone, Debug)]
 struct VMDKSparseExtentMetadata {
     /// The header of the sparse extent file
     header: VMDKSparseFileHeader,
-    /// The grain directory
+    /// The grain directory: sector of each grain table, 0 when the table is not allocated (1 for the allocated
+    /// tables of a rebuilt directory, which are never read from the file)
     grain_directory: Vec<u32>,
+    /// The grain tables, indexed like the grain directory, read on first use
+    grain_tables: Vec<OnceLock<Box<[u32]>>>,
+    /// Whether the grain tables were rebuilt from the grain markers of a streamOptimized extent without a footer
+    rebuilt: bool,
 }
 
 impl VMDKSparseExtentMetadata {
-    /// Takes a sparse extent file and reads its metadata to recover the grain directory and grain tables
+    /// Takes a sparse extent file and reads its grain directory. Grain tables are read when first needed.
     ///
     /// # Errors
     ///
     /// Errors if any IO error occurs while reading the file or if some metadata is invalid
-    fn read_from_file(file: &mut File, header: &VMDKSparseFileHeader) -> Result<Self, String> {
-        let mut grain_directory_entry_count: u64 =
-            header.capacity / (header.number_of_grain_table_entries as u64 * header.grain_number);
-        if !header.capacity.is_multiple_of(header.number_of_grain_table_entries as u64 * header.grain_number)
-        {
-            grain_directory_entry_count += 1
-        }
+    fn read_from_file(file: &Source, header: &VMDKSparseFileHeader) -> Result<Self, String> {
+        let grain_table_coverage =
+            header.number_of_grain_table_entries as u64 * header.grain_number;
+        let grain_directory_entry_count = header.capacity.div_ceil(grain_table_coverage);
         debug!(
             "Grain directory entry count: {}",
             grain_directory_entry_count
         );
-        let mut grain_directory = Vec::with_capacity(grain_directory_entry_count as usize);
+        let file_size = file
+            .len()
+            .map_err(|e| format!("Error reading sparse extent file: {}", e))?;
+        if grain_directory_entry_count.saturating_mul(4) > file_size {
+            return Err(format!(
+                "Grain directory of {} entries does not fit in the sparse extent file",
+                grain_directory_entry_count
+            ));
+        }
         let active_grain_directory_sector = if header.flags & FLAG_USE_SECONDARY_GRAIN_DIRECTORY
             == FLAG_USE_SECONDARY_GRAIN_DIRECTORY
             || header.grain_directory_sector == -1
@@ -681,34 +1122,408 @@ impl VMDKSparseExtentMetadata {
         } else {
             header.grain_directory_sector
         };
-        file.seek(io::SeekFrom::Start(
-            u64::try_from(active_grain_directory_sector).unwrap() * SECTOR_SIZE,
-        ))
-        .map_err(|e| format!("Unable to navigate the sparse extent file: {}", e))?;
-        for _ in 0..grain_directory_entry_count {
-            let mut number_buf = [0u8; 4];
-            file.read(&mut number_buf)
-                .map_err(|e| format!("Error reading sparse extent file: {}", e))?;
-            grain_directory.push(u32::from_le_bytes(number_buf));
+        let grain_directory_offset = u64::try_from(active_grain_directory_sector)
+            .ok()
+            .and_then(|sector| sector.checked_mul(SECTOR_SIZE))
+            .ok_or_else(|| {
+                format!(
+                    "Invalid grain directory sector {}",
+                    active_grain_directory_sector
+                )
+            })?;
+        let mut raw_directory = vec![0u8; grain_directory_entry_count as usize * 4];
+        file.read_exact_at(grain_directory_offset, &mut raw_directory)
+            .map_err(|e| format!("Error reading sparse extent file: {}", e))?;
+        let grain_directory: Vec<u32> = raw_directory
+            .chunks_exact(4)
+            .map(|entry| u32::from_le_bytes(entry.try_into().unwrap()))
+            .collect();
+        Ok(VMDKSparseExtentMetadata {
+            header: header.clone(),
+            grain_tables: grain_directory.iter().map(|_| OnceLock::new()).collect(),
+            grain_directory,
+            rebuilt: false,
+        })
+    }
+
+    /// Reads the metadata of a streamOptimized extent.
+    ///
+    /// The header at the start of such a file usually defers the grain directory to the footer (`gdOffset` is
+    /// [`GD_AT_END`]): a copy of the header written after a footer marker, just before the end-of-stream marker.
+    /// When the footer is not in the last sectors (trailing data), the markers are walked to find it; when there is
+    /// none (interrupted export) or its grain directory is unreadable, the grain tables are rebuilt from the grain
+    /// markers.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the header is invalid or any IO error occurs while reading the file
+    fn read_stream_optimized(file: &Source) -> Result<Self, String> {
+        let mut header_data = [0u8; 80];
+        file.read_exact_at(0, &mut header_data)
+            .map_err(|e| format!("Error reading sparse extent file: {}", e))?;
+        let header = VMDKSparseFileHeader::parse_sparse_header(&header_data)?;
+        if header.grain_directory_sector != GD_AT_END {
+            return Self::read_from_file(file, &header);
         }
-        let mut grain_table_entries = Vec::with_capacity(
-            header.number_of_grain_table_entries as usize * grain_directory_entry_count as usize,
-        );
-        for entry in grain_directory {
-            file.seek(SeekFrom::Start(u64::from(entry) * SECTOR_SIZE))
-                .map_err(|e| format!("Unable to navigate the sparse extent file: {}", e))?;
-            for _ in 0..header.number_of_grain_table_entries {
-                let mut grain_buf = [0u8; 4];
-                file.read(&mut grain_buf)
-                    .map_err(|e| format!("Error reading sparse extent file: {}", e))?;
-                grain_table_entries.push(u32::from_le_bytes(grain_buf));
-            }
+        let mut scan = None;
+        let footer = match read_stream_footer(file)? {
+            Some(footer) => Some(footer),
+            None => scan.insert(StreamScan::read(file, &header)?).footer.clone(),
+        };
+        match footer {
+            Some(footer) => match Self::read_from_file(file, &footer) {
+                Ok(metadata) => return Ok(metadata),
+                Err(e) => warn!(
+                    "Unreadable streamOptimized grain directory ({}), rebuilding the grain tables from the grain markers",
+                    e
+                ),
+            },
+            None => warn!(
+                "streamOptimized extent has no footer, rebuilding the grain tables from the grain markers"
+            ),
+        }
+        let scan = match scan {
+            Some(scan) => scan,
+            None => StreamScan::read(file, &header)?,
+        };
+        Self::from_grain_markers(file, &header, &scan.grains)
+    }
+
+    /// Builds the grain directory and tables of a streamOptimized extent from the virtual sector and file sector of
+    /// each of its grain markers.
+    fn from_grain_markers(
+        file: &Source,
+        header: &VMDKSparseFileHeader,
+        grains: &[(u64, u32)],
+    ) -> Result<Self, String> {
+        let per_table = header.number_of_grain_table_entries as u64;
+        let table_count = header.capacity.div_ceil(per_table * header.grain_number);
+        let file_size = file
+            .len()
+            .map_err(|e| format!("Error reading sparse extent file: {}", e))?;
+        if table_count.saturating_mul(4) > file_size {
+            return Err(format!(
+                "Grain directory of {} entries does not fit in the sparse extent file",
+                table_count
+            ));
+        }
+        let mut tables: Vec<Option<Box<[u32]>>> = vec![None; table_count as usize];
+        for &(sector, grain_sector) in grains {
+            let grain = sector / header.grain_number;
+            let Some(table) = tables.get_mut((grain / per_table) as usize) else {
+                warn!(
+                    "Grain marker for sector {} past the capacity of the extent",
+                    sector
+                );
+                continue;
+            };
+            table.get_or_insert_with(|| vec![0; per_table as usize].into_boxed_slice())
+                [(grain % per_table) as usize] = grain_sector;
         }
         Ok(VMDKSparseExtentMetadata {
             header: header.clone(),
-            grain_directory: grain_table_entries,
+            grain_directory: tables.iter().map(|t| u32::from(t.is_some())).collect(),
+            grain_tables: tables
+                .into_iter()
+                .map(|t| t.map_or_else(OnceLock::new, OnceLock::from))
+                .collect(),
+            rebuilt: true,
+        })
+    }
+
+    /// Returns the grain table `table`, reading it from `file` in one go the first time, or None when it is not
+    /// allocated (all of its grains are sparse, or inherited from the parent).
+    fn grain_table(
+        &self,
+        file: &Source,
+        table: usize,
+        io: &IoCounters,
+    ) -> io::Result<Option<&[u32]>> {
+        let sector = *self.grain_directory.get(table).ok_or_else(|| {
+            io::Error::other(format!("Grain directory entry not found: {}", table))
+        })?;
+        if sector == 0 {
+            return Ok(None);
+        }
+        if let Some(entries) = self.grain_tables[table].get() {
+            io.cache_hit();
+            return Ok(Some(entries));
+        }
+        io.cache_miss();
+        let mut raw_table = vec![0u8; self.header.number_of_grain_table_entries as usize * 4];
+        let _span = io_span!("vmdk.grain_table", table, len = raw_table.len());
+        file.read_exact_at(sector as u64 * SECTOR_SIZE, &mut raw_table)?;
+        let entries = raw_table
+            .chunks_exact(4)
+            .map(|entry| u32::from_le_bytes(entry.try_into().unwrap()))
+            .collect();
+        Ok(Some(self.grain_tables[table].get_or_init(|| entries)))
+    }
+
+    /// Returns the grain table entry of `grain`: the sector of its data, 0 for a sparse grain.
+    fn grain_entry(&self, file: &Source, grain: u64, io: &IoCounters) -> io::Result<u32> {
+        let per_table = self.header.number_of_grain_table_entries as u64;
+        let table = self.grain_table(file, (grain / per_table) as usize, io)?;
+        Ok(table.map_or(0, |entries| entries[(grain % per_table) as usize]))
+    }
+
+    /// Calls `f` with the grain index range and entry of every grain of every allocated grain table, and with the
+    /// whole grain range and a 0 entry for every unallocated one.
+    fn for_each_grain(
+        &self,
+        file: &Source,
+        io: &IoCounters,
+        mut f: impl FnMut(Range<u64>, u32),
+    ) -> io::Result<()> {
+        let per_table = self.header.number_of_grain_table_entries as u64;
+        for table in 0..self.grain_directory.len() {
+            let first = table as u64 * per_table;
+            match self.grain_table(file, table, io)? {
+                Some(entries) => {
+                    for (i, &entry) in entries.iter().enumerate() {
+                        f(first + i as u64..first + i as u64 + 1, entry);
+                    }
+                }
+                None => f(first..first + per_table, 0),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How a seSparse grain table entry maps its grain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SeSparseGrain {
+    /// Not written in this extent: read from the parent, zeros without one.
+    Unallocated,
+    /// Zeroed, or unmapped by the guest (SCSI UNMAP): reads as zeros.
+    Zero,
+    /// Stored at this byte offset of the extent file.
+    Data(u64),
+}
+
+/// The state of a seSparse extent file: the geometry of its constant header, its grain directory and the grain
+/// tables read so far.
+///
+/// All the fields of the constant and volatile headers are 64-bit little-endian; offsets and sizes are in sectors.
+/// See also: the seSparse support of QEMU (`block/vmdk.c`), the only public description of the format.
+#[derive(Clone, Debug)]
+struct VMDKSeSparseMetadata {
+    /// Size of the extent, in sectors
+    capacity: u64,
+    /// Size of a grain, in sectors (8)
+    grain_sectors: u64,
+    /// Entries of a grain table (4096)
+    grain_table_entries: u64,
+    /// First sector of the grain tables
+    grain_tables_sector: u64,
+    /// First sector of the grains
+    grains_sector: u64,
+    /// Whether the volatile header asks for the journal to be replayed: the extent was not closed cleanly and the
+    /// last metadata updates are only in the journal
+    replay_journal: bool,
+    /// The grain directory, one entry per grain table
+    grain_directory: Vec<u64>,
+    /// The grain tables, indexed like the grain directory, read on first use
+    grain_tables: Vec<OnceLock<Box<[u64]>>>,
+}
+
+impl VMDKSeSparseMetadata {
+    /// Parses the constant and volatile headers of a seSparse extent file and reads its grain directory.
+    fn read_from_file(file: &Source) -> Result<Self, String> {
+        let read_err = |e: io::Error| format!("Error reading seSparse extent file: {}", e);
+        let mut header = [0u8; 512];
+        file.read_exact_at(0, &mut header).map_err(read_err)?;
+        let field = |data: &[u8], index: usize| {
+            u64::from_le_bytes(data[index * 8..index * 8 + 8].try_into().unwrap())
+        };
+        if field(&header, 0) != SESPARSE_CONST_HEADER_MAGIC {
+            return Err("Invalid seSparse constant header magic".to_string());
+        }
+        if field(&header, 1) != SESPARSE_VERSION {
+            return Err(format!(
+                "Unsupported seSparse version {:#x}",
+                field(&header, 1)
+            ));
+        }
+        let capacity = field(&header, 2);
+        let grain_sectors = field(&header, 3);
+        let grain_table_sectors = field(&header, 4);
+        if grain_sectors == 0 || grain_sectors > MAX_GRAIN_SECTORS {
+            return Err(format!("Invalid seSparse grain size: {}", grain_sectors));
+        }
+        let grain_table_entries = grain_table_sectors * SECTOR_SIZE / 8;
+        if grain_table_entries == 0 || grain_table_entries > MAX_GRAIN_TABLE_ENTRIES as u64 {
+            return Err(format!(
+                "Invalid seSparse grain table size: {} sectors",
+                grain_table_sectors
+            ));
+        }
+        if field(&header, 5) != 0 {
+            return Err(format!(
+                "Unsupported seSparse flags {:#x}",
+                field(&header, 5)
+            ));
+        }
+        let volatile_header_sector = field(&header, 10);
+        let grain_directory_sector = field(&header, 16);
+        let grain_directory_sectors = field(&header, 17);
+        let grain_tables_sector = field(&header, 18);
+        let grains_sector = field(&header, 24);
+
+        let mut volatile = [0u8; 32];
+        file.read_exact_at(
+            volatile_header_sector.saturating_mul(SECTOR_SIZE),
+            &mut volatile,
+        )
+        .map_err(read_err)?;
+        if field(&volatile, 0) != SESPARSE_VOLATILE_HEADER_MAGIC {
+            return Err("Invalid seSparse volatile header magic".to_string());
+        }
+        let replay_journal = field(&volatile, 3) != 0;
+        if replay_journal {
+            warn!("seSparse extent was not closed cleanly, metadata changes left in its journal are not replayed");
+        }
+
+        let grain_directory_entry_count = capacity.div_ceil(grain_table_entries * grain_sectors);
+        if grain_directory_entry_count > grain_directory_sectors.saturating_mul(SECTOR_SIZE / 8) {
+            return Err(format!(
+                "seSparse grain directory of {} sectors is too small for {} grain tables",
+                grain_directory_sectors, grain_directory_entry_count
+            ));
+        }
+        let file_size = file.len().map_err(read_err)?;
+        if grain_directory_entry_count.saturating_mul(8) > file_size {
+            return Err(format!(
+                "Grain directory of {} entries does not fit in the seSparse extent file",
+                grain_directory_entry_count
+            ));
+        }
+        let mut raw_directory = vec![0u8; grain_directory_entry_count as usize * 8];
+        file.read_exact_at(
+            grain_directory_sector.saturating_mul(SECTOR_SIZE),
+            &mut raw_directory,
+        )
+        .map_err(read_err)?;
+        let grain_directory: Vec<u64> = raw_directory
+            .chunks_exact(8)
+            .map(|entry| u64::from_le_bytes(entry.try_into().unwrap()))
+            .collect();
+        Ok(VMDKSeSparseMetadata {
+            capacity,
+            grain_sectors,
+            grain_table_entries,
+            grain_tables_sector,
+            grains_sector,
+            replay_journal,
+            grain_tables: grain_directory.iter().map(|_| OnceLock::new()).collect(),
+            grain_directory,
         })
     }
+
+    /// Returns the grain table `table`, reading it the first time, or None when it is not allocated.
+    fn grain_table(
+        &self,
+        file: &Source,
+        table: usize,
+        io: &IoCounters,
+    ) -> io::Result<Option<&[u64]>> {
+        let entry = *self.grain_directory.get(table).ok_or_else(|| {
+            io::Error::other(format!("Grain directory entry not found: {}", table))
+        })?;
+        if entry == 0 {
+            return Ok(None);
+        }
+        if entry & 0xffffffff00000000 != SESPARSE_GRAIN_TABLE_ALLOCATED {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid seSparse grain directory entry {:#x}", entry),
+            ));
+        }
+        if let Some(entries) = self.grain_tables[table].get() {
+            io.cache_hit();
+            return Ok(Some(entries));
+        }
+        io.cache_miss();
+        let table_bytes = self.grain_table_entries * 8;
+        let offset = self.grain_tables_sector * SECTOR_SIZE + (entry & 0xffffffff) * table_bytes;
+        let mut raw_table = vec![0u8; table_bytes as usize];
+        let _span = io_span!("vmdk.grain_table", table, len = raw_table.len());
+        file.read_exact_at(offset, &mut raw_table)?;
+        let entries = raw_table
+            .chunks_exact(8)
+            .map(|entry| u64::from_le_bytes(entry.try_into().unwrap()))
+            .collect();
+        Ok(Some(self.grain_tables[table].get_or_init(|| entries)))
+    }
+
+    /// Decodes a grain table entry: the type in the top nibble, and for allocated grains a 60-bit grain index whose
+    /// top 12 bits are stored below the type.
+    fn decode(&self, entry: u64) -> io::Result<SeSparseGrain> {
+        match entry >> 60 {
+            0 if entry == 0 => Ok(SeSparseGrain::Unallocated),
+            1 | 2 => Ok(SeSparseGrain::Zero),
+            3 => {
+                let index =
+                    ((entry & 0x0fff000000000000) >> 48) | ((entry & 0x0000ffffffffffff) << 12);
+                let sector = index
+                    .checked_mul(self.grain_sectors)
+                    .and_then(|sector| sector.checked_add(self.grains_sector))
+                    .and_then(|sector| sector.checked_mul(SECTOR_SIZE));
+                sector.map(SeSparseGrain::Data).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("seSparse grain index out of range: {:#x}", entry),
+                    )
+                })
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid seSparse grain table entry {:#x}", entry),
+            )),
+        }
+    }
+
+    /// Returns how `grain` is stored.
+    fn grain(&self, file: &Source, grain: u64, io: &IoCounters) -> io::Result<SeSparseGrain> {
+        let table = self.grain_table(file, (grain / self.grain_table_entries) as usize, io)?;
+        match table {
+            Some(entries) => self.decode(entries[(grain % self.grain_table_entries) as usize]),
+            None => Ok(SeSparseGrain::Unallocated),
+        }
+    }
+
+    /// Calls `f` with the grain index range and mapping of every grain of every allocated grain table, and with the
+    /// whole grain range of every unallocated one.
+    fn for_each_grain(
+        &self,
+        file: &Source,
+        io: &IoCounters,
+        mut f: impl FnMut(Range<u64>, SeSparseGrain),
+    ) -> io::Result<()> {
+        let per_table = self.grain_table_entries;
+        for table in 0..self.grain_directory.len() {
+            let first = table as u64 * per_table;
+            match self.grain_table(file, table, io)? {
+                Some(entries) => {
+                    for (i, &entry) in entries.iter().enumerate() {
+                        f(first + i as u64..first + i as u64 + 1, self.decode(entry)?);
+                    }
+                }
+                None => f(first..first + per_table, SeSparseGrain::Unallocated),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads and parses the change tracking file at `path`.
+fn read_change_tracking(fs: &dyn FileSystem, path: &Path) -> Result<VMDKChangeTracking, String> {
+    let file = fs.open(path).map_err(|e| e.to_string())?;
+    let mut data = vec![0u8; file.len().map_err(|e| e.to_string())? as usize];
+    file.read_exact_at(0, &mut data)
+        .map_err(|e| e.to_string())?;
+    VMDKChangeTracking::parse(&data)
 }
 
 /// Reads data from a RAW type extent
@@ -717,9 +1532,25 @@ impl VMDKSparseExtentMetadata {
 ///
 /// This function takes a handle to the RAW file we want to read from and the offset from which to start reading.
 /// The data read from the RAW file is then stored in the provided buffer. An `io::Result<usize>` is returned indicating the number of bytes read.
-fn read_raw_extent(file: &mut File, buf: &mut [u8], start_offset: u64) -> io::Result<usize> {
-    file.seek(io::SeekFrom::Start(start_offset))?;
-    file.read(buf)
+fn read_raw_extent(file: &Source, buf: &mut [u8], start_offset: u64) -> io::Result<usize> {
+    file.read_at(start_offset, buf)
+}
+
+/// Reads data from the parent disk of a delta link at the given disk offset.
+///
+/// The parent may be smaller than the child (the child disk may have been grown), so anything beyond the end of the
+/// parent reads as zeros.
+fn read_parent_data(parent: &VMDK, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+    let mut done = 0;
+    while done < buf.len() {
+        let n = parent.read_at(offset + done as u64, &mut buf[done..])?;
+        if n == 0 {
+            break;
+        }
+        done += n;
+    }
+    buf[done..].fill(0);
+    Ok(())
 }
 
 /// Read data from a sparse extent
@@ -729,73 +1560,90 @@ fn read_raw_extent(file: &mut File, buf: &mut [u8], start_offset: u64) -> io::Re
 /// This function takes a handle to the sparse file we want to read from and the offset in a similar way that `read_raw_extend` does.
 /// To do so, the sparse file is "flattened" to fill the buffer in a linear manner (as the sparse file stores data in a non-linear way).
 /// An `io::Result<usize>` is returned indicating the number of bytes read.
+///
+/// When the disk is a delta link, grains absent from the extent are read from `parent`; `extent_offset` is the offset
+/// of the extent on the virtual disk, used to translate extent offsets to parent disk offsets.
+///
+/// Compressed grains are inflated whole and kept in the grain cache of `extent`, so that reading a grain piece by piece (or again)
+/// does not inflate it every time.
 fn read_sparse_extent(
-    file: &mut File,
+    extent: &VMDKExtentFile,
     buf: &mut [u8],
     start_offset: u64,
     sparse_metadata: &VMDKSparseExtentMetadata,
+    extent_offset: u64,
+    parent: Option<&VMDK>,
+    io: &IoCounters,
 ) -> io::Result<usize> {
+    let file = &extent.file;
     let grain_size_in_bytes = sparse_metadata.header.grain_number * SECTOR_SIZE;
     let first_grain = start_offset / grain_size_in_bytes;
     let last_grain = (start_offset + buf.len() as u64).div_ceil(grain_size_in_bytes);
     let grain_range = first_grain..last_grain;
     let mut read_size = 0;
     for grain in grain_range {
-        let sector_number =
-            *sparse_metadata
-                .grain_directory
-                .get(grain as usize)
-                .ok_or(io::Error::other(
-                    format!("Grain directory entry not found: {}", grain),
-                ))?;
-        if sector_number == 0 {
-            // The grain is sparse
+        let sector_number = sparse_metadata.grain_entry(file, grain, io)?;
+        let zeroed_grain = sector_number == ZEROED_GRAIN_TABLE_ENTRY
+            && sparse_metadata.header.flags & FLAG_USE_ZEROED_GRAIN_TABLE
+                == FLAG_USE_ZEROED_GRAIN_TABLE;
+        if sector_number == 0 || zeroed_grain {
+            // The grain is sparse: inherited from the parent if any, zeroes otherwise
             let remaining_buffer_size = buf.len() - read_size;
-            if grain == first_grain {
-                let additional_offset = start_offset - (grain * grain_size_in_bytes);
-                let upper_bound = min(
-                    (grain_size_in_bytes - additional_offset) as usize,
-                    remaining_buffer_size,
-                );
-                buf[read_size..read_size + upper_bound].fill(0);
-                read_size += upper_bound;
+            let additional_offset = if grain == first_grain {
+                start_offset - (grain * grain_size_in_bytes)
             } else {
-                let remaining_read = min(remaining_buffer_size, grain_size_in_bytes as usize);
-                buf[read_size..read_size + remaining_read].fill(0);
-                read_size += remaining_read;
+                0
+            };
+            let upper_bound = min(
+                (grain_size_in_bytes - additional_offset) as usize,
+                remaining_buffer_size,
+            );
+            let grain_buf = &mut buf[read_size..read_size + upper_bound];
+            match parent {
+                Some(parent) if !zeroed_grain => read_parent_data(
+                    parent,
+                    extent_offset + grain * grain_size_in_bytes + additional_offset,
+                    grain_buf,
+                )?,
+                _ => grain_buf.fill(0),
             }
+            read_size += upper_bound;
         } else {
             // The grain is not sparse, read the data from the file
-            file.seek(io::SeekFrom::Start(sector_number as u64 * SECTOR_SIZE))?;
-
+            let grain_offset = sector_number as u64 * SECTOR_SIZE;
             let remaining_buffer_size = buf.len() - read_size;
             let mut upper_bound = min(remaining_buffer_size, grain_size_in_bytes as usize);
             if sparse_metadata.header.flags & FLAG_HAS_COMPRESSED_GRAIN_DATA
                 == FLAG_HAS_COMPRESSED_GRAIN_DATA
             {
-                // Grain data is compressed, uncompress it to read
-                // We start in a grain marker
-                // Skip the sector number and the compressed data size, at this stage we should know where we are
-                // thanks to the grain table
-                // 1. Position on the grain-marker header
-                file.seek(SeekFrom::Start(sector_number as u64 * SECTOR_SIZE))?;
-
-                // 12-byte marker: 8-byte virtual-LBA + 4-byte compressed-size
-                let mut hdr = [0u8; 12];
-                file.read_exact(&mut hdr)?;
-                let comp_len = u32::from_le_bytes(hdr[8..12].try_into().unwrap()) as usize;
-
-                // 2. Read the compressed payload
-                let mut comp = vec![0u8; comp_len];
-                file.read_exact(&mut comp)?;
-
-                // 3. Inflate the whole grain
-                let mut inflater = ZlibDecoder::new(&comp[..]);
-                let mut grain_buf =
-                    vec![0u8; (sparse_metadata.header.grain_number * SECTOR_SIZE) as usize];
-                let bytes_read = inflater.read(&mut grain_buf[..])?;
-
-                // 4. Copy slice we were asked for + zero-pad if needed
+                // Grain data is compressed: inflate the whole grain, unless it still is in the cache
+                let cached = extent.grain_cache.lock().unwrap().get(grain);
+                let grain_buf = match cached {
+                    Some(data) => {
+                        io.cache_hit();
+                        data
+                    }
+                    None => {
+                        io.cache_miss();
+                        let _span = io_span!("vmdk.inflate_grain", grain);
+                        let data = Arc::new(inflate_grain(
+                            file,
+                            grain_offset,
+                            grain * sparse_metadata.header.grain_number,
+                            grain_size_in_bytes as usize,
+                            io,
+                        )?);
+                        extent
+                            .grain_cache
+                            .lock()
+                            .unwrap()
+                            .insert(grain, data.clone());
+                        data
+                    }
+                };
+                let bytes_read = grain_buf.len();
+
+                // Copy the slice we were asked for + zero-pad if needed
                 let mut upper_bound = min(remaining_buffer_size, grain_size_in_bytes as usize);
                 let additional_offset = if grain == first_grain {
                     let off = start_offset - (grain * grain_size_in_bytes);
@@ -823,80 +1671,336 @@ fn read_sparse_extent(
                 read_size += upper_bound;
             } else {
                 // Data in raw format, read directly
+                let mut additional_offset = 0;
                 if grain == first_grain {
-                    let additional_offset = start_offset - (grain * grain_size_in_bytes);
-                    // Panic shouldn't occur as it is highly unlikely that the additional offset exceeds the i64 bounds
-                    file.seek(io::SeekFrom::Current(additional_offset.try_into().unwrap()))?;
+                    additional_offset = start_offset - (grain * grain_size_in_bytes);
                     if additional_offset + upper_bound as u64 > grain_size_in_bytes {
                         upper_bound = (grain_size_in_bytes - additional_offset) as usize;
                     }
                 }
-                read_size += file.read(&mut buf[read_size..read_size + upper_bound])?;
+                read_size += file.read_at(
+                    grain_offset + additional_offset,
+                    &mut buf[read_size..read_size + upper_bound],
+                )?;
             }
         }
     }
     Ok(read_size)
 }
 
-/// Stores a VMDK extent file handle and the associated extent information for reading actual data.
+/// Inflates the compressed grain stored at `grain_offset`, returning at most `grain_size` bytes.
 ///
-/// This is a struct dedicated to maintain the state of read of a given extent file.
-struct VMDKExtentFile {
-    /// The extent description for this file
-    extent_description: VMDKExtentDescriptor,
-    /// The file handle for the extent file
-    file: File,
-    /// Metadata for sparse extent files, Some if this is a sparse extent file
-    sparse_extent_metadata: Option<VMDKSparseExtentMetadata>,
+/// The grain starts with a 12-byte marker, its virtual sector and the size of the compressed data, followed by a
+/// zlib stream. A marker for another sector than `sector` (a grain table entry pointing to the wrong grain, or
+/// into metadata) is an error rather than data read at the wrong place.
+fn inflate_grain(
+    file: &Source,
+    grain_offset: u64,
+    sector: u64,
+    grain_size: usize,
+    io: &IoCounters,
+) -> io::Result<Vec<u8>> {
+    let mut raw = [0u8; 16];
+    file.read_exact_at(grain_offset, &mut raw[..12])?;
+    let marker = StreamMarker::parse(&raw);
+    if marker.value != sector || marker.size == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "No grain marker for sector {} at offset {:#x} (found sector {}, {} bytes)",
+                sector, grain_offset, marker.value, marker.size
+            ),
+        ));
+    }
+    // zlib may expand incompressible data slightly, never this much.
+    let compressed_size = marker.size as usize;
+    if compressed_size > 2 * grain_size + SECTOR_SIZE as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Compressed grain of {} bytes at offset {:#x} is larger than a grain",
+                compressed_size, grain_offset
+            ),
+        ));
+    }
+    let mut compressed = vec![0u8; compressed_size];
+    file.read_exact_at(grain_offset + 12, &mut compressed)?;
+
+    let started = Instant::now();
+    let mut data = Vec::with_capacity(grain_size);
+    ZlibDecoder::new(&compressed[..])
+        .take(grain_size as u64)
+        .read_to_end(&mut data)?;
+    io.decompressed(started.elapsed());
+    Ok(data)
 }
 
-impl VMDKExtentFile {
-    /// Reads data in the range specified by the start and end positions (relative to the first sector defined for the extent file) and stores the data into the provided buffer.
-    ///
-    /// This function returns the actual data stored in the extent file after any parsing or decrompression if necessary.
-    ///
-    /// # Errors
-    ///
-    /// Errors if any IO error occurs while reading or if the provided range exceeds the extent file's limits. Also errors if the extent type is not supported.
-    fn read_data(&mut self, start_pos: u64, buf: &mut [u8]) -> io::Result<usize> {
-        match self.extent_description.extent_type {
-            VMDKExtentType::Flat => read_raw_extent(&mut self.file, buf, start_pos),
-            VMDKExtentType::Sparse => read_sparse_extent(
-                &mut self.file,
-                buf,
-                start_pos,
-                self.sparse_extent_metadata.as_ref().ok_or_else(|| {
-                    io::Error::new(
-                        io::ErrorKind::InvalidInput,
-                        "No sparse extent metadata available",
-                    )
-                })?,
-            ),
-            VMDKExtentType::Zero => {
-                // Zero out the buffer
-                buf.fill(0);
-                Ok(buf.len())
-            }
-            VMDKExtentType::Vmfs => read_raw_extent(&mut self.file, buf, start_pos),
-            VMDKExtentType::VmfsSparse => Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "VMFS Sparse extent type not yet supported",
-            )),
-            VMDKExtentType::VmfsRdm => Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "Unsupported extent type VMFS RDM",
-            )),
-            VMDKExtentType::VmfsRaw => Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "Unsupported extent type VMFS RAW",
-            )),
+/// Least recently used decompressed grains of an extent, up to a budget in bytes.
+struct GrainCache {
+    grains: HashMap<u64, Arc<Vec<u8>>>,
+    /// Grain numbers, least recently used first.
+    order: VecDeque<u64>,
+    /// Bytes held by `grains`.
+    size: usize,
+    budget: usize,
+}
+
+impl GrainCache {
+    fn new(budget: usize) -> Self {
+        Self {
+            grains: HashMap::new(),
+            order: VecDeque::new(),
+            size: 0,
+            budget,
         }
     }
-}
 
-/// Compression method used for data stored in VMDK sparse files
-///
-/// See also: https://github.com/libyal/libvmdk/blob/main/documentation/VMWare%20Virtual%20Disk%20Format%20(VMDK).asciidoc#412-compression-method
+    fn get(&mut self, grain: u64) -> Option<Arc<Vec<u8>>> {
+        let data = self.grains.get(&grain)?.clone();
+        self.order.retain(|&g| g != grain);
+        self.order.push_back(grain);
+        Some(data)
+    }
+
+    fn insert(&mut self, grain: u64, data: Arc<Vec<u8>>) {
+        self.size += data.len();
+        match self.grains.insert(grain, data) {
+            Some(previous) => self.size -= previous.len(),
+            None => self.order.push_back(grain),
+        }
+        // Always keep the grain just inserted, whatever its size.
+        while self.size > self.budget && self.order.len() > 1 {
+            if let Some(evicted) = self.order.pop_front() {
+                self.size -= self.grains.remove(&evicted).map_or(0, |data| data.len());
+            }
+        }
+    }
+}
+
+/// A marker of a streamOptimized extent. A grain starts with the 12 first bytes of one: the virtual sector of the grain
+/// and the size of the compressed data that follows. Metadata starts with a sector holding one with a size of 0, its
+/// type, and in `value` the number of sectors that follow (1 for the footer, 0 for the end-of-stream marker).
+struct StreamMarker {
+    value: u64,
+    size: u32,
+    kind: u32,
+}
+
+impl StreamMarker {
+    fn parse(raw: &[u8]) -> Self {
+        Self {
+            value: u64::from_le_bytes(raw[0..8].try_into().unwrap()),
+            size: u32::from_le_bytes(raw[8..12].try_into().unwrap()),
+            kind: u32::from_le_bytes(raw[12..16].try_into().unwrap()),
+        }
+    }
+
+    fn is_metadata(&self, kind: u32) -> bool {
+        self.size == 0 && self.kind == kind
+    }
+}
+
+/// Reads the footer of a streamOptimized extent where exporters write it: in the second to last sector, after a
+/// footer marker and before the end-of-stream marker. Returns None when the file does not end this way.
+fn read_stream_footer(file: &Source) -> Result<Option<VMDKSparseFileHeader>, String> {
+    let file_size = file
+        .len()
+        .map_err(|e| format!("Error reading sparse extent file: {}", e))?;
+    if file_size < 3 * SECTOR_SIZE {
+        return Ok(None);
+    }
+    let mut tail = [0u8; 3 * SECTOR_SIZE as usize];
+    file.read_exact_at(file_size - 3 * SECTOR_SIZE, &mut tail)
+        .map_err(|e| format!("Error reading sparse extent file: {}", e))?;
+    let footer_marker = StreamMarker::parse(&tail[..16]);
+    let end_marker = StreamMarker::parse(&tail[1024..1040]);
+    if !footer_marker.is_metadata(MARKER_FOOTER) || !end_marker.is_metadata(MARKER_EOS) {
+        return Ok(None);
+    }
+    Ok(VMDKSparseFileHeader::parse_sparse_header(&tail[512..1024]).ok())
+}
+
+/// What walking the markers of a streamOptimized extent found.
+struct StreamScan {
+    /// Virtual sector and file sector of every grain, in file order.
+    grains: Vec<(u64, u32)>,
+    /// The header copy following the footer marker, if any.
+    footer: Option<VMDKSparseFileHeader>,
+}
+
+impl StreamScan {
+    /// Walks the markers from the end of the header overhead to the end-of-stream marker, the end of the file or
+    /// the first sector that is not a marker.
+    fn read(file: &Source, header: &VMDKSparseFileHeader) -> Result<Self, String> {
+        let file_size = file
+            .len()
+            .map_err(|e| format!("Error reading sparse extent file: {}", e))?;
+        let mut scan = StreamScan {
+            grains: Vec::new(),
+            footer: None,
+        };
+        let mut sector = header.number_of_sectors.max(1);
+        let mut raw = [0u8; 16];
+        while sector.saturating_mul(SECTOR_SIZE).saturating_add(16) <= file_size {
+            file.read_exact_at(sector * SECTOR_SIZE, &mut raw)
+                .map_err(|e| format!("Error reading sparse extent file: {}", e))?;
+            let marker = StreamMarker::parse(&raw);
+            if marker.size != 0 {
+                let grain_sector = u32::try_from(sector).map_err(|_| {
+                    format!("Grain at sector {} out of the grain table range", sector)
+                })?;
+                scan.grains.push((marker.value, grain_sector));
+                sector += (12 + marker.size as u64).div_ceil(SECTOR_SIZE);
+                continue;
+            }
+            match marker.kind {
+                MARKER_EOS => break,
+                MARKER_GRAIN_TABLE | MARKER_GRAIN_DIRECTORY => {
+                    sector = sector.saturating_add(1).saturating_add(marker.value);
+                }
+                MARKER_FOOTER => {
+                    let mut footer = [0u8; 80];
+                    file.read_exact_at((sector + 1) * SECTOR_SIZE, &mut footer)
+                        .map_err(|e| format!("Error reading sparse extent file: {}", e))?;
+                    scan.footer = VMDKSparseFileHeader::parse_sparse_header(&footer).ok();
+                    break;
+                }
+                kind => {
+                    warn!(
+                        "Unknown streamOptimized marker type {} at sector {}, stopping there",
+                        kind, sector
+                    );
+                    break;
+                }
+            }
+        }
+        debug!("Found {} grain markers", scan.grains.len());
+        Ok(scan)
+    }
+}
+
+/// Reads data from a seSparse extent, grain by grain.
+///
+/// Grains are never compressed. Unallocated grains of a delta link are read from `parent`, at the same offset of
+/// the virtual disk (`extent_offset` is the offset of the extent on it).
+fn read_se_sparse_extent(
+    file: &Source,
+    buf: &mut [u8],
+    start_offset: u64,
+    metadata: &VMDKSeSparseMetadata,
+    extent_offset: u64,
+    parent: Option<&VMDK>,
+    io: &IoCounters,
+) -> io::Result<usize> {
+    let grain_size = metadata.grain_sectors * SECTOR_SIZE;
+    let mut done = 0;
+    while done < buf.len() {
+        let offset = start_offset + done as u64;
+        let grain = offset / grain_size;
+        let within = offset % grain_size;
+        let len = min((grain_size - within) as usize, buf.len() - done);
+        let part = &mut buf[done..done + len];
+        match metadata.grain(file, grain, io)? {
+            SeSparseGrain::Unallocated => match parent {
+                Some(parent) => read_parent_data(parent, extent_offset + offset, part)?,
+                None => part.fill(0),
+            },
+            SeSparseGrain::Zero => part.fill(0),
+            SeSparseGrain::Data(data) => file.read_exact_at(data + within, part)?,
+        }
+        done += len;
+    }
+    Ok(done)
+}
+
+/// Stores a VMDK extent file handle and the associated extent information for reading actual data.
+///
+/// This is a struct dedicated to maintain the state of read of a given extent file.
+struct VMDKExtentFile {
+    /// The extent description for this file
+    extent_description: VMDKExtentDescriptor,
+    /// The file handle for the extent file
+    file: Source,
+    /// Where the extent file was found
+    path: PathBuf,
+    /// Metadata for sparse extent files, Some if this is a sparse extent file
+    sparse_extent_metadata: Option<VMDKSparseExtentMetadata>,
+    /// Metadata for seSparse extent files, Some if this is a seSparse extent file
+    se_sparse_metadata: Option<VMDKSeSparseMetadata>,
+    /// Recently decompressed grains of a compressed (streamOptimized) sparse extent, shared by the clones
+    grain_cache: Mutex<GrainCache>,
+}
+
+impl VMDKExtentFile {
+    /// Reads data in the range specified by the start and end positions (relative to the first sector defined for the extent file) and stores the data into the provided buffer.
+    ///
+    /// This function returns the actual data stored in the extent file after any parsing or decrompression if necessary.
+    ///
+    /// # Errors
+    ///
+    /// Errors if any IO error occurs while reading or if the provided range exceeds the extent file's limits. Also errors if the extent type is not supported.
+    fn read_data(
+        &self,
+        start_pos: u64,
+        buf: &mut [u8],
+        parent: Option<&VMDK>,
+        io: &IoCounters,
+    ) -> io::Result<usize> {
+        match self.extent_description.extent_type {
+            VMDKExtentType::Flat => read_raw_extent(&self.file, buf, start_pos),
+            VMDKExtentType::Sparse => read_sparse_extent(
+                self,
+                buf,
+                start_pos,
+                self.sparse_extent_metadata.as_ref().ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "No sparse extent metadata available",
+                    )
+                })?,
+                self.extent_description.extent_start_sector.unwrap_or(0) * SECTOR_SIZE,
+                parent,
+                io,
+            ),
+            VMDKExtentType::Zero => {
+                // Zero out the buffer
+                buf.fill(0);
+                Ok(buf.len())
+            }
+            VMDKExtentType::Vmfs => read_raw_extent(&self.file, buf, start_pos),
+            VMDKExtentType::SeSparse => read_se_sparse_extent(
+                &self.file,
+                buf,
+                start_pos,
+                self.se_sparse_metadata.as_ref().ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "No seSparse extent metadata available",
+                    )
+                })?,
+                self.extent_description.extent_start_sector.unwrap_or(0) * SECTOR_SIZE,
+                parent,
+                io,
+            ),
+            VMDKExtentType::VmfsSparse => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "VMFS Sparse extent type not yet supported",
+            )),
+            VMDKExtentType::VmfsRdm => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Unsupported extent type VMFS RDM",
+            )),
+            VMDKExtentType::VmfsRaw => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Unsupported extent type VMFS RAW",
+            )),
+        }
+    }
+}
+
+/// Compression method used for data stored in VMDK sparse files
+///
+/// See also: https://github.com/libyal/libvmdk/blob/main/documentation/VMWare%20Virtual%20Disk%20Format%20(VMDK).asciidoc#412-compression-method
 #[derive(Debug, Clone, Serialize, Deserialize)]
 enum VMDKCompressionMethod {
     /// No compression
@@ -962,20 +2066,33 @@ impl VMDKSparseFileHeader {
                 1 => VMDKCompressionMethod::Deflate,
                 _ => return Err("Unsupported compression method".to_string()),
             };
+        let grain_number = u64::from_le_bytes(<[u8; 8]>::try_from(&header_data[20..28]).unwrap());
+        if !grain_number.is_power_of_two() || grain_number > MAX_GRAIN_SECTORS {
+            return Err(format!("Invalid grain size of {} sectors", grain_number));
+        }
+        let number_of_grain_table_entries =
+            u32::from_le_bytes(<[u8; 4]>::try_from(&header_data[44..48]).unwrap());
+        if number_of_grain_table_entries == 0 {
+            return Err("Sparse extent has empty grain tables".to_string());
+        }
+        if number_of_grain_table_entries > MAX_GRAIN_TABLE_ENTRIES {
+            return Err(format!(
+                "Invalid grain table size of {} entries",
+                number_of_grain_table_entries
+            ));
+        }
         Ok(Self {
             version: u32::from_le_bytes(<[u8; 4]>::try_from(&header_data[4..8]).unwrap()),
             flags: u32::from_le_bytes(<[u8; 4]>::try_from(&header_data[8..12]).unwrap()),
             capacity: u64::from_le_bytes(<[u8; 8]>::try_from(&header_data[12..20]).unwrap()),
-            grain_number: u64::from_le_bytes(<[u8; 8]>::try_from(&header_data[20..28]).unwrap()),
+            grain_number,
             embedded_descriptor_sector: u64::from_le_bytes(
                 <[u8; 8]>::try_from(&header_data[28..36]).unwrap(),
             ),
             embedded_descriptor_sectors_count: u64::from_le_bytes(
                 <[u8; 8]>::try_from(&header_data[36..44]).unwrap(),
             ),
-            number_of_grain_table_entries: u32::from_le_bytes(
-                <[u8; 4]>::try_from(&header_data[44..48]).unwrap(),
-            ),
+            number_of_grain_table_entries,
             secondary_grain_directory_sector: u64::from_le_bytes(
                 <[u8; 8]>::try_from(&header_data[48..56]).unwrap(),
             ),
@@ -997,22 +2114,60 @@ impl VMDKSparseFileHeader {
 ///
 /// Errors on file read errors and if there is no embedded descriptor in the file.
 fn get_descriptor_from_sparse(
-    file: &mut File,
+    file: &Source,
     header: &VMDKSparseFileHeader,
 ) -> Result<VMDKDescriptorFile, String> {
+    VMDKDescriptorFile::from_bytes(&read_descriptor_area(file, header)?)
+}
+
+/// Reads the embedded descriptor of a sparse file, without the zeros padding its area.
+fn read_descriptor_area(file: &Source, header: &VMDKSparseFileHeader) -> Result<Vec<u8>, String> {
     if header.embedded_descriptor_sector == 0 || header.embedded_descriptor_sectors_count == 0 {
         return Err("No embedded descriptor file found".to_string());
     }
+    if header.embedded_descriptor_sectors_count > MAX_DESCRIPTOR_SECTORS {
+        return Err(format!(
+            "Embedded descriptor of {} sectors is too large",
+            header.embedded_descriptor_sectors_count
+        ));
+    }
+    let offset = header
+        .embedded_descriptor_sector
+        .checked_mul(SECTOR_SIZE)
+        .ok_or("Invalid embedded descriptor sector")?;
     let mut descriptor_buffer =
         vec![0u8; header.embedded_descriptor_sectors_count as usize * SECTOR_SIZE as usize];
-    file.seek(io::SeekFrom::Start(
-        header.embedded_descriptor_sector * SECTOR_SIZE,
-    ))
-    .and_then(|_| file.read_exact(&mut descriptor_buffer))
-    .map_err(|e| format!("Error reading embedded descriptor file: {}", e))?;
-    let descriptor_string = String::from_utf8_lossy(&descriptor_buffer);
-    let descriptor: VMDKDescriptorFile = descriptor_string.parse()?;
-    Ok(descriptor)
+    file.read_exact_at(offset, &mut descriptor_buffer)
+        .map_err(|e| format!("Error reading embedded descriptor file: {}", e))?;
+    // The descriptor area is padded with zeros.
+    let end = descriptor_buffer
+        .iter()
+        .position(|&b| b == 0)
+        .unwrap_or(descriptor_buffer.len());
+    descriptor_buffer.truncate(end);
+    Ok(descriptor_buffer)
+}
+
+/// Reads the descriptor embedded in the monolithic sparse (or streamOptimized) file at `path`, as it is stored,
+/// without opening the rest of the disk: parsed with [`VMDKDescriptorFile::from_bytes`], it is the starting point
+/// to rewrite a lost or damaged descriptor.
+///
+/// # Errors
+///
+/// Errors if the file cannot be read, is not a sparse file or has no embedded descriptor.
+pub fn read_embedded_descriptor(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    let path = path.as_ref();
+    let file = StdFileSystem.open(path)?;
+    let mut header_data = [0u8; 80];
+    file.read_exact_at(0, &mut header_data)?;
+    VMDKSparseFileHeader::parse_sparse_header(&header_data)
+        .and_then(|header| read_descriptor_area(&file, &header))
+        .map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), e),
+            )
+        })
 }
 
 /// Represents a VMDK virtual disk in memory with the state of the file handles.
@@ -1020,39 +2175,78 @@ pub struct VMDK {
     /// The descriptor file for the volume
     descriptor_file: VMDKDescriptorFile,
     /// List of the extent files for the volume
-    extent_files: Vec<VMDKExtentFile>,
+    extent_files: Arc<[VMDKExtentFile]>,
+    /// Extents whose file could not be opened, read as zeros, see [`VMDK::open_allowing_missing_extents`]
+    missing_extents: Arc<[VMDKExtentDescriptor]>,
     /// The position of the cursor on the disk
     position: u64,
     /// Working directory path
     descriptor_path: PathBuf,
+    /// Size of the descriptor file (or monolithic extent) in bytes
+    descriptor_size: u64,
+    /// The parent disk if this disk is a delta link (snapshot)
+    parent: Option<Arc<VMDK>>,
+    /// The change tracking data, if the descriptor references a readable file
+    change_tracking: Option<VMDKChangeTracking>,
+    /// Logical sector size of the virtual disk, see [`VMDK::get_sector_size`]
+    sector_size: u64,
+    /// Read statistics, shared with the clones and the whole snapshot chain
+    io: Arc<IoCounters>,
+}
+
+/// Options of [`VMDK::open_with`].
+#[derive(Clone, Debug, Default)]
+pub struct VMDKOpenOptions {
+    /// Open the disk even when some of its extent files cannot be opened, see
+    /// [`VMDK::open_allowing_missing_extents`].
+    pub allow_missing_extents: bool,
+    /// Directories where the extent files and parent disks are looked up, in
+    /// order, when they are not where the descriptor says.
+    pub search_paths: Vec<PathBuf>,
+}
+
+/// Locates a file referenced by a descriptor: an extent file, or the parent disk of a delta link from its
+/// `parentFileNameHint`.
+///
+/// The reference is usually relative to the descriptor, but may also be an absolute path, possibly recorded on
+/// another host (with Windows separators). When it does not resolve, the file is looked up by name next to the
+/// descriptor, then in each of the `search_paths`.
+fn resolve_path(
+    fs: &dyn FileSystem,
+    descriptor_path: &Path,
+    reference: &str,
+    search_paths: &[PathBuf],
+) -> Option<PathBuf> {
+    let directory = descriptor_path.parent().unwrap_or(Path::new(""));
+    // An absolute reference replaces the directory when joined.
+    let mut candidates = vec![directory.join(reference)];
+    let file_name = reference.rsplit(['/', '\\']).next().unwrap_or(reference);
+    for directory in std::iter::once(directory).chain(search_paths.iter().map(PathBuf::as_path)) {
+        if !Path::new(reference).is_absolute() {
+            candidates.push(directory.join(reference));
+        }
+        candidates.push(directory.join(file_name));
+    }
+    candidates
+        .into_iter()
+        .find(|candidate| fs.is_file(candidate))
 }
 
 impl Clone for VMDK {
+    /// Extent reads are positional, so clones share the extent handles, the
+    /// grain tables and the parent chain rather than reopening the files.
     fn clone(&self) -> Self {
-        let mut cloned_extent_files = Vec::new();
-        for extent_file in &self.extent_files {
-            if let Some(ref file_name) = extent_file.extent_description.extent_file_name {
-                let extent_file_path = self
-                    .descriptor_path
-                    .parent()
-                    .unwrap_or(Path::new(""))
-                    .join(Path::new(file_name));
-                let file = File::open(extent_file_path);
-                // FIXME: even if it is highly unlikely that and error occurs, we should not silence it if it happens
-                if let Ok(file) = file {
-                    cloned_extent_files.push(VMDKExtentFile {
-                        extent_description: extent_file.extent_description.clone(),
-                        file,
-                        sparse_extent_metadata: extent_file.sparse_extent_metadata.clone(),
-                    });
-                }
-            }
-        }
         Self {
             descriptor_file: self.descriptor_file.clone(),
-            extent_files: cloned_extent_files,
+            extent_files: self.extent_files.clone(),
+            missing_extents: self.missing_extents.clone(),
             position: self.position,
             descriptor_path: self.descriptor_path.clone(),
+            descriptor_size: self.descriptor_size,
+            parent: self.parent.clone(),
+            change_tracking: self.change_tracking.clone(),
+            sector_size: self.sector_size,
+            io: self.io.clone(),
         }
     }
 }
@@ -1065,15 +2259,103 @@ impl VMDK {
     ///
     /// Throws an error if the file at the given path is not a valid VMDK descriptor file or if the specified extent files cannot be opened.
     /// May also throw an error if the encountered extend files are of unrecognized types.
-    pub fn new(file_path: &str) -> Result<VMDK, String> {
+    pub fn new(file_path: &str) -> Result<VMDK, ExhumeBodyError> {
+        Self::open_in(&StdFileSystem, file_path)
+    }
+
+    /// Same as [`VMDK::new`], opening the descriptor, extents and parent
+    /// disks through `fs` instead of the operating system.
+    pub fn open_in(fs: &dyn FileSystem, file_path: &str) -> Result<VMDK, ExhumeBodyError> {
+        Self::open_with(fs, file_path, &VMDKOpenOptions::default())
+    }
+
+    /// Same as [`VMDK::open_in`] with [`VMDKOpenOptions`], e.g. directories where extents moved away from the
+    /// descriptor are looked up:
+    ///
+    /// ```no_run
+    /// # use exhume_body::vfs::StdFileSystem;
+    /// # use exhume_body::vmdk::{VMDKOpenOptions, VMDK};
+    /// # fn main() -> Result<(), exhume_body::ExhumeBodyError> {
+    /// let options = VMDKOpenOptions {
+    ///     search_paths: vec!["/evidence/datastore2".into()],
+    ///     ..Default::default()
+    /// };
+    /// let vmdk = VMDK::open_with(&StdFileSystem, "/evidence/vm/disk.vmdk", &options)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn open_with(
+        fs: &dyn FileSystem,
+        file_path: &str,
+        options: &VMDKOpenOptions,
+    ) -> Result<VMDK, ExhumeBodyError> {
+        Self::open_path(fs, file_path, options, &[]).map_err(ExhumeBodyError::Vmdk)
+    }
+
+    /// Same as [`VMDK::open_in`], but an extent file that cannot be opened (deleted, not copied with the rest of
+    /// the disk) does not fail: the sectors of the extent read as zeros and are listed by
+    /// [`VMDK::missing_ranges`]. Applies to the parent disks as well.
+    pub fn open_allowing_missing_extents(
+        fs: &dyn FileSystem,
+        file_path: &str,
+    ) -> Result<VMDK, ExhumeBodyError> {
+        let options = VMDKOpenOptions {
+            allow_missing_extents: true,
+            ..Default::default()
+        };
+        Self::open_with(fs, file_path, &options)
+    }
+
+    /// Opens the descriptor at `file_path`, the parent of the disks at
+    /// `descendants` (canonical paths of the snapshot chain below it).
+    fn open_path(
+        fs: &dyn FileSystem,
+        file_path: &str,
+        options: &VMDKOpenOptions,
+        descendants: &[PathBuf],
+    ) -> Result<VMDK, String> {
         debug!("Opening and reading VMDK descriptor file: {}", file_path);
 
-        let mut vmdk_file =
-            File::open(file_path).map_err(|e| format!("Error reading descriptor file: {}", e))?;
-        let file_len = vmdk_file
-            .metadata()
-            .map_err(|e| format!("stat failed: {}", e))?
-            .len();
+        let vmdk_file = fs
+            .open(Path::new(file_path))
+            .map_err(|e| format!("Error reading descriptor file: {}", e))?;
+        Self::open(
+            vmdk_file,
+            Some((fs, Path::new(file_path))),
+            options,
+            descendants,
+        )
+    }
+
+    /// Parses a monolithic VMDK (sparse or streamOptimized, descriptor
+    /// embedded in the single extent) from `reader`, e.g. an in-memory buffer
+    /// or an archive member.
+    ///
+    /// # Errors
+    ///
+    /// Disks made of several files and delta links are refused: their extents
+    /// and parents can only be located on the filesystem.
+    pub fn from_reader(reader: impl Read + Seek + Send + 'static) -> Result<VMDK, ExhumeBodyError> {
+        Self::from_source(Source::from_reader(reader))
+    }
+
+    /// Same as [`VMDK::from_reader`] with an already opened [`Source`].
+    pub fn from_source(source: Source) -> Result<VMDK, ExhumeBodyError> {
+        Self::open(source, None, &VMDKOpenOptions::default(), &[]).map_err(ExhumeBodyError::Vmdk)
+    }
+
+    /// Opens the VMDK whose descriptor (or monolithic extent) is `vmdk_file`.
+    /// `location` (file system and descriptor path) locates the extent files
+    /// and parent disk, when known; `descendants` are the disks of the
+    /// snapshot chain it is opened as the parent of.
+    fn open(
+        mut vmdk_file: Source,
+        location: Option<(&dyn FileSystem, &Path)>,
+        options: &VMDKOpenOptions,
+        descendants: &[PathBuf],
+    ) -> Result<VMDK, String> {
+        let file_path = location.map(|(_, path)| path);
+        let file_len = vmdk_file.len().map_err(|e| format!("stat failed: {}", e))?;
 
         // Fast probe
         let probe = probe_vmdk(&mut vmdk_file, file_len)
@@ -1091,7 +2373,7 @@ impl VMDK {
                     .read_exact(&mut header_data)
                     .map_err(|e| format!("Error reading sparse header: {}", e))?;
                 sparse_header = Some(VMDKSparseFileHeader::parse_sparse_header(&header_data)?);
-                get_descriptor_from_sparse(&mut vmdk_file, sparse_header.as_ref().unwrap())?
+                get_descriptor_from_sparse(&vmdk_file, sparse_header.as_ref().unwrap())?
             }
             Some(VmdkProbe::MonolithicSparseAtEnd) => {
                 debug!("Monolithic Sparse VMDK header near EOF, extracting descriptor");
@@ -1104,7 +2386,7 @@ impl VMDK {
                     .read_exact(&mut header_data)
                     .map_err(|e| format!("Error reading tail sparse header: {}", e))?;
                 sparse_header = Some(VMDKSparseFileHeader::parse_sparse_header(&header_data)?);
-                get_descriptor_from_sparse(&mut vmdk_file, sparse_header.as_ref().unwrap())?
+                get_descriptor_from_sparse(&vmdk_file, sparse_header.as_ref().unwrap())?
             }
             Some(VmdkProbe::TextDescriptorLikely) => {
                 debug!("Text descriptor likely; reading a small chunk only");
@@ -1119,9 +2401,7 @@ impl VMDK {
                 let n = vmdk_file
                     .read(&mut buf)
                     .map_err(|e| format!("Error reading descriptor chunk: {}", e))?;
-                let descriptor_contents = String::from_utf8_lossy(&buf[..n]);
-                descriptor_contents
-                    .parse::<VMDKDescriptorFile>()
+                VMDKDescriptorFile::from_bytes(&buf[..n])
                     .map_err(|e| format!("Error parsing descriptor file: {}", e))?
             }
             None => {
@@ -1135,10 +2415,59 @@ impl VMDK {
         if descriptor_file.extent_descriptions.is_empty() {
             return Err("Not a VMDK: descriptor has no extent descriptions".to_string());
         }
-        if descriptor_file.header.parent_cid != 0xffffffff {
-            return Err("VMDK files having a parent CID (i.e. VMDK files representing a delta with another disk) are not supported".to_string());
+        let monolithic = descriptor_file.extent_descriptions.len() == 1
+            && (descriptor_file.header.create_type == VMDKDiskType::MonolithicSparse
+                || descriptor_file.header.create_type == VMDKDiskType::StreamOptimized);
+        if file_path.is_none() && !monolithic {
+            return Err(
+                "Only monolithic VMDK disks can be read from a stream, this one has separate extent files"
+                    .to_string(),
+            );
         }
 
+        let parent = if descriptor_file.header.parent_cid != NO_PARENT_CID {
+            let (fs, file_path) = location
+                .ok_or("VMDK delta link read from a stream, its parent disk cannot be located")?;
+            let hint = descriptor_file
+                .header
+                .parent_file_name_hint
+                .as_deref()
+                .ok_or("VMDK delta link has a parent CID but no parentFileNameHint")?;
+            let parent_path = resolve_path(fs, file_path, hint, &options.search_paths)
+                .ok_or_else(|| format!("Parent VMDK '{}' not found", hint))?;
+            // A chain looping back on itself would be opened forever.
+            let canonical = |path: &Path| fs.canonicalize(path).unwrap_or(path.to_path_buf());
+            let mut chain = descendants.to_vec();
+            chain.push(canonical(file_path));
+            if chain.contains(&canonical(&parent_path)) {
+                return Err(format!(
+                    "VMDK snapshot chain loops back to '{}'",
+                    parent_path.display()
+                ));
+            }
+            debug!("Opening parent VMDK: {}", parent_path.display());
+            let parent = VMDK::open_path(
+                fs,
+                parent_path
+                    .to_str()
+                    .ok_or_else(|| "Invalid parent VMDK path".to_string())?,
+                options,
+                &chain,
+            )
+            .map_err(|e| format!("Error opening parent VMDK '{}': {}", hint, e))?;
+            if parent.descriptor_file.header.cid != descriptor_file.header.parent_cid {
+                return Err(format!(
+                    "Parent VMDK CID mismatch: expected {:08x}, found {:08x} in '{}' (the parent was modified after the snapshot was taken)",
+                    descriptor_file.header.parent_cid,
+                    parent.descriptor_file.header.cid,
+                    hint
+                ));
+            }
+            Some(Arc::new(parent))
+        } else {
+            None
+        };
+
         //  Calculate implicit extent offsets
         //  When the "start-sector" column is omitted, the extent begins immediately after the previous one.
         let mut next_start = 0;
@@ -1152,15 +2481,12 @@ impl VMDK {
                 .saturating_add(extent.sector_number);
         }
 
-        if descriptor_file.extent_descriptions.len() == 1
-            && (descriptor_file.header.create_type == VMDKDiskType::MonolithicSparse
-                || descriptor_file.header.create_type == VMDKDiskType::StreamOptimized)
-        {
+        if let (true, Some(file_path)) = (monolithic, file_path) {
             // There is no other extent file in these cases and the filename can be different from the one in the descriptor file
             // So we just make sure that the file path is set correctly
             for extent in &mut descriptor_file.extent_descriptions {
                 extent.set_path(
-                    Path::new(file_path)
+                    file_path
                         .file_name()
                         .unwrap_or(OsStr::new(""))
                         .to_str()
@@ -1177,39 +2503,71 @@ impl VMDK {
             .iter()
             .filter_map(|extent| {
                 if let Some(ref extent_file_name) = extent.extent_file_name {
-                    // Ensure the path read in the descriptor file is treated as a path relative to the descriptor file
-                    // Note: the specification of VMDK does not prohibit absolute paths in the extent file name but this case is considered as
-                    // unlikely and impractical in a forensic context. This code may be corrected if the case happens in the real world.
-                    let extent_file_path = Path::new(file_path)
-                        .parent()
-                        .unwrap_or(Path::new(""))
-                        .join(extent_file_name);
-                    debug!("Opening extent file: {}", extent_file_path.display());
-                    let mut file = File::open(extent_file_path).ok()?;
+                    let (mut file, path) = match location {
+                        // The single extent is the file we already hold.
+                        _ if monolithic => (
+                            vmdk_file.try_clone().ok()?,
+                            file_path.map(Path::to_path_buf).unwrap_or_default(),
+                        ),
+                        Some((fs, file_path)) => {
+                            let Some(extent_file_path) = resolve_path(
+                                fs,
+                                file_path,
+                                extent_file_name,
+                                &options.search_paths,
+                            ) else {
+                                warn!("Extent file '{}' not found", extent_file_name);
+                                return None;
+                            };
+                            debug!("Opening extent file: {}", extent_file_path.display());
+                            match fs.open(&extent_file_path) {
+                                Ok(file) => (file, extent_file_path),
+                                Err(e) => {
+                                    warn!(
+                                        "Cannot open extent file {}: {}",
+                                        extent_file_path.display(),
+                                        e
+                                    );
+                                    return None;
+                                }
+                            }
+                        }
+                        None => return None,
+                    };
                     let sparse_extent_metadata = if extent.extent_type == VMDKExtentType::Sparse {
-                        if sparse_header.is_none()
-                            || descriptor_file.header.create_type == VMDKDiskType::StreamOptimized
-                        {
-                            if sparse_header.is_some()
-                                && descriptor_file.header.create_type
-                                    == VMDKDiskType::StreamOptimized
-                                && sparse_header.as_ref().unwrap().grain_directory_sector == -1
-                            {
-                                // StreamOptimized disks usually have their header at the end of the file
-                                file.seek(SeekFrom::End(-1024)).ok()?;
-                            } else {
+                        if descriptor_file.header.create_type == VMDKDiskType::StreamOptimized {
+                            VMDKSparseExtentMetadata::read_stream_optimized(&file)
+                                .map_err(|e| {
+                                    warn!(
+                                        "Error reading streamOptimized extent {}: {}",
+                                        extent_file_name, e
+                                    )
+                                })
+                                .ok()
+                        } else {
+                            if sparse_header.is_none() {
                                 file.seek(SeekFrom::Start(0)).ok()?;
+                                let mut header_data = [0u8; 80];
+                                sparse_header = match file.read(&mut header_data) {
+                                    Ok(_) => Some(
+                                        VMDKSparseFileHeader::parse_sparse_header(&header_data)
+                                            .ok()?,
+                                    ),
+                                    Err(_) => return None,
+                                };
                             }
-                            let mut header_data = [0u8; 80];
-                            sparse_header = match file.read(&mut header_data) {
-                                Ok(_) => Some(
-                                    VMDKSparseFileHeader::parse_sparse_header(&header_data).ok()?,
-                                ),
-                                Err(_) => return None,
-                            };
+                            debug!("Parsed header: {:?}", sparse_header);
+                            VMDKSparseExtentMetadata::read_from_file(&file, sparse_header.as_ref()?)
+                                .ok()
                         }
-                        debug!("Parsed header: {:?}", sparse_header);
-                        VMDKSparseExtentMetadata::read_from_file(&mut file, sparse_header.as_ref()?)
+                    } else {
+                        None
+                    };
+                    let se_sparse_metadata = if extent.extent_type == VMDKExtentType::SeSparse {
+                        VMDKSeSparseMetadata::read_from_file(&file)
+                            .map_err(|e| {
+                                warn!("Error reading seSparse extent {}: {}", extent_file_name, e)
+                            })
                             .ok()
                     } else {
                         None
@@ -1217,7 +2575,10 @@ impl VMDK {
                     Some(VMDKExtentFile {
                         extent_description: extent.clone(),
                         file,
+                        path,
                         sparse_extent_metadata,
+                        se_sparse_metadata,
+                        grain_cache: Mutex::new(GrainCache::new(GRAIN_CACHE_BYTES)),
                     })
                 } else {
                     None
@@ -1225,44 +2586,148 @@ impl VMDK {
             })
             .collect();
 
-        let mut descriptor_path = PathBuf::new();
-        descriptor_path.push(file_path);
+        // Extents with a file that could not be opened or parsed
+        let missing_extents: Vec<VMDKExtentDescriptor> = descriptor_file
+            .extent_descriptions
+            .iter()
+            .filter(|description| {
+                description.extent_file_name.is_some()
+                    && !extent_files.iter().any(|e| {
+                        e.extent_description.extent_file_name == description.extent_file_name
+                    })
+            })
+            .cloned()
+            .collect();
+        if let Some(missing) = missing_extents.first() {
+            if !options.allow_missing_extents {
+                return Err(format!(
+                    "Extent file '{}' could not be opened",
+                    missing.extent_file_name.as_deref().unwrap_or_default()
+                ));
+            }
+            warn!(
+                "{} extent(s) could not be opened, their sectors read as zeros",
+                missing_extents.len()
+            );
+        }
+
+        let change_tracking = match (&descriptor_file.change_tracking_file, location) {
+            (Some(section), Some((fs, file_path))) => {
+                let ctk_path = file_path
+                    .parent()
+                    .unwrap_or(Path::new(""))
+                    .join(&section.change_track_path);
+                read_change_tracking(fs, &ctk_path)
+                    .map_err(|e| {
+                        warn!(
+                            "Ignoring change tracking file {}: {}",
+                            ctk_path.display(),
+                            e
+                        )
+                    })
+                    .ok()
+            }
+            _ => None,
+        };
+
+        let descriptor_path = file_path.map(Path::to_path_buf).unwrap_or_default();
 
-        Ok(VMDK {
+        // Reads that fall through to the parent count towards the child.
+        let io = parent
+            .as_ref()
+            .map_or_else(Arc::default, |parent| parent.io.clone());
+        let mut vmdk = VMDK {
             descriptor_file,
-            extent_files,
+            extent_files: extent_files.into(),
+            missing_extents: missing_extents.into(),
             position: 0,
             descriptor_path,
-        })
+            descriptor_size: file_len,
+            parent,
+            change_tracking,
+            sector_size: SECTOR_SIZE,
+            io,
+        };
+        // Descriptor sectors are always 512 bytes, but the guest may have
+        // partitioned the disk with 4096-byte sectors.
+        vmdk.sector_size = probe_sector_size(&vmdk).map_or(SECTOR_SIZE, u64::from);
+        Ok(vmdk)
     }
 
-    /// Reads data from the VMDK descriptor and prints metadata to the console.
-    pub fn print_info(&self) {
-        info!("VMDK Disk Information:");
-
-        info!("  Disk Type: {:?}", self.descriptor_file.header.create_type);
-        info!("  Extent list:");
-        for extent in &self.descriptor_file.extent_descriptions {
-            info!(
-                "    - Extent file: {}, Number of sectors: {}, Start sector: {}",
-                extent.extent_file_name.as_deref().unwrap_or("<unknown>"),
-                extent.sector_number,
-                extent.extent_start_sector.unwrap_or(0)
+    /// Disk type, identifiers, parent and disk database fields from the
+    /// descriptor, and the extent list.
+    pub fn info(&self) -> FormatInfo {
+        let mut info = FormatInfo::new("VMDK Disk Information")
+            .field(
+                "Disk Type",
+                format!("{:?}", self.descriptor_file.header.create_type),
+            )
+            .field("Disk ID", format!("{:x}", self.descriptor_file.header.cid));
+        for (depth, parent) in self.snapshot_chain().into_iter().enumerate().skip(1) {
+            info = info.field(
+                match depth {
+                    1 => "Parent".to_string(),
+                    _ => format!("Parent (level {})", depth),
+                },
+                format!(
+                    "{} (Disk ID: {:x})",
+                    parent.descriptor_path.display(),
+                    parent.descriptor_file.header.cid
+                ),
             );
         }
-        info!("  Disk ID: {:x}", self.descriptor_file.header.cid);
         if let Some(ref disk_database) = self.descriptor_file.disk_database {
             if let Some(sectors) = disk_database.ddb_geometry_sectors {
                 // Maybe we shouldn't rely on this information and rather use the number of sectors from the extent descriptions
-                info!("  Disk sectors: {} sectors", sectors);
+                info = info.field("Disk sectors", format!("{} sectors", sectors));
             }
             if let Some(ref tools) = disk_database.ddb_tools_version {
-                info!("  Guest tools Version: {}", tools);
+                info = info.field("Guest tools Version", tools);
             }
             if let Some(thin_provisioned) = disk_database.ddb_thin_provisioned {
-                info!("  Thin Provisioned: {}", thin_provisioned);
+                info = info.field("Thin Provisioned", thin_provisioned);
             }
         }
+        if let Some(ref tracking) = self.change_tracking {
+            let changed = (0..tracking.block_count)
+                .filter(|&block| tracking.is_changed(block))
+                .count();
+            info = info.field(
+                "Changed Blocks",
+                format!(
+                    "{} of {} ({} bytes each){}",
+                    changed,
+                    tracking.block_count,
+                    tracking.block_size,
+                    if tracking.clean { "" } else { ", not clean" }
+                ),
+            );
+        }
+        let extents = self
+            .descriptor_file
+            .extent_descriptions
+            .iter()
+            .map(|extent| {
+                (
+                    extent
+                        .extent_file_name
+                        .as_deref()
+                        .unwrap_or("<unknown>")
+                        .to_string(),
+                    format!(
+                        "Number of sectors: {}, Start sector: {}",
+                        extent.sector_number,
+                        extent.extent_start_sector.unwrap_or(0)
+                    ),
+                )
+            })
+            .collect();
+        info.section("Extent list", extents)
+    }
+
+    /// Outputs [`VMDK::info`] to the current `log` subscriber.
+    pub fn print_info(&self) {
+        self.info().log();
     }
 
     /// Reads data from the VMDK disk into the given buffer, starting from the current position.
@@ -1272,57 +2737,9 @@ impl VMDK {
     ///
     /// Errors if IO errors occur while reading from the extent files. Also errors if trying to read data from unsupported extent types.
     pub fn vmdk_read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        // First, identify the extent file(s) that contains the data at the desired position
-        let buf_len = buf.len() as u64;
-        let extent_files = self.extent_files.iter_mut().filter(|e| {
-            (
-                // We want the file that contains the starting position
-                self.position >= e.extent_description.extent_start_sector.unwrap_or(0) * SECTOR_SIZE
-                    && self.position
-                        < (e.extent_description.extent_start_sector.unwrap_or(0)
-                            + e.extent_description.sector_number)
-                            * SECTOR_SIZE
-            ) || (
-                // We also want the file that contains the ending position (starting position + length of the buffer)
-                self.position + buf_len
-                    >= e.extent_description.extent_start_sector.unwrap_or(0) * SECTOR_SIZE
-                    && self.position + buf_len
-                        < (e.extent_description.extent_start_sector.unwrap_or(0)
-                            + e.extent_description.sector_number)
-                            * SECTOR_SIZE
-            ) || (
-                // And we want all the files in between
-                self.position < e.extent_description.extent_start_sector.unwrap_or(0) * SECTOR_SIZE
-                    && self.position + buf_len
-                        > (e.extent_description.extent_start_sector.unwrap_or(0)
-                            + e.extent_description.sector_number)
-                            * SECTOR_SIZE
-            )
-        });
-
-        let mut total_read = 0;
-        for extent in extent_files {
-            // Find the relative position within the extent file we want depending on the structure of the extent files we recovered
-            let end_of_extent = (extent.extent_description.extent_start_sector.unwrap_or(0)
-                + extent.extent_description.sector_number)
-                * SECTOR_SIZE;
-            let start_of_extent =
-                extent.extent_description.extent_start_sector.unwrap_or(0) * SECTOR_SIZE;
-            let start_position = self.position.saturating_sub(start_of_extent);
-            let end_position = if self.position + (buf.len() as u64) >= end_of_extent {
-                end_of_extent - start_of_extent
-            } else {
-                self.position + (buf.len() as u64) - start_of_extent
-            };
-            // Now, read the data from the extent file and update the buffer
-            let buffer_start = start_of_extent.saturating_sub(self.position);
-            let buffer_end = (buffer_start + end_position - start_position) as usize;
-            let buf_part = &mut buf[buffer_start as usize..buffer_end];
-            let read_bytes = extent.read_data(start_position, buf_part)?;
-            total_read += read_bytes;
-        }
-        self.position += total_read as u64;
-        Ok(total_read)
+        let read = self.read_at(self.position, buf)?;
+        self.position += read as u64;
+        Ok(read)
     }
 
     /// Adds the given offset to the current position in the VMDK disk.
@@ -1335,13 +2752,7 @@ impl VMDK {
         // First, check that the desired position is within the bounds of the disk as defined by the extent descriptions
         // If we are in the bounds, update the current position and return the new position
         if !self.descriptor_file.extent_descriptions.is_empty() {
-            let total_sectors: u64 = self
-                .descriptor_file
-                .extent_descriptions
-                .iter()
-                .map(|e| e.sector_number)
-                .sum();
-            let total_bytes = total_sectors * SECTOR_SIZE;
+            let total_bytes = self.size();
             match offset {
                 SeekFrom::Start(offset) => {
                     if offset <= total_bytes {
@@ -1387,27 +2798,526 @@ impl VMDK {
         }
     }
 
+    /// Logical sector size of the virtual disk: 4096 when the guest wrote a
+    /// GPT for 4096-byte sectors, 512 otherwise.
     pub fn get_sector_size(&self) -> u64 {
-        SECTOR_SIZE
+        self.sector_size
     }
-}
 
-impl Read for VMDK {
-    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        self.vmdk_read(buf)
+    /// Internal counters behind [`Body::stats`](crate::Body::stats).
+    pub(crate) fn io_counters(&self) -> &IoCounters {
+        &self.io
     }
-}
 
-impl Seek for VMDK {
-    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
-        self.vmdk_seek(pos)
+    /// Number of extents described by the descriptor.
+    pub fn extent_count(&self) -> usize {
+        self.descriptor_file.extent_descriptions.len()
     }
-}
 
-#[cfg(test)]
+    /// The descriptor, every extent file and the files of the parent disks,
+    /// with their sizes.
+    pub fn files(&self) -> io::Result<Vec<EvidenceFile>> {
+        let mut files = Vec::new();
+        let monolithic = self.extent_files.len() == 1
+            && matches!(
+                self.descriptor_file.header.create_type,
+                VMDKDiskType::MonolithicSparse | VMDKDiskType::StreamOptimized
+            );
+        if !monolithic {
+            files.push(EvidenceFile {
+                path: self.descriptor_path.display().to_string(),
+                size: self.descriptor_size,
+                role: "VMDK descriptor".to_string(),
+            });
+        }
+
+        for extent in self.extent_files.iter() {
+            let description = &extent.extent_description;
+            let path = match &description.extent_file_name {
+                // The embedded descriptor may still name the file it was created as.
+                _ if monolithic => self.descriptor_path.clone(),
+                Some(_) => extent.path.clone(),
+                None => PathBuf::new(),
+            };
+            files.push(EvidenceFile {
+                path: path.display().to_string(),
+                size: extent.file.len()?,
+                role: format!(
+                    "VMDK extent ({}, {} sectors)",
+                    format!("{:?}", description.extent_type).to_uppercase(),
+                    description.sector_number
+                ),
+            });
+        }
+
+        if let Some(parent) = &self.parent {
+            for mut file in parent.files()? {
+                file.role = format!("parent {}", file.role);
+                files.push(file);
+            }
+        }
+        Ok(files)
+    }
+
+    /// Disk type declared by the `createType` header field.
+    pub fn disk_type(&self) -> &VMDKDiskType {
+        &self.descriptor_file.header.create_type
+    }
+
+    /// Descriptor format version (1, 2 or 3).
+    pub fn version(&self) -> u8 {
+        self.descriptor_file.header.version
+    }
+
+    /// Character encoding of the descriptor.
+    pub fn encoding(&self) -> &VMDKEncoding {
+        &self.descriptor_file.header.encoding
+    }
+
+    /// Content identifier (`CID`) of this disk.
+    pub fn cid(&self) -> u32 {
+        self.descriptor_file.header.cid
+    }
+
+    /// Content identifier of the parent (`parentCID`), `None` for a disk
+    /// without parent (`ffffffff`).
+    pub fn parent_cid(&self) -> Option<u32> {
+        Some(self.descriptor_file.header.parent_cid).filter(|&cid| cid != u32::MAX)
+    }
+
+    /// `parentFileNameHint` of a delta link, as recorded in the descriptor.
+    pub fn parent_file_name_hint(&self) -> Option<&str> {
+        self.descriptor_file.header.parent_file_name_hint.as_deref()
+    }
+
+    /// The opened parent disk of a delta link.
+    pub fn parent(&self) -> Option<&VMDK> {
+        self.parent.as_deref()
+    }
+
+    /// Every layer of the snapshot chain, from this disk down to the base
+    /// disk, which is the only one for a disk without parent. (Not `chain`,
+    /// which [`Read`] already names.)
+    pub fn snapshot_chain(&self) -> Vec<&VMDK> {
+        std::iter::successors(Some(self), |disk| disk.parent()).collect()
+    }
+
+    /// The parsed descriptor, e.g. to serialize it into a report.
+    pub fn descriptor(&self) -> &VMDKDescriptorFile {
+        &self.descriptor_file
+    }
+
+    /// Extent descriptions, in disk order.
+    pub fn extents(&self) -> &[VMDKExtentDescriptor] {
+        &self.descriptor_file.extent_descriptions
+    }
+
+    /// Extents whose file could not be opened, when the disk was opened with
+    /// [`VMDK::open_allowing_missing_extents`].
+    pub fn missing_extents(&self) -> &[VMDKExtentDescriptor] {
+        &self.missing_extents
+    }
+
+    /// Byte ranges of the disk held by missing extents, here or in a parent
+    /// disk, sorted and coalesced. They read as zeros and are not part of
+    /// [`VMDK::allocated_ranges`].
+    pub fn missing_ranges(&self) -> Vec<Range<u64>> {
+        let mut ranges: Vec<Range<u64>> = self
+            .missing_extents
+            .iter()
+            .map(|extent| {
+                let start = extent.extent_start_sector.unwrap_or(0) * SECTOR_SIZE;
+                start..start + extent.sector_number * SECTOR_SIZE
+            })
+            .collect();
+        if let Some(parent) = &self.parent {
+            ranges.extend(clip_ranges(&parent.missing_ranges(), 0..self.size()));
+        }
+        merge_ranges(ranges)
+    }
+
+    /// The disk database (`ddb.*` fields), if the descriptor has one.
+    pub fn disk_database(&self) -> Option<&VMDKDiskDatabase> {
+        self.descriptor_file.disk_database.as_ref()
+    }
+
+    /// Path of the change tracking (CBT) file, `changeTrackPath`.
+    pub fn change_tracking_path(&self) -> Option<&str> {
+        self.descriptor_file
+            .change_tracking_file
+            .as_ref()
+            .map(|section| section.change_track_path.as_str())
+    }
+
+    /// Changed Block Tracking data of the file named by
+    /// [`VMDK::change_tracking_path`], when it could be read.
+    pub fn change_tracking(&self) -> Option<&VMDKChangeTracking> {
+        self.change_tracking.as_ref()
+    }
+
+    /// Path of the descriptor file (or monolithic extent) the disk was opened from.
+    pub fn descriptor_path(&self) -> &Path {
+        &self.descriptor_path
+    }
+
+    /// CHS geometry from the disk database.
+    pub fn geometry(&self) -> Option<DiskGeometry> {
+        let ddb = self.descriptor_file.disk_database.as_ref()?;
+        Some(DiskGeometry {
+            cylinders: ddb.ddb_geometry_cylinders?,
+            heads: ddb.ddb_geometry_heads? as u32,
+            sectors_per_track: ddb.ddb_geometry_sectors? as u32,
+        })
+    }
+
+    /// Byte ranges of the virtual disk backed by data: flat extents, the
+    /// allocated grains of sparse extents and, for grains a delta link does
+    /// not hold, the allocated ranges of its parent. Zero extents and
+    /// zeroed grains read as zeros and are left out.
+    pub fn allocated_ranges(&self) -> io::Result<Vec<Range<u64>>> {
+        let parent = match &self.parent {
+            Some(parent) => Some(parent.allocated_ranges()?),
+            None => None,
+        };
+        let mut ranges: Vec<Range<u64>> = Vec::new();
+        let mut push = |range: Range<u64>| match ranges.last_mut() {
+            Some(last) if last.end == range.start => last.end = range.end,
+            _ => ranges.push(range),
+        };
+        for extent in self.extent_files.iter() {
+            let description = &extent.extent_description;
+            let start = description.extent_start_sector.unwrap_or(0) * SECTOR_SIZE;
+            let end = start + description.sector_number * SECTOR_SIZE;
+            match (&description.extent_type, &extent.sparse_extent_metadata) {
+                (VMDKExtentType::Zero, _) => {}
+                (VMDKExtentType::Sparse, Some(metadata)) => {
+                    let grain_size = metadata.header.grain_number * SECTOR_SIZE;
+                    let zeroed_grains = metadata.header.flags & FLAG_USE_ZEROED_GRAIN_TABLE
+                        == FLAG_USE_ZEROED_GRAIN_TABLE;
+                    metadata.for_each_grain(&extent.file, &self.io, |grains, sector| {
+                        let grain_start = start + grains.start * grain_size;
+                        if grain_start >= end {
+                            return;
+                        }
+                        let grains = grain_start..(start + grains.end * grain_size).min(end);
+                        if sector == 0 {
+                            for range in parent.iter().flat_map(|p| clip_ranges(p, grains.clone()))
+                            {
+                                push(range);
+                            }
+                        } else if !(zeroed_grains && sector == ZEROED_GRAIN_TABLE_ENTRY) {
+                            push(grains);
+                        }
+                    })?;
+                }
+                (VMDKExtentType::SeSparse, _) if extent.se_sparse_metadata.is_some() => {
+                    let metadata = extent.se_sparse_metadata.as_ref().unwrap();
+                    let grain_size = metadata.grain_sectors * SECTOR_SIZE;
+                    metadata.for_each_grain(&extent.file, &self.io, |grains, grain| {
+                        let grain_start = start + grains.start * grain_size;
+                        if grain_start >= end {
+                            return;
+                        }
+                        let grains = grain_start..(start + grains.end * grain_size).min(end);
+                        match grain {
+                            SeSparseGrain::Unallocated => {
+                                for range in
+                                    parent.iter().flat_map(|p| clip_ranges(p, grains.clone()))
+                                {
+                                    push(range);
+                                }
+                            }
+                            SeSparseGrain::Zero => {}
+                            SeSparseGrain::Data(_) => push(grains),
+                        }
+                    })?;
+                }
+                _ => push(start..end),
+            }
+        }
+        Ok(merge_ranges(ranges))
+    }
+
+    /// Cross-checks the descriptor, the extent headers, the grain tables and
+    /// the parent link, and lists what does not add up. Nothing is checked
+    /// while opening, so this is the place to look for truncated or tampered
+    /// disks. Reads no grain data, but walks every grain table.
+    pub fn validate(&self) -> io::Result<Vec<VMDKAnomaly>> {
+        let name = |description: &VMDKExtentDescriptor| {
+            description
+                .extent_file_name
+                .clone()
+                .unwrap_or_else(|| format!("{:?}", description.extent_type).to_uppercase())
+        };
+        let mut anomalies = Vec::new();
+
+        // Extents without a file (ZERO) are never opened.
+        for description in &self.descriptor_file.extent_descriptions {
+            let opened = self
+                .extent_files
+                .iter()
+                .any(|e| e.extent_description.extent_file_name == description.extent_file_name);
+            if description.extent_file_name.is_some() && !opened {
+                anomalies.push(VMDKAnomaly::MissingExtent {
+                    extent: name(description),
+                });
+            }
+        }
+
+        let mut spans: Vec<(u64, u64, String)> = self
+            .descriptor_file
+            .extent_descriptions
+            .iter()
+            .map(|d| {
+                let start = d.extent_start_sector.unwrap_or(0);
+                (start, start.saturating_add(d.sector_number), name(d))
+            })
+            .collect();
+        spans.sort();
+        for pair in spans.windows(2) {
+            if pair[1].0 < pair[0].1 {
+                anomalies.push(VMDKAnomaly::OverlappingExtents {
+                    first: pair[0].2.clone(),
+                    second: pair[1].2.clone(),
+                });
+            }
+        }
+
+        let extent_sectors = self.size() / SECTOR_SIZE;
+        if let Some(geometry) = self.geometry() {
+            let geometry_sectors =
+                geometry.cylinders * geometry.heads as u64 * geometry.sectors_per_track as u64;
+            if geometry_sectors > extent_sectors {
+                anomalies.push(VMDKAnomaly::GeometryExceedsExtents {
+                    geometry_sectors,
+                    extent_sectors,
+                });
+            }
+        }
+
+        for extent in self.extent_files.iter() {
+            let description = &extent.extent_description;
+            if let Some(metadata) = &extent.se_sparse_metadata {
+                anomalies.extend(Self::validate_se_sparse(
+                    extent,
+                    metadata,
+                    &self.io,
+                    name(description),
+                )?);
+            }
+            let Some(metadata) = &extent.sparse_extent_metadata else {
+                continue;
+            };
+            let header = &metadata.header;
+            if header.capacity != description.sector_number {
+                anomalies.push(VMDKAnomaly::CapacityMismatch {
+                    extent: name(description),
+                    descriptor_sectors: description.sector_number,
+                    header_sectors: header.capacity,
+                });
+            }
+            if header.is_dirty {
+                anomalies.push(VMDKAnomaly::DirtyExtent {
+                    extent: name(description),
+                });
+            }
+            if metadata.rebuilt {
+                anomalies.push(VMDKAnomaly::MissingFooter {
+                    extent: name(description),
+                });
+            }
+            let file_size = extent.file.len()?;
+            let compressed =
+                header.flags & FLAG_HAS_COMPRESSED_GRAIN_DATA == FLAG_HAS_COMPRESSED_GRAIN_DATA;
+            // Compressed grains have no fixed size, only their start is checked.
+            let grain_size = if compressed {
+                1
+            } else {
+                header.grain_number * SECTOR_SIZE
+            };
+            metadata.for_each_grain(&extent.file, &self.io, |grains, sector| {
+                if sector == 0 || sector == ZEROED_GRAIN_TABLE_ENTRY {
+                    return;
+                }
+                let offset = sector as u64 * SECTOR_SIZE;
+                if offset + grain_size > file_size {
+                    anomalies.push(VMDKAnomaly::GrainOutOfBounds {
+                        extent: name(description),
+                        grain: grains.start,
+                        offset,
+                        file_size,
+                    });
+                }
+            })?;
+        }
+
+        if let Some(parent) = &self.parent {
+            let expected = self.descriptor_file.header.parent_cid;
+            let found = parent.descriptor_file.header.cid;
+            if expected != found {
+                anomalies.push(VMDKAnomaly::ParentCidMismatch { expected, found });
+            }
+            anomalies.extend(parent.validate()?);
+        }
+        Ok(anomalies)
+    }
+
+    /// The checks of [`VMDK::validate`] that apply to a seSparse extent.
+    fn validate_se_sparse(
+        extent: &VMDKExtentFile,
+        metadata: &VMDKSeSparseMetadata,
+        io: &IoCounters,
+        name: String,
+    ) -> io::Result<Vec<VMDKAnomaly>> {
+        let mut anomalies = Vec::new();
+        let description = &extent.extent_description;
+        if metadata.capacity != description.sector_number {
+            anomalies.push(VMDKAnomaly::CapacityMismatch {
+                extent: name.clone(),
+                descriptor_sectors: description.sector_number,
+                header_sectors: metadata.capacity,
+            });
+        }
+        if metadata.replay_journal {
+            anomalies.push(VMDKAnomaly::DirtyExtent {
+                extent: name.clone(),
+            });
+        }
+        let file_size = extent.file.len()?;
+        let grain_size = metadata.grain_sectors * SECTOR_SIZE;
+        metadata.for_each_grain(&extent.file, io, |grains, grain| {
+            if let SeSparseGrain::Data(offset) = grain {
+                if offset + grain_size > file_size {
+                    anomalies.push(VMDKAnomaly::GrainOutOfBounds {
+                        extent: name.clone(),
+                        grain: grains.start,
+                        offset,
+                        file_size,
+                    });
+                }
+            }
+        })?;
+        Ok(anomalies)
+    }
+
+    /// Size of the virtual disk in bytes, the sum of its extents.
+    pub fn size(&self) -> u64 {
+        self.descriptor_file
+            .extent_descriptions
+            .iter()
+            .map(|e| e.sector_number)
+            .sum::<u64>()
+            * SECTOR_SIZE
+    }
+}
+
+impl Read for VMDK {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.vmdk_read(buf)
+    }
+}
+
+impl ReadAt for VMDK {
+    /// Reads data from the VMDK disk into the given buffer, starting from `position` on the disk.
+    fn read_at(&self, position: u64, buf: &mut [u8]) -> io::Result<usize> {
+        // First, identify the extent file(s) that contains the data at the desired position
+        let buf_len = buf.len() as u64;
+        let extent_files = self.extent_files.iter().filter(|e| {
+            (
+                // We want the file that contains the starting position
+                position >= e.extent_description.extent_start_sector.unwrap_or(0) * SECTOR_SIZE
+                    && position
+                        < (e.extent_description.extent_start_sector.unwrap_or(0)
+                            + e.extent_description.sector_number)
+                            * SECTOR_SIZE
+            ) || (
+                // We also want the file that contains the ending position (starting position + length of the buffer)
+                position + buf_len
+                    >= e.extent_description.extent_start_sector.unwrap_or(0) * SECTOR_SIZE
+                    && position + buf_len
+                        < (e.extent_description.extent_start_sector.unwrap_or(0)
+                            + e.extent_description.sector_number)
+                            * SECTOR_SIZE
+            ) || (
+                // And we want all the files in between
+                position < e.extent_description.extent_start_sector.unwrap_or(0) * SECTOR_SIZE
+                    && position + buf_len
+                        > (e.extent_description.extent_start_sector.unwrap_or(0)
+                            + e.extent_description.sector_number)
+                            * SECTOR_SIZE
+            )
+        });
+
+        let mut total_read = 0;
+        for extent in extent_files {
+            // Find the relative position within the extent file we want depending on the structure of the extent files we recovered
+            let end_of_extent = (extent.extent_description.extent_start_sector.unwrap_or(0)
+                + extent.extent_description.sector_number)
+                * SECTOR_SIZE;
+            let start_of_extent =
+                extent.extent_description.extent_start_sector.unwrap_or(0) * SECTOR_SIZE;
+            let start_position = position.saturating_sub(start_of_extent);
+            let end_position = if position + (buf.len() as u64) >= end_of_extent {
+                end_of_extent - start_of_extent
+            } else {
+                position + (buf.len() as u64) - start_of_extent
+            };
+            // Now, read the data from the extent file and update the buffer
+            let buffer_start = start_of_extent.saturating_sub(position);
+            let buffer_end = (buffer_start + end_position - start_position) as usize;
+            let buf_part = &mut buf[buffer_start as usize..buffer_end];
+            let read_bytes =
+                extent.read_data(start_position, buf_part, self.parent.as_deref(), &self.io)?;
+            total_read += read_bytes;
+        }
+        for extent in self.missing_extents.iter() {
+            let start_of_extent = extent.extent_start_sector.unwrap_or(0) * SECTOR_SIZE;
+            let end_of_extent = start_of_extent + extent.sector_number * SECTOR_SIZE;
+            let start = position.max(start_of_extent);
+            let end = (position + buf_len).min(end_of_extent);
+            if start < end {
+                buf[(start - position) as usize..(end - position) as usize].fill(0);
+                total_read += (end - start) as usize;
+            }
+        }
+        Ok(total_read)
+    }
+}
+
+impl Seek for VMDK {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.vmdk_seek(pos)
+    }
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_change_tracking() {
+        let mut data = vec![0u8; CTK_HEADER_SIZE + 2];
+        data[..4].copy_from_slice(CTK_SIGNATURE);
+        data[4..8].copy_from_slice(&1u32.to_le_bytes());
+        data[8..12].copy_from_slice(&CTK_FLAG_CLEAN.to_le_bytes());
+        data[12..16].copy_from_slice(&128u32.to_le_bytes());
+        data[16..24].copy_from_slice(&10u64.to_le_bytes());
+        data[CTK_HEADER_SIZE] = 0b1000_0110;
+        data[CTK_HEADER_SIZE + 1] = 0b0000_0011;
+
+        let tracking = VMDKChangeTracking::parse(&data).unwrap();
+        assert!(tracking.clean);
+        assert_eq!(tracking.block_size, 65536);
+        assert!(tracking.is_changed(1) && !tracking.is_changed(3) && !tracking.is_changed(10));
+        assert_eq!(
+            tracking.changed_ranges(),
+            [65536..3 * 65536, 7 * 65536..10 * 65536]
+        );
+
+        data.truncate(CTK_HEADER_SIZE + 1);
+        assert!(VMDKChangeTracking::parse(&data).is_err());
+    }
+
     #[test]
     fn test_parse_key_value_pair() {
         assert_eq!(
@@ -1470,15 +3380,15 @@ ddb.toolsVersion = "0"
         assert_eq!(descriptor.header.parent_cid, 0xffffffff);
         assert_eq!(descriptor.header.is_native_snapshot, Some(false));
         assert_eq!(
-            descriptor.extent_descriptions.get(0).unwrap().access_mode,
+            descriptor.extent_descriptions.first().unwrap().access_mode,
             VMDKExtentAccessMode::Rw
         );
         assert_eq!(
-            descriptor.extent_descriptions.get(0).unwrap().sector_number,
+            descriptor.extent_descriptions.first().unwrap().sector_number,
             4192256
         );
         assert_eq!(
-            descriptor.extent_descriptions.get(0).unwrap().extent_type,
+            descriptor.extent_descriptions.first().unwrap().extent_type,
             VMDKExtentType::Zero
         );
         assert_eq!(
@@ -1486,4 +3396,528 @@ ddb.toolsVersion = "0"
             Some(16383)
         );
     }
+
+    #[test]
+    fn test_descriptor_round_trip() {
+        let text = r#"# Disk DescriptorFile
+version=1
+encoding="windows-1252"
+CID=0000beef
+parentCID=12345678
+isNativeSnapshot="no"
+createType="monolithicSparse"
+parentFileNameHint="base.vmdk"
+
+# Extent description
+RW 64 SPARSE "disk.vmdk"
+RW 8 FLAT "disk-flat.vmdk" 16
+RDONLY 8 ZERO
+
+# Change Tracking File
+changeTrackPath="disk-ctk.vmdk"
+
+# The Disk Data Base
+#DDB
+
+ddb.adapterType = "lsilogic"
+ddb.geometry.cylinders = "1"
+ddb.geometry.heads = "16"
+ddb.geometry.sectors = "63"
+ddb.longContentID = "8c5d1dd4e1b7a5e0f3b3c2a10000beef"
+ddb.uuid = "60 00 C2 9a 1b 2c 3d 4e-5f 60 71 82 93 a4 b5 c6"
+ddb.virtualHWVersion = "14"
+ddb.thinProvisioned = "1"
+"#;
+        let descriptor: VMDKDescriptorFile = text.parse().unwrap();
+        assert_eq!(descriptor.to_string(), text);
+        let disk_database = descriptor.disk_database.as_ref().unwrap();
+        assert_eq!(disk_database.ddb_thin_provisioned, Some(true));
+        assert!(disk_database.ddb_long_content_id.is_some());
+
+        let data = descriptor.to_bytes();
+        assert_eq!(data, text.as_bytes());
+        let parsed = VMDKDescriptorFile::from_bytes(&data).unwrap();
+        assert_eq!(parsed.to_string(), text);
+    }
+
+    #[test]
+    fn test_parse_gbk_descriptor() {
+        let descriptor = "# Disk DescriptorFile
+version=1
+encoding=\"GBK\"
+CID=fffffffe
+parentCID=ffffffff
+createType=\"monolithicFlat\"
+
+# Extent description
+RW 2048 FLAT \"虚拟磁盘-flat.vmdk\" 0
+";
+        let (bytes, _, _) = GBK.encode(descriptor);
+        assert!(std::str::from_utf8(&bytes).is_err());
+
+        let descriptor = VMDKDescriptorFile::from_bytes(&bytes).unwrap();
+        assert_eq!(descriptor.header.encoding, VMDKEncoding::Gbk);
+        let extent = &descriptor.extent_descriptions[0];
+        assert_eq!(
+            extent.extent_file_name.as_deref(),
+            Some("虚拟磁盘-flat.vmdk")
+        );
+        assert_eq!(
+            VMDKEncoding::from_label("sjis"),
+            Some(VMDKEncoding::ShiftJis)
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_anomalies() {
+        let descriptor = r#"# Disk DescriptorFile
+version=1
+CID=0000beef
+parentCID=ffffffff
+createType="monolithicFlat"
+
+# Extent description
+RW 8 FLAT "a-flat.vmdk" 0
+RW 8 FLAT "b-flat.vmdk" 4
+RW 8 FLAT "c-flat.vmdk" 16
+
+# The Disk Data Base
+ddb.geometry.cylinders = "1"
+ddb.geometry.heads = "1"
+ddb.geometry.sectors = "63"
+"#;
+        let mut files = crate::vfs::MemoryFileSystem::new();
+        files.insert("vm/disk.vmdk", descriptor.as_bytes().to_vec());
+        files.insert("vm/a-flat.vmdk", vec![0; 8 * 512]);
+        files.insert("vm/b-flat.vmdk", vec![0; 8 * 512]);
+
+        assert!(VMDK::open_in(&files, "vm/disk.vmdk").is_err());
+        let vmdk = VMDK::open_allowing_missing_extents(&files, "vm/disk.vmdk").unwrap();
+        assert_eq!(
+            vmdk.missing_ranges(),
+            [Range {
+                start: 8 * 1024,
+                end: 12 * 1024
+            }]
+        );
+        let mut buf = vec![0xff; 1024];
+        assert_eq!(vmdk.read_at(8 * 1024, &mut buf).unwrap(), 1024);
+        assert!(buf.iter().all(|&b| b == 0));
+        let anomalies = vmdk.validate().unwrap();
+        assert_eq!(
+            anomalies,
+            [
+                VMDKAnomaly::MissingExtent {
+                    extent: "c-flat.vmdk".to_string()
+                },
+                VMDKAnomaly::OverlappingExtents {
+                    first: "a-flat.vmdk".to_string(),
+                    second: "b-flat.vmdk".to_string()
+                },
+                VMDKAnomaly::GeometryExceedsExtents {
+                    geometry_sectors: 63,
+                    extent_sectors: 24
+                },
+            ]
+        );
+        assert_eq!(
+            anomalies[1].to_string(),
+            "extents a-flat.vmdk and b-flat.vmdk overlap"
+        );
+    }
+
+    #[test]
+    fn test_extents_found_in_search_paths() {
+        let descriptor = r#"# Disk DescriptorFile
+version=1
+CID=0000beef
+parentCID=ffffffff
+createType="twoGbMaxExtentFlat"
+
+# Extent description
+RW 8 FLAT "/datastore/a-flat.vmdk" 0
+RW 8 FLAT "b-flat.vmdk" 8
+"#;
+        let mut files = crate::vfs::MemoryFileSystem::new();
+        files.insert("vm/disk.vmdk", descriptor.as_bytes().to_vec());
+        files.insert("/datastore/a-flat.vmdk", vec![0xaa; 8 * 512]);
+        files.insert("moved/b-flat.vmdk", vec![0xbb; 16 * 512]);
+
+        assert!(VMDK::open_in(&files, "vm/disk.vmdk").is_err());
+        let options = VMDKOpenOptions {
+            search_paths: vec!["elsewhere".into(), "moved".into()],
+            ..Default::default()
+        };
+        let vmdk = VMDK::open_with(&files, "vm/disk.vmdk", &options).unwrap();
+        let mut buf = vec![0u8; 8 * 512];
+        assert_eq!(vmdk.read_at(0, &mut buf).unwrap(), buf.len());
+        assert!(buf.iter().all(|&b| b == 0xaa));
+        assert_eq!(vmdk.read_at(8 * 512, &mut buf).unwrap(), buf.len());
+        assert!(buf.iter().all(|&b| b == 0xbb));
+        let paths: Vec<String> = vmdk.files().unwrap().into_iter().map(|f| f.path).collect();
+        assert_eq!(
+            paths,
+            [
+                "vm/disk.vmdk",
+                "/datastore/a-flat.vmdk",
+                "moved/b-flat.vmdk"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_snapshot_chain() {
+        let descriptor = |cid: u32, parent: Option<(u32, &str)>, extent: &str| {
+            let (parent_cid, hint) = match parent {
+                Some((cid, hint)) => (cid, format!("parentFileNameHint=\"{}\"\n", hint)),
+                None => (NO_PARENT_CID, String::new()),
+            };
+            format!(
+                "# Disk DescriptorFile\nversion=1\nCID={:08x}\nparentCID={:08x}\n{}\
+                 createType=\"monolithicFlat\"\n\n# Extent description\nRW 8 FLAT \"{}\" 0\n",
+                cid, parent_cid, hint, extent
+            )
+            .into_bytes()
+        };
+        let mut files = crate::vfs::MemoryFileSystem::new();
+        files.insert("vm/base.vmdk", descriptor(1, None, "base-flat.vmdk"));
+        files.insert(
+            "vm/mid.vmdk",
+            descriptor(2, Some((1, "base.vmdk")), "mid-flat.vmdk"),
+        );
+        files.insert(
+            "vm/top.vmdk",
+            descriptor(3, Some((2, "mid.vmdk")), "top-flat.vmdk"),
+        );
+        for extent in ["base-flat.vmdk", "mid-flat.vmdk", "top-flat.vmdk"] {
+            files.insert(Path::new("vm").join(extent), vec![0; 8 * 512]);
+        }
+
+        let vmdk = VMDK::open_in(&files, "vm/top.vmdk").unwrap();
+        let cids: Vec<u32> = vmdk
+            .snapshot_chain()
+            .iter()
+            .map(|disk| disk.cid())
+            .collect();
+        assert_eq!(cids, [3, 2, 1]);
+        assert_eq!(
+            vmdk.snapshot_chain()[2].descriptor_path(),
+            Path::new("vm/base.vmdk")
+        );
+
+        // Two snapshots naming each other as parent.
+        files.insert(
+            "vm/a.vmdk",
+            descriptor(10, Some((11, "b.vmdk")), "top-flat.vmdk"),
+        );
+        files.insert(
+            "vm/b.vmdk",
+            descriptor(11, Some((10, "a.vmdk")), "top-flat.vmdk"),
+        );
+        let err = VMDK::open_in(&files, "vm/a.vmdk").err().unwrap();
+        assert!(err.to_string().contains("snapshot chain loops back"));
+    }
+
+    #[test]
+    fn test_grain_tables_load_on_first_read() {
+        let descriptor = r#"# Disk DescriptorFile
+version=1
+CID=0000beef
+parentCID=ffffffff
+createType="monolithicSparse"
+
+# Extent description
+RW 64 SPARSE "disk.vmdk"
+"#;
+        // Header, descriptor, grain directory, one grain table, one grain.
+        let mut image = vec![0u8; 12 * 512];
+        image[0..4].copy_from_slice(b"KDMV");
+        image[4..8].copy_from_slice(&1u32.to_le_bytes());
+        image[12..20].copy_from_slice(&64u64.to_le_bytes());
+        image[20..28].copy_from_slice(&8u64.to_le_bytes());
+        image[28..36].copy_from_slice(&1u64.to_le_bytes());
+        image[36..44].copy_from_slice(&1u64.to_le_bytes());
+        image[44..48].copy_from_slice(&4u32.to_le_bytes());
+        image[56..64].copy_from_slice(&2i64.to_le_bytes());
+        image[512..512 + descriptor.len()].copy_from_slice(descriptor.as_bytes());
+        image[1028..1032].copy_from_slice(&3u32.to_le_bytes());
+        image[1536..1540].copy_from_slice(&4u32.to_le_bytes());
+        image[2048..].fill(0xab);
+        let mut files = crate::vfs::MemoryFileSystem::new();
+        files.insert("disk.vmdk", image);
+
+        let vmdk = VMDK::open_in(&files, "disk.vmdk").unwrap();
+        let metadata = vmdk.extent_files[0]
+            .sparse_extent_metadata
+            .as_ref()
+            .unwrap();
+        assert_eq!(metadata.grain_directory, [0, 3]);
+        assert!(metadata
+            .grain_tables
+            .iter()
+            .all(|table| table.get().is_none()));
+
+        let mut buf = [0u8; 1024];
+        vmdk.read_exact_at(19968, &mut buf).unwrap();
+        assert!(buf[..512].iter().all(|&b| b == 0xab));
+        assert!(buf[512..].iter().all(|&b| b == 0));
+        assert!(metadata.grain_tables[0].get().is_none());
+        assert_eq!(metadata.grain_tables[1].get().unwrap()[..], [4, 0, 0, 0]);
+        assert_eq!(
+            vmdk.allocated_ranges().unwrap(),
+            [Range {
+                start: 16384,
+                end: 20480
+            }]
+        );
+    }
+
+    #[test]
+    fn test_compressed_grains_are_cached() {
+        let descriptor = r#"# Disk DescriptorFile
+version=1
+CID=0000beef
+parentCID=ffffffff
+createType="monolithicSparse"
+
+# Extent description
+RW 64 SPARSE "disk.vmdk"
+"#;
+        let grain: Vec<u8> = (0..4096u32).map(|i| (i % 251) as u8).collect();
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        io::Write::write_all(&mut encoder, &grain).unwrap();
+        let compressed = encoder.finish().unwrap();
+        // Header, descriptor, grain directory, one grain table, one compressed grain.
+        let mut image = vec![0u8; 2048 + 12 + compressed.len()];
+        image[0..4].copy_from_slice(b"KDMV");
+        image[4..8].copy_from_slice(&1u32.to_le_bytes());
+        image[8..12].copy_from_slice(&FLAG_HAS_COMPRESSED_GRAIN_DATA.to_le_bytes());
+        image[12..20].copy_from_slice(&64u64.to_le_bytes());
+        image[20..28].copy_from_slice(&8u64.to_le_bytes());
+        image[28..36].copy_from_slice(&1u64.to_le_bytes());
+        image[36..44].copy_from_slice(&1u64.to_le_bytes());
+        image[44..48].copy_from_slice(&4u32.to_le_bytes());
+        image[56..64].copy_from_slice(&2i64.to_le_bytes());
+        image[512..512 + descriptor.len()].copy_from_slice(descriptor.as_bytes());
+        image[1028..1032].copy_from_slice(&3u32.to_le_bytes());
+        image[1536..1540].copy_from_slice(&4u32.to_le_bytes());
+        image[2048..2056].copy_from_slice(&32u64.to_le_bytes());
+        image[2056..2060].copy_from_slice(&(compressed.len() as u32).to_le_bytes());
+        image[2060..].copy_from_slice(&compressed);
+        let mut files = crate::vfs::MemoryFileSystem::new();
+        files.insert("disk.vmdk", image);
+
+        let vmdk = VMDK::open_in(&files, "disk.vmdk").unwrap();
+        let mut buf = vec![0u8; 4096];
+        for (i, sector) in buf.chunks_mut(512).enumerate() {
+            vmdk.read_exact_at(16384 + i as u64 * 512, sector).unwrap();
+        }
+        assert_eq!(buf, grain);
+        let mut stats = crate::stats::IoStats::default();
+        vmdk.io.add_to(&mut stats);
+        assert_eq!(stats.chunks_decompressed, 1);
+
+        let mut cache = GrainCache::new(8192);
+        for grain in 0..3 {
+            cache.insert(grain, Arc::new(vec![0; 4096]));
+        }
+        assert!(cache.get(0).is_none());
+        assert!(cache.get(1).is_some());
+        cache.insert(3, Arc::new(vec![0; 4096]));
+        assert!(cache.get(2).is_none());
+        assert_eq!(cache.size, 8192);
+    }
+
+    #[test]
+    fn test_stream_optimized_markers() {
+        let descriptor = r#"# Disk DescriptorFile
+version=1
+CID=0000beef
+parentCID=ffffffff
+createType="streamOptimized"
+
+# Extent description
+RW 64 SPARSE "disk.vmdk"
+"#;
+        let grain: Vec<u8> = (0..4096u32).map(|i| (i % 251) as u8).collect();
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        io::Write::write_all(&mut encoder, &grain).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let header = |grain_directory: i64| {
+            let mut header = [0u8; 512];
+            header[0..4].copy_from_slice(b"KDMV");
+            header[4..8].copy_from_slice(&3u32.to_le_bytes());
+            header[8..12].copy_from_slice(&0x30001u32.to_le_bytes());
+            header[12..20].copy_from_slice(&64u64.to_le_bytes());
+            header[20..28].copy_from_slice(&8u64.to_le_bytes());
+            header[28..36].copy_from_slice(&1u64.to_le_bytes());
+            header[36..44].copy_from_slice(&1u64.to_le_bytes());
+            header[44..48].copy_from_slice(&4u32.to_le_bytes());
+            header[56..64].copy_from_slice(&grain_directory.to_le_bytes());
+            header[64..72].copy_from_slice(&2u64.to_le_bytes());
+            header[77..79].copy_from_slice(&1u16.to_le_bytes());
+            header
+        };
+        let marker = |value: u64, kind: u32| {
+            let mut marker = [0u8; 512];
+            marker[0..8].copy_from_slice(&value.to_le_bytes());
+            marker[12..16].copy_from_slice(&kind.to_le_bytes());
+            marker
+        };
+        let table = |entries: [u32; 4]| {
+            let mut table = [0u8; 512];
+            for (i, entry) in entries.iter().enumerate() {
+                table[i * 4..i * 4 + 4].copy_from_slice(&entry.to_le_bytes());
+            }
+            table
+        };
+        // Header, descriptor, grain 4 (sector 32), grain table, grain directory, footer, end of stream.
+        let mut image = header(GD_AT_END).to_vec();
+        image.extend_from_slice(descriptor.as_bytes());
+        image.resize(1024, 0);
+        image.extend_from_slice(&32u64.to_le_bytes());
+        image.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+        image.extend_from_slice(&compressed);
+        image.resize(image.len().next_multiple_of(512), 0);
+        let grain_table = image.len() as u32 / 512 + 1;
+        image.extend_from_slice(&marker(1, MARKER_GRAIN_TABLE));
+        image.extend_from_slice(&table([2, 0, 0, 0]));
+        image.extend_from_slice(&marker(1, MARKER_GRAIN_DIRECTORY));
+        image.extend_from_slice(&table([0, grain_table, 0, 0]));
+        image.extend_from_slice(&marker(1, MARKER_FOOTER));
+        image.extend_from_slice(&header(grain_table as i64 + 2));
+        image.extend_from_slice(&marker(0, MARKER_EOS));
+
+        let mut padded = image.clone();
+        padded.resize(image.len() + 4096, 0);
+        let interrupted = image[..(grain_table as usize - 1) * 512].to_vec();
+        for (image, rebuilt) in [(image, false), (padded, false), (interrupted, true)] {
+            let mut files = crate::vfs::MemoryFileSystem::new();
+            files.insert("disk.vmdk", image);
+            let vmdk = VMDK::open_in(&files, "disk.vmdk").unwrap();
+            let metadata = vmdk.extent_files[0]
+                .sparse_extent_metadata
+                .as_ref()
+                .unwrap();
+            assert_eq!(metadata.rebuilt, rebuilt);
+            let mut buf = vec![0xffu8; 32768];
+            vmdk.read_exact_at(0, &mut buf).unwrap();
+            assert!(buf[..16384].iter().all(|&b| b == 0));
+            assert_eq!(buf[16384..20480], grain);
+            assert!(buf[20480..].iter().all(|&b| b == 0));
+            assert_eq!(
+                vmdk.allocated_ranges().unwrap(),
+                [Range {
+                    start: 16384,
+                    end: 20480
+                }]
+            );
+            assert_eq!(
+                vmdk.validate().unwrap(),
+                if rebuilt {
+                    vec![VMDKAnomaly::MissingFooter {
+                        extent: "disk.vmdk".to_string(),
+                    }]
+                } else {
+                    Vec::new()
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_se_sparse_extent() {
+        let descriptor = r#"# Disk DescriptorFile
+version=1
+CID=0000beef
+parentCID=ffffffff
+createType="seSparse"
+
+# Extent description
+RW 64 SESPARSE "disk-sesparse.vmdk"
+"#;
+        // Constant header, volatile header, grain directory, one grain table, two grains.
+        let mut image = vec![0u8; 20 * 512];
+        let mut put = |offset: usize, value: u64| {
+            image[offset..offset + 8].copy_from_slice(&value.to_le_bytes())
+        };
+        for (field, value) in [
+            (0, SESPARSE_CONST_HEADER_MAGIC),
+            (1, SESPARSE_VERSION),
+            (2, 64),
+            (3, 8),
+            (4, 1),
+            (10, 1),
+            (11, 1),
+            (16, 2),
+            (17, 1),
+            (18, 3),
+            (19, 1),
+            (24, 4),
+            (25, 16),
+        ] {
+            put(field * 8, value);
+        }
+        put(512, SESPARSE_VOLATILE_HEADER_MAGIC);
+        put(1024, SESPARSE_GRAIN_TABLE_ALLOCATED);
+        // Grain 0 is stored at grain index 1, grain 2 is zeroed, grain 3 is stored at grain index 0.
+        put(1536, 0x3001000000000000);
+        put(1536 + 16, 0x2000000000000000);
+        put(1536 + 24, 0x3000000000000000);
+        image[2048..6144].fill(0x11);
+        image[6144..].fill(0x22);
+        let mut files = crate::vfs::MemoryFileSystem::new();
+        files.insert("disk.vmdk", descriptor.as_bytes().to_vec());
+        files.insert("disk-sesparse.vmdk", image);
+
+        let vmdk = VMDK::open_in(&files, "disk.vmdk").unwrap();
+        assert_eq!(vmdk.disk_type(), &VMDKDiskType::SeSparse);
+        assert_eq!(vmdk.extents()[0].extent_type, VMDKExtentType::SeSparse);
+        let mut buf = vec![0xffu8; 16384];
+        vmdk.read_exact_at(0, &mut buf).unwrap();
+        assert!(buf[..4096].iter().all(|&b| b == 0x22));
+        assert!(buf[4096..12288].iter().all(|&b| b == 0));
+        assert!(buf[12288..].iter().all(|&b| b == 0x11));
+        assert_eq!(vmdk.allocated_ranges().unwrap(), [0..4096, 12288..16384]);
+        assert!(vmdk.validate().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_descriptor_accessors() {
+        let descriptor = r#"# Disk DescriptorFile
+version=3
+CID=0000beef
+parentCID=ffffffff
+createType="monolithicFlat"
+
+# Extent description
+RW 8 FLAT "disk-flat.vmdk" 0
+
+# Change Tracking File
+changeTrackPath="disk-ctk.vmdk"
+
+# The Disk Data Base
+ddb.adapterType = "lsilogic"
+ddb.thinProvisioned = "1"
+"#;
+        let mut files = crate::vfs::MemoryFileSystem::new();
+        files.insert("vm/disk.vmdk", descriptor.as_bytes().to_vec());
+        files.insert("vm/disk-flat.vmdk", vec![0x5a; 8 * 512]);
+
+        let vmdk = VMDK::open_in(&files, "vm/disk.vmdk").unwrap();
+        assert_eq!(vmdk.disk_type(), &VMDKDiskType::MonolithicFlat);
+        assert_eq!(vmdk.version(), 3);
+        assert_eq!(vmdk.cid(), 0xbeef);
+        assert_eq!(vmdk.parent_cid(), None);
+        assert!(vmdk.parent().is_none());
+        assert_eq!(vmdk.change_tracking_path(), Some("disk-ctk.vmdk"));
+        let extent = &vmdk.extents()[0];
+        assert_eq!(extent.extent_type, VMDKExtentType::Flat);
+        assert_eq!(extent.sector_number, 8);
+        assert_eq!(extent.extent_file_name.as_deref(), Some("disk-flat.vmdk"));
+        let ddb = vmdk.disk_database().unwrap();
+        assert_eq!(ddb.ddb_adapter_type, Some(VMDKDiskAdapterType::LSILogic));
+    }
 }
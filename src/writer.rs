@@ -0,0 +1,257 @@
+//! Raw (dd) image writer, used to acquire a body or a device.
+//!
+//! [`RawWriter`] copies a source from its first to its last byte into any
+//! [`Write`] sink, optionally computing digests of the copied data and
+//! reporting progress along the way. When writing to a file, runs of zeroes
+//! can be left as holes to keep sparse sources sparse.
+//!
+//! ```no_run
+//! # use exhume_body::{Body, HashAlgorithm, RawWriter};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut body = Body::try_new("/dev/sdb".to_string(), "device")?;
+//! let summary = RawWriter::new()
+//!     .hash(HashAlgorithm::Md5)
+//!     .hash(HashAlgorithm::Sha256)
+//!     .progress(|done, total| eprintln!("{}/{}", done, total))
+//!     .write_to_path(&mut body, "/evidence/sdb.dd")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::hash::{HashAlgorithm, MultiHasher};
+use log::info;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Default size of the blocks copied from the source (1 MiB).
+pub const RAW_WRITER_BLOCK_SIZE: usize = 1024 * 1024;
+
+/// Granularity at which zeroes are turned into holes by sparse writes.
+const SPARSE_BLOCK_SIZE: usize = 4096;
+
+/// Outcome of a [`RawWriter`] run.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WriteSummary {
+    /// Number of bytes copied to the output.
+    pub bytes_written: u64,
+    /// Digests of the copied data, in the order they were requested.
+    pub hashes: Vec<(HashAlgorithm, String)>,
+}
+
+/// Streams a readable source to a raw image.
+pub struct RawWriter {
+    block_size: usize,
+    hashes: Vec<HashAlgorithm>,
+    sparse: bool,
+    progress: Option<Box<dyn FnMut(u64, u64)>>,
+}
+
+impl Default for RawWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RawWriter {
+    pub fn new() -> Self {
+        Self {
+            block_size: RAW_WRITER_BLOCK_SIZE,
+            hashes: Vec::new(),
+            sparse: false,
+            progress: None,
+        }
+    }
+
+    /// Size of the blocks read from the source and written to the output.
+    pub fn block_size(mut self, block_size: usize) -> Self {
+        self.block_size = block_size.max(1);
+        self
+    }
+
+    /// Compute `algorithm` over the copied data.
+    pub fn hash(mut self, algorithm: HashAlgorithm) -> Self {
+        self.hashes.push(algorithm);
+        self
+    }
+
+    /// Leave blocks of zeroes as holes in files written by
+    /// [`write_to_path`](Self::write_to_path).
+    pub fn sparse(mut self, sparse: bool) -> Self {
+        self.sparse = sparse;
+        self
+    }
+
+    /// Call `callback(bytes_done, bytes_total)` after every block.
+    pub fn progress(mut self, callback: impl FnMut(u64, u64) + 'static) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Copy `source` from its start to its end into `output`.
+    pub fn write<R, W>(&mut self, source: &mut R, output: &mut W) -> io::Result<WriteSummary>
+    where
+        R: Read + Seek + ?Sized,
+        W: Write + ?Sized,
+    {
+        let total = source.seek(SeekFrom::End(0))?;
+        source.seek(SeekFrom::Start(0))?;
+
+        let mut hasher = MultiHasher::new(&self.hashes);
+        let mut buffer = vec![0u8; self.block_size];
+        let mut done = 0u64;
+
+        while done < total {
+            let want = (total - done).min(self.block_size as u64) as usize;
+            let n = source.read(&mut buffer[..want])?;
+            if n == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("Source ended at {} bytes, {} expected", done, total),
+                ));
+            }
+            output.write_all(&buffer[..n])?;
+            hasher.update(&buffer[..n]);
+            done += n as u64;
+
+            if let Some(callback) = self.progress.as_mut() {
+                callback(done, total);
+            }
+        }
+        output.flush()?;
+
+        Ok(WriteSummary {
+            bytes_written: done,
+            hashes: hasher.finalize(),
+        })
+    }
+
+    /// Copy `source` into a new raw image at `path`.
+    ///
+    /// Refuses to overwrite an existing file.
+    pub fn write_to_path<R>(
+        &mut self,
+        source: &mut R,
+        path: impl AsRef<Path>,
+    ) -> io::Result<WriteSummary>
+    where
+        R: Read + Seek + ?Sized,
+    {
+        let path = path.as_ref();
+        let file = OpenOptions::new().write(true).create_new(true).open(path)?;
+        let summary = if self.sparse {
+            let mut output = SparseFile::new(file);
+            let summary = self.write(source, &mut output)?;
+            output.finish()?;
+            summary
+        } else {
+            let mut output = BufWriter::new(file);
+            let summary = self.write(source, &mut output)?;
+            output
+                .into_inner()
+                .map_err(|e| e.into_error())?
+                .sync_all()?;
+            summary
+        };
+
+        info!(
+            "Wrote {} bytes to {}",
+            summary.bytes_written,
+            path.display()
+        );
+        for (algorithm, digest) in &summary.hashes {
+            info!("  {}: {}", algorithm, digest);
+        }
+        Ok(summary)
+    }
+}
+
+/// File sink skipping over zero blocks instead of writing them.
+struct SparseFile {
+    file: File,
+    length: u64,
+    hole: u64,
+}
+
+impl SparseFile {
+    fn new(file: File) -> Self {
+        Self {
+            file,
+            length: 0,
+            hole: 0,
+        }
+    }
+
+    /// Extend the file over a trailing hole and sync it to disk.
+    fn finish(self) -> io::Result<()> {
+        self.file.set_len(self.length)?;
+        self.file.sync_all()
+    }
+}
+
+impl Write for SparseFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for block in buf.chunks(SPARSE_BLOCK_SIZE) {
+            if block.iter().all(|&b| b == 0) {
+                self.hole += block.len() as u64;
+            } else {
+                if self.hole > 0 {
+                    self.file.seek(SeekFrom::Current(self.hole as i64))?;
+                    self.hole = 0;
+                }
+                self.file.write_all(block)?;
+            }
+            self.length += block.len() as u64;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::io::Cursor;
+    use std::rc::Rc;
+
+    #[test]
+    fn copies_and_hashes_the_whole_source() {
+        let mut source = Cursor::new(b"abc".to_vec());
+        source.set_position(2);
+        let calls = Rc::new(Cell::new(0));
+        let seen = calls.clone();
+
+        let mut output = Vec::new();
+        let summary = RawWriter::new()
+            .block_size(2)
+            .hash(HashAlgorithm::Md5)
+            .hash(HashAlgorithm::Sha256)
+            .progress(move |_, total| {
+                assert_eq!(total, 3);
+                seen.set(seen.get() + 1);
+            })
+            .write(&mut source, &mut output)
+            .unwrap();
+
+        assert_eq!(output, b"abc");
+        assert_eq!(summary.bytes_written, 3);
+        assert_eq!(calls.get(), 2);
+        assert_eq!(
+            summary.hashes,
+            vec![
+                (
+                    HashAlgorithm::Md5,
+                    "900150983cd24fb0d6963f7d28e17f72".to_string()
+                ),
+                (
+                    HashAlgorithm::Sha256,
+                    "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,624 @@
+//! **XZ-compressed RAW reader**
+//!
+//! This module gives random access to raw disk images compressed with `xz`
+//! (`disk.raw.xz`) without decompressing them to disk first.
+//!
+//! # On-disk layout
+//!
+//! | Part          | Size     | Description                                      |
+//! |---------------|----------|--------------------------------------------------|
+//! | Stream header | 12       | Magic `FD 37 7A 58 5A 00`, flags, CRC32          |
+//! | Blocks        | …        | Block header, LZMA2 data, padding, check         |
+//! | Index         | …        | Unpadded and uncompressed size of every block    |
+//! | Stream footer | 12       | CRC32, backward size, flags, magic `YZ`          |
+//!
+//! Several streams may be concatenated, optionally separated by zero padding.
+//! The reader walks the streams backwards from the end of the file through
+//! their indexes, which yields the compressed and decompressed offset of every
+//! block without decoding anything.  Reads then decode a single block at a
+//! time, so the cost of a seek is bounded by the block size: images written by
+//! `xz -T0` or `xz --block-size=…` seek quickly, while a single-block image
+//! has to be decoded from its start.
+//!
+//! Only the LZMA2 filter is supported (no BCJ or delta pre-filters).
+//!
+//! Refer to the `.xz` file format specification (tukaani.org) for the
+//! reference definition.
+
+use crate::error::ExhumeBodyError;
+use crate::info::FormatInfo;
+use crate::read_at::ReadAt;
+use crate::source::Source;
+use crate::vfs::{FileSystem, StdFileSystem};
+use log::debug;
+use lzma_rust2::Lzma2Reader;
+use std::cmp::min;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+
+// ---- XZ constants -----------------------------------------------------------
+
+/// Stream header magic.
+const XZ_HEADER_MAGIC: [u8; 6] = [0xFD, 0x37, 0x7A, 0x58, 0x5A, 0x00];
+
+/// Stream footer magic.
+const XZ_FOOTER_MAGIC: [u8; 2] = [0x59, 0x5A];
+
+/// Size of the stream header and of the stream footer.
+const XZ_STREAM_HEADER_SIZE: u64 = 12;
+
+/// Block header flag: compressed size field present.
+const XZ_BLOCK_FLAG_COMPRESSED_SIZE: u8 = 0x40;
+
+/// Block header flag: uncompressed size field present.
+const XZ_BLOCK_FLAG_UNCOMPRESSED_SIZE: u8 = 0x80;
+
+/// Filter ID of LZMA2.
+const XZ_FILTER_LZMA2: u64 = 0x21;
+
+/// Check sizes indexed by the check type in the stream flags.
+const XZ_CHECK_SIZES: [u64; 16] = [0, 4, 4, 4, 8, 8, 8, 16, 16, 16, 32, 32, 32, 64, 64, 64];
+
+/// XZ does not record a sector size; assume 512-byte sectors.
+const XZ_SECTOR_SIZE: u16 = 512;
+
+// ---- Helpers ----------------------------------------------------------------
+
+fn le_u32(data: &[u8], off: usize) -> u32 {
+    u32::from_le_bytes(data[off..off + 4].try_into().unwrap())
+}
+
+/// Check names for the types defined by the specification.
+fn check_name(check: u8) -> &'static str {
+    match check {
+        0x00 => "None",
+        0x01 => "CRC32",
+        0x04 => "CRC64",
+        0x0A => "SHA-256",
+        _ => "Reserved",
+    }
+}
+
+/// Decode a multibyte integer (7 bits per byte, at most 9 bytes) at `*off`.
+fn read_varint(data: &[u8], off: &mut usize) -> Result<u64, String> {
+    let mut value = 0u64;
+    for i in 0..9 {
+        let byte = *data
+            .get(*off)
+            .ok_or_else(|| "Truncated xz multibyte integer".to_string())?;
+        *off += 1;
+        value |= ((byte & 0x7F) as u64) << (i * 7);
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err("Invalid xz multibyte integer".to_string())
+}
+
+/// Round `value` up to a multiple of four.
+fn pad4(value: u64) -> u64 {
+    (value + 3) & !3
+}
+
+/// Decode the LZMA2 dictionary size from its one-byte property.
+fn lzma2_dict_size(props: u8) -> Result<u32, String> {
+    match props {
+        0..=39 => Ok((2 | (props as u32 & 1)) << (props / 2 + 11)),
+        40 => Ok(u32::MAX),
+        _ => Err(format!("Invalid LZMA2 dictionary size property {}", props)),
+    }
+}
+
+// ---- Block index ------------------------------------------------------------
+
+/// Location of one block inside the compressed file and the decompressed stream.
+#[derive(Clone, Debug)]
+struct XzBlock {
+    /// Absolute offset of the block header in the `.xz` file.
+    compressed_offset: u64,
+    /// Block size without padding (header + data + check).
+    unpadded_size: u64,
+    /// Size of the check field of this block's stream.
+    check_size: u64,
+    /// Offset of the first byte this block decodes to.
+    decompressed_offset: u64,
+    /// Number of bytes this block decodes to.
+    decompressed_size: u64,
+}
+
+/// Stream-level information kept for display.
+#[derive(Clone, Debug)]
+struct XzStream {
+    /// Check type from the stream flags.
+    check: u8,
+    /// Number of blocks in the stream.
+    blocks: usize,
+}
+
+/// Read `len` bytes at `offset`.
+fn read_at(file: &mut Source, offset: u64, len: usize) -> Result<Vec<u8>, String> {
+    let mut data = vec![0u8; len];
+    file.seek(SeekFrom::Start(offset))
+        .and_then(|_| file.read_exact(&mut data))
+        .map_err(|e| format!("Error reading xz image at offset {}: {}", offset, e))?;
+    Ok(data)
+}
+
+/// Walk every stream backwards from the end of the file and collect the
+/// blocks listed in their indexes, in file order.
+fn read_index(file: &mut Source, file_size: u64) -> Result<(Vec<XzBlock>, Vec<XzStream>), String> {
+    let mut streams: Vec<(Vec<XzBlock>, XzStream)> = Vec::new();
+    let mut end = file_size;
+
+    while end > 0 {
+        // Stream padding: zero bytes in multiples of four.
+        if !end.is_multiple_of(4) {
+            return Err("xz file size is not a multiple of four".to_string());
+        }
+        if read_at(file, end - 4, 4)? == [0, 0, 0, 0] {
+            end -= 4;
+            continue;
+        }
+
+        let footer_offset = end
+            .checked_sub(XZ_STREAM_HEADER_SIZE)
+            .ok_or_else(|| "Truncated xz stream footer".to_string())?;
+        let footer = read_at(file, footer_offset, XZ_STREAM_HEADER_SIZE as usize)?;
+        if footer[10..12] != XZ_FOOTER_MAGIC {
+            return Err(format!(
+                "Invalid xz stream footer at offset {}",
+                footer_offset
+            ));
+        }
+        let index_size = (le_u32(&footer, 4) as u64 + 1) * 4;
+        let check = footer[9] & 0x0F;
+        let check_size = XZ_CHECK_SIZES[check as usize];
+
+        let index_offset = footer_offset
+            .checked_sub(index_size)
+            .ok_or_else(|| "xz index is larger than the file".to_string())?;
+        let index = read_at(file, index_offset, index_size as usize)?;
+        if index[0] != 0 {
+            return Err(format!(
+                "Invalid xz index indicator at offset {}",
+                index_offset
+            ));
+        }
+
+        let mut off = 1;
+        let count = read_varint(&index, &mut off)?;
+        let mut records = Vec::new();
+        for _ in 0..count {
+            let unpadded_size = read_varint(&index, &mut off)?;
+            let decompressed_size = read_varint(&index, &mut off)?;
+            records.push((unpadded_size, decompressed_size));
+        }
+
+        let blocks_size: u64 = records.iter().map(|(u, _)| pad4(*u)).sum();
+        let header_offset = index_offset
+            .checked_sub(blocks_size + XZ_STREAM_HEADER_SIZE)
+            .ok_or_else(|| "xz index describes more data than the file holds".to_string())?;
+        let header = read_at(file, header_offset, XZ_STREAM_HEADER_SIZE as usize)?;
+        if header[0..6] != XZ_HEADER_MAGIC || header[6..8] != footer[8..10] {
+            return Err(format!(
+                "Invalid xz stream header at offset {}",
+                header_offset
+            ));
+        }
+
+        let mut compressed_offset = header_offset + XZ_STREAM_HEADER_SIZE;
+        let blocks = records
+            .into_iter()
+            .map(|(unpadded_size, decompressed_size)| {
+                let block = XzBlock {
+                    compressed_offset,
+                    unpadded_size,
+                    check_size,
+                    decompressed_offset: 0,
+                    decompressed_size,
+                };
+                compressed_offset += pad4(unpadded_size);
+                block
+            })
+            .collect::<Vec<_>>();
+        debug!(
+            "Found xz stream at offset {} with {} blocks",
+            header_offset,
+            blocks.len()
+        );
+        let stream = XzStream {
+            check,
+            blocks: blocks.len(),
+        };
+        streams.push((blocks, stream));
+        end = header_offset;
+    }
+
+    // Streams were found last-to-first; lay out their blocks in file order.
+    let mut all_blocks = Vec::new();
+    let mut all_streams = Vec::new();
+    let mut decompressed_offset = 0u64;
+    for (blocks, stream) in streams.into_iter().rev() {
+        for mut block in blocks {
+            block.decompressed_offset = decompressed_offset;
+            decompressed_offset += block.decompressed_size;
+            all_blocks.push(block);
+        }
+        all_streams.push(stream);
+    }
+
+    Ok((all_blocks, all_streams))
+}
+
+// ---- Public xz reader -------------------------------------------------------
+
+/// Compressed bytes of one block.
+///
+/// Seeks before every read so that clones sharing the underlying file
+/// descriptor do not disturb each other.
+struct BlockSource {
+    file: Source,
+    offset: u64,
+    remaining: u64,
+}
+
+impl Read for BlockSource {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = min(buf.len() as u64, self.remaining) as usize;
+        if len == 0 {
+            return Ok(0);
+        }
+        let n = self.file.read_at(self.offset, &mut buf[..len])?;
+        self.offset += n as u64;
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+/// Decoder positioned inside one block.
+struct BlockCursor {
+    /// Index of the block being decoded.
+    block: usize,
+    /// Offset inside the block's decompressed data.
+    offset: u64,
+    /// LZMA2 decoder reading the block's compressed bytes.
+    decoder: Lzma2Reader<BlockSource>,
+}
+
+/// Seekable reader over an xz-compressed raw image.
+///
+/// Implements [`Read`], [`Seek`] and [`Clone`] so it can be used as a drop-in
+/// source of evidence bytes inside the [`Body`](crate::Body) abstraction.
+pub struct XZ {
+    /// Open file handle to the `.xz` file.
+    file: Source,
+    /// Original path (kept for display).
+    path: String,
+    /// Size of the compressed file.
+    compressed_size: u64,
+    /// Block index ordered by decompressed offset.
+    blocks: Arc<[XzBlock]>,
+    /// Streams in file order.
+    streams: Arc<[XzStream]>,
+    /// Total decompressed size.
+    size: u64,
+    /// Decoder of the most recently read block.
+    cursor: Option<Box<BlockCursor>>,
+    /// Virtual cursor position inside the decompressed stream.
+    position: u64,
+}
+
+impl XZ {
+    // ---- Construction -------------------------------------------------------
+
+    /// Open an xz-compressed image and build its block index from the stream
+    /// indexes.
+    pub fn new(file_path: &str) -> Result<XZ, ExhumeBodyError> {
+        Self::open_in(&StdFileSystem, file_path)
+    }
+
+    /// Same as [`XZ::new`], opening the image through `fs` instead of the
+    /// operating system.
+    pub fn open_in(fs: &dyn FileSystem, file_path: &str) -> Result<XZ, ExhumeBodyError> {
+        Self::new_impl(fs, file_path).map_err(ExhumeBodyError::Xz)
+    }
+
+    fn new_impl(fs: &dyn FileSystem, file_path: &str) -> Result<XZ, String> {
+        let path = Path::new(file_path);
+        let mut file = fs
+            .open(path)
+            .map_err(|e| format!("Error opening xz image: {}", e))?;
+
+        let mut magic = [0u8; 6];
+        file.read_exact(&mut magic)
+            .map_err(|e| format!("Error reading xz header: {}", e))?;
+        if magic != XZ_HEADER_MAGIC {
+            return Err("Invalid xz signature (expected FD 37 7A 58 5A 00)".to_string());
+        }
+
+        let compressed_size = file
+            .seek(SeekFrom::End(0))
+            .map_err(|e| format!("Error reading xz image size: {}", e))?;
+        let (blocks, streams) = read_index(&mut file, compressed_size)?;
+        let size = blocks
+            .last()
+            .map_or(0, |b| b.decompressed_offset + b.decompressed_size);
+        debug!("Indexed {} xz blocks ({} bytes)", blocks.len(), size);
+
+        Ok(XZ {
+            file,
+            path: file_path.to_string(),
+            compressed_size,
+            blocks: blocks.into(),
+            streams: streams.into(),
+            size,
+            cursor: None,
+            position: 0,
+        })
+    }
+
+    // ---- Info helpers -------------------------------------------------------
+
+    /// Sizes, stream and block counts, and each stream's integrity check.
+    pub fn info(&self) -> FormatInfo {
+        let streams = self
+            .streams
+            .iter()
+            .enumerate()
+            .map(|(i, stream)| {
+                (
+                    format!("Stream {}", i),
+                    format!(
+                        "{} blocks, check {}",
+                        stream.blocks,
+                        check_name(stream.check)
+                    ),
+                )
+            })
+            .collect();
+        FormatInfo::new("XZ Image Information")
+            .field("Path", &self.path)
+            .field("Compressed", format!("{} bytes", self.compressed_size))
+            .field("Size", format!("{} bytes", self.size))
+            .field("Sector Size", XZ_SECTOR_SIZE)
+            .field("Streams", self.streams.len())
+            .field("Blocks", self.blocks.len())
+            .section("Streams", streams)
+    }
+
+    /// Print parsed metadata to the log.
+    pub fn print_info(&self) {
+        self.info().log();
+    }
+
+    /// Returns the sector size (always 512 for xz images).
+    pub fn get_sector_size(&self) -> u16 {
+        XZ_SECTOR_SIZE
+    }
+
+    /// Uncompressed size of the image in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    // ---- Internal block reading ---------------------------------------------
+
+    /// Parse the header of block `index` and start decoding it.
+    fn open_block(&mut self, index: usize) -> io::Result<Box<BlockCursor>> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let block = &self.blocks[index];
+
+        let mut size_byte = [0u8; 1];
+        self.file
+            .read_exact_at(block.compressed_offset, &mut size_byte)?;
+        if size_byte[0] == 0 {
+            return Err(invalid(format!(
+                "Invalid xz block header at offset {}",
+                block.compressed_offset
+            )));
+        }
+        let header_size = (size_byte[0] as usize + 1) * 4;
+        let mut header = vec![0u8; header_size];
+        header[0] = size_byte[0];
+        self.file
+            .read_exact_at(block.compressed_offset + 1, &mut header[1..])?;
+
+        // Flags, optional sizes, then the filter chain.
+        let flags = header[1];
+        let mut off = 2;
+        if flags & XZ_BLOCK_FLAG_COMPRESSED_SIZE != 0 {
+            read_varint(&header, &mut off).map_err(invalid)?;
+        }
+        if flags & XZ_BLOCK_FLAG_UNCOMPRESSED_SIZE != 0 {
+            read_varint(&header, &mut off).map_err(invalid)?;
+        }
+        let filter_count = (flags & 0x03) + 1;
+        let filter_id = read_varint(&header, &mut off).map_err(invalid)?;
+        let props_size = read_varint(&header, &mut off).map_err(invalid)? as usize;
+        if filter_count != 1 || filter_id != XZ_FILTER_LZMA2 || props_size != 1 {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "Unsupported xz filter chain in block at offset {} (only plain LZMA2 is supported)",
+                    block.compressed_offset
+                ),
+            ));
+        }
+        let props = *header
+            .get(off)
+            .ok_or_else(|| invalid("Truncated xz block header".to_string()))?;
+        let dict_size = lzma2_dict_size(props).map_err(invalid)?;
+
+        // The dictionary never needs to exceed the data the block decodes to.
+        let dict_size = min(dict_size as u64, block.decompressed_size.max(4096)) as u32;
+        let data_size = block
+            .unpadded_size
+            .checked_sub(header_size as u64 + block.check_size)
+            .ok_or_else(|| invalid("xz block is smaller than its header".to_string()))?;
+        let source = BlockSource {
+            file: self.file.clone(),
+            offset: block.compressed_offset + header_size as u64,
+            remaining: data_size,
+        };
+
+        Ok(Box::new(BlockCursor {
+            block: index,
+            offset: 0,
+            decoder: Lzma2Reader::new(source, dict_size, None),
+        }))
+    }
+
+    /// Fill `buf` from the virtual offset `self.position`, without crossing a
+    /// block boundary. Returns the number of bytes produced.
+    fn read_block(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let index = self
+            .blocks
+            .partition_point(|b| b.decompressed_offset + b.decompressed_size <= self.position);
+        let block = self.blocks[index].clone();
+        let target = self.position - block.decompressed_offset;
+        let len = min(buf.len() as u64, block.decompressed_size - target) as usize;
+
+        // Reuse the live decoder unless it is in another block or already past `target`.
+        let reusable = matches!(&self.cursor, Some(c) if c.block == index && c.offset <= target);
+        if !reusable {
+            self.cursor = Some(self.open_block(index)?);
+        }
+        let cursor = self.cursor.as_mut().unwrap();
+
+        let skip = target - cursor.offset;
+        if skip > 0 {
+            let skipped = io::copy(&mut (&mut cursor.decoder).take(skip), &mut io::sink())?;
+            cursor.offset += skipped;
+            if skipped < skip {
+                return Err(truncated_block(&block));
+            }
+        }
+
+        cursor.decoder.read_exact(&mut buf[..len]).map_err(|e| {
+            if e.kind() == io::ErrorKind::UnexpectedEof {
+                truncated_block(&block)
+            } else {
+                e
+            }
+        })?;
+        cursor.offset += len as u64;
+        Ok(len)
+    }
+}
+
+/// Error for a block decoding to fewer bytes than the index announces.
+fn truncated_block(block: &XzBlock) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "xz block at offset {} decodes to fewer than {} bytes",
+            block.compressed_offset, block.decompressed_size
+        ),
+    )
+}
+
+// ---- Clone ------------------------------------------------------------------
+
+impl Clone for XZ {
+    fn clone(&self) -> Self {
+        Self {
+            file: self.file.clone(),
+            path: self.path.clone(),
+            compressed_size: self.compressed_size,
+            blocks: self.blocks.clone(),
+            streams: self.streams.clone(),
+            size: self.size,
+            // Reset the decoder – will be lazily reopened.
+            cursor: None,
+            position: self.position,
+        }
+    }
+}
+
+// ---- Read -------------------------------------------------------------------
+
+impl Read for XZ {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.position >= self.size {
+            return Ok(0);
+        }
+
+        let want = min(buf.len() as u64, self.size - self.position) as usize;
+        let mut total = 0usize;
+
+        while total < want {
+            let n = self.read_block(&mut buf[total..want])?;
+            total += n;
+            self.position += n as u64;
+        }
+
+        Ok(total)
+    }
+}
+
+// ---- Seek -------------------------------------------------------------------
+
+impl Seek for XZ {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let next = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.size.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Seek before start or overflow")
+        })?;
+
+        self.position = next;
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use lzma_rust2::{XzOptions, XzWriter};
+    use std::io::Write;
+    use std::num::NonZeroU64;
+
+    /// One xz stream split into blocks of at most `block_size` bytes.
+    fn compress(data: &[u8], block_size: u64) -> Vec<u8> {
+        let mut options = XzOptions::with_preset(0);
+        options.lzma_options.dict_size = 4096;
+        options.set_block_size(NonZeroU64::new(block_size));
+        let mut writer = XzWriter::new(Vec::new(), options).unwrap();
+        writer.write_all(data).unwrap();
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn reads_blocks_across_padded_streams() {
+        let data: Vec<u8> = (0..48 * 1024u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8 & 0x1F)
+            .collect();
+        let (head, tail) = data.split_at(40 * 1024);
+        let mut image = compress(head, 16 * 1024);
+        // Stream padding is a multiple of four NUL bytes.
+        image.extend_from_slice(&[0; 8]);
+        image.extend(compress(tail, 16 * 1024));
+
+        let dir = TempDir::new("xz");
+        let path = dir.join("disk.raw.xz");
+        std::fs::write(&path, image).unwrap();
+        let mut xz = XZ::new(&path.to_string_lossy()).unwrap();
+
+        let mut back = Vec::new();
+        xz.read_to_end(&mut back).unwrap();
+        assert_eq!(back, data);
+
+        // Across the stream boundary, then inside the second block.
+        for offset in [head.len() as u64 - 100, 20 * 1024] {
+            xz.seek(SeekFrom::Start(offset)).unwrap();
+            let mut buf = [0u8; 300];
+            xz.read_exact(&mut buf).unwrap();
+            assert_eq!(buf[..], data[offset as usize..offset as usize + 300]);
+        }
+    }
+}
@@ -0,0 +1,634 @@
+//! **Zstandard-compressed RAW reader**
+//!
+//! This module gives random access to raw disk images compressed with
+//! `zstd` (`disk.raw.zst`) without decompressing them to disk first.
+//!
+//! # On-disk layout
+//!
+//! A `.zst` file is a sequence of independent frames.  Images written in the
+//! **seekable format** (`zstd --seekable`, `t2sz`, …) end with a skippable
+//! frame holding a seek table:
+//!
+//! | Offset   | Size   | Description                                     |
+//! |----------|--------|-------------------------------------------------|
+//! | `0x00`   | 4      | Skippable magic `0x184D2A5E`                    |
+//! | `0x04`   | 4      | Frame size (entries + 9-byte footer)            |
+//! | `0x08`   | 8 / 12 | Per frame: compressed size, decompressed size,  |
+//! |          |        | and an optional XXH64-based checksum            |
+//! | end-9    | 4      | Number of frames                                |
+//! | end-5    | 1      | Descriptor (bit 7 = checksums present)          |
+//! | end-4    | 4      | Seekable magic `0x8F92EAB1`                     |
+//!
+//! All fields are little-endian.  When no seek table is present the frame
+//! headers are walked instead, which requires every frame to record its
+//! content size (the default for multi-threaded `zstd` output).
+//!
+//! Reads decode a single frame at a time, so the cost of a seek is bounded by
+//! the frame size rather than by the size of the image.
+//!
+//! Refer to RFC 8878 and `contrib/seekable_format` in the zstd repository for
+//! the reference definitions.
+
+use crate::error::ExhumeBodyError;
+use crate::info::FormatInfo;
+use crate::read_at::ReadAt;
+use crate::source::Source;
+use crate::vfs::{FileSystem, StdFileSystem};
+use log::debug;
+use ruzstd::decoding::{FrameDecoder, StreamingDecoder};
+use std::cmp::min;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+
+// ---- Zstd constants ---------------------------------------------------------
+
+/// Magic of a regular zstd frame.
+const ZSTD_FRAME_MAGIC: u32 = 0xFD2F_B528;
+
+/// Skippable frames use magics `0x184D2A50..=0x184D2A5F`.
+const ZSTD_SKIPPABLE_MAGIC_MASK: u32 = 0xFFFF_FFF0;
+const ZSTD_SKIPPABLE_MAGIC: u32 = 0x184D_2A50;
+
+/// Magic of the skippable frame carrying the seek table.
+const ZSTD_SEEK_TABLE_MAGIC: u32 = 0x184D_2A5E;
+
+/// Magic closing the seek table footer.
+const ZSTD_SEEKABLE_MAGIC: u32 = 0x8F92_EAB1;
+
+/// Size of the seek table footer.
+const ZSTD_SEEK_FOOTER_SIZE: u64 = 9;
+
+/// Seek table descriptor bit signalling per-frame checksums.
+const ZSTD_SEEK_CHECKSUM_FLAG: u8 = 0x80;
+
+/// Seek table descriptor bits that must be zero.
+const ZSTD_SEEK_RESERVED_MASK: u8 = 0x7C;
+
+/// Frame header descriptor bit signalling a trailing content checksum.
+const ZSTD_FRAME_CHECKSUM_FLAG: u8 = 0x04;
+
+/// Frame header descriptor bit signalling a single-segment frame.
+const ZSTD_FRAME_SINGLE_SEGMENT: u8 = 0x20;
+
+/// Block type of an RLE block (a single byte repeated `size` times).
+const ZSTD_BLOCK_RLE: u32 = 1;
+
+/// Reserved (invalid) block type.
+const ZSTD_BLOCK_RESERVED: u32 = 3;
+
+/// Zstd does not record a sector size; assume 512-byte sectors.
+const ZSTD_SECTOR_SIZE: u16 = 512;
+
+// ---- Helpers ----------------------------------------------------------------
+
+fn le_u32(data: &[u8], off: usize) -> u32 {
+    u32::from_le_bytes(data[off..off + 4].try_into().unwrap())
+}
+
+/// Decode a little-endian integer stored in `data` (at most 8 bytes).
+fn le_uint(data: &[u8]) -> u64 {
+    data.iter()
+        .rev()
+        .fold(0u64, |acc, &b| (acc << 8) | b as u64)
+}
+
+// ---- Frame index ------------------------------------------------------------
+
+/// Location of one frame inside the compressed file and the decompressed stream.
+#[derive(Clone, Debug)]
+struct ZstdFrame {
+    /// Absolute offset of the frame in the `.zst` file.
+    compressed_offset: u64,
+    /// Size of the frame in the `.zst` file.
+    compressed_size: u64,
+    /// Offset of the first byte this frame decodes to.
+    decompressed_offset: u64,
+    /// Number of bytes this frame decodes to.
+    decompressed_size: u64,
+}
+
+/// Parse the seek table at the end of the file, if there is one.
+fn read_seek_table(
+    file: &mut Source,
+    file_size: u64,
+) -> Result<Option<(Vec<ZstdFrame>, bool)>, String> {
+    if file_size < ZSTD_SEEK_FOOTER_SIZE + 8 {
+        return Ok(None);
+    }
+    let mut footer = [0u8; ZSTD_SEEK_FOOTER_SIZE as usize];
+    file.seek(SeekFrom::Start(file_size - ZSTD_SEEK_FOOTER_SIZE))
+        .and_then(|_| file.read_exact(&mut footer))
+        .map_err(|e| format!("Error reading zstd seek table footer: {}", e))?;
+    if le_u32(&footer, 5) != ZSTD_SEEKABLE_MAGIC {
+        return Ok(None);
+    }
+
+    let frame_count = le_u32(&footer, 0) as u64;
+    let descriptor = footer[4];
+    if descriptor & ZSTD_SEEK_RESERVED_MASK != 0 {
+        return Err("Invalid zstd seek table descriptor (reserved bits set)".to_string());
+    }
+    let checksums = descriptor & ZSTD_SEEK_CHECKSUM_FLAG != 0;
+    let entry_size: u64 = if checksums { 12 } else { 8 };
+
+    let table_size = frame_count * entry_size + ZSTD_SEEK_FOOTER_SIZE;
+    let table_start = file_size
+        .checked_sub(table_size + 8)
+        .ok_or_else(|| "zstd seek table is larger than the file".to_string())?;
+    let mut table = vec![0u8; (table_size + 8) as usize];
+    file.seek(SeekFrom::Start(table_start))
+        .and_then(|_| file.read_exact(&mut table))
+        .map_err(|e| format!("Error reading zstd seek table: {}", e))?;
+    if le_u32(&table, 0) != ZSTD_SEEK_TABLE_MAGIC || le_u32(&table, 4) as u64 != table_size {
+        return Err("Invalid zstd seek table frame header".to_string());
+    }
+
+    let mut frames = Vec::with_capacity(frame_count as usize);
+    let mut compressed_offset = 0u64;
+    let mut decompressed_offset = 0u64;
+    for entry in table[8..]
+        .chunks_exact(entry_size as usize)
+        .take(frame_count as usize)
+    {
+        let compressed_size = le_u32(entry, 0) as u64;
+        let decompressed_size = le_u32(entry, 4) as u64;
+        frames.push(ZstdFrame {
+            compressed_offset,
+            compressed_size,
+            decompressed_offset,
+            decompressed_size,
+        });
+        compressed_offset += compressed_size;
+        decompressed_offset += decompressed_size;
+    }
+    if compressed_offset > table_start {
+        return Err("zstd seek table describes more data than the file holds".to_string());
+    }
+
+    Ok(Some((frames, checksums)))
+}
+
+/// Build the frame index by walking frame and block headers.
+fn scan_frames(file: &mut Source, file_size: u64) -> Result<Vec<ZstdFrame>, String> {
+    let err = |e: io::Error| format!("Error scanning zstd frames: {}", e);
+    file.seek(SeekFrom::Start(0)).map_err(err)?;
+    let mut reader = BufReader::new(file);
+    let mut frames = Vec::new();
+    let mut offset = 0u64;
+    let mut decompressed_offset = 0u64;
+
+    while offset < file_size {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic).map_err(err)?;
+        let magic = u32::from_le_bytes(magic);
+
+        if magic & ZSTD_SKIPPABLE_MAGIC_MASK == ZSTD_SKIPPABLE_MAGIC {
+            let mut size = [0u8; 4];
+            reader.read_exact(&mut size).map_err(err)?;
+            let size = u32::from_le_bytes(size) as u64;
+            reader.seek_relative(size as i64).map_err(err)?;
+            offset += 8 + size;
+            continue;
+        }
+        if magic != ZSTD_FRAME_MAGIC {
+            return Err(format!("Invalid zstd frame magic at offset {}", offset));
+        }
+
+        // Frame header: descriptor, optional window, dictionary id and content size.
+        let mut descriptor = [0u8; 1];
+        reader.read_exact(&mut descriptor).map_err(err)?;
+        let descriptor = descriptor[0];
+        let single_segment = descriptor & ZSTD_FRAME_SINGLE_SEGMENT != 0;
+        let window_size = if single_segment { 0 } else { 1 };
+        let dict_id_size = [0, 1, 2, 4][(descriptor & 0x03) as usize];
+        let fcs_size = match descriptor >> 6 {
+            0 if single_segment => 1,
+            0 => 0,
+            1 => 2,
+            2 => 4,
+            _ => 8,
+        };
+        if fcs_size == 0 {
+            return Err(format!(
+                "zstd frame at offset {} does not record its content size; recompress with --seekable",
+                offset
+            ));
+        }
+        let mut header = vec![0u8; window_size + dict_id_size + fcs_size];
+        reader.read_exact(&mut header).map_err(err)?;
+        let mut content_size = le_uint(&header[window_size + dict_id_size..]);
+        if fcs_size == 2 {
+            content_size += 256;
+        }
+        let mut frame_size = 5 + header.len() as u64;
+
+        // Blocks: 3-byte header, then the payload (a single byte for RLE blocks).
+        loop {
+            let mut block = [0u8; 3];
+            reader.read_exact(&mut block).map_err(err)?;
+            let block = u32::from_le_bytes([block[0], block[1], block[2], 0]);
+            let block_type = (block >> 1) & 0x03;
+            if block_type == ZSTD_BLOCK_RESERVED {
+                return Err(format!(
+                    "Reserved zstd block type in frame at offset {}",
+                    offset
+                ));
+            }
+            let payload = if block_type == ZSTD_BLOCK_RLE {
+                1
+            } else {
+                block >> 3
+            };
+            reader.seek_relative(payload as i64).map_err(err)?;
+            frame_size += 3 + payload as u64;
+            if block & 1 != 0 {
+                break;
+            }
+        }
+        if descriptor & ZSTD_FRAME_CHECKSUM_FLAG != 0 {
+            reader.seek_relative(4).map_err(err)?;
+            frame_size += 4;
+        }
+
+        frames.push(ZstdFrame {
+            compressed_offset: offset,
+            compressed_size: frame_size,
+            decompressed_offset,
+            decompressed_size: content_size,
+        });
+        offset += frame_size;
+        decompressed_offset += content_size;
+    }
+
+    Ok(frames)
+}
+
+// ---- Public zstd reader -----------------------------------------------------
+
+/// Compressed bytes of one frame.
+///
+/// Seeks before every read so that clones sharing the underlying file
+/// descriptor do not disturb each other.
+struct FrameSource {
+    file: Source,
+    offset: u64,
+    remaining: u64,
+}
+
+impl Read for FrameSource {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = min(buf.len() as u64, self.remaining) as usize;
+        if len == 0 {
+            return Ok(0);
+        }
+        let n = self.file.read_at(self.offset, &mut buf[..len])?;
+        self.offset += n as u64;
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+/// Decoder positioned inside one frame.
+struct FrameCursor {
+    /// Index of the frame being decoded.
+    frame: usize,
+    /// Offset inside the frame's decompressed data.
+    offset: u64,
+    /// Streaming decoder reading the frame's compressed bytes.
+    decoder: StreamingDecoder<FrameSource, FrameDecoder>,
+}
+
+/// Seekable reader over a zstd-compressed raw image.
+///
+/// Implements [`Read`], [`Seek`] and [`Clone`] so it can be used as a drop-in
+/// source of evidence bytes inside the [`Body`](crate::Body) abstraction.
+pub struct ZSTD {
+    /// Open file handle to the `.zst` file.
+    file: Source,
+    /// Original path (kept for display).
+    path: String,
+    /// Size of the compressed file.
+    compressed_size: u64,
+    /// Frame index ordered by decompressed offset.
+    frames: Arc<[ZstdFrame]>,
+    /// Whether the index comes from a seek table (as opposed to a frame scan).
+    seek_table: bool,
+    /// Whether the seek table carries per-frame checksums.
+    checksums: bool,
+    /// Total decompressed size.
+    size: u64,
+    /// Decoder of the most recently read frame.
+    cursor: Option<Box<FrameCursor>>,
+    /// Virtual cursor position inside the decompressed stream.
+    position: u64,
+}
+
+impl ZSTD {
+    // ---- Construction -------------------------------------------------------
+
+    /// Open a zstd-compressed image and build its frame index.
+    ///
+    /// The seek table is used when present; otherwise the frame headers are
+    /// walked, which fails for frames that do not record their content size.
+    pub fn new(file_path: &str) -> Result<ZSTD, ExhumeBodyError> {
+        Self::open_in(&StdFileSystem, file_path)
+    }
+
+    /// Same as [`ZSTD::new`], opening the image through `fs` instead of the
+    /// operating system.
+    pub fn open_in(fs: &dyn FileSystem, file_path: &str) -> Result<ZSTD, ExhumeBodyError> {
+        Self::new_impl(fs, file_path).map_err(ExhumeBodyError::Zstd)
+    }
+
+    fn new_impl(fs: &dyn FileSystem, file_path: &str) -> Result<ZSTD, String> {
+        let path = Path::new(file_path);
+        let mut file = fs
+            .open(path)
+            .map_err(|e| format!("Error opening zstd image: {}", e))?;
+
+        let mut magic = [0u8; 4];
+        file.read_exact(&mut magic)
+            .map_err(|e| format!("Error reading zstd header: {}", e))?;
+        if u32::from_le_bytes(magic) != ZSTD_FRAME_MAGIC {
+            return Err("Invalid zstd signature (expected 28 B5 2F FD)".to_string());
+        }
+
+        let compressed_size = file
+            .seek(SeekFrom::End(0))
+            .map_err(|e| format!("Error reading zstd image size: {}", e))?;
+
+        let (frames, seek_table, checksums) = match read_seek_table(&mut file, compressed_size)? {
+            Some((frames, checksums)) => (frames, true, checksums),
+            None => {
+                debug!("No zstd seek table, scanning frame headers");
+                (scan_frames(&mut file, compressed_size)?, false, false)
+            }
+        };
+        let size = frames
+            .last()
+            .map_or(0, |f| f.decompressed_offset + f.decompressed_size);
+        debug!("Indexed {} zstd frames ({} bytes)", frames.len(), size);
+
+        Ok(ZSTD {
+            file,
+            path: file_path.to_string(),
+            compressed_size,
+            frames: frames.into(),
+            seek_table,
+            checksums,
+            size,
+            cursor: None,
+            position: 0,
+        })
+    }
+
+    // ---- Info helpers -------------------------------------------------------
+
+    /// Sizes, frame count and how the frames were indexed.
+    pub fn info(&self) -> FormatInfo {
+        let info = FormatInfo::new("Zstd Image Information")
+            .field("Path", &self.path)
+            .field("Compressed", format!("{} bytes", self.compressed_size))
+            .field("Size", format!("{} bytes", self.size))
+            .field("Sector Size", ZSTD_SECTOR_SIZE)
+            .field("Frames", self.frames.len())
+            .field(
+                "Index",
+                if self.seek_table {
+                    "seek table"
+                } else {
+                    "frame scan"
+                },
+            );
+        if self.seek_table {
+            info.field("Checksums", self.checksums)
+        } else {
+            info
+        }
+    }
+
+    /// Print parsed metadata to the log.
+    pub fn print_info(&self) {
+        self.info().log();
+    }
+
+    /// Returns the sector size (always 512 for zstd images).
+    pub fn get_sector_size(&self) -> u16 {
+        ZSTD_SECTOR_SIZE
+    }
+
+    /// Uncompressed size of the image in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    // ---- Internal frame reading ---------------------------------------------
+
+    /// Start decoding frame `index` from its beginning.
+    fn open_frame(&mut self, index: usize) -> io::Result<Box<FrameCursor>> {
+        let frame = &self.frames[index];
+        let source = FrameSource {
+            file: self.file.clone(),
+            offset: frame.compressed_offset,
+            remaining: frame.compressed_size,
+        };
+        let decoder = StreamingDecoder::new(source)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        Ok(Box::new(FrameCursor {
+            frame: index,
+            offset: 0,
+            decoder,
+        }))
+    }
+
+    /// Fill `buf` from the virtual offset `self.position`, without crossing a
+    /// frame boundary. Returns the number of bytes produced.
+    fn read_frame(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let index = self
+            .frames
+            .partition_point(|f| f.decompressed_offset + f.decompressed_size <= self.position);
+        let frame = self.frames[index].clone();
+        let target = self.position - frame.decompressed_offset;
+        let len = min(buf.len() as u64, frame.decompressed_size - target) as usize;
+
+        // Reuse the live decoder unless it is in another frame or already past `target`.
+        let reusable = matches!(&self.cursor, Some(c) if c.frame == index && c.offset <= target);
+        if !reusable {
+            self.cursor = Some(self.open_frame(index)?);
+        }
+        let cursor = self.cursor.as_mut().unwrap();
+
+        let skip = target - cursor.offset;
+        if skip > 0 {
+            let skipped = io::copy(&mut (&mut cursor.decoder).take(skip), &mut io::sink())?;
+            cursor.offset += skipped;
+            if skipped < skip {
+                return Err(truncated_frame(&frame));
+            }
+        }
+
+        cursor.decoder.read_exact(&mut buf[..len]).map_err(|e| {
+            if e.kind() == io::ErrorKind::UnexpectedEof {
+                truncated_frame(&frame)
+            } else {
+                e
+            }
+        })?;
+        cursor.offset += len as u64;
+        Ok(len)
+    }
+}
+
+/// Error for a frame decoding to fewer bytes than its index entry announces.
+fn truncated_frame(frame: &ZstdFrame) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "zstd frame at offset {} decodes to fewer than {} bytes",
+            frame.compressed_offset, frame.decompressed_size
+        ),
+    )
+}
+
+// ---- Clone ------------------------------------------------------------------
+
+impl Clone for ZSTD {
+    fn clone(&self) -> Self {
+        Self {
+            file: self.file.clone(),
+            path: self.path.clone(),
+            compressed_size: self.compressed_size,
+            frames: self.frames.clone(),
+            seek_table: self.seek_table,
+            checksums: self.checksums,
+            size: self.size,
+            // Reset the decoder – will be lazily reopened.
+            cursor: None,
+            position: self.position,
+        }
+    }
+}
+
+// ---- Read -------------------------------------------------------------------
+
+impl Read for ZSTD {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.position >= self.size {
+            return Ok(0);
+        }
+
+        let want = min(buf.len() as u64, self.size - self.position) as usize;
+        let mut total = 0usize;
+
+        while total < want {
+            let n = self.read_frame(&mut buf[total..want])?;
+            total += n;
+            self.position += n as u64;
+        }
+
+        Ok(total)
+    }
+}
+
+// ---- Seek -------------------------------------------------------------------
+
+impl Seek for ZSTD {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let next = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.size.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Seek before start or overflow")
+        })?;
+
+        self.position = next;
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use ruzstd::encoding::{compress_to_vec, CompressionLevel};
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len as u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8 & 0x0F)
+            .collect()
+    }
+
+    /// A single-segment frame holding `data` in one raw block, with a
+    /// 2-byte content size so that the frame can be walked without a seek
+    /// table.
+    fn raw_frame(data: &[u8]) -> Vec<u8> {
+        let mut frame = ZSTD_FRAME_MAGIC.to_le_bytes().to_vec();
+        frame.push(0x40 | ZSTD_FRAME_SINGLE_SEGMENT);
+        frame.extend_from_slice(&(data.len() as u16 - 256).to_le_bytes());
+        frame.extend_from_slice(&(((data.len() as u32) << 3) | 1).to_le_bytes()[..3]);
+        frame.extend_from_slice(data);
+        frame
+    }
+
+    fn read_back(zstd: &mut ZSTD, data: &[u8]) {
+        let mut back = Vec::new();
+        zstd.read_to_end(&mut back).unwrap();
+        assert_eq!(back, data);
+
+        for offset in [data.len() as u64 - 3000, 10, 4095] {
+            zstd.seek(SeekFrom::Start(offset)).unwrap();
+            let mut buf = [0u8; 2500];
+            zstd.read_exact(&mut buf).unwrap();
+            assert_eq!(buf[..], data[offset as usize..offset as usize + 2500]);
+        }
+    }
+
+    #[test]
+    fn reads_frames_listed_in_a_seek_table() {
+        let data = sample(3 * 4096 + 1000);
+        let mut image = Vec::new();
+        let mut table = Vec::new();
+        for chunk in data.chunks(4096) {
+            let frame = compress_to_vec(chunk, CompressionLevel::Fastest);
+            table.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            table.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+            image.extend(frame);
+        }
+        table.extend_from_slice(&4u32.to_le_bytes());
+        table.push(0);
+        table.extend_from_slice(&ZSTD_SEEKABLE_MAGIC.to_le_bytes());
+        image.extend_from_slice(&ZSTD_SEEK_TABLE_MAGIC.to_le_bytes());
+        image.extend_from_slice(&(table.len() as u32).to_le_bytes());
+        image.extend(table);
+
+        let dir = TempDir::new("zstd_seek_table");
+        let path = dir.join("disk.raw.zst");
+        std::fs::write(&path, image).unwrap();
+        let mut zstd = ZSTD::new(&path.to_string_lossy()).unwrap();
+        read_back(&mut zstd, &data);
+    }
+
+    #[test]
+    fn walks_frames_without_a_seek_table() {
+        let data = sample(3 * 4096 + 1000);
+        let mut image = Vec::new();
+        for (i, chunk) in data.chunks(4096).enumerate() {
+            image.extend(raw_frame(chunk));
+            if i == 1 {
+                // Skippable frames carry no image data.
+                image.extend_from_slice(&(ZSTD_SKIPPABLE_MAGIC | 3).to_le_bytes());
+                image.extend_from_slice(&5u32.to_le_bytes());
+                image.extend_from_slice(b"notes");
+            }
+        }
+
+        let dir = TempDir::new("zstd_frame_scan");
+        let path = dir.join("disk.raw.zst");
+        std::fs::write(&path, image).unwrap();
+        let mut zstd = ZSTD::new(&path.to_string_lossy()).unwrap();
+        read_back(&mut zstd, &data);
+    }
+}